@@ -258,6 +258,142 @@ pub enum ContentBlockDelta {
     InputJsonDelta { partial_json: String },
 }
 
+/// The Anthropic API client for processing requests asynchronously with the Message Batches API.
+///
+/// Based on https://docs.anthropic.com/en/api/creating-message-batches
+pub struct MessageBatchesApi {
+    api_key: String,
+    client: Client,
+}
+
+impl MessageBatchesApi {
+    pub fn new(api_key: String) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+        Self { api_key, client }
+    }
+
+    pub fn create_batch(&self, request: CreateMessageBatchRequest) -> Result<MessageBatch, Error> {
+        trace!("Sending batch request to Anthropic API: {request:?}");
+
+        let response: Response = self
+            .client
+            .request(Method::POST, format!("{BASE_URL}/v1/messages/batches"))
+            .header("anthropic-version", "2023-06-01")
+            .header("x-api-key", &self.api_key)
+            .json(&request)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+
+    pub fn retrieve_batch(&self, batch_id: &str) -> Result<MessageBatch, Error> {
+        trace!("Retrieving batch {batch_id} from Anthropic API");
+
+        let response: Response = self
+            .client
+            .request(
+                Method::GET,
+                format!("{BASE_URL}/v1/messages/batches/{batch_id}"),
+            )
+            .header("anthropic-version", "2023-06-01")
+            .header("x-api-key", &self.api_key)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+
+    pub fn cancel_batch(&self, batch_id: &str) -> Result<MessageBatch, Error> {
+        trace!("Cancelling batch {batch_id} with Anthropic API");
+
+        let response: Response = self
+            .client
+            .request(
+                Method::POST,
+                format!("{BASE_URL}/v1/messages/batches/{batch_id}/cancel"),
+            )
+            .header("anthropic-version", "2023-06-01")
+            .header("x-api-key", &self.api_key)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+
+    /// Downloads the `.jsonl` results file of an ended batch
+    pub fn batch_results(&self, results_url: &str) -> Result<String, Error> {
+        trace!("Downloading batch results from {results_url}");
+
+        let response: Response = self
+            .client
+            .request(Method::GET, results_url)
+            .header("anthropic-version", "2023-06-01")
+            .header("x-api-key", &self.api_key)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        if response.status().is_success() {
+            response
+                .text()
+                .map_err(|err| from_reqwest_error("Failed to receive batch results", err))
+        } else {
+            parse_response(response)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateMessageBatchRequest {
+    pub requests: Vec<MessageBatchRequest>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageBatchRequest {
+    pub custom_id: String,
+    pub params: MessagesRequest,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageBatch {
+    pub id: String,
+    pub processing_status: String,
+    pub request_counts: MessageBatchRequestCounts,
+    pub created_at: String,
+    pub cancel_initiated_at: Option<String>,
+    pub results_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageBatchRequestCounts {
+    pub processing: u32,
+    pub succeeded: u32,
+    pub errored: u32,
+    pub canceled: u32,
+    pub expired: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageBatchResultLine {
+    pub custom_id: String,
+    pub result: MessageBatchResult,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum MessageBatchResult {
+    #[serde(rename = "succeeded")]
+    Succeeded { message: MessagesResponse },
+    #[serde(rename = "errored")]
+    Errored { error: ErrorResponse },
+    #[serde(rename = "canceled")]
+    Canceled,
+    #[serde(rename = "expired")]
+    Expired,
+}
+
 fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, Error> {
     let status = response.status();
     if status.is_success() {
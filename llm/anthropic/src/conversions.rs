@@ -1,8 +1,11 @@
 use crate::client::{
-    Content, ImageSource as ClientImageSource, MediaType, MessagesRequest, MessagesRequestMetadata,
-    MessagesResponse, StopReason, Tool, ToolChoice,
+    Content, ImageSource as ClientImageSource, MediaType, MessageBatch, MessageBatchResult,
+    MessageBatchResultLine, MessagesRequest, MessagesRequestMetadata, MessagesResponse, StopReason,
+    Tool, ToolChoice,
 };
 use base64::{engine::general_purpose, Engine as _};
+use golem_llm::config::{reject_unsupported_parameters, SamplingParameter};
+use golem_llm::golem::llm::batch::{BatchInfo, BatchRequestCounts, BatchResult, BatchStatus};
 use golem_llm::golem::llm::llm::{
     ChatEvent, CompleteResponse, Config, ContentPart, Error, ErrorCode, FinishReason,
     ImageReference, ImageSource, ImageUrl, Message, ResponseMetadata, Role, ToolCall,
@@ -14,6 +17,17 @@ pub fn messages_to_request(
     messages: Vec<Message>,
     config: Config,
 ) -> Result<MessagesRequest, Error> {
+    reject_unsupported_parameters(
+        "Anthropic",
+        &config,
+        &[
+            SamplingParameter::Seed,
+            SamplingParameter::FrequencyPenalty,
+            SamplingParameter::PresencePenalty,
+            SamplingParameter::RepetitionPenalty,
+        ],
+    )?;
+
     let options = config
         .provider_options
         .into_iter()
@@ -68,12 +82,16 @@ pub fn messages_to_request(
         temperature: config.temperature,
         tool_choice,
         tools,
-        top_k: options
-            .get("top_k")
-            .and_then(|top_k_s| top_k_s.parse::<u32>().ok()),
-        top_p: options
-            .get("top_p")
-            .and_then(|top_p_s| top_p_s.parse::<f32>().ok()),
+        top_k: config.top_k.or_else(|| {
+            options
+                .get("top_k")
+                .and_then(|top_k_s| top_k_s.parse::<u32>().ok())
+        }),
+        top_p: config.top_p.or_else(|| {
+            options
+                .get("top_p")
+                .and_then(|top_p_s| top_p_s.parse::<f32>().ok())
+        }),
     })
 }
 
@@ -157,6 +175,7 @@ pub fn process_response(response: MessagesResponse) -> ChatEvent {
             provider_id: None,
             timestamp: None,
             provider_metadata_json: None,
+            cost: None,
         };
 
         ChatEvent::Message(CompleteResponse {
@@ -168,6 +187,73 @@ pub fn process_response(response: MessagesResponse) -> ChatEvent {
     }
 }
 
+pub fn process_message_batch(batch: MessageBatch) -> BatchInfo {
+    let counts = &batch.request_counts;
+    let status = match batch.processing_status.as_str() {
+        "in_progress" | "canceling" => BatchStatus::Running,
+        _ if batch.cancel_initiated_at.is_some() => BatchStatus::Cancelled,
+        _ if counts.expired > 0 && counts.succeeded == 0 && counts.errored == 0 => {
+            BatchStatus::Expired
+        }
+        _ => BatchStatus::Succeeded,
+    };
+
+    BatchInfo {
+        job_id: batch.id.clone(),
+        status,
+        request_counts: Some(BatchRequestCounts {
+            total: counts.processing
+                + counts.succeeded
+                + counts.errored
+                + counts.canceled
+                + counts.expired,
+            succeeded: counts.succeeded,
+            failed: counts.errored + counts.canceled + counts.expired,
+        }),
+        created_at: Some(batch.created_at.clone()),
+        provider_metadata_json: serde_json::to_string(&batch).ok(),
+    }
+}
+
+pub fn process_message_batch_results(content: &str) -> Result<Vec<BatchResult>, Error> {
+    let mut results = Vec::new();
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        let line: MessageBatchResultLine = serde_json::from_str(line).map_err(|err| Error {
+            code: ErrorCode::InternalError,
+            message: format!("Failed to decode batch result: {err}"),
+            provider_error_json: Some(line.to_string()),
+        })?;
+
+        let event = match line.result {
+            MessageBatchResult::Succeeded { message } => process_response(message),
+            MessageBatchResult::Errored { error } => ChatEvent::Error(Error {
+                code: match error.error.typ.as_str() {
+                    "invalid_request_error" => ErrorCode::InvalidRequest,
+                    _ => ErrorCode::InternalError,
+                },
+                message: error.error.message.clone(),
+                provider_error_json: serde_json::to_string(&error).ok(),
+            }),
+            MessageBatchResult::Canceled => ChatEvent::Error(Error {
+                code: ErrorCode::Unknown,
+                message: "Request was canceled before it was processed".to_string(),
+                provider_error_json: None,
+            }),
+            MessageBatchResult::Expired => ChatEvent::Error(Error {
+                code: ErrorCode::Unknown,
+                message: "Batch expired before the request was processed".to_string(),
+                provider_error_json: None,
+            }),
+        };
+
+        results.push(BatchResult {
+            custom_id: line.custom_id,
+            event,
+        });
+    }
+    Ok(results)
+}
+
 pub fn tool_results_to_messages(
     tool_results: Vec<(ToolCall, ToolResult)>,
 ) -> Vec<crate::client::Message> {
@@ -221,11 +307,19 @@ pub fn stop_reason_to_finish_reason(stop_reason: StopReason) -> FinishReason {
     }
 }
 
+/// Anthropic reports the tokens read from and written to the prompt cache separately, while
+/// `input_tokens` of `Usage` includes them
 pub fn convert_usage(usage: crate::client::Usage) -> Usage {
+    let cache_read_input_tokens = usage.cache_read_input_tokens.unwrap_or(0);
+    let cache_creation_input_tokens = usage.cache_creation_input_tokens.unwrap_or(0);
     Usage {
-        input_tokens: Some(usage.input_tokens),
+        input_tokens: Some(
+            usage.input_tokens + cache_read_input_tokens + cache_creation_input_tokens,
+        ),
         output_tokens: Some(usage.output_tokens),
         total_tokens: None,
+        cached_input_tokens: usage.cache_read_input_tokens,
+        reasoning_tokens: None,
     }
 }
 
@@ -2,20 +2,29 @@ mod client;
 mod conversions;
 
 use crate::client::{
-    Content, ContentBlockDelta, ErrorResponse, MessagesApi, MessagesRequest, StopReason, Usage,
+    Content, ContentBlockDelta, CreateMessageBatchRequest, ErrorResponse, MessageBatchRequest,
+    MessageBatchesApi, MessagesApi, MessagesRequest, StopReason, Usage,
 };
 use crate::conversions::{
-    convert_usage, messages_to_request, process_response, stop_reason_to_finish_reason,
-    tool_results_to_messages,
+    convert_usage, messages_to_request, process_message_batch, process_message_batch_results,
+    process_response, stop_reason_to_finish_reason, tool_results_to_messages,
 };
+use golem_llm::batch_stream::LlmBatchResultStream;
 use golem_llm::chat_stream::{LlmChatStream, LlmChatStreamState};
 use golem_llm::config::with_config_key;
-use golem_llm::durability::{DurableLLM, ExtendedGuest};
+use golem_llm::durability::{DurableLLM, ExtendedBatchGuest, ExtendedGuest};
 use golem_llm::event_source::EventSource;
+use golem_llm::golem::llm::batch::{
+    BatchInfo, BatchRequest, BatchResult, BatchResultStream, Guest as BatchGuest,
+};
 use golem_llm::golem::llm::llm::{
-    ChatEvent, ChatStream, Config, ContentPart, Error, ErrorCode, Guest, Message, ResponseMetadata,
-    Role, StreamDelta, StreamEvent, ToolCall, ToolResult,
+    ChatEvent, ChatStream, Config, ContentPart, Error, ErrorCode, Guest, Kv, Message,
+    ResponseMetadata, Role, StreamDelta, StreamEvent, ToolCall, ToolResult,
+};
+use golem_llm::golem::llm::moderation::{
+    Guest as ModerationGuest, ModerationConfig, ModerationResponse,
 };
+use golem_llm::guardrails::moderate_with_local_policy;
 use golem_rust::wasm_rpc::Pollable;
 use log::trace;
 use std::cell::{Ref, RefCell, RefMut};
@@ -49,6 +58,7 @@ impl AnthropicChatStream {
                 provider_id: None,
                 timestamp: None,
                 provider_metadata_json: None,
+                cost: None,
             }),
         })
     }
@@ -65,6 +75,7 @@ impl AnthropicChatStream {
                 provider_id: None,
                 timestamp: None,
                 provider_metadata_json: None,
+                cost: None,
             }),
         })
     }
@@ -253,6 +264,26 @@ impl AnthropicComponent {
             Err(err) => AnthropicChatStream::failed(err),
         }
     }
+
+    fn fetch_batch_results(
+        client: MessageBatchesApi,
+        job_id: String,
+    ) -> Result<Vec<BatchResult>, Error> {
+        let batch = client.retrieve_batch(&job_id)?;
+        match batch.results_url {
+            Some(results_url) => {
+                process_message_batch_results(&client.batch_results(&results_url)?)
+            }
+            None => Err(Error {
+                code: ErrorCode::InvalidRequest,
+                message: format!(
+                    "Batch {job_id} has no results yet ({})",
+                    batch.processing_status
+                ),
+                provider_error_json: None,
+            }),
+        }
+    }
 }
 
 impl Guest for AnthropicComponent {
@@ -294,7 +325,62 @@ impl Guest for AnthropicComponent {
     }
 }
 
+impl ModerationGuest for AnthropicComponent {
+    fn moderate(
+        inputs: Vec<String>,
+        _config: ModerationConfig,
+    ) -> Result<ModerationResponse, Error> {
+        moderate_with_local_policy(inputs)
+    }
+}
+
+impl BatchGuest for AnthropicComponent {
+    type BatchResultStream = LlmBatchResultStream;
+
+    fn submit(requests: Vec<BatchRequest>) -> Result<String, Error> {
+        with_config_key(Self::ENV_VAR_NAME, Err, |anthropic_api_key| {
+            let client = MessageBatchesApi::new(anthropic_api_key);
+
+            let requests = requests
+                .into_iter()
+                .map(|request| {
+                    Ok(MessageBatchRequest {
+                        custom_id: request.custom_id,
+                        params: messages_to_request(request.messages, request.config)?,
+                    })
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let batch = client.create_batch(CreateMessageBatchRequest { requests })?;
+            Ok(batch.id)
+        })
+    }
+
+    fn poll(job_id: String, _credentials: Option<Vec<Kv>>) -> Result<BatchInfo, Error> {
+        with_config_key(Self::ENV_VAR_NAME, Err, |anthropic_api_key| {
+            let client = MessageBatchesApi::new(anthropic_api_key);
+
+            client.retrieve_batch(&job_id).map(process_message_batch)
+        })
+    }
+
+    fn cancel(job_id: String, _credentials: Option<Vec<Kv>>) -> Result<String, Error> {
+        with_config_key(Self::ENV_VAR_NAME, Err, |anthropic_api_key| {
+            let client = MessageBatchesApi::new(anthropic_api_key);
+
+            client
+                .cancel_batch(&job_id)
+                .map(|batch| batch.processing_status)
+        })
+    }
+
+    fn results(job_id: String, _credentials: Option<Vec<Kv>>) -> BatchResultStream {
+        BatchResultStream::new(Self::unwrapped_batch_results(job_id))
+    }
+}
+
 impl ExtendedGuest for AnthropicComponent {
+    const PROVIDER: &'static str = "anthropic";
+
     fn unwrapped_stream(
         messages: Vec<Message>,
         config: Config,
@@ -367,6 +453,22 @@ impl ExtendedGuest for AnthropicComponent {
     }
 }
 
+impl ExtendedBatchGuest for AnthropicComponent {
+    fn unwrapped_batch_results(job_id: String) -> Self::BatchResultStream {
+        with_config_key(
+            Self::ENV_VAR_NAME,
+            LlmBatchResultStream::failed,
+            |anthropic_api_key| {
+                let client = MessageBatchesApi::new(anthropic_api_key);
+
+                LlmBatchResultStream::new(move || Self::fetch_batch_results(client, job_id))
+            },
+        )
+    }
+}
+
 type DurableAnthropicComponent = DurableLLM<AnthropicComponent>;
 
 golem_llm::export_llm!(DurableAnthropicComponent with_types_in golem_llm);
+golem_llm::export_moderation!(DurableAnthropicComponent with_types_in golem_llm::moderation_library);
+golem_llm::export_batch!(DurableAnthropicComponent with_types_in golem_llm::batch_library);
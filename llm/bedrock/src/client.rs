@@ -1,10 +1,20 @@
 use crate::{
     async_utils::UnsafeFuture,
-    conversions::{self, from_converse_sdk_error, from_converse_stream_sdk_error, BedrockInput},
+    conversions::{
+        self, custom_error, from_apply_guardrail_sdk_error, from_batch_sdk_error,
+        from_converse_sdk_error, from_converse_stream_sdk_error, from_invoke_model_sdk_error,
+        BatchInput, BatchModelFamily, BedrockInput, GuardrailRequest, ImageGenerationRequest,
+        ImageGenerationResponse, S3Location, BATCH_ROLE_ARN_KEY, BATCH_S3_URI_KEY,
+    },
     stream::BedrockChatStream,
     wasi_client::WasiClient,
 };
 use aws_config::BehaviorVersion;
+use aws_sdk_bedrock::types::{
+    ModelInvocationJobInputDataConfig, ModelInvocationJobOutputDataConfig,
+    ModelInvocationJobS3InputDataConfig, ModelInvocationJobS3OutputDataConfig,
+    ModelInvocationJobStatus, S3InputFormat,
+};
 use aws_sdk_bedrockruntime::{
     self as bedrock,
     config::{AsyncSleep, Sleep},
@@ -12,34 +22,50 @@ use aws_sdk_bedrockruntime::{
         converse::builders::ConverseFluentBuilder,
         converse_stream::builders::ConverseStreamFluentBuilder,
     },
+    types::{GuardrailContentBlock, GuardrailTextBlock},
 };
+use aws_sdk_s3::primitives::ByteStream;
+use aws_smithy_types::Blob;
 use aws_types::region;
 use golem_llm::{
     config::{get_config_key, get_config_key_or_none},
-    golem::llm::llm,
+    credentials::TokenCache,
+    golem::llm::{batch, images, llm, moderation},
 };
 use log::trace;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use wasi::clocks::monotonic_clock;
 use wstd::runtime::Reactor;
 
+thread_local! {
+    /// Temporary credentials of the assumed roles, keyed by the base access key and the role ARN
+    static ASSUMED_ROLE_CREDENTIALS: TokenCache<bedrock::config::Credentials> = TokenCache::new();
+}
+
 #[derive(Debug)]
 pub struct Bedrock {
     client: bedrock::Client,
+    batch_client: aws_sdk_bedrock::Client,
+    s3_client: aws_sdk_s3::Client,
 }
 
 impl Bedrock {
     pub async fn new() -> Result<Self, llm::Error> {
-        let environment = BedrockEnvironment::load_from_env()?;
+        let environment = BedrockEnvironment::load()?;
+        let credentials = environment.aws_credentials().await?;
 
         let sdk_config = aws_config::defaults(BehaviorVersion::latest())
             .region(environment.aws_region())
             .http_client(WasiClient::new())
-            .credentials_provider(environment.aws_credentials())
+            .credentials_provider(credentials)
             .sleep_impl(WasiSleep::new())
             .load()
             .await;
-        let client = bedrock::Client::new(&sdk_config);
-        Ok(Self { client })
+        Ok(Self {
+            client: bedrock::Client::new(&sdk_config),
+            batch_client: aws_sdk_bedrock::Client::new(&sdk_config),
+            s3_client: aws_sdk_s3::Client::new(&sdk_config),
+        })
     }
 
     pub async fn converse(
@@ -107,6 +133,211 @@ impl Bedrock {
         }
     }
 
+    /// Invokes one of the Titan Image Generator / Nova Canvas models with the given native request
+    pub async fn invoke_image_model(
+        &self,
+        model_id: String,
+        request: ImageGenerationRequest,
+    ) -> Result<images::ImageResponse, llm::Error> {
+        trace!("Sending image request to AWS Bedrock model {model_id}: {request:?}");
+        let seed = request.image_generation_config.seed;
+        let body = serde_json::to_vec(&request).map_err(|err| {
+            custom_error(
+                llm::ErrorCode::InternalError,
+                format!("Failed to serialize image request: {err}"),
+            )
+        })?;
+
+        let response = self
+            .client
+            .invoke_model()
+            .model_id(model_id.clone())
+            .content_type("application/json")
+            .accept("application/json")
+            .body(Blob::new(body))
+            .send()
+            .await
+            .map_err(|e| from_invoke_model_sdk_error(model_id, e))?;
+
+        let response: ImageGenerationResponse = serde_json::from_slice(response.body().as_ref())
+            .map_err(|err| {
+                custom_error(
+                    llm::ErrorCode::InternalError,
+                    format!("Failed to decode image response: {err}"),
+                )
+            })?;
+
+        conversions::image_generation_response_to_image_response(response, seed)
+    }
+
+    /// Applies a guardrail to a single piece of text, using the ApplyGuardrail API
+    pub async fn apply_guardrail(
+        &self,
+        request: &GuardrailRequest,
+        text: String,
+    ) -> Result<(moderation::ModerationResult, Vec<String>), llm::Error> {
+        trace!("Applying AWS Bedrock guardrail: {request:?}");
+        let text_block = GuardrailTextBlock::builder()
+            .text(text)
+            .build()
+            .map_err(|err| {
+                custom_error(
+                    llm::ErrorCode::InternalError,
+                    format!("Failed to build guardrail content: {err}"),
+                )
+            })?;
+
+        let output = self
+            .client
+            .apply_guardrail()
+            .guardrail_identifier(request.guardrail_id.clone())
+            .guardrail_version(request.guardrail_version.clone())
+            .source(request.source.clone())
+            .content(GuardrailContentBlock::Text(text_block))
+            .send()
+            .await
+            .map_err(|e| from_apply_guardrail_sdk_error(request.guardrail_id.clone(), e))?;
+
+        let outputs = output
+            .outputs()
+            .iter()
+            .filter_map(|output| output.text().map(|text| text.to_string()))
+            .collect();
+        Ok((
+            conversions::apply_guardrail_output_to_moderation_result(&output),
+            outputs,
+        ))
+    }
+
+    /// Writes the records of a batch to S3 and creates a batch inference job reading them. The
+    /// ARN of the job is used as the job ID.
+    pub async fn create_batch_job(&self, input: BatchInput) -> Result<String, llm::Error> {
+        let role_arn = get_config_key(BATCH_ROLE_ARN_KEY)?;
+        let location = S3Location::parse(&get_config_key(BATCH_S3_URI_KEY)?)?;
+        let job_name = format!(
+            "golem-llm-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis()
+        );
+        let location = location.join(&job_name);
+        let input_key = location.key("input.jsonl");
+
+        trace!(
+            "Writing batch input of model {} to s3://{}/{input_key}",
+            input.model_id,
+            location.bucket
+        );
+        self.s3_client
+            .put_object()
+            .bucket(&location.bucket)
+            .key(&input_key)
+            .content_type("application/jsonl")
+            .body(ByteStream::from(input.records.into_bytes()))
+            .send()
+            .await
+            .map_err(|e| from_batch_sdk_error("writing the batch input to S3", e))?;
+
+        let input_data_config = ModelInvocationJobS3InputDataConfig::builder()
+            .s3_uri(format!("s3://{}/{input_key}", location.bucket))
+            .s3_input_format(S3InputFormat::Jsonl)
+            .build()
+            .map_err(|e| from_batch_sdk_error("building the batch input configuration", e))?;
+        let output_data_config = ModelInvocationJobS3OutputDataConfig::builder()
+            .s3_uri(location.join("output").uri())
+            .build()
+            .map_err(|e| from_batch_sdk_error("building the batch output configuration", e))?;
+
+        let response = self
+            .batch_client
+            .create_model_invocation_job()
+            .job_name(job_name)
+            .role_arn(role_arn)
+            .model_id(input.model_id)
+            .input_data_config(ModelInvocationJobInputDataConfig::S3InputDataConfig(
+                input_data_config,
+            ))
+            .output_data_config(ModelInvocationJobOutputDataConfig::S3OutputDataConfig(
+                output_data_config,
+            ))
+            .send()
+            .await
+            .map_err(|e| from_batch_sdk_error("creating the batch inference job", e))?;
+
+        Ok(response.job_arn().to_string())
+    }
+
+    pub async fn get_batch_job(&self, job_id: &str) -> Result<batch::BatchInfo, llm::Error> {
+        let job = self
+            .batch_client
+            .get_model_invocation_job()
+            .job_identifier(job_id)
+            .send()
+            .await
+            .map_err(|e| from_batch_sdk_error(&format!("getting batch job {job_id}"), e))?;
+
+        Ok(conversions::model_invocation_job_to_batch_info(&job))
+    }
+
+    pub async fn stop_batch_job(&self, job_id: &str) -> Result<String, llm::Error> {
+        self.batch_client
+            .stop_model_invocation_job()
+            .job_identifier(job_id)
+            .send()
+            .await
+            .map_err(|e| from_batch_sdk_error(&format!("stopping batch job {job_id}"), e))?;
+
+        Ok(ModelInvocationJobStatus::Stopping.as_str().to_string())
+    }
+
+    /// Reads the results of a completed batch job from its output file in S3
+    pub async fn batch_results(&self, job_id: &str) -> Result<Vec<batch::BatchResult>, llm::Error> {
+        let job = self
+            .batch_client
+            .get_model_invocation_job()
+            .job_identifier(job_id)
+            .send()
+            .await
+            .map_err(|e| from_batch_sdk_error(&format!("getting batch job {job_id}"), e))?;
+
+        match job.status() {
+            Some(ModelInvocationJobStatus::Completed)
+            | Some(ModelInvocationJobStatus::PartiallyCompleted) => {}
+            status => {
+                return Err(custom_error(
+                    llm::ErrorCode::InvalidRequest,
+                    format!(
+                        "Batch {job_id} has no results yet ({})",
+                        status.map(|status| status.as_str()).unwrap_or("unknown")
+                    ),
+                ))
+            }
+        }
+
+        let family = BatchModelFamily::of(job.model_id())?;
+        let (output, key) = conversions::batch_output_location(&job)?;
+        trace!("Reading batch output from s3://{}/{key}", output.bucket);
+        let response = self
+            .s3_client
+            .get_object()
+            .bucket(&output.bucket)
+            .key(&key)
+            .send()
+            .await
+            .map_err(|e| from_batch_sdk_error("reading the batch output from S3", e))?;
+        let bytes = response
+            .body
+            .collect()
+            .await
+            .map_err(|e| from_batch_sdk_error("reading the batch output from S3", e))?
+            .into_bytes();
+        let content = String::from_utf8(bytes.to_vec())
+            .map_err(|e| from_batch_sdk_error("decoding the batch output", e))?;
+
+        conversions::batch_output_to_batch_results(family, &content)
+    }
+
     fn init_converse(&self, input: conversions::BedrockInput) -> ConverseFluentBuilder {
         self.client
             .converse()
@@ -116,6 +347,8 @@ impl Bedrock {
             .inference_config(input.inference_configuration)
             .set_tool_config(input.tools)
             .additional_model_request_fields(input.additional_fields)
+            .set_guardrail_config(input.guardrail_config)
+            .set_performance_config(input.performance_config)
     }
 
     fn init_converse_stream(
@@ -130,6 +363,8 @@ impl Bedrock {
             .inference_config(input.inference_configuration)
             .set_tool_config(input.tools)
             .additional_model_request_fields(input.additional_fields)
+            .set_guardrail_config(input.guardrail_stream_config)
+            .set_performance_config(input.performance_config)
     }
 }
 
@@ -139,15 +374,32 @@ pub struct BedrockEnvironment {
     region: String,
     secret_access_key: String,
     session_token: Option<String>,
+    assume_role: Option<AssumeRole>,
+}
+
+#[derive(Debug)]
+struct AssumeRole {
+    role_arn: String,
+    session_name: String,
+    external_id: Option<String>,
 }
 
 impl BedrockEnvironment {
-    pub fn load_from_env() -> Result<Self, llm::Error> {
+    /// Loads the configuration from the per-call credentials, the secret store or the environment
+    pub fn load() -> Result<Self, llm::Error> {
+        let assume_role = get_config_key_or_none("AWS_ROLE_ARN").map(|role_arn| AssumeRole {
+            role_arn,
+            session_name: get_config_key_or_none("AWS_ROLE_SESSION_NAME")
+                .unwrap_or("golem-llm-bedrock".to_string()),
+            external_id: get_config_key_or_none("AWS_EXTERNAL_ID"),
+        });
+
         Ok(Self {
             access_key_id: get_config_key("AWS_ACCESS_KEY_ID")?,
             region: get_config_key("AWS_REGION")?,
             secret_access_key: get_config_key("AWS_SECRET_ACCESS_KEY")?,
             session_token: get_config_key_or_none("AWS_SESSION_TOKEN"),
+            assume_role,
         })
     }
 
@@ -155,14 +407,66 @@ impl BedrockEnvironment {
         region::Region::new(self.region.clone())
     }
 
-    fn aws_credentials(&self) -> bedrock::config::Credentials {
-        bedrock::config::Credentials::new(
+    /// Returns the configured credentials, or when `AWS_ROLE_ARN` is set, the temporary
+    /// credentials of the assumed role, which are refreshed with STS before they expire
+    async fn aws_credentials(&self) -> Result<bedrock::config::Credentials, llm::Error> {
+        let credentials = bedrock::config::Credentials::new(
             self.access_key_id.clone(),
             self.secret_access_key.clone(),
             self.session_token.clone(),
             None,
             "llm-bedrock",
-        )
+        );
+
+        let Some(assume_role) = &self.assume_role else {
+            return Ok(credentials);
+        };
+
+        let cache_key = format!("{}:{}", self.access_key_id, assume_role.role_arn);
+        if let Some(credentials) = ASSUMED_ROLE_CREDENTIALS.with(|cache| cache.get(&cache_key)) {
+            return Ok(credentials);
+        }
+
+        trace!("Assuming role {}", assume_role.role_arn);
+        let sts_config = aws_config::defaults(BehaviorVersion::latest())
+            .region(self.aws_region())
+            .http_client(WasiClient::new())
+            .credentials_provider(credentials)
+            .sleep_impl(WasiSleep::new())
+            .load()
+            .await;
+        let response = aws_sdk_sts::Client::new(&sts_config)
+            .assume_role()
+            .role_arn(&assume_role.role_arn)
+            .role_session_name(&assume_role.session_name)
+            .set_external_id(assume_role.external_id.clone())
+            .send()
+            .await
+            .map_err(|err| {
+                custom_error(
+                    llm::ErrorCode::AuthenticationFailed,
+                    format!("Failed to assume role {}: {err:?}", assume_role.role_arn),
+                )
+            })?;
+        let assumed = response.credentials().ok_or_else(|| {
+            custom_error(
+                llm::ErrorCode::AuthenticationFailed,
+                format!("No credentials returned for role {}", assume_role.role_arn),
+            )
+        })?;
+
+        let expires_at =
+            SystemTime::UNIX_EPOCH + Duration::from_secs(assumed.expiration().secs().max(0) as u64);
+        let credentials = bedrock::config::Credentials::new(
+            assumed.access_key_id(),
+            assumed.secret_access_key(),
+            Some(assumed.session_token().to_string()),
+            Some(expires_at),
+            "llm-bedrock-assume-role",
+        );
+        ASSUMED_ROLE_CREDENTIALS
+            .with(|cache| cache.insert(cache_key, credentials.clone(), expires_at));
+        Ok(credentials)
     }
 }
 
@@ -1,18 +1,30 @@
-use aws_smithy_types::{Document, Number};
+use aws_sdk_bedrock::{
+    operation::get_model_invocation_job::GetModelInvocationJobOutput,
+    types::ModelInvocationJobStatus,
+};
+use aws_smithy_types::{date_time::Format as DateTimeFormat, Document, Number};
 use std::collections::HashMap;
 
 use aws_sdk_bedrockruntime::{
     self as bedrock,
     error::SdkError,
-    operation::{converse, converse_stream},
+    operation::{apply_guardrail, converse, converse_stream, invoke_model},
     types::{
         ContentBlockDeltaEvent, ContentBlockStartEvent, ConversationRole,
-        ConverseStreamMetadataEvent, ConverseStreamOutput, ImageBlock, ImageFormat,
-        InferenceConfiguration, MessageStopEvent, SystemContentBlock, Tool, ToolConfiguration,
-        ToolInputSchema, ToolSpecification, ToolUseBlock,
+        ConverseStreamMetadataEvent, ConverseStreamOutput, GuardrailAction, GuardrailAssessment,
+        GuardrailConfiguration, GuardrailContentFilterConfidence, GuardrailContentSource,
+        GuardrailStreamConfiguration, GuardrailStreamProcessingMode, GuardrailTrace,
+        GuardrailTraceAssessment, ImageBlock, ImageFormat, InferenceConfiguration,
+        MessageStopEvent, PerformanceConfigLatency, PerformanceConfiguration, SystemContentBlock,
+        Tool, ToolConfiguration, ToolInputSchema, ToolSpecification, ToolUseBlock,
     },
 };
-use golem_llm::golem::llm::llm;
+use base64::{engine::general_purpose, Engine as _};
+use golem_llm::config::{reject_unsupported_parameters, SamplingParameter};
+use golem_llm::error::unsupported;
+use golem_llm::golem::llm::{batch, images, llm, moderation};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use wstd::http;
 
 #[derive(Debug)]
@@ -23,6 +35,9 @@ pub struct BedrockInput {
     pub inference_configuration: InferenceConfiguration,
     pub tools: Option<ToolConfiguration>,
     pub additional_fields: aws_smithy_types::Document,
+    pub guardrail_config: Option<GuardrailConfiguration>,
+    pub guardrail_stream_config: Option<GuardrailStreamConfiguration>,
+    pub performance_config: Option<PerformanceConfiguration>,
 }
 
 impl BedrockInput {
@@ -31,6 +46,17 @@ impl BedrockInput {
         config: llm::Config,
         tool_results: Option<Vec<(llm::ToolCall, llm::ToolResult)>>,
     ) -> Result<Self, llm::Error> {
+        let mut unsupported_parameters = vec![
+            SamplingParameter::Seed,
+            SamplingParameter::FrequencyPenalty,
+            SamplingParameter::PresencePenalty,
+            SamplingParameter::RepetitionPenalty,
+        ];
+        if !supports_top_k(&config.model) {
+            unsupported_parameters.push(SamplingParameter::TopK);
+        }
+        reject_unsupported_parameters("Bedrock", &config, &unsupported_parameters)?;
+
         let (mut user_messages, system_instructions) =
             messages_to_bedrock_message_groups(messages).await?;
 
@@ -38,31 +64,243 @@ impl BedrockInput {
             user_messages.extend(tool_call_results_to_bedrock_tools(tool_results)?);
         }
 
-        let options = config
+        let mut provider_options = config
             .provider_options
             .into_iter()
-            .map(|kv| (kv.key, Document::String(kv.value)))
+            .map(|kv| (kv.key, kv.value))
             .collect::<HashMap<_, _>>();
+        let converse_options = ConverseOptions::take(&mut provider_options)?;
+
+        let mut options = provider_options
+            .into_iter()
+            .map(|(key, value)| (key, Document::String(value)))
+            .collect::<HashMap<_, _>>();
+        // The Converse API has no top-k parameter, it is passed to the model as an additional
+        // request field
+        if let Some(top_k) = config.top_k {
+            options.insert(
+                "top_k".to_string(),
+                Document::Number(Number::PosInt(top_k as u64)),
+            );
+        }
 
         Ok(BedrockInput {
-            model_id: config.model,
+            model_id: converse_options.model_id(config.model),
             inference_configuration: InferenceConfiguration::builder()
                 .set_max_tokens(config.max_tokens.map(|x| x as i32))
                 .set_temperature(config.temperature)
                 .set_stop_sequences(config.stop_sequences)
-                .set_top_p(options.get("top_p").and_then(|v| match v {
-                    Document::String(v) => v.parse::<f32>().ok(),
-                    _ => None,
-                }))
+                .set_top_p(config.top_p.or(converse_options.top_p))
                 .build(),
             messages: user_messages,
             system_instructions,
             tools: tool_defs_to_bedrock_tool_config(config.tools)?,
             additional_fields: Document::Object(options),
+            guardrail_config: converse_options
+                .guardrail
+                .as_ref()
+                .map(GuardrailOptions::converse_config)
+                .transpose()?,
+            guardrail_stream_config: converse_options
+                .guardrail
+                .as_ref()
+                .map(GuardrailOptions::stream_config)
+                .transpose()?,
+            performance_config: converse_options
+                .performance_latency
+                .map(|latency| PerformanceConfiguration::builder().latency(latency).build()),
         })
     }
 }
 
+/// The Converse API has no top-k parameter, and only the Anthropic, Mistral and Cohere models
+/// accept it as an additional request field. Also matches inference profiles and ARNs of the models.
+fn supports_top_k(model: &str) -> bool {
+    ["anthropic.", "mistral.", "cohere."]
+        .iter()
+        .any(|provider| model.contains(provider))
+}
+
+/// Settings of the Converse request passed as provider options. They are removed from the
+/// options, which are otherwise sent to the model as additional request fields.
+#[derive(Debug, Default)]
+struct ConverseOptions {
+    guardrail: Option<GuardrailOptions>,
+    performance_latency: Option<PerformanceConfigLatency>,
+    inference_profile: Option<String>,
+    cross_region: Option<String>,
+    top_p: Option<f32>,
+}
+
+impl ConverseOptions {
+    fn take(options: &mut HashMap<String, String>) -> Result<Self, llm::Error> {
+        if !options.contains_key("guardrail_id") {
+            for key in [
+                "guardrail_version",
+                "guardrail_trace",
+                "guardrail_stream_mode",
+            ] {
+                if options.contains_key(key) {
+                    return Err(custom_error(
+                        llm::ErrorCode::InvalidRequest,
+                        format!("The {key} provider option requires guardrail_id"),
+                    ));
+                }
+            }
+        }
+
+        let guardrail = match options.remove("guardrail_id") {
+            Some(identifier) => Some(GuardrailOptions {
+                identifier,
+                version: options
+                    .remove("guardrail_version")
+                    .unwrap_or("DRAFT".to_string()),
+                trace: match options.remove("guardrail_trace").as_deref() {
+                    None | Some("enabled") => GuardrailTrace::Enabled,
+                    Some("enabled_full") => GuardrailTrace::EnabledFull,
+                    Some("disabled") => GuardrailTrace::Disabled,
+                    Some(other) => {
+                        return Err(invalid_option(
+                            "guardrail_trace",
+                            other,
+                            "enabled, enabled_full or disabled",
+                        ))
+                    }
+                },
+                stream_processing_mode: match options.remove("guardrail_stream_mode").as_deref() {
+                    None => None,
+                    Some("sync") => Some(GuardrailStreamProcessingMode::Sync),
+                    Some("async") => Some(GuardrailStreamProcessingMode::Async),
+                    Some(other) => {
+                        return Err(invalid_option(
+                            "guardrail_stream_mode",
+                            other,
+                            "sync or async",
+                        ))
+                    }
+                },
+            }),
+            None => None,
+        };
+
+        let performance_latency = match options.remove("performance_latency").as_deref() {
+            None => None,
+            Some("standard") => Some(PerformanceConfigLatency::Standard),
+            Some("optimized") => Some(PerformanceConfigLatency::Optimized),
+            Some(other) => {
+                return Err(invalid_option(
+                    "performance_latency",
+                    other,
+                    "standard or optimized",
+                ))
+            }
+        };
+
+        let inference_profile = options.remove("inference_profile");
+        let cross_region = match options.remove("cross_region") {
+            Some(_) if inference_profile.is_some() => {
+                return Err(custom_error(
+                    llm::ErrorCode::InvalidRequest,
+                    "The cross_region and inference_profile provider options can't be combined"
+                        .to_string(),
+                ))
+            }
+            Some(region)
+                if region.is_empty()
+                    || !region.chars().all(|c| c.is_ascii_lowercase() || c == '-') =>
+            {
+                return Err(invalid_option(
+                    "cross_region",
+                    &region,
+                    "a geography prefix such as us, eu or apac",
+                ))
+            }
+            region => region,
+        };
+
+        // top_p is also sent to the model as an additional request field
+        let top_p = match options.get("top_p") {
+            None => None,
+            Some(value) => Some(
+                value
+                    .parse::<f32>()
+                    .map_err(|_| invalid_option("top_p", value, "a number"))?,
+            ),
+        };
+
+        Ok(Self {
+            guardrail,
+            performance_latency,
+            inference_profile,
+            cross_region,
+            top_p,
+        })
+    }
+
+    /// The model is invoked through the given inference profile (ID or ARN), or through the
+    /// system-defined cross-region inference profile of the model for the given geography. The
+    /// requested model is still used to look up its price.
+    fn model_id(&self, model: String) -> String {
+        if let Some(inference_profile) = &self.inference_profile {
+            inference_profile.clone()
+        } else if let Some(region) = &self.cross_region {
+            if model.starts_with("arn:") || model.starts_with(&format!("{region}.")) {
+                model
+            } else {
+                format!("{region}.{model}")
+            }
+        } else {
+            model
+        }
+    }
+}
+
+#[derive(Debug)]
+struct GuardrailOptions {
+    identifier: String,
+    version: String,
+    trace: GuardrailTrace,
+    stream_processing_mode: Option<GuardrailStreamProcessingMode>,
+}
+
+impl GuardrailOptions {
+    fn converse_config(&self) -> Result<GuardrailConfiguration, llm::Error> {
+        GuardrailConfiguration::builder()
+            .guardrail_identifier(self.identifier.clone())
+            .guardrail_version(self.version.clone())
+            .trace(self.trace.clone())
+            .build()
+            .map_err(|err| {
+                custom_error(
+                    llm::ErrorCode::InvalidRequest,
+                    format!("Invalid guardrail configuration: {err}"),
+                )
+            })
+    }
+
+    fn stream_config(&self) -> Result<GuardrailStreamConfiguration, llm::Error> {
+        GuardrailStreamConfiguration::builder()
+            .guardrail_identifier(self.identifier.clone())
+            .guardrail_version(self.version.clone())
+            .trace(self.trace.clone())
+            .set_stream_processing_mode(self.stream_processing_mode.clone())
+            .build()
+            .map_err(|err| {
+                custom_error(
+                    llm::ErrorCode::InvalidRequest,
+                    format!("Invalid guardrail configuration: {err}"),
+                )
+            })
+    }
+}
+
+fn invalid_option(key: &str, value: &str, expected: &str) -> llm::Error {
+    custom_error(
+        llm::ErrorCode::InvalidRequest,
+        format!("Invalid {key} provider option: {value}, expected {expected}"),
+    )
+}
+
 fn tool_call_results_to_bedrock_tools(
     results: Vec<(llm::ToolCall, llm::ToolResult)>,
 ) -> Result<Vec<bedrock::types::Message>, llm::Error> {
@@ -370,11 +608,92 @@ fn converse_output_to_response_metadata(
         finish_reason: Some(bedrock_stop_reason_to_finish_reason(response.stop_reason())),
         usage: response.usage().map(bedrock_usage_to_llm_usage),
         provider_id: Some("bedrock".to_owned()),
-        provider_metadata_json: response
-            .additional_model_response_fields
-            .clone()
-            .and_then(smithy_document_to_metadata_json),
+        provider_metadata_json: merge_metadata_json(
+            response
+                .additional_model_response_fields
+                .clone()
+                .and_then(smithy_document_to_metadata_json),
+            response
+                .trace()
+                .and_then(|trace| trace.guardrail())
+                .map(guardrail_trace_to_metadata_json),
+        ),
         timestamp: None,
+        cost: None,
+    }
+}
+
+/// Describes the assessments of the guardrail attached to the request, as
+/// `{"guardrail": {"action_reason": ..., "input_assessment": ..., "output_assessments": ...}}`
+fn guardrail_trace_to_metadata_json(trace: &GuardrailTraceAssessment) -> String {
+    let assessment_json = |assessment: &GuardrailAssessment| {
+        guardrail_assessment_categories(assessment)
+            .into_iter()
+            .map(|category| {
+                serde_json::json!({
+                    "category": category.category,
+                    "flagged": category.flagged,
+                    "score": category.score,
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let input_assessment = trace.input_assessment().map(|assessments| {
+        assessments
+            .iter()
+            .map(|(id, assessment)| (id.clone(), serde_json::json!(assessment_json(assessment))))
+            .collect::<serde_json::Map<_, _>>()
+    });
+    let output_assessments = trace.output_assessments().map(|assessments| {
+        assessments
+            .iter()
+            .map(|(id, assessments)| {
+                (
+                    id.clone(),
+                    serde_json::json!(assessments.iter().map(assessment_json).collect::<Vec<_>>()),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>()
+    });
+
+    serde_json::json!({
+        "guardrail": {
+            "action_reason": trace.action_reason(),
+            "model_output": trace.model_output(),
+            "input_assessment": input_assessment,
+            "output_assessments": output_assessments,
+        }
+    })
+    .to_string()
+}
+
+/// Merges the additional model response fields with the guardrail trace. The fields of the
+/// model take precedence, and they are nested under `additional_fields` if they are not an object.
+fn merge_metadata_json(first: Option<String>, second: Option<String>) -> Option<String> {
+    match (first, second) {
+        (Some(first), Some(second)) => {
+            match (
+                serde_json::from_str::<serde_json::Value>(&first),
+                serde_json::from_str::<serde_json::Value>(&second),
+            ) {
+                (
+                    Ok(serde_json::Value::Object(mut first)),
+                    Ok(serde_json::Value::Object(second)),
+                ) => {
+                    for (key, value) in second {
+                        first.entry(key).or_insert(value);
+                    }
+                    Some(serde_json::Value::Object(first).to_string())
+                }
+                (Ok(first), Ok(serde_json::Value::Object(mut second))) => {
+                    second.insert("additional_fields".to_string(), first);
+                    Some(serde_json::Value::Object(second).to_string())
+                }
+                _ => Some(first),
+            }
+        }
+        (first, second) => first.or(second),
     }
 }
 
@@ -382,11 +701,19 @@ fn smithy_document_to_metadata_json(doc: Document) -> Option<String> {
     serde_json::to_string(&smithy_document_to_json_value(doc)).ok()
 }
 
+/// Bedrock reports the tokens read from and written to the prompt cache separately, while
+/// `input_tokens` of `Usage` includes them
 fn bedrock_usage_to_llm_usage(usage: &bedrock::types::TokenUsage) -> llm::Usage {
+    let cache_read_input_tokens = usage.cache_read_input_tokens().unwrap_or(0);
+    let cache_write_input_tokens = usage.cache_write_input_tokens().unwrap_or(0);
     llm::Usage {
-        input_tokens: Some(usage.input_tokens() as u32),
+        input_tokens: Some(
+            (usage.input_tokens() + cache_read_input_tokens + cache_write_input_tokens) as u32,
+        ),
         output_tokens: Some(usage.output_tokens() as u32),
         total_tokens: Some(usage.total_tokens() as u32),
+        cached_input_tokens: usage.cache_read_input_tokens().map(|tokens| tokens as u32),
+        reasoning_tokens: None,
     }
 }
 
@@ -485,7 +812,11 @@ fn process_metadata_event(metadata: ConverseStreamMetadataEvent) -> Option<llm::
         timestamp: None,
         usage: metadata.usage().map(bedrock_usage_to_llm_usage),
         provider_id: Some("bedrock".to_owned()),
-        provider_metadata_json: None,
+        provider_metadata_json: metadata
+            .trace()
+            .and_then(|trace| trace.guardrail())
+            .map(guardrail_trace_to_metadata_json),
+        cost: None,
     }))
 }
 
@@ -499,6 +830,7 @@ fn process_message_stop_event(event: MessageStopEvent) -> Option<llm::StreamEven
             .additional_model_response_fields
             .clone()
             .and_then(smithy_document_to_metadata_json),
+        cost: None,
     }))
 }
 
@@ -594,6 +926,28 @@ pub fn from_converse_stream_sdk_error(
     }
 }
 
+pub fn from_invoke_model_sdk_error(
+    model_id: String,
+    sdk_error: SdkError<invoke_model::InvokeModelError>,
+) -> llm::Error {
+    llm::Error {
+        code: llm::ErrorCode::InternalError,
+        message: format!("Error calling Bedrock model {model_id}: {sdk_error:?}",),
+        provider_error_json: None,
+    }
+}
+
+pub fn from_apply_guardrail_sdk_error(
+    guardrail_id: String,
+    sdk_error: SdkError<apply_guardrail::ApplyGuardrailError>,
+) -> llm::Error {
+    llm::Error {
+        code: llm::ErrorCode::InternalError,
+        message: format!("Error applying Bedrock guardrail {guardrail_id}: {sdk_error:?}",),
+        provider_error_json: None,
+    }
+}
+
 pub fn custom_error(code: llm::ErrorCode, message: String) -> llm::Error {
     llm::Error {
         code,
@@ -610,9 +964,1268 @@ pub fn merge_metadata(
     metadata1.timestamp = metadata1.timestamp.or(metadata2.timestamp);
     metadata1.provider_id = metadata1.provider_id.or(metadata2.provider_id);
     metadata1.finish_reason = metadata1.finish_reason.or(metadata2.finish_reason);
-    metadata1.provider_metadata_json = metadata1
-        .provider_metadata_json
-        .or(metadata2.provider_metadata_json);
+    metadata1.provider_metadata_json = merge_metadata_json(
+        metadata1.provider_metadata_json,
+        metadata2.provider_metadata_json,
+    );
+    metadata1.cost = metadata1.cost.or(metadata2.cost);
 
     metadata1
 }
+
+/// Default model used for the `images` interface, Titan Image Generator v2 shares the same request format
+pub const DEFAULT_IMAGE_MODEL_ID: &str = "amazon.nova-canvas-v1:0";
+
+/// Request body of the Amazon Titan Image Generator and Nova Canvas models
+///
+/// Based on https://docs.aws.amazon.com/nova/latest/userguide/image-gen-req-resp-structure.html
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageGenerationRequest {
+    pub task_type: ImageTaskType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_to_image_params: Option<TextToImageParams>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub in_painting_params: Option<InPaintingParams>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_variation_params: Option<ImageVariationParams>,
+    pub image_generation_config: ImageGenerationConfig,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ImageTaskType {
+    TextImage,
+    Inpainting,
+    ImageVariation,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextToImageParams {
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub negative_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InPaintingParams {
+    pub image: String,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub negative_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mask_image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mask_prompt: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageVariationParams {
+    pub images: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub negative_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similarity_strength: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageGenerationConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number_of_images: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cfg_scale: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImageGenerationResponse {
+    #[serde(default)]
+    pub images: Vec<String>,
+    pub error: Option<String>,
+}
+
+pub fn image_model_id(config: &images::ImageConfig) -> String {
+    config
+        .model
+        .clone()
+        .unwrap_or_else(|| DEFAULT_IMAGE_MODEL_ID.to_string())
+}
+
+pub fn text_to_image_request(
+    prompt: String,
+    config: images::ImageConfig,
+) -> Result<ImageGenerationRequest, llm::Error> {
+    check_unsupported_image_options(&config)?;
+    Ok(ImageGenerationRequest {
+        task_type: ImageTaskType::TextImage,
+        text_to_image_params: Some(TextToImageParams {
+            text: prompt,
+            negative_text: config.negative_prompt.clone(),
+            style: config.style.clone(),
+        }),
+        in_painting_params: None,
+        image_variation_params: None,
+        image_generation_config: image_generation_config(&config),
+    })
+}
+
+pub fn inpainting_request(
+    image: llm::ImageSource,
+    mask: Option<llm::ImageSource>,
+    prompt: String,
+    config: images::ImageConfig,
+) -> Result<ImageGenerationRequest, llm::Error> {
+    check_unsupported_image_options(&config)?;
+    if config.style.is_some() {
+        return Err(custom_error(
+            llm::ErrorCode::Unsupported,
+            "Unsupported: style is only supported for text to image generation on Bedrock"
+                .to_string(),
+        ));
+    }
+
+    let mask_prompt = image_option(&config, "mask_prompt");
+    if mask.is_none() && mask_prompt.is_none() {
+        return Err(custom_error(
+            llm::ErrorCode::InvalidRequest,
+            "Bedrock image editing requires either a mask image or a 'mask_prompt' provider option"
+                .to_string(),
+        ));
+    }
+
+    Ok(ImageGenerationRequest {
+        task_type: ImageTaskType::Inpainting,
+        text_to_image_params: None,
+        in_painting_params: Some(InPaintingParams {
+            image: general_purpose::STANDARD.encode(&image.data),
+            text: prompt,
+            negative_text: config.negative_prompt.clone(),
+            mask_image: mask.map(|mask| general_purpose::STANDARD.encode(&mask.data)),
+            mask_prompt,
+        }),
+        image_variation_params: None,
+        image_generation_config: image_generation_config(&config),
+    })
+}
+
+pub fn image_variation_request(
+    image: llm::ImageSource,
+    config: images::ImageConfig,
+) -> Result<ImageGenerationRequest, llm::Error> {
+    check_unsupported_image_options(&config)?;
+    if config.style.is_some() {
+        return Err(custom_error(
+            llm::ErrorCode::Unsupported,
+            "Unsupported: style is only supported for text to image generation on Bedrock"
+                .to_string(),
+        ));
+    }
+
+    Ok(ImageGenerationRequest {
+        task_type: ImageTaskType::ImageVariation,
+        text_to_image_params: None,
+        in_painting_params: None,
+        image_variation_params: Some(ImageVariationParams {
+            images: vec![general_purpose::STANDARD.encode(&image.data)],
+            text: image_option(&config, "text"),
+            negative_text: config.negative_prompt.clone(),
+            similarity_strength: image_option(&config, "similarity_strength")
+                .and_then(|v| v.parse::<f32>().ok()),
+        }),
+        image_generation_config: image_generation_config(&config),
+    })
+}
+
+pub fn image_generation_response_to_image_response(
+    response: ImageGenerationResponse,
+    seed: Option<u32>,
+) -> Result<images::ImageResponse, llm::Error> {
+    if let Some(error) = response.error {
+        return Err(custom_error(llm::ErrorCode::InternalError, error));
+    }
+
+    let mut generated = Vec::new();
+    for image in response.images {
+        let data = general_purpose::STANDARD.decode(image).map_err(|err| {
+            custom_error(
+                llm::ErrorCode::InternalError,
+                format!("Failed to decode base64 image data: {err}"),
+            )
+        })?;
+        generated.push(images::GeneratedImage {
+            image: llm::ImageReference::Inline(llm::ImageSource {
+                data,
+                mime_type: "image/png".to_string(),
+                detail: None,
+            }),
+            revised_prompt: None,
+            seed,
+        });
+    }
+
+    Ok(images::ImageResponse {
+        images: generated,
+        provider_id: None,
+        timestamp: None,
+        provider_metadata_json: None,
+    })
+}
+
+fn check_unsupported_image_options(config: &images::ImageConfig) -> Result<(), llm::Error> {
+    if config.response_format == Some(images::ImageResponseFormat::Url) {
+        Err(custom_error(
+            llm::ErrorCode::Unsupported,
+            "Unsupported: Bedrock image models only return inline image data".to_string(),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn image_option(config: &images::ImageConfig, key: &str) -> Option<String> {
+    config
+        .provider_options
+        .iter()
+        .find(|kv| kv.key == key)
+        .map(|kv| kv.value.clone())
+}
+
+fn image_generation_config(config: &images::ImageConfig) -> ImageGenerationConfig {
+    ImageGenerationConfig {
+        number_of_images: config.count,
+        width: config.size.as_ref().map(|size| size.width),
+        height: config.size.as_ref().map(|size| size.height),
+        quality: config.quality.map(|quality| match quality {
+            images::ImageQuality::Standard => "standard".to_string(),
+            images::ImageQuality::High => "premium".to_string(),
+        }),
+        cfg_scale: image_option(config, "cfg_scale").and_then(|v| v.parse::<f32>().ok()),
+        seed: config.seed,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GuardrailRequest {
+    pub guardrail_id: String,
+    pub guardrail_version: String,
+    pub source: GuardrailContentSource,
+}
+
+/// Bedrock moderation is done by applying a pre-configured guardrail, identified by the
+/// `model` field, to the inputs
+pub fn guardrail_request(
+    guardrail_id: String,
+    config: &moderation::ModerationConfig,
+) -> Result<GuardrailRequest, llm::Error> {
+    let option = |key: &str| {
+        config
+            .provider_options
+            .iter()
+            .find(|kv| kv.key == key)
+            .map(|kv| kv.value.clone())
+    };
+
+    let source = match option("source").as_deref() {
+        None | Some("input") => GuardrailContentSource::Input,
+        Some("output") => GuardrailContentSource::Output,
+        Some(other) => {
+            return Err(custom_error(
+                llm::ErrorCode::InvalidRequest,
+                format!("Invalid guardrail source: {other}, expected input or output"),
+            ))
+        }
+    };
+
+    Ok(GuardrailRequest {
+        guardrail_id,
+        guardrail_version: option("guardrail_version").unwrap_or("DRAFT".to_string()),
+        source,
+    })
+}
+
+pub fn apply_guardrail_output_to_moderation_result(
+    output: &apply_guardrail::ApplyGuardrailOutput,
+) -> moderation::ModerationResult {
+    moderation::ModerationResult {
+        flagged: *output.action() == GuardrailAction::GuardrailIntervened,
+        categories: output
+            .assessments()
+            .iter()
+            .flat_map(guardrail_assessment_categories)
+            .collect(),
+    }
+}
+
+fn guardrail_assessment_categories(
+    assessment: &GuardrailAssessment,
+) -> Vec<moderation::CategoryScore> {
+    let mut categories = Vec::new();
+
+    if let Some(content_policy) = assessment.content_policy() {
+        for filter in content_policy.filters() {
+            categories.push(moderation::CategoryScore {
+                category: filter.r#type().as_str().to_lowercase(),
+                flagged: is_guardrail_action(filter.action().as_str()),
+                score: Some(confidence_score(filter.confidence())),
+            });
+        }
+    }
+    if let Some(topic_policy) = assessment.topic_policy() {
+        for topic in topic_policy.topics() {
+            categories.push(moderation::CategoryScore {
+                category: format!("topic:{}", topic.name()),
+                flagged: is_guardrail_action(topic.action().as_str()),
+                score: None,
+            });
+        }
+    }
+    if let Some(word_policy) = assessment.word_policy() {
+        for word in word_policy.custom_words() {
+            categories.push(moderation::CategoryScore {
+                category: "custom-word".to_string(),
+                flagged: is_guardrail_action(word.action().as_str()),
+                score: None,
+            });
+        }
+        for word in word_policy.managed_word_lists() {
+            categories.push(moderation::CategoryScore {
+                category: word.r#type().as_str().to_lowercase(),
+                flagged: is_guardrail_action(word.action().as_str()),
+                score: None,
+            });
+        }
+    }
+    if let Some(sensitive_information_policy) = assessment.sensitive_information_policy() {
+        for entity in sensitive_information_policy.pii_entities() {
+            categories.push(moderation::CategoryScore {
+                category: format!("pii:{}", entity.r#type().as_str().to_lowercase()),
+                flagged: is_guardrail_action(entity.action().as_str()),
+                score: None,
+            });
+        }
+        for regex in sensitive_information_policy.regexes() {
+            categories.push(moderation::CategoryScore {
+                category: format!("regex:{}", regex.name().unwrap_or("unnamed")),
+                flagged: is_guardrail_action(regex.action().as_str()),
+                score: None,
+            });
+        }
+    }
+
+    categories
+}
+
+/// Any guardrail action other than `NONE` (blocking, anonymizing) means that the policy matched
+fn is_guardrail_action(action: &str) -> bool {
+    action != "NONE"
+}
+
+fn confidence_score(confidence: &GuardrailContentFilterConfidence) -> f32 {
+    match confidence {
+        GuardrailContentFilterConfidence::None => 0.0,
+        GuardrailContentFilterConfidence::Low => 0.25,
+        GuardrailContentFilterConfidence::Medium => 0.5,
+        GuardrailContentFilterConfidence::High => 1.0,
+        _ => 0.0,
+    }
+}
+
+/// Configuration key of the IAM service role which Bedrock assumes to run batch inference jobs
+pub const BATCH_ROLE_ARN_KEY: &str = "BEDROCK_BATCH_ROLE_ARN";
+
+/// Configuration key of the `s3://bucket/prefix` location of the batch inference jobs' input and
+/// output files
+pub const BATCH_S3_URI_KEY: &str = "BEDROCK_BATCH_S3_URI";
+
+/// Model families supported by batch inference. Batch jobs invoke the model with its native
+/// request body instead of the Converse API, so each family needs its own request format.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BatchModelFamily {
+    Anthropic,
+    Nova,
+}
+
+impl BatchModelFamily {
+    /// Also matches inference profiles and ARNs of the models, such as `us.anthropic.claude-...`
+    pub fn of(model_id: &str) -> Result<Self, llm::Error> {
+        if model_id.contains("anthropic.") {
+            Ok(Self::Anthropic)
+        } else if model_id.contains("amazon.nova") {
+            Ok(Self::Nova)
+        } else {
+            Err(unsupported(format!(
+                "Bedrock batch inference is only supported for the Anthropic Claude and Amazon Nova models, not {model_id}"
+            )))
+        }
+    }
+}
+
+/// Bucket and key prefix of an `s3://bucket/prefix` URI
+#[derive(Debug, Clone, PartialEq)]
+pub struct S3Location {
+    pub bucket: String,
+    pub prefix: String,
+}
+
+impl S3Location {
+    pub fn parse(uri: &str) -> Result<Self, llm::Error> {
+        let path = uri.strip_prefix("s3://").ok_or_else(|| {
+            custom_error(
+                llm::ErrorCode::InvalidRequest,
+                format!("Invalid S3 URI {uri}, expected s3://bucket/prefix"),
+            )
+        })?;
+        let (bucket, prefix) = path.split_once('/').unwrap_or((path, ""));
+        if bucket.is_empty() {
+            return Err(custom_error(
+                llm::ErrorCode::InvalidRequest,
+                format!("Invalid S3 URI {uri}, the bucket is missing"),
+            ));
+        }
+
+        Ok(Self {
+            bucket: bucket.to_string(),
+            prefix: prefix.trim_matches('/').to_string(),
+        })
+    }
+
+    pub fn join(&self, name: &str) -> Self {
+        Self {
+            bucket: self.bucket.clone(),
+            prefix: self.key(name),
+        }
+    }
+
+    pub fn key(&self, name: &str) -> String {
+        if self.prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{name}", self.prefix)
+        }
+    }
+
+    pub fn uri(&self) -> String {
+        format!("s3://{}/{}", self.bucket, self.prefix)
+    }
+}
+
+/// Input of a batch inference job: the model shared by every request and the JSONL records
+/// written to S3
+#[derive(Debug)]
+pub struct BatchInput {
+    pub model_id: String,
+    pub records: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchInputRecord {
+    record_id: String,
+    model_input: serde_json::Value,
+}
+
+/// A batch inference job runs a single model, so every request of the batch has to use the same
+/// model
+pub async fn batch_requests_to_batch_input(
+    requests: Vec<batch::BatchRequest>,
+) -> Result<BatchInput, llm::Error> {
+    let mut model_id: Option<String> = None;
+    let mut records = String::new();
+
+    for request in requests {
+        let (request_model_id, model_input) =
+            batch_request_to_model_input(request.messages, request.config).await?;
+        match &model_id {
+            Some(model_id) if *model_id != request_model_id => {
+                return Err(custom_error(
+                    llm::ErrorCode::InvalidRequest,
+                    format!(
+                        "Every request of a Bedrock batch job must use the same model, got {model_id} and {request_model_id}"
+                    ),
+                ));
+            }
+            Some(_) => {}
+            None => model_id = Some(request_model_id),
+        }
+
+        let record = serde_json::to_string(&BatchInputRecord {
+            record_id: request.custom_id,
+            model_input,
+        })
+        .map_err(|err| {
+            custom_error(
+                llm::ErrorCode::InternalError,
+                format!("Failed to serialize batch record: {err}"),
+            )
+        })?;
+        records.push_str(&record);
+        records.push('\n');
+    }
+
+    match model_id {
+        Some(model_id) => Ok(BatchInput { model_id, records }),
+        None => Err(custom_error(
+            llm::ErrorCode::InvalidRequest,
+            "A batch job needs at least one request".to_string(),
+        )),
+    }
+}
+
+/// Converts a request to the native request body of the model. Like with the Converse API, the
+/// provider options which are not Converse settings are passed to the model as request fields.
+async fn batch_request_to_model_input(
+    messages: Vec<llm::Message>,
+    config: llm::Config,
+) -> Result<(String, serde_json::Value), llm::Error> {
+    reject_unsupported_parameters(
+        "Bedrock",
+        &config,
+        &[
+            SamplingParameter::Seed,
+            SamplingParameter::FrequencyPenalty,
+            SamplingParameter::PresencePenalty,
+            SamplingParameter::RepetitionPenalty,
+        ],
+    )?;
+
+    let mut provider_options = config
+        .provider_options
+        .into_iter()
+        .map(|kv| (kv.key, kv.value))
+        .collect::<HashMap<_, _>>();
+    let converse_options = ConverseOptions::take(&mut provider_options)?;
+    if converse_options.guardrail.is_some() || converse_options.performance_latency.is_some() {
+        return Err(unsupported(
+            "guardrails and latency-optimized inference in Bedrock batch jobs",
+        ));
+    }
+    let model_id = converse_options.model_id(config.model);
+    let family = BatchModelFamily::of(&model_id)?;
+
+    let mut system = vec![];
+    let mut native_messages = vec![];
+    for message in messages {
+        if message.role == llm::Role::System {
+            for content in message.content {
+                if let llm::ContentPart::Text(text) = content {
+                    system.push(text);
+                }
+            }
+            continue;
+        }
+
+        let mut content = vec![];
+        for part in message.content {
+            content.push(match part {
+                llm::ContentPart::Text(text) => match family {
+                    BatchModelFamily::Anthropic => json!({ "type": "text", "text": text }),
+                    BatchModelFamily::Nova => json!({ "text": text }),
+                },
+                llm::ContentPart::Image(image) => {
+                    let (format, bytes) = image_ref_to_bytes(image).await?;
+                    let data = general_purpose::STANDARD.encode(bytes);
+                    match family {
+                        BatchModelFamily::Anthropic => json!({
+                            "type": "image",
+                            "source": {
+                                "type": "base64",
+                                "media_type": format!("image/{}", format.as_str()),
+                                "data": data,
+                            },
+                        }),
+                        BatchModelFamily::Nova => json!({
+                            "image": { "format": format.as_str(), "source": { "bytes": data } },
+                        }),
+                    }
+                }
+            });
+        }
+        let role = if message.role == llm::Role::User {
+            "user"
+        } else {
+            "assistant"
+        };
+        native_messages.push(json!({ "role": role, "content": content }));
+    }
+
+    let mut tools = vec![];
+    for tool in config.tools {
+        let schema: serde_json::Value =
+            serde_json::from_str(&tool.parameters_schema).map_err(|err| {
+                custom_error(
+                    llm::ErrorCode::InvalidRequest,
+                    format!("Invalid parameters schema of tool {}: {err}", tool.name),
+                )
+            })?;
+        tools.push(match family {
+            BatchModelFamily::Anthropic => json!({
+                "name": tool.name,
+                "description": tool.description,
+                "input_schema": schema,
+            }),
+            BatchModelFamily::Nova => json!({
+                "toolSpec": {
+                    "name": tool.name,
+                    "description": tool.description,
+                    "inputSchema": { "json": schema },
+                },
+            }),
+        });
+    }
+
+    let mut body = serde_json::Map::new();
+    match family {
+        BatchModelFamily::Anthropic => {
+            body.insert("anthropic_version".to_string(), json!("bedrock-2023-05-31"));
+            body.insert(
+                "max_tokens".to_string(),
+                json!(config.max_tokens.unwrap_or(4096)),
+            );
+            if !system.is_empty() {
+                body.insert("system".to_string(), json!(system.join("\n")));
+            }
+            body.insert("messages".to_string(), json!(native_messages));
+            insert_some(&mut body, "temperature", config.temperature);
+            insert_some(&mut body, "top_p", config.top_p);
+            insert_some(&mut body, "top_k", config.top_k);
+            insert_some(&mut body, "stop_sequences", config.stop_sequences);
+            if !tools.is_empty() {
+                body.insert("tools".to_string(), json!(tools));
+            }
+        }
+        BatchModelFamily::Nova => {
+            body.insert("schemaVersion".to_string(), json!("messages-v1"));
+            if !system.is_empty() {
+                let system = system
+                    .into_iter()
+                    .map(|text| json!({ "text": text }))
+                    .collect::<Vec<_>>();
+                body.insert("system".to_string(), json!(system));
+            }
+            body.insert("messages".to_string(), json!(native_messages));
+            let mut inference_config = serde_json::Map::new();
+            insert_some(&mut inference_config, "maxTokens", config.max_tokens);
+            insert_some(&mut inference_config, "temperature", config.temperature);
+            insert_some(&mut inference_config, "topP", config.top_p);
+            insert_some(&mut inference_config, "topK", config.top_k);
+            insert_some(
+                &mut inference_config,
+                "stopSequences",
+                config.stop_sequences,
+            );
+            if !inference_config.is_empty() {
+                body.insert(
+                    "inferenceConfig".to_string(),
+                    serde_json::Value::Object(inference_config),
+                );
+            }
+            if !tools.is_empty() {
+                body.insert("toolConfig".to_string(), json!({ "tools": tools }));
+            }
+        }
+    }
+    for (key, value) in provider_options {
+        body.insert(key, serde_json::Value::String(value));
+    }
+
+    Ok((model_id, serde_json::Value::Object(body)))
+}
+
+fn insert_some(
+    object: &mut serde_json::Map<String, serde_json::Value>,
+    key: &str,
+    value: Option<impl Serialize>,
+) {
+    if let Some(value) = value {
+        object.insert(key.to_string(), json!(value));
+    }
+}
+
+async fn image_ref_to_bytes(
+    image_reference: llm::ImageReference,
+) -> Result<(ImageFormat, Vec<u8>), llm::Error> {
+    match image_reference {
+        llm::ImageReference::Inline(image) => {
+            Ok((str_to_bedrock_mime_type(&image.mime_type)?, image.data))
+        }
+        llm::ImageReference::Url(url) => {
+            let bytes = get_bytes_from_url(&url.url).await?;
+            match infer::get(&bytes) {
+                Some(kind) => Ok((str_to_bedrock_mime_type(kind.mime_type())?, bytes)),
+                None => Err(custom_error(
+                    llm::ErrorCode::InvalidRequest,
+                    format!(
+                        "Could not infer the mime type of the image downloaded from url: {}",
+                        url.url
+                    ),
+                )),
+            }
+        }
+    }
+}
+
+pub fn model_invocation_job_to_batch_info(job: &GetModelInvocationJobOutput) -> batch::BatchInfo {
+    let status = match job.status() {
+        Some(ModelInvocationJobStatus::InProgress) | Some(ModelInvocationJobStatus::Stopping) => {
+            batch::BatchStatus::Running
+        }
+        Some(ModelInvocationJobStatus::Completed)
+        | Some(ModelInvocationJobStatus::PartiallyCompleted) => batch::BatchStatus::Succeeded,
+        Some(ModelInvocationJobStatus::Failed) => {
+            batch::BatchStatus::Failed(job.message().unwrap_or("Batch job failed").to_string())
+        }
+        Some(ModelInvocationJobStatus::Stopped) => batch::BatchStatus::Cancelled,
+        Some(ModelInvocationJobStatus::Expired) => batch::BatchStatus::Expired,
+        _ => batch::BatchStatus::Pending,
+    };
+
+    let metadata = json!({
+        "job_name": job.job_name(),
+        "model_id": job.model_id(),
+        "status": job.status().map(|status| status.as_str()),
+        "message": job.message(),
+        "output_s3_uri": job
+            .output_data_config()
+            .and_then(|config| config.as_s3_output_data_config().ok())
+            .map(|config| config.s3_uri()),
+    });
+
+    batch::BatchInfo {
+        job_id: job.job_arn().to_string(),
+        status,
+        // Bedrock only reports the record counts in the manifest of the job's output
+        request_counts: None,
+        created_at: job.submit_time().fmt(DateTimeFormat::DateTime).ok(),
+        provider_metadata_json: Some(metadata.to_string()),
+    }
+}
+
+/// The output of a job is written to `{output}/{job id}/{input file}.out`, where the job ID is
+/// the last segment of the job's ARN
+pub fn batch_output_location(
+    job: &GetModelInvocationJobOutput,
+) -> Result<(S3Location, String), llm::Error> {
+    let input_uri = job
+        .input_data_config()
+        .and_then(|config| config.as_s3_input_data_config().ok())
+        .map(|config| config.s3_uri());
+    let output_uri = job
+        .output_data_config()
+        .and_then(|config| config.as_s3_output_data_config().ok())
+        .map(|config| config.s3_uri());
+    let (Some(input_uri), Some(output_uri)) = (input_uri, output_uri) else {
+        return Err(custom_error(
+            llm::ErrorCode::InternalError,
+            format!("Batch job {} has no S3 input or output", job.job_arn()),
+        ));
+    };
+
+    let input_file = input_uri.rsplit('/').next().unwrap_or(input_uri);
+    let job_id = job.job_arn().rsplit('/').next().unwrap_or(job.job_arn());
+    let output = S3Location::parse(output_uri)?.join(job_id);
+    let key = output.key(&format!("{input_file}.out"));
+    Ok((output, key))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchOutputRecord {
+    record_id: String,
+    model_output: Option<serde_json::Value>,
+    error: Option<BatchRecordError>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchRecordError {
+    error_code: Option<i64>,
+    error_message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicModelOutput {
+    id: Option<String>,
+    content: Vec<AnthropicContent>,
+    stop_reason: Option<String>,
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum AnthropicContent {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicUsage {
+    input_tokens: u32,
+    output_tokens: u32,
+    cache_read_input_tokens: Option<u32>,
+    cache_creation_input_tokens: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NovaModelOutput {
+    output: NovaOutput,
+    stop_reason: Option<String>,
+    usage: Option<NovaUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NovaOutput {
+    message: NovaMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct NovaMessage {
+    content: Vec<NovaContent>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NovaContent {
+    text: Option<String>,
+    tool_use: Option<NovaToolUse>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NovaToolUse {
+    tool_use_id: String,
+    name: String,
+    input: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NovaUsage {
+    input_tokens: u32,
+    output_tokens: u32,
+    cache_read_input_token_count: Option<u32>,
+    cache_write_input_token_count: Option<u32>,
+}
+
+pub fn batch_output_to_batch_results(
+    family: BatchModelFamily,
+    content: &str,
+) -> Result<Vec<batch::BatchResult>, llm::Error> {
+    let mut results = Vec::new();
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        let record: BatchOutputRecord = serde_json::from_str(line).map_err(|err| llm::Error {
+            code: llm::ErrorCode::InternalError,
+            message: format!("Failed to decode batch result: {err}"),
+            provider_error_json: Some(line.to_string()),
+        })?;
+
+        let event = match (record.model_output, record.error) {
+            (_, Some(error)) => llm::ChatEvent::Error(llm::Error {
+                code: match error.error_code {
+                    Some(400..=499) => llm::ErrorCode::InvalidRequest,
+                    _ => llm::ErrorCode::InternalError,
+                },
+                message: error
+                    .error_message
+                    .clone()
+                    .unwrap_or("Batch record failed".to_string()),
+                provider_error_json: serde_json::to_string(&error).ok(),
+            }),
+            (Some(model_output), None) => model_output_to_chat_event(family, model_output)
+                .unwrap_or_else(llm::ChatEvent::Error),
+            (None, None) => llm::ChatEvent::Error(custom_error(
+                llm::ErrorCode::InternalError,
+                "Batch record has neither an output nor an error".to_string(),
+            )),
+        };
+
+        results.push(batch::BatchResult {
+            custom_id: record.record_id,
+            event,
+        });
+    }
+    Ok(results)
+}
+
+fn model_output_to_chat_event(
+    family: BatchModelFamily,
+    model_output: serde_json::Value,
+) -> Result<llm::ChatEvent, llm::Error> {
+    let decode_error = |err: serde_json::Error| {
+        custom_error(
+            llm::ErrorCode::InternalError,
+            format!("Failed to decode batch model output: {err}"),
+        )
+    };
+
+    let mut content = vec![];
+    let mut tool_calls = vec![];
+    let (id, stop_reason, usage) = match family {
+        BatchModelFamily::Anthropic => {
+            let output: AnthropicModelOutput =
+                serde_json::from_value(model_output).map_err(decode_error)?;
+            for block in output.content {
+                match block {
+                    AnthropicContent::Text { text } => content.push(llm::ContentPart::Text(text)),
+                    AnthropicContent::ToolUse { id, name, input } => {
+                        tool_calls.push(llm::ToolCall {
+                            id,
+                            name,
+                            arguments_json: input.to_string(),
+                        })
+                    }
+                    AnthropicContent::Other => {}
+                }
+            }
+            let usage = output.usage.map(|usage| {
+                let cache_read_input_tokens = usage.cache_read_input_tokens.unwrap_or(0);
+                let input_tokens = usage.input_tokens
+                    + cache_read_input_tokens
+                    + usage.cache_creation_input_tokens.unwrap_or(0);
+                llm::Usage {
+                    input_tokens: Some(input_tokens),
+                    output_tokens: Some(usage.output_tokens),
+                    total_tokens: Some(input_tokens + usage.output_tokens),
+                    cached_input_tokens: usage.cache_read_input_tokens,
+                    reasoning_tokens: None,
+                }
+            });
+            (output.id, output.stop_reason, usage)
+        }
+        BatchModelFamily::Nova => {
+            let output: NovaModelOutput =
+                serde_json::from_value(model_output).map_err(decode_error)?;
+            for block in output.output.message.content {
+                if let Some(text) = block.text {
+                    content.push(llm::ContentPart::Text(text));
+                }
+                if let Some(tool_use) = block.tool_use {
+                    tool_calls.push(llm::ToolCall {
+                        id: tool_use.tool_use_id,
+                        name: tool_use.name,
+                        arguments_json: tool_use.input.to_string(),
+                    });
+                }
+            }
+            let usage = output.usage.map(|usage| {
+                let input_tokens = usage.input_tokens
+                    + usage.cache_read_input_token_count.unwrap_or(0)
+                    + usage.cache_write_input_token_count.unwrap_or(0);
+                llm::Usage {
+                    input_tokens: Some(input_tokens),
+                    output_tokens: Some(usage.output_tokens),
+                    total_tokens: Some(input_tokens + usage.output_tokens),
+                    cached_input_tokens: usage.cache_read_input_token_count,
+                    reasoning_tokens: None,
+                }
+            });
+            (None, output.stop_reason, usage)
+        }
+    };
+
+    if stop_reason.as_deref() == Some("tool_use") {
+        return Ok(llm::ChatEvent::ToolRequest(tool_calls));
+    }
+
+    Ok(llm::ChatEvent::Message(llm::CompleteResponse {
+        id: id.clone().unwrap_or_default(),
+        content,
+        tool_calls,
+        metadata: llm::ResponseMetadata {
+            finish_reason: stop_reason
+                .as_deref()
+                .map(native_stop_reason_to_finish_reason),
+            usage,
+            provider_id: id,
+            timestamp: None,
+            provider_metadata_json: None,
+            cost: None,
+        },
+    }))
+}
+
+fn native_stop_reason_to_finish_reason(stop_reason: &str) -> llm::FinishReason {
+    match stop_reason {
+        "end_turn" | "stop_sequence" => llm::FinishReason::Stop,
+        "max_tokens" => llm::FinishReason::Length,
+        "tool_use" => llm::FinishReason::ToolCalls,
+        "content_filtered" | "guardrail_intervened" => llm::FinishReason::ContentFilter,
+        _ => llm::FinishReason::Other,
+    }
+}
+
+pub fn from_batch_sdk_error(what: &str, sdk_error: impl std::fmt::Debug) -> llm::Error {
+    llm::Error {
+        code: llm::ErrorCode::InternalError,
+        message: format!("Error {what}: {sdk_error:?}"),
+        provider_error_json: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn s3_locations_are_parsed_and_joined() {
+        let location = S3Location::parse("s3://bucket/batches/").unwrap();
+        assert_eq!(location.bucket, "bucket");
+        assert_eq!(location.prefix, "batches");
+
+        let job = location.join("job");
+        assert_eq!(job.key("input.jsonl"), "batches/job/input.jsonl");
+        assert_eq!(job.uri(), "s3://bucket/batches/job");
+
+        let root = S3Location::parse("s3://bucket").unwrap();
+        assert_eq!(root.key("input.jsonl"), "input.jsonl");
+
+        assert!(S3Location::parse("https://bucket/batches").is_err());
+        assert!(S3Location::parse("s3:///batches").is_err());
+    }
+
+    fn provider_options(options: &[(&str, &str)]) -> HashMap<String, String> {
+        options
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn converse_options_are_parsed() {
+        let mut options = provider_options(&[
+            ("guardrail_id", "gr-123"),
+            ("guardrail_trace", "enabled_full"),
+            ("guardrail_stream_mode", "async"),
+            ("performance_latency", "optimized"),
+            ("top_p", "0.9"),
+            ("custom_field", "value"),
+        ]);
+        let converse_options = ConverseOptions::take(&mut options).unwrap();
+
+        let guardrail = converse_options.guardrail.unwrap();
+        assert_eq!(guardrail.identifier, "gr-123");
+        assert_eq!(guardrail.version, "DRAFT");
+        assert_eq!(guardrail.trace, GuardrailTrace::EnabledFull);
+        assert_eq!(
+            guardrail.stream_processing_mode,
+            Some(GuardrailStreamProcessingMode::Async)
+        );
+        assert_eq!(
+            converse_options.performance_latency,
+            Some(PerformanceConfigLatency::Optimized)
+        );
+        assert_eq!(converse_options.top_p, Some(0.9));
+        // The remaining options are sent to the model as additional request fields
+        assert_eq!(
+            options,
+            provider_options(&[("top_p", "0.9"), ("custom_field", "value")])
+        );
+    }
+
+    #[test]
+    fn invalid_converse_options_are_rejected() {
+        for options in [
+            vec![("guardrail_id", "gr-123"), ("guardrail_trace", "full")],
+            vec![
+                ("guardrail_id", "gr-123"),
+                ("guardrail_stream_mode", "batch"),
+            ],
+            vec![("guardrail_trace", "enabled")],
+            vec![("performance_latency", "fast")],
+            vec![("cross_region", "US")],
+            vec![("cross_region", "")],
+            vec![("cross_region", "us"), ("inference_profile", "my-profile")],
+            vec![("top_p", "high")],
+        ] {
+            let error = ConverseOptions::take(&mut provider_options(&options)).unwrap_err();
+            assert_eq!(error.code, llm::ErrorCode::InvalidRequest, "{options:?}");
+        }
+    }
+
+    #[test]
+    fn cross_region_prefixes_the_model_id() {
+        let options = |options: &[(&str, &str)]| {
+            ConverseOptions::take(&mut provider_options(options)).unwrap()
+        };
+        let model = "anthropic.claude-3-5-sonnet-20240620-v1:0".to_string();
+
+        assert_eq!(
+            options(&[("cross_region", "eu")]).model_id(model.clone()),
+            "eu.anthropic.claude-3-5-sonnet-20240620-v1:0"
+        );
+        assert_eq!(
+            options(&[("cross_region", "us-gov")]).model_id(model.clone()),
+            "us-gov.anthropic.claude-3-5-sonnet-20240620-v1:0"
+        );
+        assert_eq!(
+            options(&[("cross_region", "eu")])
+                .model_id("eu.anthropic.claude-3-5-sonnet-20240620-v1:0".to_string()),
+            "eu.anthropic.claude-3-5-sonnet-20240620-v1:0"
+        );
+        let arn = "arn:aws:bedrock:eu-west-1::foundation-model/amazon.nova-pro-v1:0";
+        assert_eq!(
+            options(&[("cross_region", "eu")]).model_id(arn.to_string()),
+            arn
+        );
+        assert_eq!(
+            options(&[("inference_profile", "my-profile")]).model_id(model.clone()),
+            "my-profile"
+        );
+        assert_eq!(options(&[]).model_id(model.clone()), model);
+    }
+
+    #[test]
+    fn guardrail_trace_is_merged_into_the_metadata() {
+        let trace = GuardrailTraceAssessment::builder()
+            .action_reason("Guardrail blocked")
+            .build();
+        let trace_json = guardrail_trace_to_metadata_json(&trace);
+        let trace_value = serde_json::from_str::<serde_json::Value>(&trace_json).unwrap();
+        assert_eq!(
+            trace_value["guardrail"]["action_reason"],
+            "Guardrail blocked"
+        );
+        assert_eq!(
+            trace_value["guardrail"]["input_assessment"],
+            serde_json::Value::Null
+        );
+
+        let merged = merge_metadata_json(
+            Some(r#"{"stop_sequence": "END"}"#.to_string()),
+            Some(trace_json.clone()),
+        )
+        .unwrap();
+        let merged = serde_json::from_str::<serde_json::Value>(&merged).unwrap();
+        assert_eq!(merged["stop_sequence"], "END");
+        assert_eq!(merged["guardrail"]["action_reason"], "Guardrail blocked");
+
+        let nested =
+            merge_metadata_json(Some("[1, 2]".to_string()), Some(trace_json.clone())).unwrap();
+        let nested = serde_json::from_str::<serde_json::Value>(&nested).unwrap();
+        assert_eq!(nested["additional_fields"], serde_json::json!([1, 2]));
+        assert_eq!(nested["guardrail"]["action_reason"], "Guardrail blocked");
+
+        assert_eq!(
+            merge_metadata_json(None, Some(trace_json.clone())),
+            Some(trace_json)
+        );
+        assert_eq!(merge_metadata_json(None, None), None);
+    }
+
+    #[test]
+    fn top_k_is_only_supported_by_some_models() {
+        assert!(supports_top_k("anthropic.claude-3-5-sonnet-20240620-v1:0"));
+        assert!(supports_top_k("eu.mistral.pixtral-large-2502-v1:0"));
+        assert!(supports_top_k("cohere.command-r-plus-v1:0"));
+        assert!(!supports_top_k("amazon.nova-pro-v1:0"));
+        assert!(!supports_top_k("meta.llama3-70b-instruct-v1:0"));
+    }
+
+    #[test]
+    fn batch_model_family_is_detected_from_the_model_id() {
+        assert_eq!(
+            BatchModelFamily::of("us.anthropic.claude-3-5-haiku-20241022-v1:0").unwrap(),
+            BatchModelFamily::Anthropic
+        );
+        assert_eq!(
+            BatchModelFamily::of("amazon.nova-lite-v1:0").unwrap(),
+            BatchModelFamily::Nova
+        );
+        assert_eq!(
+            BatchModelFamily::of("meta.llama3-70b-instruct-v1:0")
+                .unwrap_err()
+                .code,
+            llm::ErrorCode::Unsupported
+        );
+    }
+
+    #[test]
+    fn anthropic_batch_output_is_converted() {
+        let content = r#"{"recordId":"a","modelInput":{},"modelOutput":{"id":"msg_1","type":"message","role":"assistant","content":[{"type":"text","text":"Hello"}],"stop_reason":"end_turn","usage":{"input_tokens":10,"output_tokens":2,"cache_read_input_tokens":4}}}
+{"recordId":"b","modelInput":{},"modelOutput":{"id":"msg_2","content":[{"type":"tool_use","id":"t1","name":"lookup","input":{"q":"x"}}],"stop_reason":"tool_use"}}
+{"recordId":"c","modelInput":{},"error":{"errorCode":400,"errorMessage":"Malformed input"}}"#;
+
+        let results = batch_output_to_batch_results(BatchModelFamily::Anthropic, content).unwrap();
+        assert_eq!(results.len(), 3);
+
+        assert_eq!(results[0].custom_id, "a");
+        let llm::ChatEvent::Message(response) = &results[0].event else {
+            panic!("expected a message, got {:?}", results[0].event);
+        };
+        assert_eq!(response.id, "msg_1");
+        assert_eq!(
+            response.content,
+            vec![llm::ContentPart::Text("Hello".to_string())]
+        );
+        assert_eq!(
+            response.metadata.finish_reason,
+            Some(llm::FinishReason::Stop)
+        );
+        let usage = response.metadata.usage.clone().unwrap();
+        assert_eq!(usage.input_tokens, Some(14));
+        assert_eq!(usage.cached_input_tokens, Some(4));
+
+        let llm::ChatEvent::ToolRequest(tool_calls) = &results[1].event else {
+            panic!("expected a tool request, got {:?}", results[1].event);
+        };
+        assert_eq!(tool_calls[0].name, "lookup");
+        assert_eq!(tool_calls[0].arguments_json, r#"{"q":"x"}"#);
+
+        let llm::ChatEvent::Error(error) = &results[2].event else {
+            panic!("expected an error, got {:?}", results[2].event);
+        };
+        assert_eq!(error.code, llm::ErrorCode::InvalidRequest);
+        assert_eq!(error.message, "Malformed input");
+    }
+
+    #[test]
+    fn nova_batch_output_is_converted() {
+        let content = r#"{"recordId":"a","modelInput":{},"modelOutput":{"output":{"message":{"role":"assistant","content":[{"text":"Hi"}]}},"stopReason":"max_tokens","usage":{"inputTokens":5,"outputTokens":7,"totalTokens":12}}}"#;
+
+        let results = batch_output_to_batch_results(BatchModelFamily::Nova, content).unwrap();
+        let llm::ChatEvent::Message(response) = &results[0].event else {
+            panic!("expected a message, got {:?}", results[0].event);
+        };
+        assert_eq!(
+            response.content,
+            vec![llm::ContentPart::Text("Hi".to_string())]
+        );
+        assert_eq!(
+            response.metadata.finish_reason,
+            Some(llm::FinishReason::Length)
+        );
+        assert_eq!(
+            response.metadata.usage.clone().unwrap().total_tokens,
+            Some(12)
+        );
+    }
+
+    #[test]
+    fn invalid_batch_output_lines_fail() {
+        let error = batch_output_to_batch_results(BatchModelFamily::Nova, "not json").unwrap_err();
+        assert_eq!(error.code, llm::ErrorCode::InternalError);
+    }
+}
@@ -1,8 +1,15 @@
 use async_utils::get_async_runtime;
 use client::Bedrock;
 use golem_llm::{
-    durability::{DurableLLM, ExtendedGuest},
-    golem::llm::llm::{self, ChatEvent, ChatStream, Config, Guest, Message, ToolCall, ToolResult},
+    batch_stream::LlmBatchResultStream,
+    durability::{DurableLLM, ExtendedBatchGuest, ExtendedGuest},
+    golem::llm::batch::{BatchInfo, BatchRequest, BatchResultStream, Guest as BatchGuest},
+    golem::llm::images::{Guest as ImagesGuest, ImageConfig, ImageResponse},
+    golem::llm::llm::{
+        self, ChatEvent, ChatStream, Config, Guest, ImageSource, Kv, Message, ToolCall, ToolResult,
+    },
+    golem::llm::moderation::{Guest as ModerationGuest, ModerationConfig, ModerationResponse},
+    guardrails::moderate_with_local_policy,
 };
 use golem_rust::bindings::wasi::clocks::monotonic_clock;
 use stream::BedrockChatStream;
@@ -53,7 +60,122 @@ impl Guest for BedrockComponent {
     }
 }
 
+impl ImagesGuest for BedrockComponent {
+    fn generate(prompt: String, config: ImageConfig) -> Result<ImageResponse, llm::Error> {
+        let runtime = get_async_runtime();
+
+        runtime.block_on(async {
+            let client = get_bedrock_client().await?;
+            let model_id = conversions::image_model_id(&config);
+            let request = conversions::text_to_image_request(prompt, config)?;
+            client.invoke_image_model(model_id, request).await
+        })
+    }
+
+    fn edit(
+        image: ImageSource,
+        mask: Option<ImageSource>,
+        prompt: String,
+        config: ImageConfig,
+    ) -> Result<ImageResponse, llm::Error> {
+        let runtime = get_async_runtime();
+
+        runtime.block_on(async {
+            let client = get_bedrock_client().await?;
+            let model_id = conversions::image_model_id(&config);
+            let request = conversions::inpainting_request(image, mask, prompt, config)?;
+            client.invoke_image_model(model_id, request).await
+        })
+    }
+
+    fn create_variations(
+        image: ImageSource,
+        config: ImageConfig,
+    ) -> Result<ImageResponse, llm::Error> {
+        let runtime = get_async_runtime();
+
+        runtime.block_on(async {
+            let client = get_bedrock_client().await?;
+            let model_id = conversions::image_model_id(&config);
+            let request = conversions::image_variation_request(image, config)?;
+            client.invoke_image_model(model_id, request).await
+        })
+    }
+}
+
+impl ModerationGuest for BedrockComponent {
+    fn moderate(
+        inputs: Vec<String>,
+        config: ModerationConfig,
+    ) -> Result<ModerationResponse, llm::Error> {
+        // Without a guardrail identifier, the local policy is used
+        let Some(guardrail_id) = config.model.clone() else {
+            return moderate_with_local_policy(inputs);
+        };
+
+        let runtime = get_async_runtime();
+
+        runtime.block_on(async {
+            let client = get_bedrock_client().await?;
+            let request = conversions::guardrail_request(guardrail_id.clone(), &config)?;
+
+            let mut results = Vec::new();
+            let mut outputs = Vec::new();
+            for input in inputs {
+                let (result, output) = client.apply_guardrail(&request, input).await?;
+                results.push(result);
+                outputs.push(output);
+            }
+
+            Ok(ModerationResponse {
+                results,
+                model: Some(guardrail_id),
+                provider_id: None,
+                provider_metadata_json: Some(serde_json::json!({ "outputs": outputs }).to_string()),
+            })
+        })
+    }
+}
+
+impl BatchGuest for BedrockComponent {
+    type BatchResultStream = LlmBatchResultStream;
+
+    fn submit(requests: Vec<BatchRequest>) -> Result<String, llm::Error> {
+        let runtime = get_async_runtime();
+
+        runtime.block_on(async {
+            let client = get_bedrock_client().await?;
+            let input = conversions::batch_requests_to_batch_input(requests).await?;
+            client.create_batch_job(input).await
+        })
+    }
+
+    fn poll(job_id: String, _credentials: Option<Vec<Kv>>) -> Result<BatchInfo, llm::Error> {
+        let runtime = get_async_runtime();
+
+        runtime.block_on(async {
+            let client = get_bedrock_client().await?;
+            client.get_batch_job(&job_id).await
+        })
+    }
+
+    fn cancel(job_id: String, _credentials: Option<Vec<Kv>>) -> Result<String, llm::Error> {
+        let runtime = get_async_runtime();
+
+        runtime.block_on(async {
+            let client = get_bedrock_client().await?;
+            client.stop_batch_job(&job_id).await
+        })
+    }
+
+    fn results(job_id: String, _credentials: Option<Vec<Kv>>) -> BatchResultStream {
+        BatchResultStream::new(Self::unwrapped_batch_results(job_id))
+    }
+}
+
 impl ExtendedGuest for BedrockComponent {
+    const PROVIDER: &'static str = "bedrock";
+
     fn unwrapped_stream(
         messages: Vec<golem_llm::golem::llm::llm::Message>,
         config: golem_llm::golem::llm::llm::Config,
@@ -128,6 +250,21 @@ impl ExtendedGuest for BedrockComponent {
     }
 }
 
+impl ExtendedBatchGuest for BedrockComponent {
+    fn unwrapped_batch_results(job_id: String) -> Self::BatchResultStream {
+        // The client is created eagerly, so it uses the credentials active when the stream is opened
+        let runtime = get_async_runtime();
+        match runtime.block_on(get_bedrock_client()) {
+            Ok(client) => LlmBatchResultStream::new(move || {
+                let runtime = get_async_runtime();
+
+                runtime.block_on(client.batch_results(&job_id))
+            }),
+            Err(error) => LlmBatchResultStream::failed(error),
+        }
+    }
+}
+
 async fn get_bedrock_client() -> Result<Bedrock, llm::Error> {
     Bedrock::new().await
 }
@@ -135,3 +272,6 @@ async fn get_bedrock_client() -> Result<Bedrock, llm::Error> {
 type DurableBedrockComponent = DurableLLM<BedrockComponent>;
 
 golem_llm::export_llm!(DurableBedrockComponent with_types_in golem_llm);
+golem_llm::export_images!(DurableBedrockComponent with_types_in golem_llm::images_library);
+golem_llm::export_moderation!(DurableBedrockComponent with_types_in golem_llm::moderation_library);
+golem_llm::export_batch!(DurableBedrockComponent with_types_in golem_llm::batch_library);
@@ -0,0 +1,172 @@
+use crate::golem::llm::llm::{ContentPart, Message, Role};
+use serde::Deserialize;
+use std::collections::HashSet;
+
+/// A single conversation of the dataset, with the graders scoring its response
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Case {
+    pub id: String,
+    pub messages: Vec<CaseMessage>,
+    /// The expected response, used by the `exact_match` grader
+    #[serde(default)]
+    pub expected: Option<String>,
+    /// Instructions for the `llm_judge` grader
+    #[serde(default)]
+    pub rubric: Option<String>,
+    /// When empty, the response is compared to `expected` and judged by `rubric`
+    #[serde(default)]
+    pub graders: Vec<Grader>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CaseMessage {
+    pub role: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Grader {
+    /// Compares the trimmed response to the expected one, ignoring case unless `case_sensitive`
+    ExactMatch {
+        #[serde(default)]
+        expected: Option<String>,
+        #[serde(default)]
+        case_sensitive: bool,
+    },
+    /// Checks that the response matches a regular expression
+    Regex { pattern: String },
+    /// Checks that the response is JSON matching a JSON schema
+    JsonSchema { schema: serde_json::Value },
+    /// Asks the judge model to score the response according to a rubric
+    LlmJudge {
+        #[serde(default)]
+        rubric: Option<String>,
+        #[serde(default = "default_threshold")]
+        threshold: f64,
+    },
+}
+
+fn default_threshold() -> f64 {
+    0.5
+}
+
+impl Grader {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Grader::ExactMatch { .. } => "exact_match",
+            Grader::Regex { .. } => "regex",
+            Grader::JsonSchema { .. } => "json_schema",
+            Grader::LlmJudge { .. } => "llm_judge",
+        }
+    }
+}
+
+impl Case {
+    /// The graders of the case, or the ones implied by `expected` and `rubric`
+    pub fn graders(&self) -> Vec<Grader> {
+        if !self.graders.is_empty() {
+            return self.graders.clone();
+        }
+        let mut graders = Vec::new();
+        if self.expected.is_some() {
+            graders.push(Grader::ExactMatch {
+                expected: None,
+                case_sensitive: false,
+            });
+        }
+        if self.rubric.is_some() {
+            graders.push(Grader::LlmJudge {
+                rubric: None,
+                threshold: default_threshold(),
+            });
+        }
+        graders
+    }
+
+    pub fn to_messages(&self) -> Vec<Message> {
+        self.messages
+            .iter()
+            .map(|message| Message {
+                role: match message.role.as_str() {
+                    "system" => Role::System,
+                    "assistant" => Role::Assistant,
+                    _ => Role::User,
+                },
+                name: None,
+                content: vec![ContentPart::Text(message.content.clone())],
+            })
+            .collect()
+    }
+
+    /// The text of the last user message, used to match scripted responses
+    pub fn last_user_message(&self) -> &str {
+        self.messages
+            .iter()
+            .rev()
+            .find(|message| message.role == "user")
+            .map(|message| message.content.as_str())
+            .unwrap_or_default()
+    }
+}
+
+/// Parses a JSONL dataset, with one case per line
+pub fn parse_dataset(dataset: &str) -> Result<Vec<Case>, String> {
+    let mut cases = Vec::new();
+    let mut ids = HashSet::new();
+    for (index, line) in dataset.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let case: Case = serde_json::from_str(line)
+            .map_err(|err| format!("Invalid case on line {}: {err}", index + 1))?;
+        if case.messages.is_empty() {
+            return Err(format!("Case {} has no messages", case.id));
+        }
+        if let Some(message) = case
+            .messages
+            .iter()
+            .find(|message| !matches!(message.role.as_str(), "system" | "user" | "assistant"))
+        {
+            return Err(format!(
+                "Case {} has a message with an unsupported role: {}",
+                case.id, message.role
+            ));
+        }
+        if case.graders().is_empty() {
+            return Err(format!(
+                "Case {} has no graders, expected or rubric",
+                case.id
+            ));
+        }
+        for grader in case.graders() {
+            match grader {
+                Grader::ExactMatch { expected: None, .. } if case.expected.is_none() => {
+                    return Err(format!(
+                        "Case {} has an exact_match grader without an expected response",
+                        case.id
+                    ))
+                }
+                Grader::LlmJudge { rubric: None, .. } if case.rubric.is_none() => {
+                    return Err(format!(
+                        "Case {} has an llm_judge grader without a rubric",
+                        case.id
+                    ))
+                }
+                Grader::Regex { pattern } => {
+                    regex::Regex::new(&pattern)
+                        .map_err(|err| format!("Case {} has an invalid regex: {err}", case.id))?;
+                }
+                _ => {}
+            }
+        }
+        if !ids.insert(case.id.clone()) {
+            return Err(format!("Duplicate case {}", case.id));
+        }
+        cases.push(case);
+    }
+    if cases.is_empty() {
+        return Err("The dataset has no cases".to_string());
+    }
+    Ok(cases)
+}
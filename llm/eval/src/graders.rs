@@ -0,0 +1,222 @@
+use crate::dataset::{Case, Grader};
+use crate::exports::golem::llm_eval::evaluation::GraderScore;
+use crate::golem::llm::llm::{ContentPart, Message, Role};
+use regex::Regex;
+use serde_json::Value;
+
+/// Sends a prompt to the judge model, and returns the text of its response
+pub type Judge<'a> = dyn FnMut(Vec<Message>) -> Result<String, String> + 'a;
+
+const JUDGE_INSTRUCTIONS: &str = "You are grading the response of an AI assistant to a conversation, according to a rubric. \
+Reply only with a JSON object of the form {\"score\": <number between 0 and 1>, \"reason\": \"<short explanation>\"}.";
+
+/// Scores a response with a grader
+pub fn grade(grader: &Grader, case: &Case, output: &str, judge: Option<&mut Judge>) -> GraderScore {
+    let result = match grader {
+        Grader::ExactMatch {
+            expected,
+            case_sensitive,
+        } => {
+            let expected = expected
+                .as_deref()
+                .or(case.expected.as_deref())
+                .unwrap_or_default()
+                .trim();
+            let output = output.trim();
+            let matches = if *case_sensitive {
+                output == expected
+            } else {
+                output.to_lowercase() == expected.to_lowercase()
+            };
+            if matches {
+                Ok((1.0, None))
+            } else {
+                Ok((0.0, Some(format!("expected {expected:?}"))))
+            }
+        }
+        Grader::Regex { pattern } => Regex::new(pattern)
+            .map_err(|err| format!("invalid regex: {err}"))
+            .map(|regex| {
+                if regex.is_match(output) {
+                    (1.0, None)
+                } else {
+                    (0.0, Some(format!("does not match {pattern}")))
+                }
+            }),
+        Grader::JsonSchema { schema } => {
+            match serde_json::from_str::<Value>(strip_code_fence(output)) {
+                Ok(value) => {
+                    let errors = schema_errors(schema, &value, "$");
+                    if errors.is_empty() {
+                        Ok((1.0, None))
+                    } else {
+                        Ok((0.0, Some(errors.join("; "))))
+                    }
+                }
+                Err(err) => Ok((0.0, Some(format!("not valid JSON: {err}")))),
+            }
+        }
+        Grader::LlmJudge { rubric, .. } => {
+            let rubric = rubric
+                .as_deref()
+                .or(case.rubric.as_deref())
+                .unwrap_or_default();
+            match judge {
+                Some(judge) => judge(judge_prompt(case, rubric, output))
+                    .and_then(|response| parse_judgement(&response)),
+                None => Err("no judge target is configured".to_string()),
+            }
+        }
+    };
+
+    let threshold = match grader {
+        Grader::LlmJudge { threshold, .. } => *threshold,
+        _ => 1.0,
+    };
+    match result {
+        Ok((score, reason)) => GraderScore {
+            grader: grader.name().to_string(),
+            score,
+            passed: score >= threshold,
+            reason,
+        },
+        Err(reason) => GraderScore {
+            grader: grader.name().to_string(),
+            score: 0.0,
+            passed: false,
+            reason: Some(reason),
+        },
+    }
+}
+
+fn judge_prompt(case: &Case, rubric: &str, output: &str) -> Vec<Message> {
+    let conversation = case
+        .messages
+        .iter()
+        .map(|message| format!("{}: {}", message.role, message.content))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut prompt = format!("Conversation:\n{conversation}\n\nRubric:\n{rubric}\n");
+    if let Some(expected) = &case.expected {
+        prompt.push_str(&format!("\nExpected response:\n{expected}\n"));
+    }
+    prompt.push_str(&format!("\nResponse to grade:\n{output}"));
+
+    vec![
+        Message {
+            role: Role::System,
+            name: None,
+            content: vec![ContentPart::Text(JUDGE_INSTRUCTIONS.to_string())],
+        },
+        Message {
+            role: Role::User,
+            name: None,
+            content: vec![ContentPart::Text(prompt)],
+        },
+    ]
+}
+
+/// Parses the JSON object in the judge's response, ignoring any text around it
+fn parse_judgement(response: &str) -> Result<(f64, Option<String>), String> {
+    let json = match (response.find('{'), response.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &response[start..=end],
+        _ => return Err(format!("the judge did not return JSON: {response}")),
+    };
+    let judgement: Value = serde_json::from_str(json)
+        .map_err(|err| format!("the judge returned invalid JSON: {err}"))?;
+    let score = judgement
+        .get("score")
+        .and_then(Value::as_f64)
+        .ok_or_else(|| format!("the judge did not return a score: {json}"))?;
+    let reason = judgement
+        .get("reason")
+        .and_then(Value::as_str)
+        .map(|reason| reason.to_string());
+    Ok((score.clamp(0.0, 1.0), reason))
+}
+
+fn strip_code_fence(output: &str) -> &str {
+    let output = output.trim();
+    match output.strip_prefix("```") {
+        Some(fenced) => {
+            let body = fenced.split_once('\n').map_or("", |(_, body)| body);
+            body.trim_end().strip_suffix("```").unwrap_or(body).trim()
+        }
+        None => output,
+    }
+}
+
+/// Validates a value against the commonly used subset of JSON Schema: `type`, `enum`,
+/// `properties`, `required`, `additionalProperties` and `items`
+fn schema_errors(schema: &Value, value: &Value, path: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+    let Some(schema) = schema.as_object() else {
+        return errors;
+    };
+
+    if let Some(expected) = schema.get("type").and_then(Value::as_str) {
+        let matches = match expected {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "number" => value.is_number(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "boolean" => value.is_boolean(),
+            "null" => value.is_null(),
+            _ => true,
+        };
+        if !matches {
+            errors.push(format!("{path}: expected {expected}"));
+            return errors;
+        }
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            errors.push(format!("{path}: {value} is not allowed"));
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            for required in schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+            {
+                if !object.contains_key(required) {
+                    errors.push(format!("{path}: missing required property {required}"));
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (key, property) in object {
+                match properties.and_then(|properties| properties.get(key)) {
+                    Some(property_schema) => errors.extend(schema_errors(
+                        property_schema,
+                        property,
+                        &format!("{path}.{key}"),
+                    )),
+                    None => {
+                        if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+                            errors.push(format!("{path}: unexpected property {key}"));
+                        }
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    errors.extend(schema_errors(
+                        item_schema,
+                        item,
+                        &format!("{path}[{index}]"),
+                    ));
+                }
+            }
+        }
+        _ => {}
+    }
+    errors
+}
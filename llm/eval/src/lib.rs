@@ -0,0 +1,199 @@
+mod dataset;
+mod graders;
+mod report;
+mod runner;
+
+use crate::dataset::parse_dataset;
+use crate::exports::golem::llm_eval::evaluation::{Guest, Report, Target};
+use crate::golem::llm::llm::{Error, ErrorCode};
+use crate::report::build_report;
+use crate::runner::{parse_script, run_cases, Backend};
+
+wit_bindgen::generate!({
+    path: "wit",
+    world: "evaluator",
+    generate_all,
+});
+
+struct Component;
+
+impl Guest for Component {
+    fn run(dataset: String, targets: Vec<Target>, judge: Option<Target>) -> Result<Report, Error> {
+        let cases = parse_dataset(&dataset).map_err(invalid_request)?;
+        if targets.is_empty() {
+            return Err(invalid_request("No targets to evaluate".to_string()));
+        }
+        let names = targets
+            .iter()
+            .map(|target| target.name.clone())
+            .collect::<Vec<_>>();
+        let targets = targets
+            .into_iter()
+            .map(runner_target)
+            .collect::<Result<Vec<_>, _>>()?;
+        let judge = judge.map(runner_target).transpose()?;
+
+        let results = run_cases(&cases, &targets, judge.as_ref());
+        Ok(build_report(&names, results))
+    }
+}
+
+fn runner_target(target: Target) -> Result<runner::Target, Error> {
+    let backend = match &target.script {
+        Some(script) => Backend::Scripted(
+            parse_script(script)
+                .map_err(|err| invalid_request(format!("Target {}: {err}", target.name)))?,
+        ),
+        None => Backend::Provider,
+    };
+    Ok(runner::Target {
+        name: target.name,
+        config: target.config,
+        backend,
+    })
+}
+
+fn invalid_request(message: String) -> Error {
+    Error {
+        code: ErrorCode::InvalidRequest,
+        message,
+        provider_error_json: None,
+    }
+}
+
+export!(Component);
+
+#[cfg(test)]
+mod tests {
+    use crate::dataset::parse_dataset;
+    use crate::golem::llm::llm::Config;
+    use crate::report::build_report;
+    use crate::runner::{parse_script, run_cases, Backend, Target};
+
+    fn config(model: &str) -> Config {
+        Config {
+            model: model.to_string(),
+            temperature: None,
+            max_tokens: None,
+            stop_sequences: None,
+            top_p: None,
+            top_k: None,
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            repetition_penalty: None,
+            tools: vec![],
+            tool_choice: None,
+            provider_options: vec![],
+            credentials: None,
+        }
+    }
+
+    fn scripted(name: &str, script: &str) -> Target {
+        Target {
+            name: name.to_string(),
+            config: config(name),
+            backend: Backend::Scripted(parse_script(script).unwrap()),
+        }
+    }
+
+    const DATASET: &str = r#"
+{"id": "capital", "messages": [{"role": "user", "content": "What is the capital of France?"}], "expected": "Paris"}
+{"id": "digits", "messages": [{"role": "user", "content": "Give me a number"}], "graders": [{"type": "regex", "pattern": "^\\d+$"}]}
+{"id": "person", "messages": [{"role": "user", "content": "Describe Ada as JSON"}], "graders": [{"type": "json_schema", "schema": {"type": "object", "required": ["name", "age"], "properties": {"name": {"type": "string"}, "age": {"type": "integer"}}}}]}
+{"id": "poem", "messages": [{"role": "user", "content": "Write a haiku"}], "rubric": "A haiku about nature"}
+"#;
+
+    #[test]
+    fn invalid_datasets_are_rejected() {
+        assert!(parse_dataset("").is_err());
+        assert!(
+            parse_dataset(r#"{"id": "a", "messages": [{"role": "user", "content": "hi"}]}"#)
+                .unwrap_err()
+                .contains("no graders")
+        );
+        assert!(parse_dataset(
+            r#"{"id": "a", "messages": [{"role": "tool", "content": "hi"}], "expected": "x"}"#
+        )
+        .unwrap_err()
+        .contains("unsupported role"));
+        assert!(parse_dataset(
+            "{\"id\": \"a\", \"messages\": [{\"role\": \"user\", \"content\": \"hi\"}], \"expected\": \"x\"}\n\
+             {\"id\": \"a\", \"messages\": [{\"role\": \"user\", \"content\": \"hi\"}], \"expected\": \"x\"}"
+        )
+        .unwrap_err()
+        .contains("Duplicate"));
+    }
+
+    #[test]
+    fn scripted_targets_are_graded_and_summarized() {
+        let cases = parse_dataset(DATASET).unwrap();
+        let good = scripted(
+            "good",
+            r#"
+{"case": "capital", "response": " paris ", "latency_ms": 100, "input_tokens": 10, "output_tokens": 2, "cost": 0.001}
+{"match": "number", "response": "42", "latency_ms": 200}
+{"case": "person", "response": "```json\n{\"name\": \"Ada\", \"age\": 36}\n```", "latency_ms": 300}
+{"match": "haiku", "response": "Leaves fall", "latency_ms": 400}
+"#,
+        );
+        let bad = scripted(
+            "bad",
+            r#"
+{"case": "capital", "response": "Lyon"}
+{"case": "digits", "error": "rate limited"}
+{"case": "person", "response": "{\"name\": \"Ada\", \"age\": \"old\"}"}
+{"case": "poem", "response": "Roses are red"}
+"#,
+        );
+        let judge = scripted(
+            "judge",
+            r#"
+{"match": "Leaves fall", "response": "{\"score\": 0.9, \"reason\": \"nice\"}"}
+{"match": "Roses", "response": "Sure: {\"score\": 0.2, \"reason\": \"not a haiku\"}"}
+"#,
+        );
+
+        let results = run_cases(&cases, &[good, bad], Some(&judge));
+        let report = build_report(&["good".to_string(), "bad".to_string()], results);
+
+        let good = &report.summaries[0];
+        assert_eq!((good.cases, good.passed, good.errors), (4, 4, 0));
+        assert_eq!(good.p95_latency_ms, 400);
+        assert_eq!(good.mean_latency_ms, 250.0);
+        assert_eq!((good.input_tokens, good.output_tokens), (10, 2));
+        assert_eq!(good.total_cost, 0.001);
+
+        let bad = &report.summaries[1];
+        assert_eq!((bad.cases, bad.passed, bad.errors), (4, 0, 1));
+        let poem = report
+            .results
+            .iter()
+            .find(|result| result.target == "bad" && result.case_id == "poem")
+            .unwrap();
+        assert_eq!(poem.scores[0].score, 0.2);
+        assert_eq!(poem.scores[0].reason.as_deref(), Some("not a haiku"));
+
+        assert!(report.markdown.contains("| good | 4/4 | 0 |"));
+        assert!(report
+            .markdown
+            .contains("| bad | digits | error: rate limited |"));
+        assert!(report.markdown.contains("$.age: expected integer"));
+        let json: serde_json::Value = serde_json::from_str(&report.json).unwrap();
+        assert_eq!(json["results"].as_array().unwrap().len(), 8);
+    }
+
+    #[test]
+    fn judge_graders_fail_without_a_judge() {
+        let cases = parse_dataset(DATASET).unwrap();
+        let target = scripted("target", r#"{"match": ".*", "response": "Leaves fall"}"#);
+
+        let results = run_cases(&cases[3..], &[target], None);
+
+        assert!(!results[0].passed);
+        assert_eq!(
+            results[0].scores[0].reason.as_deref(),
+            Some("no judge target is configured")
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use crate::exports::golem::llm_eval::evaluation::{CaseResult, Report, TargetSummary};
+use serde_json::json;
+
+/// Summarizes the results per target, and renders them as Markdown and JSON
+pub fn build_report(targets: &[String], results: Vec<CaseResult>) -> Report {
+    let summaries = targets
+        .iter()
+        .map(|target| {
+            let results = results
+                .iter()
+                .filter(|result| &result.target == target)
+                .collect::<Vec<_>>();
+            summarize(target, &results)
+        })
+        .collect::<Vec<_>>();
+    let markdown = markdown(&summaries, &results);
+    let json = json!({
+        "summaries": summaries.iter().map(summary_json).collect::<Vec<_>>(),
+        "results": results.iter().map(result_json).collect::<Vec<_>>(),
+    })
+    .to_string();
+    Report {
+        summaries,
+        results,
+        markdown,
+        json,
+    }
+}
+
+fn summarize(target: &str, results: &[&CaseResult]) -> TargetSummary {
+    let scores = results
+        .iter()
+        .flat_map(|result| result.scores.iter().map(|score| score.score))
+        .collect::<Vec<_>>();
+    let mut latencies = results
+        .iter()
+        .filter(|result| result.error.is_none())
+        .map(|result| result.latency_ms)
+        .collect::<Vec<_>>();
+    latencies.sort_unstable();
+
+    TargetSummary {
+        target: target.to_string(),
+        cases: results.len() as u32,
+        passed: results.iter().filter(|result| result.passed).count() as u32,
+        errors: results
+            .iter()
+            .filter(|result| result.error.is_some())
+            .count() as u32,
+        mean_score: mean(scores.iter().copied()),
+        mean_latency_ms: mean(latencies.iter().map(|latency| *latency as f64)),
+        p95_latency_ms: percentile(&latencies, 0.95),
+        input_tokens: results
+            .iter()
+            .filter_map(|result| result.input_tokens)
+            .map(u64::from)
+            .sum(),
+        output_tokens: results
+            .iter()
+            .filter_map(|result| result.output_tokens)
+            .map(u64::from)
+            .sum(),
+        total_cost: results.iter().filter_map(|result| result.cost).sum(),
+    }
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f64
+    }
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[u64], percentile: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (percentile * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn markdown(summaries: &[TargetSummary], results: &[CaseResult]) -> String {
+    let mut markdown = String::from(
+        "| Target | Passed | Errors | Mean score | Mean latency (ms) | p95 latency (ms) | Input tokens | Output tokens | Cost (USD) |\n\
+         |---|---|---|---|---|---|---|---|---|\n",
+    );
+    for summary in summaries {
+        markdown.push_str(&format!(
+            "| {} | {}/{} | {} | {:.2} | {:.0} | {} | {} | {} | {:.4} |\n",
+            escape(&summary.target),
+            summary.passed,
+            summary.cases,
+            summary.errors,
+            summary.mean_score,
+            summary.mean_latency_ms,
+            summary.p95_latency_ms,
+            summary.input_tokens,
+            summary.output_tokens,
+            summary.total_cost,
+        ));
+    }
+
+    let failed = results
+        .iter()
+        .filter(|result| !result.passed)
+        .collect::<Vec<_>>();
+    if !failed.is_empty() {
+        markdown.push_str("\n### Failed cases\n\n| Target | Case | Reason |\n|---|---|---|\n");
+        for result in failed {
+            let reason = match &result.error {
+                Some(error) => format!("error: {error}"),
+                None => result
+                    .scores
+                    .iter()
+                    .filter(|score| !score.passed)
+                    .map(|score| match &score.reason {
+                        Some(reason) => format!("{}: {reason}", score.grader),
+                        None => format!("{}: score {:.2}", score.grader, score.score),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            };
+            markdown.push_str(&format!(
+                "| {} | {} | {} |\n",
+                escape(&result.target),
+                escape(&result.case_id),
+                escape(&reason)
+            ));
+        }
+    }
+    markdown
+}
+
+fn escape(cell: &str) -> String {
+    cell.replace('|', "\\|").replace('\n', " ")
+}
+
+fn summary_json(summary: &TargetSummary) -> serde_json::Value {
+    json!({
+        "target": summary.target,
+        "cases": summary.cases,
+        "passed": summary.passed,
+        "errors": summary.errors,
+        "mean_score": summary.mean_score,
+        "mean_latency_ms": summary.mean_latency_ms,
+        "p95_latency_ms": summary.p95_latency_ms,
+        "input_tokens": summary.input_tokens,
+        "output_tokens": summary.output_tokens,
+        "total_cost": summary.total_cost,
+    })
+}
+
+fn result_json(result: &CaseResult) -> serde_json::Value {
+    json!({
+        "case_id": result.case_id,
+        "target": result.target,
+        "output": result.output,
+        "error": result.error,
+        "scores": result.scores.iter().map(|score| json!({
+            "grader": score.grader,
+            "score": score.score,
+            "passed": score.passed,
+            "reason": score.reason,
+        })).collect::<Vec<_>>(),
+        "passed": result.passed,
+        "latency_ms": result.latency_ms,
+        "input_tokens": result.input_tokens,
+        "output_tokens": result.output_tokens,
+        "cost": result.cost,
+    })
+}
@@ -0,0 +1,257 @@
+use crate::dataset::Case;
+use crate::exports::golem::llm_eval::evaluation::CaseResult;
+use crate::golem::llm::llm::{
+    send, ChatEvent, CompleteResponse, Config, ContentPart, Cost, FinishReason, Message,
+    ResponseMetadata, Usage,
+};
+use crate::graders::grade;
+use regex::Regex;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+/// Where the requests of a target are sent
+pub enum Backend {
+    /// The imported `golem:llm` provider
+    Provider,
+    /// Scripted responses, to run the evaluation offline
+    Scripted(Vec<ScriptedResponse>),
+}
+
+/// A scripted response, selected by the case identifier or by a regular expression matching
+/// the last user message. The first matching line of the script is used.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScriptedResponse {
+    #[serde(default)]
+    pub case: Option<String>,
+    #[serde(default, rename = "match", with = "serde_regex")]
+    pub pattern: Option<Regex>,
+    #[serde(default)]
+    pub response: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+    #[serde(default)]
+    pub input_tokens: Option<u32>,
+    #[serde(default)]
+    pub output_tokens: Option<u32>,
+    #[serde(default)]
+    pub cost: Option<f64>,
+}
+
+mod serde_regex {
+    use regex::Regex;
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Regex>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|pattern| Regex::new(&pattern).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+/// Parses a JSONL script of responses
+pub fn parse_script(script: &str) -> Result<Vec<ScriptedResponse>, String> {
+    script
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .map_err(|err| format!("Invalid scripted response on line {}: {err}", index + 1))
+        })
+        .collect()
+}
+
+/// A model to evaluate
+pub struct Target {
+    pub name: String,
+    pub config: Config,
+    pub backend: Backend,
+}
+
+impl Target {
+    /// Sends the messages, and returns the response with the measured latency. `case_id` and
+    /// `text` select the scripted response.
+    pub fn send(&self, case_id: &str, text: &str, messages: &[Message]) -> (ChatEvent, Duration) {
+        match &self.backend {
+            Backend::Provider => {
+                let start = Instant::now();
+                let event = send(messages, &self.config);
+                (event, start.elapsed())
+            }
+            Backend::Scripted(script) => {
+                let scripted = script.iter().find(|scripted| {
+                    scripted.case.as_deref() == Some(case_id)
+                        || scripted
+                            .pattern
+                            .as_ref()
+                            .is_some_and(|pattern| pattern.is_match(text))
+                });
+                match scripted {
+                    Some(scripted) => (
+                        scripted.to_event(&self.config),
+                        Duration::from_millis(scripted.latency_ms.unwrap_or_default()),
+                    ),
+                    None => (
+                        ChatEvent::Error(crate::golem::llm::llm::Error {
+                            code: crate::golem::llm::llm::ErrorCode::InvalidRequest,
+                            message: format!("No scripted response for case {case_id}"),
+                            provider_error_json: None,
+                        }),
+                        Duration::ZERO,
+                    ),
+                }
+            }
+        }
+    }
+}
+
+impl ScriptedResponse {
+    fn to_event(&self, config: &Config) -> ChatEvent {
+        if let Some(error) = &self.error {
+            return ChatEvent::Error(crate::golem::llm::llm::Error {
+                code: crate::golem::llm::llm::ErrorCode::InternalError,
+                message: error.clone(),
+                provider_error_json: None,
+            });
+        }
+        ChatEvent::Message(CompleteResponse {
+            id: "scripted".to_string(),
+            content: vec![ContentPart::Text(self.response.clone().unwrap_or_default())],
+            tool_calls: vec![],
+            metadata: ResponseMetadata {
+                finish_reason: Some(FinishReason::Stop),
+                usage: Some(Usage {
+                    input_tokens: self.input_tokens,
+                    output_tokens: self.output_tokens,
+                    total_tokens: self
+                        .input_tokens
+                        .zip(self.output_tokens)
+                        .map(|(input, output)| input + output),
+                    cached_input_tokens: None,
+                    reasoning_tokens: None,
+                }),
+                provider_id: Some(config.model.clone()),
+                timestamp: None,
+                provider_metadata_json: None,
+                cost: self.cost.map(|cost| Cost {
+                    input_cost: 0.0,
+                    output_cost: 0.0,
+                    cached_input_cost: 0.0,
+                    reasoning_cost: 0.0,
+                    total_cost: cost,
+                    currency: "USD".to_string(),
+                }),
+            },
+        })
+    }
+}
+
+/// Runs every case on every target, and grades the responses
+pub fn run_cases(cases: &[Case], targets: &[Target], judge: Option<&Target>) -> Vec<CaseResult> {
+    let mut results = Vec::new();
+    for target in targets {
+        for case in cases {
+            results.push(run_case(case, target, judge));
+        }
+    }
+    results
+}
+
+fn run_case(case: &Case, target: &Target, judge: Option<&Target>) -> CaseResult {
+    let (event, latency) = target.send(&case.id, case.last_user_message(), &case.to_messages());
+    let mut result = CaseResult {
+        case_id: case.id.clone(),
+        target: target.name.clone(),
+        output: None,
+        error: None,
+        scores: vec![],
+        passed: false,
+        latency_ms: latency.as_millis() as u64,
+        input_tokens: None,
+        output_tokens: None,
+        cost: None,
+    };
+
+    let output = match event {
+        ChatEvent::Message(response) => {
+            if let Some(usage) = &response.metadata.usage {
+                result.input_tokens = usage.input_tokens;
+                result.output_tokens = usage.output_tokens;
+            }
+            result.cost = response.metadata.cost.as_ref().map(|cost| cost.total_cost);
+            if response.tool_calls.is_empty() {
+                response_text(&response.content)
+            } else {
+                tool_calls_json(&response.tool_calls)
+            }
+        }
+        ChatEvent::ToolRequest(tool_calls) => tool_calls_json(&tool_calls),
+        ChatEvent::Error(error) => {
+            result.error = Some(error.message);
+            return result;
+        }
+    };
+
+    let mut judge_fn = judge.map(|judge| {
+        let case_id = case.id.clone();
+        move |messages: Vec<Message>| {
+            let text = match messages.last().and_then(|message| message.content.first()) {
+                Some(ContentPart::Text(text)) => text.clone(),
+                _ => String::new(),
+            };
+            match judge.send(&case_id, &text, &messages).0 {
+                ChatEvent::Message(response) => Ok(response_text(&response.content)),
+                ChatEvent::ToolRequest(_) => Err("the judge requested a tool call".to_string()),
+                ChatEvent::Error(error) => Err(format!("the judge failed: {}", error.message)),
+            }
+        }
+    });
+    result.scores = case
+        .graders()
+        .iter()
+        .map(|grader| {
+            grade(
+                grader,
+                case,
+                &output,
+                judge_fn
+                    .as_mut()
+                    .map(|judge| judge as &mut crate::graders::Judge),
+            )
+        })
+        .collect();
+    result.passed = result.scores.iter().all(|score| score.passed);
+    result.output = Some(output);
+    result
+}
+
+fn response_text(content: &[ContentPart]) -> String {
+    content
+        .iter()
+        .filter_map(|part| match part {
+            ContentPart::Text(text) => Some(text.as_str()),
+            ContentPart::Image(_) => None,
+        })
+        .collect::<Vec<_>>()
+        .join("")
+}
+
+fn tool_calls_json(tool_calls: &[crate::golem::llm::llm::ToolCall]) -> String {
+    serde_json::Value::Array(
+        tool_calls
+            .iter()
+            .map(|tool_call| {
+                serde_json::json!({
+                    "name": tool_call.name,
+                    "arguments": serde_json::from_str::<serde_json::Value>(&tool_call.arguments_json)
+                        .unwrap_or_else(|_| serde_json::Value::String(tool_call.arguments_json.clone())),
+                })
+            })
+            .collect(),
+    )
+    .to_string()
+}
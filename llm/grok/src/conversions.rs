@@ -1,5 +1,6 @@
 use crate::client::{CompletionsRequest, CompletionsResponse, Detail, Effort};
 use base64::{engine::general_purpose, Engine as _};
+use golem_llm::config::{reject_unsupported_parameters, SamplingParameter};
 use golem_llm::golem::llm::llm::{
     ChatEvent, CompleteResponse, Config, ContentPart, Error, ErrorCode, FinishReason, ImageDetail,
     ImageReference, Message, ResponseMetadata, Role, ToolCall, ToolDefinition, ToolResult, Usage,
@@ -10,6 +11,15 @@ pub fn messages_to_request(
     messages: Vec<Message>,
     config: Config,
 ) -> Result<CompletionsRequest, Error> {
+    reject_unsupported_parameters(
+        "Grok",
+        &config,
+        &[
+            SamplingParameter::TopK,
+            SamplingParameter::RepetitionPenalty,
+        ],
+    )?;
+
     let options = config
         .provider_options
         .into_iter()
@@ -48,20 +58,26 @@ pub fn messages_to_request(
     Ok(CompletionsRequest {
         messages: completion_messages,
         model: config.model,
-        frequency_penalty: options
-            .get("frequency_penalty")
-            .and_then(|fp_s| fp_s.parse::<f32>().ok()),
+        frequency_penalty: config.frequency_penalty.or_else(|| {
+            options
+                .get("frequency_penalty")
+                .and_then(|fp_s| fp_s.parse::<f32>().ok())
+        }),
         max_completion_tokens: config.max_tokens,
         n: options.get("n").and_then(|n_s| n_s.parse::<u32>().ok()),
-        presence_penalty: options
-            .get("presence_penalty")
-            .and_then(|pp_s| pp_s.parse::<f32>().ok()),
+        presence_penalty: config.presence_penalty.or_else(|| {
+            options
+                .get("presence_penalty")
+                .and_then(|pp_s| pp_s.parse::<f32>().ok())
+        }),
         reasoning_effort: options
             .get("reasoning_effort")
             .and_then(|effort_s| effort_s.parse::<Effort>().ok()),
-        seed: options
-            .get("seed")
-            .and_then(|seed_s| seed_s.parse::<u32>().ok()),
+        seed: config.seed.or_else(|| {
+            options
+                .get("seed")
+                .and_then(|seed_s| seed_s.parse::<u32>().ok())
+        }),
         stop: config.stop_sequences,
         stream: Some(false),
         stream_options: None,
@@ -71,9 +87,11 @@ pub fn messages_to_request(
         top_logprobs: options
             .get("top_logprobs")
             .and_then(|top_logprobs_s| top_logprobs_s.parse::<u8>().ok()),
-        top_p: options
-            .get("top_p")
-            .and_then(|top_p_s| top_p_s.parse::<f32>().ok()),
+        top_p: config.top_p.or_else(|| {
+            options
+                .get("top_p")
+                .and_then(|top_p_s| top_p_s.parse::<f32>().ok())
+        }),
         user: options.get("user_id").cloned(),
     })
 }
@@ -102,6 +120,7 @@ pub fn process_response(response: CompletionsResponse) -> ChatEvent {
                 provider_id: None,
                 timestamp: Some(response.created.to_string()),
                 provider_metadata_json: None,
+                cost: None,
             };
 
             ChatEvent::Message(CompleteResponse {
@@ -218,6 +237,8 @@ pub fn convert_usage(value: &crate::client::Usage) -> Usage {
         input_tokens: Some(value.prompt_tokens),
         output_tokens: Some(value.completion_tokens),
         total_tokens: Some(value.total_tokens),
+        cached_input_tokens: Some(value.prompt_tokens_details.cached_tokens),
+        reasoning_tokens: Some(value.completion_tokens_details.reasoning_tokens),
     }
 }
 
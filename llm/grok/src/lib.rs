@@ -14,6 +14,10 @@ use golem_llm::golem::llm::llm::{
     ChatEvent, ChatStream, Config, ContentPart, Error, FinishReason, Guest, Message,
     ResponseMetadata, StreamDelta, StreamEvent, ToolCall, ToolResult,
 };
+use golem_llm::golem::llm::moderation::{
+    Guest as ModerationGuest, ModerationConfig, ModerationResponse,
+};
+use golem_llm::guardrails::moderate_with_local_policy;
 use golem_rust::wasm_rpc::Pollable;
 use log::trace;
 use std::cell::{Ref, RefCell, RefMut};
@@ -102,6 +106,7 @@ impl LlmChatStreamState for GrokChatStream {
                         provider_id: None,
                         timestamp: Some(message.created.to_string()),
                         provider_metadata_json: None,
+                        cost: None,
                     })))
                 } else {
                     Ok(None)
@@ -179,7 +184,18 @@ impl Guest for GrokComponent {
     }
 }
 
+impl ModerationGuest for GrokComponent {
+    fn moderate(
+        inputs: Vec<String>,
+        _config: ModerationConfig,
+    ) -> Result<ModerationResponse, Error> {
+        moderate_with_local_policy(inputs)
+    }
+}
+
 impl ExtendedGuest for GrokComponent {
+    const PROVIDER: &'static str = "grok";
+
     fn unwrapped_stream(messages: Vec<Message>, config: Config) -> LlmChatStream<GrokChatStream> {
         with_config_key(Self::ENV_VAR_NAME, GrokChatStream::failed, |xai_api_key| {
             let client = CompletionsApi::new(xai_api_key);
@@ -199,3 +215,4 @@ impl ExtendedGuest for GrokComponent {
 type DurableGrokComponent = DurableLLM<GrokComponent>;
 
 golem_llm::export_llm!(DurableGrokComponent with_types_in golem_llm);
+golem_llm::export_moderation!(DurableGrokComponent with_types_in golem_llm::moderation_library);
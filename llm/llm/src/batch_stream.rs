@@ -0,0 +1,63 @@
+use crate::golem::llm::batch::{BatchResult, GuestBatchResultStream};
+use crate::golem::llm::llm::Error;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+/// Number of results returned by a single `get_next` call
+const PAGE_SIZE: usize = 100;
+
+type FetchResults = Box<dyn FnOnce() -> Result<Vec<BatchResult>, Error>>;
+
+enum LlmBatchResultStreamState {
+    NotFetched(FetchResults),
+    Fetched(VecDeque<BatchResult>),
+    Failed(Error),
+}
+
+/// Batch result stream for providers returning the results of a batch job as a single
+/// downloadable file. The results are downloaded on the first `get_next` call, and returned
+/// in pages of `PAGE_SIZE` results.
+pub struct LlmBatchResultStream {
+    state: RefCell<Option<LlmBatchResultStreamState>>,
+}
+
+impl LlmBatchResultStream {
+    pub fn new(fetch: impl FnOnce() -> Result<Vec<BatchResult>, Error> + 'static) -> Self {
+        Self {
+            state: RefCell::new(Some(LlmBatchResultStreamState::NotFetched(Box::new(fetch)))),
+        }
+    }
+
+    pub fn failed(error: Error) -> Self {
+        Self {
+            state: RefCell::new(Some(LlmBatchResultStreamState::Failed(error))),
+        }
+    }
+}
+
+impl GuestBatchResultStream for LlmBatchResultStream {
+    fn get_next(&self) -> Result<Option<Vec<BatchResult>>, Error> {
+        let mut state = self.state.borrow_mut();
+        if matches!(&*state, Some(LlmBatchResultStreamState::NotFetched(_))) {
+            if let Some(LlmBatchResultStreamState::NotFetched(fetch)) = state.take() {
+                *state = Some(match fetch() {
+                    Ok(results) => LlmBatchResultStreamState::Fetched(results.into()),
+                    Err(error) => LlmBatchResultStreamState::Failed(error),
+                });
+            }
+        }
+
+        match &mut *state {
+            Some(LlmBatchResultStreamState::Fetched(results)) => {
+                if results.is_empty() {
+                    Ok(None)
+                } else {
+                    let count = results.len().min(PAGE_SIZE);
+                    Ok(Some(results.drain(..count).collect()))
+                }
+            }
+            Some(LlmBatchResultStreamState::Failed(error)) => Err(error.clone()),
+            _ => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,832 @@
+use crate::durability::ExtendedGuest;
+use crate::golem::llm::llm::{
+    ChatEvent, CompleteResponse, Config, ContentPart, Cost, FinishReason, ImageDetail,
+    ImageReference, ImageSource, ImageUrl, Kv, Message, ResponseMetadata, Role, ToolCall,
+    ToolResult, Usage,
+};
+use crate::guardrails::LocalPolicy;
+use crate::tool_validation::ToolValidation;
+use log::{trace, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::{Duration, SystemTime};
+
+/// Provider option controlling the response cache for a single request. It is removed from the
+/// configuration before the request is passed to the provider.
+///
+/// - `bypass` skips the cache
+/// - `force` caches the response even if the request would be bypassed by the temperature rule
+pub const CACHE_OPTION: &str = "cache";
+
+/// How the response cache is used for a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheMode {
+    /// The response is cached if the request is deterministic enough, see `CacheSettings`
+    Default,
+    Bypass,
+    Force,
+}
+
+/// Where the cached responses are stored
+#[derive(Debug, Clone, PartialEq)]
+pub enum CacheStorage {
+    /// Kept in the memory of the component instance
+    Memory,
+    /// Stored through the imported `golem:llm/cache` interface
+    #[cfg(feature = "cache-interface")]
+    Interface,
+}
+
+/// Settings of the response cache, configured with environment variables. The cache is disabled
+/// when `GOLEM_LLM_CACHE` is not set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheSettings {
+    pub storage: CacheStorage,
+    pub ttl: Duration,
+    /// Requests with a higher temperature are not cached, as their responses are expected to vary.
+    /// Requests without a temperature use the provider's default, which is usually not zero, so
+    /// they are not cached either.
+    pub max_temperature: f32,
+}
+
+impl CacheSettings {
+    pub const ENV_VAR_NAME: &'static str = "GOLEM_LLM_CACHE";
+    pub const TTL_ENV_VAR_NAME: &'static str = "GOLEM_LLM_CACHE_TTL";
+    pub const MAX_TEMPERATURE_ENV_VAR_NAME: &'static str = "GOLEM_LLM_CACHE_MAX_TEMPERATURE";
+    const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60);
+
+    /// Gets the cache settings configured by the environment variables, or `None` if the cache
+    /// is disabled
+    pub fn from_env() -> Rc<Option<CacheSettings>> {
+        CACHE_SETTINGS.with(|cached| {
+            cached
+                .borrow_mut()
+                .get_or_insert_with(|| {
+                    let storage = std::env::var(Self::ENV_VAR_NAME).unwrap_or_default();
+                    let ttl = std::env::var(Self::TTL_ENV_VAR_NAME).ok();
+                    let max_temperature = std::env::var(Self::MAX_TEMPERATURE_ENV_VAR_NAME).ok();
+                    Rc::new(
+                        Self::parse(&storage, ttl.as_deref(), max_temperature.as_deref())
+                            .unwrap_or_else(|message| {
+                                warn!("{message}");
+                                None
+                            }),
+                    )
+                })
+                .clone()
+        })
+    }
+
+    pub fn parse(
+        storage: &str,
+        ttl: Option<&str>,
+        max_temperature: Option<&str>,
+    ) -> Result<Option<Self>, String> {
+        let storage = match storage.trim().to_lowercase().as_str() {
+            "" | "off" | "none" => return Ok(None),
+            "memory" => CacheStorage::Memory,
+            #[cfg(feature = "cache-interface")]
+            "interface" => CacheStorage::Interface,
+            other => {
+                return Err(format!(
+                    "Unsupported value for {}: {other}, the response cache is disabled",
+                    Self::ENV_VAR_NAME
+                ))
+            }
+        };
+        let ttl = match ttl {
+            Some(ttl) => Duration::from_secs(ttl.parse::<u64>().map_err(|err| {
+                format!(
+                    "Invalid {}: {err}, the response cache is disabled",
+                    Self::TTL_ENV_VAR_NAME
+                )
+            })?),
+            None => Self::DEFAULT_TTL,
+        };
+        let max_temperature = match max_temperature {
+            Some(max_temperature) => max_temperature.parse::<f32>().map_err(|err| {
+                format!(
+                    "Invalid {}: {err}, the response cache is disabled",
+                    Self::MAX_TEMPERATURE_ENV_VAR_NAME
+                )
+            })?,
+            None => 0.0,
+        };
+
+        Ok(Some(Self {
+            storage,
+            ttl,
+            max_temperature,
+        }))
+    }
+
+    fn is_cacheable(&self, config: &Config, mode: CacheMode) -> bool {
+        match mode {
+            CacheMode::Bypass => false,
+            CacheMode::Force => true,
+            CacheMode::Default => config
+                .temperature
+                .is_some_and(|temperature| temperature <= self.max_temperature),
+        }
+    }
+}
+
+thread_local! {
+    static CACHE_SETTINGS: RefCell<Option<Rc<Option<CacheSettings>>>> = const { RefCell::new(None) };
+    static MEMORY_CACHE: RefCell<HashMap<String, (CompleteResponse, SystemTime)>> = RefCell::new(HashMap::new());
+}
+
+/// Removes the cache option from the provider options of a request
+pub(crate) fn take_cache_mode(config: &mut Config) -> CacheMode {
+    let Some(index) = config
+        .provider_options
+        .iter()
+        .position(|kv| kv.key == CACHE_OPTION)
+    else {
+        return CacheMode::Default;
+    };
+    match config.provider_options.remove(index).value.as_str() {
+        "bypass" => CacheMode::Bypass,
+        "force" => CacheMode::Force,
+        other => {
+            warn!("Unsupported value for the {CACHE_OPTION} provider option: {other}");
+            CacheMode::Default
+        }
+    }
+}
+
+/// Calculates the cache key of a request, as the SHA-256 hash of a canonical JSON encoding of the
+/// provider, the messages, the tool results and the whole config. The provider options and the
+/// credentials are sorted, so their order does not change the key. The per-call credentials are
+/// part of the key, so responses are never shared between tenants.
+///
+/// The tool validation and the local guardrail policy change the returned response, so they are
+/// part of the key too.
+pub fn cache_key(
+    provider: &str,
+    messages: &[Message],
+    tool_results: Option<&[(ToolCall, ToolResult)]>,
+    config: &Config,
+    tool_validation: ToolValidation,
+    guardrail_policy: Option<&str>,
+) -> String {
+    let request = json!({
+        "provider": provider,
+        "messages": messages.iter().map(message_value).collect::<Vec<_>>(),
+        "tool_results": tool_results.map(|tool_results| {
+            tool_results
+                .iter()
+                .map(|(tool_call, tool_result)| {
+                    json!([tool_call_value(tool_call), tool_result_value(tool_result)])
+                })
+                .collect::<Vec<_>>()
+        }),
+        "config": config_value(config),
+        "tool_validation": match tool_validation {
+            ToolValidation::Off => json!("off"),
+            ToolValidation::Validate => json!("validate"),
+            ToolValidation::Repair { attempts } => json!({ "repair": attempts }),
+        },
+        "guardrail_policy": guardrail_policy,
+    });
+    format!("{:x}", Sha256::digest(request.to_string().as_bytes()))
+}
+
+fn message_value(message: &Message) -> Value {
+    json!({
+        "role": match message.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::System => "system",
+            Role::Tool => "tool",
+        },
+        "name": message.name,
+        "content": message.content.iter().map(content_part_value).collect::<Vec<_>>(),
+    })
+}
+
+fn content_part_value(part: &ContentPart) -> Value {
+    match part {
+        ContentPart::Text(text) => json!({ "text": text }),
+        ContentPart::Image(ImageReference::Url(image)) => json!({
+            "image_url": image.url,
+            "detail": image.detail.map(image_detail_name),
+        }),
+        // Only the hash of the image is encoded, to keep the key input small
+        ContentPart::Image(ImageReference::Inline(image)) => json!({
+            "inline_image": format!("{:x}", Sha256::digest(&image.data)),
+            "mime_type": image.mime_type,
+            "detail": image.detail.map(image_detail_name),
+        }),
+    }
+}
+
+fn tool_call_value(tool_call: &ToolCall) -> Value {
+    json!({
+        "id": tool_call.id,
+        "name": tool_call.name,
+        "arguments_json": tool_call.arguments_json,
+    })
+}
+
+fn tool_result_value(tool_result: &ToolResult) -> Value {
+    match tool_result {
+        ToolResult::Success(success) => json!({
+            "id": success.id,
+            "name": success.name,
+            "result_json": success.result_json,
+            "execution_time_ms": success.execution_time_ms,
+        }),
+        ToolResult::Error(failure) => json!({
+            "id": failure.id,
+            "name": failure.name,
+            "error_message": failure.error_message,
+            "error_code": failure.error_code,
+        }),
+    }
+}
+
+fn config_value(config: &Config) -> Value {
+    json!({
+        "model": config.model,
+        "temperature": config.temperature,
+        "max_tokens": config.max_tokens,
+        "stop_sequences": config.stop_sequences,
+        "top_p": config.top_p,
+        "top_k": config.top_k,
+        "seed": config.seed,
+        "frequency_penalty": config.frequency_penalty,
+        "presence_penalty": config.presence_penalty,
+        "repetition_penalty": config.repetition_penalty,
+        "tools": config
+            .tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "parameters_schema": tool.parameters_schema,
+                })
+            })
+            .collect::<Vec<_>>(),
+        "tool_choice": config.tool_choice,
+        "provider_options": sorted_kvs(&config.provider_options),
+        "credentials": config.credentials.as_deref().map(sorted_kvs),
+    })
+}
+
+fn sorted_kvs(kvs: &[Kv]) -> Vec<(&str, &str)> {
+    let mut kvs = kvs
+        .iter()
+        .map(|kv| (kv.key.as_str(), kv.value.as_str()))
+        .collect::<Vec<_>>();
+    kvs.sort();
+    kvs
+}
+
+/// Runs `send` or `continue` through the response cache, if it is enabled and the request is
+/// cacheable. Only complete responses are cached, tool requests and errors are always sent to the
+/// provider again.
+///
+/// Cache hits are returned as regular responses, flagged with `"cached": true` in their
+/// `provider-metadata-json`, and with a zero cost as nothing was charged for them.
+pub(crate) fn cached<Impl: ExtendedGuest>(
+    messages: &[Message],
+    tool_results: Option<&[(ToolCall, ToolResult)]>,
+    config: &Config,
+    mode: CacheMode,
+    tool_validation: ToolValidation,
+    call: impl FnOnce() -> ChatEvent,
+) -> ChatEvent {
+    let settings = CacheSettings::from_env();
+    let Some(settings) = &*settings else {
+        return call();
+    };
+    if !settings.is_cacheable(config, mode) {
+        return call();
+    }
+
+    let guardrail_policy = std::env::var(LocalPolicy::ENV_VAR_NAME).ok();
+    let key = cache_key(
+        Impl::PROVIDER,
+        messages,
+        tool_results,
+        config,
+        tool_validation,
+        guardrail_policy.as_deref(),
+    );
+    if let Some(response) = get(&settings.storage, &key) {
+        trace!("Response cache hit for {key}");
+        return ChatEvent::Message(cache_hit(response));
+    }
+
+    let event = call();
+    if let ChatEvent::Message(response) = &event {
+        set(&settings.storage, key, response, settings.ttl);
+    }
+    event
+}
+
+fn cache_hit(mut response: CompleteResponse) -> CompleteResponse {
+    let mut metadata = match response
+        .metadata
+        .provider_metadata_json
+        .as_deref()
+        .map(serde_json::from_str::<serde_json::Value>)
+    {
+        Some(Ok(serde_json::Value::Object(metadata))) => metadata,
+        Some(Ok(other)) => serde_json::Map::from_iter([("provider_metadata".to_string(), other)]),
+        _ => serde_json::Map::new(),
+    };
+    metadata.insert("cached".to_string(), serde_json::Value::Bool(true));
+    response.metadata.provider_metadata_json =
+        Some(serde_json::Value::Object(metadata).to_string());
+
+    if let Some(cost) = &mut response.metadata.cost {
+        *cost = Cost {
+            input_cost: 0.0,
+            output_cost: 0.0,
+            cached_input_cost: 0.0,
+            reasoning_cost: 0.0,
+            total_cost: 0.0,
+            currency: cost.currency.clone(),
+        };
+    }
+    response
+}
+
+fn get(storage: &CacheStorage, key: &str) -> Option<CompleteResponse> {
+    match storage {
+        CacheStorage::Memory => MEMORY_CACHE.with_borrow_mut(|cache| {
+            let (response, expires_at) = cache.get(key)?;
+            if SystemTime::now() < *expires_at {
+                Some(response.clone())
+            } else {
+                cache.remove(key);
+                None
+            }
+        }),
+        #[cfg(feature = "cache-interface")]
+        CacheStorage::Interface => {
+            let value = cache_interface::get(key)?;
+            match serde_json::from_slice::<CachedResponse>(&value) {
+                Ok(response) => Some(response.into()),
+                Err(err) => {
+                    warn!("Failed to decode cached response {key}: {err}");
+                    None
+                }
+            }
+        }
+    }
+}
+
+fn set(storage: &CacheStorage, key: String, response: &CompleteResponse, ttl: Duration) {
+    match storage {
+        CacheStorage::Memory => MEMORY_CACHE.with_borrow_mut(|cache| {
+            let now = SystemTime::now();
+            cache.retain(|_, (_, expires_at)| now < *expires_at);
+            cache.insert(key, (response.clone(), now + ttl));
+        }),
+        #[cfg(feature = "cache-interface")]
+        CacheStorage::Interface => match serde_json::to_vec(&CachedResponse::from(response)) {
+            Ok(value) => cache_interface::set(&key, &value, ttl),
+            Err(err) => warn!("Failed to encode cached response {key}: {err}"),
+        },
+    }
+}
+
+#[cfg(feature = "cache-interface")]
+mod cache_interface {
+    use std::time::Duration;
+
+    wit_bindgen::generate!({
+        path: "../wit",
+        world: "cache-storage",
+    });
+
+    pub fn get(key: &str) -> Option<Vec<u8>> {
+        golem::llm::cache::get(key)
+    }
+
+    pub fn set(key: &str, value: &[u8], ttl: Duration) {
+        golem::llm::cache::set(key, value, ttl.as_secs())
+    }
+}
+
+/// Serializable form of `CompleteResponse`, used to store responses through the cache interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CachedResponse {
+    id: String,
+    content: Vec<CachedContentPart>,
+    tool_calls: Vec<CachedToolCall>,
+    finish_reason: Option<String>,
+    usage: Option<CachedUsage>,
+    provider_id: Option<String>,
+    timestamp: Option<String>,
+    provider_metadata_json: Option<String>,
+    cost: Option<CachedCost>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum CachedContentPart {
+    Text {
+        text: String,
+    },
+    ImageUrl {
+        url: String,
+        detail: Option<String>,
+    },
+    InlineImage {
+        data: Vec<u8>,
+        mime_type: String,
+        detail: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CachedToolCall {
+    id: String,
+    name: String,
+    arguments_json: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CachedUsage {
+    input_tokens: Option<u32>,
+    output_tokens: Option<u32>,
+    total_tokens: Option<u32>,
+    cached_input_tokens: Option<u32>,
+    reasoning_tokens: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CachedCost {
+    input_cost: f64,
+    output_cost: f64,
+    cached_input_cost: f64,
+    reasoning_cost: f64,
+    total_cost: f64,
+    currency: String,
+}
+
+impl From<&CompleteResponse> for CachedResponse {
+    fn from(response: &CompleteResponse) -> Self {
+        let metadata = &response.metadata;
+        Self {
+            id: response.id.clone(),
+            content: response
+                .content
+                .iter()
+                .map(|part| match part {
+                    ContentPart::Text(text) => CachedContentPart::Text { text: text.clone() },
+                    ContentPart::Image(ImageReference::Url(image)) => CachedContentPart::ImageUrl {
+                        url: image.url.clone(),
+                        detail: image.detail.map(image_detail_name),
+                    },
+                    ContentPart::Image(ImageReference::Inline(image)) => {
+                        CachedContentPart::InlineImage {
+                            data: image.data.clone(),
+                            mime_type: image.mime_type.clone(),
+                            detail: image.detail.map(image_detail_name),
+                        }
+                    }
+                })
+                .collect(),
+            tool_calls: response
+                .tool_calls
+                .iter()
+                .map(|tool_call| CachedToolCall {
+                    id: tool_call.id.clone(),
+                    name: tool_call.name.clone(),
+                    arguments_json: tool_call.arguments_json.clone(),
+                })
+                .collect(),
+            finish_reason: metadata.finish_reason.map(finish_reason_name),
+            usage: metadata.usage.as_ref().map(|usage| CachedUsage {
+                input_tokens: usage.input_tokens,
+                output_tokens: usage.output_tokens,
+                total_tokens: usage.total_tokens,
+                cached_input_tokens: usage.cached_input_tokens,
+                reasoning_tokens: usage.reasoning_tokens,
+            }),
+            provider_id: metadata.provider_id.clone(),
+            timestamp: metadata.timestamp.clone(),
+            provider_metadata_json: metadata.provider_metadata_json.clone(),
+            cost: metadata.cost.as_ref().map(|cost| CachedCost {
+                input_cost: cost.input_cost,
+                output_cost: cost.output_cost,
+                cached_input_cost: cost.cached_input_cost,
+                reasoning_cost: cost.reasoning_cost,
+                total_cost: cost.total_cost,
+                currency: cost.currency.clone(),
+            }),
+        }
+    }
+}
+
+impl From<CachedResponse> for CompleteResponse {
+    fn from(response: CachedResponse) -> Self {
+        Self {
+            id: response.id,
+            content: response
+                .content
+                .into_iter()
+                .map(|part| match part {
+                    CachedContentPart::Text { text } => ContentPart::Text(text),
+                    CachedContentPart::ImageUrl { url, detail } => {
+                        ContentPart::Image(ImageReference::Url(ImageUrl {
+                            url,
+                            detail: detail.as_deref().and_then(parse_image_detail),
+                        }))
+                    }
+                    CachedContentPart::InlineImage {
+                        data,
+                        mime_type,
+                        detail,
+                    } => ContentPart::Image(ImageReference::Inline(ImageSource {
+                        data,
+                        mime_type,
+                        detail: detail.as_deref().and_then(parse_image_detail),
+                    })),
+                })
+                .collect(),
+            tool_calls: response
+                .tool_calls
+                .into_iter()
+                .map(|tool_call| ToolCall {
+                    id: tool_call.id,
+                    name: tool_call.name,
+                    arguments_json: tool_call.arguments_json,
+                })
+                .collect(),
+            metadata: ResponseMetadata {
+                finish_reason: response
+                    .finish_reason
+                    .as_deref()
+                    .and_then(parse_finish_reason),
+                usage: response.usage.map(|usage| Usage {
+                    input_tokens: usage.input_tokens,
+                    output_tokens: usage.output_tokens,
+                    total_tokens: usage.total_tokens,
+                    cached_input_tokens: usage.cached_input_tokens,
+                    reasoning_tokens: usage.reasoning_tokens,
+                }),
+                provider_id: response.provider_id,
+                timestamp: response.timestamp,
+                provider_metadata_json: response.provider_metadata_json,
+                cost: response.cost.map(|cost| Cost {
+                    input_cost: cost.input_cost,
+                    output_cost: cost.output_cost,
+                    cached_input_cost: cost.cached_input_cost,
+                    reasoning_cost: cost.reasoning_cost,
+                    total_cost: cost.total_cost,
+                    currency: cost.currency,
+                }),
+            },
+        }
+    }
+}
+
+fn image_detail_name(detail: ImageDetail) -> String {
+    match detail {
+        ImageDetail::Low => "low",
+        ImageDetail::High => "high",
+        ImageDetail::Auto => "auto",
+    }
+    .to_string()
+}
+
+fn parse_image_detail(detail: &str) -> Option<ImageDetail> {
+    match detail {
+        "low" => Some(ImageDetail::Low),
+        "high" => Some(ImageDetail::High),
+        "auto" => Some(ImageDetail::Auto),
+        _ => None,
+    }
+}
+
+fn finish_reason_name(reason: FinishReason) -> String {
+    match reason {
+        FinishReason::Stop => "stop",
+        FinishReason::Length => "length",
+        FinishReason::ToolCalls => "tool-calls",
+        FinishReason::ContentFilter => "content-filter",
+        FinishReason::Error => "error",
+        FinishReason::Other => "other",
+    }
+    .to_string()
+}
+
+fn parse_finish_reason(reason: &str) -> Option<FinishReason> {
+    match reason {
+        "stop" => Some(FinishReason::Stop),
+        "length" => Some(FinishReason::Length),
+        "tool-calls" => Some(FinishReason::ToolCalls),
+        "content-filter" => Some(FinishReason::ContentFilter),
+        "error" => Some(FinishReason::Error),
+        "other" => Some(FinishReason::Other),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::{
+        cache_hit, cache_key, take_cache_mode, CacheMode, CacheSettings, CacheStorage,
+        CachedResponse,
+    };
+    use crate::golem::llm::llm::{
+        CompleteResponse, Config, ContentPart, Cost, FinishReason, ImageDetail, ImageReference,
+        ImageUrl, Kv, Message, ResponseMetadata, Role, ToolCall,
+    };
+    use crate::tool_validation::ToolValidation;
+    use std::time::Duration;
+
+    fn config(temperature: Option<f32>) -> Config {
+        Config {
+            model: "gpt-4o".to_string(),
+            temperature,
+            max_tokens: None,
+            stop_sequences: None,
+            top_p: None,
+            top_k: None,
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            repetition_penalty: None,
+            tools: vec![],
+            tool_choice: None,
+            provider_options: vec![Kv {
+                key: "cache".to_string(),
+                value: "force".to_string(),
+            }],
+            credentials: None,
+        }
+    }
+
+    fn messages(text: &str) -> Vec<Message> {
+        vec![Message {
+            role: Role::User,
+            name: None,
+            content: vec![ContentPart::Text(text.to_string())],
+        }]
+    }
+
+    fn response() -> CompleteResponse {
+        CompleteResponse {
+            id: "resp_1".to_string(),
+            content: vec![
+                ContentPart::Text("Hello".to_string()),
+                ContentPart::Image(ImageReference::Url(ImageUrl {
+                    url: "https://example.com/cat.png".to_string(),
+                    detail: Some(ImageDetail::High),
+                })),
+            ],
+            tool_calls: vec![ToolCall {
+                id: "call_1".to_string(),
+                name: "search".to_string(),
+                arguments_json: "{}".to_string(),
+            }],
+            metadata: ResponseMetadata {
+                finish_reason: Some(FinishReason::Stop),
+                usage: None,
+                provider_id: None,
+                timestamp: None,
+                provider_metadata_json: Some(r#"{"region":"us"}"#.to_string()),
+                cost: Some(Cost {
+                    input_cost: 0.1,
+                    output_cost: 0.2,
+                    cached_input_cost: 0.0,
+                    reasoning_cost: 0.0,
+                    total_cost: 0.3,
+                    currency: "USD".to_string(),
+                }),
+            },
+        }
+    }
+
+    #[test]
+    fn cache_key_depends_on_every_input() {
+        let key_of = |provider: &str, text: &str, temperature: Option<f32>| {
+            cache_key(
+                provider,
+                &messages(text),
+                None,
+                &config(temperature),
+                ToolValidation::Off,
+                None,
+            )
+        };
+        let key = key_of("openai", "Hi", Some(0.0));
+        assert_eq!(key.len(), 64);
+        assert_eq!(key, key_of("openai", "Hi", Some(0.0)));
+        assert_ne!(key, key_of("anthropic", "Hi", Some(0.0)));
+        assert_ne!(key, key_of("openai", "Hello", Some(0.0)));
+        assert_ne!(key, key_of("openai", "Hi", Some(0.5)));
+
+        let key_with = |tool_validation: ToolValidation, guardrail_policy: Option<&str>| {
+            cache_key(
+                "openai",
+                &messages("Hi"),
+                None,
+                &config(Some(0.0)),
+                tool_validation,
+                guardrail_policy,
+            )
+        };
+        assert_eq!(key, key_with(ToolValidation::Off, None));
+        assert_ne!(key, key_with(ToolValidation::Validate, None));
+        assert_ne!(
+            key_with(ToolValidation::Repair { attempts: 1 }, None),
+            key_with(ToolValidation::Repair { attempts: 2 }, None)
+        );
+        assert_ne!(
+            key,
+            key_with(
+                ToolValidation::Off,
+                Some(r#"{"blocked_terms": ["secret"]}"#)
+            )
+        );
+    }
+
+    #[test]
+    fn cache_key_ignores_the_order_of_the_provider_options() {
+        let mut first = config(Some(0.0));
+        first.provider_options.push(Kv {
+            key: "top_a".to_string(),
+            value: "0.5".to_string(),
+        });
+        let mut second = first.clone();
+        second.provider_options.reverse();
+
+        assert_eq!(
+            cache_key(
+                "openai",
+                &messages("Hi"),
+                None,
+                &first,
+                ToolValidation::Off,
+                None
+            ),
+            cache_key(
+                "openai",
+                &messages("Hi"),
+                None,
+                &second,
+                ToolValidation::Off,
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn bypasses_requests_with_temperature() {
+        let settings = CacheSettings::parse("memory", None, None).unwrap().unwrap();
+        assert_eq!(settings.storage, CacheStorage::Memory);
+        assert_eq!(settings.ttl, Duration::from_secs(3600));
+
+        assert!(settings.is_cacheable(&config(Some(0.0)), CacheMode::Default));
+        assert!(!settings.is_cacheable(&config(Some(0.7)), CacheMode::Default));
+        assert!(!settings.is_cacheable(&config(None), CacheMode::Default));
+        assert!(settings.is_cacheable(&config(Some(0.7)), CacheMode::Force));
+        assert!(!settings.is_cacheable(&config(Some(0.0)), CacheMode::Bypass));
+
+        assert_eq!(CacheSettings::parse("", None, None), Ok(None));
+        assert!(CacheSettings::parse("memory", Some("soon"), None).is_err());
+    }
+
+    #[test]
+    fn cache_option_is_removed() {
+        let mut config = config(None);
+        assert_eq!(take_cache_mode(&mut config), CacheMode::Force);
+        assert!(config.provider_options.is_empty());
+        assert_eq!(take_cache_mode(&mut config), CacheMode::Default);
+    }
+
+    #[test]
+    fn cache_hits_are_flagged_and_free() {
+        let hit = cache_hit(response());
+        let metadata: serde_json::Value =
+            serde_json::from_str(&hit.metadata.provider_metadata_json.unwrap()).unwrap();
+        assert_eq!(
+            metadata,
+            serde_json::json!({ "cached": true, "region": "us" })
+        );
+        assert_eq!(hit.metadata.cost.unwrap().total_cost, 0.0);
+        assert_eq!(hit.content, response().content);
+    }
+
+    #[test]
+    fn cached_responses_roundtrip() {
+        let encoded = serde_json::to_vec(&CachedResponse::from(&response())).unwrap();
+        let decoded: CompleteResponse = serde_json::from_slice::<CachedResponse>(&encoded)
+            .unwrap()
+            .into();
+        assert_eq!(decoded, response());
+    }
+}
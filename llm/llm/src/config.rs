@@ -1,30 +1,27 @@
-use crate::golem::llm::llm::{Error, ErrorCode};
+use crate::credentials::resolve_config_key;
+use crate::error::unsupported;
+use crate::golem::llm::llm::{Config, Error, ErrorCode};
 use std::ffi::OsStr;
 
-/// Gets an expected configuration value from the environment, and fails if its is not found
-/// using the `fail` function. Otherwise, it runs `succeed` with the configuration value.
+/// Gets an expected configuration value, and fails if its is not found using the `fail` function.
+/// Otherwise, it runs `succeed` with the configuration value.
+///
+/// The value is resolved from the per-call credentials, the secret store or the environment,
+/// see `resolve_config_key`.
 pub fn with_config_key<R>(
     key: impl AsRef<OsStr>,
     fail: impl FnOnce(Error) -> R,
     succeed: impl FnOnce(String) -> R,
 ) -> R {
-    let key_str = key.as_ref().to_string_lossy().to_string();
-    match std::env::var(key) {
+    match get_config_key(key) {
         Ok(value) => succeed(value),
-        Err(_) => {
-            let error = Error {
-                code: ErrorCode::InternalError,
-                message: format!("Missing config key: {key_str}"),
-                provider_error_json: None,
-            };
-            fail(error)
-        }
+        Err(error) => fail(error),
     }
 }
 
 pub fn get_config_key(key: impl AsRef<OsStr>) -> Result<String, Error> {
     let key_str = key.as_ref().to_string_lossy().to_string();
-    std::env::var(key).map_err(|_| Error {
+    resolve_config_key(&key_str)?.ok_or_else(|| Error {
         code: ErrorCode::InternalError,
         message: format!("Missing config key: {key_str}"),
         provider_error_json: None,
@@ -32,5 +29,119 @@ pub fn get_config_key(key: impl AsRef<OsStr>) -> Result<String, Error> {
 }
 
 pub fn get_config_key_or_none(key: impl AsRef<OsStr>) -> Option<String> {
-    std::env::var(key).ok()
+    let key_str = key.as_ref().to_string_lossy().to_string();
+    match resolve_config_key(&key_str) {
+        Ok(value) => value,
+        Err(error) => {
+            log::warn!("{}", error.message);
+            None
+        }
+    }
+}
+
+/// A typed sampling parameter of `Config`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SamplingParameter {
+    TopP,
+    TopK,
+    Seed,
+    FrequencyPenalty,
+    PresencePenalty,
+    RepetitionPenalty,
+}
+
+impl SamplingParameter {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SamplingParameter::TopP => "top-p",
+            SamplingParameter::TopK => "top-k",
+            SamplingParameter::Seed => "seed",
+            SamplingParameter::FrequencyPenalty => "frequency-penalty",
+            SamplingParameter::PresencePenalty => "presence-penalty",
+            SamplingParameter::RepetitionPenalty => "repetition-penalty",
+        }
+    }
+
+    pub fn is_set(&self, config: &Config) -> bool {
+        match self {
+            SamplingParameter::TopP => config.top_p.is_some(),
+            SamplingParameter::TopK => config.top_k.is_some(),
+            SamplingParameter::Seed => config.seed.is_some(),
+            SamplingParameter::FrequencyPenalty => config.frequency_penalty.is_some(),
+            SamplingParameter::PresencePenalty => config.presence_penalty.is_some(),
+            SamplingParameter::RepetitionPenalty => config.repetition_penalty.is_some(),
+        }
+    }
+}
+
+/// Fails with an `unsupported` error listing the parameters which are set in the config, but
+/// can't be honored by the provider.
+pub fn reject_unsupported_parameters(
+    provider: &str,
+    config: &Config,
+    unsupported_parameters: &[SamplingParameter],
+) -> Result<(), Error> {
+    let names = unsupported_parameters
+        .iter()
+        .filter(|parameter| parameter.is_set(config))
+        .map(|parameter| parameter.name())
+        .collect::<Vec<_>>();
+    if names.is_empty() {
+        Ok(())
+    } else {
+        Err(unsupported(format!(
+            "{provider} does not support the {} parameter{}",
+            names.join(", "),
+            if names.len() > 1 { "s" } else { "" }
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{reject_unsupported_parameters, SamplingParameter};
+    use crate::golem::llm::llm::{Config, ErrorCode};
+
+    #[test]
+    fn rejects_only_the_parameters_which_are_set() {
+        let config = Config {
+            model: "claude-sonnet-4".to_string(),
+            temperature: None,
+            max_tokens: None,
+            stop_sequences: None,
+            top_p: Some(0.9),
+            top_k: None,
+            seed: Some(42),
+            frequency_penalty: None,
+            presence_penalty: Some(0.5),
+            repetition_penalty: None,
+            tools: vec![],
+            tool_choice: None,
+            provider_options: vec![],
+            credentials: None,
+        };
+
+        assert!(reject_unsupported_parameters(
+            "Anthropic",
+            &config,
+            &[SamplingParameter::FrequencyPenalty]
+        )
+        .is_ok());
+
+        let error = reject_unsupported_parameters(
+            "Anthropic",
+            &config,
+            &[
+                SamplingParameter::Seed,
+                SamplingParameter::FrequencyPenalty,
+                SamplingParameter::PresencePenalty,
+            ],
+        )
+        .unwrap_err();
+        assert_eq!(error.code, ErrorCode::Unsupported);
+        assert_eq!(
+            error.message,
+            "Unsupported: Anthropic does not support the seed, presence-penalty parameters"
+        );
+    }
 }
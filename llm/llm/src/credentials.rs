@@ -0,0 +1,283 @@
+use crate::golem::llm::batch::BatchRequest;
+use crate::golem::llm::llm::{Config, Error, ErrorCode, Kv};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+/// Short-lived tokens are refreshed this long before they expire
+const REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
+
+thread_local! {
+    static CALL_CREDENTIALS: RefCell<Option<HashMap<String, String>>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` with the per-call credentials of the given config overriding the configured ones.
+/// Without per-call credentials, the currently active ones are kept.
+pub fn with_credentials<R>(config: &Config, f: impl FnOnce() -> R) -> R {
+    with_job_credentials(config.credentials.as_deref(), f)
+}
+
+/// Runs `f` with the per-call credentials passed to the batch job operations (`poll`, `cancel`
+/// and `results`). Without per-call credentials, the currently active ones are kept.
+pub fn with_job_credentials<R>(credentials: Option<&[Kv]>, f: impl FnOnce() -> R) -> R {
+    let Some(credentials) = credentials else {
+        return f();
+    };
+
+    let overrides = credentials
+        .iter()
+        .map(|kv| (kv.key.clone(), kv.value.clone()))
+        .collect();
+    let previous = CALL_CREDENTIALS.with_borrow_mut(|current| current.replace(overrides));
+    let result = f();
+    CALL_CREDENTIALS.with_borrow_mut(|current| *current = previous);
+    result
+}
+
+/// Runs `f` with the per-call credentials of a batch. All the requests of a batch are submitted
+/// as a single job, so they have to carry the same credentials.
+pub fn with_batch_credentials<R>(
+    requests: &[BatchRequest],
+    f: impl FnOnce() -> R,
+) -> Result<R, Error> {
+    let Some((first, rest)) = requests.split_first() else {
+        return Ok(f());
+    };
+    if rest
+        .iter()
+        .any(|request| request.config.credentials != first.config.credentials)
+    {
+        return Err(Error {
+            code: ErrorCode::InvalidRequest,
+            message: "All the requests of a batch must have the same credentials".to_string(),
+            provider_error_json: None,
+        });
+    }
+
+    Ok(with_credentials(&first.config, f))
+}
+
+/// Removes the per-call credentials from the config, so it can be written to the oplog
+pub fn without_credentials(config: Config) -> Config {
+    Config {
+        credentials: None,
+        ..config
+    }
+}
+
+/// Resolves a configuration key from the per-call credentials, then from the secret store when
+/// the `secret-store` feature is enabled, and finally from the environment variables.
+pub fn resolve_config_key(key: &str) -> Result<Option<String>, Error> {
+    let overridden = CALL_CREDENTIALS.with_borrow(|current| {
+        current
+            .as_ref()
+            .and_then(|credentials| credentials.get(key).cloned())
+    });
+    if overridden.is_some() {
+        return Ok(overridden);
+    }
+
+    #[cfg(feature = "secret-store")]
+    if let Some(value) = secret_store::get_secret(key)? {
+        return Ok(Some(value));
+    }
+
+    Ok(std::env::var(key).ok())
+}
+
+#[cfg(feature = "secret-store")]
+mod secret_store {
+    use crate::golem::llm::llm::{Error, ErrorCode};
+
+    wit_bindgen::generate!({
+        path: "../wit",
+        world: "secret-store",
+    });
+
+    pub fn get_secret(name: &str) -> Result<Option<String>, Error> {
+        golem::llm::secrets::get_secret(name).map_err(|error| Error {
+            code: ErrorCode::InternalError,
+            message: format!("Failed to get {name} from the secret store: {error}"),
+            provider_error_json: None,
+        })
+    }
+}
+
+/// Cache of short-lived tokens, such as temporary cloud credentials or OAuth access tokens.
+/// Tokens are returned until shortly before they expire, after which they have to be refreshed.
+///
+/// The cache is only kept in memory, so the tokens are never written to the oplog.
+pub struct TokenCache<T> {
+    tokens: RefCell<HashMap<String, (T, SystemTime)>>,
+}
+
+impl<T: Clone> TokenCache<T> {
+    pub fn new() -> Self {
+        Self {
+            tokens: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the cached token for the key, unless it is about to expire
+    pub fn get(&self, key: &str) -> Option<T> {
+        let tokens = self.tokens.borrow();
+        let (token, expires_at) = tokens.get(key)?;
+        let refresh_at = expires_at
+            .checked_sub(REFRESH_MARGIN)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        if SystemTime::now() < refresh_at {
+            Some(token.clone())
+        } else {
+            None
+        }
+    }
+
+    pub fn insert(&self, key: String, token: T, expires_at: SystemTime) {
+        self.tokens.borrow_mut().insert(key, (token, expires_at));
+    }
+}
+
+impl<T: Clone> Default for TokenCache<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::credentials::{
+        resolve_config_key, with_batch_credentials, with_credentials, with_job_credentials,
+        without_credentials, TokenCache,
+    };
+    use crate::golem::llm::batch::BatchRequest;
+    use crate::golem::llm::llm::{Config, ErrorCode, Kv};
+    use std::time::{Duration, SystemTime};
+
+    fn config(credentials: Option<Vec<Kv>>) -> Config {
+        Config {
+            model: "gpt-4o".to_string(),
+            temperature: None,
+            max_tokens: None,
+            stop_sequences: None,
+            top_p: None,
+            top_k: None,
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            repetition_penalty: None,
+            tools: vec![],
+            tool_choice: None,
+            provider_options: vec![],
+            credentials,
+        }
+    }
+
+    #[test]
+    fn per_call_credentials_are_scoped() {
+        let tenant_a = config(Some(vec![Kv {
+            key: "GOLEM_LLM_TEST_API_KEY".to_string(),
+            value: "tenant-a".to_string(),
+        }]));
+        let tenant_b = config(Some(vec![Kv {
+            key: "GOLEM_LLM_TEST_API_KEY".to_string(),
+            value: "tenant-b".to_string(),
+        }]));
+
+        with_credentials(&tenant_a, || {
+            assert_eq!(
+                resolve_config_key("GOLEM_LLM_TEST_API_KEY").unwrap(),
+                Some("tenant-a".to_string())
+            );
+            with_credentials(&tenant_b, || {
+                assert_eq!(
+                    resolve_config_key("GOLEM_LLM_TEST_API_KEY").unwrap(),
+                    Some("tenant-b".to_string())
+                );
+            });
+            with_credentials(&config(None), || {
+                assert_eq!(
+                    resolve_config_key("GOLEM_LLM_TEST_API_KEY").unwrap(),
+                    Some("tenant-a".to_string())
+                );
+            });
+        });
+        assert_eq!(resolve_config_key("GOLEM_LLM_TEST_API_KEY").unwrap(), None);
+    }
+
+    #[test]
+    fn batch_credentials_must_match() {
+        let request = |credentials: Option<Vec<Kv>>| BatchRequest {
+            custom_id: "request-1".to_string(),
+            messages: vec![],
+            config: config(credentials),
+        };
+        let tenant_a = Some(vec![Kv {
+            key: "GOLEM_LLM_TEST_BATCH_API_KEY".to_string(),
+            value: "tenant-a".to_string(),
+        }]);
+
+        let resolved = with_batch_credentials(
+            &[request(tenant_a.clone()), request(tenant_a.clone())],
+            || resolve_config_key("GOLEM_LLM_TEST_BATCH_API_KEY").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(resolved, Some("tenant-a".to_string()));
+
+        let error = with_batch_credentials(&[request(tenant_a), request(None)], || ()).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidRequest);
+    }
+
+    #[test]
+    fn job_credentials_are_scoped() {
+        let tenant_a = vec![Kv {
+            key: "GOLEM_LLM_TEST_JOB_API_KEY".to_string(),
+            value: "tenant-a".to_string(),
+        }];
+
+        with_job_credentials(Some(&tenant_a), || {
+            assert_eq!(
+                resolve_config_key("GOLEM_LLM_TEST_JOB_API_KEY").unwrap(),
+                Some("tenant-a".to_string())
+            );
+            with_job_credentials(None, || {
+                assert_eq!(
+                    resolve_config_key("GOLEM_LLM_TEST_JOB_API_KEY").unwrap(),
+                    Some("tenant-a".to_string())
+                );
+            });
+        });
+        assert_eq!(
+            resolve_config_key("GOLEM_LLM_TEST_JOB_API_KEY").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn credentials_are_removed_before_persisting() {
+        let config = without_credentials(config(Some(vec![Kv {
+            key: "OPENAI_API_KEY".to_string(),
+            value: "sk-secret".to_string(),
+        }])));
+        assert_eq!(config.credentials, None);
+        assert_eq!(config.model, "gpt-4o");
+    }
+
+    #[test]
+    fn expiring_tokens_are_not_returned() {
+        let cache = TokenCache::new();
+        cache.insert(
+            "valid".to_string(),
+            "token-1".to_string(),
+            SystemTime::now() + Duration::from_secs(3600),
+        );
+        cache.insert(
+            "expiring".to_string(),
+            "token-2".to_string(),
+            SystemTime::now() + Duration::from_secs(60),
+        );
+
+        assert_eq!(cache.get("valid"), Some("token-1".to_string()));
+        assert_eq!(cache.get("expiring"), None);
+        assert_eq!(cache.get("missing"), None);
+    }
+}
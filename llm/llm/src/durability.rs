@@ -1,4 +1,19 @@
-use crate::golem::llm::llm::{Config, ContentPart, Guest, Message, Role, StreamDelta};
+use crate::cache::{cached, CacheMode};
+use crate::credentials::with_credentials;
+use crate::error::unsupported;
+use crate::golem::llm::batch::Guest as BatchGuest;
+use crate::golem::llm::costs::{GenerationStats, Guest as CostsGuest};
+use crate::golem::llm::llm::{
+    ChatEvent, Config, ContentPart, Cost, Error, Guest, Message, Role, StreamDelta, ToolCall,
+    ToolResult,
+};
+use crate::golem::llm::prompts::{Guest as PromptsGuest, RenderedPrompt, TemplateVariable};
+use crate::guardrails::{
+    apply_local_input_policy, apply_local_output_policy, guarded, GuardrailOutcome,
+};
+use crate::pricing::{cumulative_cost, priced, reset_cumulative_cost};
+use crate::telemetry::traced;
+use crate::tool_validation::{validated, ToolValidation};
 use golem_rust::wasm_rpc::Pollable;
 use std::marker::PhantomData;
 
@@ -9,6 +24,9 @@ pub struct DurableLLM<Impl> {
 
 /// Trait to be implemented in addition to the LLM `Guest` trait when wrapping it with `DurableLLM`.
 pub trait ExtendedGuest: Guest + 'static {
+    /// Name of the provider, used to look up the built-in model prices of the pricing registry
+    const PROVIDER: &'static str;
+
     /// Creates an instance of the LLM specific `ChatStream` without wrapping it in a `Resource`
     fn unwrapped_stream(messages: Vec<Message>, config: Config) -> Self::ChatStream;
 
@@ -59,38 +77,318 @@ pub trait ExtendedGuest: Guest + 'static {
     }
 
     fn subscribe(stream: &Self::ChatStream) -> Pollable;
+
+    /// Guardrail hook applied to the messages before they are sent to the provider. It can redact
+    /// the messages, or block the request by returning an error. The default implementation applies
+    /// the input rules of the local policy configured in `GOLEM_LLM_GUARDRAIL_POLICY`, if any.
+    fn pre_send_guardrail(messages: Vec<Message>) -> Result<Vec<Message>, Error> {
+        apply_local_input_policy(messages)
+    }
+
+    /// Guardrail hook applied to the content received from the provider before it is returned to
+    /// the caller. Blocked responses are returned without content, with the `content-filter` finish
+    /// reason. The default implementation applies the output rules of the local policy, if any.
+    fn post_send_guardrail(content: Vec<ContentPart>) -> GuardrailOutcome<Vec<ContentPart>> {
+        apply_local_output_policy(content)
+    }
+
+    /// Looks up the statistics of a previous generation by the id of its response. The default
+    /// implementation fails with `unsupported`, as most providers don't keep them.
+    fn generation_stats(_response_id: String) -> Result<GenerationStats, Error> {
+        Err(unsupported(format!(
+            "{} does not support looking up generation statistics",
+            Self::PROVIDER
+        )))
+    }
+}
+
+/// Trait to be implemented in addition to the batch `Guest` trait when wrapping it with `DurableLLM`.
+/// The other optional interfaces are wrapped without any extra trait.
+pub trait ExtendedBatchGuest: BatchGuest + 'static {
+    /// Creates an instance of the LLM specific `BatchResultStream` without wrapping it in a `Resource`
+    fn unwrapped_batch_results(job_id: String) -> Self::BatchResultStream;
+}
+
+/// The cumulative costs are kept in memory, and rebuilt by recording the costs of the replayed
+/// responses again, so they are the same with and without durability
+impl<Impl: ExtendedGuest> CostsGuest for DurableLLM<Impl> {
+    fn get_cumulative_cost(conversation_id: Option<String>) -> Vec<Cost> {
+        cumulative_cost(conversation_id.as_deref())
+    }
+
+    fn reset_cumulative_cost(conversation_id: Option<String>) {
+        reset_cumulative_cost(conversation_id.as_deref())
+    }
+
+    fn get_generation_stats(response_id: String) -> Result<GenerationStats, Error> {
+        generation_stats::<Impl>(response_id)
+    }
+}
+
+/// Prompt templates are rendered by the library, the same way for every provider
+impl<Impl: ExtendedGuest> PromptsGuest for DurableLLM<Impl> {
+    fn render_prompt(
+        name: String,
+        version: Option<String>,
+        variables: Vec<TemplateVariable>,
+    ) -> Result<RenderedPrompt, Error> {
+        rendered_prompt(name, version, variables)
+    }
+}
+
+#[cfg(not(feature = "durability"))]
+use passthrough_impl::{generation_stats, rendered_prompt};
+
+#[cfg(feature = "durability")]
+use durable_impl::{generation_stats, rendered_prompt};
+
+/// Sends a chat request through the response cache, the cost calculation, the guardrails, the
+/// tool call validation and the tracing, with the per-call credentials applied. Without tool
+/// results the request is sent with `send`, otherwise it is continued with `continue`.
+fn chat_pipeline<Impl: ExtendedGuest>(
+    messages: &[Message],
+    tool_results: Option<&[(ToolCall, ToolResult)]>,
+    config: &Config,
+    cache_mode: CacheMode,
+    tool_validation: ToolValidation,
+) -> ChatEvent {
+    let call = || {
+        priced::<Impl>(
+            &config.model,
+            guarded::<Impl>(messages.to_vec(), |messages| {
+                validated(config, tool_validation, |feedback| {
+                    traced(Impl::PROVIDER, config, || {
+                        with_credentials(config, || match tool_results {
+                            None if feedback.is_empty() => {
+                                Impl::send(messages.clone(), config.clone())
+                            }
+                            _ => Impl::continue_(
+                                messages.clone(),
+                                [tool_results.unwrap_or_default().to_vec(), feedback].concat(),
+                                config.clone(),
+                            ),
+                        })
+                    })
+                })
+            }),
+        )
+    };
+    cached::<Impl>(
+        messages,
+        tool_results,
+        config,
+        cache_mode,
+        tool_validation,
+        call,
+    )
 }
 
 /// When the durability feature flag is off, wrapping with `DurableLLM` is just a passthrough
 #[cfg(not(feature = "durability"))]
 mod passthrough_impl {
-    use crate::durability::{DurableLLM, ExtendedGuest};
+    use crate::cache::take_cache_mode;
+    use crate::credentials::{with_batch_credentials, with_job_credentials};
+    use crate::durability::{chat_pipeline, DurableLLM, ExtendedBatchGuest, ExtendedGuest};
+    use crate::golem::llm::batch::{
+        BatchInfo, BatchRequest, BatchResultStream, Guest as BatchGuest,
+    };
+    use crate::golem::llm::costs::GenerationStats;
+    use crate::golem::llm::images::{Guest as ImagesGuest, ImageConfig, ImageResponse};
     use crate::golem::llm::llm::{
-        ChatEvent, ChatStream, Config, Guest, Message, ToolCall, ToolResult,
+        ChatEvent, ChatStream, Config, Error, Guest, GuestChatStream, ImageSource, Kv, Message,
+        StreamEvent, ToolCall, ToolResult,
     };
+    use crate::golem::llm::moderation::{
+        Guest as ModerationGuest, ModerationConfig, ModerationResponse,
+    };
+    use crate::golem::llm::prompts::{RenderedPrompt, TemplateVariable};
+    use crate::golem::llm::speech::{Guest as SpeechGuest, SpeechConfig, SpeechResult, VoiceInfo};
+    use crate::golem::llm::transcription::{
+        AudioSource, Guest as TranscriptionGuest, TranscriptionConfig, TranscriptionResult,
+    };
+    use crate::guardrails::GuardedChatStream;
     use crate::init_logging;
+    use crate::pricing::{record_chat_event_cost, record_stream_cost, take_conversation_id};
+    use crate::prompts::{render_prompt, take_prompt_template};
+    use crate::tool_validation::take_tool_validation;
 
     impl<Impl: ExtendedGuest> Guest for DurableLLM<Impl> {
-        type ChatStream = Impl::ChatStream;
+        type ChatStream = CostRecordingChatStream<Impl>;
 
-        fn send(messages: Vec<Message>, config: Config) -> ChatEvent {
+        fn send(messages: Vec<Message>, mut config: Config) -> ChatEvent {
             init_logging();
-            Impl::send(messages, config)
+            let conversation_id = take_conversation_id(&mut config);
+            // The prompt template is only recorded in the oplog, with durability enabled
+            take_prompt_template(&mut config);
+            let cache_mode = take_cache_mode(&mut config);
+            let tool_validation = take_tool_validation(&mut config);
+            let result =
+                chat_pipeline::<Impl>(&messages, None, &config, cache_mode, tool_validation);
+            record_chat_event_cost(conversation_id.as_deref(), &result);
+            result
         }
 
         fn continue_(
             messages: Vec<Message>,
             tool_results: Vec<(ToolCall, ToolResult)>,
-            config: Config,
+            mut config: Config,
         ) -> ChatEvent {
             init_logging();
-            Impl::continue_(messages, tool_results, config)
+            let conversation_id = take_conversation_id(&mut config);
+            // The prompt template is only recorded in the oplog, with durability enabled
+            take_prompt_template(&mut config);
+            let cache_mode = take_cache_mode(&mut config);
+            let tool_validation = take_tool_validation(&mut config);
+            let result = chat_pipeline::<Impl>(
+                &messages,
+                Some(&tool_results),
+                &config,
+                cache_mode,
+                tool_validation,
+            );
+            record_chat_event_cost(conversation_id.as_deref(), &result);
+            result
+        }
+
+        fn stream(messages: Vec<Message>, mut config: Config) -> ChatStream {
+            init_logging();
+            let conversation_id = take_conversation_id(&mut config);
+            // The prompt template is only recorded in the oplog, with durability enabled
+            take_prompt_template(&mut config);
+            // Streamed responses are not cached, and their tool calls are not validated
+            take_cache_mode(&mut config);
+            take_tool_validation(&mut config);
+            ChatStream::new(CostRecordingChatStream {
+                stream: GuardedChatStream::<Impl>::new(messages, config),
+                conversation_id,
+            })
+        }
+    }
+
+    /// Records the cost of the streamed response in the cumulative costs. With durability
+    /// enabled this is done by `DurableChatStream` instead.
+    pub struct CostRecordingChatStream<Impl: ExtendedGuest> {
+        stream: GuardedChatStream<Impl>,
+        conversation_id: Option<String>,
+    }
+
+    impl<Impl: ExtendedGuest> GuestChatStream for CostRecordingChatStream<Impl> {
+        fn get_next(&self) -> Option<Vec<StreamEvent>> {
+            let result = self.stream.get_next();
+            if let Some(events) = &result {
+                record_stream_cost(self.conversation_id.as_deref(), events);
+            }
+            result
+        }
+
+        fn blocking_get_next(&self) -> Vec<StreamEvent> {
+            let result = self.stream.blocking_get_next();
+            record_stream_cost(self.conversation_id.as_deref(), &result);
+            result
+        }
+    }
+
+    impl<Impl: ImagesGuest> ImagesGuest for DurableLLM<Impl> {
+        fn generate(prompt: String, config: ImageConfig) -> Result<ImageResponse, Error> {
+            init_logging();
+            Impl::generate(prompt, config)
+        }
+
+        fn edit(
+            image: ImageSource,
+            mask: Option<ImageSource>,
+            prompt: String,
+            config: ImageConfig,
+        ) -> Result<ImageResponse, Error> {
+            init_logging();
+            Impl::edit(image, mask, prompt, config)
+        }
+
+        fn create_variations(
+            image: ImageSource,
+            config: ImageConfig,
+        ) -> Result<ImageResponse, Error> {
+            init_logging();
+            Impl::create_variations(image, config)
+        }
+    }
+
+    impl<Impl: TranscriptionGuest> TranscriptionGuest for DurableLLM<Impl> {
+        fn transcribe(
+            audio: AudioSource,
+            config: TranscriptionConfig,
+        ) -> Result<TranscriptionResult, Error> {
+            init_logging();
+            Impl::transcribe(audio, config)
+        }
+    }
+
+    impl<Impl: SpeechGuest> SpeechGuest for DurableLLM<Impl> {
+        fn synthesize(text: String, config: SpeechConfig) -> Result<SpeechResult, Error> {
+            init_logging();
+            Impl::synthesize(text, config)
+        }
+
+        fn list_voices(language: Option<String>) -> Result<Vec<VoiceInfo>, Error> {
+            init_logging();
+            Impl::list_voices(language)
+        }
+    }
+
+    impl<Impl: ModerationGuest> ModerationGuest for DurableLLM<Impl> {
+        fn moderate(
+            inputs: Vec<String>,
+            config: ModerationConfig,
+        ) -> Result<ModerationResponse, Error> {
+            init_logging();
+            Impl::moderate(inputs, config)
+        }
+    }
+
+    impl<Impl: ExtendedBatchGuest> BatchGuest for DurableLLM<Impl> {
+        type BatchResultStream = Impl::BatchResultStream;
+
+        fn submit(requests: Vec<BatchRequest>) -> Result<String, Error> {
+            init_logging();
+            with_batch_credentials(&requests, || Impl::submit(requests.clone()))?
+        }
+
+        fn poll(job_id: String, credentials: Option<Vec<Kv>>) -> Result<BatchInfo, Error> {
+            init_logging();
+            with_job_credentials(credentials.as_deref(), || {
+                Impl::poll(job_id, credentials.clone())
+            })
         }
 
-        fn stream(messages: Vec<Message>, config: Config) -> ChatStream {
+        fn cancel(job_id: String, credentials: Option<Vec<Kv>>) -> Result<String, Error> {
             init_logging();
-            Impl::stream(messages, config)
+            with_job_credentials(credentials.as_deref(), || {
+                Impl::cancel(job_id, credentials.clone())
+            })
         }
+
+        fn results(job_id: String, credentials: Option<Vec<Kv>>) -> BatchResultStream {
+            init_logging();
+            with_job_credentials(credentials.as_deref(), || {
+                Impl::results(job_id, credentials.clone())
+            })
+        }
+    }
+
+    pub(super) fn generation_stats<Impl: ExtendedGuest>(
+        response_id: String,
+    ) -> Result<GenerationStats, Error> {
+        init_logging();
+        Impl::generation_stats(response_id)
+    }
+
+    pub(super) fn rendered_prompt(
+        name: String,
+        version: Option<String>,
+        variables: Vec<TemplateVariable>,
+    ) -> Result<RenderedPrompt, Error> {
+        init_logging();
+        render_prompt(&name, version.as_deref(), &variables)
     }
 }
 
@@ -104,12 +402,32 @@ mod passthrough_impl {
 /// which is implemented using the type classes and builder in the `golem-rust` library.
 #[cfg(feature = "durability")]
 mod durable_impl {
-    use crate::durability::{DurableLLM, ExtendedGuest};
+    use crate::cache::take_cache_mode;
+    use crate::credentials::{with_batch_credentials, with_job_credentials, without_credentials};
+    use crate::durability::{chat_pipeline, DurableLLM, ExtendedBatchGuest, ExtendedGuest};
+    use crate::golem::llm::batch::{
+        BatchInfo, BatchRequest, BatchResult, BatchResultStream, Guest as BatchGuest,
+        GuestBatchResultStream,
+    };
+    use crate::golem::llm::costs::GenerationStats;
+    use crate::golem::llm::images::{Guest as ImagesGuest, ImageConfig, ImageResponse};
     use crate::golem::llm::llm::{
-        ChatEvent, ChatStream, Config, Guest, GuestChatStream, Message, StreamDelta, StreamEvent,
-        ToolCall, ToolResult,
+        ChatEvent, ChatStream, Config, Error, Guest, GuestChatStream, ImageSource, Kv, Message,
+        StreamDelta, StreamEvent, ToolCall, ToolResult,
+    };
+    use crate::golem::llm::moderation::{
+        Guest as ModerationGuest, ModerationConfig, ModerationResponse,
     };
+    use crate::golem::llm::prompts::{RenderedPrompt, TemplateVariable};
+    use crate::golem::llm::speech::{Guest as SpeechGuest, SpeechConfig, SpeechResult, VoiceInfo};
+    use crate::golem::llm::transcription::{
+        AudioSource, Guest as TranscriptionGuest, TranscriptionConfig, TranscriptionResult,
+    };
+    use crate::guardrails::GuardedChatStream;
     use crate::init_logging;
+    use crate::pricing::{record_chat_event_cost, record_stream_cost, take_conversation_id};
+    use crate::prompts::{render_prompt, take_prompt_template};
+    use crate::tool_validation::take_tool_validation;
     use golem_rust::bindings::golem::durability::durability::DurableFunctionType;
     #[cfg(not(feature = "nopoll"))]
     use golem_rust::bindings::golem::durability::durability::LazyInitializedPollable;
@@ -122,55 +440,86 @@ mod durable_impl {
     impl<Impl: ExtendedGuest> Guest for DurableLLM<Impl> {
         type ChatStream = DurableChatStream<Impl>;
 
-        fn send(messages: Vec<Message>, config: Config) -> ChatEvent {
+        fn send(messages: Vec<Message>, mut config: Config) -> ChatEvent {
             init_logging();
+            let conversation_id = take_conversation_id(&mut config);
+            let prompt_template = take_prompt_template(&mut config);
+            let cache_mode = take_cache_mode(&mut config);
+            let tool_validation = take_tool_validation(&mut config);
 
             let durability = Durability::<ChatEvent, UnusedError>::new(
                 "golem_llm",
                 "send",
                 DurableFunctionType::WriteRemote,
             );
-            if durability.is_live() {
+            let result = if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::send(messages.clone(), config.clone())
+                    chat_pipeline::<Impl>(&messages, None, &config, cache_mode, tool_validation)
                 });
-                durability.persist_infallible(SendInput { messages, config }, result)
+                durability.persist_infallible(
+                    SendInput {
+                        messages,
+                        config: without_credentials(config),
+                        prompt_template,
+                    },
+                    result,
+                )
             } else {
                 durability.replay_infallible()
-            }
+            };
+            record_chat_event_cost(conversation_id.as_deref(), &result);
+            result
         }
 
         fn continue_(
             messages: Vec<Message>,
             tool_results: Vec<(ToolCall, ToolResult)>,
-            config: Config,
+            mut config: Config,
         ) -> ChatEvent {
             init_logging();
+            let conversation_id = take_conversation_id(&mut config);
+            let prompt_template = take_prompt_template(&mut config);
+            let cache_mode = take_cache_mode(&mut config);
+            let tool_validation = take_tool_validation(&mut config);
 
             let durability = Durability::<ChatEvent, UnusedError>::new(
                 "golem_llm",
                 "continue",
                 DurableFunctionType::WriteRemote,
             );
-            if durability.is_live() {
+            let result = if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::continue_(messages.clone(), tool_results.clone(), config.clone())
+                    chat_pipeline::<Impl>(
+                        &messages,
+                        Some(&tool_results),
+                        &config,
+                        cache_mode,
+                        tool_validation,
+                    )
                 });
                 durability.persist_infallible(
                     ContinueInput {
                         messages,
                         tool_results,
-                        config,
+                        config: without_credentials(config),
+                        prompt_template,
                     },
                     result,
                 )
             } else {
                 durability.replay_infallible()
-            }
+            };
+            record_chat_event_cost(conversation_id.as_deref(), &result);
+            result
         }
 
-        fn stream(messages: Vec<Message>, config: Config) -> ChatStream {
+        fn stream(messages: Vec<Message>, mut config: Config) -> ChatStream {
             init_logging();
+            let conversation_id = take_conversation_id(&mut config);
+            let prompt_template = take_prompt_template(&mut config);
+            // Streamed responses are not cached, and their tool calls are not validated
+            take_cache_mode(&mut config);
+            take_tool_validation(&mut config);
 
             let durability = Durability::<NoOutput, UnusedError>::new(
                 "golem_llm",
@@ -179,16 +528,460 @@ mod durable_impl {
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    ChatStream::new(DurableChatStream::<Impl>::live(Impl::unwrapped_stream(
-                        messages.clone(),
-                        config.clone(),
-                    )))
+                    ChatStream::new(DurableChatStream::<Impl>::live(
+                        GuardedChatStream::new(messages.clone(), config.clone()),
+                        conversation_id,
+                    ))
                 });
-                let _ = durability.persist_infallible(SendInput { messages, config }, NoOutput);
+                let _ = durability.persist_infallible(
+                    SendInput {
+                        messages,
+                        config: without_credentials(config),
+                        prompt_template,
+                    },
+                    NoOutput,
+                );
                 result
             } else {
                 let _: NoOutput = durability.replay_infallible();
-                ChatStream::new(DurableChatStream::<Impl>::replay(messages, config))
+                ChatStream::new(DurableChatStream::<Impl>::replay(
+                    messages,
+                    config,
+                    conversation_id,
+                ))
+            }
+        }
+    }
+
+    impl<Impl: ImagesGuest> ImagesGuest for DurableLLM<Impl> {
+        fn generate(prompt: String, config: ImageConfig) -> Result<ImageResponse, Error> {
+            init_logging();
+
+            let durability = Durability::<ImageResponse, Error>::new(
+                "golem_llm",
+                "generate_image",
+                DurableFunctionType::WriteRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    Impl::generate(prompt.clone(), config.clone())
+                });
+                durability.persist(GenerateImageInput { prompt, config }, result)
+            } else {
+                durability.replay()
+            }
+        }
+
+        fn edit(
+            image: ImageSource,
+            mask: Option<ImageSource>,
+            prompt: String,
+            config: ImageConfig,
+        ) -> Result<ImageResponse, Error> {
+            init_logging();
+
+            let durability = Durability::<ImageResponse, Error>::new(
+                "golem_llm",
+                "edit_image",
+                DurableFunctionType::WriteRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    Impl::edit(image.clone(), mask.clone(), prompt.clone(), config.clone())
+                });
+                durability.persist(
+                    EditImageInput {
+                        image,
+                        mask,
+                        prompt,
+                        config,
+                    },
+                    result,
+                )
+            } else {
+                durability.replay()
+            }
+        }
+
+        fn create_variations(
+            image: ImageSource,
+            config: ImageConfig,
+        ) -> Result<ImageResponse, Error> {
+            init_logging();
+
+            let durability = Durability::<ImageResponse, Error>::new(
+                "golem_llm",
+                "create_image_variations",
+                DurableFunctionType::WriteRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    Impl::create_variations(image.clone(), config.clone())
+                });
+                durability.persist(CreateImageVariationsInput { image, config }, result)
+            } else {
+                durability.replay()
+            }
+        }
+    }
+
+    impl<Impl: TranscriptionGuest> TranscriptionGuest for DurableLLM<Impl> {
+        fn transcribe(
+            audio: AudioSource,
+            config: TranscriptionConfig,
+        ) -> Result<TranscriptionResult, Error> {
+            init_logging();
+
+            let durability = Durability::<TranscriptionResult, Error>::new(
+                "golem_llm",
+                "transcribe",
+                DurableFunctionType::WriteRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    Impl::transcribe(audio.clone(), config.clone())
+                });
+                durability.persist(TranscribeInput { audio, config }, result)
+            } else {
+                durability.replay()
+            }
+        }
+    }
+
+    impl<Impl: SpeechGuest> SpeechGuest for DurableLLM<Impl> {
+        fn synthesize(text: String, config: SpeechConfig) -> Result<SpeechResult, Error> {
+            init_logging();
+
+            let durability = Durability::<SpeechResult, Error>::new(
+                "golem_llm",
+                "synthesize",
+                DurableFunctionType::WriteRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    Impl::synthesize(text.clone(), config.clone())
+                });
+                durability.persist(SynthesizeInput { text, config }, result)
+            } else {
+                durability.replay()
+            }
+        }
+
+        fn list_voices(language: Option<String>) -> Result<Vec<VoiceInfo>, Error> {
+            init_logging();
+
+            let durability = Durability::<Vec<VoiceInfo>, Error>::new(
+                "golem_llm",
+                "list_voices",
+                DurableFunctionType::ReadRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    Impl::list_voices(language.clone())
+                });
+                durability.persist(ListVoicesInput { language }, result)
+            } else {
+                durability.replay()
+            }
+        }
+    }
+
+    impl<Impl: ModerationGuest> ModerationGuest for DurableLLM<Impl> {
+        fn moderate(
+            inputs: Vec<String>,
+            config: ModerationConfig,
+        ) -> Result<ModerationResponse, Error> {
+            init_logging();
+
+            let durability = Durability::<ModerationResponse, Error>::new(
+                "golem_llm",
+                "moderate",
+                DurableFunctionType::ReadRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    Impl::moderate(inputs.clone(), config.clone())
+                });
+                durability.persist(ModerateInput { inputs, config }, result)
+            } else {
+                durability.replay()
+            }
+        }
+    }
+
+    impl<Impl: ExtendedBatchGuest> BatchGuest for DurableLLM<Impl> {
+        type BatchResultStream = DurableBatchResultStream<Impl>;
+
+        fn submit(requests: Vec<BatchRequest>) -> Result<String, Error> {
+            init_logging();
+
+            let durability = Durability::<String, Error>::new(
+                "golem_llm",
+                "submit_batch",
+                DurableFunctionType::WriteRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    with_batch_credentials(&requests, || Impl::submit(requests.clone()))?
+                });
+                let requests = requests
+                    .into_iter()
+                    .map(|request| BatchRequest {
+                        config: without_credentials(request.config),
+                        ..request
+                    })
+                    .collect();
+                durability.persist(SubmitBatchInput { requests }, result)
+            } else {
+                durability.replay()
+            }
+        }
+
+        fn poll(job_id: String, credentials: Option<Vec<Kv>>) -> Result<BatchInfo, Error> {
+            init_logging();
+
+            let durability = Durability::<BatchInfo, Error>::new(
+                "golem_llm",
+                "poll_batch",
+                DurableFunctionType::ReadRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    with_job_credentials(credentials.as_deref(), || {
+                        Impl::poll(job_id.clone(), credentials.clone())
+                    })
+                });
+                durability.persist(BatchJobInput { job_id }, result)
+            } else {
+                durability.replay()
+            }
+        }
+
+        fn cancel(job_id: String, credentials: Option<Vec<Kv>>) -> Result<String, Error> {
+            init_logging();
+
+            let durability = Durability::<String, Error>::new(
+                "golem_llm",
+                "cancel_batch",
+                DurableFunctionType::WriteRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    with_job_credentials(credentials.as_deref(), || {
+                        Impl::cancel(job_id.clone(), credentials.clone())
+                    })
+                });
+                durability.persist(BatchJobInput { job_id }, result)
+            } else {
+                durability.replay()
+            }
+        }
+
+        fn results(job_id: String, credentials: Option<Vec<Kv>>) -> BatchResultStream {
+            init_logging();
+
+            let durability = Durability::<NoOutput, UnusedError>::new(
+                "golem_llm",
+                "batch_results",
+                DurableFunctionType::ReadRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    BatchResultStream::new(DurableBatchResultStream::<Impl>::live(
+                        with_job_credentials(credentials.as_deref(), || {
+                            Impl::unwrapped_batch_results(job_id.clone())
+                        }),
+                    ))
+                });
+                let _ = durability.persist_infallible(BatchJobInput { job_id }, NoOutput);
+                result
+            } else {
+                let _: NoOutput = durability.replay_infallible();
+                BatchResultStream::new(DurableBatchResultStream::<Impl>::replay(
+                    job_id,
+                    credentials,
+                ))
+            }
+        }
+    }
+
+    /// Represents the durable batch result stream's state
+    ///
+    /// In live mode it directly calls the underlying batch result stream. In replay mode it
+    /// only counts the replayed results, and when reaching the end of the replay mode it creates
+    /// a new live stream and skips the results that were already returned. The per-call
+    /// credentials are only kept in memory, for creating the live stream.
+    enum DurableBatchResultStreamState<Impl: ExtendedBatchGuest> {
+        Live {
+            stream: Impl::BatchResultStream,
+        },
+        Replay {
+            job_id: String,
+            credentials: Option<Vec<Kv>>,
+            consumed: usize,
+        },
+    }
+
+    pub struct DurableBatchResultStream<Impl: ExtendedBatchGuest> {
+        state: RefCell<Option<DurableBatchResultStreamState<Impl>>>,
+    }
+
+    impl<Impl: ExtendedBatchGuest> DurableBatchResultStream<Impl> {
+        fn live(stream: Impl::BatchResultStream) -> Self {
+            Self {
+                state: RefCell::new(Some(DurableBatchResultStreamState::Live { stream })),
+            }
+        }
+
+        fn replay(job_id: String, credentials: Option<Vec<Kv>>) -> Self {
+            Self {
+                state: RefCell::new(Some(DurableBatchResultStreamState::Replay {
+                    job_id,
+                    credentials,
+                    consumed: 0,
+                })),
+            }
+        }
+    }
+
+    impl<Impl: ExtendedBatchGuest> Drop for DurableBatchResultStream<Impl> {
+        fn drop(&mut self) {
+            if let Some(DurableBatchResultStreamState::Live { stream }) = self.state.take() {
+                with_persistence_level(PersistenceLevel::PersistNothing, move || {
+                    drop(stream);
+                });
+            }
+        }
+    }
+
+    impl<Impl: ExtendedBatchGuest> GuestBatchResultStream for DurableBatchResultStream<Impl> {
+        fn get_next(&self) -> Result<Option<Vec<BatchResult>>, Error> {
+            let durability = Durability::<Option<Vec<BatchResult>>, Error>::new(
+                "golem_llm",
+                "batch_results_get_next",
+                DurableFunctionType::ReadRemote,
+            );
+            let mut state = self.state.borrow_mut();
+            if durability.is_live() {
+                let (result, new_live_stream) = match &*state {
+                    Some(DurableBatchResultStreamState::Live { stream }) => {
+                        let result =
+                            with_persistence_level(PersistenceLevel::PersistNothing, || {
+                                stream.get_next()
+                            });
+                        (result, None)
+                    }
+                    Some(DurableBatchResultStreamState::Replay {
+                        job_id,
+                        credentials,
+                        consumed,
+                    }) => {
+                        let (stream, result) =
+                            with_persistence_level(PersistenceLevel::PersistNothing, || {
+                                let stream = with_job_credentials(credentials.as_deref(), || {
+                                    Impl::unwrapped_batch_results(job_id.clone())
+                                });
+                                let result = skip_batch_results(&stream, *consumed);
+                                (stream, result)
+                            });
+                        (result, Some(stream))
+                    }
+                    None => {
+                        unreachable!()
+                    }
+                };
+
+                if let Some(stream) = new_live_stream {
+                    *state = Some(DurableBatchResultStreamState::Live { stream });
+                }
+
+                durability.persist(NoInput, result)
+            } else {
+                let result: Result<Option<Vec<BatchResult>>, Error> = durability.replay();
+                match &mut *state {
+                    Some(DurableBatchResultStreamState::Live { .. }) => {
+                        unreachable!(
+                            "Durable batch result stream cannot be in live mode during replay"
+                        )
+                    }
+                    Some(DurableBatchResultStreamState::Replay { consumed, .. }) => {
+                        if let Ok(Some(results)) = &result {
+                            *consumed += results.len();
+                        }
+                    }
+                    None => {
+                        unreachable!()
+                    }
+                }
+                result
+            }
+        }
+    }
+
+    pub(super) fn generation_stats<Impl: ExtendedGuest>(
+        response_id: String,
+    ) -> Result<GenerationStats, Error> {
+        init_logging();
+
+        let durability = Durability::<GenerationStats, Error>::new(
+            "golem_llm",
+            "get_generation_stats",
+            DurableFunctionType::ReadRemote,
+        );
+        if durability.is_live() {
+            let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                Impl::generation_stats(response_id.clone())
+            });
+            durability.persist(GenerationStatsInput { response_id }, result)
+        } else {
+            durability.replay()
+        }
+    }
+
+    /// Templates may be loaded from the imported prompt store, so the rendered prompt is
+    /// persisted to get the same messages during replay
+    pub(super) fn rendered_prompt(
+        name: String,
+        version: Option<String>,
+        variables: Vec<TemplateVariable>,
+    ) -> Result<RenderedPrompt, Error> {
+        init_logging();
+
+        let durability = Durability::<RenderedPrompt, Error>::new(
+            "golem_llm",
+            "render_prompt",
+            DurableFunctionType::ReadRemote,
+        );
+        if durability.is_live() {
+            let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                render_prompt(&name, version.as_deref(), &variables)
+            });
+            durability.persist(
+                RenderPromptInput {
+                    name,
+                    version,
+                    variables,
+                },
+                result,
+            )
+        } else {
+            durability.replay()
+        }
+    }
+
+    /// Gets the next page of results from a newly created batch result stream, after skipping
+    /// the given number of results
+    fn skip_batch_results<S: GuestBatchResultStream>(
+        stream: &S,
+        mut to_skip: usize,
+    ) -> Result<Option<Vec<BatchResult>>, Error> {
+        loop {
+            match stream.get_next()? {
+                Some(results) if to_skip > 0 && results.len() <= to_skip => {
+                    to_skip -= results.len();
+                }
+                Some(results) => return Ok(Some(results.into_iter().skip(to_skip).collect())),
+                None => return Ok(None),
             }
         }
     }
@@ -210,7 +1003,7 @@ mod durable_impl {
     /// stream and continue the response seamlessly.
     enum DurableChatStreamState<Impl: ExtendedGuest> {
         Live {
-            stream: Impl::ChatStream,
+            stream: GuardedChatStream<Impl>,
             #[cfg(not(feature = "nopoll"))]
             pollables: Vec<LazyInitializedPollable>,
         },
@@ -227,10 +1020,11 @@ mod durable_impl {
     pub struct DurableChatStream<Impl: ExtendedGuest> {
         state: RefCell<Option<DurableChatStreamState<Impl>>>,
         subscription: RefCell<Option<Pollable>>,
+        conversation_id: Option<String>,
     }
 
     impl<Impl: ExtendedGuest> DurableChatStream<Impl> {
-        fn live(stream: Impl::ChatStream) -> Self {
+        fn live(stream: GuardedChatStream<Impl>, conversation_id: Option<String>) -> Self {
             Self {
                 state: RefCell::new(Some(DurableChatStreamState::Live {
                     stream,
@@ -238,10 +1032,15 @@ mod durable_impl {
                     pollables: Vec::new(),
                 })),
                 subscription: RefCell::new(None),
+                conversation_id,
             }
         }
 
-        fn replay(original_messages: Vec<Message>, config: Config) -> Self {
+        fn replay(
+            original_messages: Vec<Message>,
+            config: Config,
+            conversation_id: Option<String>,
+        ) -> Self {
             Self {
                 state: RefCell::new(Some(DurableChatStreamState::Replay {
                     original_messages,
@@ -252,13 +1051,14 @@ mod durable_impl {
                     finished: false,
                 })),
                 subscription: RefCell::new(None),
+                conversation_id,
             }
         }
         #[cfg(not(feature = "nopoll"))]
         fn subscribe(&self) -> Pollable {
             let mut state = self.state.borrow_mut();
             match &mut *state {
-                Some(DurableChatStreamState::Live { stream, .. }) => Impl::subscribe(stream),
+                Some(DurableChatStreamState::Live { stream, .. }) => stream.subscribe(),
                 Some(DurableChatStreamState::Replay { pollables, .. }) => {
                     let lazy_pollable = LazyInitializedPollable::new();
                     let pollable = lazy_pollable.subscribe();
@@ -308,7 +1108,7 @@ mod durable_impl {
                 "get_next",
                 DurableFunctionType::ReadRemote,
             );
-            if durability.is_live() {
+            let result = if durability.is_live() {
                 let mut state = self.state.borrow_mut();
                 let (result, new_live_stream) = match &*state {
                     Some(DurableChatStreamState::Live { stream, .. }) => {
@@ -334,13 +1134,13 @@ mod durable_impl {
 
                             let (stream, first_live_result) =
                                 with_persistence_level(PersistenceLevel::PersistNothing, || {
-                                    let stream = <Impl as ExtendedGuest>::unwrapped_stream(
+                                    let stream = GuardedChatStream::<Impl>::retry(
                                         extended_messages,
                                         config.clone(),
                                     );
                                     #[cfg(not(feature = "nopoll"))]
                                     for lazy_initialized_pollable in pollables {
-                                        lazy_initialized_pollable.set(Impl::subscribe(&stream));
+                                        lazy_initialized_pollable.set(stream.subscribe());
                                     }
 
                                     let next = stream.get_next();
@@ -406,7 +1206,11 @@ mod durable_impl {
                     }
                 }
                 result
+            };
+            if let Some(events) = &result {
+                record_stream_cost(self.conversation_id.as_deref(), events);
             }
+            result
         }
 
         fn blocking_get_next(&self) -> Vec<StreamEvent> {
@@ -437,6 +1241,8 @@ mod durable_impl {
     struct SendInput {
         messages: Vec<Message>,
         config: Config,
+        /// Identifies the prompt template which produced the messages
+        prompt_template: Option<String>,
     }
 
     #[derive(Debug, IntoValue)]
@@ -444,6 +1250,72 @@ mod durable_impl {
         messages: Vec<Message>,
         tool_results: Vec<(ToolCall, ToolResult)>,
         config: Config,
+        prompt_template: Option<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, IntoValue)]
+    struct GenerateImageInput {
+        prompt: String,
+        config: ImageConfig,
+    }
+
+    #[derive(Debug, Clone, PartialEq, FromValueAndType, IntoValue)]
+    struct EditImageInput {
+        image: ImageSource,
+        mask: Option<ImageSource>,
+        prompt: String,
+        config: ImageConfig,
+    }
+
+    #[derive(Debug, Clone, PartialEq, IntoValue)]
+    struct CreateImageVariationsInput {
+        image: ImageSource,
+        config: ImageConfig,
+    }
+
+    #[derive(Debug, Clone, PartialEq, IntoValue)]
+    struct TranscribeInput {
+        audio: AudioSource,
+        config: TranscriptionConfig,
+    }
+
+    #[derive(Debug, Clone, PartialEq, IntoValue)]
+    struct SynthesizeInput {
+        text: String,
+        config: SpeechConfig,
+    }
+
+    #[derive(Debug, Clone, PartialEq, IntoValue)]
+    struct ListVoicesInput {
+        language: Option<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, IntoValue)]
+    struct SubmitBatchInput {
+        requests: Vec<BatchRequest>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, IntoValue)]
+    struct BatchJobInput {
+        job_id: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, IntoValue)]
+    struct GenerationStatsInput {
+        response_id: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, IntoValue)]
+    struct RenderPromptInput {
+        name: String,
+        version: Option<String>,
+        variables: Vec<TemplateVariable>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, IntoValue)]
+    struct ModerateInput {
+        inputs: Vec<String>,
+        config: ModerationConfig,
     }
 
     #[derive(Debug, IntoValue)]
@@ -461,13 +1333,32 @@ mod durable_impl {
         }
     }
 
+    impl From<&Error> for Error {
+        fn from(error: &Error) -> Self {
+            error.clone()
+        }
+    }
+
     #[cfg(test)]
     mod tests {
-        use crate::durability::durable_impl::SendInput;
+        use crate::durability::durable_impl::{EditImageInput, SendInput};
+        use crate::golem::llm::batch::{BatchInfo, BatchRequestCounts, BatchResult, BatchStatus};
+        use crate::golem::llm::images::{
+            GeneratedImage, ImageConfig, ImageQuality, ImageResponse, ImageResponseFormat,
+            ImageSize,
+        };
         use crate::golem::llm::llm::{
-            ChatEvent, CompleteResponse, Config, ContentPart, Error, ErrorCode, FinishReason,
-            ImageDetail, ImageReference, ImageSource, ImageUrl, Message, ResponseMetadata, Role,
-            ToolCall, Usage,
+            ChatEvent, CompleteResponse, Config, ContentPart, Cost, Error, ErrorCode, FinishReason,
+            ImageDetail, ImageReference, ImageSource, ImageUrl, Kv, Message, ResponseMetadata,
+            Role, ToolCall, Usage,
+        };
+        use crate::golem::llm::moderation::{
+            CategoryScore, ModerationConfig, ModerationResponse, ModerationResult,
+        };
+        use crate::golem::llm::speech::{AudioFormat, SpeechConfig, SpeechResult};
+        use crate::golem::llm::transcription::{
+            TimestampGranularity, TranscriptionConfig, TranscriptionResult, TranscriptionSegment,
+            TranscriptionWord,
         };
         use golem_rust::value_and_type::{FromValueAndType, IntoValueAndType};
         use golem_rust::wasm_rpc::WitTypeNode;
@@ -548,11 +1439,15 @@ mod durable_impl {
                 input_tokens: Some(100),
                 output_tokens: Some(200),
                 total_tokens: Some(300),
+                cached_input_tokens: Some(50),
+                reasoning_tokens: Some(120),
             });
             roundtrip_test(Usage {
                 input_tokens: None,
                 output_tokens: None,
                 total_tokens: None,
+                cached_input_tokens: None,
+                reasoning_tokens: None,
             });
         }
 
@@ -564,10 +1459,20 @@ mod durable_impl {
                     input_tokens: Some(100),
                     output_tokens: None,
                     total_tokens: Some(100),
+                    cached_input_tokens: Some(20),
+                    reasoning_tokens: None,
                 }),
                 provider_id: Some("provider_id".to_string()),
                 timestamp: Some("2023-10-01T00:00:00Z".to_string()),
                 provider_metadata_json: Some("{\"key\": \"value\"}".to_string()),
+                cost: Some(Cost {
+                    input_cost: 0.0002,
+                    output_cost: 0.0,
+                    cached_input_cost: 0.000025,
+                    reasoning_cost: 0.0,
+                    total_cost: 0.000225,
+                    currency: "USD".to_string(),
+                }),
             });
             roundtrip_test(ResponseMetadata {
                 finish_reason: None,
@@ -575,6 +1480,7 @@ mod durable_impl {
                 provider_id: None,
                 timestamp: None,
                 provider_metadata_json: None,
+                cost: None,
             });
         }
 
@@ -600,6 +1506,7 @@ mod durable_impl {
                     provider_id: None,
                     timestamp: None,
                     provider_metadata_json: None,
+                    cost: None,
                 },
             });
         }
@@ -626,6 +1533,7 @@ mod durable_impl {
                     provider_id: None,
                     timestamp: None,
                     provider_metadata_json: None,
+                    cost: None,
                 },
             }));
             roundtrip_test(ChatEvent::ToolRequest(vec![ToolCall {
@@ -640,6 +1548,210 @@ mod durable_impl {
             }));
         }
 
+        #[test]
+        fn image_config_roundtrip() {
+            roundtrip_test(ImageConfig {
+                model: Some("gpt-image-1".to_string()),
+                size: Some(ImageSize {
+                    width: 1024,
+                    height: 1024,
+                }),
+                quality: Some(ImageQuality::High),
+                style: Some("vivid".to_string()),
+                seed: Some(42),
+                count: Some(2),
+                negative_prompt: Some("blurry".to_string()),
+                response_format: Some(ImageResponseFormat::Inline),
+                provider_options: vec![Kv {
+                    key: "background".to_string(),
+                    value: "transparent".to_string(),
+                }],
+            });
+            roundtrip_test(ImageConfig {
+                model: None,
+                size: None,
+                quality: None,
+                style: None,
+                seed: None,
+                count: None,
+                negative_prompt: None,
+                response_format: None,
+                provider_options: vec![],
+            });
+        }
+
+        #[test]
+        fn image_response_roundtrip() {
+            roundtrip_test(ImageResponse {
+                images: vec![
+                    GeneratedImage {
+                        image: ImageReference::Url(ImageUrl {
+                            url: "https://example.com/image.png".to_string(),
+                            detail: None,
+                        }),
+                        revised_prompt: Some("A cat sitting on a mat".to_string()),
+                        seed: None,
+                    },
+                    GeneratedImage {
+                        image: ImageReference::Inline(ImageSource {
+                            data: vec![0, 1, 2, 3, 4, 5],
+                            mime_type: "image/png".to_string(),
+                            detail: None,
+                        }),
+                        revised_prompt: None,
+                        seed: Some(1234),
+                    },
+                ],
+                provider_id: Some("img_123".to_string()),
+                timestamp: Some("2023-10-01T00:00:00Z".to_string()),
+                provider_metadata_json: None,
+            });
+        }
+
+        #[test]
+        fn transcription_config_roundtrip() {
+            roundtrip_test(TranscriptionConfig {
+                model: Some("whisper-1".to_string()),
+                language: Some("en".to_string()),
+                prompt: Some("Call center conversation".to_string()),
+                temperature: Some(0.0),
+                timestamp_granularities: vec![
+                    TimestampGranularity::Segment,
+                    TimestampGranularity::Word,
+                ],
+                diarization: true,
+                provider_options: vec![],
+            });
+        }
+
+        #[test]
+        fn transcription_result_roundtrip() {
+            roundtrip_test(TranscriptionResult {
+                text: "Hello, how can I help you?".to_string(),
+                language: Some("english".to_string()),
+                duration: Some(2.5),
+                segments: vec![TranscriptionSegment {
+                    id: 0,
+                    text: "Hello, how can I help you?".to_string(),
+                    start: 0.0,
+                    end: 2.5,
+                    speaker: Some("A".to_string()),
+                }],
+                words: vec![TranscriptionWord {
+                    text: "Hello".to_string(),
+                    start: 0.0,
+                    end: 0.4,
+                    speaker: None,
+                }],
+                provider_metadata_json: None,
+            });
+        }
+
+        #[test]
+        fn speech_roundtrip() {
+            roundtrip_test(SpeechConfig {
+                model: Some("tts-1".to_string()),
+                voice: "alloy".to_string(),
+                format: Some(AudioFormat::Wav),
+                speed: Some(1.25),
+                instructions: None,
+                provider_options: vec![],
+            });
+            roundtrip_test(SpeechResult {
+                data: vec![82, 73, 70, 70],
+                mime_type: "audio/wav".to_string(),
+                provider_metadata_json: None,
+            });
+        }
+
+        #[test]
+        fn moderation_roundtrip() {
+            roundtrip_test(ModerationConfig {
+                model: Some("omni-moderation-latest".to_string()),
+                provider_options: vec![],
+            });
+            roundtrip_test(ModerationResponse {
+                results: vec![ModerationResult {
+                    flagged: true,
+                    categories: vec![
+                        CategoryScore {
+                            category: "violence".to_string(),
+                            flagged: true,
+                            score: Some(0.92),
+                        },
+                        CategoryScore {
+                            category: "harassment".to_string(),
+                            flagged: false,
+                            score: None,
+                        },
+                    ],
+                }],
+                model: Some("omni-moderation-latest".to_string()),
+                provider_id: Some("modr-123".to_string()),
+                provider_metadata_json: None,
+            });
+        }
+
+        #[test]
+        fn batch_roundtrip() {
+            roundtrip_test(BatchInfo {
+                job_id: "batch_123".to_string(),
+                status: BatchStatus::Failed("input file is invalid".to_string()),
+                request_counts: Some(BatchRequestCounts {
+                    total: 10,
+                    succeeded: 7,
+                    failed: 3,
+                }),
+                created_at: Some("1711471533".to_string()),
+                provider_metadata_json: None,
+            });
+            roundtrip_test(BatchResult {
+                custom_id: "request-1".to_string(),
+                event: ChatEvent::Error(Error {
+                    code: ErrorCode::RateLimitExceeded,
+                    message: "Rate limit exceeded".to_string(),
+                    provider_error_json: None,
+                }),
+            });
+        }
+
+        #[test]
+        fn edit_image_input_encoding() {
+            let input = EditImageInput {
+                image: ImageSource {
+                    data: vec![0, 1, 2, 3],
+                    mime_type: "image/png".to_string(),
+                    detail: None,
+                },
+                mask: Some(ImageSource {
+                    data: vec![255, 255, 0, 0],
+                    mime_type: "image/png".to_string(),
+                    detail: None,
+                }),
+                prompt: "Replace the background with a beach".to_string(),
+                config: ImageConfig {
+                    model: None,
+                    size: None,
+                    quality: None,
+                    style: None,
+                    seed: None,
+                    count: Some(1),
+                    negative_prompt: None,
+                    response_format: None,
+                    provider_options: vec![],
+                },
+            };
+
+            roundtrip_test(input.clone());
+            let encoded = input.into_value_and_type();
+
+            for wit_type in encoded.typ.nodes {
+                if let WitTypeNode::ListType(idx) = wit_type {
+                    assert!(idx >= 0);
+                }
+            }
+        }
+
         #[test]
         fn send_input_encoding() {
             let input = SendInput {
@@ -675,10 +1787,18 @@ mod durable_impl {
                     temperature: Some(0.7),
                     max_tokens: Some(100),
                     stop_sequences: Some(vec!["\n".to_string()]),
+                    top_p: None,
+                    top_k: None,
+                    seed: None,
+                    frequency_penalty: None,
+                    presence_penalty: None,
+                    repetition_penalty: None,
                     tools: vec![],
                     tool_choice: None,
                     provider_options: vec![],
+                    credentials: None,
                 },
+                prompt_template: Some("support-agent@3".to_string()),
             };
 
             let encoded = input.into_value_and_type();
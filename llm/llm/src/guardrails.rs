@@ -0,0 +1,520 @@
+use crate::credentials::with_credentials;
+use crate::durability::ExtendedGuest;
+use crate::golem::llm::llm::{
+    ChatEvent, Config, ContentPart, Error, ErrorCode, FinishReason, GuestChatStream, Message,
+    ResponseMetadata, StreamEvent,
+};
+use crate::golem::llm::moderation::{CategoryScore, ModerationResponse, ModerationResult};
+use crate::pricing::add_cost;
+use crate::telemetry::ChatSpan;
+use golem_rust::wasm_rpc::Pollable;
+use log::warn;
+use regex::{NoExpand, Regex};
+use serde::Deserialize;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// The result of running a guardrail on some content
+#[derive(Debug, Clone, PartialEq)]
+pub enum GuardrailOutcome<T> {
+    /// The content, possibly redacted, can be passed on
+    Pass(T),
+    /// The content must not be passed on, for the given reason
+    Block(String),
+}
+
+/// Which direction of the communication a policy rule applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyStage {
+    /// Messages sent to the provider
+    Input,
+    /// Content returned by the provider
+    Output,
+    Both,
+}
+
+impl PolicyStage {
+    fn covers(&self, stage: PolicyStage) -> bool {
+        *self == PolicyStage::Both || *self == stage
+    }
+}
+
+/// What happens with content matching a policy rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyAction {
+    /// Only reported by `moderate` and logged by the guardrail hooks
+    Flag,
+    /// The matching parts are replaced with the policy's replacement text
+    Redact,
+    /// The whole request or response is blocked
+    Block,
+}
+
+#[derive(Debug, Deserialize)]
+struct PolicyDefinition {
+    #[serde(default = "default_replacement")]
+    replacement: String,
+    rules: Vec<RuleDefinition>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuleDefinition {
+    category: String,
+    #[serde(default)]
+    keywords: Vec<String>,
+    #[serde(default)]
+    patterns: Vec<String>,
+    #[serde(default = "default_action")]
+    action: PolicyAction,
+    #[serde(default = "default_stage")]
+    stage: PolicyStage,
+}
+
+fn default_replacement() -> String {
+    "[REDACTED]".to_string()
+}
+
+fn default_action() -> PolicyAction {
+    PolicyAction::Block
+}
+
+fn default_stage() -> PolicyStage {
+    PolicyStage::Both
+}
+
+#[derive(Debug)]
+struct PolicyRule {
+    category: String,
+    regexes: Vec<Regex>,
+    action: PolicyAction,
+    stage: PolicyStage,
+}
+
+impl PolicyRule {
+    fn matches(&self, text: &str) -> bool {
+        self.regexes.iter().any(|regex| regex.is_match(text))
+    }
+}
+
+/// A local keyword and regular expression based content policy.
+///
+/// The policy is defined as JSON in the `GOLEM_LLM_GUARDRAIL_POLICY` environment variable:
+///
+/// ```json
+/// {
+///   "replacement": "[REDACTED]",
+///   "rules": [
+///     { "category": "pii", "patterns": ["\\b\\d{3}-\\d{2}-\\d{4}\\b"], "action": "redact" },
+///     { "category": "profanity", "keywords": ["darn"], "action": "block", "stage": "output" }
+///   ]
+/// }
+/// ```
+///
+/// Keywords are matched case-insensitively on word boundaries, patterns are regular expressions.
+/// Rules default to the `block` action and to the `both` stage.
+#[derive(Debug)]
+pub struct LocalPolicy {
+    replacement: String,
+    rules: Vec<PolicyRule>,
+}
+
+impl LocalPolicy {
+    pub const ENV_VAR_NAME: &'static str = "GOLEM_LLM_GUARDRAIL_POLICY";
+
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let definition: PolicyDefinition =
+            serde_json::from_str(json).map_err(|err| format!("Invalid guardrail policy: {err}"))?;
+
+        let mut rules = Vec::new();
+        for rule in definition.rules {
+            let mut regexes = Vec::new();
+            for keyword in &rule.keywords {
+                let regex = Regex::new(&format!(r"(?i)\b{}\b", regex::escape(keyword)))
+                    .map_err(|err| format!("Invalid guardrail keyword {keyword:?}: {err}"))?;
+                regexes.push(regex);
+            }
+            for pattern in &rule.patterns {
+                let regex = Regex::new(pattern)
+                    .map_err(|err| format!("Invalid guardrail pattern {pattern:?}: {err}"))?;
+                regexes.push(regex);
+            }
+            rules.push(PolicyRule {
+                category: rule.category,
+                regexes,
+                action: rule.action,
+                stage: rule.stage,
+            });
+        }
+
+        Ok(Self {
+            replacement: definition.replacement,
+            rules,
+        })
+    }
+
+    /// Gets the policy configured in the environment, parsed only once per worker
+    pub fn from_env() -> Result<Option<Rc<LocalPolicy>>, Error> {
+        LOCAL_POLICY.with(|cached| {
+            cached
+                .borrow_mut()
+                .get_or_insert_with(|| match std::env::var(Self::ENV_VAR_NAME) {
+                    Ok(json) => LocalPolicy::parse(&json).map(|policy| Some(Rc::new(policy))),
+                    Err(_) => Ok(None),
+                })
+                .clone()
+                .map_err(|message| Error {
+                    code: ErrorCode::InternalError,
+                    message,
+                    provider_error_json: None,
+                })
+        })
+    }
+
+    /// Applies the rules of the given stage to a piece of text
+    pub fn check(&self, stage: PolicyStage, text: &str) -> GuardrailOutcome<String> {
+        let mut result = text.to_string();
+        for rule in self.rules.iter().filter(|rule| rule.stage.covers(stage)) {
+            if !rule.matches(&result) {
+                continue;
+            }
+            match rule.action {
+                PolicyAction::Flag => {
+                    warn!("Content flagged by guardrail rule {}", rule.category);
+                }
+                PolicyAction::Redact => {
+                    for regex in &rule.regexes {
+                        result = regex
+                            .replace_all(&result, NoExpand(&self.replacement))
+                            .to_string();
+                    }
+                }
+                PolicyAction::Block => {
+                    return GuardrailOutcome::Block(rule.category.clone());
+                }
+            }
+        }
+        GuardrailOutcome::Pass(result)
+    }
+
+    /// Classifies a piece of text by every category of the policy, regardless of stage and action
+    pub fn classify(&self, text: &str) -> ModerationResult {
+        let mut categories: Vec<CategoryScore> = Vec::new();
+        for rule in &self.rules {
+            let flagged = rule.matches(text);
+            match categories
+                .iter_mut()
+                .find(|score| score.category == rule.category)
+            {
+                Some(score) => {
+                    score.flagged |= flagged;
+                    if flagged {
+                        score.score = Some(1.0);
+                    }
+                }
+                None => categories.push(CategoryScore {
+                    category: rule.category.clone(),
+                    flagged,
+                    score: Some(if flagged { 1.0 } else { 0.0 }),
+                }),
+            }
+        }
+
+        ModerationResult {
+            flagged: categories.iter().any(|score| score.flagged),
+            categories,
+        }
+    }
+
+    fn check_content(
+        &self,
+        stage: PolicyStage,
+        content: Vec<ContentPart>,
+    ) -> GuardrailOutcome<Vec<ContentPart>> {
+        let mut result = Vec::with_capacity(content.len());
+        for part in content {
+            match part {
+                ContentPart::Text(text) => match self.check(stage, &text) {
+                    GuardrailOutcome::Pass(text) => result.push(ContentPart::Text(text)),
+                    GuardrailOutcome::Block(reason) => return GuardrailOutcome::Block(reason),
+                },
+                other => result.push(other),
+            }
+        }
+        GuardrailOutcome::Pass(result)
+    }
+}
+
+type CachedPolicy = Result<Option<Rc<LocalPolicy>>, String>;
+
+thread_local! {
+    static LOCAL_POLICY: RefCell<Option<CachedPolicy>> = const { RefCell::new(None) };
+}
+
+/// Default pre-send guardrail, applying the input rules of the local policy to the text content
+/// of all the messages
+pub fn apply_local_input_policy(messages: Vec<Message>) -> Result<Vec<Message>, Error> {
+    match LocalPolicy::from_env()? {
+        Some(policy) => {
+            let mut result = Vec::with_capacity(messages.len());
+            for message in messages {
+                match policy.check_content(PolicyStage::Input, message.content) {
+                    GuardrailOutcome::Pass(content) => result.push(Message { content, ..message }),
+                    GuardrailOutcome::Block(reason) => return Err(blocked_error(&reason)),
+                }
+            }
+            Ok(result)
+        }
+        None => Ok(messages),
+    }
+}
+
+/// Default post-send guardrail, applying the output rules of the local policy to the text content
+/// returned by the provider
+pub fn apply_local_output_policy(content: Vec<ContentPart>) -> GuardrailOutcome<Vec<ContentPart>> {
+    match LocalPolicy::from_env() {
+        Ok(Some(policy)) => policy.check_content(PolicyStage::Output, content),
+        Ok(None) => GuardrailOutcome::Pass(content),
+        Err(error) => GuardrailOutcome::Block(error.message),
+    }
+}
+
+/// Moderation for providers without a moderation API of their own, based on the local policy
+pub fn moderate_with_local_policy(inputs: Vec<String>) -> Result<ModerationResponse, Error> {
+    match LocalPolicy::from_env()? {
+        Some(policy) => Ok(ModerationResponse {
+            results: inputs.iter().map(|input| policy.classify(input)).collect(),
+            model: Some("local-policy".to_string()),
+            provider_id: None,
+            provider_metadata_json: None,
+        }),
+        None => Err(Error {
+            code: ErrorCode::Unsupported,
+            message: format!(
+                "Unsupported: moderation requires a local policy in {}",
+                LocalPolicy::ENV_VAR_NAME
+            ),
+            provider_error_json: None,
+        }),
+    }
+}
+
+fn blocked_error(reason: &str) -> Error {
+    Error {
+        code: ErrorCode::InvalidRequest,
+        message: format!("Blocked by guardrail: {reason}"),
+        provider_error_json: None,
+    }
+}
+
+fn guardrail_metadata_json(reason: &str) -> Option<String> {
+    Some(serde_json::json!({ "guardrail": reason }).to_string())
+}
+
+/// Runs `send` or `continue` with the pre- and post-send guardrail hooks of the implementation
+pub(crate) fn guarded<Impl: ExtendedGuest>(
+    messages: Vec<Message>,
+    call: impl FnOnce(Vec<Message>) -> ChatEvent,
+) -> ChatEvent {
+    match Impl::pre_send_guardrail(messages) {
+        Ok(messages) => match call(messages) {
+            ChatEvent::Message(mut response) => {
+                match Impl::post_send_guardrail(response.content) {
+                    GuardrailOutcome::Pass(content) => {
+                        response.content = content;
+                    }
+                    GuardrailOutcome::Block(reason) => {
+                        response.content = vec![];
+                        response.metadata.finish_reason = Some(FinishReason::ContentFilter);
+                        response.metadata.provider_metadata_json = guardrail_metadata_json(&reason);
+                    }
+                }
+                ChatEvent::Message(response)
+            }
+            other => other,
+        },
+        Err(error) => ChatEvent::Error(error),
+    }
+}
+
+/// Wraps the implementation's chat stream with the guardrail hooks, adds the estimated cost
+/// to its finish event and records its telemetry span.
+///
+/// The post-send hook is applied to each delta separately, so redaction patterns spanning
+/// multiple deltas are not detected. When a delta is blocked, the stream finishes with a
+/// `content-filter` finish reason.
+pub struct GuardedChatStream<Impl: ExtendedGuest> {
+    inner: Option<Impl::ChatStream>,
+    model: String,
+    failure: RefCell<Option<Error>>,
+    finished: Cell<bool>,
+    span: RefCell<ChatSpan>,
+}
+
+impl<Impl: ExtendedGuest> GuardedChatStream<Impl> {
+    pub fn new(messages: Vec<Message>, config: Config) -> Self {
+        Self::create(messages, config, 0)
+    }
+
+    /// Creates the stream continuing an interrupted one, see `ExtendedGuest::retry_prompt`
+    pub fn retry(messages: Vec<Message>, config: Config) -> Self {
+        Self::create(messages, config, 1)
+    }
+
+    fn create(messages: Vec<Message>, config: Config, retries: u32) -> Self {
+        let model = config.model.clone();
+        let span = RefCell::new(ChatSpan::start(Impl::PROVIDER, &config, true).retried(retries));
+        match Impl::pre_send_guardrail(messages) {
+            Ok(messages) => Self {
+                inner: Some(with_credentials(&config, || {
+                    Impl::unwrapped_stream(messages, config.clone())
+                })),
+                model,
+                failure: RefCell::new(None),
+                finished: Cell::new(false),
+                span,
+            },
+            Err(error) => Self {
+                inner: None,
+                model,
+                failure: RefCell::new(Some(error)),
+                finished: Cell::new(false),
+                span,
+            },
+        }
+    }
+
+    pub fn subscribe(&self) -> Pollable {
+        match &self.inner {
+            Some(stream) => Impl::subscribe(stream),
+            None => golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0),
+        }
+    }
+
+    fn filter(&self, events: Vec<StreamEvent>) -> Vec<StreamEvent> {
+        let mut result = Vec::with_capacity(events.len());
+        for event in events {
+            match event {
+                StreamEvent::Delta(mut delta) => {
+                    if let Some(content) = delta.content {
+                        match Impl::post_send_guardrail(content) {
+                            GuardrailOutcome::Pass(content) => delta.content = Some(content),
+                            GuardrailOutcome::Block(reason) => {
+                                self.finished.set(true);
+                                result.push(StreamEvent::Finish(ResponseMetadata {
+                                    finish_reason: Some(FinishReason::ContentFilter),
+                                    usage: None,
+                                    provider_id: None,
+                                    timestamp: None,
+                                    provider_metadata_json: guardrail_metadata_json(&reason),
+                                    cost: None,
+                                }));
+                                break;
+                            }
+                        }
+                    }
+                    result.push(StreamEvent::Delta(delta));
+                }
+                StreamEvent::Finish(mut metadata) => {
+                    add_cost::<Impl>(&self.model, &mut metadata);
+                    result.push(StreamEvent::Finish(metadata));
+                }
+                other => result.push(other),
+            }
+        }
+
+        let mut span = self.span.borrow_mut();
+        if span.record_stream_events(&result) {
+            span.end();
+        }
+        result
+    }
+
+    fn fail(&self) -> Vec<StreamEvent> {
+        self.finished.set(true);
+        let failure = self.failure.take();
+        let mut span = self.span.borrow_mut();
+        if let Some(error) = &failure {
+            span.record_error(error.code);
+        }
+        span.end();
+        failure.map(StreamEvent::Error).into_iter().collect()
+    }
+}
+
+impl<Impl: ExtendedGuest> GuestChatStream for GuardedChatStream<Impl> {
+    fn get_next(&self) -> Option<Vec<StreamEvent>> {
+        if self.finished.get() {
+            return Some(vec![]);
+        }
+        match &self.inner {
+            Some(stream) => stream.get_next().map(|events| self.filter(events)),
+            None => Some(self.fail()),
+        }
+    }
+
+    fn blocking_get_next(&self) -> Vec<StreamEvent> {
+        if self.finished.get() {
+            return vec![];
+        }
+        match &self.inner {
+            Some(stream) => self.filter(stream.blocking_get_next()),
+            None => self.fail(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::guardrails::{GuardrailOutcome, LocalPolicy, PolicyStage};
+
+    const POLICY: &str = r#"{
+        "rules": [
+            { "category": "pii", "patterns": ["\\b\\d{3}-\\d{2}-\\d{4}\\b"], "action": "redact" },
+            { "category": "profanity", "keywords": ["darn"], "stage": "output" },
+            { "category": "competitors", "keywords": ["acme"], "action": "flag" }
+        ]
+    }"#;
+
+    #[test]
+    fn redacts_matching_patterns() {
+        let policy = LocalPolicy::parse(POLICY).unwrap();
+        assert_eq!(
+            policy.check(PolicyStage::Input, "My SSN is 123-45-6789."),
+            GuardrailOutcome::Pass("My SSN is [REDACTED].".to_string())
+        );
+    }
+
+    #[test]
+    fn blocks_keywords_only_in_their_stage() {
+        let policy = LocalPolicy::parse(POLICY).unwrap();
+        assert_eq!(
+            policy.check(PolicyStage::Input, "Darn it"),
+            GuardrailOutcome::Pass("Darn it".to_string())
+        );
+        assert_eq!(
+            policy.check(PolicyStage::Output, "Darn it"),
+            GuardrailOutcome::Block("profanity".to_string())
+        );
+    }
+
+    #[test]
+    fn classifies_all_categories() {
+        let policy = LocalPolicy::parse(POLICY).unwrap();
+        let result = policy.classify("Acme is better");
+        assert!(result.flagged);
+        assert_eq!(result.categories.len(), 3);
+        assert!(result
+            .categories
+            .iter()
+            .all(|score| score.flagged == (score.category == "competitors")));
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert!(
+            LocalPolicy::parse(r#"{ "rules": [{ "category": "x", "patterns": ["("] }] }"#).is_err()
+        );
+    }
+}
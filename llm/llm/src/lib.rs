@@ -1,22 +1,114 @@
+pub mod batch_stream;
+pub mod cache;
 pub mod chat_stream;
 pub mod config;
+pub mod credentials;
 pub mod durability;
 pub mod error;
+pub mod guardrails;
+pub mod multipart;
+pub mod pricing;
+pub mod prompts;
+pub mod telemetry;
+pub mod tool_validation;
 
 #[allow(dead_code)]
 pub mod event_source;
 
 wit_bindgen::generate!({
     path: "../wit",
-    world: "llm-library",
+    world: "chat-library",
     generate_all,
     generate_unused_types: true,
     additional_derives: [PartialEq, golem_rust::FromValueAndType, golem_rust::IntoValue],
     pub_export_macro: true,
 });
 
-pub use crate::exports::golem;
-pub use __export_llm_library_impl as export_llm;
+pub use __export_chat_library_impl as export_llm;
+
+// The other interfaces have their own worlds and export macros, so each provider exports only the
+// ones it implements. Their bindings share the types of the chat bindings.
+pub mod images_library {
+    wit_bindgen::generate!({
+        path: "../wit",
+        world: "images-library",
+        with: {
+            "golem:llm/types@1.0.0": crate::golem::llm::types,
+        },
+        additional_derives: [PartialEq, golem_rust::FromValueAndType, golem_rust::IntoValue],
+        pub_export_macro: true,
+    });
+}
+
+pub use __export_images_library_impl as export_images;
+
+pub mod transcription_library {
+    wit_bindgen::generate!({
+        path: "../wit",
+        world: "transcription-library",
+        with: {
+            "golem:llm/types@1.0.0": crate::golem::llm::types,
+        },
+        additional_derives: [PartialEq, golem_rust::FromValueAndType, golem_rust::IntoValue],
+        pub_export_macro: true,
+    });
+}
+
+pub use __export_transcription_library_impl as export_transcription;
+
+pub mod speech_library {
+    wit_bindgen::generate!({
+        path: "../wit",
+        world: "speech-library",
+        with: {
+            "golem:llm/types@1.0.0": crate::golem::llm::types,
+        },
+        additional_derives: [PartialEq, golem_rust::FromValueAndType, golem_rust::IntoValue],
+        pub_export_macro: true,
+    });
+}
+
+pub use __export_speech_library_impl as export_speech;
+
+pub mod moderation_library {
+    wit_bindgen::generate!({
+        path: "../wit",
+        world: "moderation-library",
+        with: {
+            "golem:llm/types@1.0.0": crate::golem::llm::types,
+        },
+        additional_derives: [PartialEq, golem_rust::FromValueAndType, golem_rust::IntoValue],
+        pub_export_macro: true,
+    });
+}
+
+pub use __export_moderation_library_impl as export_moderation;
+
+pub mod batch_library {
+    wit_bindgen::generate!({
+        path: "../wit",
+        world: "batch-library",
+        with: {
+            "golem:llm/types@1.0.0": crate::golem::llm::types,
+        },
+        additional_derives: [PartialEq, golem_rust::FromValueAndType, golem_rust::IntoValue],
+        pub_export_macro: true,
+    });
+}
+
+pub use __export_batch_library_impl as export_batch;
+
+pub mod golem {
+    pub mod llm {
+        pub use crate::batch_library::exports::golem::llm::batch;
+        pub use crate::exports::golem::llm::{costs, llm, prompts, types};
+        pub use crate::images_library::exports::golem::llm::images;
+        pub use crate::moderation_library::exports::golem::llm::moderation;
+        pub use crate::speech_library::exports::golem::llm::speech;
+        pub use crate::transcription_library::exports::golem::llm::transcription;
+    }
+}
+
 use std::cell::RefCell;
 use std::str::FromStr;
 
@@ -0,0 +1,119 @@
+use crate::golem::llm::speech::AudioFormat;
+
+/// Builder for `multipart/form-data` request bodies.
+///
+/// The golem fork of `reqwest` does not support multipart requests (and `rand` conflicts with
+/// the WASM target), so the body is assembled manually and sent with `MultipartBody::content_type`.
+pub struct MultipartBody {
+    boundary: String,
+    body: Vec<u8>,
+}
+
+impl MultipartBody {
+    pub fn new() -> Self {
+        // There is no randomness source we can rely on here, so the boundary is derived
+        // from the worker name, similarly to the other manually built multipart bodies.
+        let boundary = format!(
+            "----formdata-golem-llm-{}",
+            std::env::var("GOLEM_WORKER_NAME").unwrap_or_else(|_| "boundary".to_string())
+        );
+        Self {
+            boundary,
+            body: Vec::new(),
+        }
+    }
+
+    /// Adds a simple text field
+    pub fn text(&mut self, name: &str, value: impl AsRef<str>) -> &mut Self {
+        self.body
+            .extend_from_slice(format!("--{}\r\n", self.boundary).as_bytes());
+        self.body.extend_from_slice(
+            format!("Content-Disposition: form-data; name=\"{name}\"\r\n\r\n").as_bytes(),
+        );
+        self.body.extend_from_slice(value.as_ref().as_bytes());
+        self.body.extend_from_slice(b"\r\n");
+        self
+    }
+
+    /// Adds a text field only if the value is present
+    pub fn optional_text(&mut self, name: &str, value: Option<impl ToString>) -> &mut Self {
+        if let Some(value) = value {
+            self.text(name, value.to_string());
+        }
+        self
+    }
+
+    /// Adds a binary file field
+    pub fn file(&mut self, name: &str, filename: &str, mime_type: &str, data: &[u8]) -> &mut Self {
+        self.body
+            .extend_from_slice(format!("--{}\r\n", self.boundary).as_bytes());
+        self.body.extend_from_slice(
+            format!("Content-Disposition: form-data; name=\"{name}\"; filename=\"{filename}\"\r\n")
+                .as_bytes(),
+        );
+        self.body
+            .extend_from_slice(format!("Content-Type: {mime_type}\r\n\r\n").as_bytes());
+        self.body.extend_from_slice(data);
+        self.body.extend_from_slice(b"\r\n");
+        self
+    }
+
+    /// The value to be used as the request's `content-type` header
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    /// Closes the body and returns the encoded bytes
+    pub fn finish(mut self) -> Vec<u8> {
+        self.body
+            .extend_from_slice(format!("--{}--\r\n", self.boundary).as_bytes());
+        self.body
+    }
+}
+
+impl Default for MultipartBody {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Guesses a file extension for a MIME type, used for naming multipart file fields
+pub fn file_extension(mime_type: &str) -> &'static str {
+    match mime_type {
+        "image/png" => "png",
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/webp" => "webp",
+        "image/gif" => "gif",
+        "audio/mpeg" | "audio/mp3" => "mp3",
+        "audio/wav" | "audio/x-wav" | "audio/wave" => "wav",
+        "audio/ogg" | "audio/opus" => "ogg",
+        "audio/flac" | "audio/x-flac" => "flac",
+        "audio/mp4" | "audio/m4a" | "audio/x-m4a" | "audio/aac" => "m4a",
+        "audio/webm" | "video/webm" => "webm",
+        _ => "bin",
+    }
+}
+
+/// Name of an audio format, as used by the speech endpoints
+pub fn audio_format_name(format: AudioFormat) -> &'static str {
+    match format {
+        AudioFormat::Mp3 => "mp3",
+        AudioFormat::Opus => "opus",
+        AudioFormat::Aac => "aac",
+        AudioFormat::Flac => "flac",
+        AudioFormat::Wav => "wav",
+        AudioFormat::Pcm => "pcm",
+    }
+}
+
+/// MIME type of audio synthesized in an audio format
+pub fn audio_format_mime_type(format: AudioFormat) -> &'static str {
+    match format {
+        AudioFormat::Mp3 => "audio/mpeg",
+        AudioFormat::Opus => "audio/opus",
+        AudioFormat::Aac => "audio/aac",
+        AudioFormat::Flac => "audio/flac",
+        AudioFormat::Wav => "audio/wav",
+        AudioFormat::Pcm => "audio/pcm",
+    }
+}
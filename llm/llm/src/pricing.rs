@@ -0,0 +1,476 @@
+use crate::durability::ExtendedGuest;
+use crate::golem::llm::llm::{ChatEvent, Config, Cost, ResponseMetadata, StreamEvent, Usage};
+use log::warn;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Provider option used to assign a request to a conversation for cost tracking. It is removed
+/// from the configuration before the request is passed to the provider.
+pub const CONVERSATION_ID_OPTION: &str = "conversation_id";
+
+/// Price of a model, in `currency` per million tokens
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ModelPricing {
+    pub input: f64,
+    pub output: f64,
+    /// Price of the input tokens read from the prompt cache, defaults to the input price
+    #[serde(default)]
+    pub cached_input: Option<f64>,
+    /// Price of the reasoning tokens, defaults to the output price
+    #[serde(default)]
+    pub reasoning: Option<f64>,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+}
+
+impl ModelPricing {
+    fn usd(input: f64, output: f64, cached_input: Option<f64>) -> Self {
+        Self {
+            input,
+            output,
+            cached_input,
+            reasoning: None,
+            currency: default_currency(),
+        }
+    }
+
+    /// Calculates the cost of a response with the given usage. Returns `None` if the provider
+    /// did not report the number of input and output tokens.
+    pub fn cost(&self, usage: &Usage) -> Option<Cost> {
+        if usage.input_tokens.is_none() && usage.output_tokens.is_none() {
+            return None;
+        }
+
+        let cached_input_tokens = usage.cached_input_tokens.unwrap_or(0);
+        let input_tokens = usage
+            .input_tokens
+            .unwrap_or(0)
+            .saturating_sub(cached_input_tokens);
+        let reasoning_tokens = usage.reasoning_tokens.unwrap_or(0);
+        let output_tokens = usage
+            .output_tokens
+            .unwrap_or(0)
+            .saturating_sub(reasoning_tokens);
+
+        let input_cost = per_million(input_tokens, self.input);
+        let cached_input_cost =
+            per_million(cached_input_tokens, self.cached_input.unwrap_or(self.input));
+        let output_cost = per_million(output_tokens, self.output);
+        let reasoning_cost = per_million(reasoning_tokens, self.reasoning.unwrap_or(self.output));
+
+        Some(Cost {
+            input_cost,
+            output_cost,
+            cached_input_cost,
+            reasoning_cost,
+            total_cost: input_cost + output_cost + cached_input_cost + reasoning_cost,
+            currency: self.currency.clone(),
+        })
+    }
+}
+
+fn default_currency() -> String {
+    "USD".to_string()
+}
+
+fn per_million(tokens: u32, price: f64) -> f64 {
+    tokens as f64 * price / 1_000_000.0
+}
+
+/// Built-in prices in USD per million tokens, as (provider, model prefix, input, output,
+/// cached input). The `*` model matches every model of the provider.
+const DEFAULT_PRICES: &[(&str, &str, f64, f64, Option<f64>)] = &[
+    ("anthropic", "claude-opus-4", 15.0, 75.0, Some(1.5)),
+    ("anthropic", "claude-sonnet-4", 3.0, 15.0, Some(0.3)),
+    ("anthropic", "claude-3-7-sonnet", 3.0, 15.0, Some(0.3)),
+    ("anthropic", "claude-3-5-sonnet", 3.0, 15.0, Some(0.3)),
+    ("anthropic", "claude-3-5-haiku", 0.8, 4.0, Some(0.08)),
+    ("anthropic", "claude-3-opus", 15.0, 75.0, Some(1.5)),
+    ("anthropic", "claude-3-haiku", 0.25, 1.25, Some(0.03)),
+    ("bedrock", "anthropic.claude-opus-4", 15.0, 75.0, Some(1.5)),
+    ("bedrock", "anthropic.claude-sonnet-4", 3.0, 15.0, Some(0.3)),
+    (
+        "bedrock",
+        "anthropic.claude-3-7-sonnet",
+        3.0,
+        15.0,
+        Some(0.3),
+    ),
+    ("bedrock", "anthropic.claude-3-5-sonnet", 3.0, 15.0, None),
+    ("bedrock", "anthropic.claude-3-5-haiku", 0.8, 4.0, None),
+    ("bedrock", "anthropic.claude-3-haiku", 0.25, 1.25, None),
+    ("bedrock", "amazon.nova-premier", 2.5, 12.5, None),
+    ("bedrock", "amazon.nova-pro", 0.8, 3.2, Some(0.2)),
+    ("bedrock", "amazon.nova-lite", 0.06, 0.24, Some(0.015)),
+    ("bedrock", "amazon.nova-micro", 0.035, 0.14, Some(0.00875)),
+    ("bedrock", "meta.llama3-3-70b-instruct", 0.72, 0.72, None),
+    ("bedrock", "meta.llama3-1-8b-instruct", 0.22, 0.22, None),
+    ("grok", "grok-4", 3.0, 15.0, Some(0.75)),
+    ("grok", "grok-3-mini", 0.3, 0.5, Some(0.075)),
+    ("grok", "grok-3", 3.0, 15.0, Some(0.75)),
+    ("grok", "grok-2", 2.0, 10.0, None),
+    ("ollama", "*", 0.0, 0.0, None),
+    ("openai", "gpt-4.1-nano", 0.1, 0.4, Some(0.025)),
+    ("openai", "gpt-4.1-mini", 0.4, 1.6, Some(0.1)),
+    ("openai", "gpt-4.1", 2.0, 8.0, Some(0.5)),
+    ("openai", "gpt-4o-mini", 0.15, 0.6, Some(0.075)),
+    ("openai", "gpt-4o", 2.5, 10.0, Some(1.25)),
+    ("openai", "gpt-4-turbo", 10.0, 30.0, None),
+    ("openai", "gpt-3.5-turbo", 0.5, 1.5, None),
+    ("openai", "o4-mini", 1.1, 4.4, Some(0.275)),
+    ("openai", "o3-mini", 1.1, 4.4, Some(0.55)),
+    ("openai", "o3", 2.0, 8.0, Some(0.5)),
+    ("openai", "o1-mini", 1.1, 4.4, Some(0.55)),
+    ("openai", "o1", 15.0, 60.0, Some(7.5)),
+    ("openrouter", "openai/gpt-4o-mini", 0.15, 0.6, Some(0.075)),
+    ("openrouter", "openai/gpt-4o", 2.5, 10.0, Some(1.25)),
+    ("openrouter", "openai/gpt-4.1", 2.0, 8.0, Some(0.5)),
+    (
+        "openrouter",
+        "anthropic/claude-sonnet-4",
+        3.0,
+        15.0,
+        Some(0.3),
+    ),
+    (
+        "openrouter",
+        "anthropic/claude-3.7-sonnet",
+        3.0,
+        15.0,
+        Some(0.3),
+    ),
+    (
+        "openrouter",
+        "anthropic/claude-3.5-sonnet",
+        3.0,
+        15.0,
+        Some(0.3),
+    ),
+    (
+        "openrouter",
+        "anthropic/claude-3.5-haiku",
+        0.8,
+        4.0,
+        Some(0.08),
+    ),
+    ("openrouter", "x-ai/grok-3", 3.0, 15.0, Some(0.75)),
+];
+
+/// Model prices of a provider, combining the built-in defaults with the overrides defined as
+/// JSON in the `GOLEM_LLM_PRICING` environment variable:
+///
+/// ```json
+/// {
+///   "gpt-4o": { "input": 2.5, "output": 10.0, "cached_input": 1.25 },
+///   "my-fine-tuned-model": { "input": 3.0, "output": 12.0, "currency": "EUR" }
+/// }
+/// ```
+///
+/// Models are matched by the longest prefix, so `gpt-4o` also covers `gpt-4o-2024-08-06`. The
+/// region prefix of cross-region inference profiles (such as `us.` on Bedrock) is ignored.
+#[derive(Debug, Default)]
+pub struct PricingRegistry {
+    prices: HashMap<String, ModelPricing>,
+}
+
+impl PricingRegistry {
+    pub const ENV_VAR_NAME: &'static str = "GOLEM_LLM_PRICING";
+
+    pub fn new(provider: &str, overrides: HashMap<String, ModelPricing>) -> Self {
+        let mut prices: HashMap<String, ModelPricing> = DEFAULT_PRICES
+            .iter()
+            .filter(|(p, ..)| *p == provider)
+            .map(|(_, model, input, output, cached_input)| {
+                (
+                    model.to_string(),
+                    ModelPricing::usd(*input, *output, *cached_input),
+                )
+            })
+            .collect();
+        prices.extend(overrides);
+        Self { prices }
+    }
+
+    pub fn parse(provider: &str, json: &str) -> Result<Self, String> {
+        let overrides = serde_json::from_str(json)
+            .map_err(|err| format!("Invalid pricing configuration: {err}"))?;
+        Ok(Self::new(provider, overrides))
+    }
+
+    /// Gets the registry of the given provider, parsed only once per worker. An invalid pricing
+    /// configuration is logged and ignored, as it should not make the requests fail.
+    pub fn from_env(provider: &str) -> Rc<PricingRegistry> {
+        PRICING_REGISTRY.with(|cached| {
+            cached
+                .borrow_mut()
+                .get_or_insert_with(|| {
+                    let registry = match std::env::var(Self::ENV_VAR_NAME) {
+                        Ok(json) => Self::parse(provider, &json).unwrap_or_else(|message| {
+                            warn!("{message}");
+                            Self::new(provider, HashMap::new())
+                        }),
+                        Err(_) => Self::new(provider, HashMap::new()),
+                    };
+                    Rc::new(registry)
+                })
+                .clone()
+        })
+    }
+
+    /// Gets the price of a model
+    pub fn lookup(&self, model: &str) -> Option<&ModelPricing> {
+        let mut candidate = model;
+        loop {
+            let best = self
+                .prices
+                .iter()
+                .filter(|(prefix, _)| candidate.starts_with(prefix.as_str()))
+                .max_by_key(|(prefix, _)| prefix.len());
+            if let Some((_, pricing)) = best {
+                return Some(pricing);
+            }
+            match candidate.split_once('.') {
+                Some((_, rest)) => candidate = rest,
+                None => return self.prices.get("*"),
+            }
+        }
+    }
+}
+
+thread_local! {
+    static PRICING_REGISTRY: RefCell<Option<Rc<PricingRegistry>>> = const { RefCell::new(None) };
+}
+
+/// Sets the estimated cost in the metadata of a response, unless the provider already reported it
+pub(crate) fn add_cost<Impl: ExtendedGuest>(model: &str, metadata: &mut ResponseMetadata) {
+    if metadata.cost.is_none() {
+        if let Some(usage) = &metadata.usage {
+            metadata.cost = PricingRegistry::from_env(Impl::PROVIDER)
+                .lookup(model)
+                .and_then(|pricing| pricing.cost(usage));
+        }
+    }
+}
+
+/// Adds the estimated cost to a response of `send` or `continue`
+pub(crate) fn priced<Impl: ExtendedGuest>(model: &str, event: ChatEvent) -> ChatEvent {
+    match event {
+        ChatEvent::Message(mut response) => {
+            add_cost::<Impl>(model, &mut response.metadata);
+            ChatEvent::Message(response)
+        }
+        other => other,
+    }
+}
+
+/// Removes the conversation identifier from the provider options of a request
+pub(crate) fn take_conversation_id(config: &mut Config) -> Option<String> {
+    let index = config
+        .provider_options
+        .iter()
+        .position(|kv| kv.key == CONVERSATION_ID_OPTION)?;
+    Some(config.provider_options.remove(index).value)
+}
+
+/// Costs are only added up within the same currency, so the ledger keeps one total per currency
+#[derive(Default)]
+struct CostLedger {
+    total: Vec<Cost>,
+    conversations: HashMap<String, Vec<Cost>>,
+}
+
+thread_local! {
+    static COST_LEDGER: RefCell<CostLedger> = RefCell::new(CostLedger::default());
+}
+
+fn accumulate(totals: &mut Vec<Cost>, cost: &Cost) {
+    match totals
+        .iter_mut()
+        .find(|total| total.currency == cost.currency)
+    {
+        Some(total) => {
+            total.input_cost += cost.input_cost;
+            total.output_cost += cost.output_cost;
+            total.cached_input_cost += cost.cached_input_cost;
+            total.reasoning_cost += cost.reasoning_cost;
+            total.total_cost += cost.total_cost;
+        }
+        None => totals.push(cost.clone()),
+    }
+}
+
+/// Adds the cost of a response to the cumulative cost of the worker, and of its conversation
+pub(crate) fn record_cost(conversation_id: Option<&str>, cost: &Cost) {
+    COST_LEDGER.with_borrow_mut(|ledger| {
+        accumulate(&mut ledger.total, cost);
+        if let Some(conversation_id) = conversation_id {
+            accumulate(
+                ledger
+                    .conversations
+                    .entry(conversation_id.to_string())
+                    .or_default(),
+                cost,
+            );
+        }
+    });
+}
+
+/// Records the cost of a response of `send` or `continue`, if any
+pub(crate) fn record_chat_event_cost(conversation_id: Option<&str>, event: &ChatEvent) {
+    if let ChatEvent::Message(response) = event {
+        if let Some(cost) = &response.metadata.cost {
+            record_cost(conversation_id, cost);
+        }
+    }
+}
+
+/// Records the cost reported by the finish events of a chat stream, if any
+pub(crate) fn record_stream_cost(conversation_id: Option<&str>, events: &[StreamEvent]) {
+    for event in events {
+        if let StreamEvent::Finish(ResponseMetadata {
+            cost: Some(cost), ..
+        }) = event
+        {
+            record_cost(conversation_id, cost);
+        }
+    }
+}
+
+/// Gets the cumulative cost of the worker, or of a single conversation, with one entry per
+/// currency of the recorded costs
+pub fn cumulative_cost(conversation_id: Option<&str>) -> Vec<Cost> {
+    COST_LEDGER.with_borrow(|ledger| match conversation_id {
+        Some(conversation_id) => ledger
+            .conversations
+            .get(conversation_id)
+            .cloned()
+            .unwrap_or_default(),
+        None => ledger.total.clone(),
+    })
+}
+
+/// Resets the cumulative cost of the worker, including all of its conversations, or of a single
+/// conversation
+pub fn reset_cumulative_cost(conversation_id: Option<&str>) {
+    COST_LEDGER.with_borrow_mut(|ledger| match conversation_id {
+        Some(conversation_id) => {
+            ledger.conversations.remove(conversation_id);
+        }
+        None => *ledger = CostLedger::default(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::golem::llm::llm::Usage;
+    use crate::pricing::{
+        cumulative_cost, record_cost, reset_cumulative_cost, ModelPricing, PricingRegistry,
+    };
+
+    fn usage(input: u32, output: u32, cached: Option<u32>, reasoning: Option<u32>) -> Usage {
+        Usage {
+            input_tokens: Some(input),
+            output_tokens: Some(output),
+            total_tokens: Some(input + output),
+            cached_input_tokens: cached,
+            reasoning_tokens: reasoning,
+        }
+    }
+
+    #[test]
+    fn matches_longest_prefix() {
+        let registry = PricingRegistry::new("openai", Default::default());
+        assert_eq!(registry.lookup("gpt-4o-2024-08-06").unwrap().input, 2.5);
+        assert_eq!(registry.lookup("gpt-4o-mini").unwrap().input, 0.15);
+        assert!(registry.lookup("davinci-002").is_none());
+    }
+
+    #[test]
+    fn ignores_inference_profile_region() {
+        let registry = PricingRegistry::new("bedrock", Default::default());
+        let pricing = registry
+            .lookup("us.anthropic.claude-3-5-haiku-20241022-v1:0")
+            .unwrap();
+        assert_eq!(pricing.output, 4.0);
+    }
+
+    #[test]
+    fn overrides_take_precedence() {
+        let registry = PricingRegistry::parse(
+            "openai",
+            r#"{ "gpt-4o": { "input": 1.0, "output": 2.0, "currency": "EUR" } }"#,
+        )
+        .unwrap();
+        let pricing = registry.lookup("gpt-4o").unwrap();
+        assert_eq!(pricing.input, 1.0);
+        assert_eq!(pricing.currency, "EUR");
+        assert_eq!(registry.lookup("gpt-4o-mini").unwrap().input, 0.15);
+    }
+
+    #[test]
+    fn splits_cached_and_reasoning_costs() {
+        let registry = PricingRegistry::new("openai", Default::default());
+        let cost = registry
+            .lookup("o3")
+            .unwrap()
+            .cost(&usage(1_000_000, 2_000_000, Some(400_000), Some(500_000)))
+            .unwrap();
+        assert_eq!(cost.input_cost, 1.2);
+        assert_eq!(cost.cached_input_cost, 0.2);
+        assert_eq!(cost.output_cost, 12.0);
+        assert_eq!(cost.reasoning_cost, 4.0);
+        assert!((cost.total_cost - 17.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn accumulates_costs_per_conversation() {
+        let registry = PricingRegistry::new("ollama", Default::default());
+        let free = registry
+            .lookup("llama3.2")
+            .unwrap()
+            .cost(&usage(10, 10, None, None))
+            .unwrap();
+        assert_eq!(free.total_cost, 0.0);
+
+        let pricing = PricingRegistry::new("anthropic", Default::default());
+        let cost = pricing
+            .lookup("claude-3-haiku-20240307")
+            .unwrap()
+            .cost(&usage(1_000_000, 0, None, None))
+            .unwrap();
+        record_cost(Some("a"), &cost);
+        record_cost(Some("a"), &cost);
+        record_cost(None, &cost);
+        assert_eq!(cumulative_cost(Some("a"))[0].total_cost, 0.5);
+        assert_eq!(cumulative_cost(None)[0].total_cost, 0.75);
+
+        reset_cumulative_cost(Some("a"));
+        assert!(cumulative_cost(Some("a")).is_empty());
+        assert_eq!(cumulative_cost(None)[0].total_cost, 0.75);
+    }
+
+    #[test]
+    fn keeps_a_total_per_currency() {
+        let usd = ModelPricing::usd(1.0, 1.0, None)
+            .cost(&usage(1_000_000, 0, None, None))
+            .unwrap();
+        let eur = ModelPricing {
+            currency: "EUR".to_string(),
+            ..ModelPricing::usd(2.0, 2.0, None)
+        }
+        .cost(&usage(1_000_000, 0, None, None))
+        .unwrap();
+        record_cost(Some("b"), &usd);
+        record_cost(Some("b"), &eur);
+        record_cost(Some("b"), &usd);
+
+        let totals = cumulative_cost(Some("b"));
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[0].currency, "USD");
+        assert_eq!(totals[0].total_cost, 2.0);
+        assert_eq!(totals[1].currency, "EUR");
+        assert_eq!(totals[1].total_cost, 2.0);
+    }
+}
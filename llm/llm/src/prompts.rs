@@ -0,0 +1,793 @@
+use crate::golem::llm::llm::{
+    Config, ContentPart, Error, ErrorCode, ImageReference, Message, Role,
+};
+use crate::golem::llm::prompts::{RenderedPrompt, TemplateValue, TemplateVariable};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Provider option identifying the prompt template which produced the messages of a request, as
+/// returned in `rendered-prompt.id`. It is recorded with the request in the oplog, and removed
+/// from the configuration before the request is passed to the provider.
+pub const PROMPT_TEMPLATE_OPTION: &str = "prompt_template";
+
+/// Environment variable of the directory containing the bundled prompt template files
+pub const PROMPTS_DIR_ENV_VAR_NAME: &str = "GOLEM_LLM_PROMPTS_DIR";
+
+const DEFAULT_PROMPTS_DIR: &str = "/prompts";
+
+/// File extension of the prompt template files
+const TEMPLATE_EXTENSION: &str = "prompt";
+
+/// Maximum nesting of partials, to detect cycles
+const MAX_PARTIAL_DEPTH: usize = 8;
+
+/// Removes the prompt template identifier from the provider options of a request
+pub(crate) fn take_prompt_template(config: &mut Config) -> Option<String> {
+    let index = config
+        .provider_options
+        .iter()
+        .position(|kv| kv.key == PROMPT_TEMPLATE_OPTION)?;
+    Some(config.provider_options.remove(index).value)
+}
+
+/// Type of a template variable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    Image,
+}
+
+impl VariableType {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "string" => Some(VariableType::String),
+            "number" => Some(VariableType::Number),
+            "integer" => Some(VariableType::Integer),
+            "boolean" => Some(VariableType::Boolean),
+            "image" => Some(VariableType::Image),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            VariableType::String => "string",
+            VariableType::Number => "number",
+            VariableType::Integer => "integer",
+            VariableType::Boolean => "boolean",
+            VariableType::Image => "image",
+        }
+    }
+
+    fn accepts(&self, value: &TemplateValue) -> bool {
+        match (self, value) {
+            (VariableType::String, TemplateValue::Text(_)) => true,
+            (VariableType::Number, TemplateValue::Number(_)) => true,
+            (VariableType::Integer, TemplateValue::Number(number)) => number.fract() == 0.0,
+            (VariableType::Boolean, TemplateValue::Boolean(_)) => true,
+            (VariableType::Image, TemplateValue::Image(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+/// A variable declared with `@var name: type`, `@var name?: type` or `@var name: type = default`
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableDeclaration {
+    pub name: String,
+    pub variable_type: VariableType,
+    pub optional: bool,
+    pub default: Option<TemplateValue>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Variable(String),
+    Image(String),
+    Partial(String),
+}
+
+enum SectionKind {
+    Message(Role),
+    Partial(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Section {
+    role: Role,
+    segments: Vec<Segment>,
+}
+
+/// A parsed prompt template.
+///
+/// Templates are text files starting with optional directives, followed by role-tagged sections,
+/// each rendered to one message:
+///
+/// ```text
+/// @version 3
+/// @var customer: string
+/// @var tier: string = basic
+/// @var screenshot?: image
+///
+/// [partial tone]
+/// Be concise and friendly.
+///
+/// [system]
+/// You are a support agent for {{tier}} customers. {{> tone}}
+///
+/// [user]
+/// Hi, I'm {{customer}}.
+/// {{image screenshot}}
+/// ```
+///
+/// `{{> name}}` includes a partial defined in the same file, or otherwise the body of another
+/// template without sections. A template without sections renders to a single user message.
+/// Sections rendering to no content, because of missing optional variables, are left out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptTemplate {
+    pub name: String,
+    pub version: String,
+    pub variables: Vec<VariableDeclaration>,
+    partials: HashMap<String, Vec<Segment>>,
+    sections: Vec<Section>,
+    /// Whether the template has no role-tagged sections, so it can be used as a partial
+    plain: bool,
+}
+
+impl PromptTemplate {
+    /// Parses a template. When it has no `@version` directive, `default_version` is used, or
+    /// otherwise a hash of its content.
+    pub fn parse(name: &str, source: &str, default_version: Option<&str>) -> Result<Self, Error> {
+        let mut version = None;
+        let mut variables = Vec::new();
+        let mut partials = HashMap::new();
+        let mut sections = Vec::new();
+        let mut preamble = Vec::new();
+        let mut current: Option<(SectionKind, Vec<&str>)> = None;
+
+        let finish = |current: Option<(SectionKind, Vec<&str>)>,
+                      partials: &mut HashMap<String, Vec<Segment>>,
+                      sections: &mut Vec<Section>|
+         -> Result<(), Error> {
+            if let Some((kind, lines)) = current {
+                let segments = parse_body(name, &lines.join("\n"))?;
+                match kind {
+                    SectionKind::Message(role) => sections.push(Section { role, segments }),
+                    SectionKind::Partial(partial) => {
+                        partials.insert(partial, segments);
+                    }
+                }
+            }
+            Ok(())
+        };
+
+        for line in source.lines() {
+            if let Some(kind) = section_header(line) {
+                finish(current.take(), &mut partials, &mut sections)?;
+                current = Some((kind, Vec::new()));
+            } else if let Some((_, lines)) = &mut current {
+                lines.push(line);
+            } else if let Some(directive) = line.trim().strip_prefix('@') {
+                match directive.split_once(char::is_whitespace) {
+                    Some(("version", value)) => version = Some(value.trim().to_string()),
+                    Some(("var", declaration)) => {
+                        variables.push(parse_declaration(name, declaration)?)
+                    }
+                    _ => {
+                        return Err(template_error(
+                            name,
+                            format!("unknown directive @{directive}"),
+                        ))
+                    }
+                }
+            } else if !line.trim_start().starts_with('#') {
+                preamble.push(line);
+            }
+        }
+        finish(current, &mut partials, &mut sections)?;
+
+        let plain = sections.is_empty();
+        if plain {
+            sections.push(Section {
+                role: Role::User,
+                segments: parse_body(name, &preamble.join("\n"))?,
+            });
+        } else if preamble.iter().any(|line| !line.trim().is_empty()) {
+            return Err(template_error(
+                name,
+                "text before the first section".to_string(),
+            ));
+        }
+
+        let version = version
+            .or_else(|| default_version.map(|version| version.to_string()))
+            .unwrap_or_else(|| {
+                let hash = format!("{:x}", Sha256::digest(source.as_bytes()));
+                hash[..12].to_string()
+            });
+
+        Ok(Self {
+            name: name.to_string(),
+            version,
+            variables,
+            partials,
+            sections,
+            plain,
+        })
+    }
+
+    /// The identifier of this version of the template, to be passed as the `prompt_template`
+    /// provider option
+    pub fn id(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+
+    /// Renders the template to messages. Templates referenced by `{{> name}}` which are not
+    /// partials of this template are loaded with `load_partial`.
+    pub fn render(
+        &self,
+        variables: &[TemplateVariable],
+        load_partial: &dyn Fn(&str) -> Result<PromptTemplate, Error>,
+    ) -> Result<Vec<Message>, Error> {
+        let values = self.bind(variables)?;
+        let mut messages = Vec::new();
+        for section in &self.sections {
+            let mut renderer = Renderer {
+                template: self,
+                values: &values,
+                load_partial,
+                content: Vec::new(),
+                text: String::new(),
+            };
+            renderer.render(&section.segments, 0)?;
+            let content = renderer.finish();
+            if !content.is_empty() {
+                messages.push(Message {
+                    role: section.role,
+                    name: None,
+                    content,
+                });
+            }
+        }
+        Ok(messages)
+    }
+
+    /// Checks the given variables against the declarations, and applies the defaults
+    fn bind<'a>(
+        &'a self,
+        variables: &'a [TemplateVariable],
+    ) -> Result<HashMap<&'a str, &'a TemplateValue>, Error> {
+        let mut values = HashMap::new();
+        for variable in variables {
+            let declaration = self
+                .variables
+                .iter()
+                .find(|declaration| declaration.name == variable.name)
+                .ok_or_else(|| {
+                    invalid_request(format!(
+                        "Prompt template {} has no variable {}",
+                        self.name, variable.name
+                    ))
+                })?;
+            if !declaration.variable_type.accepts(&variable.value) {
+                return Err(invalid_request(format!(
+                    "Variable {} of prompt template {} must be a {}",
+                    variable.name,
+                    self.name,
+                    declaration.variable_type.name()
+                )));
+            }
+            values.insert(variable.name.as_str(), &variable.value);
+        }
+        for declaration in &self.variables {
+            if values.contains_key(declaration.name.as_str()) {
+                continue;
+            }
+            match &declaration.default {
+                Some(default) => {
+                    values.insert(declaration.name.as_str(), default);
+                }
+                None if declaration.optional => {}
+                None => {
+                    return Err(invalid_request(format!(
+                        "Missing variable {} of prompt template {}",
+                        declaration.name, self.name
+                    )))
+                }
+            }
+        }
+        Ok(values)
+    }
+}
+
+struct Renderer<'a> {
+    template: &'a PromptTemplate,
+    values: &'a HashMap<&'a str, &'a TemplateValue>,
+    load_partial: &'a dyn Fn(&str) -> Result<PromptTemplate, Error>,
+    content: Vec<ContentPart>,
+    text: String,
+}
+
+impl<'a> Renderer<'a> {
+    fn render(&mut self, segments: &[Segment], depth: usize) -> Result<(), Error> {
+        for segment in segments {
+            match segment {
+                Segment::Text(text) => self.text.push_str(text),
+                Segment::Variable(name) => match self.value(name)? {
+                    Some(TemplateValue::Text(text)) => self.text.push_str(text),
+                    Some(TemplateValue::Number(number)) => self.text.push_str(&number.to_string()),
+                    Some(TemplateValue::Boolean(boolean)) => {
+                        self.text.push_str(&boolean.to_string())
+                    }
+                    Some(TemplateValue::Image(_)) => {
+                        return Err(template_error(
+                            &self.template.name,
+                            format!("image variable {name} must be used as {{{{image {name}}}}}"),
+                        ))
+                    }
+                    None => {}
+                },
+                Segment::Image(name) => match self.value(name)? {
+                    Some(TemplateValue::Image(image)) => self.push_image(image.clone()),
+                    Some(_) => {
+                        return Err(template_error(
+                            &self.template.name,
+                            format!("variable {name} is not an image"),
+                        ))
+                    }
+                    None => {}
+                },
+                Segment::Partial(name) => {
+                    if depth >= MAX_PARTIAL_DEPTH {
+                        return Err(template_error(
+                            &self.template.name,
+                            format!("partial {name} is nested too deeply"),
+                        ));
+                    }
+                    let template = self.template;
+                    match template.partials.get(name) {
+                        Some(partial) => self.render(partial, depth + 1)?,
+                        None => {
+                            let partial = (self.load_partial)(name)?;
+                            if !partial.plain {
+                                return Err(template_error(
+                                    &self.template.name,
+                                    format!("template {name} has sections, it can't be used as a partial"),
+                                ));
+                            }
+                            self.render(&partial.sections[0].segments, depth + 1)?;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the value of a variable, or `None` for a missing optional variable
+    fn value(&self, name: &str) -> Result<Option<&'a TemplateValue>, Error> {
+        if let Some(value) = self.values.get(name) {
+            return Ok(Some(*value));
+        }
+        if self
+            .template
+            .variables
+            .iter()
+            .any(|declaration| declaration.name == name)
+        {
+            Ok(None)
+        } else {
+            Err(template_error(
+                &self.template.name,
+                format!("undeclared variable {name}"),
+            ))
+        }
+    }
+
+    fn push_image(&mut self, image: ImageReference) {
+        self.flush_text();
+        self.content.push(ContentPart::Image(image));
+    }
+
+    fn flush_text(&mut self) {
+        let text = std::mem::take(&mut self.text);
+        let text = text.trim();
+        if !text.is_empty() {
+            self.content.push(ContentPart::Text(text.to_string()));
+        }
+    }
+
+    fn finish(mut self) -> Vec<ContentPart> {
+        self.flush_text();
+        self.content
+    }
+}
+
+/// Parses a `[system]`, `[user]`, `[assistant]`, `[tool]` or `[partial name]` section header.
+/// Other lines in brackets are kept as text.
+fn section_header(line: &str) -> Option<SectionKind> {
+    let header = line.trim().strip_prefix('[')?.strip_suffix(']')?.trim();
+    match header {
+        "system" => Some(SectionKind::Message(Role::System)),
+        "user" => Some(SectionKind::Message(Role::User)),
+        "assistant" => Some(SectionKind::Message(Role::Assistant)),
+        "tool" => Some(SectionKind::Message(Role::Tool)),
+        other => {
+            let partial = other.strip_prefix("partial ")?.trim();
+            (!partial.is_empty() && !partial.contains(char::is_whitespace))
+                .then(|| SectionKind::Partial(partial.to_string()))
+        }
+    }
+}
+
+fn parse_declaration(template: &str, declaration: &str) -> Result<VariableDeclaration, Error> {
+    let invalid = || {
+        template_error(
+            template,
+            format!("invalid variable declaration {declaration}"),
+        )
+    };
+    let (name, rest) = declaration.split_once(':').ok_or_else(invalid)?;
+    let (variable_type, default) = match rest.split_once('=') {
+        Some((variable_type, default)) => (variable_type.trim(), Some(default.trim())),
+        None => (rest.trim(), None),
+    };
+    let (name, optional) = match name.trim().strip_suffix('?') {
+        Some(name) => (name.trim(), true),
+        None => (name.trim(), false),
+    };
+    if name.is_empty() {
+        return Err(invalid());
+    }
+    let variable_type = VariableType::parse(variable_type).ok_or_else(invalid)?;
+    let default = match default {
+        Some(default) => Some(match variable_type {
+            VariableType::String => TemplateValue::Text(default.to_string()),
+            VariableType::Number | VariableType::Integer => {
+                TemplateValue::Number(default.parse().map_err(|_| invalid())?)
+            }
+            VariableType::Boolean => {
+                TemplateValue::Boolean(default.parse().map_err(|_| invalid())?)
+            }
+            VariableType::Image => return Err(invalid()),
+        }),
+        None => None,
+    };
+    if default
+        .as_ref()
+        .is_some_and(|default| !variable_type.accepts(default))
+    {
+        return Err(invalid());
+    }
+
+    Ok(VariableDeclaration {
+        name: name.to_string(),
+        variable_type,
+        optional,
+        default,
+    })
+}
+
+fn parse_body(template: &str, body: &str) -> Result<Vec<Segment>, Error> {
+    let mut segments = Vec::new();
+    let mut rest = body.trim();
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            segments.push(Segment::Text(rest[..start].to_string()));
+        }
+        let end = rest[start..]
+            .find("}}")
+            .ok_or_else(|| template_error(template, "unterminated {{ placeholder".to_string()))?;
+        let placeholder = rest[start + 2..start + end].trim();
+        let segment = if let Some(partial) = placeholder.strip_prefix('>') {
+            Segment::Partial(partial.trim().to_string())
+        } else if let Some(image) = placeholder.strip_prefix("image ") {
+            Segment::Image(image.trim().to_string())
+        } else {
+            Segment::Variable(placeholder.to_string())
+        };
+        segments.push(segment);
+        rest = &rest[start + end + 2..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest.to_string()));
+    }
+    Ok(segments)
+}
+
+/// Loads a template from the imported prompt store when the `prompt-store` feature is enabled,
+/// and otherwise from the template files bundled in the component.
+///
+/// The files are looked up in the directory set by `GOLEM_LLM_PROMPTS_DIR` (`/prompts` by
+/// default), as `{name}@{version}.prompt` or `{name}.prompt`. Without a `@version` directive,
+/// the version in the file name is used.
+pub fn load_template(name: &str, version: Option<&str>) -> Result<PromptTemplate, Error> {
+    check_identifier("name", name)?;
+    if let Some(version) = version {
+        check_identifier("version", version)?;
+    }
+
+    #[cfg(feature = "prompt-store")]
+    if let Some(source) = prompt_store::get_template(name, version)? {
+        return PromptTemplate::parse(name, &source, version)
+            .and_then(|template| check_version(template, version));
+    }
+
+    let dir = PathBuf::from(
+        std::env::var(PROMPTS_DIR_ENV_VAR_NAME).unwrap_or_else(|_| DEFAULT_PROMPTS_DIR.to_string()),
+    );
+    let versioned = version.map(|version| {
+        (
+            dir.join(format!("{name}@{version}.{TEMPLATE_EXTENSION}")),
+            Some(version),
+        )
+    });
+    let unversioned = (dir.join(format!("{name}.{TEMPLATE_EXTENSION}")), None);
+    for (path, file_version) in versioned.into_iter().chain(std::iter::once(unversioned)) {
+        match std::fs::read_to_string(&path) {
+            Ok(source) => {
+                let template = PromptTemplate::parse(name, &source, file_version)?;
+                return check_version(template, version);
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => {
+                return Err(Error {
+                    code: ErrorCode::InternalError,
+                    message: format!("Failed to read prompt template {}: {err}", path.display()),
+                    provider_error_json: None,
+                })
+            }
+        }
+    }
+
+    Err(invalid_request(match version {
+        Some(version) => format!("Prompt template {name} version {version} not found"),
+        None => format!("Prompt template {name} not found"),
+    }))
+}
+
+/// Loads and renders a template
+pub fn render_prompt(
+    name: &str,
+    version: Option<&str>,
+    variables: &[TemplateVariable],
+) -> Result<RenderedPrompt, Error> {
+    let template = load_template(name, version)?;
+    let messages = template.render(variables, &|partial| load_template(partial, None))?;
+    Ok(RenderedPrompt {
+        id: template.id(),
+        name: template.name,
+        version: template.version,
+        messages,
+    })
+}
+
+fn check_version(template: PromptTemplate, version: Option<&str>) -> Result<PromptTemplate, Error> {
+    match version {
+        Some(version) if template.version != version => Err(invalid_request(format!(
+            "Prompt template {} version {version} not found, the available version is {}",
+            template.name, template.version
+        ))),
+        _ => Ok(template),
+    }
+}
+
+/// Template names and versions are used in file names, so they are restricted to a safe set of
+/// characters
+fn check_identifier(kind: &str, value: &str) -> Result<(), Error> {
+    let valid = !value.is_empty()
+        && !value.starts_with('.')
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(invalid_request(format!(
+            "Invalid prompt template {kind}: {value}"
+        )))
+    }
+}
+
+fn template_error(template: &str, message: String) -> Error {
+    invalid_request(format!("Invalid prompt template {template}: {message}"))
+}
+
+fn invalid_request(message: String) -> Error {
+    Error {
+        code: ErrorCode::InvalidRequest,
+        message,
+        provider_error_json: None,
+    }
+}
+
+#[cfg(feature = "prompt-store")]
+mod prompt_store {
+    use crate::golem::llm::llm::{Error, ErrorCode};
+
+    wit_bindgen::generate!({
+        path: "../wit",
+        world: "prompt-store",
+    });
+
+    pub fn get_template(name: &str, version: Option<&str>) -> Result<Option<String>, Error> {
+        golem::llm::templates::get_template(name, version).map_err(|error| Error {
+            code: ErrorCode::InternalError,
+            message: format!("Failed to get prompt template {name} from the prompt store: {error}"),
+            provider_error_json: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::golem::llm::llm::{
+        Config, ContentPart, Error, ImageReference, ImageUrl, Kv, Message, Role,
+    };
+    use crate::golem::llm::prompts::{TemplateValue, TemplateVariable};
+    use crate::prompts::{take_prompt_template, PromptTemplate};
+
+    const SUPPORT_AGENT: &str = r#"
+@version 3
+@var customer: string
+@var tier: string = basic
+@var screenshot?: image
+# Comments are ignored
+
+[partial tone]
+Be concise and friendly.
+
+[system]
+You are a support agent for {{tier}} customers. {{> tone}} {{> signature}}
+
+[user]
+Hi, I'm {{customer}}.
+{{image screenshot}}
+"#;
+
+    fn variable(name: &str, value: TemplateValue) -> TemplateVariable {
+        TemplateVariable {
+            name: name.to_string(),
+            value,
+        }
+    }
+
+    fn load_partial(name: &str) -> Result<PromptTemplate, Error> {
+        assert_eq!(name, "signature");
+        PromptTemplate::parse(name, "Sign as {{tier}} support.", None)
+    }
+
+    #[test]
+    fn renders_sections_partials_and_images() {
+        let template = PromptTemplate::parse("support-agent", SUPPORT_AGENT, None).unwrap();
+        assert_eq!(template.id(), "support-agent@3");
+
+        let image = ImageReference::Url(ImageUrl {
+            url: "https://example.com/error.png".to_string(),
+            detail: None,
+        });
+        let messages = template
+            .render(
+                &[
+                    variable("customer", TemplateValue::Text("Ann".to_string())),
+                    variable("screenshot", TemplateValue::Image(image.clone())),
+                ],
+                &load_partial,
+            )
+            .unwrap();
+        assert_eq!(
+            messages,
+            vec![
+                Message {
+                    role: Role::System,
+                    name: None,
+                    content: vec![ContentPart::Text(
+                        "You are a support agent for basic customers. Be concise and friendly. Sign as basic support."
+                            .to_string()
+                    )],
+                },
+                Message {
+                    role: Role::User,
+                    name: None,
+                    content: vec![
+                        ContentPart::Text("Hi, I'm Ann.".to_string()),
+                        ContentPart::Image(image),
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_variables() {
+        let template = PromptTemplate::parse("support-agent", SUPPORT_AGENT, None).unwrap();
+        let render = |variables: &[TemplateVariable]| {
+            template
+                .render(variables, &load_partial)
+                .map_err(|error| error.message)
+        };
+
+        assert_eq!(
+            render(&[]),
+            Err("Missing variable customer of prompt template support-agent".to_string())
+        );
+        assert_eq!(
+            render(&[variable("customer", TemplateValue::Number(1.0))]),
+            Err("Variable customer of prompt template support-agent must be a string".to_string())
+        );
+        assert_eq!(
+            render(&[
+                variable("customer", TemplateValue::Text("Ann".to_string())),
+                variable("age", TemplateValue::Number(30.0)),
+            ]),
+            Err("Prompt template support-agent has no variable age".to_string())
+        );
+    }
+
+    #[test]
+    fn plain_templates_render_to_a_user_message() {
+        let source = "@var name: string\n\nHello {{name}}!";
+        let template = PromptTemplate::parse("greeting", source, None).unwrap();
+        assert_eq!(template.version.len(), 12);
+        assert_eq!(
+            template.version,
+            PromptTemplate::parse("greeting", source, None)
+                .unwrap()
+                .version
+        );
+        assert_eq!(
+            PromptTemplate::parse("greeting", source, Some("2"))
+                .unwrap()
+                .version,
+            "2"
+        );
+
+        let messages = template
+            .render(
+                &[variable("name", TemplateValue::Text("Bob".to_string()))],
+                &load_partial,
+            )
+            .unwrap();
+        assert_eq!(
+            messages,
+            vec![Message {
+                role: Role::User,
+                name: None,
+                content: vec![ContentPart::Text("Hello Bob!".to_string())],
+            }]
+        );
+    }
+
+    #[test]
+    fn prompt_template_option_is_removed() {
+        let mut config = Config {
+            model: "gpt-4o".to_string(),
+            temperature: None,
+            max_tokens: None,
+            stop_sequences: None,
+            top_p: None,
+            top_k: None,
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            repetition_penalty: None,
+            tools: vec![],
+            tool_choice: None,
+            provider_options: vec![Kv {
+                key: "prompt_template".to_string(),
+                value: "support-agent@3".to_string(),
+            }],
+            credentials: None,
+        };
+        assert_eq!(
+            take_prompt_template(&mut config),
+            Some("support-agent@3".to_string())
+        );
+        assert!(config.provider_options.is_empty());
+    }
+}
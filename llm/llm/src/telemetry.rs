@@ -0,0 +1,685 @@
+use crate::golem::llm::llm::{
+    ChatEvent, Config, ErrorCode, FinishReason, ResponseMetadata, StreamEvent, Usage,
+};
+use log::{info, warn};
+use serde_json::{json, Value};
+use std::cell::{Cell, RefCell};
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Value of a span attribute
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    String(String),
+    Int(i64),
+    Double(f64),
+    Bool(bool),
+    StringArray(Vec<String>),
+}
+
+/// A finished span describing a single call to the provider, with attributes following the
+/// OpenTelemetry semantic conventions for generative AI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub name: String,
+    /// 32 hex digits
+    pub trace_id: String,
+    /// 16 hex digits
+    pub span_id: String,
+    pub start_time: SystemTime,
+    pub end_time: SystemTime,
+    pub attributes: Vec<(String, AttributeValue)>,
+    /// The `error.type` of a failed call
+    pub error: Option<String>,
+}
+
+impl Span {
+    /// Encodes the span as an OTLP/JSON trace export request
+    pub fn to_otlp_json(&self, service_name: &str) -> Value {
+        let mut status = json!({ "code": 1 });
+        if let Some(error) = &self.error {
+            status = json!({ "code": 2, "message": error });
+        }
+
+        json!({
+            "resourceSpans": [{
+                "resource": {
+                    "attributes": [otlp_attribute("service.name", &AttributeValue::String(service_name.to_string()))]
+                },
+                "scopeSpans": [{
+                    "scope": { "name": "golem-llm" },
+                    "spans": [{
+                        "traceId": self.trace_id,
+                        "spanId": self.span_id,
+                        "name": self.name,
+                        "kind": 3,
+                        "startTimeUnixNano": unix_nanos(self.start_time).to_string(),
+                        "endTimeUnixNano": unix_nanos(self.end_time).to_string(),
+                        "attributes": self.attributes
+                            .iter()
+                            .map(|(key, value)| otlp_attribute(key, value))
+                            .collect::<Vec<_>>(),
+                        "status": status,
+                    }]
+                }]
+            }]
+        })
+    }
+
+    /// Encodes the span as a flat JSON object, used by the log exporter
+    pub fn to_log_json(&self) -> Value {
+        let attributes = self
+            .attributes
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    AttributeValue::String(value) => json!(value),
+                    AttributeValue::Int(value) => json!(value),
+                    AttributeValue::Double(value) => json!(value),
+                    AttributeValue::Bool(value) => json!(value),
+                    AttributeValue::StringArray(values) => json!(values),
+                };
+                (key.clone(), value)
+            })
+            .collect::<serde_json::Map<_, _>>();
+
+        json!({
+            "name": self.name,
+            "trace_id": self.trace_id,
+            "span_id": self.span_id,
+            "start_time_unix_nano": unix_nanos(self.start_time),
+            "end_time_unix_nano": unix_nanos(self.end_time),
+            "attributes": attributes,
+            "error": self.error,
+        })
+    }
+}
+
+fn otlp_attribute(key: &str, value: &AttributeValue) -> Value {
+    let value = match value {
+        AttributeValue::String(value) => json!({ "stringValue": value }),
+        // 64 bit integers are encoded as strings in OTLP/JSON
+        AttributeValue::Int(value) => json!({ "intValue": value.to_string() }),
+        AttributeValue::Double(value) => json!({ "doubleValue": value }),
+        AttributeValue::Bool(value) => json!({ "boolValue": value }),
+        AttributeValue::StringArray(values) => json!({
+            "arrayValue": {
+                "values": values.iter().map(|value| json!({ "stringValue": value })).collect::<Vec<_>>()
+            }
+        }),
+    };
+    json!({ "key": key, "value": value })
+}
+
+fn unix_nanos(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+fn random_hex_id(words: usize) -> String {
+    thread_local! {
+        static COUNTER: Cell<u64> = const { Cell::new(0) };
+    }
+
+    (0..words)
+        .map(|_| {
+            let counter = COUNTER.get();
+            COUNTER.set(counter.wrapping_add(1));
+            format!("{:016x}", RandomState::new().hash_one(counter))
+        })
+        .collect()
+}
+
+/// Where the spans are sent, selected by the `GOLEM_LLM_TELEMETRY` environment variable
+#[derive(Debug, Clone, PartialEq)]
+pub enum Exporter {
+    Disabled,
+    /// Each span is logged as a JSON object with the `info` level
+    Log,
+    /// Each span is sent as OTLP/JSON to the given HTTP endpoint
+    Otlp {
+        endpoint: String,
+    },
+    /// Each span is passed to the imported `golem:llm/telemetry` interface
+    #[cfg(feature = "telemetry-interface")]
+    Interface,
+}
+
+impl Exporter {
+    pub const ENV_VAR_NAME: &'static str = "GOLEM_LLM_TELEMETRY";
+    pub const OTLP_ENDPOINT_ENV_VAR_NAME: &'static str = "GOLEM_LLM_OTLP_ENDPOINT";
+    const DEFAULT_OTLP_ENDPOINT: &'static str = "http://localhost:4318/v1/traces";
+
+    /// Gets the exporter configured by the environment variables
+    pub fn from_env() -> Rc<Exporter> {
+        EXPORTER.with(|cached| {
+            cached
+                .borrow_mut()
+                .get_or_insert_with(|| {
+                    let endpoint = std::env::var(Self::OTLP_ENDPOINT_ENV_VAR_NAME)
+                        .unwrap_or_else(|_| Self::DEFAULT_OTLP_ENDPOINT.to_string());
+                    let name = std::env::var(Self::ENV_VAR_NAME).unwrap_or_default();
+                    Rc::new(Self::parse(&name, endpoint).unwrap_or_else(|message| {
+                        warn!("{message}");
+                        Exporter::Disabled
+                    }))
+                })
+                .clone()
+        })
+    }
+
+    pub fn parse(name: &str, otlp_endpoint: String) -> Result<Self, String> {
+        match name.trim().to_lowercase().as_str() {
+            "" | "off" | "none" => Ok(Exporter::Disabled),
+            "log" => Ok(Exporter::Log),
+            "otlp" => Ok(Exporter::Otlp {
+                endpoint: otlp_endpoint,
+            }),
+            #[cfg(feature = "telemetry-interface")]
+            "interface" => Ok(Exporter::Interface),
+            other => Err(format!(
+                "Unsupported value for {}: {other}, telemetry is disabled",
+                Self::ENV_VAR_NAME
+            )),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        *self != Exporter::Disabled
+    }
+
+    /// Exports a span. Failures are logged and otherwise ignored.
+    pub fn export(&self, provider: &str, span: &Span) {
+        match self {
+            Exporter::Disabled => {}
+            Exporter::Log => {
+                info!(target: "golem_llm::telemetry", "{}", span.to_log_json());
+            }
+            Exporter::Otlp { endpoint } => {
+                let service_name = format!("golem-llm-{provider}");
+                let result = reqwest::Client::new()
+                    .post(endpoint)
+                    .json(&span.to_otlp_json(&service_name))
+                    .send()
+                    .and_then(|response| response.error_for_status());
+                if let Err(error) = result {
+                    warn!("Failed to export telemetry span to {endpoint}: {error}");
+                }
+            }
+            #[cfg(feature = "telemetry-interface")]
+            Exporter::Interface => telemetry_interface::export_span(span),
+        }
+    }
+}
+
+thread_local! {
+    static EXPORTER: RefCell<Option<Rc<Exporter>>> = const { RefCell::new(None) };
+}
+
+#[cfg(feature = "telemetry-interface")]
+mod telemetry_interface {
+    use crate::telemetry::{unix_nanos, AttributeValue, Span};
+
+    wit_bindgen::generate!({
+        path: "../wit",
+        world: "telemetry-exporter",
+    });
+
+    use golem::llm::telemetry;
+
+    pub fn export_span(span: &Span) {
+        telemetry::export_span(&telemetry::Span {
+            name: span.name.clone(),
+            trace_id: span.trace_id.clone(),
+            span_id: span.span_id.clone(),
+            start_time_unix_nano: unix_nanos(span.start_time),
+            end_time_unix_nano: unix_nanos(span.end_time),
+            attributes: span
+                .attributes
+                .iter()
+                .map(|(key, value)| telemetry::Attribute {
+                    key: key.clone(),
+                    value: match value {
+                        AttributeValue::String(value) => {
+                            telemetry::AttributeValue::String(value.clone())
+                        }
+                        AttributeValue::Int(value) => telemetry::AttributeValue::Int(*value),
+                        AttributeValue::Double(value) => telemetry::AttributeValue::Double(*value),
+                        AttributeValue::Bool(value) => telemetry::AttributeValue::Boolean(*value),
+                        AttributeValue::StringArray(values) => {
+                            telemetry::AttributeValue::StringArray(values.clone())
+                        }
+                    },
+                })
+                .collect(),
+            error: span.error.clone(),
+        })
+    }
+}
+
+/// Records a span for a single `send`, `continue` or `stream` call. The span is exported when
+/// `end` is called, which only happens once.
+///
+/// Spans are only recorded for calls actually sent to the provider, so nothing is exported while
+/// a worker is replaying its oplog.
+pub(crate) struct ChatSpan {
+    provider: &'static str,
+    config: SpanConfig,
+    streaming: bool,
+    started: Instant,
+    start_time: SystemTime,
+    first_token: Option<Duration>,
+    retries: u32,
+    response_id: Option<String>,
+    finish_reasons: Vec<&'static str>,
+    usage: Option<Usage>,
+    error: Option<ErrorCode>,
+    ended: bool,
+}
+
+struct SpanConfig {
+    model: String,
+    temperature: Option<f32>,
+    max_tokens: Option<u32>,
+}
+
+impl ChatSpan {
+    pub fn start(provider: &'static str, config: &Config, streaming: bool) -> Self {
+        Self {
+            provider,
+            config: SpanConfig {
+                model: config.model.clone(),
+                temperature: config.temperature,
+                max_tokens: config.max_tokens,
+            },
+            streaming,
+            started: Instant::now(),
+            start_time: SystemTime::now(),
+            first_token: None,
+            retries: 0,
+            response_id: None,
+            finish_reasons: Vec::new(),
+            usage: None,
+            error: None,
+            ended: false,
+        }
+    }
+
+    /// Marks the call as a retry of an interrupted stream
+    pub fn retried(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    pub fn record_event(&mut self, event: &ChatEvent) {
+        match event {
+            ChatEvent::Message(response) => {
+                self.response_id = Some(response.id.clone());
+                self.record_metadata(&response.metadata);
+            }
+            ChatEvent::ToolRequest(_) => {
+                self.finish_reasons.push("tool_calls");
+            }
+            ChatEvent::Error(error) => {
+                self.error = Some(error.code);
+            }
+        }
+    }
+
+    /// Records a batch of stream events, and returns true if the stream has finished
+    pub fn record_stream_events(&mut self, events: &[StreamEvent]) -> bool {
+        let mut finished = false;
+        for event in events {
+            match event {
+                StreamEvent::Delta(_) => {
+                    if self.first_token.is_none() {
+                        self.first_token = Some(self.started.elapsed());
+                    }
+                }
+                StreamEvent::Finish(metadata) => {
+                    self.record_metadata(metadata);
+                    finished = true;
+                }
+                StreamEvent::Error(error) => {
+                    self.error = Some(error.code);
+                    finished = true;
+                }
+            }
+        }
+        finished
+    }
+
+    pub fn record_error(&mut self, code: ErrorCode) {
+        self.error = Some(code);
+    }
+
+    fn record_metadata(&mut self, metadata: &ResponseMetadata) {
+        if let Some(id) = &metadata.provider_id {
+            self.response_id.get_or_insert_with(|| id.clone());
+        }
+        if let Some(reason) = metadata.finish_reason {
+            self.finish_reasons.push(finish_reason_name(reason));
+        }
+        if metadata.usage.is_some() {
+            self.usage = metadata.usage.clone();
+        }
+    }
+
+    /// Finishes the span and exports it
+    pub fn end(&mut self) {
+        if self.ended {
+            return;
+        }
+        self.ended = true;
+
+        let exporter = Exporter::from_env();
+        if exporter.is_enabled() {
+            let span = self.to_span(self.started.elapsed());
+            exporter.export(self.provider, &span);
+        }
+    }
+
+    fn to_span(&self, latency: Duration) -> Span {
+        let mut attributes = vec![
+            (
+                "gen_ai.operation.name".to_string(),
+                AttributeValue::String("chat".to_string()),
+            ),
+            (
+                "gen_ai.system".to_string(),
+                AttributeValue::String(system_name(self.provider).to_string()),
+            ),
+            (
+                "gen_ai.request.model".to_string(),
+                AttributeValue::String(self.config.model.clone()),
+            ),
+            (
+                "golem_llm.streaming".to_string(),
+                AttributeValue::Bool(self.streaming),
+            ),
+            (
+                "golem_llm.latency_ms".to_string(),
+                AttributeValue::Double(millis(latency)),
+            ),
+            (
+                "golem_llm.retries".to_string(),
+                AttributeValue::Int(self.retries as i64),
+            ),
+        ];
+
+        if let Some(temperature) = self.config.temperature {
+            attributes.push((
+                "gen_ai.request.temperature".to_string(),
+                AttributeValue::Double(temperature as f64),
+            ));
+        }
+        if let Some(max_tokens) = self.config.max_tokens {
+            attributes.push((
+                "gen_ai.request.max_tokens".to_string(),
+                AttributeValue::Int(max_tokens as i64),
+            ));
+        }
+        if let Some(id) = &self.response_id {
+            attributes.push((
+                "gen_ai.response.id".to_string(),
+                AttributeValue::String(id.clone()),
+            ));
+        }
+        if !self.finish_reasons.is_empty() {
+            attributes.push((
+                "gen_ai.response.finish_reasons".to_string(),
+                AttributeValue::StringArray(
+                    self.finish_reasons.iter().map(|r| r.to_string()).collect(),
+                ),
+            ));
+        }
+        if let Some(first_token) = self.first_token {
+            attributes.push((
+                "golem_llm.time_to_first_token_ms".to_string(),
+                AttributeValue::Double(millis(first_token)),
+            ));
+        }
+        if let Some(usage) = &self.usage {
+            if let Some(input_tokens) = usage.input_tokens {
+                attributes.push((
+                    "gen_ai.usage.input_tokens".to_string(),
+                    AttributeValue::Int(input_tokens as i64),
+                ));
+            }
+            if let Some(output_tokens) = usage.output_tokens {
+                attributes.push((
+                    "gen_ai.usage.output_tokens".to_string(),
+                    AttributeValue::Int(output_tokens as i64),
+                ));
+
+                // For streams, the generation speed is measured from the first token
+                let generation = latency.saturating_sub(self.first_token.unwrap_or_default());
+                if !generation.is_zero() {
+                    attributes.push((
+                        "golem_llm.tokens_per_second".to_string(),
+                        AttributeValue::Double(output_tokens as f64 / generation.as_secs_f64()),
+                    ));
+                }
+            }
+        }
+
+        let error = self.error.map(|code| error_type(code).to_string());
+        if let Some(error) = &error {
+            attributes.push((
+                "error.type".to_string(),
+                AttributeValue::String(error.clone()),
+            ));
+        }
+
+        Span {
+            name: format!("chat {}", self.config.model),
+            trace_id: random_hex_id(2),
+            span_id: random_hex_id(1),
+            start_time: self.start_time,
+            end_time: self.start_time + latency,
+            attributes,
+            error,
+        }
+    }
+}
+
+impl Drop for ChatSpan {
+    fn drop(&mut self) {
+        // Streams dropped before they finished are still reported
+        self.end();
+    }
+}
+
+/// Runs `send` or `continue` while recording its span
+pub(crate) fn traced(
+    provider: &'static str,
+    config: &Config,
+    call: impl FnOnce() -> ChatEvent,
+) -> ChatEvent {
+    let mut span = ChatSpan::start(provider, config, false);
+    let event = call();
+    span.record_event(&event);
+    span.end();
+    event
+}
+
+/// The `gen_ai.system` value of a provider
+fn system_name(provider: &str) -> &str {
+    match provider {
+        "bedrock" => "aws.bedrock",
+        "grok" => "xai",
+        other => other,
+    }
+}
+
+fn finish_reason_name(reason: FinishReason) -> &'static str {
+    match reason {
+        FinishReason::Stop => "stop",
+        FinishReason::Length => "length",
+        FinishReason::ToolCalls => "tool_calls",
+        FinishReason::ContentFilter => "content_filter",
+        FinishReason::Error => "error",
+        FinishReason::Other => "other",
+    }
+}
+
+fn error_type(code: ErrorCode) -> &'static str {
+    match code {
+        ErrorCode::InvalidRequest => "invalid_request",
+        ErrorCode::AuthenticationFailed => "authentication_failed",
+        ErrorCode::RateLimitExceeded => "rate_limit_exceeded",
+        ErrorCode::InternalError => "internal_error",
+        ErrorCode::Unsupported => "unsupported",
+        ErrorCode::Unknown => "unknown",
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::golem::llm::llm::{
+        CompleteResponse, Config, Error, ErrorCode, FinishReason, ResponseMetadata, StreamDelta,
+        StreamEvent, Usage,
+    };
+    use crate::telemetry::{AttributeValue, ChatSpan, Exporter};
+    use std::time::Duration;
+
+    fn config() -> Config {
+        Config {
+            model: "claude-sonnet-4".to_string(),
+            temperature: Some(0.5),
+            max_tokens: Some(1024),
+            stop_sequences: None,
+            top_p: None,
+            top_k: None,
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            repetition_penalty: None,
+            tools: vec![],
+            tool_choice: None,
+            provider_options: vec![],
+            credentials: None,
+        }
+    }
+
+    fn metadata(output_tokens: u32) -> ResponseMetadata {
+        ResponseMetadata {
+            finish_reason: Some(FinishReason::Stop),
+            usage: Some(Usage {
+                input_tokens: Some(12),
+                output_tokens: Some(output_tokens),
+                total_tokens: None,
+                cached_input_tokens: None,
+                reasoning_tokens: None,
+            }),
+            provider_id: None,
+            timestamp: None,
+            provider_metadata_json: None,
+            cost: None,
+        }
+    }
+
+    fn attribute<'a>(
+        attributes: &'a [(String, AttributeValue)],
+        key: &str,
+    ) -> Option<&'a AttributeValue> {
+        attributes
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+
+    #[test]
+    fn records_response_attributes() {
+        let mut span = ChatSpan::start("bedrock", &config(), false);
+        span.record_event(&crate::golem::llm::llm::ChatEvent::Message(
+            CompleteResponse {
+                id: "msg_1".to_string(),
+                content: vec![],
+                tool_calls: vec![],
+                metadata: metadata(200),
+            },
+        ));
+        let span_data = span.to_span(Duration::from_secs(2));
+
+        assert_eq!(span_data.name, "chat claude-sonnet-4");
+        assert_eq!(span_data.trace_id.len(), 32);
+        assert_eq!(span_data.span_id.len(), 16);
+        assert_eq!(
+            attribute(&span_data.attributes, "gen_ai.system"),
+            Some(&AttributeValue::String("aws.bedrock".to_string()))
+        );
+        assert_eq!(
+            attribute(&span_data.attributes, "gen_ai.response.id"),
+            Some(&AttributeValue::String("msg_1".to_string()))
+        );
+        assert_eq!(
+            attribute(&span_data.attributes, "gen_ai.usage.output_tokens"),
+            Some(&AttributeValue::Int(200))
+        );
+        assert_eq!(
+            attribute(&span_data.attributes, "golem_llm.tokens_per_second"),
+            Some(&AttributeValue::Double(100.0))
+        );
+        assert_eq!(span_data.error, None);
+    }
+
+    #[test]
+    fn records_stream_errors_and_first_token() {
+        let mut span = ChatSpan::start("openai", &config(), true).retried(1);
+        assert!(
+            !span.record_stream_events(&[StreamEvent::Delta(StreamDelta {
+                content: None,
+                tool_calls: None,
+            })])
+        );
+        assert!(span.record_stream_events(&[StreamEvent::Error(Error {
+            code: ErrorCode::RateLimitExceeded,
+            message: "Too many requests".to_string(),
+            provider_error_json: None,
+        })]));
+        let span_data = span.to_span(Duration::from_secs(1));
+
+        assert!(attribute(&span_data.attributes, "golem_llm.time_to_first_token_ms").is_some());
+        assert_eq!(
+            attribute(&span_data.attributes, "golem_llm.retries"),
+            Some(&AttributeValue::Int(1))
+        );
+        assert_eq!(span_data.error, Some("rate_limit_exceeded".to_string()));
+
+        let otlp = span_data.to_otlp_json("golem-llm-openai");
+        let otlp_span = &otlp["resourceSpans"][0]["scopeSpans"][0]["spans"][0];
+        assert_eq!(otlp_span["status"]["code"], 2);
+        assert_eq!(otlp_span["kind"], 3);
+        assert!(otlp_span["attributes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|attribute| attribute["key"] == "golem_llm.retries"
+                && attribute["value"]["intValue"] == "1"));
+    }
+
+    #[test]
+    fn parses_exporter() {
+        let endpoint = "http://collector:4318/v1/traces".to_string();
+        assert_eq!(
+            Exporter::parse("", endpoint.clone()),
+            Ok(Exporter::Disabled)
+        );
+        assert_eq!(Exporter::parse("LOG", endpoint.clone()), Ok(Exporter::Log));
+        assert_eq!(
+            Exporter::parse("otlp", endpoint.clone()),
+            Ok(Exporter::Otlp {
+                endpoint: endpoint.clone()
+            })
+        );
+        assert!(Exporter::parse("zipkin", endpoint).is_err());
+    }
+}
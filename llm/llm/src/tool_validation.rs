@@ -0,0 +1,741 @@
+use crate::golem::llm::llm::{
+    ChatEvent, Config, Error, ErrorCode, ToolCall, ToolDefinition, ToolFailure, ToolResult,
+};
+use log::{trace, warn};
+use regex::Regex;
+use serde_json::Value;
+
+/// Provider option selecting the tool call validation of a single request, with the same values
+/// as `GOLEM_LLM_TOOL_VALIDATION`. It is removed from the configuration before the request is
+/// passed to the provider.
+pub const TOOL_VALIDATION_OPTION: &str = "tool_validation";
+
+/// Provider option overriding `GOLEM_LLM_TOOL_REPAIR_ATTEMPTS` for a single request
+pub const TOOL_REPAIR_ATTEMPTS_OPTION: &str = "tool_repair_attempts";
+
+/// Maximum nesting of `$ref`, `anyOf`, `oneOf` and `allOf` followed while validating
+const MAX_SCHEMA_DEPTH: usize = 32;
+
+/// Maximum number of truncation points tried when repairing JSON
+const MAX_REPAIR_CANDIDATES: usize = 32;
+
+/// How the tool calls returned by the model are checked against the `parameters-schema` of
+/// their tool definitions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolValidation {
+    /// Tool calls are returned as the model produced them
+    Off,
+    /// Invalid tool calls turn the response into an error
+    Validate,
+    /// Truncated or malformed JSON arguments are repaired, and the model is asked again with the
+    /// validation errors at most `attempts` times before failing
+    Repair { attempts: u32 },
+}
+
+impl ToolValidation {
+    pub const ENV_VAR_NAME: &'static str = "GOLEM_LLM_TOOL_VALIDATION";
+    pub const REPAIR_ATTEMPTS_ENV_VAR_NAME: &'static str = "GOLEM_LLM_TOOL_REPAIR_ATTEMPTS";
+    const DEFAULT_REPAIR_ATTEMPTS: u32 = 1;
+
+    pub fn parse(value: &str, attempts: Option<&str>) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "off" => Ok(ToolValidation::Off),
+            "validate" => Ok(ToolValidation::Validate),
+            "repair" => {
+                let attempts = match attempts {
+                    Some(attempts) => attempts
+                        .trim()
+                        .parse::<u32>()
+                        .map_err(|err| format!("Invalid number of tool repair attempts: {err}"))?,
+                    None => Self::DEFAULT_REPAIR_ATTEMPTS,
+                };
+                Ok(ToolValidation::Repair { attempts })
+            }
+            other => Err(format!("Unsupported tool validation: {other}")),
+        }
+    }
+}
+
+/// Removes the tool validation options from the provider options of a request, and returns the
+/// validation to use for it
+pub(crate) fn take_tool_validation(config: &mut Config) -> ToolValidation {
+    let validation = take_option(config, TOOL_VALIDATION_OPTION)
+        .unwrap_or_else(|| std::env::var(ToolValidation::ENV_VAR_NAME).unwrap_or_default());
+    let attempts = take_option(config, TOOL_REPAIR_ATTEMPTS_OPTION)
+        .or_else(|| std::env::var(ToolValidation::REPAIR_ATTEMPTS_ENV_VAR_NAME).ok());
+    ToolValidation::parse(&validation, attempts.as_deref()).unwrap_or_else(|message| {
+        warn!("{message}, tool calls are not validated");
+        ToolValidation::Off
+    })
+}
+
+fn take_option(config: &mut Config, key: &str) -> Option<String> {
+    let index = config
+        .provider_options
+        .iter()
+        .position(|kv| kv.key == key)?;
+    Some(config.provider_options.remove(index).value)
+}
+
+/// Runs `send` or `continue` and checks the returned tool calls against the schemas of the tools
+/// in the config.
+///
+/// `call` receives the tool results to append to the conversation. It is first called with no
+/// tool results, and with `Repair` it is called again with the invalid tool calls answered by
+/// their validation errors, so the model can correct them.
+pub(crate) fn validated(
+    config: &Config,
+    validation: ToolValidation,
+    mut call: impl FnMut(Vec<(ToolCall, ToolResult)>) -> ChatEvent,
+) -> ChatEvent {
+    if validation == ToolValidation::Off || config.tools.is_empty() {
+        return call(vec![]);
+    }
+
+    let repair = matches!(validation, ToolValidation::Repair { .. });
+    let mut attempts_left = match validation {
+        ToolValidation::Repair { attempts } => attempts,
+        _ => 0,
+    };
+    let mut feedback = Vec::new();
+    loop {
+        let mut event = call(feedback.clone());
+        let tool_calls = match &mut event {
+            ChatEvent::ToolRequest(tool_calls) => tool_calls,
+            ChatEvent::Message(response) => &mut response.tool_calls,
+            ChatEvent::Error(_) => return event,
+        };
+
+        let invalid = check_tool_calls(&config.tools, tool_calls, repair);
+        if invalid.is_empty() {
+            return event;
+        }
+        if attempts_left == 0 {
+            return ChatEvent::Error(invalid_tool_calls_error(&invalid));
+        }
+
+        attempts_left -= 1;
+        trace!(
+            "Asking the model to correct {} invalid tool calls",
+            invalid.len()
+        );
+        feedback.extend(tool_calls.iter().map(|tool_call| {
+            let result = match invalid.iter().find(|invalid| invalid.id == tool_call.id) {
+                Some(invalid) => ToolFailure {
+                    id: tool_call.id.clone(),
+                    name: tool_call.name.clone(),
+                    error_message: format!(
+                        "Invalid arguments: {}. Call the tool again with arguments matching its parameters schema.",
+                        invalid.errors.join("; ")
+                    ),
+                    error_code: Some("invalid_arguments".to_string()),
+                },
+                None => ToolFailure {
+                    id: tool_call.id.clone(),
+                    name: tool_call.name.clone(),
+                    error_message: "Not executed, because other tool calls had invalid arguments. Call the tool again if it is still needed.".to_string(),
+                    error_code: Some("not_executed".to_string()),
+                },
+            };
+            (tool_call.clone(), ToolResult::Error(result))
+        }));
+    }
+}
+
+/// A tool call which does not match the schema of its tool
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments_json: String,
+    pub errors: Vec<String>,
+}
+
+/// Checks the tool calls against the schemas of the tools. With `repair`, malformed JSON
+/// arguments are replaced by their repaired version when it is valid.
+pub fn check_tool_calls(
+    tools: &[ToolDefinition],
+    tool_calls: &mut [ToolCall],
+    repair: bool,
+) -> Vec<InvalidToolCall> {
+    let mut invalid = Vec::new();
+    for tool_call in tool_calls.iter_mut() {
+        let errors = match check_tool_call(tools, tool_call, repair) {
+            Ok(()) => continue,
+            Err(errors) => errors,
+        };
+        invalid.push(InvalidToolCall {
+            id: tool_call.id.clone(),
+            name: tool_call.name.clone(),
+            arguments_json: tool_call.arguments_json.clone(),
+            errors,
+        });
+    }
+    invalid
+}
+
+fn check_tool_call(
+    tools: &[ToolDefinition],
+    tool_call: &mut ToolCall,
+    repair: bool,
+) -> Result<(), Vec<String>> {
+    let Some(tool) = tools.iter().find(|tool| tool.name == tool_call.name) else {
+        return Err(vec![format!("unknown tool {}", tool_call.name)]);
+    };
+
+    let arguments = match serde_json::from_str::<Value>(&tool_call.arguments_json) {
+        Ok(arguments) => arguments,
+        Err(err) => {
+            let repaired = if repair {
+                repair_json(&tool_call.arguments_json)
+            } else {
+                None
+            };
+            match repaired {
+                Some(repaired) => {
+                    trace!(
+                        "Repaired the arguments of tool call {}: {repaired}",
+                        tool_call.id
+                    );
+                    tool_call.arguments_json = repaired;
+                    serde_json::from_str(&tool_call.arguments_json)
+                        .map_err(|err| vec![format!("arguments are not valid JSON: {err}")])?
+                }
+                None => return Err(vec![format!("arguments are not valid JSON: {err}")]),
+            }
+        }
+    };
+
+    let schema = match serde_json::from_str::<Value>(&tool.parameters_schema) {
+        Ok(schema) => schema,
+        Err(err) => {
+            warn!(
+                "The parameters schema of tool {} is not valid JSON, its calls are not validated: {err}",
+                tool.name
+            );
+            return Ok(());
+        }
+    };
+
+    let errors = validate_json(&schema, &arguments);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn invalid_tool_calls_error(invalid: &[InvalidToolCall]) -> Error {
+    let details = invalid
+        .iter()
+        .map(|invalid| format!("{} ({})", invalid.name, invalid.errors.join("; ")))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Error {
+        code: ErrorCode::InvalidRequest,
+        message: format!("The model returned tool calls with invalid arguments: {details}"),
+        provider_error_json: Some(
+            serde_json::json!({
+                "invalid_tool_calls": invalid
+                    .iter()
+                    .map(|invalid| serde_json::json!({
+                        "id": invalid.id,
+                        "name": invalid.name,
+                        "arguments_json": invalid.arguments_json,
+                        "errors": invalid.errors,
+                    }))
+                    .collect::<Vec<_>>()
+            })
+            .to_string(),
+        ),
+    }
+}
+
+/// Validates a JSON value against a JSON schema, and returns the validation errors.
+///
+/// The commonly used subset of JSON Schema is supported: `type`, `enum`, `const`, `properties`,
+/// `required`, `additionalProperties`, `items`, the numeric, length and size bounds, `pattern`,
+/// `anyOf`, `oneOf`, `allOf` and local `$ref` references. Other keywords are ignored.
+pub fn validate_json(schema: &Value, value: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_value(schema, schema, value, "$", 0, &mut errors);
+    errors
+}
+
+fn validate_value(
+    root: &Value,
+    schema: &Value,
+    value: &Value,
+    path: &str,
+    depth: usize,
+    errors: &mut Vec<String>,
+) {
+    let schema = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => {
+            errors.push(format!("{path}: no value is allowed"));
+            return;
+        }
+        Value::Object(schema) => schema,
+        _ => return,
+    };
+    if depth > MAX_SCHEMA_DEPTH {
+        errors.push(format!("{path}: the schema is nested too deeply"));
+        return;
+    }
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer))
+        {
+            Some(referenced) => validate_value(root, referenced, value, path, depth + 1, errors),
+            None => warn!("Unsupported schema reference {reference}"),
+        }
+    }
+
+    if let Some(types) = schema.get("type") {
+        let types = match types {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        if !types.is_empty() && !types.iter().any(|name| has_type(value, name)) {
+            errors.push(format!(
+                "{path}: expected {}, found {}",
+                types.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            errors.push(format!(
+                "{path}: expected one of {}, found {value}",
+                Value::Array(allowed.clone())
+            ));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            errors.push(format!("{path}: expected {expected}, found {value}"));
+        }
+    }
+
+    for sub_schema in schema
+        .get("allOf")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        validate_value(root, sub_schema, value, path, depth + 1, errors);
+    }
+    if let Some(sub_schemas) = schema.get("anyOf").and_then(Value::as_array) {
+        let matching = count_matching(root, sub_schemas, value, path, depth);
+        if matching == 0 {
+            errors.push(format!("{path}: does not match any of the allowed schemas"));
+        }
+    }
+    if let Some(sub_schemas) = schema.get("oneOf").and_then(Value::as_array) {
+        let matching = count_matching(root, sub_schemas, value, path, depth);
+        if matching != 1 {
+            errors.push(format!(
+                "{path}: expected to match exactly one of the allowed schemas, matches {matching}"
+            ));
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for required in schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+            {
+                if !object.contains_key(required) {
+                    errors.push(format!("{path}: missing required property {required}"));
+                }
+            }
+            for (key, property) in object {
+                let property_path = format!("{path}.{key}");
+                match properties.and_then(|properties| properties.get(key)) {
+                    Some(property_schema) => validate_value(
+                        root,
+                        property_schema,
+                        property,
+                        &property_path,
+                        depth + 1,
+                        errors,
+                    ),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            errors.push(format!("{path}: unexpected property {key}"));
+                        }
+                        Some(additional @ Value::Object(_)) => validate_value(
+                            root,
+                            additional,
+                            property,
+                            &property_path,
+                            depth + 1,
+                            errors,
+                        ),
+                        _ => {}
+                    },
+                }
+            }
+            check_bound(schema, "minProperties", object.len(), path, errors);
+            check_bound(schema, "maxProperties", object.len(), path, errors);
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_value(
+                        root,
+                        item_schema,
+                        item,
+                        &format!("{path}[{index}]"),
+                        depth + 1,
+                        errors,
+                    );
+                }
+            }
+            check_bound(schema, "minItems", items.len(), path, errors);
+            check_bound(schema, "maxItems", items.len(), path, errors);
+        }
+        Value::String(string) => {
+            let length = string.chars().count();
+            check_bound(schema, "minLength", length, path, errors);
+            check_bound(schema, "maxLength", length, path, errors);
+            if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+                match Regex::new(pattern) {
+                    Ok(regex) if !regex.is_match(string) => {
+                        errors.push(format!("{path}: does not match the pattern {pattern}"));
+                    }
+                    Ok(_) => {}
+                    Err(err) => warn!("Unsupported schema pattern {pattern}: {err}"),
+                }
+            }
+        }
+        Value::Number(number) => {
+            if let Some(number) = number.as_f64() {
+                let limit = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+                if limit("minimum").is_some_and(|minimum| number < minimum)
+                    || limit("exclusiveMinimum").is_some_and(|minimum| number <= minimum)
+                {
+                    errors.push(format!("{path}: {number} is below the minimum"));
+                }
+                if limit("maximum").is_some_and(|maximum| number > maximum)
+                    || limit("exclusiveMaximum").is_some_and(|maximum| number >= maximum)
+                {
+                    errors.push(format!("{path}: {number} is above the maximum"));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn count_matching(
+    root: &Value,
+    sub_schemas: &[Value],
+    value: &Value,
+    path: &str,
+    depth: usize,
+) -> usize {
+    sub_schemas
+        .iter()
+        .filter(|sub_schema| {
+            let mut errors = Vec::new();
+            validate_value(root, sub_schema, value, path, depth + 1, &mut errors);
+            errors.is_empty()
+        })
+        .count()
+}
+
+fn check_bound(
+    schema: &serde_json::Map<String, Value>,
+    keyword: &str,
+    size: usize,
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    let Some(bound) = schema.get(keyword).and_then(Value::as_u64) else {
+        return;
+    };
+    let violated = if keyword.starts_with("min") {
+        (size as u64) < bound
+    } else {
+        (size as u64) > bound
+    };
+    if violated {
+        errors.push(format!("{path}: size {size} violates {keyword} {bound}"));
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64()
+                || value.is_u64()
+                || value.as_f64().is_some_and(|number| number.fract() == 0.0)
+        }
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Repairs malformed JSON produced by a model: strips Markdown code fences and trailing commas,
+/// and completes truncated output by closing the open strings, arrays and objects. When the
+/// truncated value can't be completed, it is cut back to the last complete element. Returns
+/// `None` if no valid JSON could be recovered.
+pub fn repair_json(text: &str) -> Option<String> {
+    let text = strip_code_fence(text.trim());
+    if text.is_empty() {
+        return Some("{}".to_string());
+    }
+
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut output = String::with_capacity(text.len());
+    let mut closers = Vec::new();
+    // Prefixes of the output after which the open arrays and objects can be closed
+    let mut cut_points = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, &c) in chars.iter().enumerate() {
+        if in_string {
+            output.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                output.push(c);
+            }
+            '{' | '[' => {
+                output.push(c);
+                closers.push(if c == '{' { '}' } else { ']' });
+                cut_points.push((output.len(), closers.clone()));
+            }
+            '}' | ']' => {
+                if closers.last() == Some(&c) {
+                    closers.pop();
+                }
+                output.push(c);
+            }
+            ',' => {
+                let next = chars[index + 1..].iter().find(|c| !c.is_whitespace());
+                if matches!(next, Some('}') | Some(']')) {
+                    continue;
+                }
+                cut_points.push((output.len(), closers.clone()));
+                output.push(c);
+            }
+            _ => output.push(c),
+        }
+    }
+
+    let mut complete = output.clone();
+    if in_string {
+        if escaped {
+            complete.pop();
+        }
+        complete.push('"');
+    }
+    std::iter::once(close_json(complete, &closers))
+        .chain(
+            cut_points
+                .iter()
+                .rev()
+                .take(MAX_REPAIR_CANDIDATES)
+                .map(|(length, closers)| close_json(output[..*length].to_string(), closers)),
+        )
+        .find(|candidate| serde_json::from_str::<Value>(candidate).is_ok())
+}
+
+fn strip_code_fence(text: &str) -> &str {
+    match text.strip_prefix("```") {
+        Some(fenced) => {
+            let body = fenced.split_once('\n').map_or("", |(_, body)| body);
+            body.trim_end().strip_suffix("```").unwrap_or(body).trim()
+        }
+        None => text,
+    }
+}
+
+fn close_json(mut json: String, closers: &[char]) -> String {
+    let trimmed = json.trim_end().trim_end_matches([',', ':']).len();
+    json.truncate(trimmed);
+    json.extend(closers.iter().rev());
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::golem::llm::llm::{
+        ChatEvent, Config, ErrorCode, Kv, ToolCall, ToolDefinition, ToolResult,
+    };
+    use crate::tool_validation::{
+        repair_json, take_tool_validation, validate_json, validated, ToolValidation,
+    };
+    use serde_json::json;
+
+    fn config() -> Config {
+        Config {
+            model: "gpt-4o".to_string(),
+            temperature: None,
+            max_tokens: None,
+            stop_sequences: None,
+            top_p: None,
+            top_k: None,
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            repetition_penalty: None,
+            tools: vec![ToolDefinition {
+                name: "get_weather".to_string(),
+                description: None,
+                parameters_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "city": { "type": "string" },
+                        "unit": { "enum": ["celsius", "fahrenheit"] }
+                    },
+                    "required": ["city"],
+                    "additionalProperties": false
+                })
+                .to_string(),
+            }],
+            tool_choice: None,
+            provider_options: vec![
+                Kv {
+                    key: "tool_validation".to_string(),
+                    value: "repair".to_string(),
+                },
+                Kv {
+                    key: "tool_repair_attempts".to_string(),
+                    value: "2".to_string(),
+                },
+            ],
+            credentials: None,
+        }
+    }
+
+    fn tool_request(arguments_json: &str) -> ChatEvent {
+        ChatEvent::ToolRequest(vec![ToolCall {
+            id: "call_1".to_string(),
+            name: "get_weather".to_string(),
+            arguments_json: arguments_json.to_string(),
+        }])
+    }
+
+    #[test]
+    fn repairs_truncated_json() {
+        assert_eq!(
+            repair_json(r#"{"city": "Par"#),
+            Some(r#"{"city": "Par"}"#.to_string())
+        );
+        assert_eq!(
+            repair_json(r#"{"city": "Paris", "unit""#),
+            Some(r#"{"city": "Paris"}"#.to_string())
+        );
+        assert_eq!(
+            repair_json("```json\n{\"tags\": [1, 2,],}\n```"),
+            Some(r#"{"tags": [1, 2]}"#.to_string())
+        );
+        assert_eq!(repair_json(""), Some("{}".to_string()));
+        assert_eq!(repair_json("not json"), None);
+    }
+
+    #[test]
+    fn validates_against_schema() {
+        let schema: serde_json::Value =
+            serde_json::from_str(&config().tools[0].parameters_schema).unwrap();
+        assert!(validate_json(&schema, &json!({ "city": "Paris" })).is_empty());
+        let mut errors = validate_json(&schema, &json!({ "unit": "kelvin", "country": "FR" }));
+        errors.sort();
+        assert_eq!(
+            errors,
+            vec![
+                "$: missing required property city".to_string(),
+                "$: unexpected property country".to_string(),
+                r#"$.unit: expected one of ["celsius","fahrenheit"], found "kelvin""#.to_string(),
+            ]
+        );
+        assert_eq!(
+            validate_json(
+                &json!({ "type": "array", "items": { "type": "integer" } }),
+                &json!([1, "2"])
+            ),
+            vec!["$[1]: expected integer, found string".to_string()]
+        );
+    }
+
+    #[test]
+    fn tool_validation_options_are_removed() {
+        let mut config = config();
+        assert_eq!(
+            take_tool_validation(&mut config),
+            ToolValidation::Repair { attempts: 2 }
+        );
+        assert!(config.provider_options.is_empty());
+    }
+
+    #[test]
+    fn invalid_tool_calls_are_sent_back_to_the_model() {
+        let mut feedback_lengths = Vec::new();
+        let event = validated(
+            &config(),
+            ToolValidation::Repair { attempts: 1 },
+            |feedback| {
+                feedback_lengths.push(feedback.len());
+                match feedback.first() {
+                    None => tool_request(r#"{"town": "Paris"}"#),
+                    Some((_, ToolResult::Error(failure))) => {
+                        assert_eq!(failure.error_code.as_deref(), Some("invalid_arguments"));
+                        tool_request(r#"{"city": "Paris""#)
+                    }
+                    Some(_) => panic!("unexpected tool result"),
+                }
+            },
+        );
+        assert_eq!(feedback_lengths, vec![0, 1]);
+        assert_eq!(event, tool_request(r#"{"city": "Paris"}"#));
+
+        let event = validated(&config(), ToolValidation::Validate, |_| {
+            tool_request(r#"{"town": "Paris"}"#)
+        });
+        let ChatEvent::Error(error) = event else {
+            panic!("expected an error");
+        };
+        assert_eq!(error.code, ErrorCode::InvalidRequest);
+        assert!(error.message.contains("missing required property city"));
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fmt::Debug, fs, path::Path};
+use std::{collections::HashMap, fmt::Debug, fs, path::Path};
 
 use base64::{engine::general_purpose, Engine};
 use golem_llm::{
@@ -89,6 +89,104 @@ impl OllamaApi {
         EventSource::new(response)
             .map_err(|err| from_event_source_error("Failed to create EventSource stream", err))
     }
+
+    pub fn pull_model(&self, model: &str) -> Result<EventSource, Error> {
+        trace!("Pulling model {model} with Ollama API");
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers.insert("Accept", HeaderValue::from_static("application/x-ndjson"));
+
+        let url = format!("{}/api/pull", self.base_url);
+        let response = self
+            .client
+            .request(Method::POST, url)
+            .headers(headers)
+            .json(&PullRequest {
+                model: model.to_string(),
+                stream: true,
+            })
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+        EventSource::new(response)
+            .map_err(|err| from_event_source_error("Failed to create EventSource stream", err))
+    }
+
+    pub fn delete_model(&self, model: &str) -> Result<(), Error> {
+        trace!("Deleting model {model} with Ollama API");
+
+        let url = format!("{}/api/delete", self.base_url);
+        let response: Response = self
+            .client
+            .request(Method::DELETE, url)
+            .json(&ModelRequest {
+                model: model.to_string(),
+            })
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        handle_empty_response(response)
+    }
+
+    pub fn show_model(&self, model: &str) -> Result<ShowModelResponse, Error> {
+        trace!("Showing model {model} with Ollama API");
+
+        let response = self.send_show_request(model)?;
+        handle_response::<ShowModelResponse>(response)
+    }
+
+    /// Checks whether the model is available locally, without pulling it
+    pub fn has_model(&self, model: &str) -> Result<bool, Error> {
+        let response = self.send_show_request(model)?;
+        if response.status() == StatusCode::NOT_FOUND {
+            Ok(false)
+        } else {
+            handle_response::<ShowModelResponse>(response).map(|_| true)
+        }
+    }
+
+    pub fn list_running_models(&self) -> Result<RunningModelsResponse, Error> {
+        trace!("Listing running models with Ollama API");
+
+        let url = format!("{}/api/ps", self.base_url);
+        let response: Response = self
+            .client
+            .request(Method::GET, url)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        handle_response::<RunningModelsResponse>(response)
+    }
+
+    /// Loads or unloads a model by sending a generate request without a prompt
+    pub fn set_keep_alive(&self, model: &str, keep_alive: &str) -> Result<GenerateResponse, Error> {
+        trace!("Setting keep-alive of model {model} to {keep_alive} with Ollama API");
+
+        let url = format!("{}/api/generate", self.base_url);
+        let response: Response = self
+            .client
+            .request(Method::POST, url)
+            .json(&KeepAliveRequest {
+                model: model.to_string(),
+                keep_alive: keep_alive.to_string(),
+                stream: false,
+            })
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        handle_response::<GenerateResponse>(response)
+    }
+
+    fn send_show_request(&self, model: &str) -> Result<Response, Error> {
+        let url = format!("{}/api/show", self.base_url);
+        self.client
+            .request(Method::POST, url)
+            .json(&ModelRequest {
+                model: model.to_string(),
+            })
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))
+    }
 }
 
 /// GenerateOptions is Options for generating completions
@@ -267,9 +365,104 @@ pub struct Function {
     pub arguments: serde_json::Value,
 }
 
+/// PullRequest is parameters for a request to the pull endpoint
+///
+/// Refer to https://github.com/ollama/ollama/blob/main/docs/api.md#pull-a-model for more details
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PullRequest {
+    pub model: String,
+    pub stream: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PullResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModelRequest {
+    pub model: String,
+}
+
+/// ShowModelResponse is the response of the show endpoint
+///
+/// Refer to https://github.com/ollama/ollama/blob/main/docs/api.md#show-model-information for more details
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ShowModelResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<ModelDetailsResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_info: Option<HashMap<String, serde_json::Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ModelDetailsResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub family: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameter_size: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantization_level: Option<String>,
+}
+
+/// RunningModelsResponse is the response of the ps endpoint
+///
+/// Refer to https://github.com/ollama/ollama/blob/main/docs/api.md#list-running-models for more details
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RunningModelsResponse {
+    pub models: Vec<RunningModelResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RunningModelResponse {
+    pub name: String,
+    pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_vram: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_length: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+}
+
+/// KeepAliveRequest is a generate request without a prompt, which only loads or unloads the model
+///
+/// Refer to https://github.com/ollama/ollama/blob/main/docs/api.md#load-a-model for more details
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KeepAliveRequest {
+    pub model: String,
+    pub keep_alive: String,
+    pub stream: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GenerateResponse {
+    pub model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub done_reason: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct OllamaRequestError {
-    status_code: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status_code: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     status: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -309,13 +502,21 @@ pub fn handle_response<T: DeserializeOwned + Debug>(response: Response) -> Resul
 
             Err(Error {
                 code: error_code_from_status(status),
-                message: error_body.status.unwrap_or_default(),
+                message: error_body.error.or(error_body.status).unwrap_or_default(),
                 provider_error_json: error_body.error_message,
             })
         }
     }
 }
 
+pub fn handle_empty_response(response: Response) -> Result<(), Error> {
+    if response.status() == StatusCode::OK {
+        Ok(())
+    } else {
+        handle_response::<serde_json::Value>(response).map(|_| ())
+    }
+}
+
 pub fn image_to_base64(source: &str) -> Result<String, Box<dyn std::error::Error>> {
     let bytes = if Url::parse(source).is_ok() {
         let client = Client::new();
@@ -2,8 +2,9 @@ use std::collections::HashMap;
 
 use crate::client::{
     image_to_base64, CompletionsRequest, CompletionsResponse, FunctionTool, MessageRequest,
-    MessageRole, OllamaModelOptions, Tool,
+    MessageRole, OllamaModelOptions, PullResponse, RunningModelsResponse, ShowModelResponse, Tool,
 };
+use crate::exports::golem::llm_ollama::models::{ModelDetails, PullProgress, RunningModel};
 use base64::{engine::general_purpose, Engine};
 use golem_llm::golem::llm::llm::{
     ChatEvent, CompleteResponse, Config, ContentPart, Error, ErrorCode, FinishReason,
@@ -95,16 +96,32 @@ pub fn messages_to_request(
         });
     }
 
+    let seed = config
+        .seed
+        .map(|seed| {
+            i32::try_from(seed).map_err(|_| Error {
+                code: ErrorCode::InvalidRequest,
+                message: format!("Ollama only supports seeds up to {}, got {seed}", i32::MAX),
+                provider_error_json: None,
+            })
+        })
+        .transpose()?;
+
     let ollama_options = OllamaModelOptions {
         min_p: parse_option(&options, "min_p"),
         temperature: config.temperature,
-        top_p: parse_option(&options, "top_p"),
-        top_k: parse_option(&options, "top_k"),
+        top_p: config.top_p.or_else(|| parse_option(&options, "top_p")),
+        top_k: config
+            .top_k
+            .map(|top_k| top_k as i32)
+            .or_else(|| parse_option(&options, "top_k")),
         num_predict: parse_option(&options, "num_predict"),
         stop: config.stop_sequences.clone(),
-        repeat_penalty: parse_option(&options, "repeat_penalty"),
+        repeat_penalty: config
+            .repetition_penalty
+            .or_else(|| parse_option(&options, "repeat_penalty")),
         num_ctx: parse_option(&options, "num_ctx"),
-        seed: parse_option(&options, "seed"),
+        seed: seed.or_else(|| parse_option(&options, "seed")),
         mirostat: parse_option(&options, "mirostat"),
         mirostat_eta: parse_option(&options, "mirostat_eta"),
         mirostat_tau: parse_option(&options, "mirostat_tau"),
@@ -114,8 +131,12 @@ pub fn messages_to_request(
         num_keep: parse_option(&options, "num_keep"),
         typical_p: parse_option(&options, "typical_p"),
         repeat_last_n: parse_option(&options, "repeat_last_n"),
-        presence_penalty: parse_option(&options, "presence_penalty"),
-        frequency_penalty: parse_option(&options, "frequency_penalty"),
+        presence_penalty: config
+            .presence_penalty
+            .or_else(|| parse_option(&options, "presence_penalty")),
+        frequency_penalty: config
+            .frequency_penalty
+            .or_else(|| parse_option(&options, "frequency_penalty")),
         numa: parse_option(&options, "numa"),
         num_batch: parse_option(&options, "num_batch"),
         main_gpu: parse_option(&options, "main_gpu"),
@@ -201,6 +222,8 @@ pub fn process_response(response: CompletionsResponse) -> ChatEvent {
             input_tokens,
             output_tokens,
             total_tokens: Some(input_tokens.unwrap_or(0) + output_tokens.unwrap_or(0)),
+            cached_input_tokens: None,
+            reasoning_tokens: None,
         };
 
         let timestamp = response.created_at.clone();
@@ -211,6 +234,7 @@ pub fn process_response(response: CompletionsResponse) -> ChatEvent {
             provider_id: Some("ollama".to_string()),
             timestamp: Some(timestamp.clone()),
             provider_metadata_json: Some(get_provider_metadata(&response)),
+            cost: None,
         };
 
         ChatEvent::Message(CompleteResponse {
@@ -244,3 +268,64 @@ pub fn get_provider_metadata(response: &CompletionsResponse) -> String {
         response.eval_count.unwrap_or(0)
     )
 }
+
+pub fn process_pull_progress(raw: &str) -> Result<Option<PullProgress>, Error> {
+    trace!("Parsing pull progress: {raw}");
+    let response: PullResponse = serde_json::from_str(raw.trim()).map_err(|err| Error {
+        code: ErrorCode::InternalError,
+        message: format!("Failed to parse pull progress: {err}"),
+        provider_error_json: Some(raw.to_string()),
+    })?;
+
+    if let Some(error) = response.error {
+        return Err(Error {
+            code: ErrorCode::InvalidRequest,
+            message: error,
+            provider_error_json: Some(raw.to_string()),
+        });
+    }
+
+    Ok(response.status.map(|status| PullProgress {
+        status,
+        digest: response.digest,
+        total_bytes: response.total,
+        completed_bytes: response.completed,
+    }))
+}
+
+pub fn process_show_response(model: String, response: ShowModelResponse) -> ModelDetails {
+    // The context length is reported in the model info with the architecture as prefix,
+    // for example `llama.context_length`
+    let context_length = response.model_info.as_ref().and_then(|info| {
+        info.iter()
+            .find(|(key, _)| key.ends_with(".context_length"))
+            .and_then(|(_, value)| value.as_u64())
+            .map(|length| length as u32)
+    });
+    let details = response.details.unwrap_or_default();
+
+    ModelDetails {
+        name: model,
+        family: details.family,
+        parameter_size: details.parameter_size,
+        quantization_level: details.quantization_level,
+        parameters: response.parameters,
+        template: response.template,
+        context_length,
+        modified_at: response.modified_at,
+    }
+}
+
+pub fn process_running_models(response: RunningModelsResponse) -> Vec<RunningModel> {
+    response
+        .models
+        .into_iter()
+        .map(|model| RunningModel {
+            name: model.name,
+            size_bytes: model.size,
+            vram_bytes: model.size_vram,
+            context_length: model.context_length,
+            expires_at: model.expires_at,
+        })
+        .collect()
+}
@@ -0,0 +1,348 @@
+/// When the durability feature flag is off, the model management functions of
+/// `DurableLLM<OllamaComponent>` are just a passthrough
+#[cfg(not(feature = "durability"))]
+mod passthrough_impl {
+    use crate::exports::golem::llm_ollama::models::{
+        Guest as ModelsGuest, ModelDetails, PullStream, RunningModel,
+    };
+    use crate::OllamaComponent;
+    use golem_llm::durability::DurableLLM;
+    use golem_llm::golem::llm::llm::Error;
+    use golem_llm::init_logging;
+
+    impl ModelsGuest for DurableLLM<OllamaComponent> {
+        type PullStream = <OllamaComponent as ModelsGuest>::PullStream;
+
+        fn pull(model: String) -> PullStream {
+            init_logging();
+            OllamaComponent::pull(model)
+        }
+
+        fn delete(model: String) -> Result<(), Error> {
+            init_logging();
+            OllamaComponent::delete(model)
+        }
+
+        fn show(model: String) -> Result<ModelDetails, Error> {
+            init_logging();
+            OllamaComponent::show(model)
+        }
+
+        fn list_running() -> Result<Vec<RunningModel>, Error> {
+            init_logging();
+            OllamaComponent::list_running()
+        }
+
+        fn keep_alive(model: String, duration: String) -> Result<(), Error> {
+            init_logging();
+            OllamaComponent::keep_alive(model, duration)
+        }
+
+        fn unload(model: String) -> Result<(), Error> {
+            init_logging();
+            OllamaComponent::unload(model)
+        }
+    }
+}
+
+/// When the durability feature flag is on, the model management functions of
+/// `DurableLLM<OllamaComponent>` save their inputs and outputs in the oplog, the same way as the
+/// functions of the `golem:llm` interfaces.
+#[cfg(feature = "durability")]
+mod durable_impl {
+    use crate::exports::golem::llm_ollama::models::{
+        Guest as ModelsGuest, GuestPullStream, ModelDetails, PullProgress, PullStream, RunningModel,
+    };
+    use crate::pull_stream::OllamaPullStream;
+    use crate::OllamaComponent;
+    use golem_llm::durability::DurableLLM;
+    use golem_llm::golem::llm::llm::Error;
+    use golem_llm::init_logging;
+    use golem_rust::bindings::golem::durability::durability::DurableFunctionType;
+    use golem_rust::durability::Durability;
+    use golem_rust::{with_persistence_level, FromValueAndType, IntoValue, PersistenceLevel};
+    use std::cell::RefCell;
+    use std::fmt::{Display, Formatter};
+
+    impl ModelsGuest for DurableLLM<OllamaComponent> {
+        type PullStream = DurablePullStream;
+
+        fn pull(model: String) -> PullStream {
+            init_logging();
+
+            let durability = Durability::<NoOutput, UnusedError>::new(
+                "golem_llm",
+                "pull_model",
+                DurableFunctionType::WriteRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    PullStream::new(DurablePullStream::live(OllamaComponent::unwrapped_pull(
+                        model.clone(),
+                    )))
+                });
+                let _ = durability.persist_infallible(ModelInput { model }, NoOutput);
+                result
+            } else {
+                let _: NoOutput = durability.replay_infallible();
+                PullStream::new(DurablePullStream::replay(model))
+            }
+        }
+
+        fn delete(model: String) -> Result<(), Error> {
+            init_logging();
+
+            let durability = Durability::<NoOutput, Error>::new(
+                "golem_llm",
+                "delete_model",
+                DurableFunctionType::WriteRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    OllamaComponent::delete(model.clone()).map(|()| NoOutput)
+                });
+                durability
+                    .persist(ModelInput { model }, result)
+                    .map(|_: NoOutput| ())
+            } else {
+                durability.replay().map(|_: NoOutput| ())
+            }
+        }
+
+        fn show(model: String) -> Result<ModelDetails, Error> {
+            init_logging();
+
+            let durability = Durability::<ModelDetails, Error>::new(
+                "golem_llm",
+                "show_model",
+                DurableFunctionType::ReadRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    OllamaComponent::show(model.clone())
+                });
+                durability.persist(ModelInput { model }, result)
+            } else {
+                durability.replay()
+            }
+        }
+
+        fn list_running() -> Result<Vec<RunningModel>, Error> {
+            init_logging();
+
+            let durability = Durability::<Vec<RunningModel>, Error>::new(
+                "golem_llm",
+                "list_running_models",
+                DurableFunctionType::ReadRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    OllamaComponent::list_running()
+                });
+                durability.persist(NoInput, result)
+            } else {
+                durability.replay()
+            }
+        }
+
+        fn keep_alive(model: String, duration: String) -> Result<(), Error> {
+            init_logging();
+
+            let durability = Durability::<NoOutput, Error>::new(
+                "golem_llm",
+                "keep_alive_model",
+                DurableFunctionType::WriteRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    OllamaComponent::keep_alive(model.clone(), duration.clone()).map(|()| NoOutput)
+                });
+                durability
+                    .persist(KeepAliveInput { model, duration }, result)
+                    .map(|_: NoOutput| ())
+            } else {
+                durability.replay().map(|_: NoOutput| ())
+            }
+        }
+
+        fn unload(model: String) -> Result<(), Error> {
+            init_logging();
+
+            let durability = Durability::<NoOutput, Error>::new(
+                "golem_llm",
+                "unload_model",
+                DurableFunctionType::WriteRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    OllamaComponent::unload(model.clone()).map(|()| NoOutput)
+                });
+                durability
+                    .persist(ModelInput { model }, result)
+                    .map(|_: NoOutput| ())
+            } else {
+                durability.replay().map(|_: NoOutput| ())
+            }
+        }
+    }
+
+    /// Represents the durable pull stream's state
+    ///
+    /// In live mode it directly calls the underlying pull stream. In replay mode it returns the
+    /// persisted progress updates, and when reaching the end of the replay mode it starts pulling
+    /// the model again. Pulls are resumed by Ollama, so the already downloaded layers are only
+    /// reported as completed.
+    enum DurablePullStreamState {
+        Live { stream: OllamaPullStream },
+        Replay { model: String },
+    }
+
+    pub struct DurablePullStream {
+        state: RefCell<Option<DurablePullStreamState>>,
+    }
+
+    impl DurablePullStream {
+        fn live(stream: OllamaPullStream) -> Self {
+            Self {
+                state: RefCell::new(Some(DurablePullStreamState::Live { stream })),
+            }
+        }
+
+        fn replay(model: String) -> Self {
+            Self {
+                state: RefCell::new(Some(DurablePullStreamState::Replay { model })),
+            }
+        }
+    }
+
+    impl Drop for DurablePullStream {
+        fn drop(&mut self) {
+            if let Some(DurablePullStreamState::Live { stream }) = self.state.take() {
+                with_persistence_level(PersistenceLevel::PersistNothing, move || {
+                    drop(stream);
+                });
+            }
+        }
+    }
+
+    impl GuestPullStream for DurablePullStream {
+        fn get_next(&self) -> Result<Option<Vec<PullProgress>>, Error> {
+            let durability = Durability::<Option<Vec<PullProgress>>, Error>::new(
+                "golem_llm",
+                "pull_model_get_next",
+                DurableFunctionType::ReadRemote,
+            );
+            let mut state = self.state.borrow_mut();
+            if durability.is_live() {
+                let (result, new_live_stream) = match &*state {
+                    Some(DurablePullStreamState::Live { stream }) => {
+                        let result =
+                            with_persistence_level(PersistenceLevel::PersistNothing, || {
+                                stream.get_next()
+                            });
+                        (result, None)
+                    }
+                    Some(DurablePullStreamState::Replay { model }) => {
+                        let (stream, result) =
+                            with_persistence_level(PersistenceLevel::PersistNothing, || {
+                                let stream = OllamaComponent::unwrapped_pull(model.clone());
+                                let result = stream.get_next();
+                                (stream, result)
+                            });
+                        (result, Some(stream))
+                    }
+                    None => {
+                        unreachable!()
+                    }
+                };
+
+                if let Some(stream) = new_live_stream {
+                    *state = Some(DurablePullStreamState::Live { stream });
+                }
+
+                durability.persist(NoInput, result)
+            } else {
+                match &*state {
+                    Some(DurablePullStreamState::Live { .. }) => {
+                        unreachable!("Durable pull stream cannot be in live mode during replay")
+                    }
+                    Some(DurablePullStreamState::Replay { .. }) => durability.replay(),
+                    None => {
+                        unreachable!()
+                    }
+                }
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, IntoValue)]
+    struct ModelInput {
+        model: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, IntoValue)]
+    struct KeepAliveInput {
+        model: String,
+        duration: String,
+    }
+
+    #[derive(Debug, IntoValue)]
+    struct NoInput;
+
+    #[derive(Debug, Clone, FromValueAndType, IntoValue)]
+    struct NoOutput;
+
+    #[derive(Debug, FromValueAndType, IntoValue)]
+    struct UnusedError;
+
+    impl Display for UnusedError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "UnusedError")
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::exports::golem::llm_ollama::models::{ModelDetails, PullProgress, RunningModel};
+        use golem_rust::value_and_type::{FromValueAndType, IntoValueAndType};
+        use std::fmt::Debug;
+
+        fn roundtrip_test<T: Debug + Clone + PartialEq + IntoValueAndType + FromValueAndType>(
+            value: T,
+        ) {
+            let vnt = value.clone().into_value_and_type();
+            let extracted = T::from_value_and_type(vnt).unwrap();
+            assert_eq!(value, extracted);
+        }
+
+        #[test]
+        fn models_roundtrip() {
+            roundtrip_test(PullProgress {
+                status: "pulling 6a0746a1ec1a".to_string(),
+                digest: Some(
+                    "sha256:6a0746a1ec1aef3e7ec53868f220ff6e389f6f8ef87a01d77c96807de94ca2aa"
+                        .to_string(),
+                ),
+                total_bytes: Some(4661211424),
+                completed_bytes: Some(1034256384),
+            });
+            roundtrip_test(ModelDetails {
+                name: "llama3.2".to_string(),
+                family: Some("llama".to_string()),
+                parameter_size: Some("3.2B".to_string()),
+                quantization_level: Some("Q4_K_M".to_string()),
+                parameters: Some("stop \"<|eot_id|>\"".to_string()),
+                template: None,
+                context_length: Some(131072),
+                modified_at: Some("2025-05-12T09:41:53.283736+02:00".to_string()),
+            });
+            roundtrip_test(RunningModel {
+                name: "llama3.2:latest".to_string(),
+                size_bytes: 3850000000,
+                vram_bytes: Some(3850000000),
+                context_length: None,
+                expires_at: Some("2025-05-12T10:01:53.283736+02:00".to_string()),
+            });
+        }
+    }
+}
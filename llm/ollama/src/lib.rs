@@ -1,7 +1,11 @@
 use std::cell::{Ref, RefCell, RefMut};
+use std::collections::HashSet;
 
 use client::{CompletionsRequest, OllamaApi};
-use conversions::{messages_to_request, process_response};
+use conversions::{
+    messages_to_request, process_pull_progress, process_response, process_running_models,
+    process_show_response,
+};
 use golem_llm::{
     chat_stream::{LlmChatStream, LlmChatStreamState},
     durability::{DurableLLM, ExtendedGuest},
@@ -10,12 +14,39 @@ use golem_llm::{
         ChatEvent, ChatStream, Config, ContentPart, Error, FinishReason, Guest, Message,
         ResponseMetadata, Role, StreamDelta, StreamEvent, ToolCall, ToolResult, Usage,
     },
+    golem::llm::moderation::{Guest as ModerationGuest, ModerationConfig, ModerationResponse},
+    guardrails::moderate_with_local_policy,
 };
 use golem_rust::wasm_rpc::Pollable;
 use log::trace;
+use pull_stream::OllamaPullStream;
+
+use crate::exports::golem::llm_ollama::models::{
+    Guest as ModelsGuest, ModelDetails, PullStream, RunningModel,
+};
 
 mod client;
 mod conversions;
+mod durability;
+mod pull_stream;
+
+wit_bindgen::generate!({
+    path: "wit",
+    world: "llm-library",
+    with: {
+        "golem:llm/types@1.0.0": golem_llm::golem::llm::types,
+        "golem:llm/llm@1.0.0": golem_llm::golem::llm::llm,
+        "golem:llm/moderation@1.0.0": golem_llm::golem::llm::moderation,
+        "golem:llm/costs@1.0.0": golem_llm::golem::llm::costs,
+        "golem:llm/prompts@1.0.0": golem_llm::golem::llm::prompts,
+    },
+    additional_derives: [PartialEq, golem_rust::FromValueAndType, golem_rust::IntoValue],
+});
+
+thread_local! {
+    /// Models which are known to be available, so they are not checked again before each request
+    static AVAILABLE_MODELS: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+}
 
 struct OllamaChatStream {
     stream: RefCell<Option<EventSource>>,
@@ -80,6 +111,8 @@ impl LlmChatStreamState for OllamaChatStream {
                 input_tokens: Some(input_tokens),
                 output_tokens: Some(input_tokens),
                 total_tokens: Some(input_tokens + output_tokens),
+                cached_input_tokens: None,
+                reasoning_tokens: None,
             };
 
             let total_duration = json
@@ -118,6 +151,7 @@ impl LlmChatStreamState for OllamaChatStream {
                 provider_id: Some("ollama".to_string()),
                 timestamp,
                 provider_metadata_json: Some(provider_metadata),
+                cost: None,
             })));
         }
 
@@ -179,7 +213,12 @@ impl LlmChatStreamState for OllamaChatStream {
 struct OllamaComponent;
 
 impl OllamaComponent {
+    const AUTO_PULL_ENV_VAR_NAME: &'static str = "GOLEM_OLLAMA_AUTO_PULL";
+
     fn request(client: &OllamaApi, request: CompletionsRequest) -> ChatEvent {
+        if let Err(err) = Self::ensure_model(client, &request) {
+            return ChatEvent::Error(err);
+        }
         match client.send_chat(request) {
             Ok(response) => process_response(response),
             Err(err) => ChatEvent::Error(err),
@@ -190,12 +229,50 @@ impl OllamaComponent {
         client: &OllamaApi,
         mut request: CompletionsRequest,
     ) -> LlmChatStream<OllamaChatStream> {
+        if let Err(err) = Self::ensure_model(client, &request) {
+            return OllamaChatStream::failed(err);
+        }
         request.stream = Some(true);
         match client.send_chat_stream(request) {
             Ok(stream) => OllamaChatStream::new(stream),
             Err(err) => OllamaChatStream::failed(err),
         }
     }
+
+    /// When `GOLEM_OLLAMA_AUTO_PULL` is enabled, pulls the requested model if it is not available
+    /// on the Ollama server yet. Each model is only checked on its first use.
+    fn ensure_model(client: &OllamaApi, request: &CompletionsRequest) -> Result<(), Error> {
+        let auto_pull = std::env::var(Self::AUTO_PULL_ENV_VAR_NAME)
+            .map(|value| value == "true" || value == "1")
+            .unwrap_or(false);
+        let Some(model) = request.model.as_ref() else {
+            return Ok(());
+        };
+        if !auto_pull {
+            return Ok(());
+        }
+        if AVAILABLE_MODELS.with_borrow(|models| models.contains(model)) {
+            return Ok(());
+        }
+
+        if !client.has_model(model)? {
+            trace!("Model {model} is not available, pulling it");
+            let stream = OllamaPullStream::new(client.pull_model(model)?, process_pull_progress);
+            stream.wait_until_finished()?;
+        }
+
+        AVAILABLE_MODELS.with_borrow_mut(|models| models.insert(model.clone()));
+        Ok(())
+    }
+
+    /// Creates the `PullStream` of the model without wrapping it in a `Resource`
+    fn unwrapped_pull(model: String) -> OllamaPullStream {
+        let client = OllamaApi::new(model.clone());
+        match client.pull_model(&model) {
+            Ok(stream) => OllamaPullStream::new(stream, process_pull_progress),
+            Err(err) => OllamaPullStream::failed(err),
+        }
+    }
 }
 
 impl Guest for OllamaComponent {
@@ -227,7 +304,55 @@ impl Guest for OllamaComponent {
     }
 }
 
+impl ModerationGuest for OllamaComponent {
+    fn moderate(
+        inputs: Vec<String>,
+        _config: ModerationConfig,
+    ) -> Result<ModerationResponse, Error> {
+        moderate_with_local_policy(inputs)
+    }
+}
+
+impl ModelsGuest for OllamaComponent {
+    type PullStream = OllamaPullStream;
+
+    fn pull(model: String) -> PullStream {
+        PullStream::new(Self::unwrapped_pull(model))
+    }
+
+    fn delete(model: String) -> Result<(), Error> {
+        let client = OllamaApi::new(model.clone());
+        client.delete_model(&model)?;
+        AVAILABLE_MODELS.with_borrow_mut(|models| models.remove(&model));
+        Ok(())
+    }
+
+    fn show(model: String) -> Result<ModelDetails, Error> {
+        let client = OllamaApi::new(model.clone());
+        let response = client.show_model(&model)?;
+        Ok(process_show_response(model, response))
+    }
+
+    fn list_running() -> Result<Vec<RunningModel>, Error> {
+        let client = OllamaApi::new(String::new());
+        let response = client.list_running_models()?;
+        Ok(process_running_models(response))
+    }
+
+    fn keep_alive(model: String, duration: String) -> Result<(), Error> {
+        let client = OllamaApi::new(model.clone());
+        client.set_keep_alive(&model, &duration).map(|_| ())
+    }
+
+    fn unload(model: String) -> Result<(), Error> {
+        let client = OllamaApi::new(model.clone());
+        client.set_keep_alive(&model, "0").map(|_| ())
+    }
+}
+
 impl ExtendedGuest for OllamaComponent {
+    const PROVIDER: &'static str = "ollama";
+
     fn unwrapped_stream(messages: Vec<Message>, config: Config) -> LlmChatStream<OllamaChatStream> {
         let client = OllamaApi::new(config.model.clone());
         match messages_to_request(messages, config.clone(), None) {
@@ -297,3 +422,5 @@ impl ExtendedGuest for OllamaComponent {
 type DurableOllamaComponent = DurableLLM<OllamaComponent>;
 
 golem_llm::export_llm!(DurableOllamaComponent with_types_in golem_llm);
+golem_llm::export_moderation!(DurableOllamaComponent with_types_in golem_llm::moderation_library);
+export!(DurableOllamaComponent);
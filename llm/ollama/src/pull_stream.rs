@@ -0,0 +1,106 @@
+use crate::exports::golem::llm_ollama::models::{GuestPullStream, PullProgress};
+use golem_llm::event_source::{self, Event, EventSource, MessageEvent};
+use golem_llm::golem::llm::llm::{Error, ErrorCode};
+use std::cell::RefCell;
+use std::task::Poll;
+
+type DecodeProgress = fn(&str) -> Result<Option<PullProgress>, Error>;
+
+enum OllamaPullStreamState {
+    Streaming(EventSource),
+    Finished,
+    Failed(Error),
+}
+
+/// Pull stream reporting the progress of downloading a model from the NDJSON messages of Ollama's
+/// pull endpoint. Each `get_next` call blocks until at least one progress update is
+/// received, and returns every other update which is already available.
+pub struct OllamaPullStream {
+    state: RefCell<OllamaPullStreamState>,
+    decode: DecodeProgress,
+}
+
+impl OllamaPullStream {
+    pub fn new(stream: EventSource, decode: DecodeProgress) -> Self {
+        Self {
+            state: RefCell::new(OllamaPullStreamState::Streaming(stream)),
+            decode,
+        }
+    }
+
+    pub fn failed(error: Error) -> Self {
+        Self {
+            state: RefCell::new(OllamaPullStreamState::Failed(error)),
+            decode: |_| Ok(None),
+        }
+    }
+
+    /// Reads the whole stream, and returns the last progress update
+    pub fn wait_until_finished(&self) -> Result<Option<PullProgress>, Error> {
+        let mut last = None;
+        while let Some(updates) = self.get_next()? {
+            if let Some(update) = updates.into_iter().last() {
+                last = Some(update);
+            }
+        }
+        Ok(last)
+    }
+}
+
+impl GuestPullStream for OllamaPullStream {
+    fn get_next(&self) -> Result<Option<Vec<PullProgress>>, Error> {
+        let mut state = self.state.borrow_mut();
+        let stream = match &mut *state {
+            OllamaPullStreamState::Streaming(stream) => stream,
+            OllamaPullStreamState::Finished => return Ok(None),
+            OllamaPullStreamState::Failed(error) => return Err(error.clone()),
+        };
+
+        let pollable = stream.subscribe();
+        let mut updates = Vec::new();
+        let result = loop {
+            match stream.poll_next() {
+                Poll::Ready(None)
+                | Poll::Ready(Some(Err(event_source::error::Error::StreamEnded))) => {
+                    break Ok(false);
+                }
+                Poll::Ready(Some(Err(error))) => {
+                    break Err(Error {
+                        code: ErrorCode::InternalError,
+                        message: error.to_string(),
+                        provider_error_json: None,
+                    });
+                }
+                Poll::Ready(Some(Ok(Event::Open))) => {}
+                Poll::Ready(Some(Ok(Event::Message(MessageEvent { data, .. })))) => {
+                    match (self.decode)(&data) {
+                        Ok(Some(update)) => updates.push(update),
+                        Ok(None) => {
+                            // Ignored message
+                        }
+                        Err(error) => break Err(error),
+                    }
+                }
+                Poll::Pending if updates.is_empty() => pollable.block(),
+                Poll::Pending => break Ok(true),
+            }
+        };
+        drop(pollable);
+
+        match result {
+            Ok(true) => Ok(Some(updates)),
+            Ok(false) => {
+                *state = OllamaPullStreamState::Finished;
+                if updates.is_empty() {
+                    Ok(None)
+                } else {
+                    Ok(Some(updates))
+                }
+            }
+            Err(error) => {
+                *state = OllamaPullStreamState::Failed(error.clone());
+                Err(error)
+            }
+        }
+    }
+}
@@ -1,11 +1,13 @@
 use golem_llm::error::{error_code_from_status, from_event_source_error, from_reqwest_error};
 use golem_llm::event_source::EventSource;
 use golem_llm::golem::llm::llm::Error;
+use golem_llm::multipart::MultipartBody;
 use log::trace;
 use reqwest::header::HeaderValue;
 use reqwest::{Client, Method, Response};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 
 const BASE_URL: &str = "https://api.openai.com";
@@ -270,6 +272,439 @@ pub struct ResponseOutputItemDone {
     pub output_index: u32,
 }
 
+/// The OpenAI API client for generating and editing images.
+///
+/// Based on https://platform.openai.com/docs/api-reference/images
+pub struct ImagesApi {
+    openai_api_key: String,
+    client: Client,
+}
+
+impl ImagesApi {
+    pub fn new(openai_api_key: String) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+        Self {
+            openai_api_key,
+            client,
+        }
+    }
+
+    pub fn create_image(&self, request: CreateImageRequest) -> Result<ImagesResponse, Error> {
+        trace!("Sending image generation request to OpenAI API: {request:?}");
+
+        let response: Response = self
+            .client
+            .request(Method::POST, format!("{BASE_URL}/v1/images/generations"))
+            .bearer_auth(&self.openai_api_key)
+            .json(&request)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+
+    pub fn create_image_edit(&self, body: MultipartBody) -> Result<ImagesResponse, Error> {
+        trace!("Sending image edit request to OpenAI API");
+
+        self.send_multipart("/v1/images/edits", body)
+    }
+
+    pub fn create_image_variation(&self, body: MultipartBody) -> Result<ImagesResponse, Error> {
+        trace!("Sending image variation request to OpenAI API");
+
+        self.send_multipart("/v1/images/variations", body)
+    }
+
+    fn send_multipart(&self, path: &str, body: MultipartBody) -> Result<ImagesResponse, Error> {
+        let response: Response = self
+            .client
+            .request(Method::POST, format!("{BASE_URL}{path}"))
+            .bearer_auth(&self.openai_api_key)
+            .header(reqwest::header::CONTENT_TYPE, body.content_type())
+            .body(body.finish())
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateImageRequest {
+    pub prompt: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImagesResponse {
+    pub created: u64,
+    pub data: Vec<ImageData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageData {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub b64_json: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revised_prompt: Option<String>,
+}
+
+/// The OpenAI API client for transcription and speech synthesis.
+///
+/// Based on https://platform.openai.com/docs/api-reference/audio
+pub struct AudioApi {
+    openai_api_key: String,
+    client: Client,
+}
+
+impl AudioApi {
+    pub fn new(openai_api_key: String) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+        Self {
+            openai_api_key,
+            client,
+        }
+    }
+
+    pub fn create_transcription(
+        &self,
+        body: MultipartBody,
+    ) -> Result<TranscriptionResponse, Error> {
+        trace!("Sending transcription request to OpenAI API");
+
+        let response: Response = self
+            .client
+            .request(Method::POST, format!("{BASE_URL}/v1/audio/transcriptions"))
+            .bearer_auth(&self.openai_api_key)
+            .header(reqwest::header::CONTENT_TYPE, body.content_type())
+            .body(body.finish())
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+
+    pub fn create_speech(&self, request: CreateSpeechRequest) -> Result<Vec<u8>, Error> {
+        trace!("Sending speech request to OpenAI API: {request:?}");
+
+        let response: Response = self
+            .client
+            .request(Method::POST, format!("{BASE_URL}/v1/audio/speech"))
+            .bearer_auth(&self.openai_api_key)
+            .json(&request)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        let status = response.status();
+        if status.is_success() {
+            let bytes = response
+                .bytes()
+                .map_err(|err| from_reqwest_error("Failed to read audio data", err))?;
+            Ok(bytes.to_vec())
+        } else {
+            parse_response(response)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionResponse {
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f32>,
+    #[serde(default)]
+    pub segments: Vec<TranscriptionSegment>,
+    #[serde(default)]
+    pub words: Vec<TranscriptionWord>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionSegment {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<serde_json::Value>,
+    pub start: f32,
+    pub end: f32,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionWord {
+    pub word: String,
+    pub start: f32,
+    pub end: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateSpeechRequest {
+    pub model: String,
+    pub input: String,
+    pub voice: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f32>,
+}
+
+/// The OpenAI API client for classifying potentially harmful content.
+///
+/// Based on https://platform.openai.com/docs/api-reference/moderations
+pub struct ModerationsApi {
+    openai_api_key: String,
+    client: Client,
+}
+
+impl ModerationsApi {
+    pub fn new(openai_api_key: String) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+        Self {
+            openai_api_key,
+            client,
+        }
+    }
+
+    pub fn create_moderation(
+        &self,
+        request: CreateModerationRequest,
+    ) -> Result<ModerationsResponse, Error> {
+        trace!("Sending moderation request to OpenAI API: {request:?}");
+
+        let response: Response = self
+            .client
+            .request(Method::POST, format!("{BASE_URL}/v1/moderations"))
+            .bearer_auth(&self.openai_api_key)
+            .json(&request)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateModerationRequest {
+    pub input: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationsResponse {
+    pub id: String,
+    pub model: String,
+    pub results: Vec<ModerationResultObject>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationResultObject {
+    pub flagged: bool,
+    pub categories: BTreeMap<String, bool>,
+    pub category_scores: BTreeMap<String, f32>,
+}
+
+/// The OpenAI API client for running asynchronous batch jobs.
+///
+/// Based on https://platform.openai.com/docs/api-reference/batch
+pub struct BatchesApi {
+    openai_api_key: String,
+    client: Client,
+}
+
+impl BatchesApi {
+    pub fn new(openai_api_key: String) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+        Self {
+            openai_api_key,
+            client,
+        }
+    }
+
+    pub fn upload_batch_file(&self, jsonl: String) -> Result<FileObject, Error> {
+        trace!("Uploading batch input file to OpenAI API");
+
+        let mut body = MultipartBody::new();
+        body.text("purpose", "batch");
+        body.file("file", "batch.jsonl", "application/jsonl", jsonl.as_bytes());
+
+        let response: Response = self
+            .client
+            .request(Method::POST, format!("{BASE_URL}/v1/files"))
+            .bearer_auth(&self.openai_api_key)
+            .header(reqwest::header::CONTENT_TYPE, body.content_type())
+            .body(body.finish())
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+
+    pub fn create_batch(&self, request: CreateBatchRequest) -> Result<BatchObject, Error> {
+        trace!("Sending batch request to OpenAI API: {request:?}");
+
+        let response: Response = self
+            .client
+            .request(Method::POST, format!("{BASE_URL}/v1/batches"))
+            .bearer_auth(&self.openai_api_key)
+            .json(&request)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+
+    pub fn retrieve_batch(&self, batch_id: &str) -> Result<BatchObject, Error> {
+        trace!("Retrieving batch {batch_id} from OpenAI API");
+
+        let response: Response = self
+            .client
+            .request(Method::GET, format!("{BASE_URL}/v1/batches/{batch_id}"))
+            .bearer_auth(&self.openai_api_key)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+
+    pub fn cancel_batch(&self, batch_id: &str) -> Result<BatchObject, Error> {
+        trace!("Cancelling batch {batch_id} with OpenAI API");
+
+        let response: Response = self
+            .client
+            .request(
+                Method::POST,
+                format!("{BASE_URL}/v1/batches/{batch_id}/cancel"),
+            )
+            .bearer_auth(&self.openai_api_key)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+
+    pub fn file_content(&self, file_id: &str) -> Result<String, Error> {
+        trace!("Downloading file {file_id} from OpenAI API");
+
+        let response: Response = self
+            .client
+            .request(
+                Method::GET,
+                format!("{BASE_URL}/v1/files/{file_id}/content"),
+            )
+            .bearer_auth(&self.openai_api_key)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        if response.status().is_success() {
+            response
+                .text()
+                .map_err(|err| from_reqwest_error("Failed to receive file content", err))
+        } else {
+            parse_response(response)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileObject {
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchRequestLine {
+    pub custom_id: String,
+    pub method: String,
+    pub url: String,
+    pub body: CreateModelResponseRequest,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateBatchRequest {
+    pub input_file_id: String,
+    pub endpoint: String,
+    pub completion_window: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchObject {
+    pub id: String,
+    pub status: String,
+    pub created_at: u64,
+    pub request_counts: Option<BatchRequestCounts>,
+    pub output_file_id: Option<String>,
+    pub error_file_id: Option<String>,
+    pub errors: Option<BatchErrors>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchRequestCounts {
+    pub total: u32,
+    pub completed: u32,
+    pub failed: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchErrors {
+    #[serde(default)]
+    pub data: Vec<BatchError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchError {
+    pub code: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchResultLine {
+    pub custom_id: String,
+    pub response: Option<BatchResultResponse>,
+    pub error: Option<BatchError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchResultResponse {
+    pub status_code: u16,
+    pub body: serde_json::Value,
+}
+
 fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, Error> {
     let status = response.status();
     if status.is_success() {
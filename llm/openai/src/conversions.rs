@@ -1,12 +1,33 @@
 use crate::client::{
-    CreateModelResponseRequest, CreateModelResponseResponse, Detail, InnerInput, InnerInputItem,
-    Input, InputItem, OutputItem, OutputMessageContent, Tool,
+    BatchObject, BatchRequestLine, BatchResultLine, CreateImageRequest, CreateModelResponseRequest,
+    CreateModelResponseResponse, CreateModerationRequest, CreateSpeechRequest, Detail,
+    ImagesResponse, InnerInput, InnerInputItem, Input, InputItem, ModerationsResponse, OutputItem,
+    OutputMessageContent, Tool, TranscriptionResponse,
 };
 use base64::{engine::general_purpose, Engine as _};
-use golem_llm::error::error_code_from_status;
+use golem_llm::config::{reject_unsupported_parameters, SamplingParameter};
+use golem_llm::error::{error_code_from_status, unsupported};
+use golem_llm::golem::llm::batch::{
+    BatchInfo, BatchRequest, BatchRequestCounts, BatchResult, BatchStatus,
+};
+use golem_llm::golem::llm::images::{
+    GeneratedImage, ImageConfig, ImageQuality, ImageResponse, ImageResponseFormat,
+};
 use golem_llm::golem::llm::llm::{
     ChatEvent, CompleteResponse, Config, ContentPart, Error, ErrorCode, ImageDetail,
-    ImageReference, Message, ResponseMetadata, Role, ToolCall, ToolDefinition, ToolResult, Usage,
+    ImageReference, ImageSource, ImageUrl, Message, ResponseMetadata, Role, ToolCall,
+    ToolDefinition, ToolResult, Usage,
+};
+use golem_llm::golem::llm::moderation::{
+    CategoryScore, ModerationConfig, ModerationResponse, ModerationResult,
+};
+use golem_llm::golem::llm::speech::{AudioFormat, SpeechConfig, SpeechResult, VoiceInfo};
+use golem_llm::golem::llm::transcription::{
+    AudioSource, TimestampGranularity, TranscriptionConfig, TranscriptionResult,
+    TranscriptionSegment, TranscriptionWord,
+};
+use golem_llm::multipart::{
+    audio_format_mime_type, audio_format_name, file_extension, MultipartBody,
 };
 use reqwest::StatusCode;
 use std::collections::HashMap;
@@ -16,14 +37,26 @@ pub fn create_request(
     items: Vec<InputItem>,
     config: Config,
     tools: Vec<Tool>,
-) -> CreateModelResponseRequest {
+) -> Result<CreateModelResponseRequest, Error> {
+    reject_unsupported_parameters(
+        "OpenAI",
+        &config,
+        &[
+            SamplingParameter::TopK,
+            SamplingParameter::Seed,
+            SamplingParameter::FrequencyPenalty,
+            SamplingParameter::PresencePenalty,
+            SamplingParameter::RepetitionPenalty,
+        ],
+    )?;
+
     let options = config
         .provider_options
         .into_iter()
         .map(|kv| (kv.key, kv.value))
         .collect::<HashMap<_, _>>();
 
-    CreateModelResponseRequest {
+    Ok(CreateModelResponseRequest {
         input: Input::List(items),
         model: config.model,
         temperature: config.temperature,
@@ -31,13 +64,15 @@ pub fn create_request(
         tools,
         tool_choice: config.tool_choice,
         stream: false,
-        top_p: options
-            .get("top_p")
-            .and_then(|top_p_s| top_p_s.parse::<f32>().ok()),
+        top_p: config.top_p.or_else(|| {
+            options
+                .get("top_p")
+                .and_then(|top_p_s| top_p_s.parse::<f32>().ok())
+        }),
         user: options
             .get("user")
             .and_then(|user_s| user_s.parse::<String>().ok()),
-    }
+    })
 }
 
 pub fn messages_to_input_items(messages: Vec<Message>) -> Vec<InputItem> {
@@ -232,9 +267,489 @@ pub fn create_response_metadata(response: &CreateModelResponseResponse) -> Respo
             input_tokens: Some(usage.input_tokens),
             output_tokens: Some(usage.output_tokens),
             total_tokens: Some(usage.total_tokens),
+            cached_input_tokens: Some(usage.input_tokens_details.cached_tokens),
+            reasoning_tokens: Some(usage.output_tokens_details.reasoning_tokens),
         }),
         provider_id: Some(response.id.clone()),
         timestamp: Some(response.created_at.to_string()),
         provider_metadata_json: response.metadata.as_ref().map(|m| m.to_string()),
+        cost: None,
+    }
+}
+
+pub fn create_image_request(
+    prompt: String,
+    config: ImageConfig,
+) -> Result<CreateImageRequest, Error> {
+    check_unsupported_image_options(&config)?;
+    let options = image_provider_options(&config);
+
+    Ok(CreateImageRequest {
+        prompt,
+        quality: config
+            .quality
+            .map(|quality| to_openai_quality(config.model.as_deref(), quality)),
+        response_format: config
+            .response_format
+            .and_then(|format| to_openai_response_format(config.model.as_deref(), format)),
+        size: config
+            .size
+            .map(|size| format!("{}x{}", size.width, size.height)),
+        style: config.style,
+        n: config.count,
+        model: config.model,
+        background: options.get("background").cloned(),
+        output_format: options.get("output_format").cloned(),
+        user: options.get("user").cloned(),
+    })
+}
+
+pub fn create_image_edit_body(
+    image: ImageSource,
+    mask: Option<ImageSource>,
+    prompt: String,
+    config: ImageConfig,
+) -> Result<MultipartBody, Error> {
+    check_unsupported_image_options(&config)?;
+    if config.style.is_some() {
+        return Err(unsupported("style is not supported for OpenAI image edits"));
+    }
+    let options = image_provider_options(&config);
+
+    let mut body = MultipartBody::new();
+    body.file(
+        "image",
+        &format!("image.{}", file_extension(&image.mime_type)),
+        &image.mime_type,
+        &image.data,
+    );
+    if let Some(mask) = mask {
+        body.file(
+            "mask",
+            &format!("mask.{}", file_extension(&mask.mime_type)),
+            &mask.mime_type,
+            &mask.data,
+        );
+    }
+    body.text("prompt", prompt);
+    add_common_image_fields(&mut body, &config);
+    body.optional_text(
+        "quality",
+        config
+            .quality
+            .map(|quality| to_openai_quality(config.model.as_deref(), quality)),
+    );
+    body.optional_text("background", options.get("background"));
+    body.optional_text("output_format", options.get("output_format"));
+    body.optional_text("user", options.get("user"));
+    Ok(body)
+}
+
+pub fn create_image_variation_body(
+    image: ImageSource,
+    config: ImageConfig,
+) -> Result<MultipartBody, Error> {
+    check_unsupported_image_options(&config)?;
+    if config.style.is_some() || config.quality.is_some() {
+        return Err(unsupported(
+            "style and quality are not supported for OpenAI image variations",
+        ));
+    }
+    let options = image_provider_options(&config);
+
+    let mut body = MultipartBody::new();
+    body.file(
+        "image",
+        &format!("image.{}", file_extension(&image.mime_type)),
+        &image.mime_type,
+        &image.data,
+    );
+    add_common_image_fields(&mut body, &config);
+    body.optional_text("user", options.get("user"));
+    Ok(body)
+}
+
+pub fn process_image_response(response: ImagesResponse) -> Result<ImageResponse, Error> {
+    let mime_type = match response.output_format.as_deref() {
+        Some("jpeg") => "image/jpeg",
+        Some("webp") => "image/webp",
+        _ => "image/png",
+    };
+
+    let mut images = Vec::new();
+    for data in response.data {
+        let image = if let Some(b64_json) = data.b64_json {
+            let bytes = general_purpose::STANDARD
+                .decode(b64_json)
+                .map_err(|err| Error {
+                    code: ErrorCode::InternalError,
+                    message: format!("Failed to decode base64 image data: {err}"),
+                    provider_error_json: None,
+                })?;
+            ImageReference::Inline(ImageSource {
+                data: bytes,
+                mime_type: mime_type.to_string(),
+                detail: None,
+            })
+        } else if let Some(url) = data.url {
+            ImageReference::Url(ImageUrl { url, detail: None })
+        } else {
+            return Err(Error {
+                code: ErrorCode::InternalError,
+                message: "Image response contains neither a URL nor inline data".to_string(),
+                provider_error_json: None,
+            });
+        };
+
+        images.push(GeneratedImage {
+            image,
+            revised_prompt: data.revised_prompt,
+            seed: None,
+        });
+    }
+
+    Ok(ImageResponse {
+        images,
+        provider_id: None,
+        timestamp: Some(response.created.to_string()),
+        provider_metadata_json: response.usage.map(|usage| usage.to_string()),
+    })
+}
+
+fn check_unsupported_image_options(config: &ImageConfig) -> Result<(), Error> {
+    if config.seed.is_some() {
+        Err(unsupported("seed is not supported by OpenAI image models"))
+    } else if config.negative_prompt.is_some() {
+        Err(unsupported(
+            "negative prompts are not supported by OpenAI image models",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn image_provider_options(config: &ImageConfig) -> HashMap<String, String> {
+    config
+        .provider_options
+        .iter()
+        .map(|kv| (kv.key.clone(), kv.value.clone()))
+        .collect()
+}
+
+fn add_common_image_fields(body: &mut MultipartBody, config: &ImageConfig) {
+    body.optional_text("model", config.model.as_ref());
+    body.optional_text("n", config.count);
+    body.optional_text(
+        "size",
+        config
+            .size
+            .as_ref()
+            .map(|size| format!("{}x{}", size.width, size.height)),
+    );
+    body.optional_text(
+        "response_format",
+        config
+            .response_format
+            .and_then(|format| to_openai_response_format(config.model.as_deref(), format)),
+    );
+}
+
+fn is_gpt_image_model(model: Option<&str>) -> bool {
+    model.is_some_and(|model| model.starts_with("gpt-image"))
+}
+
+fn to_openai_quality(model: Option<&str>, quality: ImageQuality) -> String {
+    match (is_gpt_image_model(model), quality) {
+        (true, ImageQuality::Standard) => "medium".to_string(),
+        (true, ImageQuality::High) => "high".to_string(),
+        (false, ImageQuality::Standard) => "standard".to_string(),
+        (false, ImageQuality::High) => "hd".to_string(),
+    }
+}
+
+/// GPT image models always return base64 encoded images and reject the `response_format` parameter
+fn to_openai_response_format(model: Option<&str>, format: ImageResponseFormat) -> Option<String> {
+    if is_gpt_image_model(model) {
+        None
+    } else {
+        match format {
+            ImageResponseFormat::Url => Some("url".to_string()),
+            ImageResponseFormat::Inline => Some("b64_json".to_string()),
+        }
+    }
+}
+
+const DEFAULT_TRANSCRIPTION_MODEL: &str = "whisper-1";
+const DIARIZATION_MODEL: &str = "gpt-4o-transcribe-diarize";
+const DEFAULT_SPEECH_MODEL: &str = "gpt-4o-mini-tts";
+
+/// Voices available for the OpenAI text-to-speech models
+const VOICES: [&str; 11] = [
+    "alloy", "ash", "ballad", "coral", "echo", "fable", "nova", "onyx", "sage", "shimmer", "verse",
+];
+
+pub fn create_transcription_body(
+    audio: AudioSource,
+    config: TranscriptionConfig,
+) -> Result<MultipartBody, Error> {
+    let model = match (config.model, config.diarization) {
+        (Some(model), true) if model != DIARIZATION_MODEL => {
+            return Err(unsupported(format!(
+                "diarization is only supported by the {DIARIZATION_MODEL} model"
+            )))
+        }
+        (Some(model), _) => model,
+        (None, true) => DIARIZATION_MODEL.to_string(),
+        (None, false) => DEFAULT_TRANSCRIPTION_MODEL.to_string(),
+    };
+
+    let response_format = if config.diarization {
+        "diarized_json"
+    } else if model == DEFAULT_TRANSCRIPTION_MODEL {
+        "verbose_json"
+    } else if config.timestamp_granularities.is_empty() {
+        "json"
+    } else {
+        return Err(unsupported(format!(
+            "timestamps are not supported by the {model} model"
+        )));
+    };
+
+    let mut body = MultipartBody::new();
+    body.file(
+        "file",
+        &format!("audio.{}", file_extension(&audio.mime_type)),
+        &audio.mime_type,
+        &audio.data,
+    );
+    body.text("model", &model);
+    body.text("response_format", response_format);
+    body.optional_text("language", config.language);
+    body.optional_text("prompt", config.prompt);
+    body.optional_text("temperature", config.temperature);
+    if config.diarization {
+        body.text("chunking_strategy", "auto");
+    }
+    if response_format == "verbose_json" {
+        for granularity in config.timestamp_granularities {
+            body.text(
+                "timestamp_granularities[]",
+                match granularity {
+                    TimestampGranularity::Segment => "segment",
+                    TimestampGranularity::Word => "word",
+                },
+            );
+        }
+    }
+    for kv in config.provider_options {
+        body.text(&kv.key, kv.value);
+    }
+    Ok(body)
+}
+
+pub fn process_transcription_response(response: TranscriptionResponse) -> TranscriptionResult {
+    TranscriptionResult {
+        text: response.text,
+        language: response.language,
+        duration: response.duration,
+        segments: response
+            .segments
+            .into_iter()
+            .enumerate()
+            .map(|(idx, segment)| TranscriptionSegment {
+                id: idx as u32,
+                text: segment.text,
+                start: segment.start,
+                end: segment.end,
+                speaker: segment.speaker,
+            })
+            .collect(),
+        words: response
+            .words
+            .into_iter()
+            .map(|word| TranscriptionWord {
+                text: word.word,
+                start: word.start,
+                end: word.end,
+                speaker: None,
+            })
+            .collect(),
+        provider_metadata_json: response.usage.map(|usage| usage.to_string()),
+    }
+}
+
+pub fn create_speech_request(text: String, config: SpeechConfig) -> CreateSpeechRequest {
+    CreateSpeechRequest {
+        model: config
+            .model
+            .unwrap_or_else(|| DEFAULT_SPEECH_MODEL.to_string()),
+        input: text,
+        voice: config.voice,
+        instructions: config.instructions,
+        response_format: config
+            .format
+            .map(|format| audio_format_name(format).to_string()),
+        speed: config.speed,
+    }
+}
+
+pub fn process_speech_response(data: Vec<u8>, config_format: Option<AudioFormat>) -> SpeechResult {
+    SpeechResult {
+        data,
+        mime_type: audio_format_mime_type(config_format.unwrap_or(AudioFormat::Mp3)).to_string(),
+        provider_metadata_json: None,
+    }
+}
+
+pub fn list_openai_voices(language: Option<String>) -> Vec<VoiceInfo> {
+    // All OpenAI voices are multilingual, so the language filter does not exclude any of them
+    VOICES
+        .iter()
+        .map(|voice| VoiceInfo {
+            id: voice.to_string(),
+            name: voice.to_string(),
+            language: language.clone(),
+            description: None,
+        })
+        .collect()
+}
+
+pub fn create_moderation_request(
+    inputs: Vec<String>,
+    config: ModerationConfig,
+) -> CreateModerationRequest {
+    CreateModerationRequest {
+        input: inputs,
+        model: config.model,
+    }
+}
+
+pub fn process_moderation_response(response: ModerationsResponse) -> ModerationResponse {
+    let results = response
+        .results
+        .into_iter()
+        .map(|result| ModerationResult {
+            flagged: result.flagged,
+            categories: result
+                .categories
+                .iter()
+                .map(|(category, flagged)| CategoryScore {
+                    category: category.clone(),
+                    flagged: *flagged,
+                    score: result.category_scores.get(category).copied(),
+                })
+                .collect(),
+        })
+        .collect();
+
+    ModerationResponse {
+        results,
+        model: Some(response.model),
+        provider_id: Some(response.id),
+        provider_metadata_json: None,
+    }
+}
+
+pub fn create_batch_input_file(requests: Vec<BatchRequest>) -> Result<String, Error> {
+    let mut lines = Vec::new();
+    for request in requests {
+        let tools = tool_defs_to_tools(&request.config.tools)?;
+        let items = messages_to_input_items(request.messages);
+        let line = BatchRequestLine {
+            custom_id: request.custom_id,
+            method: "POST".to_string(),
+            url: "/v1/responses".to_string(),
+            body: create_request(items, request.config, tools)?,
+        };
+        lines.push(serde_json::to_string(&line).map_err(|err| Error {
+            code: ErrorCode::InternalError,
+            message: format!("Failed to encode batch request: {err}"),
+            provider_error_json: None,
+        })?);
+    }
+    Ok(lines.join("\n"))
+}
+
+pub fn process_batch_object(batch: BatchObject) -> BatchInfo {
+    let status = match batch.status.as_str() {
+        "validating" => BatchStatus::Pending,
+        "in_progress" | "finalizing" | "cancelling" => BatchStatus::Running,
+        "completed" => BatchStatus::Succeeded,
+        "cancelled" => BatchStatus::Cancelled,
+        "expired" => BatchStatus::Expired,
+        _ => BatchStatus::Failed(
+            batch
+                .errors
+                .iter()
+                .flat_map(|errors| errors.data.iter())
+                .filter_map(|error| error.message.clone())
+                .collect::<Vec<_>>()
+                .join("; "),
+        ),
+    };
+
+    BatchInfo {
+        job_id: batch.id.clone(),
+        status,
+        request_counts: batch
+            .request_counts
+            .as_ref()
+            .map(|counts| BatchRequestCounts {
+                total: counts.total,
+                succeeded: counts.completed,
+                failed: counts.failed,
+            }),
+        created_at: Some(batch.created_at.to_string()),
+        provider_metadata_json: serde_json::to_string(&batch).ok(),
+    }
+}
+
+pub fn process_batch_results(content: &str) -> Result<Vec<BatchResult>, Error> {
+    let mut results = Vec::new();
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        let line: BatchResultLine = serde_json::from_str(line).map_err(|err| Error {
+            code: ErrorCode::InternalError,
+            message: format!("Failed to decode batch result: {err}"),
+            provider_error_json: Some(line.to_string()),
+        })?;
+
+        let event = match (line.response, line.error) {
+            (Some(response), _) if (200..300).contains(&response.status_code) => {
+                match serde_json::from_value::<CreateModelResponseResponse>(response.body) {
+                    Ok(response) => process_model_response(response),
+                    Err(err) => ChatEvent::Error(Error {
+                        code: ErrorCode::InternalError,
+                        message: format!("Failed to decode batch response body: {err}"),
+                        provider_error_json: None,
+                    }),
+                }
+            }
+            (Some(response), _) => ChatEvent::Error(Error {
+                code: StatusCode::from_u16(response.status_code)
+                    .map(error_code_from_status)
+                    .unwrap_or(ErrorCode::Unknown),
+                message: format!("Request failed with {}", response.status_code),
+                provider_error_json: Some(response.body.to_string()),
+            }),
+            (None, Some(error)) => ChatEvent::Error(Error {
+                code: error
+                    .code
+                    .map(parse_error_code)
+                    .unwrap_or(ErrorCode::Unknown),
+                message: error.message.unwrap_or_default(),
+                provider_error_json: None,
+            }),
+            (None, None) => ChatEvent::Error(Error {
+                code: ErrorCode::Unknown,
+                message: "Batch result has neither a response nor an error".to_string(),
+                provider_error_json: None,
+            }),
+        };
+
+        results.push(BatchResult {
+            custom_id: line.custom_id,
+            event,
+        });
     }
+    Ok(results)
 }
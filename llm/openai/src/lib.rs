@@ -1,18 +1,36 @@
 use crate::client::{
-    CreateModelResponseResponse, InputItem, OutputItem, ResponseOutputItemDone,
-    ResponseOutputTextDelta, ResponsesApi,
+    AudioApi, BatchesApi, CreateBatchRequest, CreateModelResponseResponse, ImagesApi, InputItem,
+    ModerationsApi, OutputItem, ResponseOutputItemDone, ResponseOutputTextDelta, ResponsesApi,
 };
 use crate::conversions::{
-    create_request, create_response_metadata, messages_to_input_items, parse_error_code,
-    process_model_response, tool_defs_to_tools, tool_results_to_input_items,
+    create_batch_input_file, create_image_edit_body, create_image_request,
+    create_image_variation_body, create_moderation_request, create_request,
+    create_response_metadata, create_speech_request, create_transcription_body, list_openai_voices,
+    messages_to_input_items, parse_error_code, process_batch_object, process_batch_results,
+    process_image_response, process_model_response, process_moderation_response,
+    process_speech_response, process_transcription_response, tool_defs_to_tools,
+    tool_results_to_input_items,
 };
+use golem_llm::batch_stream::LlmBatchResultStream;
 use golem_llm::chat_stream::{LlmChatStream, LlmChatStreamState};
 use golem_llm::config::with_config_key;
-use golem_llm::durability::{DurableLLM, ExtendedGuest};
+use golem_llm::durability::{DurableLLM, ExtendedBatchGuest, ExtendedGuest};
+use golem_llm::error::unsupported;
 use golem_llm::event_source::EventSource;
+use golem_llm::golem::llm::batch::{
+    BatchInfo, BatchRequest, BatchResult, BatchResultStream, Guest as BatchGuest,
+};
+use golem_llm::golem::llm::images::{Guest as ImagesGuest, ImageConfig, ImageResponse};
 use golem_llm::golem::llm::llm::{
-    ChatEvent, ChatStream, Config, ContentPart, Error, ErrorCode, Guest, Message, StreamDelta,
-    StreamEvent, ToolCall, ToolResult,
+    ChatEvent, ChatStream, Config, ContentPart, Error, ErrorCode, Guest, ImageSource, Kv, Message,
+    StreamDelta, StreamEvent, ToolCall, ToolResult,
+};
+use golem_llm::golem::llm::moderation::{
+    Guest as ModerationGuest, ModerationConfig, ModerationResponse,
+};
+use golem_llm::golem::llm::speech::{Guest as SpeechGuest, SpeechConfig, SpeechResult, VoiceInfo};
+use golem_llm::golem::llm::transcription::{
+    AudioSource, Guest as TranscriptionGuest, TranscriptionConfig, TranscriptionResult,
 };
 use golem_rust::wasm_rpc::Pollable;
 use log::trace;
@@ -161,14 +179,13 @@ impl OpenAIComponent {
     const ENV_VAR_NAME: &'static str = "OPENAI_API_KEY";
 
     fn request(client: ResponsesApi, items: Vec<InputItem>, config: Config) -> ChatEvent {
-        match tool_defs_to_tools(&config.tools) {
-            Ok(tools) => {
-                let request = create_request(items, config, tools);
-                match client.create_model_response(request) {
-                    Ok(response) => process_model_response(response),
-                    Err(error) => ChatEvent::Error(error),
-                }
-            }
+        match tool_defs_to_tools(&config.tools)
+            .and_then(|tools| create_request(items, config, tools))
+        {
+            Ok(request) => match client.create_model_response(request) {
+                Ok(response) => process_model_response(response),
+                Err(error) => ChatEvent::Error(error),
+            },
             Err(error) => ChatEvent::Error(error),
         }
     }
@@ -178,9 +195,10 @@ impl OpenAIComponent {
         items: Vec<InputItem>,
         config: Config,
     ) -> LlmChatStream<OpenAIChatStream> {
-        match tool_defs_to_tools(&config.tools) {
-            Ok(tools) => {
-                let mut request = create_request(items, config, tools);
+        match tool_defs_to_tools(&config.tools)
+            .and_then(|tools| create_request(items, config, tools))
+        {
+            Ok(mut request) => {
                 request.stream = true;
                 match client.stream_model_response(request) {
                     Ok(stream) => OpenAIChatStream::new(stream),
@@ -190,6 +208,29 @@ impl OpenAIComponent {
             Err(error) => OpenAIChatStream::failed(error),
         }
     }
+
+    /// Downloads both the output and the error file of a batch, as requests failing during
+    /// the batch run are only reported in the latter
+    fn fetch_batch_results(client: BatchesApi, job_id: String) -> Result<Vec<BatchResult>, Error> {
+        let batch = client.retrieve_batch(&job_id)?;
+        if batch.output_file_id.is_none() && batch.error_file_id.is_none() {
+            return Err(Error {
+                code: ErrorCode::InvalidRequest,
+                message: format!("Batch {job_id} has no results yet ({})", batch.status),
+                provider_error_json: None,
+            });
+        }
+
+        let mut results = Vec::new();
+        for file_id in batch
+            .output_file_id
+            .iter()
+            .chain(batch.error_file_id.iter())
+        {
+            results.extend(process_batch_results(&client.file_content(file_id)?)?);
+        }
+        Ok(results)
+    }
 }
 
 impl Guest for OpenAIComponent {
@@ -223,7 +264,131 @@ impl Guest for OpenAIComponent {
     }
 }
 
+impl ImagesGuest for OpenAIComponent {
+    fn generate(prompt: String, config: ImageConfig) -> Result<ImageResponse, Error> {
+        with_config_key(Self::ENV_VAR_NAME, Err, |openai_api_key| {
+            let client = ImagesApi::new(openai_api_key);
+
+            let request = create_image_request(prompt, config)?;
+            process_image_response(client.create_image(request)?)
+        })
+    }
+
+    fn edit(
+        image: ImageSource,
+        mask: Option<ImageSource>,
+        prompt: String,
+        config: ImageConfig,
+    ) -> Result<ImageResponse, Error> {
+        with_config_key(Self::ENV_VAR_NAME, Err, |openai_api_key| {
+            let client = ImagesApi::new(openai_api_key);
+
+            let body = create_image_edit_body(image, mask, prompt, config)?;
+            process_image_response(client.create_image_edit(body)?)
+        })
+    }
+
+    fn create_variations(image: ImageSource, config: ImageConfig) -> Result<ImageResponse, Error> {
+        with_config_key(Self::ENV_VAR_NAME, Err, |openai_api_key| {
+            let client = ImagesApi::new(openai_api_key);
+
+            let body = create_image_variation_body(image, config)?;
+            process_image_response(client.create_image_variation(body)?)
+        })
+    }
+}
+
+impl TranscriptionGuest for OpenAIComponent {
+    fn transcribe(
+        audio: AudioSource,
+        config: TranscriptionConfig,
+    ) -> Result<TranscriptionResult, Error> {
+        with_config_key(Self::ENV_VAR_NAME, Err, |openai_api_key| {
+            let client = AudioApi::new(openai_api_key);
+
+            let body = create_transcription_body(audio, config)?;
+            client
+                .create_transcription(body)
+                .map(process_transcription_response)
+        })
+    }
+}
+
+impl SpeechGuest for OpenAIComponent {
+    fn synthesize(text: String, config: SpeechConfig) -> Result<SpeechResult, Error> {
+        with_config_key(Self::ENV_VAR_NAME, Err, |openai_api_key| {
+            let client = AudioApi::new(openai_api_key);
+
+            let format = config.format;
+            let request = create_speech_request(text, config);
+            client
+                .create_speech(request)
+                .map(|data| process_speech_response(data, format))
+        })
+    }
+
+    fn list_voices(language: Option<String>) -> Result<Vec<VoiceInfo>, Error> {
+        Ok(list_openai_voices(language))
+    }
+}
+
+impl ModerationGuest for OpenAIComponent {
+    fn moderate(
+        inputs: Vec<String>,
+        config: ModerationConfig,
+    ) -> Result<ModerationResponse, Error> {
+        with_config_key(Self::ENV_VAR_NAME, Err, |openai_api_key| {
+            let client = ModerationsApi::new(openai_api_key);
+
+            let request = create_moderation_request(inputs, config);
+            client
+                .create_moderation(request)
+                .map(process_moderation_response)
+        })
+    }
+}
+
+impl BatchGuest for OpenAIComponent {
+    type BatchResultStream = LlmBatchResultStream;
+
+    fn submit(requests: Vec<BatchRequest>) -> Result<String, Error> {
+        with_config_key(Self::ENV_VAR_NAME, Err, |openai_api_key| {
+            let client = BatchesApi::new(openai_api_key);
+
+            let input_file = client.upload_batch_file(create_batch_input_file(requests)?)?;
+            let batch = client.create_batch(CreateBatchRequest {
+                input_file_id: input_file.id,
+                endpoint: "/v1/responses".to_string(),
+                completion_window: "24h".to_string(),
+            })?;
+            Ok(batch.id)
+        })
+    }
+
+    fn poll(job_id: String, _credentials: Option<Vec<Kv>>) -> Result<BatchInfo, Error> {
+        with_config_key(Self::ENV_VAR_NAME, Err, |openai_api_key| {
+            let client = BatchesApi::new(openai_api_key);
+
+            client.retrieve_batch(&job_id).map(process_batch_object)
+        })
+    }
+
+    fn cancel(job_id: String, _credentials: Option<Vec<Kv>>) -> Result<String, Error> {
+        with_config_key(Self::ENV_VAR_NAME, Err, |openai_api_key| {
+            let client = BatchesApi::new(openai_api_key);
+
+            client.cancel_batch(&job_id).map(|batch| batch.status)
+        })
+    }
+
+    fn results(job_id: String, _credentials: Option<Vec<Kv>>) -> BatchResultStream {
+        BatchResultStream::new(Self::unwrapped_batch_results(job_id))
+    }
+}
+
 impl ExtendedGuest for OpenAIComponent {
+    const PROVIDER: &'static str = "openai";
+
     fn unwrapped_stream(messages: Vec<Message>, config: Config) -> Self::ChatStream {
         with_config_key(
             Self::ENV_VAR_NAME,
@@ -242,6 +407,25 @@ impl ExtendedGuest for OpenAIComponent {
     }
 }
 
+impl ExtendedBatchGuest for OpenAIComponent {
+    fn unwrapped_batch_results(job_id: String) -> Self::BatchResultStream {
+        with_config_key(
+            Self::ENV_VAR_NAME,
+            LlmBatchResultStream::failed,
+            |openai_api_key| {
+                let client = BatchesApi::new(openai_api_key);
+
+                LlmBatchResultStream::new(move || Self::fetch_batch_results(client, job_id))
+            },
+        )
+    }
+}
+
 type DurableOpenAIComponent = DurableLLM<OpenAIComponent>;
 
 golem_llm::export_llm!(DurableOpenAIComponent with_types_in golem_llm);
+golem_llm::export_images!(DurableOpenAIComponent with_types_in golem_llm::images_library);
+golem_llm::export_transcription!(DurableOpenAIComponent with_types_in golem_llm::transcription_library);
+golem_llm::export_speech!(DurableOpenAIComponent with_types_in golem_llm::speech_library);
+golem_llm::export_moderation!(DurableOpenAIComponent with_types_in golem_llm::moderation_library);
+golem_llm::export_batch!(DurableOpenAIComponent with_types_in golem_llm::batch_library);
@@ -58,6 +58,22 @@ impl CompletionsApi {
         EventSource::new(response)
             .map_err(|err| from_event_source_error("Failed to create SSE stream", err))
     }
+
+    /// Gets the statistics of a previous generation. They are only available a short time after
+    /// the response has been received.
+    pub fn get_generation(&self, id: &str) -> Result<GenerationResponse, Error> {
+        trace!("Getting generation {id} from OpenRouter API");
+
+        let response: Response = self
+            .client
+            .request(Method::GET, format!("{BASE_URL}/api/v1/generation"))
+            .bearer_auth(self.api_key.clone())
+            .query(&[("id", id)])
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -92,6 +108,70 @@ pub struct CompletionsRequest {
     pub min_p: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub top_a: Option<f32>,
+    /// Models to try in order if the primary model fails
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub models: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<ProviderPreferences>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<UsageAccounting>,
+}
+
+/// Provider routing preferences, see https://openrouter.ai/docs/features/provider-routing
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProviderPreferences {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_fallbacks: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub require_parameters: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_collection: Option<DataCollection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub only: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantizations: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<ProviderSort>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_price: Option<MaxPrice>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataCollection {
+    Allow,
+    Deny,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderSort {
+    Price,
+    Throughput,
+    Latency,
+}
+
+/// Maximum price in USD per million tokens, or per request or image
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MaxPrice {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<f64>,
+}
+
+/// Makes OpenRouter report the credits charged for the request in the usage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageAccounting {
+    pub include: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -222,6 +302,8 @@ pub struct CompletionsResponse {
     pub model: String,
     pub system_fingerprint: Option<String>,
     pub usage: Option<Usage>,
+    /// The upstream provider which served the request
+    pub provider: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -270,6 +352,25 @@ pub struct Usage {
     pub completion_tokens: u32,
     pub prompt_tokens: u32,
     pub total_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_tokens_details: Option<PromptTokensDetails>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion_tokens_details: Option<CompletionTokensDetails>,
+    /// Credits charged for the request, only reported with usage accounting
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_byok: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTokensDetails {
+    pub cached_tokens: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletionTokensDetails {
+    pub reasoning_tokens: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -280,6 +381,7 @@ pub struct ChatCompletionChunk {
     pub choices: Vec<ChoiceChunk>,
     pub usage: Option<Usage>,
     pub system_fingerprint: Option<String>,
+    pub provider: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -297,6 +399,34 @@ pub struct ChoiceDelta {
     pub role: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationResponse {
+    pub data: Generation,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Generation {
+    pub id: String,
+    pub model: String,
+    pub provider_name: Option<String>,
+    pub total_cost: f64,
+    pub created_at: Option<String>,
+    pub tokens_prompt: Option<u32>,
+    pub tokens_completion: Option<u32>,
+    pub native_tokens_prompt: Option<u32>,
+    pub native_tokens_completion: Option<u32>,
+    pub native_tokens_reasoning: Option<u32>,
+    pub native_tokens_cached: Option<u32>,
+    /// Time to the first token, in milliseconds
+    pub latency: Option<f64>,
+    /// Total generation time, in milliseconds
+    pub generation_time: Option<f64>,
+    pub finish_reason: Option<String>,
+    pub upstream_id: Option<String>,
+    pub cache_discount: Option<f64>,
+    pub is_byok: Option<bool>,
+}
+
 fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, Error> {
     let status = response.status();
     if status.is_success() {
@@ -1,12 +1,16 @@
 use crate::client::{
-    CompletionsRequest, CompletionsResponse, Detail, FunctionName, ToolChoiceFunction,
+    CompletionsRequest, CompletionsResponse, DataCollection, Detail, FunctionName, Generation,
+    ProviderPreferences, ProviderSort, ToolChoiceFunction, UsageAccounting,
 };
 use base64::{engine::general_purpose, Engine as _};
+use golem_llm::golem::llm::costs::GenerationStats;
 use golem_llm::golem::llm::llm::{
-    ChatEvent, CompleteResponse, Config, ContentPart, Error, ErrorCode, FinishReason, ImageDetail,
-    ImageReference, Message, ResponseMetadata, Role, ToolCall, ToolDefinition, ToolResult, Usage,
+    ChatEvent, CompleteResponse, Config, ContentPart, Cost, Error, ErrorCode, FinishReason,
+    ImageDetail, ImageReference, Message, ResponseMetadata, Role, ToolCall, ToolDefinition,
+    ToolResult, Usage,
 };
 use std::collections::HashMap;
+use std::str::FromStr;
 
 pub fn messages_to_request(
     messages: Vec<Message>,
@@ -47,42 +51,134 @@ pub fn messages_to_request(
         tools.push(tool_definition_to_tool(tool)?)
     }
 
+    let provider = provider_preferences(&options)?;
+
     Ok(CompletionsRequest {
         messages: completion_messages,
         model: config.model,
-        frequency_penalty: options
-            .get("frequency_penalty")
-            .and_then(|fp_s| fp_s.parse::<f32>().ok()),
+        frequency_penalty: config.frequency_penalty.or_else(|| {
+            options
+                .get("frequency_penalty")
+                .and_then(|fp_s| fp_s.parse::<f32>().ok())
+        }),
         max_tokens: config.max_tokens,
-        presence_penalty: options
-            .get("presence_penalty")
-            .and_then(|pp_s| pp_s.parse::<f32>().ok()),
-        repetition_penalty: options
-            .get("repetition_penalty")
-            .and_then(|rp_s| rp_s.parse::<f32>().ok()),
-        seed: options
-            .get("seed")
-            .and_then(|seed_s| seed_s.parse::<u32>().ok()),
+        presence_penalty: config.presence_penalty.or_else(|| {
+            options
+                .get("presence_penalty")
+                .and_then(|pp_s| pp_s.parse::<f32>().ok())
+        }),
+        repetition_penalty: config.repetition_penalty.or_else(|| {
+            options
+                .get("repetition_penalty")
+                .and_then(|rp_s| rp_s.parse::<f32>().ok())
+        }),
+        seed: config.seed.or_else(|| {
+            options
+                .get("seed")
+                .and_then(|seed_s| seed_s.parse::<u32>().ok())
+        }),
         stop: config.stop_sequences,
         stream: Some(false),
         temperature: config.temperature,
         tool_choice: config.tool_choice.map(convert_tool_choice),
         tools,
-        top_p: options
-            .get("top_p")
-            .and_then(|top_p_s| top_p_s.parse::<f32>().ok()),
-        top_k: options
-            .get("top_k")
-            .and_then(|top_k_s| top_k_s.parse::<f32>().ok()),
+        top_p: config.top_p.or_else(|| {
+            options
+                .get("top_p")
+                .and_then(|top_p_s| top_p_s.parse::<f32>().ok())
+        }),
+        top_k: config.top_k.map(|top_k| top_k as f32).or_else(|| {
+            options
+                .get("top_k")
+                .and_then(|top_k_s| top_k_s.parse::<f32>().ok())
+        }),
         min_p: options
             .get("min_p")
             .and_then(|min_p_s| min_p_s.parse::<f32>().ok()),
         top_a: options
             .get("top_a")
             .and_then(|top_a_s| top_a_s.parse::<f32>().ok()),
+        models: options.get("models").map(|models| parse_list(models)),
+        provider,
+        usage: Some(UsageAccounting { include: true }),
     })
 }
 
+/// Builds the provider routing preferences from the `provider` option, containing the whole
+/// routing object as JSON, and the options overriding its individual fields.
+fn provider_preferences(
+    options: &HashMap<String, String>,
+) -> Result<Option<ProviderPreferences>, Error> {
+    let mut preferences = match options.get("provider") {
+        Some(json) => serde_json::from_str::<ProviderPreferences>(json)
+            .map_err(|err| invalid_option("provider", err))?,
+        None => ProviderPreferences::default(),
+    };
+
+    if let Some(order) = options.get("provider_order") {
+        preferences.order = Some(parse_list(order));
+    }
+    if let Some(only) = options.get("provider_only") {
+        preferences.only = Some(parse_list(only));
+    }
+    if let Some(ignore) = options.get("provider_ignore") {
+        preferences.ignore = Some(parse_list(ignore));
+    }
+    if let Some(quantizations) = options.get("quantizations") {
+        preferences.quantizations = Some(parse_list(quantizations));
+    }
+    if let Some(allow_fallbacks) = options.get("allow_fallbacks") {
+        preferences.allow_fallbacks = Some(parse_option("allow_fallbacks", allow_fallbacks)?);
+    }
+    if let Some(require_parameters) = options.get("require_parameters") {
+        preferences.require_parameters =
+            Some(parse_option("require_parameters", require_parameters)?);
+    }
+    if let Some(data_collection) = options.get("data_collection") {
+        preferences.data_collection = Some(
+            serde_json::from_value::<DataCollection>(serde_json::Value::String(
+                data_collection.clone(),
+            ))
+            .map_err(|err| invalid_option("data_collection", err))?,
+        );
+    }
+    if let Some(sort) = options.get("provider_sort") {
+        preferences.sort = Some(
+            serde_json::from_value::<ProviderSort>(serde_json::Value::String(sort.clone()))
+                .map_err(|err| invalid_option("provider_sort", err))?,
+        );
+    }
+
+    if preferences == ProviderPreferences::default() {
+        Ok(None)
+    } else {
+        Ok(Some(preferences))
+    }
+}
+
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn parse_option<T: FromStr>(key: &str, value: &str) -> Result<T, Error>
+where
+    T::Err: std::fmt::Display,
+{
+    value.parse::<T>().map_err(|err| invalid_option(key, err))
+}
+
+fn invalid_option(key: &str, err: impl std::fmt::Display) -> Error {
+    Error {
+        code: ErrorCode::InvalidRequest,
+        message: format!("Invalid {key} provider option: {err}"),
+        provider_error_json: None,
+    }
+}
+
 pub fn process_response(response: CompletionsResponse) -> ChatEvent {
     let choice = response.choices.first();
     if let Some(choice) = choice {
@@ -106,7 +202,11 @@ pub fn process_response(response: CompletionsResponse) -> ChatEvent {
                 usage: response.usage.as_ref().map(convert_usage),
                 provider_id: None,
                 timestamp: Some(response.created.to_string()),
-                provider_metadata_json: None,
+                provider_metadata_json: routing_metadata_json(
+                    &response.model,
+                    response.provider.as_deref(),
+                ),
+                cost: response.usage.as_ref().and_then(convert_cost),
             };
 
             ChatEvent::Message(CompleteResponse {
@@ -231,6 +331,88 @@ pub fn convert_usage(value: &crate::client::Usage) -> Usage {
         input_tokens: Some(value.prompt_tokens),
         output_tokens: Some(value.completion_tokens),
         total_tokens: Some(value.total_tokens),
+        cached_input_tokens: value
+            .prompt_tokens_details
+            .as_ref()
+            .and_then(|details| details.cached_tokens),
+        reasoning_tokens: value
+            .completion_tokens_details
+            .as_ref()
+            .and_then(|details| details.reasoning_tokens),
+    }
+}
+
+/// Credits are denominated in USD. OpenRouter only reports the total amount charged, so the
+/// other fields of the cost are left at zero.
+pub fn convert_cost(value: &crate::client::Usage) -> Option<Cost> {
+    value.cost.map(|total_cost| Cost {
+        input_cost: 0.0,
+        output_cost: 0.0,
+        cached_input_cost: 0.0,
+        reasoning_cost: 0.0,
+        total_cost,
+        currency: "USD".to_string(),
+    })
+}
+
+/// Describes which model and upstream provider actually served the request, as they can differ
+/// from the requested ones when fallbacks are used. Returns `None` if OpenRouter did not report
+/// the provider.
+pub fn routing_metadata_json(model: &str, provider: Option<&str>) -> Option<String> {
+    provider.map(|provider| {
+        serde_json::json!({
+            "model": model,
+            "provider": provider,
+        })
+        .to_string()
+    })
+}
+
+pub fn process_generation(generation: Generation) -> GenerationStats {
+    let input_tokens = generation.native_tokens_prompt.or(generation.tokens_prompt);
+    let output_tokens = generation
+        .native_tokens_completion
+        .or(generation.tokens_completion);
+    let total_tokens = match (input_tokens, output_tokens) {
+        (Some(input), Some(output)) => Some(input + output),
+        _ => None,
+    };
+
+    GenerationStats {
+        id: generation.id,
+        model: generation.model,
+        provider_name: generation.provider_name,
+        usage: Some(Usage {
+            input_tokens,
+            output_tokens,
+            total_tokens,
+            cached_input_tokens: generation.native_tokens_cached,
+            reasoning_tokens: generation.native_tokens_reasoning,
+        }),
+        cost: Some(Cost {
+            input_cost: 0.0,
+            output_cost: 0.0,
+            cached_input_cost: 0.0,
+            reasoning_cost: 0.0,
+            total_cost: generation.total_cost,
+            currency: "USD".to_string(),
+        }),
+        latency_ms: generation.latency.map(|latency| latency as u32),
+        generation_time_ms: generation.generation_time.map(|time| time as u32),
+        finish_reason: generation.finish_reason.and_then(|reason| {
+            serde_json::from_value::<crate::client::FinishReason>(serde_json::Value::String(reason))
+                .ok()
+                .map(|reason| convert_finish_reason(&reason))
+        }),
+        created_at: generation.created_at,
+        provider_metadata_json: Some(
+            serde_json::json!({
+                "upstream_id": generation.upstream_id,
+                "cache_discount": generation.cache_discount,
+                "is_byok": generation.is_byok,
+            })
+            .to_string(),
+        ),
     }
 }
 
@@ -259,3 +441,195 @@ fn convert_tool_choice(tool_choice: String) -> crate::client::ToolChoice {
         }),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::MaxPrice;
+
+    fn options(options: &[(&str, &str)]) -> HashMap<String, String> {
+        options
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn usage(cost: Option<f64>) -> crate::client::Usage {
+        crate::client::Usage {
+            completion_tokens: 20,
+            prompt_tokens: 10,
+            total_tokens: 30,
+            prompt_tokens_details: None,
+            completion_tokens_details: None,
+            cost,
+            is_byok: None,
+        }
+    }
+
+    #[test]
+    fn provider_preferences_are_parsed() {
+        assert_eq!(provider_preferences(&options(&[])).unwrap(), None);
+
+        let preferences = provider_preferences(&options(&[
+            (
+                "provider",
+                r#"{"order": ["openai"], "max_price": {"prompt": 1.5, "completion": 2}}"#,
+            ),
+            ("provider_order", "anthropic, google-vertex"),
+            ("provider_only", "anthropic,google-vertex,"),
+            ("provider_ignore", "deepinfra"),
+            ("provider_sort", "throughput"),
+            ("allow_fallbacks", "false"),
+            ("data_collection", "deny"),
+        ]))
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(
+            preferences,
+            ProviderPreferences {
+                order: Some(vec!["anthropic".to_string(), "google-vertex".to_string()]),
+                allow_fallbacks: Some(false),
+                require_parameters: None,
+                data_collection: Some(DataCollection::Deny),
+                only: Some(vec!["anthropic".to_string(), "google-vertex".to_string()]),
+                ignore: Some(vec!["deepinfra".to_string()]),
+                quantizations: None,
+                sort: Some(ProviderSort::Throughput),
+                max_price: Some(MaxPrice {
+                    prompt: Some(1.5),
+                    completion: Some(2.0),
+                    request: None,
+                    image: None,
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_provider_preferences_are_rejected() {
+        for invalid in [
+            ("provider", "not json"),
+            ("provider", r#"{"max_price": {"prompt": "cheap"}}"#),
+            ("provider_sort", "fastest"),
+            ("allow_fallbacks", "maybe"),
+            ("require_parameters", "1"),
+            ("data_collection", "sometimes"),
+        ] {
+            let error = provider_preferences(&options(&[invalid])).unwrap_err();
+            assert_eq!(error.code, ErrorCode::InvalidRequest, "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn cost_is_only_reported_with_usage_accounting() {
+        assert_eq!(convert_cost(&usage(None)), None);
+        assert_eq!(
+            convert_cost(&usage(Some(0.0125))),
+            Some(Cost {
+                input_cost: 0.0,
+                output_cost: 0.0,
+                cached_input_cost: 0.0,
+                reasoning_cost: 0.0,
+                total_cost: 0.0125,
+                currency: "USD".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn routing_metadata_needs_the_provider() {
+        assert_eq!(routing_metadata_json("openai/gpt-4o", None), None);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(
+                &routing_metadata_json("openai/gpt-4o", Some("Azure")).unwrap()
+            )
+            .unwrap(),
+            serde_json::json!({ "model": "openai/gpt-4o", "provider": "Azure" })
+        );
+    }
+
+    #[test]
+    fn generations_are_converted() {
+        let generation = serde_json::from_value::<Generation>(serde_json::json!({
+            "id": "gen-123",
+            "model": "anthropic/claude-3.5-sonnet",
+            "provider_name": "Anthropic",
+            "total_cost": 0.0042,
+            "created_at": "2025-01-01T00:00:00Z",
+            "tokens_prompt": 12,
+            "tokens_completion": 30,
+            "native_tokens_prompt": 15,
+            "native_tokens_completion": 34,
+            "native_tokens_reasoning": 4,
+            "native_tokens_cached": 5,
+            "latency": 250.7,
+            "generation_time": 1200.0,
+            "finish_reason": "length",
+            "upstream_id": "msg_123",
+            "cache_discount": 0.001,
+            "is_byok": false
+        }))
+        .unwrap();
+
+        let stats = process_generation(generation);
+        assert_eq!(stats.id, "gen-123");
+        assert_eq!(stats.model, "anthropic/claude-3.5-sonnet");
+        assert_eq!(stats.provider_name, Some("Anthropic".to_string()));
+        assert_eq!(
+            stats.usage,
+            Some(Usage {
+                input_tokens: Some(15),
+                output_tokens: Some(34),
+                total_tokens: Some(49),
+                cached_input_tokens: Some(5),
+                reasoning_tokens: Some(4),
+            })
+        );
+        assert_eq!(stats.cost.map(|cost| cost.total_cost), Some(0.0042));
+        assert_eq!(stats.latency_ms, Some(250));
+        assert_eq!(stats.generation_time_ms, Some(1200));
+        assert_eq!(stats.finish_reason, Some(FinishReason::Length));
+        assert_eq!(stats.created_at, Some("2025-01-01T00:00:00Z".to_string()));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&stats.provider_metadata_json.unwrap())
+                .unwrap(),
+            serde_json::json!({
+                "upstream_id": "msg_123",
+                "cache_discount": 0.001,
+                "is_byok": false
+            })
+        );
+    }
+
+    #[test]
+    fn generations_without_native_counts_use_the_normalized_ones() {
+        let generation = serde_json::from_value::<Generation>(serde_json::json!({
+            "id": "gen-456",
+            "model": "openai/gpt-4o",
+            "provider_name": null,
+            "total_cost": 0.0,
+            "created_at": null,
+            "tokens_prompt": 12,
+            "tokens_completion": null,
+            "native_tokens_prompt": null,
+            "native_tokens_completion": null,
+            "native_tokens_reasoning": null,
+            "native_tokens_cached": null,
+            "latency": null,
+            "generation_time": null,
+            "finish_reason": "unknown",
+            "upstream_id": null,
+            "cache_discount": null,
+            "is_byok": null
+        }))
+        .unwrap();
+
+        let stats = process_generation(generation);
+        let usage = stats.usage.unwrap();
+        assert_eq!(usage.input_tokens, Some(12));
+        assert_eq!(usage.output_tokens, None);
+        assert_eq!(usage.total_tokens, None);
+        assert_eq!(stats.finish_reason, None);
+    }
+}
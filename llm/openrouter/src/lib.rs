@@ -3,18 +3,23 @@ mod conversions;
 
 use crate::client::{ChatCompletionChunk, CompletionsApi, CompletionsRequest, FunctionCall};
 use crate::conversions::{
-    convert_finish_reason, convert_usage, messages_to_request, process_response,
-    tool_results_to_messages,
+    convert_cost, convert_finish_reason, convert_usage, messages_to_request, process_generation,
+    process_response, routing_metadata_json, tool_results_to_messages,
 };
 use golem_llm::chat_stream::{LlmChatStream, LlmChatStreamState};
 use golem_llm::config::with_config_key;
 use golem_llm::durability::{DurableLLM, ExtendedGuest};
 use golem_llm::error::error_code_from_status;
 use golem_llm::event_source::EventSource;
+use golem_llm::golem::llm::costs::GenerationStats;
 use golem_llm::golem::llm::llm::{
     ChatEvent, ChatStream, Config, ContentPart, Error, FinishReason, Guest, Message,
     ResponseMetadata, Role, StreamDelta, StreamEvent, ToolCall, ToolResult,
 };
+use golem_llm::golem::llm::moderation::{
+    Guest as ModerationGuest, ModerationConfig, ModerationResponse,
+};
+use golem_llm::guardrails::moderate_with_local_policy;
 use golem_rust::wasm_rpc::Pollable;
 use log::trace;
 use reqwest::StatusCode;
@@ -102,7 +107,11 @@ impl LlmChatStreamState for OpenRouterChatStream {
                             usage: Some(convert_usage(&usage)),
                             provider_id: None,
                             timestamp: Some(message.created.to_string()),
-                            provider_metadata_json: None,
+                            provider_metadata_json: routing_metadata_json(
+                                &message.model,
+                                message.provider.as_deref(),
+                            ),
+                            cost: convert_cost(&usage),
                         })))
                     } else if let Some(choice) = message.choices.into_iter().next() {
                         if let Some(finish_reason) = choice.finish_reason {
@@ -287,7 +296,18 @@ impl Guest for OpenRouterComponent {
     }
 }
 
+impl ModerationGuest for OpenRouterComponent {
+    fn moderate(
+        inputs: Vec<String>,
+        _config: ModerationConfig,
+    ) -> Result<ModerationResponse, Error> {
+        moderate_with_local_policy(inputs)
+    }
+}
+
 impl ExtendedGuest for OpenRouterComponent {
+    const PROVIDER: &'static str = "openrouter";
+
     fn unwrapped_stream(
         messages: Vec<Message>,
         config: Config,
@@ -358,8 +378,18 @@ impl ExtendedGuest for OpenRouterComponent {
     fn subscribe(stream: &Self::ChatStream) -> Pollable {
         stream.subscribe()
     }
+
+    fn generation_stats(response_id: String) -> Result<GenerationStats, Error> {
+        with_config_key(Self::ENV_VAR_NAME, Err, |openrouter_api_key| {
+            let client = CompletionsApi::new(openrouter_api_key);
+            client
+                .get_generation(&response_id)
+                .map(|response| process_generation(response.data))
+        })
+    }
 }
 
 type DurableOpenRouterComponent = DurableLLM<OpenRouterComponent>;
 
 golem_llm::export_llm!(DurableOpenRouterComponent with_types_in golem_llm);
+golem_llm::export_moderation!(DurableOpenRouterComponent with_types_in golem_llm::moderation_library);
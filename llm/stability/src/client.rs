@@ -0,0 +1,210 @@
+use golem_llm::error::{error_code_from_status, from_reqwest_error};
+use golem_llm::golem::llm::types::Error;
+use golem_llm::multipart::{file_extension, MultipartBody};
+use log::trace;
+use reqwest::{Client, Method, Response};
+use serde::{Deserialize, Serialize};
+
+const BASE_URL: &str = "https://api.stability.ai";
+const ACCEPT_HEADER_JSON: &str = "application/json";
+
+/// The Stability API client for the Stable Image endpoints
+///
+/// The Accept header in reqwest can only be set in initial client creation, so the client
+/// always asks for JSON responses which contain the base64 encoded image together with
+/// the seed and finish reason.
+/// Issue: https://github.com/seanmonstar/reqwest/issues/2279
+///
+/// Based on https://platform.stability.ai/docs/api-reference#tag/Generate
+pub struct StabilityApi {
+    api_key: String,
+    client: Client,
+}
+
+impl StabilityApi {
+    pub fn new(api_key: String) -> Self {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            "accept",
+            ACCEPT_HEADER_JSON.parse().expect("Invalid header value"),
+        );
+
+        let client = Client::builder()
+            .default_headers(headers)
+            .build()
+            .expect("Failed to initialize HTTP client");
+
+        Self { api_key, client }
+    }
+
+    /// Generates an image with one of the `core`, `ultra` or `sd3` text-to-image endpoints
+    pub fn generate(
+        &self,
+        endpoint: GenerateEndpoint,
+        request: GenerateImageRequest,
+    ) -> Result<ImageGenerationResponse, Error> {
+        trace!("Sending image generation request to Stability API: {request:?}");
+
+        let mut body = MultipartBody::new();
+        body.text("prompt", &request.prompt);
+        body.optional_text("model", request.model.as_ref());
+        body.optional_text("mode", request.mode.as_ref());
+        body.optional_text("aspect_ratio", request.aspect_ratio.as_ref());
+        body.optional_text("negative_prompt", request.negative_prompt.as_ref());
+        body.optional_text("seed", request.seed);
+        body.optional_text("style_preset", request.style_preset.as_ref());
+        body.optional_text("strength", request.strength);
+        body.text("output_format", &request.output_format);
+        if let Some(image) = &request.image {
+            body.file(
+                "image",
+                &format!("image.{}", file_extension(&image.mime_type)),
+                &image.mime_type,
+                &image.data,
+            );
+        }
+
+        self.send_multipart(endpoint.path(), body)
+    }
+
+    /// Edits an image using the inpaint endpoint, optionally guided by a mask
+    pub fn inpaint(&self, request: InpaintRequest) -> Result<ImageGenerationResponse, Error> {
+        trace!("Sending inpaint request to Stability API");
+
+        let mut body = MultipartBody::new();
+        body.file(
+            "image",
+            &format!("image.{}", file_extension(&request.image.mime_type)),
+            &request.image.mime_type,
+            &request.image.data,
+        );
+        if let Some(mask) = &request.mask {
+            body.file(
+                "mask",
+                &format!("mask.{}", file_extension(&mask.mime_type)),
+                &mask.mime_type,
+                &mask.data,
+            );
+        }
+        body.text("prompt", &request.prompt);
+        body.optional_text("negative_prompt", request.negative_prompt.as_ref());
+        body.optional_text("seed", request.seed);
+        body.optional_text("style_preset", request.style_preset.as_ref());
+        body.optional_text("grow_mask", request.grow_mask);
+        body.text("output_format", &request.output_format);
+
+        self.send_multipart("/v2beta/stable-image/edit/inpaint", body)
+    }
+
+    fn send_multipart(
+        &self,
+        path: &str,
+        body: MultipartBody,
+    ) -> Result<ImageGenerationResponse, Error> {
+        let response: Response = self
+            .client
+            .request(Method::POST, format!("{BASE_URL}{path}"))
+            .header("authorization", format!("Bearer {}", &self.api_key))
+            .header("content-type", body.content_type())
+            .body(body.finish())
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GenerateEndpoint {
+    Core,
+    Ultra,
+    Sd3,
+}
+
+impl GenerateEndpoint {
+    fn path(&self) -> &'static str {
+        match self {
+            GenerateEndpoint::Core => "/v2beta/stable-image/generate/core",
+            GenerateEndpoint::Ultra => "/v2beta/stable-image/generate/ultra",
+            GenerateEndpoint::Sd3 => "/v2beta/stable-image/generate/sd3",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InputFile {
+    pub data: Vec<u8>,
+    pub mime_type: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct GenerateImageRequest {
+    pub prompt: String,
+    pub model: Option<String>,
+    pub mode: Option<String>,
+    pub image: Option<InputFile>,
+    pub strength: Option<f32>,
+    pub aspect_ratio: Option<String>,
+    pub negative_prompt: Option<String>,
+    pub seed: Option<u32>,
+    pub style_preset: Option<String>,
+    pub output_format: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct InpaintRequest {
+    pub image: InputFile,
+    pub mask: Option<InputFile>,
+    pub prompt: String,
+    pub negative_prompt: Option<String>,
+    pub seed: Option<u32>,
+    pub style_preset: Option<String>,
+    pub grow_mask: Option<u32>,
+    pub output_format: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageGenerationResponse {
+    pub image: String,
+    pub seed: Option<u32>,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+fn parse_response(response: Response) -> Result<ImageGenerationResponse, Error> {
+    let status = response.status();
+    if status.is_success() {
+        response
+            .json::<ImageGenerationResponse>()
+            .map_err(|err| from_reqwest_error("Failed to decode response body", err))
+    } else {
+        let body = response
+            .text()
+            .map_err(|err| from_reqwest_error("Failed to receive error response body", err))?;
+
+        trace!("Received {status} response from Stability API: {body:?}");
+
+        let message = if let Ok(error_response) = serde_json::from_str::<ErrorResponse>(&body) {
+            format!(
+                "Request failed with {status}: {} {}",
+                error_response.name,
+                error_response.errors.join(", ")
+            )
+        } else {
+            format!("Request failed with {status}")
+        };
+
+        Err(Error {
+            code: error_code_from_status(status),
+            message,
+            provider_error_json: Some(body),
+        })
+    }
+}
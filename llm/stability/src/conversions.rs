@@ -0,0 +1,398 @@
+use crate::client::{
+    GenerateEndpoint, GenerateImageRequest, ImageGenerationResponse, InpaintRequest, InputFile,
+};
+use base64::{engine::general_purpose, Engine as _};
+use golem_llm::error::unsupported;
+use golem_llm::golem::llm::images::{
+    GeneratedImage, ImageConfig, ImageQuality, ImageResponseFormat, ImageSize,
+};
+use golem_llm::golem::llm::types::{Error, ErrorCode, ImageReference, ImageSource};
+use std::collections::HashMap;
+
+/// Aspect ratios accepted by the Stable Image generate endpoints
+const ASPECT_RATIOS: [(&str, u32, u32); 9] = [
+    ("21:9", 21, 9),
+    ("16:9", 16, 9),
+    ("3:2", 3, 2),
+    ("5:4", 5, 4),
+    ("1:1", 1, 1),
+    ("4:5", 4, 5),
+    ("2:3", 2, 3),
+    ("9:16", 9, 16),
+    ("9:21", 9, 21),
+];
+
+pub fn check_config(config: &ImageConfig) -> Result<HashMap<String, String>, Error> {
+    if config.response_format == Some(ImageResponseFormat::Url) {
+        return Err(unsupported(
+            "Stability image endpoints only return inline image data",
+        ));
+    }
+
+    Ok(config
+        .provider_options
+        .iter()
+        .map(|kv| (kv.key.clone(), kv.value.clone()))
+        .collect())
+}
+
+/// Selects the generate endpoint based on the requested model, falling back to the quality setting
+/// (`core` for standard and `ultra` for high quality) if no model was given.
+pub fn endpoint_and_model(config: &ImageConfig) -> (GenerateEndpoint, Option<String>) {
+    match config.model.as_deref() {
+        Some("core") | Some("stable-image-core") => (GenerateEndpoint::Core, None),
+        Some("ultra") | Some("stable-image-ultra") => (GenerateEndpoint::Ultra, None),
+        Some(model) if model.starts_with("sd3") => (GenerateEndpoint::Sd3, Some(model.to_string())),
+        _ => match config.quality {
+            Some(ImageQuality::High) => (GenerateEndpoint::Ultra, None),
+            _ => (GenerateEndpoint::Core, None),
+        },
+    }
+}
+
+pub fn generate_requests(
+    prompt: String,
+    config: &ImageConfig,
+    options: &HashMap<String, String>,
+) -> Vec<GenerateImageRequest> {
+    let (_, model) = endpoint_and_model(config);
+    seeds(config)
+        .into_iter()
+        .map(|seed| GenerateImageRequest {
+            prompt: prompt.clone(),
+            model: model.clone(),
+            mode: None,
+            image: None,
+            strength: None,
+            aspect_ratio: config.size.as_ref().map(to_aspect_ratio),
+            negative_prompt: config.negative_prompt.clone(),
+            seed,
+            style_preset: config.style.clone(),
+            output_format: output_format(options),
+        })
+        .collect()
+}
+
+/// The inpaint endpoint has a single model and keeps the size of the input image, so neither the
+/// size nor the quality can be chosen
+pub fn inpaint_requests(
+    image: ImageSource,
+    mask: Option<ImageSource>,
+    prompt: String,
+    config: &ImageConfig,
+    options: &HashMap<String, String>,
+) -> Result<Vec<InpaintRequest>, Error> {
+    if config.size.is_some() {
+        return Err(unsupported(
+            "size is not supported for Stability inpainting",
+        ));
+    }
+    if config.quality.is_some() {
+        return Err(unsupported(
+            "quality is not supported for Stability inpainting",
+        ));
+    }
+
+    Ok(seeds(config)
+        .into_iter()
+        .map(|seed| InpaintRequest {
+            image: to_input_file(&image),
+            mask: mask.as_ref().map(to_input_file),
+            prompt: prompt.clone(),
+            negative_prompt: config.negative_prompt.clone(),
+            seed,
+            style_preset: config.style.clone(),
+            grow_mask: options.get("grow_mask").and_then(|v| v.parse::<u32>().ok()),
+            output_format: output_format(options),
+        })
+        .collect())
+}
+
+/// Stability has no dedicated variations endpoint, so variations are created with the SD3
+/// image-to-image mode. This mode requires a prompt, which has to be passed in the `prompt`
+/// provider option.
+pub fn variation_requests(
+    image: ImageSource,
+    config: &ImageConfig,
+    options: &HashMap<String, String>,
+) -> Result<Vec<GenerateImageRequest>, Error> {
+    if config.size.is_some() {
+        return Err(unsupported(
+            "size is not supported for Stability image variations",
+        ));
+    }
+
+    let prompt = options.get("prompt").cloned().ok_or_else(|| Error {
+        code: ErrorCode::InvalidRequest,
+        message: "Stability image variations require a 'prompt' provider option".to_string(),
+        provider_error_json: None,
+    })?;
+    let model = match endpoint_and_model(config) {
+        (GenerateEndpoint::Sd3, model) => model,
+        _ => None,
+    };
+
+    Ok(seeds(config)
+        .into_iter()
+        .map(|seed| GenerateImageRequest {
+            prompt: prompt.clone(),
+            model: model.clone(),
+            mode: Some("image-to-image".to_string()),
+            image: Some(to_input_file(&image)),
+            strength: Some(
+                options
+                    .get("strength")
+                    .and_then(|v| v.parse::<f32>().ok())
+                    .unwrap_or(0.7),
+            ),
+            aspect_ratio: None,
+            negative_prompt: config.negative_prompt.clone(),
+            seed,
+            style_preset: None,
+            output_format: output_format(options),
+        })
+        .collect())
+}
+
+pub fn process_image_response(
+    response: ImageGenerationResponse,
+    output_format: &str,
+) -> Result<GeneratedImage, Error> {
+    if response.finish_reason.as_deref() == Some("CONTENT_FILTERED") {
+        return Err(Error {
+            code: ErrorCode::InvalidRequest,
+            message: "The generated image was filtered by Stability's content moderation"
+                .to_string(),
+            provider_error_json: None,
+        });
+    }
+
+    let data = general_purpose::STANDARD
+        .decode(response.image)
+        .map_err(|err| Error {
+            code: ErrorCode::InternalError,
+            message: format!("Failed to decode base64 image data: {err}"),
+            provider_error_json: None,
+        })?;
+
+    Ok(GeneratedImage {
+        image: ImageReference::Inline(ImageSource {
+            data,
+            mime_type: format!("image/{output_format}"),
+            detail: None,
+        }),
+        revised_prompt: None,
+        seed: response.seed,
+    })
+}
+
+pub fn output_format(options: &HashMap<String, String>) -> String {
+    options
+        .get("output_format")
+        .cloned()
+        .unwrap_or_else(|| "png".to_string())
+}
+
+/// Each request produces a single image. When multiple images are requested with a fixed seed,
+/// consecutive seeds are used so the results differ.
+fn seeds(config: &ImageConfig) -> Vec<Option<u32>> {
+    let count = config.count.unwrap_or(1).max(1);
+    (0..count)
+        .map(|idx| config.seed.map(|seed| seed.wrapping_add(idx)))
+        .collect()
+}
+
+fn to_input_file(source: &ImageSource) -> InputFile {
+    InputFile {
+        data: source.data.clone(),
+        mime_type: source.mime_type.clone(),
+    }
+}
+
+/// Stability works with aspect ratios instead of exact sizes, so the closest supported one is chosen
+fn to_aspect_ratio(size: &ImageSize) -> String {
+    let requested = size.width as f32 / size.height.max(1) as f32;
+    ASPECT_RATIOS
+        .iter()
+        .min_by(|(_, w1, h1), (_, w2, h2)| {
+            let d1 = (*w1 as f32 / *h1 as f32 - requested).abs();
+            let d2 = (*w2 as f32 / *h2 as f32 - requested).abs();
+            d1.total_cmp(&d2)
+        })
+        .map(|(ratio, _, _)| ratio.to_string())
+        .unwrap_or_else(|| "1:1".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use golem_llm::golem::llm::types::Kv;
+
+    fn config() -> ImageConfig {
+        ImageConfig {
+            model: None,
+            size: None,
+            quality: None,
+            style: None,
+            seed: None,
+            count: None,
+            negative_prompt: None,
+            response_format: None,
+            provider_options: vec![],
+        }
+    }
+
+    fn image() -> ImageSource {
+        ImageSource {
+            data: vec![0, 1, 2, 3],
+            mime_type: "image/png".to_string(),
+            detail: None,
+        }
+    }
+
+    #[test]
+    fn endpoint_is_selected_by_model_or_quality() {
+        assert_eq!(
+            endpoint_and_model(&config()),
+            (GenerateEndpoint::Core, None)
+        );
+        assert_eq!(
+            endpoint_and_model(&ImageConfig {
+                quality: Some(ImageQuality::High),
+                ..config()
+            }),
+            (GenerateEndpoint::Ultra, None)
+        );
+        assert_eq!(
+            endpoint_and_model(&ImageConfig {
+                model: Some("sd3.5-large".to_string()),
+                quality: Some(ImageQuality::High),
+                ..config()
+            }),
+            (GenerateEndpoint::Sd3, Some("sd3.5-large".to_string()))
+        );
+    }
+
+    #[test]
+    fn multiple_images_use_consecutive_seeds() {
+        let config = ImageConfig {
+            seed: Some(41),
+            count: Some(3),
+            size: Some(ImageSize {
+                width: 1920,
+                height: 1080,
+            }),
+            ..config()
+        };
+        let requests = generate_requests("A lighthouse".to_string(), &config, &HashMap::new());
+
+        assert_eq!(
+            requests.iter().map(|r| r.seed).collect::<Vec<_>>(),
+            vec![Some(41), Some(42), Some(43)]
+        );
+        assert_eq!(requests[0].aspect_ratio.as_deref(), Some("16:9"));
+        assert_eq!(requests[0].output_format, "png");
+    }
+
+    #[test]
+    fn inpainting_rejects_size_and_quality() {
+        let inpaint = |config: &ImageConfig| {
+            inpaint_requests(
+                image(),
+                None,
+                "A beach".to_string(),
+                config,
+                &HashMap::new(),
+            )
+        };
+
+        assert_eq!(inpaint(&config()).unwrap().len(), 1);
+        assert_eq!(
+            inpaint(&ImageConfig {
+                quality: Some(ImageQuality::High),
+                ..config()
+            })
+            .unwrap_err()
+            .code,
+            ErrorCode::Unsupported
+        );
+        assert_eq!(
+            inpaint(&ImageConfig {
+                size: Some(ImageSize {
+                    width: 512,
+                    height: 512,
+                }),
+                ..config()
+            })
+            .unwrap_err()
+            .code,
+            ErrorCode::Unsupported
+        );
+    }
+
+    #[test]
+    fn variations_require_a_prompt_option() {
+        assert_eq!(
+            variation_requests(image(), &config(), &HashMap::new())
+                .unwrap_err()
+                .code,
+            ErrorCode::InvalidRequest
+        );
+
+        let config = ImageConfig {
+            provider_options: vec![Kv {
+                key: "prompt".to_string(),
+                value: "The same lighthouse at night".to_string(),
+            }],
+            ..config()
+        };
+        let options = check_config(&config).unwrap();
+        let requests = variation_requests(image(), &config, &options).unwrap();
+        assert_eq!(requests[0].mode.as_deref(), Some("image-to-image"));
+        assert_eq!(requests[0].strength, Some(0.7));
+    }
+
+    #[test]
+    fn url_responses_are_rejected() {
+        let config = ImageConfig {
+            response_format: Some(ImageResponseFormat::Url),
+            ..config()
+        };
+        assert_eq!(
+            check_config(&config).unwrap_err().code,
+            ErrorCode::Unsupported
+        );
+    }
+
+    #[test]
+    fn image_responses_are_decoded() {
+        let image = process_image_response(
+            ImageGenerationResponse {
+                image: general_purpose::STANDARD.encode([1, 2, 3]),
+                seed: Some(7),
+                finish_reason: Some("SUCCESS".to_string()),
+            },
+            "webp",
+        )
+        .unwrap();
+        assert_eq!(
+            image.image,
+            ImageReference::Inline(ImageSource {
+                data: vec![1, 2, 3],
+                mime_type: "image/webp".to_string(),
+                detail: None,
+            })
+        );
+        assert_eq!(image.seed, Some(7));
+
+        let filtered = process_image_response(
+            ImageGenerationResponse {
+                image: String::new(),
+                seed: None,
+                finish_reason: Some("CONTENT_FILTERED".to_string()),
+            },
+            "png",
+        );
+        assert_eq!(filtered.unwrap_err().code, ErrorCode::InvalidRequest);
+    }
+}
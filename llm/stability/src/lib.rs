@@ -0,0 +1,104 @@
+mod client;
+mod conversions;
+
+use crate::client::StabilityApi;
+use crate::conversions::{
+    check_config, endpoint_and_model, generate_requests, inpaint_requests, output_format,
+    process_image_response, variation_requests,
+};
+use golem_llm::config::with_config_key;
+use golem_llm::durability::DurableLLM;
+use golem_llm::golem::llm::images::{Guest as ImagesGuest, ImageConfig, ImageResponse};
+use golem_llm::golem::llm::moderation::{
+    Guest as ModerationGuest, ModerationConfig, ModerationResponse,
+};
+use golem_llm::golem::llm::types::{Error, ImageSource};
+use golem_llm::guardrails::moderate_with_local_policy;
+
+struct StabilityComponent;
+
+impl StabilityComponent {
+    const ENV_VAR_NAME: &'static str = "STABILITY_API_KEY";
+}
+
+impl ImagesGuest for StabilityComponent {
+    fn generate(prompt: String, config: ImageConfig) -> Result<ImageResponse, Error> {
+        with_config_key(Self::ENV_VAR_NAME, Err, |api_key| {
+            let client = StabilityApi::new(api_key);
+
+            let options = check_config(&config)?;
+            let (endpoint, _) = endpoint_and_model(&config);
+            let format = output_format(&options);
+            let mut images = Vec::new();
+            for request in generate_requests(prompt, &config, &options) {
+                let response = client.generate(endpoint, request)?;
+                images.push(process_image_response(response, &format)?);
+            }
+            Ok(ImageResponse {
+                images,
+                provider_id: None,
+                timestamp: None,
+                provider_metadata_json: None,
+            })
+        })
+    }
+
+    fn edit(
+        image: ImageSource,
+        mask: Option<ImageSource>,
+        prompt: String,
+        config: ImageConfig,
+    ) -> Result<ImageResponse, Error> {
+        with_config_key(Self::ENV_VAR_NAME, Err, |api_key| {
+            let client = StabilityApi::new(api_key);
+
+            let options = check_config(&config)?;
+            let format = output_format(&options);
+            let mut images = Vec::new();
+            for request in inpaint_requests(image, mask, prompt, &config, &options)? {
+                let response = client.inpaint(request)?;
+                images.push(process_image_response(response, &format)?);
+            }
+            Ok(ImageResponse {
+                images,
+                provider_id: None,
+                timestamp: None,
+                provider_metadata_json: None,
+            })
+        })
+    }
+
+    fn create_variations(image: ImageSource, config: ImageConfig) -> Result<ImageResponse, Error> {
+        with_config_key(Self::ENV_VAR_NAME, Err, |api_key| {
+            let client = StabilityApi::new(api_key);
+
+            let options = check_config(&config)?;
+            let format = output_format(&options);
+            let mut images = Vec::new();
+            for request in variation_requests(image, &config, &options)? {
+                let response = client.generate(client::GenerateEndpoint::Sd3, request)?;
+                images.push(process_image_response(response, &format)?);
+            }
+            Ok(ImageResponse {
+                images,
+                provider_id: None,
+                timestamp: None,
+                provider_metadata_json: None,
+            })
+        })
+    }
+}
+
+impl ModerationGuest for StabilityComponent {
+    fn moderate(
+        inputs: Vec<String>,
+        _config: ModerationConfig,
+    ) -> Result<ModerationResponse, Error> {
+        moderate_with_local_policy(inputs)
+    }
+}
+
+type DurableStabilityComponent = DurableLLM<StabilityComponent>;
+
+golem_llm::export_images!(DurableStabilityComponent with_types_in golem_llm::images_library);
+golem_llm::export_moderation!(DurableStabilityComponent with_types_in golem_llm::moderation_library);
@@ -0,0 +1,190 @@
+use golem_llm::error::{error_code_from_status, from_reqwest_error};
+use golem_llm::golem::llm::types::{Error, ErrorCode};
+use golem_llm::multipart::MultipartBody;
+use log::trace;
+use reqwest::{Client, Method, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+/// Client for the HTTP server example shipped with whisper.cpp
+///
+/// Based on https://github.com/ggml-org/whisper.cpp/tree/master/examples/server
+pub struct WhisperApi {
+    base_url: String,
+    client: Client,
+}
+
+impl WhisperApi {
+    pub fn new(base_url: String) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+        Self { base_url, client }
+    }
+
+    pub fn inference(&self, body: MultipartBody) -> Result<InferenceResponse, Error> {
+        trace!("Sending inference request to whisper.cpp server");
+
+        let response: Response = self
+            .client
+            .request(Method::POST, format!("{}/inference", self.base_url))
+            .header(reqwest::header::CONTENT_TYPE, body.content_type())
+            .body(body.finish())
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+}
+
+/// Client for a local text-to-speech server exposing the OpenAI compatible `/v1/audio/speech`
+/// endpoint, such as Kokoro-FastAPI or openedai-speech.
+pub struct SpeechApi {
+    base_url: String,
+    client: Client,
+}
+
+impl SpeechApi {
+    pub fn new(base_url: String) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to initialize HTTP client");
+        Self { base_url, client }
+    }
+
+    pub fn create_speech(&self, request: CreateSpeechRequest) -> Result<Vec<u8>, Error> {
+        trace!("Sending speech request to local TTS server: {request:?}");
+
+        let response: Response = self
+            .client
+            .request(Method::POST, format!("{}/v1/audio/speech", self.base_url))
+            .json(&request)
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        let status = response.status();
+        if status.is_success() {
+            let bytes = response
+                .bytes()
+                .map_err(|err| from_reqwest_error("Failed to read audio data", err))?;
+            Ok(bytes.to_vec())
+        } else {
+            parse_response(response)
+        }
+    }
+
+    pub fn list_voices(&self) -> Result<VoicesResponse, Error> {
+        trace!("Listing voices of local TTS server");
+
+        let response: Response = self
+            .client
+            .request(Method::GET, format!("{}/v1/audio/voices", self.base_url))
+            .send()
+            .map_err(|err| from_reqwest_error("Request failed", err))?;
+
+        parse_response(response)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferenceResponse {
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f32>,
+    #[serde(default)]
+    pub segments: Vec<InferenceSegment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferenceSegment {
+    pub id: u32,
+    pub text: String,
+    #[serde(default)]
+    pub start: f32,
+    #[serde(default)]
+    pub end: f32,
+    #[serde(default)]
+    pub words: Vec<InferenceWord>,
+    /// Set when the server runs with stereo diarization enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<serde_json::Value>,
+    /// Set when the server runs with a tinydiarize model
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker_turn_next: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferenceWord {
+    pub word: String,
+    #[serde(default)]
+    pub start: f32,
+    #[serde(default)]
+    pub end: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateSpeechRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub input: String,
+    pub voice: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoicesResponse {
+    pub voices: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, Error> {
+    let status = response.status();
+    let body = response
+        .text()
+        .map_err(|err| from_reqwest_error("Failed to receive response body", err))?;
+
+    // The whisper.cpp server reports some failures with a 200 status and an `error` field
+    if let Ok(error) = serde_json::from_str::<ErrorResponse>(&body) {
+        trace!("Received error response from whisper.cpp server: {body:?}");
+
+        return Err(Error {
+            code: error_code_from_status(if status.is_success() {
+                reqwest::StatusCode::BAD_REQUEST
+            } else {
+                status
+            }),
+            message: format!("Request failed: {}", error.error),
+            provider_error_json: Some(body),
+        });
+    }
+
+    if status.is_success() {
+        let parsed = serde_json::from_str::<T>(&body).map_err(|err| Error {
+            code: ErrorCode::InternalError,
+            message: format!("Failed to decode response body: {err}"),
+            provider_error_json: Some(body),
+        })?;
+
+        trace!("Received response from whisper.cpp server: {parsed:?}");
+
+        Ok(parsed)
+    } else {
+        trace!("Received {status} response from local server: {body:?}");
+
+        Err(Error {
+            code: error_code_from_status(status),
+            message: format!("Request failed with {status}"),
+            provider_error_json: Some(body),
+        })
+    }
+}
@@ -0,0 +1,140 @@
+use crate::client::{CreateSpeechRequest, InferenceResponse, VoicesResponse};
+use golem_llm::error::unsupported;
+use golem_llm::golem::llm::speech::{AudioFormat, SpeechConfig, SpeechResult, VoiceInfo};
+use golem_llm::golem::llm::transcription::{
+    AudioSource, TimestampGranularity, TranscriptionConfig, TranscriptionResult,
+    TranscriptionSegment, TranscriptionWord,
+};
+use golem_llm::golem::llm::types::Error;
+use golem_llm::multipart::{
+    audio_format_mime_type, audio_format_name, file_extension, MultipartBody,
+};
+
+pub fn create_inference_body(
+    audio: AudioSource,
+    config: TranscriptionConfig,
+) -> Result<MultipartBody, Error> {
+    if config.model.is_some() {
+        return Err(unsupported(
+            "the whisper.cpp server uses the model it was started with",
+        ));
+    }
+
+    let mut body = MultipartBody::new();
+    body.file(
+        "file",
+        &format!("audio.{}", file_extension(&audio.mime_type)),
+        &audio.mime_type,
+        &audio.data,
+    );
+    body.text("response_format", "verbose_json");
+    body.optional_text("language", config.language);
+    body.optional_text("prompt", config.prompt);
+    body.optional_text("temperature", config.temperature);
+    if config.diarization {
+        body.text("diarize", "true");
+    }
+    if config.timestamp_granularities.is_empty() {
+        body.text("no_timestamps", "true");
+    }
+    for kv in config.provider_options {
+        body.text(&kv.key, kv.value);
+    }
+    Ok(body)
+}
+
+pub fn process_inference_response(
+    response: InferenceResponse,
+    config_granularities: &[TimestampGranularity],
+) -> TranscriptionResult {
+    let include_segments = config_granularities.contains(&TimestampGranularity::Segment);
+    let include_words = config_granularities.contains(&TimestampGranularity::Word);
+
+    let mut segments = Vec::new();
+    let mut words = Vec::new();
+    let mut speaker_turn = 0;
+
+    for segment in response.segments {
+        let speaker = match (&segment.speaker, segment.speaker_turn_next) {
+            (Some(serde_json::Value::String(speaker)), _) => Some(speaker.clone()),
+            (Some(speaker), _) => Some(speaker.to_string()),
+            (None, Some(_)) => Some(speaker_turn.to_string()),
+            (None, None) => None,
+        };
+        if segment.speaker_turn_next == Some(true) {
+            speaker_turn += 1;
+        }
+
+        if include_words {
+            for word in &segment.words {
+                words.push(TranscriptionWord {
+                    text: word.word.clone(),
+                    start: word.start,
+                    end: word.end,
+                    speaker: speaker.clone(),
+                });
+            }
+        }
+
+        if include_segments {
+            segments.push(TranscriptionSegment {
+                id: segment.id,
+                text: segment.text.trim().to_string(),
+                start: segment.start,
+                end: segment.end,
+                speaker,
+            });
+        }
+    }
+
+    TranscriptionResult {
+        text: response.text.trim().to_string(),
+        language: response.language,
+        duration: response.duration,
+        segments,
+        words,
+        provider_metadata_json: None,
+    }
+}
+
+pub fn create_speech_request(
+    text: String,
+    config: SpeechConfig,
+) -> Result<CreateSpeechRequest, Error> {
+    if config.instructions.is_some() {
+        return Err(unsupported(
+            "instructions are not supported by the local speech server",
+        ));
+    }
+
+    Ok(CreateSpeechRequest {
+        model: config.model,
+        input: text,
+        voice: config.voice,
+        response_format: config
+            .format
+            .map(|format| audio_format_name(format).to_string()),
+        speed: config.speed,
+    })
+}
+
+pub fn process_speech_response(data: Vec<u8>, config_format: Option<AudioFormat>) -> SpeechResult {
+    SpeechResult {
+        data,
+        mime_type: audio_format_mime_type(config_format.unwrap_or(AudioFormat::Mp3)).to_string(),
+        provider_metadata_json: None,
+    }
+}
+
+pub fn process_voices_response(response: VoicesResponse) -> Vec<VoiceInfo> {
+    response
+        .voices
+        .into_iter()
+        .map(|voice| VoiceInfo {
+            id: voice.clone(),
+            name: voice,
+            language: None,
+            description: None,
+        })
+        .collect()
+}
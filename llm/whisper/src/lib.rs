@@ -0,0 +1,93 @@
+mod client;
+mod conversions;
+
+use crate::client::{SpeechApi, WhisperApi};
+use crate::conversions::{
+    create_inference_body, create_speech_request, process_inference_response,
+    process_speech_response, process_voices_response,
+};
+use golem_llm::config::get_config_key_or_none;
+use golem_llm::durability::DurableLLM;
+use golem_llm::error::unsupported;
+use golem_llm::golem::llm::moderation::{
+    Guest as ModerationGuest, ModerationConfig, ModerationResponse,
+};
+use golem_llm::golem::llm::speech::{Guest as SpeechGuest, SpeechConfig, SpeechResult, VoiceInfo};
+use golem_llm::golem::llm::transcription::{
+    AudioSource, Guest as TranscriptionGuest, TranscriptionConfig, TranscriptionResult,
+};
+use golem_llm::golem::llm::types::Error;
+use golem_llm::guardrails::moderate_with_local_policy;
+
+struct WhisperComponent;
+
+impl WhisperComponent {
+    const BASE_URL_ENV_VAR_NAME: &'static str = "GOLEM_WHISPER_BASE_URL";
+    const TTS_BASE_URL_ENV_VAR_NAME: &'static str = "GOLEM_WHISPER_TTS_BASE_URL";
+
+    fn whisper_api() -> WhisperApi {
+        let base_url = get_config_key_or_none(Self::BASE_URL_ENV_VAR_NAME)
+            .unwrap_or("http://localhost:8080".to_string());
+        WhisperApi::new(base_url)
+    }
+
+    /// Speech synthesis is only available when a local TTS server is configured
+    fn speech_api() -> Result<SpeechApi, Error> {
+        get_config_key_or_none(Self::TTS_BASE_URL_ENV_VAR_NAME)
+            .map(SpeechApi::new)
+            .ok_or_else(|| {
+                unsupported(format!(
+                    "speech synthesis requires {} to point to a local TTS server",
+                    Self::TTS_BASE_URL_ENV_VAR_NAME
+                ))
+            })
+    }
+}
+
+impl TranscriptionGuest for WhisperComponent {
+    fn transcribe(
+        audio: AudioSource,
+        config: TranscriptionConfig,
+    ) -> Result<TranscriptionResult, Error> {
+        let client = Self::whisper_api();
+
+        let granularities = config.timestamp_granularities.clone();
+        let body = create_inference_body(audio, config)?;
+        let response = client.inference(body)?;
+        Ok(process_inference_response(response, &granularities))
+    }
+}
+
+impl SpeechGuest for WhisperComponent {
+    fn synthesize(text: String, config: SpeechConfig) -> Result<SpeechResult, Error> {
+        let client = Self::speech_api()?;
+
+        let format = config.format;
+        let request = create_speech_request(text, config)?;
+        let data = client.create_speech(request)?;
+        Ok(process_speech_response(data, format))
+    }
+
+    fn list_voices(_language: Option<String>) -> Result<Vec<VoiceInfo>, Error> {
+        let client = Self::speech_api()?;
+
+        // Local TTS servers only report voice names, so there is nothing to filter by language
+        let response = client.list_voices()?;
+        Ok(process_voices_response(response))
+    }
+}
+
+impl ModerationGuest for WhisperComponent {
+    fn moderate(
+        inputs: Vec<String>,
+        _config: ModerationConfig,
+    ) -> Result<ModerationResponse, Error> {
+        moderate_with_local_policy(inputs)
+    }
+}
+
+type DurableWhisperComponent = DurableLLM<WhisperComponent>;
+
+golem_llm::export_transcription!(DurableWhisperComponent with_types_in golem_llm::transcription_library);
+golem_llm::export_speech!(DurableWhisperComponent with_types_in golem_llm::speech_library);
+golem_llm::export_moderation!(DurableWhisperComponent with_types_in golem_llm::moderation_library);
@@ -66,6 +66,20 @@ pub struct IndexSettings {
         default
     )]
     pub replicas: Vec<String>,
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        rename = "indexLanguages",
+        deserialize_with = "deserialize_nullable_vec",
+        default
+    )]
+    pub index_languages: Vec<String>,
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        rename = "queryLanguages",
+        deserialize_with = "deserialize_nullable_vec",
+        default
+    )]
+    pub query_languages: Vec<String>,
 }
 
 /// The Algolia Search API client for managing indices and performing search
@@ -118,6 +132,28 @@ impl AlgoliaSearchApi {
         parse_response(response)
     }
 
+    /// Moves the index to the destination, replacing the destination atomically
+    pub fn move_index(
+        &self,
+        index_name: &str,
+        destination: &str,
+    ) -> Result<OperationIndexResponse, SearchError> {
+        trace!("Moving index {index_name} to {destination}");
+
+        let url = format!("{}/1/indexes/{}/operation", self.write_url, index_name);
+
+        let response = self
+            .create_request(Method::POST, &url)
+            .json(&OperationIndexRequest {
+                operation: "move".to_string(),
+                destination: destination.to_string(),
+            })
+            .send()
+            .map_err(|e| internal_error(format!("Failed to move index: {e}")))?;
+
+        parse_response(response)
+    }
+
     pub fn list_indexes(&self) -> Result<ListIndexesResponse, SearchError> {
         trace!("Listing indexes");
 
@@ -270,6 +306,28 @@ impl AlgoliaSearchApi {
         }
     }
 
+    /// Browses the records matching the query, page by page. The first page is requested with
+    /// the query, and the next ones with the cursor returned by the previous page.
+    pub fn browse(
+        &self,
+        index_name: &str,
+        request: &BrowseRequest,
+    ) -> Result<BrowseResponse, SearchError> {
+        trace!("Browsing index {index_name} with request: {request:?}");
+
+        let url = format!("{}/1/indexes/{}/browse", self.search_url, index_name);
+
+        let response = self.create_request(Method::POST, &url).json(request).send();
+
+        match response {
+            Ok(resp) => parse_response(resp),
+            Err(e) => {
+                let error_msg = format!("Failed to browse: {url}: {e}");
+                Err(internal_error(error_msg))
+            }
+        }
+    }
+
     pub fn get_settings(&self, index_name: &str) -> Result<IndexSettings, SearchError> {
         trace!("Getting settings for index: {index_name}");
 
@@ -301,37 +359,29 @@ impl AlgoliaSearchApi {
         parse_response(response)
     }
 
-    pub fn _wait_for_task(&self, index_name: &str, task_id: u64) -> Result<(), SearchError> {
-        trace!("Waiting for task {task_id} on index {index_name}");
+    pub fn get_task(&self, index_name: &str, task_id: u64) -> Result<TaskResponse, SearchError> {
+        trace!("Getting task {task_id} of index {index_name}");
+
         let url = format!(
             "{}/1/indexes/{}/task/{}",
             self.write_url, index_name, task_id
         );
 
+        let response = self
+            .create_request(Method::GET, &url)
+            .send()
+            .map_err(|e| internal_error(format!("Failed to get task: {e}")))?;
+
+        parse_response(response)
+    }
+
+    pub fn wait_for_task(&self, index_name: &str, task_id: u64) -> Result<(), SearchError> {
+        trace!("Waiting for task {task_id} on index {index_name}");
+
         for _ in 0..20 {
             // Poll for up to 10 seconds
-            let response = self.create_request(Method::GET, &url).send();
-            match response {
-                Ok(resp) => {
-                    let body_str = match resp.text() {
-                        Ok(s) => s,
-                        Err(_e) => {
-                            continue;
-                        }
-                    };
-                    let body: serde_json::Value = match serde_json::from_str(&body_str) {
-                        Ok(b) => b,
-                        Err(_e) => {
-                            continue;
-                        }
-                    };
-                    if body.get("status").and_then(|s| s.as_str()) == Some("published") {
-                        return Ok(());
-                    }
-                }
-                Err(e) => {
-                    eprintln!("[Algolia] Error waiting for task: {e:?}");
-                }
+            if self.get_task(index_name, task_id)?.status == "published" {
+                return Ok(());
             }
             std::thread::sleep(std::time::Duration::from_millis(500));
         }
@@ -357,6 +407,15 @@ pub struct SearchQuery {
     pub filters: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub numeric_filters: Option<serde_json::Value>,
+    #[serde(rename = "aroundLatLng")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub around_lat_lng: Option<String>,
+    #[serde(rename = "aroundRadius")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub around_radius: Option<u32>,
+    #[serde(rename = "insideBoundingBox")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inside_bounding_box: Option<Vec<[f64; 4]>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub page: Option<u32>,
     #[serde(rename = "hitsPerPage")]
@@ -368,6 +427,21 @@ pub struct SearchQuery {
     pub length: Option<u32>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub facets: Vec<String>,
+    #[serde(rename = "maxValuesPerFacet")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_values_per_facet: Option<u32>,
+    #[serde(rename = "attributesToHighlight")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attributes_to_highlight: Vec<String>,
+    #[serde(rename = "attributesToSnippet")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attributes_to_snippet: Vec<String>,
+    #[serde(rename = "highlightPreTag")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlight_pre_tag: Option<String>,
+    #[serde(rename = "highlightPostTag")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlight_post_tag: Option<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub attributes_to_retrieve: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -400,6 +474,22 @@ pub struct SearchResponse {
     pub params: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BrowseRequest {
+    Query(SearchQuery),
+    Cursor { cursor: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowseResponse {
+    #[serde(flatten)]
+    pub response: SearchResponse,
+    /// Absent on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchHit {
     #[serde(rename = "objectID")]
@@ -445,6 +535,20 @@ pub struct DeleteIndexResponse {
     pub deleted_at: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationIndexRequest {
+    pub operation: String,
+    pub destination: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationIndexResponse {
+    #[serde(rename = "taskID")]
+    pub task_id: u64,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ListIndexesResponse {
     pub items: Vec<IndexInfo>,
@@ -514,6 +618,14 @@ pub struct SetSettingsResponse {
     pub task_id: u64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskResponse {
+    /// `published` once the task is applied, `notPublished` before
+    pub status: String,
+    #[serde(rename = "pendingTask", default)]
+    pub pending_task: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BatchRequest {
     pub requests: Vec<BatchOperation>,
@@ -1,10 +1,21 @@
 use crate::client::{
-    AlgoliaObject, IndexSettings, SearchHit as AlgoliaSearchHit, SearchQuery as AlgoliaSearchQuery,
-    SearchResponse,
+    AlgoliaObject, BrowseRequest, IndexSettings, SearchHit as AlgoliaSearchHit,
+    SearchQuery as AlgoliaSearchQuery, SearchResponse, TaskResponse,
 };
+use golem_search::error::{invalid_query, unsupported};
+use golem_search::facets::{
+    facet_field, facet_requests, terms_facet_result, unsupported_facet, DEFAULT_FACET_SIZE,
+};
+use golem_search::filter::{resolve_filter, unsupported_filter, Filter};
 use golem_search::golem::search::types::{
-    Doc, FieldType, Schema, SchemaField, SearchHit, SearchQuery, SearchResults,
+    Doc, FacetBucket, FacetRequest, FacetResult, FacetStats, FieldHighlight, FieldType,
+    FilterValue, HighlightConfig, Schema, SchemaChangeKind, SchemaField, SearchError, SearchHit,
+    SearchQuery, SearchResults, TaskStatus,
+};
+use golem_search::highlights::{
+    field_highlight, highlight_tags, validate_highlight_config, DEFAULT_SNIPPET_LENGTH,
 };
+use golem_search::schema::{impossible, in_place, validate_field, Classification, SchemaDiff};
 use serde_json::{Map, Value};
 
 pub fn doc_to_algolia_object(doc: Doc) -> Result<AlgoliaObject, String> {
@@ -25,25 +36,63 @@ pub fn algolia_object_to_doc(obj: AlgoliaObject) -> Doc {
     }
 }
 
-pub fn search_query_to_algolia_query(query: SearchQuery) -> AlgoliaSearchQuery {
+pub fn search_query_to_algolia_query(
+    query: SearchQuery,
+) -> Result<AlgoliaSearchQuery, SearchError> {
+    let facets = facet_requests(&query)?;
     let mut algolia_query = AlgoliaSearchQuery {
         query: query.q,
         filters: None,
         numeric_filters: None,
+        around_lat_lng: None,
+        around_radius: None,
+        inside_bounding_box: None,
         page: query.page,
         hits_per_page: query.per_page,
         offset: query.offset,
         length: None,
-        facets: query.facets,
+        facets: vec![],
+        max_values_per_facet: None,
+        attributes_to_highlight: vec![],
+        attributes_to_snippet: vec![],
+        highlight_pre_tag: None,
+        highlight_post_tag: None,
         attributes_to_retrieve: vec![],
         typo_tolerance: None,
         analytics: Some(false),
     };
 
+    // NeuralSearch computes its own embeddings, query vectors can't be sent to Algolia
+    if query.vector.is_some() {
+        return Err(unsupported("vector search"));
+    }
+
+    // Algolia returns the values with the highest counts, and the stats of numeric facets
+    for facet in &facets {
+        let size = match facet {
+            FacetRequest::Terms(terms) => terms.size.unwrap_or(DEFAULT_FACET_SIZE),
+            FacetRequest::Stats(_) => DEFAULT_FACET_SIZE,
+            FacetRequest::Range(_) | FacetRequest::DateHistogram(_) => {
+                return Err(unsupported_facet(facet, "Algolia"));
+            }
+        };
+        algolia_query.max_values_per_facet =
+            Some(algolia_query.max_values_per_facet.unwrap_or(0).max(size));
+        let field = facet_field(facet).to_string();
+        if !algolia_query.facets.contains(&field) {
+            algolia_query.facets.push(field);
+        }
+    }
+
     // Handle filters - Algolia uses the filters field for general attribute filtering
-    if !query.filters.is_empty() {
-        // Each filter should be in the format "attribute:value" or "attribute>value", etc.
-        algolia_query.filters = Some(query.filters.join(" AND "));
+    // Each filter should be in the format "attribute:value" or "attribute>value", etc.
+    let mut filters = query.filters;
+    if let Some(expression) = &query.filter {
+        let filter = resolve_filter(expression)?;
+        filters.extend(apply_filter_to_algolia_query(&filter, &mut algolia_query)?);
+    }
+    if !filters.is_empty() {
+        algolia_query.filters = Some(filters.join(" AND "));
     }
 
     // Handle sort - convert to Algolia's ranking format
@@ -52,12 +101,19 @@ pub fn search_query_to_algolia_query(query: SearchQuery) -> AlgoliaSearchQuery {
         // For now, we'll include this in the provider params if available
     }
 
-    // Note: Algolia handles highlighting automatically in the index settings
-    // and returns _highlightResult in search responses. Query-level highlight
-    // parameters are not supported in the search API.
-    if let Some(_highlight) = query.highlight {
-        // Highlighting configuration would need to be set at the index level
-        // For now, we acknowledge but ignore highlight settings
+    // Without highlighted fields, the searchable attributes of the index are highlighted
+    if let Some(highlight) = query.highlight {
+        validate_highlight_config(&highlight)?;
+        let (pre_tag, post_tag) = highlight_tags(&highlight);
+        algolia_query.highlight_pre_tag = Some(pre_tag.to_string());
+        algolia_query.highlight_post_tag = Some(post_tag.to_string());
+        algolia_query.attributes_to_highlight = highlight.fields.clone();
+        let snippet_length = highlight.snippet_length.unwrap_or(DEFAULT_SNIPPET_LENGTH);
+        algolia_query.attributes_to_snippet = highlight
+            .snippet_fields
+            .iter()
+            .map(|field| format!("{field}:{snippet_length}"))
+            .collect();
     }
 
     if let Some(config) = query.config {
@@ -79,14 +135,228 @@ pub fn search_query_to_algolia_query(query: SearchQuery) -> AlgoliaSearchQuery {
         }
     }
 
-    algolia_query
+    Ok(algolia_query)
+}
+
+/// Compiles a provider-neutral filter for Algolia. Geo filters become the `aroundLatLng` and
+/// `insideBoundingBox` parameters, so they must be at the top level of the filter. The returned
+/// filter strings are combined with AND.
+pub fn apply_filter_to_algolia_query(
+    filter: &Filter,
+    algolia_query: &mut AlgoliaSearchQuery,
+) -> Result<Option<String>, SearchError> {
+    let conjuncts = match filter {
+        Filter::And(filters) => filters.iter().collect::<Vec<_>>(),
+        filter => vec![filter],
+    };
+
+    let mut filters = Vec::new();
+    for conjunct in conjuncts {
+        match conjunct {
+            Filter::GeoRadius(radius) => {
+                check_geo_field(&radius.field)?;
+                if algolia_query.around_lat_lng.is_some() {
+                    return Err(invalid_query("Algolia supports a single geo-radius filter"));
+                }
+                algolia_query.around_lat_lng =
+                    Some(format!("{},{}", radius.center.lat, radius.center.lon));
+                algolia_query.around_radius = Some(radius.radius_meters.ceil() as u32);
+            }
+            Filter::GeoBoundingBox(bbox) => {
+                check_geo_field(&bbox.field)?;
+                algolia_query
+                    .inside_bounding_box
+                    .get_or_insert_with(Vec::new)
+                    .push([
+                        bbox.top_left.lat,
+                        bbox.top_left.lon,
+                        bbox.bottom_right.lat,
+                        bbox.bottom_right.lon,
+                    ]);
+            }
+            filter => filters.push(filter_to_algolia(filter, false)?),
+        }
+    }
+
+    Ok(if filters.is_empty() {
+        None
+    } else {
+        Some(filters.join(" AND "))
+    })
+}
+
+/// Algolia filters can't nest AND or negations inside OR, and only compare numbers with ranges
+fn filter_to_algolia(filter: &Filter, in_or: bool) -> Result<String, SearchError> {
+    match filter {
+        Filter::Eq(field, value) => Ok(algolia_comparison(field, value, false)),
+        Filter::Ne(field, value) => {
+            check_not_in_or(filter, in_or)?;
+            Ok(algolia_comparison(field, value, true))
+        }
+        Filter::Range(field, bounds) => {
+            let mut conditions = Vec::new();
+            for (operator, bound) in [
+                (">", &bounds.gt),
+                (">=", &bounds.gte),
+                ("<", &bounds.lt),
+                ("<=", &bounds.lte),
+            ] {
+                match bound {
+                    Some(FilterValue::Integer(integer)) => {
+                        conditions.push(format!("{field} {operator} {integer}"))
+                    }
+                    Some(FilterValue::Float(float)) => {
+                        conditions.push(format!("{field} {operator} {float}"))
+                    }
+                    Some(_) => {
+                        return Err(invalid_query(format!(
+                        "Algolia only supports numeric ranges, the range on {field} is not numeric"
+                    )))
+                    }
+                    None => {}
+                }
+            }
+            if conditions.len() > 1 && in_or {
+                return Err(invalid_query(format!(
+                    "Algolia can't combine the bounds of the range on {field} inside an OR"
+                )));
+            }
+            Ok(conditions.join(" AND "))
+        }
+        Filter::In(field, values) => {
+            let values = values
+                .iter()
+                .map(|value| algolia_comparison(field, value, false))
+                .collect::<Vec<_>>()
+                .join(" OR ");
+            Ok(if in_or { values } else { format!("({values})") })
+        }
+        Filter::And(filters) => {
+            if in_or {
+                return Err(invalid_query("Algolia can't combine AND inside an OR"));
+            }
+            Ok(filters
+                .iter()
+                .map(|filter| filter_to_algolia(filter, false))
+                .collect::<Result<Vec<_>, _>>()?
+                .join(" AND "))
+        }
+        Filter::Or(filters) => {
+            let filters = filters
+                .iter()
+                .map(|filter| filter_to_algolia(filter, true))
+                .collect::<Result<Vec<_>, _>>()?
+                .join(" OR ");
+            Ok(if in_or {
+                filters
+            } else {
+                format!("({filters})")
+            })
+        }
+        Filter::Not(negated) => {
+            check_not_in_or(filter, in_or)?;
+            match negated.as_ref() {
+                Filter::Eq(field, value) => Ok(algolia_comparison(field, value, true)),
+                Filter::Ne(field, value) => Ok(algolia_comparison(field, value, false)),
+                _ => Err(invalid_query(format!(
+                    "Algolia can only negate eq and ne filters, not {}",
+                    negated.operator()
+                ))),
+            }
+        }
+        Filter::GeoRadius(..) | Filter::GeoBoundingBox(..) => Err(invalid_query(format!(
+            "Algolia only supports {} filters at the top level of the filter",
+            filter.operator()
+        ))),
+        Filter::Exists(..) | Filter::Prefix(..) => Err(unsupported_filter(filter, "Algolia")),
+    }
+}
+
+fn algolia_comparison(field: &str, value: &FilterValue, negated: bool) -> String {
+    let operator = if negated { "!=" } else { "=" };
+    let facet_value = match value {
+        FilterValue::Integer(integer) => return format!("{field} {operator} {integer}"),
+        FilterValue::Float(float) => return format!("{field} {operator} {float}"),
+        FilterValue::Text(text) => {
+            format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+        }
+        FilterValue::Boolean(boolean) => boolean.to_string(),
+    };
+    if negated {
+        format!("NOT {field}:{facet_value}")
+    } else {
+        format!("{field}:{facet_value}")
+    }
 }
 
-pub fn algolia_response_to_search_results(response: SearchResponse) -> SearchResults {
+fn check_not_in_or(filter: &Filter, in_or: bool) -> Result<(), SearchError> {
+    if in_or {
+        Err(invalid_query(format!(
+            "Algolia can't use the {} filter inside an OR",
+            filter.operator()
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn check_geo_field(field: &str) -> Result<(), SearchError> {
+    if field == "_geoloc" {
+        Ok(())
+    } else {
+        Err(invalid_query(format!(
+            "Algolia only supports geo filters on the _geoloc attribute, not on {field}"
+        )))
+    }
+}
+
+pub fn algolia_response_to_search_results(
+    response: SearchResponse,
+    facets: &[FacetRequest],
+    highlight: Option<&HighlightConfig>,
+) -> SearchResults {
     let hits = response
         .hits
         .into_iter()
-        .map(algolia_hit_to_search_hit)
+        .map(|hit| algolia_hit_to_search_hit(hit, highlight))
+        .collect();
+
+    let facet_results = facets
+        .iter()
+        .filter_map(|facet| match facet {
+            FacetRequest::Terms(terms) => {
+                let buckets = response
+                    .facets
+                    .as_ref()
+                    .and_then(|facets| facets.get(&terms.field)?.as_object())
+                    .into_iter()
+                    .flatten()
+                    .map(|(value, count)| FacetBucket {
+                        value: value.clone(),
+                        count: count.as_u64().unwrap_or(0),
+                        range: None,
+                    })
+                    .collect();
+                Some(terms_facet_result(terms, buckets, None))
+            }
+            FacetRequest::Stats(field) => Some(FacetResult {
+                field: field.clone(),
+                buckets: vec![],
+                stats: response
+                    .facets_stats
+                    .as_ref()
+                    .and_then(|stats| stats.get(field))
+                    .map(|stats| FacetStats {
+                        count: None,
+                        min: stats.get("min").and_then(|m| m.as_f64()),
+                        max: stats.get("max").and_then(|m| m.as_f64()),
+                        avg: stats.get("avg").and_then(|a| a.as_f64()),
+                        sum: stats.get("sum").and_then(|s| s.as_f64()),
+                    }),
+            }),
+            // Rejected when building the query
+            FacetRequest::Range(_) | FacetRequest::DateHistogram(_) => None,
+        })
         .collect();
 
     SearchResults {
@@ -97,11 +367,19 @@ pub fn algolia_response_to_search_results(response: SearchResponse) -> SearchRes
         facets: response
             .facets
             .map(|f| serde_json::to_string(&f).unwrap_or_default()),
+        facet_results,
         took_ms: Some(response.processing_time_ms),
     }
 }
 
-pub fn algolia_hit_to_search_hit(hit: AlgoliaSearchHit) -> SearchHit {
+pub fn algolia_hit_to_search_hit(
+    hit: AlgoliaSearchHit,
+    highlight: Option<&HighlightConfig>,
+) -> SearchHit {
+    let highlight_fields = highlight
+        .map(|config| algolia_results_to_field_highlights(&hit, config))
+        .unwrap_or_default();
+
     let highlights = hit
         .highlight_result
         .map(|h| serde_json::to_string(&h).unwrap_or_default());
@@ -113,13 +391,92 @@ pub fn algolia_hit_to_search_hit(hit: AlgoliaSearchHit) -> SearchHit {
         score,
         content: Some(serde_json::to_string(&hit.content).unwrap_or_else(|_| "{}".to_string())),
         highlights,
+        highlight_fields,
+    }
+}
+
+/// Snippet fields are read from `_snippetResult`, the other fields from `_highlightResult`. When
+/// no field is listed, only the highlighted attributes with matches are kept.
+fn algolia_results_to_field_highlights(
+    hit: &AlgoliaSearchHit,
+    config: &HighlightConfig,
+) -> Vec<FieldHighlight> {
+    let all_fields = config.fields.is_empty() && config.snippet_fields.is_empty();
+    let mut highlight_fields = vec![];
+
+    if let Some(Value::Object(results)) = &hit.highlight_result {
+        for (field, result) in results {
+            if config.snippet_fields.contains(field)
+                || !(all_fields || config.fields.contains(field))
+            {
+                continue;
+            }
+            let highlight = field_highlight(field.as_str(), &algolia_result_values(result), config);
+            let matched = highlight
+                .fragments
+                .iter()
+                .any(|fragment| !fragment.matches.is_empty());
+            if matched || !all_fields {
+                highlight_fields.push(highlight);
+            }
+        }
+    }
+    if let Some(Value::Object(results)) = &hit.snippet_result {
+        for field in &config.snippet_fields {
+            if let Some(result) = results.get(field) {
+                highlight_fields.push(field_highlight(
+                    field.as_str(),
+                    &algolia_result_values(result),
+                    config,
+                ));
+            }
+        }
     }
+
+    highlight_fields
 }
 
-pub fn schema_to_algolia_settings(schema: Schema) -> IndexSettings {
+/// Results of array attributes hold one value per element
+fn algolia_result_values(result: &Value) -> Vec<&str> {
+    match result {
+        Value::Array(results) => results.iter().flat_map(algolia_result_values).collect(),
+        Value::Object(result) => result
+            .get("value")
+            .and_then(|value| value.as_str())
+            .into_iter()
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Algolia sets the languages of the whole index, so text fields can only have one locale, which
+/// the schema reads back on every searchable text field
+pub fn schema_to_algolia_settings(schema: Schema) -> Result<IndexSettings, SearchError> {
     let mut settings = IndexSettings::default();
 
     for field in schema.fields {
+        validate_field(&field)?;
+        if field.analyzer.is_some() {
+            return Err(invalid_query(format!(
+                "Algolia doesn't support custom analyzers, set on the field {}",
+                field.name
+            )));
+        }
+        if let Some(locale) = &field.locale {
+            if settings.index_languages.iter().all(|l| l == locale) {
+                settings.index_languages = vec![locale.clone()];
+                settings.query_languages = vec![locale.clone()];
+            } else {
+                return Err(invalid_query(format!(
+                    "Algolia indexes have a single language, {} is {locale} while other fields are {}",
+                    field.name, settings.index_languages[0]
+                )));
+            }
+        }
+        if !field.stored {
+            settings.unretrievable_attributes.push(field.name.clone());
+        }
+
         match field.field_type {
             FieldType::Text => {
                 if field.index {
@@ -160,6 +517,16 @@ pub fn schema_to_algolia_settings(schema: Schema) -> IndexSettings {
                     settings.attributes_for_faceting.push(field.name.clone());
                 }
             }
+            FieldType::Object | FieldType::Nested => {
+                // Searching an object searches all its attributes
+                if field.index {
+                    settings.searchable_attributes.push(field.name.clone());
+                }
+            }
+            FieldType::GeoShape | FieldType::Vector => {
+                // Shapes and vectors are stored with the records, but can't be searched
+                continue;
+            }
         }
 
         // Handle sorting - in Algolia, sorting is done via custom ranking
@@ -170,13 +537,17 @@ pub fn schema_to_algolia_settings(schema: Schema) -> IndexSettings {
         }
     }
 
-    settings
+    Ok(settings)
 }
 
 pub fn algolia_settings_to_schema(settings: IndexSettings) -> Schema {
     let mut fields = Vec::new();
 
     // Convert searchable attributes to text fields
+    let locale = match settings.index_languages.as_slice() {
+        [locale] => Some(locale.clone()),
+        _ => None,
+    };
     for attr in settings.searchable_attributes {
         fields.push(SchemaField {
             name: attr,
@@ -185,6 +556,11 @@ pub fn algolia_settings_to_schema(settings: IndexSettings) -> Schema {
             facet: false,
             sort: false,
             index: true,
+            array: false,
+            stored: true,
+            analyzer: None,
+            locale: locale.clone(),
+            vector: None,
         });
     }
 
@@ -209,6 +585,11 @@ pub fn algolia_settings_to_schema(settings: IndexSettings) -> Schema {
                 facet: true,
                 sort: false,
                 index: false,
+                array: false,
+                stored: true,
+                analyzer: None,
+                locale: None,
+                vector: None,
             });
         }
     }
@@ -226,11 +607,37 @@ pub fn algolia_settings_to_schema(settings: IndexSettings) -> Schema {
                     facet: false,
                     sort: true,
                     index: false,
+                    array: false,
+                    stored: true,
+                    analyzer: None,
+                    locale: None,
+                    vector: None,
                 });
             }
         }
     }
 
+    // Unretrievable attributes are only searched or filtered on
+    for attr in settings.unretrievable_attributes {
+        if let Some(existing_field) = fields.iter_mut().find(|f| f.name == attr) {
+            existing_field.stored = false;
+        } else {
+            fields.push(SchemaField {
+                name: attr,
+                field_type: FieldType::Text,
+                required: false,
+                facet: false,
+                sort: false,
+                index: false,
+                array: false,
+                stored: false,
+                analyzer: None,
+                locale: None,
+                vector: None,
+            });
+        }
+    }
+
     Schema {
         fields,
         primary_key: None,
@@ -248,6 +655,37 @@ fn extract_field_from_ranking(ranking_rule: &str) -> Option<String> {
     None
 }
 
+/// Default number of hits of a search stream page
+pub const STREAM_PAGE_SIZE: u32 = 1000;
+
+/// Builds the request of the first page of a search stream. Browsing pages through the results
+/// with cursors, so the page and offset of the query are not used, and neither are the facets
+/// as the stream doesn't return them.
+pub fn search_query_to_algolia_browse_request(
+    query: SearchQuery,
+) -> Result<BrowseRequest, SearchError> {
+    let page_size = query.per_page.unwrap_or(STREAM_PAGE_SIZE);
+    let mut algolia_query = search_query_to_algolia_query(query)?;
+    algolia_query.page = None;
+    algolia_query.offset = None;
+    algolia_query.hits_per_page = Some(page_size);
+    algolia_query.facets = vec![];
+    algolia_query.max_values_per_facet = None;
+
+    Ok(BrowseRequest::Query(algolia_query))
+}
+
+/// Algolia records are schemaless and its settings are replaced as a whole, so only the primary
+/// key, which is always the objectID, can't change
+pub fn classify_algolia_change(diff: &SchemaDiff) -> Classification {
+    match diff.kind {
+        SchemaChangeKind::PrimaryKeyChanged => {
+            impossible("Algolia identifies records by their objectID")
+        }
+        _ => in_place(),
+    }
+}
+
 pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
     let mut retry_query = original_query.clone();
 
@@ -265,10 +703,23 @@ pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHi
     retry_query
 }
 
+/// Algolia only reports whether a task is applied, without distinguishing enqueued tasks from
+/// the ones being processed
+pub fn algolia_task_to_task_status(task: &TaskResponse) -> TaskStatus {
+    if task.status == "published" {
+        TaskStatus::Succeeded
+    } else {
+        TaskStatus::Processing
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use golem_search::golem::search::types::{HighlightConfig, SearchConfig};
+    use golem_search::golem::search::types::{
+        FacetOrder, FilterExpression, FilterNode, GeoPoint, GeoRadius, HighlightConfig,
+        RangeBounds, SchemaChangeApplication, SearchConfig, TermsFacet, VectorQuery,
+    };
 
     #[test]
     fn test_doc_to_algolia_object() {
@@ -329,8 +780,11 @@ mod tests {
         let search_query = SearchQuery {
             q: Some("test query".to_string()),
             filters: vec!["category:electronics".to_string(), "price:>100".to_string()],
+            filter: None,
+            vector: None,
             sort: vec!["price:desc".to_string()],
             facets: vec!["category".to_string(), "brand".to_string()],
+            facet_requests: vec![],
             page: Some(1),
             per_page: Some(20),
             offset: None,
@@ -339,11 +793,14 @@ mod tests {
                 pre_tag: Some("<mark>".to_string()),
                 post_tag: Some("</mark>".to_string()),
                 max_length: Some(200),
+                snippet_fields: vec![],
+                snippet_length: None,
+                max_snippets: None,
             }),
             config: None,
         };
 
-        let algolia_query = search_query_to_algolia_query(search_query);
+        let algolia_query = search_query_to_algolia_query(search_query).unwrap();
         assert_eq!(algolia_query.query, Some("test query".to_string()));
         assert_eq!(
             algolia_query.filters,
@@ -353,17 +810,148 @@ mod tests {
             algolia_query.facets,
             vec!["category".to_string(), "brand".to_string()]
         );
+        assert_eq!(algolia_query.max_values_per_facet, Some(10));
+        assert_eq!(
+            algolia_query.attributes_to_highlight,
+            vec!["title".to_string(), "description".to_string()]
+        );
+        assert_eq!(algolia_query.highlight_pre_tag, Some("<mark>".to_string()));
         assert_eq!(algolia_query.page, Some(1));
         assert_eq!(algolia_query.hits_per_page, Some(20));
     }
 
+    #[test]
+    fn test_search_query_with_filter_expression() {
+        let search_query = SearchQuery {
+            q: Some("pizza".to_string()),
+            filters: vec![],
+            filter: Some(FilterExpression {
+                nodes: vec![
+                    FilterNode::And(vec![1, 2, 3]),
+                    FilterNode::Or(vec![4, 5]),
+                    FilterNode::Ne((
+                        "cuisine".to_string(),
+                        FilterValue::Text("fast food".to_string()),
+                    )),
+                    FilterNode::GeoRadius(GeoRadius {
+                        field: "_geoloc".to_string(),
+                        center: GeoPoint {
+                            lat: 40.71,
+                            lon: -74.01,
+                        },
+                        radius_meters: 1000.0,
+                    }),
+                    FilterNode::In((
+                        "borough".to_string(),
+                        vec![
+                            FilterValue::Text("Brooklyn".to_string()),
+                            FilterValue::Text("Queens".to_string()),
+                        ],
+                    )),
+                    FilterNode::Range((
+                        "rating".to_string(),
+                        RangeBounds {
+                            gt: None,
+                            gte: Some(FilterValue::Float(4.5)),
+                            lt: None,
+                            lte: None,
+                        },
+                    )),
+                ],
+            }),
+            vector: None,
+            sort: vec![],
+            facets: vec![],
+            facet_requests: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        };
+
+        let algolia_query = search_query_to_algolia_query(search_query).unwrap();
+        assert_eq!(
+            algolia_query.filters,
+            Some(
+                r#"(borough:"Brooklyn" OR borough:"Queens" OR rating >= 4.5) AND NOT cuisine:"fast food""#
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            algolia_query.around_lat_lng,
+            Some("40.71,-74.01".to_string())
+        );
+        assert_eq!(algolia_query.around_radius, Some(1000));
+
+        let mut algolia_query = search_query_to_algolia_query(SearchQuery {
+            q: None,
+            filters: vec![],
+            filter: None,
+            vector: None,
+            sort: vec![],
+            facets: vec![],
+            facet_requests: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        })
+        .unwrap();
+        let filter = resolve_filter(&FilterExpression {
+            nodes: vec![
+                FilterNode::Or(vec![1, 2]),
+                FilterNode::Exists("a".to_string()),
+                FilterNode::Eq(("b".to_string(), FilterValue::Boolean(true))),
+            ],
+        })
+        .unwrap();
+        assert!(matches!(
+            apply_filter_to_algolia_query(&filter, &mut algolia_query),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_vector_search_is_unsupported() {
+        let search_query = SearchQuery {
+            q: Some("test".to_string()),
+            filters: vec![],
+            filter: None,
+            vector: Some(VectorQuery {
+                field: "embedding".to_string(),
+                vector: vec![0.1, 0.2],
+                k: 10,
+                semantic_ratio: None,
+                filter: None,
+            }),
+            sort: vec![],
+            facets: vec![],
+            facet_requests: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        };
+
+        assert_eq!(
+            search_query_to_algolia_query(search_query).unwrap_err(),
+            SearchError::Unsupported
+        );
+    }
+
     #[test]
     fn test_search_query_with_config() {
         let search_query = SearchQuery {
             q: Some("test".to_string()),
             filters: vec![],
+            filter: None,
+            vector: None,
             sort: vec![],
             facets: vec![],
+            facet_requests: vec![],
             page: None,
             per_page: None,
             offset: None,
@@ -381,7 +969,7 @@ mod tests {
             }),
         };
 
-        let algolia_query = search_query_to_algolia_query(search_query);
+        let algolia_query = search_query_to_algolia_query(search_query).unwrap();
         assert_eq!(
             algolia_query.attributes_to_retrieve,
             vec!["title".to_string(), "price".to_string()]
@@ -401,6 +989,11 @@ mod tests {
                     facet: false,
                     sort: false,
                     index: true,
+                    array: false,
+                    stored: true,
+                    analyzer: None,
+                    locale: None,
+                    vector: None,
                 },
                 SchemaField {
                     name: "category".to_string(),
@@ -409,6 +1002,11 @@ mod tests {
                     facet: true,
                     sort: false,
                     index: true,
+                    array: false,
+                    stored: true,
+                    analyzer: None,
+                    locale: None,
+                    vector: None,
                 },
                 SchemaField {
                     name: "price".to_string(),
@@ -417,12 +1015,17 @@ mod tests {
                     facet: true,
                     sort: true,
                     index: false,
+                    array: false,
+                    stored: true,
+                    analyzer: None,
+                    locale: None,
+                    vector: None,
                 },
             ],
             primary_key: Some("id".to_string()),
         };
 
-        let settings = schema_to_algolia_settings(schema);
+        let settings = schema_to_algolia_settings(schema).unwrap();
         assert!(settings
             .searchable_attributes
             .contains(&"title".to_string()));
@@ -438,6 +1041,73 @@ mod tests {
         assert!(settings.custom_ranking.contains(&"desc(price)".to_string()));
     }
 
+    #[test]
+    fn test_localized_and_unstored_fields_round_trip() {
+        let field = |name: &str, field_type| SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet: false,
+            sort: false,
+            index: true,
+            array: false,
+            stored: true,
+            analyzer: None,
+            locale: None,
+            vector: None,
+        };
+        let title = SchemaField {
+            locale: Some("fr".to_string()),
+            ..field("title", FieldType::Text)
+        };
+        let description = SchemaField {
+            locale: Some("fr".to_string()),
+            stored: false,
+            ..field("description", FieldType::Text)
+        };
+        let schema = Schema {
+            fields: vec![
+                title.clone(),
+                description.clone(),
+                field("variants", FieldType::Nested),
+                field("area", FieldType::GeoShape),
+            ],
+            primary_key: None,
+        };
+
+        let settings = schema_to_algolia_settings(schema).unwrap();
+        assert_eq!(settings.index_languages, vec!["fr".to_string()]);
+        assert_eq!(settings.query_languages, vec!["fr".to_string()]);
+        assert_eq!(
+            settings.unretrievable_attributes,
+            vec!["description".to_string()]
+        );
+        assert_eq!(
+            settings.searchable_attributes,
+            vec![
+                "title".to_string(),
+                "description".to_string(),
+                "variants".to_string()
+            ]
+        );
+
+        let fields = algolia_settings_to_schema(settings).fields;
+        assert!(fields.contains(&title));
+        assert!(fields.contains(&description));
+
+        let mixed_locales = Schema {
+            fields: vec![
+                title,
+                SchemaField {
+                    locale: Some("de".to_string()),
+                    ..field("title_de", FieldType::Text)
+                },
+            ],
+            primary_key: None,
+        };
+        assert!(schema_to_algolia_settings(mixed_locales).is_err());
+    }
+
     #[test]
     fn test_algolia_response_conversion() {
         let algolia_response = SearchResponse {
@@ -447,7 +1117,9 @@ mod tests {
                 highlight_result: Some(
                     serde_json::json!({"title": {"value": "Test <em>Document</em> 1"}}),
                 ),
-                snippet_result: None,
+                snippet_result: Some(serde_json::json!({
+                    "description": { "value": "… a <em>document</em> about …" }
+                })),
                 ranking_info: Some(crate::client::RankingInfo {
                     nb_typos: 0,
                     first_matched_word: 0,
@@ -466,14 +1138,34 @@ mod tests {
             hits_per_page: 20,
             processing_time_ms: 5,
             facets: Some(serde_json::json!({"category": {"electronics": 1}})),
-            facets_stats: None,
+            facets_stats: Some(serde_json::json!({
+                "price": { "min": 1.5, "max": 99.0, "avg": 25.0, "sum": 250.0 }
+            })),
             exhaustive_nb_hits: true,
             exhaustive_facets_count: true,
             query: "test".to_string(),
             params: "q=test".to_string(),
         };
 
-        let search_results = algolia_response_to_search_results(algolia_response);
+        let facets = vec![
+            FacetRequest::Stats("price".to_string()),
+            FacetRequest::Terms(TermsFacet {
+                field: "category".to_string(),
+                size: None,
+                order: FacetOrder::Count,
+            }),
+        ];
+        let highlight = HighlightConfig {
+            fields: vec!["title".to_string()],
+            pre_tag: None,
+            post_tag: None,
+            max_length: None,
+            snippet_fields: vec!["description".to_string()],
+            snippet_length: None,
+            max_snippets: None,
+        };
+        let search_results =
+            algolia_response_to_search_results(algolia_response, &facets, Some(&highlight));
         assert_eq!(search_results.total, Some(1));
         assert_eq!(search_results.page, Some(0));
         assert_eq!(search_results.per_page, Some(20));
@@ -482,6 +1174,22 @@ mod tests {
         assert_eq!(search_results.hits[0].score, Some(100.0));
         assert!(search_results.facets.is_some());
         assert_eq!(search_results.took_ms, Some(5));
+
+        let highlight_fields = &search_results.hits[0].highlight_fields;
+        assert_eq!(highlight_fields.len(), 2);
+        assert_eq!(highlight_fields[0].field, "title");
+        assert_eq!(highlight_fields[0].fragments[0].text, "Test Document 1");
+        assert_eq!(highlight_fields[1].field, "description");
+        assert_eq!(
+            highlight_fields[1].fragments[0].text,
+            "… a document about …"
+        );
+
+        let facet_results = search_results.facet_results;
+        assert_eq!(facet_results[0].stats.as_ref().unwrap().avg, Some(25.0));
+        assert_eq!(facet_results[1].field, "category");
+        assert_eq!(facet_results[1].buckets[0].value, "electronics");
+        assert_eq!(facet_results[1].buckets[0].count, 1);
     }
 
     #[test]
@@ -489,8 +1197,11 @@ mod tests {
         let original_query = SearchQuery {
             q: Some("test".to_string()),
             filters: vec![],
+            filter: None,
+            vector: None,
             sort: vec![],
             facets: vec![],
+            facet_requests: vec![],
             page: Some(1),
             per_page: Some(10),
             offset: None,
@@ -503,6 +1214,7 @@ mod tests {
             score: Some(1.0),
             content: Some("{}".to_string()),
             highlights: None,
+            highlight_fields: vec![],
         }];
 
         let retry_query = create_retry_query(&original_query, &partial_hits);
@@ -514,8 +1226,11 @@ mod tests {
         let original_query = SearchQuery {
             q: Some("test".to_string()),
             filters: vec![],
+            filter: None,
+            vector: None,
             sort: vec![],
             facets: vec![],
+            facet_requests: vec![],
             page: None,
             per_page: Some(10),
             offset: Some(20),
@@ -528,12 +1243,48 @@ mod tests {
             score: Some(1.0),
             content: Some("{}".to_string()),
             highlights: None,
+            highlight_fields: vec![],
         }];
 
         let retry_query = create_retry_query(&original_query, &partial_hits);
         assert_eq!(retry_query.offset, Some(21));
     }
 
+    #[test]
+    fn test_search_query_to_algolia_browse_request() {
+        let query = SearchQuery {
+            q: Some("test".to_string()),
+            filters: vec!["category:books".to_string()],
+            filter: None,
+            vector: None,
+            sort: vec![],
+            facets: vec!["category".to_string()],
+            facet_requests: vec![],
+            page: Some(3),
+            per_page: None,
+            offset: Some(20),
+            highlight: None,
+            config: None,
+        };
+
+        let request = search_query_to_algolia_browse_request(query).unwrap();
+        let body = serde_json::to_value(&request).unwrap();
+        assert_eq!(body["query"], "test");
+        assert_eq!(body["filters"], "category:books");
+        assert_eq!(body["hitsPerPage"], STREAM_PAGE_SIZE);
+        assert!(body.get("page").is_none());
+        assert!(body.get("offset").is_none());
+        assert!(body.get("facets").is_none());
+
+        let next_page = BrowseRequest::Cursor {
+            cursor: "jMDY3M2MwM2QwMWUxMmQwYWI0ZTN".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&next_page).unwrap(),
+            serde_json::json!({ "cursor": "jMDY3M2MwM2QwMWUxMmQwYWI0ZTN" })
+        );
+    }
+
     #[test]
     fn test_extract_field_from_ranking() {
         assert_eq!(
@@ -547,4 +1298,44 @@ mod tests {
         assert_eq!(extract_field_from_ranking("invalid"), None);
         assert_eq!(extract_field_from_ranking("desc()"), Some("".to_string()));
     }
+
+    #[test]
+    fn test_algolia_task_to_task_status() {
+        let published: TaskResponse =
+            serde_json::from_str(r#"{"status": "published", "pendingTask": false}"#).unwrap();
+        assert_eq!(
+            algolia_task_to_task_status(&published),
+            TaskStatus::Succeeded
+        );
+
+        let not_published: TaskResponse =
+            serde_json::from_str(r#"{"status": "notPublished", "pendingTask": true}"#).unwrap();
+        assert_eq!(
+            algolia_task_to_task_status(&not_published),
+            TaskStatus::Processing
+        );
+    }
+
+    #[test]
+    fn test_classify_algolia_change() {
+        let diff = |kind| SchemaDiff {
+            kind,
+            field: "year".to_string(),
+            current: None,
+            target: None,
+        };
+
+        assert_eq!(
+            classify_algolia_change(&diff(SchemaChangeKind::FieldTypeChanged)),
+            in_place()
+        );
+        assert_eq!(
+            classify_algolia_change(&diff(SchemaChangeKind::FieldRemoved)),
+            in_place()
+        );
+        assert_eq!(
+            classify_algolia_change(&diff(SchemaChangeKind::PrimaryKeyChanged)).application,
+            SchemaChangeApplication::Impossible
+        );
+    }
 }
@@ -1,28 +1,54 @@
-use crate::client::AlgoliaSearchApi;
+use crate::client::{AlgoliaSearchApi, BrowseRequest};
 use crate::conversions::{
     algolia_object_to_doc, algolia_response_to_search_results, algolia_settings_to_schema,
-    create_retry_query, doc_to_algolia_object, schema_to_algolia_settings,
+    algolia_task_to_task_status, classify_algolia_change, create_retry_query,
+    doc_to_algolia_object, schema_to_algolia_settings, search_query_to_algolia_browse_request,
     search_query_to_algolia_query,
 };
 use golem_rust::wasm_rpc::Pollable;
+use golem_search::bulk::{
+    doc_chunks, id_chunks, send_converted, send_with_retries, write_chunks, ItemOutcome,
+};
 use golem_search::config::with_config_keys;
 use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::error::invalid_query;
+use golem_search::facets::facet_requests;
 use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
 use golem_search::golem::search::types::{
-    Doc, DocumentId, IndexName, Schema, SearchError, SearchHit, SearchQuery, SearchResults,
+    BulkResult, Doc, DocumentId, IndexAlias, IndexName, ReindexResult, ReindexTransform, Schema,
+    SchemaChange, SchemaUpdateMode, SearchError, SearchHit, SearchQuery, SearchResults, TaskId,
+    TaskStatus, WriteConsistency,
 };
+use golem_search::schema::{check_in_place, plan_schema_change, reindex_to_schema};
+use log::trace;
 use std::cell::{Cell, RefCell};
 
 mod client;
 mod conversions;
 
+/// Waits for the task when the write must be visible, and returns its id
+fn finish_task(
+    client: &AlgoliaSearchApi,
+    index: &str,
+    task_id: u64,
+    consistency: WriteConsistency,
+) -> Result<Option<TaskId>, SearchError> {
+    if consistency == WriteConsistency::WaitForVisible {
+        client.wait_for_task(index, task_id)?;
+    }
+    Ok(Some(task_id.to_string()))
+}
+
+/// Streams the results with the browse API, whose cursors are not limited by the pagination
+/// limit of the index. Browsing returns the matching records without ranking them.
 struct AlgoliaSearchStream {
     client: AlgoliaSearchApi,
     index_name: String,
     query: SearchQuery,
-    current_page: Cell<u32>,
+    /// Cursor of the next page, set once the first page is received. It is empty once the last
+    /// page has been received.
+    cursor: RefCell<Option<String>>,
     finished: Cell<bool>,
-    last_response: RefCell<Option<SearchResults>>,
 }
 
 impl AlgoliaSearchStream {
@@ -30,55 +56,77 @@ impl AlgoliaSearchStream {
         Self {
             client,
             index_name,
-            query: query.clone(),
-            current_page: Cell::new(query.page.unwrap_or(0)),
+            query,
+            cursor: RefCell::new(None),
             finished: Cell::new(false),
-            last_response: RefCell::new(None),
+        }
+    }
+
+    pub fn resumed(
+        client: AlgoliaSearchApi,
+        index_name: String,
+        query: SearchQuery,
+        cursor: String,
+    ) -> Self {
+        Self {
+            client,
+            index_name,
+            query,
+            finished: Cell::new(cursor.is_empty()),
+            cursor: RefCell::new(Some(cursor)),
         }
     }
 
     pub fn subscribe(&self) -> Pollable {
         golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
     }
-}
 
-impl GuestSearchStream for AlgoliaSearchStream {
-    fn get_next(&self) -> Option<Vec<SearchHit>> {
+    pub fn cursor(&self) -> Option<String> {
+        self.cursor.borrow().clone()
+    }
+
+    /// Returns the next page of hits, an empty page once all the hits have been returned, or the
+    /// error which ended the stream
+    fn next_page(&self) -> Result<Vec<SearchHit>, SearchError> {
         if self.finished.get() {
-            return Some(vec![]);
+            return Ok(vec![]);
         }
 
-        let mut search_query = self.query.clone();
-        search_query.page = Some(self.current_page.get());
-
-        let algolia_query = search_query_to_algolia_query(search_query);
-
-        match self.client.search(&self.index_name, &algolia_query) {
-            Ok(response) => {
-                let search_results = algolia_response_to_search_results(response);
-
-                let current_page = self.current_page.get();
-                let total_pages = if let (Some(total), Some(per_page)) =
-                    (search_results.total, search_results.per_page)
-                {
-                    total.div_ceil(per_page)
-                } else {
-                    current_page + 1
-                };
-
-                if current_page >= total_pages || search_results.hits.is_empty() {
-                    self.finished.set(true);
-                }
+        let result = self.read_page();
+        if result.is_err() {
+            self.finished.set(true);
+        }
+        result
+    }
 
-                self.current_page.set(current_page + 1);
+    fn read_page(&self) -> Result<Vec<SearchHit>, SearchError> {
+        let request = match self.cursor.borrow().clone() {
+            Some(cursor) => BrowseRequest::Cursor { cursor },
+            None => search_query_to_algolia_browse_request(self.query.clone())?,
+        };
+
+        let response = self.client.browse(&self.index_name, &request)?;
+        let search_results = algolia_response_to_search_results(
+            response.response,
+            &[],
+            self.query.highlight.as_ref(),
+        );
+
+        if response.cursor.is_none() {
+            self.finished.set(true);
+        }
+        *self.cursor.borrow_mut() = Some(response.cursor.unwrap_or_default());
 
-                let hits = search_results.hits.clone();
-                *self.last_response.borrow_mut() = Some(search_results);
+        Ok(search_results.hits)
+    }
+}
 
-                Some(hits)
-            }
-            Err(_) => {
-                self.finished.set(true);
+impl GuestSearchStream for AlgoliaSearchStream {
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        match self.next_page() {
+            Ok(hits) => Some(hits),
+            Err(e) => {
+                trace!("Search stream failed: {e:?}");
                 Some(vec![])
             }
         }
@@ -117,23 +165,25 @@ impl AlgoliaComponent {
 impl Guest for AlgoliaComponent {
     type SearchStream = AlgoliaSearchStream;
 
-    fn create_index(_name: IndexName, _schema: Option<Schema>) -> Result<(), SearchError> {
+    fn create_index(
+        _name: IndexName,
+        _schema: Option<Schema>,
+        _consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
         // Algolia doesn't require explicit index creation - indices are created automatically
         // when you first add documents.
         // providers that don't support index creation should return unsupported.
         Err(SearchError::Unsupported)
     }
 
-    fn delete_index(name: IndexName) -> Result<(), SearchError> {
+    fn delete_index(
+        name: IndexName,
+        consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
         let client = Self::create_client()?;
 
-        match client.delete_index(&name) {
-            Ok(response) => {
-                let _ = response;
-                Ok(())
-            }
-            Err(e) => Err(e),
-        }
+        let response = client.delete_index(&name)?;
+        finish_task(&client, &name, response.task_id, consistency)
     }
 
     fn list_indexes() -> Result<Vec<IndexName>, SearchError> {
@@ -145,59 +195,133 @@ impl Guest for AlgoliaComponent {
         }
     }
 
-    fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+    fn create_alias(_alias: IndexName, _index: IndexName) -> Result<(), SearchError> {
+        // Algolia has no aliases, an index can only be moved to another one
+        Err(SearchError::Unsupported)
+    }
+
+    /// The given index is moved to the alias, replacing the index named after the alias
+    /// atomically. The given index doesn't exist anymore afterwards.
+    fn swap_alias(alias: IndexName, index: IndexName) -> Result<(), SearchError> {
         let client = Self::create_client()?;
-        let algolia_object = doc_to_algolia_object(doc).map_err(SearchError::InvalidQuery)?;
 
-        match client.save_object(&index, &algolia_object) {
-            Ok(response) => {
-                let _ = response;
-                Ok(())
-            }
-            Err(e) => Err(e),
-        }
+        let response = client.move_index(&index, &alias)?;
+        client.wait_for_task(&index, response.task_id)
     }
 
-    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
-        let client = Self::create_client()?;
-        let mut algolia_objects = Vec::new();
+    fn list_aliases() -> Result<Vec<IndexAlias>, SearchError> {
+        Err(SearchError::Unsupported)
+    }
 
-        for doc in docs {
-            let algolia_object = doc_to_algolia_object(doc).map_err(SearchError::InvalidQuery)?;
-            algolia_objects.push(algolia_object);
-        }
+    fn delete_alias(_alias: IndexName) -> Result<(), SearchError> {
+        Err(SearchError::Unsupported)
+    }
 
-        match client.save_objects(&index, &algolia_objects) {
-            Ok(response) => {
-                let _ = response;
-                Ok(())
-            }
-            Err(e) => Err(e),
-        }
+    fn reindex(
+        source: IndexName,
+        target: IndexName,
+        alias: Option<IndexName>,
+        transform: Option<ReindexTransform>,
+    ) -> Result<ReindexResult, SearchError> {
+        golem_search::reindex::reindex::<Self>(source, target, alias, transform, Self::next_page)
     }
 
-    fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+    fn upsert(
+        index: IndexName,
+        doc: Doc,
+        consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
         let client = Self::create_client()?;
+        let algolia_object = doc_to_algolia_object(doc).map_err(SearchError::InvalidQuery)?;
 
-        match client.delete_object(&index, &id) {
-            Ok(response) => {
-                let _ = response;
-                Ok(())
-            }
-            Err(e) => Err(e),
-        }
+        let response = client.save_object(&index, &algolia_object)?;
+        finish_task(&client, &index, response.task_id, consistency)
     }
 
-    fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+    fn upsert_many(
+        index: IndexName,
+        docs: Vec<Doc>,
+        consistency: WriteConsistency,
+    ) -> Result<BulkResult, SearchError> {
         let client = Self::create_client()?;
+        let limits = Self::bulk_limits();
+
+        // A batch is applied as a whole, so every object it contains succeeded once it is accepted
+        write_chunks(
+            doc_chunks(docs, &limits),
+            |doc| doc.id.as_str(),
+            |docs| {
+                let mut task_ids = Vec::new();
+                let mut result = send_with_retries(
+                    docs,
+                    |doc| doc.id.as_str(),
+                    &limits,
+                    |docs| {
+                        send_converted(
+                            docs,
+                            |doc| doc_to_algolia_object(doc.clone()),
+                            |algolia_objects| {
+                                let response = client.save_objects(&index, algolia_objects)?;
+                                task_ids.extend(finish_task(
+                                    &client,
+                                    &index,
+                                    response.task_id,
+                                    consistency,
+                                )?);
+                                Ok(vec![ItemOutcome::success(); algolia_objects.len()])
+                            },
+                        )
+                    },
+                )?;
+                result.task_ids = task_ids;
+                Ok(result)
+            },
+        )
+    }
 
-        match client.delete_objects(&index, &ids) {
-            Ok(response) => {
-                let _ = response;
-                Ok(())
-            }
-            Err(e) => Err(e),
-        }
+    fn delete(
+        index: IndexName,
+        id: DocumentId,
+        consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
+        let client = Self::create_client()?;
+
+        let response = client.delete_object(&index, &id)?;
+        finish_task(&client, &index, response.task_id, consistency)
+    }
+
+    fn delete_many(
+        index: IndexName,
+        ids: Vec<DocumentId>,
+        consistency: WriteConsistency,
+    ) -> Result<BulkResult, SearchError> {
+        let client = Self::create_client()?;
+        let limits = Self::bulk_limits();
+
+        write_chunks(
+            id_chunks(ids, &limits),
+            |id| id.as_str(),
+            |ids| {
+                let mut task_ids = Vec::new();
+                let mut result = send_with_retries(
+                    ids,
+                    |id| id.as_str(),
+                    &limits,
+                    |ids| {
+                        let response = client.delete_objects(&index, ids)?;
+                        task_ids.extend(finish_task(
+                            &client,
+                            &index,
+                            response.task_id,
+                            consistency,
+                        )?);
+                        Ok(vec![ItemOutcome::success(); ids.len()])
+                    },
+                )?;
+                result.task_ids = task_ids;
+                Ok(result)
+            },
+        )
     }
 
     fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
@@ -212,16 +336,24 @@ impl Guest for AlgoliaComponent {
 
     fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
         let client = Self::create_client()?;
-        let algolia_query = search_query_to_algolia_query(query);
+        let facets = facet_requests(&query)?;
+        let highlight = query.highlight.clone();
+        let algolia_query = search_query_to_algolia_query(query)?;
 
         match client.search(&index, &algolia_query) {
-            Ok(response) => Ok(algolia_response_to_search_results(response)),
+            Ok(response) => Ok(algolia_response_to_search_results(
+                response,
+                &facets,
+                highlight.as_ref(),
+            )),
             Err(e) => Err(e),
         }
     }
 
     fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
         let client = Self::create_client()?;
+        // Rejects invalid filters before the stream is created
+        search_query_to_algolia_query(query.clone())?;
         let stream = AlgoliaSearchStream::new(client, index, query);
         Ok(SearchStream::new(stream))
     }
@@ -235,13 +367,44 @@ impl Guest for AlgoliaComponent {
         }
     }
 
-    fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
+    fn plan_schema_change(
+        index: IndexName,
+        schema: Schema,
+    ) -> Result<Vec<SchemaChange>, SearchError> {
+        let current = Self::get_schema(index)?;
+        Ok(plan_schema_change(
+            &current,
+            &schema,
+            classify_algolia_change,
+        ))
+    }
+
+    fn update_schema(
+        index: IndexName,
+        schema: Schema,
+        consistency: WriteConsistency,
+        mode: SchemaUpdateMode,
+    ) -> Result<Option<TaskId>, SearchError> {
+        if let SchemaUpdateMode::Reindex(reindex) = mode {
+            return reindex_to_schema::<Self>(index, schema, reindex);
+        }
+        check_in_place(Self::plan_schema_change(index.clone(), schema.clone()))?;
+
         let client = Self::create_client()?;
-        let settings = schema_to_algolia_settings(schema);
+        let settings = schema_to_algolia_settings(schema)?;
+
+        let response = client.set_settings(&index, &settings)?;
+        finish_task(&client, &index, response.task_id, consistency)
+    }
 
-        client.set_settings(&index, &settings)?;
+    fn task_status(index: IndexName, task_id: TaskId) -> Result<TaskStatus, SearchError> {
+        let client = Self::create_client()?;
+        let task_id = task_id
+            .parse()
+            .map_err(|_| invalid_query(format!("Invalid task id: {task_id}")))?;
 
-        Ok(())
+        let task = client.get_task(&index, task_id)?;
+        Ok(algolia_task_to_task_status(&task))
     }
 }
 
@@ -253,6 +416,17 @@ impl ExtendedGuest for AlgoliaComponent {
         AlgoliaSearchStream::new(client, index, query)
     }
 
+    fn resumed_stream(index: IndexName, query: SearchQuery, cursor: String) -> Self::SearchStream {
+        let client = Self::create_client()
+            .unwrap_or_else(|_| AlgoliaSearchApi::new("dummy".to_string(), "dummy".to_string()));
+
+        AlgoliaSearchStream::resumed(client, index, query, cursor)
+    }
+
+    fn stream_cursor(stream: &Self::SearchStream) -> Option<String> {
+        stream.cursor()
+    }
+
     fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
         create_retry_query(original_query, partial_hits)
     }
@@ -260,6 +434,10 @@ impl ExtendedGuest for AlgoliaComponent {
     fn subscribe(stream: &Self::SearchStream) -> Pollable {
         stream.subscribe()
     }
+
+    fn next_page(stream: &Self::SearchStream) -> Result<Vec<SearchHit>, SearchError> {
+        stream.next_page()
+    }
 }
 
 type DurableAlgoliaComponent = DurableSearch<AlgoliaComponent>;
@@ -1,10 +1,11 @@
 use golem_search::error::{from_reqwest_error, internal_error, search_error_from_status};
 use golem_search::golem::search::types::SearchError;
 use log::trace;
-use reqwest::{Client, Method, RequestBuilder, Response};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 
 /// The Elasticsearch Search API client for managing indices and performing search
@@ -32,6 +33,10 @@ pub struct ElasticsearchMappings {
     pub properties: Option<Map<String, Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dynamic: Option<bool>,
+    #[serde(rename = "_source", skip_serializing_if = "Option::is_none")]
+    pub source: Option<Value>,
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -39,6 +44,8 @@ pub struct ElasticsearchQuery {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub query: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub knn: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub from: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub size: Option<u32>,
@@ -50,6 +57,10 @@ pub struct ElasticsearchQuery {
     pub aggs: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub _source: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pit: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_after: Option<Vec<Value>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -60,6 +71,8 @@ pub struct ElasticsearchSearchResponse {
     pub hits: ElasticsearchHits,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub aggregations: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pit_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -89,6 +102,8 @@ pub struct ElasticsearchHit {
     pub source: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub highlight: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<Vec<Value>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -119,6 +134,15 @@ pub struct ElasticsearchBulkResponse {
     pub items: Vec<Value>,
 }
 
+/// Aliases of each index, keyed by index name
+pub type ElasticsearchAliases = BTreeMap<String, ElasticsearchIndexAliases>;
+
+#[derive(Debug, Deserialize)]
+pub struct ElasticsearchIndexAliases {
+    #[serde(default)]
+    pub aliases: Map<String, Value>,
+}
+
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
 pub struct ElasticsearchIndexInfo {
@@ -155,21 +179,8 @@ pub struct ElasticsearchError {
 }
 
 #[derive(Debug, Deserialize)]
-#[allow(dead_code)]
-pub struct ElasticsearchScrollResponse {
-    #[serde(rename = "_scroll_id")]
-    pub scroll_id: String,
-    pub took: u32,
-    pub timed_out: bool,
-    pub hits: ElasticsearchHits,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub aggregations: Option<Value>,
-}
-
-#[derive(Debug, Serialize)]
-pub struct ScrollRequest {
-    pub scroll: String,
-    pub scroll_id: String,
+pub struct ElasticsearchPointInTime {
+    pub id: String,
 }
 
 impl ElasticsearchApi {
@@ -251,6 +262,42 @@ impl ElasticsearchApi {
         }
     }
 
+    /// Returns the aliases of each index, only the given alias if any
+    pub fn get_aliases(&self, alias: Option<&str>) -> Result<ElasticsearchAliases, SearchError> {
+        trace!("Getting aliases: {alias:?}");
+
+        let url = match alias {
+            Some(alias) => format!("{}/_alias/{}", self.base_url, alias),
+            None => format!("{}/_alias", self.base_url),
+        };
+
+        let response = self
+            .create_request(Method::GET, &url)
+            .send()
+            .map_err(|e| internal_error(format!("Failed to get aliases: {e}")))?;
+
+        parse_response(response)
+    }
+
+    /// Applies the alias actions atomically
+    pub fn update_aliases(&self, actions: Vec<Value>) -> Result<(), SearchError> {
+        trace!("Updating aliases with {} actions", actions.len());
+
+        let url = format!("{}/_aliases", self.base_url);
+
+        let response = self
+            .create_request(Method::POST, &url)
+            .json(&serde_json::json!({ "actions": actions }))
+            .send()
+            .map_err(|e| internal_error(format!("Failed to update aliases: {e}")))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(search_error_from_status(response.status()))
+        }
+    }
+
     pub fn list_indices(&self) -> Result<Vec<ElasticsearchIndexInfo>, SearchError> {
         trace!("Listing indices");
 
@@ -269,10 +316,17 @@ impl ElasticsearchApi {
         index_name: &str,
         id: &str,
         document: &Value,
+        wait_for_refresh: bool,
     ) -> Result<(), SearchError> {
         trace!("Indexing document {id} in index: {index_name}");
 
-        let url = format!("{}/{}/_doc/{}", self.base_url, index_name, id);
+        let url = format!(
+            "{}/{}/_doc/{}{}",
+            self.base_url,
+            index_name,
+            id,
+            refresh_param(wait_for_refresh)
+        );
 
         let response = self
             .create_request(Method::PUT, &url)
@@ -281,17 +335,20 @@ impl ElasticsearchApi {
             .map_err(|e| internal_error(format!("Failed to index document: {e}")))?;
 
         if response.status().is_success() {
-            self.refresh_index(index_name)?;
             Ok(())
         } else {
             Err(search_error_from_status(response.status()))
         }
     }
 
-    pub fn bulk_index(&self, operations: &str) -> Result<ElasticsearchBulkResponse, SearchError> {
+    pub fn bulk_index(
+        &self,
+        operations: &str,
+        wait_for_refresh: bool,
+    ) -> Result<ElasticsearchBulkResponse, SearchError> {
         trace!("Performing bulk index operation");
 
-        let url = format!("{}/_bulk", self.base_url);
+        let url = format!("{}/_bulk{}", self.base_url, refresh_param(wait_for_refresh));
 
         // Building request without create_request to avoid Content-Type conflicts
         let mut builder = self
@@ -314,10 +371,21 @@ impl ElasticsearchApi {
         parse_response(response)
     }
 
-    pub fn delete_document(&self, index_name: &str, id: &str) -> Result<(), SearchError> {
+    pub fn delete_document(
+        &self,
+        index_name: &str,
+        id: &str,
+        wait_for_refresh: bool,
+    ) -> Result<(), SearchError> {
         trace!("Deleting document {id} from index: {index_name}");
 
-        let url = format!("{}/{}/_doc/{}", self.base_url, index_name, id);
+        let url = format!(
+            "{}/{}/_doc/{}{}",
+            self.base_url,
+            index_name,
+            id,
+            refresh_param(wait_for_refresh)
+        );
 
         let response = self
             .create_request(Method::DELETE, &url)
@@ -374,72 +442,66 @@ impl ElasticsearchApi {
         parse_response(response)
     }
 
-    pub fn search_with_scroll(
+    pub fn open_point_in_time(
         &self,
         index_name: &str,
-        query: &ElasticsearchQuery,
-        scroll_timeout: &str,
-    ) -> Result<ElasticsearchScrollResponse, SearchError> {
-        trace!("Searching index {index_name} with scroll, timeout: {scroll_timeout}");
+        keep_alive: &str,
+    ) -> Result<String, SearchError> {
+        trace!("Opening point in time on index {index_name}, keep alive: {keep_alive}");
 
         let url = format!(
-            "{}/{}/_search?scroll={}",
-            self.base_url, index_name, scroll_timeout
+            "{}/{}/_pit?keep_alive={}",
+            self.base_url, index_name, keep_alive
         );
 
         let response = self
             .create_request(Method::POST, &url)
-            .json(query)
             .send()
-            .map_err(|e| internal_error(format!("Failed to search with scroll: {e}")))?;
+            .map_err(|e| internal_error(format!("Failed to open point in time: {e}")))?;
 
-        parse_response(response)
+        parse_response::<ElasticsearchPointInTime>(response).map(|pit| pit.id)
     }
 
-    pub fn scroll(
+    /// Searches a point in time, the query must not target an index. Returns `None` if the point
+    /// in time has expired.
+    pub fn search_point_in_time(
         &self,
-        scroll_id: &str,
-        scroll_timeout: &str,
-    ) -> Result<ElasticsearchScrollResponse, SearchError> {
-        trace!("Continuing scroll with ID: {scroll_id}, timeout: {scroll_timeout}");
-
-        let url = format!("{}/_search/scroll", self.base_url);
+        query: &ElasticsearchQuery,
+    ) -> Result<Option<ElasticsearchSearchResponse>, SearchError> {
+        trace!("Searching point in time with query: {query:?}");
 
-        let scroll_request = ScrollRequest {
-            scroll: scroll_timeout.to_string(),
-            scroll_id: scroll_id.to_string(),
-        };
+        let url = format!("{}/_search", self.base_url);
 
         let response = self
             .create_request(Method::POST, &url)
-            .json(&scroll_request)
+            .json(query)
             .send()
-            .map_err(|e| internal_error(format!("Failed to continue scroll: {e}")))?;
+            .map_err(|e| internal_error(format!("Failed to search point in time: {e}")))?;
 
-        parse_response(response)
+        parse_point_in_time_response(response)
     }
 
-    pub fn clear_scroll(&self, scroll_id: &str) -> Result<(), SearchError> {
-        trace!("Clearing scroll with ID: {scroll_id}");
+    pub fn close_point_in_time(&self, pit_id: &str) -> Result<(), SearchError> {
+        trace!("Closing point in time with ID: {pit_id}");
 
-        let url = format!("{}/_search/scroll", self.base_url);
+        let url = format!("{}/_pit", self.base_url);
 
-        let clear_request = serde_json::json!({
-            "scroll_id": scroll_id
+        let close_request = serde_json::json!({
+            "id": pit_id
         });
 
         let response = self
             .create_request(Method::DELETE, &url)
-            .json(&clear_request)
+            .json(&close_request)
             .send()
-            .map_err(|e| internal_error(format!("Failed to clear scroll: {e}")))?;
+            .map_err(|e| internal_error(format!("Failed to close point in time: {e}")))?;
 
         if response.status().is_success() {
             Ok(())
         } else {
             trace!(
-                "Failed to clear scroll {}: {}",
-                scroll_id,
+                "Failed to close point in time {}: {}",
+                pit_id,
                 response.status()
             );
             Ok(())
@@ -480,23 +542,35 @@ impl ElasticsearchApi {
             Err(search_error_from_status(response.status()))
         }
     }
+}
 
-    pub fn refresh_index(&self, index_name: &str) -> Result<(), SearchError> {
-        trace!("Refreshing index: {index_name}");
+/// `refresh=wait_for` returns once the write is visible to searches, without forcing a refresh
+fn refresh_param(wait_for_refresh: bool) -> &'static str {
+    if wait_for_refresh {
+        "?refresh=wait_for"
+    } else {
+        ""
+    }
+}
 
-        let url = format!("{}/{}/_refresh", self.base_url, index_name);
+/// Expired points in time are reported with a `404` and a `search_context_missing_exception`
+fn parse_point_in_time_response<T: DeserializeOwned + Debug>(
+    response: Response,
+) -> Result<Option<T>, SearchError> {
+    if response.status() != StatusCode::NOT_FOUND {
+        return parse_response(response).map(Some);
+    }
 
-        let response = self
-            .create_request(Method::POST, &url)
-            .send()
-            .map_err(|e| internal_error(format!("Failed to refresh index: {e}")))?;
+    let error_body = response
+        .text()
+        .map_err(|err| from_reqwest_error("Failed to receive error response body", err))?;
 
-        if response.status().is_success() {
-            Ok(())
-        } else {
-            // Refreshing is not critical, so we can ignore errors
-            Ok(())
-        }
+    trace!("Received 404 response from Elasticsearch API: {error_body:?}");
+
+    if error_body.contains("search_context_missing_exception") {
+        Ok(None)
+    } else {
+        Err(search_error_from_status(StatusCode::NOT_FOUND))
     }
 }
 
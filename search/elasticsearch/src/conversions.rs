@@ -1,10 +1,26 @@
 use crate::client::{
-    ElasticsearchHit, ElasticsearchMappings, ElasticsearchQuery, ElasticsearchSearchResponse,
-    ElasticsearchSettings,
+    ElasticsearchAliases, ElasticsearchBulkResponse, ElasticsearchHit, ElasticsearchMappings,
+    ElasticsearchQuery, ElasticsearchSearchResponse, ElasticsearchSettings,
 };
+use golem_search::bulk::{is_retryable_status, ItemOutcome};
+use golem_search::error::invalid_query;
+use golem_search::facets::{facet_field, facet_requests, range_label, DEFAULT_FACET_SIZE};
+use golem_search::filter::{resolve_filter, Filter};
 use golem_search::golem::search::types::{
-    Doc, FieldType, Schema, SchemaField, SearchHit, SearchQuery, SearchResults,
+    CalendarInterval, Doc, FacetBucket, FacetOrder, FacetRequest, FacetResult, FacetStats,
+    FieldHighlight, FieldType, FilterValue, HighlightConfig, IndexAlias, Schema, SchemaChangeKind,
+    SchemaField, SearchError, SearchHit, SearchQuery, SearchResults, VectorOptions,
+    VectorSimilarity,
 };
+use golem_search::highlights::{
+    field_highlight, highlight_tags, validate_highlight_config, DEFAULT_SNIPPET_LENGTH,
+};
+use golem_search::schema::{
+    impossible, in_place, language_analyzer, needs_reindex, validate_field, Classification,
+    SchemaDiff,
+};
+use golem_search::vector::{validate_vector_query, vector_options};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Value};
 
 pub fn doc_to_elasticsearch_document(doc: Doc) -> Result<Value, String> {
@@ -40,15 +56,24 @@ pub fn elasticsearch_document_to_doc(id: String, source: Value) -> Doc {
     Doc { id, content }
 }
 
-pub fn search_query_to_elasticsearch_query(query: SearchQuery) -> ElasticsearchQuery {
+/// Average length of a word with its separator, to size the snippet fragments
+const CHARACTERS_PER_WORD: u32 = 6;
+
+pub fn search_query_to_elasticsearch_query(
+    query: SearchQuery,
+) -> Result<ElasticsearchQuery, SearchError> {
+    let facets = facet_requests(&query)?;
     let mut es_query = ElasticsearchQuery {
         query: None,
+        knn: None,
         from: query.offset,
         size: query.per_page,
         sort: None,
         highlight: None,
         aggs: None,
         _source: None,
+        pit: None,
+        search_after: None,
     };
 
     let mut bool_query = json!({
@@ -74,55 +99,60 @@ pub fn search_query_to_elasticsearch_query(query: SearchQuery) -> ElasticsearchQ
     }
 
     for filter in query.filters {
-        if let Ok(filter_value) = serde_json::from_str::<Value>(&filter) {
-            // JSON filter
-            bool_query["bool"]["filter"]
-                .as_array_mut()
-                .unwrap()
-                .push(filter_value);
-        } else if filter.contains(':') {
-            let parts: Vec<&str> = filter.splitn(2, ':').collect();
-            if parts.len() == 2 {
-                let field = parts[0].trim();
-                let value = parts[1].trim();
-                bool_query["bool"]["filter"]
-                    .as_array_mut()
-                    .unwrap()
-                    .push(json!({
-                        "term": {
-                            field: value
-                        }
-                    }));
-            }
-        } else if filter.contains('=') {
-            let parts: Vec<&str> = filter.splitn(2, '=').collect();
-            if parts.len() == 2 {
-                let field = parts[0].trim();
-                let value = parts[1].trim().trim_matches('"').trim_matches('\'');
-                bool_query["bool"]["filter"]
-                    .as_array_mut()
-                    .unwrap()
-                    .push(json!({
-                        "term": {
-                            field: value
-                        }
-                    }));
-            }
-        } else {
-            bool_query["bool"]["filter"]
-                .as_array_mut()
-                .unwrap()
-                .push(json!({
-                    "term": {
-                        "status": filter
+        // Filters are either query DSL JSON, exact `field:value` or `field=value` matches, or
+        // Lucene query string syntax
+        let filter = match serde_json::from_str::<Value>(&filter) {
+            Ok(filter_value) if filter_value.is_object() => filter_value,
+            _ => term_filter(&filter).unwrap_or_else(|| {
+                json!({
+                    "query_string": {
+                        "query": filter
                     }
-                }));
-        }
+                })
+            }),
+        };
+        bool_query["bool"]["filter"]
+            .as_array_mut()
+            .unwrap()
+            .push(filter);
     }
 
-    if !bool_query["bool"]["must"].as_array().unwrap().is_empty()
-        || !bool_query["bool"]["filter"].as_array().unwrap().is_empty()
-    {
+    if let Some(expression) = &query.filter {
+        let filter = resolve_filter(expression)?;
+        bool_query["bool"]["filter"]
+            .as_array_mut()
+            .unwrap()
+            .push(filter_to_elasticsearch_query(&filter));
+    }
+
+    let has_text = !bool_query["bool"]["must"].as_array().unwrap().is_empty();
+    if let Some(vector_query) = &query.vector {
+        let semantic_ratio = validate_vector_query(vector_query)?;
+        let mut filters = bool_query["bool"]["filter"].as_array().unwrap().clone();
+        if let Some(expression) = &vector_query.filter {
+            filters.push(filter_to_elasticsearch_query(&resolve_filter(expression)?));
+        }
+        let num_candidates = vector_query
+            .k
+            .saturating_mul(10)
+            .clamp(100, 10_000)
+            .max(vector_query.k);
+        let mut knn = json!({
+            "field": vector_query.field,
+            "query_vector": vector_query.vector,
+            "k": vector_query.k,
+            "num_candidates": num_candidates,
+            "filter": filters
+        });
+        // Without a text query, the nearest neighbours are the only hits
+        if has_text {
+            knn["boost"] = json!(semantic_ratio);
+            bool_query["bool"]["boost"] = json!(1.0 - semantic_ratio);
+            es_query.query = Some(bool_query);
+        }
+        es_query.knn = Some(knn);
+        es_query.size = es_query.size.or(Some(vector_query.k));
+    } else if has_text || !bool_query["bool"]["filter"].as_array().unwrap().is_empty() {
         es_query.query = Some(bool_query);
     } else {
         es_query.query = Some(json!({
@@ -160,19 +190,29 @@ pub fn search_query_to_elasticsearch_query(query: SearchQuery) -> ElasticsearchQ
     }
 
     if let Some(highlight_config) = query.highlight {
+        validate_highlight_config(&highlight_config)?;
+        let (pre_tag, post_tag) = highlight_tags(&highlight_config);
         let mut highlight = json!({
-            "fields": {}
+            "fields": {},
+            "pre_tags": [pre_tag],
+            "post_tags": [post_tag]
         });
 
-        for field in highlight_config.fields {
+        for field in &highlight_config.fields {
             highlight["fields"][field] = json!({});
         }
 
-        if let Some(pre_tag) = highlight_config.pre_tag {
-            highlight["pre_tags"] = json!([pre_tag]);
-        }
-        if let Some(post_tag) = highlight_config.post_tag {
-            highlight["post_tags"] = json!([post_tag]);
+        // Fragments are sized in characters, rather than in words
+        let snippet_length = highlight_config
+            .snippet_length
+            .unwrap_or(DEFAULT_SNIPPET_LENGTH)
+            .saturating_mul(CHARACTERS_PER_WORD);
+        for field in &highlight_config.snippet_fields {
+            let mut snippet = json!({ "fragment_size": snippet_length });
+            if let Some(max_snippets) = highlight_config.max_snippets {
+                snippet["number_of_fragments"] = json!(max_snippets);
+            }
+            highlight["fields"][field] = snippet;
         }
 
         if let Some(max_length) = highlight_config.max_length {
@@ -182,17 +222,15 @@ pub fn search_query_to_elasticsearch_query(query: SearchQuery) -> ElasticsearchQ
         es_query.highlight = Some(highlight);
     }
 
-    if !query.facets.is_empty() {
-        let mut aggs = json!({});
-        for facet in query.facets {
-            aggs[&facet] = json!({
-                "terms": {
-                    "field": format!("{}.keyword", facet),
-                    "size": 10
-                }
-            });
+    if !facets.is_empty() {
+        let mut aggs = Map::new();
+        for (index, facet) in facets.iter().enumerate() {
+            aggs.insert(
+                format!("facet_{index}"),
+                facet_to_elasticsearch_aggregation(facet, index < query.facets.len()),
+            );
         }
-        es_query.aggs = Some(aggs);
+        es_query.aggs = Some(Value::Object(aggs));
     }
 
     if let Some(config) = query.config {
@@ -219,17 +257,244 @@ pub fn search_query_to_elasticsearch_query(query: SearchQuery) -> ElasticsearchQ
         }
     }
 
-    es_query
+    Ok(es_query)
+}
+
+/// Compiles a provider-neutral filter to the query DSL, every operator is supported
+/// Translates a `field:value` or `field=value` filter to an exact `term` query. Values using the
+/// Lucene syntax, such as ranges, wildcards or boolean operators, are left to `query_string`.
+fn term_filter(filter: &str) -> Option<Value> {
+    let is_field = |field: &str| {
+        !field.is_empty()
+            && field
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | '@'))
+    };
+
+    let (field, value) = match filter.split_once(':') {
+        Some((field, value)) => {
+            let value = value.trim();
+            let is_plain = !value.is_empty()
+                && !value.starts_with(['+', '-'])
+                && !value
+                    .chars()
+                    .any(|c| c.is_whitespace() || "&|!(){}[]^\"~*?:/<>=\\".contains(c));
+            if !is_plain {
+                return None;
+            }
+            (field.trim(), value)
+        }
+        // `=` is not part of the query string syntax
+        None => {
+            let (field, value) = filter.split_once('=')?;
+            (
+                field.trim(),
+                value.trim().trim_matches('"').trim_matches('\''),
+            )
+        }
+    };
+    if !is_field(field) {
+        return None;
+    }
+
+    Some(json!({
+        "term": {
+            field: value
+        }
+    }))
+}
+
+pub fn filter_to_elasticsearch_query(filter: &Filter) -> Value {
+    match filter {
+        Filter::Eq(field, value) => json!({ "term": { field: filter_value_to_json(value) } }),
+        Filter::Ne(field, value) => json!({
+            "bool": { "must_not": [{ "term": { field: filter_value_to_json(value) } }] }
+        }),
+        Filter::Range(field, bounds) => {
+            let mut range = Map::new();
+            for (name, bound) in [
+                ("gt", &bounds.gt),
+                ("gte", &bounds.gte),
+                ("lt", &bounds.lt),
+                ("lte", &bounds.lte),
+            ] {
+                if let Some(bound) = bound {
+                    range.insert(name.to_string(), filter_value_to_json(bound));
+                }
+            }
+            json!({ "range": { field: range } })
+        }
+        Filter::In(field, values) => json!({
+            "terms": { field: values.iter().map(filter_value_to_json).collect::<Vec<_>>() }
+        }),
+        Filter::Exists(field) => json!({ "exists": { "field": field } }),
+        Filter::Prefix(field, prefix) => json!({ "prefix": { field: prefix } }),
+        Filter::GeoRadius(radius) => json!({
+            "geo_distance": {
+                "distance": format!("{}m", radius.radius_meters),
+                &radius.field: { "lat": radius.center.lat, "lon": radius.center.lon }
+            }
+        }),
+        Filter::GeoBoundingBox(bbox) => json!({
+            "geo_bounding_box": {
+                &bbox.field: {
+                    "top_left": { "lat": bbox.top_left.lat, "lon": bbox.top_left.lon },
+                    "bottom_right": { "lat": bbox.bottom_right.lat, "lon": bbox.bottom_right.lon }
+                }
+            }
+        }),
+        Filter::And(filters) => json!({
+            "bool": { "filter": filters.iter().map(filter_to_elasticsearch_query).collect::<Vec<_>>() }
+        }),
+        Filter::Or(filters) => json!({
+            "bool": {
+                "should": filters.iter().map(filter_to_elasticsearch_query).collect::<Vec<_>>(),
+                "minimum_should_match": 1
+            }
+        }),
+        Filter::Not(filter) => json!({
+            "bool": { "must_not": [filter_to_elasticsearch_query(filter)] }
+        }),
+    }
+}
+
+fn filter_value_to_json(value: &FilterValue) -> Value {
+    match value {
+        FilterValue::Text(text) => json!(text),
+        FilterValue::Integer(integer) => json!(integer),
+        FilterValue::Float(float) => json!(float),
+        FilterValue::Boolean(boolean) => json!(boolean),
+    }
+}
+
+/// Compiles a facet to an aggregation named `facet_<index>`. The plain `facets` fields aggregate
+/// the `keyword` sub-field that text fields are mapped with.
+fn facet_to_elasticsearch_aggregation(facet: &FacetRequest, keyword_subfield: bool) -> Value {
+    match facet {
+        FacetRequest::Terms(terms) => {
+            let field = if keyword_subfield {
+                format!("{}.keyword", terms.field)
+            } else {
+                terms.field.clone()
+            };
+            let order = match terms.order {
+                FacetOrder::Count => json!({ "_count": "desc" }),
+                FacetOrder::Value => json!({ "_key": "asc" }),
+            };
+            json!({
+                "terms": {
+                    "field": field,
+                    "size": terms.size.unwrap_or(DEFAULT_FACET_SIZE),
+                    "order": order
+                }
+            })
+        }
+        FacetRequest::Range(range) => {
+            let ranges = range
+                .ranges
+                .iter()
+                .map(|bounds| {
+                    let mut range = Map::new();
+                    if let Some(from) = bounds.from {
+                        range.insert("from".to_string(), json!(from));
+                    }
+                    if let Some(to) = bounds.to {
+                        range.insert("to".to_string(), json!(to));
+                    }
+                    Value::Object(range)
+                })
+                .collect::<Vec<_>>();
+            json!({ "range": { "field": range.field, "ranges": ranges } })
+        }
+        FacetRequest::DateHistogram(histogram) => {
+            let interval = match histogram.interval {
+                CalendarInterval::Minute => "minute",
+                CalendarInterval::Hour => "hour",
+                CalendarInterval::Day => "day",
+                CalendarInterval::Week => "week",
+                CalendarInterval::Month => "month",
+                CalendarInterval::Quarter => "quarter",
+                CalendarInterval::Year => "year",
+            };
+            json!({
+                "date_histogram": { "field": histogram.field, "calendar_interval": interval }
+            })
+        }
+        FacetRequest::Stats(field) => json!({ "stats": { "field": field } }),
+    }
+}
+
+fn elasticsearch_aggregation_to_facet_result(
+    facet: &FacetRequest,
+    aggregation: &Value,
+) -> FacetResult {
+    let buckets = aggregation
+        .get("buckets")
+        .and_then(|buckets| buckets.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let doc_count = |bucket: &Value| {
+        bucket
+            .get("doc_count")
+            .and_then(|c| c.as_u64())
+            .unwrap_or(0)
+    };
+
+    match facet {
+        FacetRequest::Range(range) => FacetResult {
+            field: range.field.clone(),
+            buckets: range
+                .ranges
+                .iter()
+                .zip(buckets)
+                .map(|(bounds, bucket)| FacetBucket {
+                    value: range_label(bounds),
+                    count: doc_count(bucket),
+                    range: Some(bounds.clone()),
+                })
+                .collect(),
+            stats: None,
+        },
+        FacetRequest::Stats(field) => FacetResult {
+            field: field.clone(),
+            buckets: vec![],
+            stats: Some(FacetStats {
+                count: aggregation.get("count").and_then(|c| c.as_u64()),
+                min: aggregation.get("min").and_then(|m| m.as_f64()),
+                max: aggregation.get("max").and_then(|m| m.as_f64()),
+                avg: aggregation.get("avg").and_then(|a| a.as_f64()),
+                sum: aggregation.get("sum").and_then(|s| s.as_f64()),
+            }),
+        },
+        FacetRequest::Terms(_) | FacetRequest::DateHistogram(_) => FacetResult {
+            field: facet_field(facet).to_string(),
+            buckets: buckets
+                .iter()
+                .map(|bucket| FacetBucket {
+                    value: match bucket.get("key_as_string").or_else(|| bucket.get("key")) {
+                        Some(Value::String(key)) => key.clone(),
+                        Some(key) => key.to_string(),
+                        None => String::new(),
+                    },
+                    count: doc_count(bucket),
+                    range: None,
+                })
+                .collect(),
+            stats: None,
+        },
+    }
 }
 
 pub fn elasticsearch_response_to_search_results(
     response: ElasticsearchSearchResponse,
+    facets: &[FacetRequest],
+    highlight: Option<&HighlightConfig>,
 ) -> SearchResults {
     let hits = response
         .hits
         .hits
         .into_iter()
-        .map(elasticsearch_hit_to_search_hit)
+        .map(|hit| elasticsearch_hit_to_search_hit(hit, highlight))
         .collect();
 
     let total = match response.hits.total.relation.as_str() {
@@ -238,6 +503,24 @@ pub fn elasticsearch_response_to_search_results(
         _ => None,
     };
 
+    let facet_results = response
+        .aggregations
+        .as_ref()
+        .map(|aggs| {
+            facets
+                .iter()
+                .enumerate()
+                .filter_map(|(index, facet)| {
+                    let aggregation = aggs.get(format!("facet_{index}"))?;
+                    Some(elasticsearch_aggregation_to_facet_result(
+                        facet,
+                        aggregation,
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
     SearchResults {
         total,
         page: None, // Elasticsearch uses from/size, not page-based pagination
@@ -246,15 +529,26 @@ pub fn elasticsearch_response_to_search_results(
         facets: response
             .aggregations
             .map(|aggs| serde_json::to_string(&aggs).unwrap_or_else(|_| "{}".to_string())),
+        facet_results,
         took_ms: Some(response.took),
     }
 }
 
-fn elasticsearch_hit_to_search_hit(hit: ElasticsearchHit) -> SearchHit {
+fn elasticsearch_hit_to_search_hit(
+    hit: ElasticsearchHit,
+    highlight: Option<&HighlightConfig>,
+) -> SearchHit {
     let content = hit
         .source
         .map(|source| serde_json::to_string(&source).unwrap_or_else(|_| "{}".to_string()));
 
+    let highlight_fields = match (highlight, &hit.highlight) {
+        (Some(config), Some(Value::Object(fields))) => {
+            elasticsearch_highlight_to_field_highlights(fields, config)
+        }
+        _ => vec![],
+    };
+
     let highlights = hit
         .highlight
         .map(|highlight| serde_json::to_string(&highlight).unwrap_or_else(|_| "{}".to_string()));
@@ -264,13 +558,42 @@ fn elasticsearch_hit_to_search_hit(hit: ElasticsearchHit) -> SearchHit {
         score: hit.score,
         content,
         highlights,
+        highlight_fields,
     }
 }
 
-pub fn schema_to_elasticsearch_settings(schema: Schema) -> ElasticsearchSettings {
+/// Each highlighted field holds a list of fragments
+fn elasticsearch_highlight_to_field_highlights(
+    fields: &Map<String, Value>,
+    config: &HighlightConfig,
+) -> Vec<FieldHighlight> {
+    fields
+        .iter()
+        .map(|(field, fragments)| {
+            let fragments = fragments
+                .as_array()
+                .map(|fragments| {
+                    fragments
+                        .iter()
+                        .filter_map(|fragment| fragment.as_str())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            field_highlight(field.as_str(), &fragments, config)
+        })
+        .collect()
+}
+
+pub fn schema_to_elasticsearch_settings(
+    schema: Schema,
+) -> Result<ElasticsearchSettings, SearchError> {
     let mut properties = Map::new();
+    let mut excludes = Vec::new();
+    let mut arrays = Vec::new();
+    let mut locales = Map::new();
 
     for field in schema.fields {
+        validate_field(&field)?;
         let mut field_mapping = Map::new();
 
         match field.field_type {
@@ -286,6 +609,9 @@ pub fn schema_to_elasticsearch_settings(schema: Schema) -> ElasticsearchSettings
                         }
                     }),
                 );
+                if let Some(analyzer) = text_analyzer(&field)? {
+                    field_mapping.insert("analyzer".to_string(), Value::String(analyzer));
+                }
             }
             FieldType::Keyword => {
                 field_mapping.insert("type".to_string(), Value::String("keyword".to_string()));
@@ -305,13 +631,46 @@ pub fn schema_to_elasticsearch_settings(schema: Schema) -> ElasticsearchSettings
             FieldType::GeoPoint => {
                 field_mapping.insert("type".to_string(), Value::String("geo_point".to_string()));
             }
+            FieldType::GeoShape => {
+                field_mapping.insert("type".to_string(), Value::String("geo_shape".to_string()));
+            }
+            FieldType::Vector => {
+                let vector = vector_options(&field)?;
+                let similarity = match vector.similarity {
+                    VectorSimilarity::Cosine => "cosine",
+                    VectorSimilarity::DotProduct => "dot_product",
+                    VectorSimilarity::Euclidean => "l2_norm",
+                };
+                field_mapping.insert(
+                    "type".to_string(),
+                    Value::String("dense_vector".to_string()),
+                );
+                field_mapping.insert("dims".to_string(), json!(vector.dimensions));
+                field_mapping.insert("similarity".to_string(), json!(similarity));
+            }
+            FieldType::Object => {
+                field_mapping.insert("type".to_string(), Value::String("object".to_string()));
+            }
+            FieldType::Nested => {
+                field_mapping.insert("type".to_string(), Value::String("nested".to_string()));
+            }
         }
 
-        if !field.index {
+        // Objects have no `index` parameter, their sub-fields are indexed on their own
+        if !field.index && !matches!(field.field_type, FieldType::Object | FieldType::Nested) {
             field_mapping.insert("index".to_string(), Value::Bool(false));
         }
+        if !field.stored {
+            excludes.push(Value::String(field.name.clone()));
+        }
+        if field.array {
+            arrays.push(Value::String(field.name.clone()));
+        }
+        if let Some(locale) = &field.locale {
+            locales.insert(field.name.clone(), Value::String(locale.clone()));
+        }
 
-        properties.insert(field.name, Value::Object(field_mapping));
+        insert_property(&mut properties, &field.name, field_mapping);
     }
 
     properties.insert(
@@ -327,53 +686,94 @@ pub fn schema_to_elasticsearch_settings(schema: Schema) -> ElasticsearchSettings
         }),
     );
 
+    // Any field can hold a list in Elasticsearch, and locales only pick an analyzer, so both are
+    // kept in the mapping metadata for the schema to be read back
     let mappings = ElasticsearchMappings {
         properties: Some(properties),
         dynamic: Some(true),
+        source: (!excludes.is_empty()).then(|| json!({ "excludes": excludes })),
+        meta: Some(json!({ "arrays": arrays, "locales": locales })),
     };
 
-    ElasticsearchSettings {
+    Ok(ElasticsearchSettings {
         mappings: Some(mappings),
         settings: None,
+    })
+}
+
+fn text_analyzer(field: &SchemaField) -> Result<Option<String>, SearchError> {
+    match (&field.analyzer, &field.locale) {
+        (Some(analyzer), _) => Ok(Some(analyzer.clone())),
+        (None, Some(locale)) => language_analyzer(locale)
+            .map(|analyzer| Some(analyzer.to_string()))
+            .ok_or_else(|| {
+                invalid_query(format!(
+                    "Elasticsearch has no language analyzer for the locale {locale} of {}, set its analyzer",
+                    field.name
+                ))
+            }),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Inserts the mapping of a field under the properties of its parent objects, whether the parents
+/// are declared before or after their sub-fields
+fn insert_property(properties: &mut Map<String, Value>, name: &str, mapping: Map<String, Value>) {
+    match name.split_once('.') {
+        Some((parent, sub_field)) => {
+            let parent = properties
+                .entry(parent.to_string())
+                .or_insert_with(|| json!({ "type": "object" }));
+            if let Some(sub_properties) = parent
+                .as_object_mut()
+                .map(|parent| parent.entry("properties").or_insert_with(|| json!({})))
+                .and_then(|sub_properties| sub_properties.as_object_mut())
+            {
+                insert_property(sub_properties, sub_field, mapping);
+            }
+        }
+        None => match properties.get_mut(name).and_then(|p| p.as_object_mut()) {
+            Some(existing) => existing.extend(mapping),
+            None => {
+                properties.insert(name.to_string(), Value::Object(mapping));
+            }
+        },
     }
 }
 
 pub fn elasticsearch_mappings_to_schema(mappings: Value, index_name: &str) -> Schema {
     let mut fields = Vec::new();
 
-    if let Some(index_mappings) = mappings.get(index_name) {
-        if let Some(properties) = index_mappings
-            .get("mappings")
-            .and_then(|m| m.get("properties"))
-            .and_then(|p| p.as_object())
-        {
-            for (field_name, field_def) in properties {
-                if let Some(field_type_str) = field_def.get("type").and_then(|t| t.as_str()) {
-                    let field_type = match field_type_str {
-                        "text" => FieldType::Text,
-                        "keyword" => FieldType::Keyword,
-                        "integer" | "long" | "short" | "byte" => FieldType::Integer,
-                        "float" | "double" | "half_float" | "scaled_float" => FieldType::Float,
-                        "boolean" => FieldType::Boolean,
-                        "date" => FieldType::Date,
-                        "geo_point" => FieldType::GeoPoint,
-                        _ => FieldType::Text,
-                    };
+    if let Some(index_mappings) = mappings.get(index_name).and_then(|m| m.get("mappings")) {
+        if let Some(properties) = index_mappings.get("properties").and_then(|p| p.as_object()) {
+            mapped_fields(properties, "", &mut fields);
+        }
 
-                    let index = field_def
-                        .get("index")
-                        .and_then(|i| i.as_bool())
-                        .unwrap_or(true);
-
-                    fields.push(SchemaField {
-                        name: field_name.clone(),
-                        field_type,
-                        required: false, // Elasticsearch doesn't have required fields in mappings
-                        facet: field_type == FieldType::Keyword, // Keywords can be used for faceting
-                        sort: field_type != FieldType::Text, // Text fields typically can't be sorted directly
-                        index,
-                    });
-                }
+        let names = |value: Option<&Value>| -> Vec<String> {
+            value
+                .and_then(|v| v.as_array())
+                .map(|names| {
+                    names
+                        .iter()
+                        .filter_map(|name| name.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let excludes = names(index_mappings.pointer("/_source/excludes"));
+        let arrays = names(index_mappings.pointer("/_meta/arrays"));
+        let locales = index_mappings.pointer("/_meta/locales");
+
+        for field in &mut fields {
+            field.stored = !excludes.contains(&field.name);
+            field.array = arrays.contains(&field.name);
+            field.locale = locales
+                .and_then(|l| l.get(&field.name))
+                .and_then(|l| l.as_str())
+                .map(str::to_string);
+            // The analyzer picked from the locale isn't part of the schema
+            if field.locale.as_deref().and_then(language_analyzer) == field.analyzer.as_deref() {
+                field.analyzer = None;
             }
         }
     }
@@ -384,6 +784,107 @@ pub fn elasticsearch_mappings_to_schema(mappings: Value, index_name: &str) -> Sc
     }
 }
 
+/// Lists the mapped fields, each object followed by its sub-fields named `<object>.<sub-field>`
+fn mapped_fields(properties: &Map<String, Value>, prefix: &str, fields: &mut Vec<SchemaField>) {
+    for (field_name, field_def) in properties {
+        let name = format!("{prefix}{field_name}");
+        let sub_properties = field_def.get("properties").and_then(|p| p.as_object());
+        let field_type = match field_def.get("type").and_then(|t| t.as_str()) {
+            Some(field_type_str) => match field_type_str {
+                "text" => FieldType::Text,
+                "keyword" => FieldType::Keyword,
+                "integer" | "long" | "short" | "byte" => FieldType::Integer,
+                "float" | "double" | "half_float" | "scaled_float" => FieldType::Float,
+                "boolean" => FieldType::Boolean,
+                "date" => FieldType::Date,
+                "geo_point" => FieldType::GeoPoint,
+                "geo_shape" | "shape" => FieldType::GeoShape,
+                "dense_vector" => FieldType::Vector,
+                "object" => FieldType::Object,
+                "nested" => FieldType::Nested,
+                _ => FieldType::Text,
+            },
+            // Elasticsearch leaves out the type of objects
+            None if sub_properties.is_some() => FieldType::Object,
+            None => continue,
+        };
+        let vector = (field_type == FieldType::Vector).then(|| VectorOptions {
+            dimensions: field_def
+                .get("dims")
+                .and_then(|d| d.as_u64())
+                .unwrap_or_default() as u32,
+            similarity: match field_def.get("similarity").and_then(|s| s.as_str()) {
+                Some("dot_product") | Some("max_inner_product") => VectorSimilarity::DotProduct,
+                Some("l2_norm") => VectorSimilarity::Euclidean,
+                _ => VectorSimilarity::Cosine,
+            },
+        });
+
+        let index = field_def
+            .get("index")
+            .and_then(|i| i.as_bool())
+            .unwrap_or(true);
+
+        fields.push(SchemaField {
+            name: name.clone(),
+            field_type,
+            required: false, // Elasticsearch doesn't have required fields in mappings
+            facet: field_type == FieldType::Keyword, // Keywords can be used for faceting
+            sort: !matches!(
+                field_type,
+                FieldType::Text
+                    | FieldType::Vector
+                    | FieldType::GeoShape
+                    | FieldType::Object
+                    | FieldType::Nested
+            ), // Text, shape, vector and object fields can't be sorted
+            index,
+            array: false,
+            stored: true,
+            analyzer: field_def
+                .get("analyzer")
+                .and_then(|a| a.as_str())
+                .map(str::to_string),
+            locale: None,
+            vector,
+        });
+
+        if let Some(sub_properties) = sub_properties {
+            mapped_fields(sub_properties, &format!("{name}."), fields);
+        }
+    }
+}
+
+/// Mappings only accept new fields: a mapped field keeps its type and options until the documents
+/// are reindexed. A removed field stays mapped, which doesn't prevent documents from omitting it.
+pub fn classify_elasticsearch_change(diff: &SchemaDiff) -> Classification {
+    match diff.kind {
+        SchemaChangeKind::FieldAdded | SchemaChangeKind::FieldRemoved => in_place(),
+        SchemaChangeKind::FieldTypeChanged => {
+            needs_reindex("Elasticsearch can't change the type of a mapped field")
+        }
+        SchemaChangeKind::VectorOptionsChanged => {
+            needs_reindex("Elasticsearch can't change the dimensions or similarity of a vector")
+        }
+        // Facets and sorts use the keyword sub-field, and arrays are only recorded in the
+        // mapping metadata
+        SchemaChangeKind::FieldOptionsChanged => match (&diff.current, &diff.target) {
+            (Some(current), Some(target))
+                if (current.index, current.stored, &current.analyzer, &current.locale)
+                    != (target.index, target.stored, &target.analyzer, &target.locale) =>
+            {
+                needs_reindex(
+                    "Elasticsearch can't change whether a mapped field is indexed or stored, or how it's analyzed",
+                )
+            }
+            _ => in_place(),
+        },
+        SchemaChangeKind::PrimaryKeyChanged => {
+            impossible("Elasticsearch identifies documents by their _id")
+        }
+    }
+}
+
 pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
     let mut retry_query = original_query.clone();
 
@@ -396,6 +897,50 @@ pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHi
     retry_query
 }
 
+/// How long the point in time of a search stream is kept open between two pages
+pub const POINT_IN_TIME_KEEP_ALIVE: &str = "5m";
+
+/// Default number of hits of a search stream page
+pub const STREAM_PAGE_SIZE: u32 = 100;
+
+/// Position of a search stream: the point in time it reads, and the sort values of the last hit
+/// it returned
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ElasticsearchStreamCursor {
+    pub pit_id: String,
+    pub search_after: Vec<Value>,
+}
+
+/// Builds the query of a search stream page, reading the given point in time after the sort
+/// values of the previous page's last hit
+pub fn search_query_to_elasticsearch_stream_query(
+    query: SearchQuery,
+    pit_id: &str,
+    search_after: Option<Vec<Value>>,
+) -> Result<ElasticsearchQuery, SearchError> {
+    let page_size = query.per_page.unwrap_or(STREAM_PAGE_SIZE);
+    let mut es_query = search_query_to_elasticsearch_query(query)?;
+    es_query.size = Some(page_size);
+    es_query.pit = Some(json!({
+        "id": pit_id,
+        "keep_alive": POINT_IN_TIME_KEEP_ALIVE
+    }));
+    // The offset only applies to the first page, the next ones start after the previous hit
+    if search_after.is_some() {
+        es_query.from = None;
+    }
+    es_query.search_after = search_after;
+
+    // Hits with equal sort values are ordered by their position in the point in time, so that no
+    // hit is skipped or returned twice across pages
+    es_query
+        .sort
+        .get_or_insert_with(|| vec![json!({ "_score": "desc" })])
+        .push(json!({ "_shard_doc": "asc" }));
+
+    Ok(es_query)
+}
+
 pub fn build_bulk_operations(
     index_name: &str,
     docs: &[Doc],
@@ -440,13 +985,73 @@ pub fn build_bulk_delete_operations(index_name: &str, ids: &[String]) -> Result<
     Ok(bulk_ops)
 }
 
+/// Reads the outcome of each operation of a bulk request, in the order of the operations
+pub fn elasticsearch_bulk_response_to_outcomes(
+    response: ElasticsearchBulkResponse,
+) -> Vec<ItemOutcome> {
+    response
+        .items
+        .iter()
+        .map(|item| {
+            // Each item is keyed by its operation, e.g. `{"index": {"_id": .., "status": 201}}`
+            let result = item
+                .as_object()
+                .and_then(|item| item.values().next())
+                .unwrap_or(&Value::Null);
+            match result.get("error") {
+                Some(error) => {
+                    let status = result.get("status").and_then(|s| s.as_u64()).unwrap_or(0);
+                    let reason = match (error.get("type"), error.get("reason")) {
+                        (Some(Value::String(error_type)), Some(Value::String(reason))) => {
+                            format!("{error_type}: {reason}")
+                        }
+                        _ => error.to_string(),
+                    };
+                    ItemOutcome::failure(reason, is_retryable_status(status as u16))
+                }
+                None => ItemOutcome::success(),
+            }
+        })
+        .collect()
+}
+
+pub fn elasticsearch_aliases_to_index_aliases(aliases: ElasticsearchAliases) -> Vec<IndexAlias> {
+    aliases
+        .into_iter()
+        .flat_map(|(index, index_aliases)| {
+            index_aliases
+                .aliases
+                .into_iter()
+                .map(move |(alias, _)| IndexAlias {
+                    alias,
+                    index: index.clone(),
+                })
+        })
+        .collect()
+}
+
+/// Actions moving the alias from the indices it points to, to the given index, in one request
+pub fn alias_swap_actions(alias: &str, current_indices: &[String], index: &str) -> Vec<Value> {
+    current_indices
+        .iter()
+        .filter(|current| current.as_str() != index)
+        .map(|current| json!({ "remove": { "index": current, "alias": alias } }))
+        .chain(std::iter::once(
+            json!({ "add": { "index": index, "alias": alias } }),
+        ))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::client::{
         ElasticsearchHit, ElasticsearchHits, ElasticsearchSearchResponse, ElasticsearchTotal,
     };
-    use golem_search::golem::search::types::{HighlightConfig, SearchConfig};
+    use golem_search::golem::search::types::{
+        DateHistogramFacet, FacetRange, FilterExpression, FilterNode, HighlightConfig, RangeFacet,
+        SchemaChangeApplication, SearchConfig, TermsFacet, VectorQuery,
+    };
 
     #[test]
     fn test_doc_to_elasticsearch_document() {
@@ -506,8 +1111,11 @@ mod tests {
         let search_query = SearchQuery {
             q: Some("test query".to_string()),
             filters: vec!["category:electronics".to_string()],
+            filter: None,
+            vector: None,
             sort: vec!["price:desc".to_string()],
             facets: vec!["category".to_string()],
+            facet_requests: vec![],
             page: None,
             per_page: Some(20),
             offset: Some(10),
@@ -516,17 +1124,27 @@ mod tests {
                 pre_tag: Some("<mark>".to_string()),
                 post_tag: Some("</mark>".to_string()),
                 max_length: Some(200),
+                snippet_fields: vec!["description".to_string()],
+                snippet_length: Some(20),
+                max_snippets: Some(2),
             }),
             config: None,
         };
 
-        let es_query = search_query_to_elasticsearch_query(search_query);
+        let es_query = search_query_to_elasticsearch_query(search_query).unwrap();
         assert_eq!(es_query.from, Some(10));
         assert_eq!(es_query.size, Some(20));
         assert!(es_query.query.is_some());
         assert!(es_query.sort.is_some());
-        assert!(es_query.highlight.is_some());
         assert!(es_query.aggs.is_some());
+
+        let highlight = es_query.highlight.unwrap();
+        assert_eq!(highlight["pre_tags"], serde_json::json!(["<mark>"]));
+        assert_eq!(highlight["fields"]["title"], serde_json::json!({}));
+        assert_eq!(
+            highlight["fields"]["description"],
+            serde_json::json!({ "fragment_size": 120, "number_of_fragments": 2 })
+        );
     }
 
     #[test]
@@ -534,8 +1152,11 @@ mod tests {
         let search_query = SearchQuery {
             q: None,
             filters: vec![],
+            filter: None,
+            vector: None,
             sort: vec![],
             facets: vec![],
+            facet_requests: vec![],
             page: None,
             per_page: None,
             offset: None,
@@ -543,19 +1164,159 @@ mod tests {
             config: None,
         };
 
-        let es_query = search_query_to_elasticsearch_query(search_query);
+        let es_query = search_query_to_elasticsearch_query(search_query).unwrap();
         assert!(es_query.query.is_some());
         // Should have match_all query
         assert_eq!(es_query.query.unwrap()["match_all"], serde_json::json!({}));
     }
 
+    #[test]
+    fn test_native_filters() {
+        let search_query = SearchQuery {
+            q: None,
+            filters: vec![
+                "category:electronics".to_string(),
+                "status = \"in-stock\"".to_string(),
+                "title:(rust OR go)".to_string(),
+                r#"{"range": {"price": {"lt": 100}}}"#.to_string(),
+            ],
+            filter: None,
+            vector: None,
+            sort: vec![],
+            facets: vec![],
+            facet_requests: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        };
+
+        let es_query = search_query_to_elasticsearch_query(search_query).unwrap();
+        assert_eq!(
+            es_query.query.unwrap()["bool"]["filter"],
+            serde_json::json!([
+                { "term": { "category": "electronics" } },
+                { "term": { "status": "in-stock" } },
+                { "query_string": { "query": "title:(rust OR go)" } },
+                { "range": { "price": { "lt": 100 } } }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_search_query_with_filter_expression() {
+        let search_query = SearchQuery {
+            q: None,
+            filters: vec!["year:>2000".to_string()],
+            filter: Some(FilterExpression {
+                nodes: vec![
+                    FilterNode::Or(vec![1, 2]),
+                    FilterNode::In((
+                        "genre".to_string(),
+                        vec![
+                            FilterValue::Text("fiction".to_string()),
+                            FilterValue::Text("poetry".to_string()),
+                        ],
+                    )),
+                    FilterNode::Not(3),
+                    FilterNode::Exists("isbn".to_string()),
+                ],
+            }),
+            vector: None,
+            sort: vec![],
+            facets: vec![],
+            facet_requests: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        };
+
+        let es_query = search_query_to_elasticsearch_query(search_query).unwrap();
+        assert_eq!(
+            es_query.query.unwrap()["bool"]["filter"],
+            serde_json::json!([
+                { "query_string": { "query": "year:>2000" } },
+                {
+                    "bool": {
+                        "should": [
+                            { "terms": { "genre": ["fiction", "poetry"] } },
+                            { "bool": { "must_not": [{ "exists": { "field": "isbn" } }] } }
+                        ],
+                        "minimum_should_match": 1
+                    }
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_hybrid_search_query() {
+        let search_query = SearchQuery {
+            q: Some("space opera".to_string()),
+            filters: vec![],
+            filter: Some(FilterExpression {
+                nodes: vec![FilterNode::Eq((
+                    "genre".to_string(),
+                    FilterValue::Text("fiction".to_string()),
+                ))],
+            }),
+            vector: Some(VectorQuery {
+                field: "embedding".to_string(),
+                vector: vec![0.1, 0.2, 0.3],
+                k: 5,
+                semantic_ratio: Some(0.75),
+                filter: Some(FilterExpression {
+                    nodes: vec![FilterNode::Exists("isbn".to_string())],
+                }),
+            }),
+            sort: vec![],
+            facets: vec![],
+            facet_requests: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        };
+
+        let es_query = search_query_to_elasticsearch_query(search_query.clone()).unwrap();
+        let knn = es_query.knn.unwrap();
+        assert_eq!(knn["field"], "embedding");
+        assert_eq!(knn["k"], 5);
+        assert_eq!(knn["boost"], 0.75);
+        assert_eq!(
+            knn["filter"],
+            serde_json::json!([
+                { "term": { "genre": "fiction" } },
+                { "exists": { "field": "isbn" } }
+            ])
+        );
+        assert_eq!(es_query.query.unwrap()["bool"]["boost"], 0.25);
+        assert_eq!(es_query.size, Some(5));
+
+        // Without q, the nearest neighbours are the only hits
+        let vector_only = SearchQuery {
+            q: None,
+            ..search_query
+        };
+        let es_query = search_query_to_elasticsearch_query(vector_only).unwrap();
+        assert!(es_query.query.is_none());
+        assert!(es_query.knn.unwrap().get("boost").is_none());
+    }
+
     #[test]
     fn test_search_query_with_config() {
         let search_query = SearchQuery {
             q: Some("test".to_string()),
             filters: vec![],
+            filter: None,
+            vector: None,
             sort: vec![],
             facets: vec![],
+            facet_requests: vec![],
             page: None,
             per_page: None,
             offset: None,
@@ -571,7 +1332,7 @@ mod tests {
             }),
         };
 
-        let es_query = search_query_to_elasticsearch_query(search_query);
+        let es_query = search_query_to_elasticsearch_query(search_query).unwrap();
         assert!(es_query._source.is_some());
         assert_eq!(
             es_query._source.unwrap(),
@@ -596,18 +1357,149 @@ mod tests {
                     score: Some(1.0),
                     source: Some(serde_json::json!({"title": "Test Document"})),
                     highlight: Some(serde_json::json!({"title": ["Test <em>Document</em>"]})),
+                    sort: None,
                 }],
             },
             aggregations: Some(serde_json::json!({"category": {"buckets": []}})),
+            pit_id: None,
         };
 
-        let search_results = elasticsearch_response_to_search_results(es_response);
+        let highlight = HighlightConfig {
+            fields: vec!["title".to_string()],
+            pre_tag: None,
+            post_tag: None,
+            max_length: None,
+            snippet_fields: vec![],
+            snippet_length: None,
+            max_snippets: None,
+        };
+        let search_results =
+            elasticsearch_response_to_search_results(es_response, &[], Some(&highlight));
         assert_eq!(search_results.total, Some(1));
         assert_eq!(search_results.hits.len(), 1);
         assert_eq!(search_results.hits[0].id, "doc1");
         assert_eq!(search_results.hits[0].score, Some(1.0));
         assert!(search_results.facets.is_some());
         assert_eq!(search_results.took_ms, Some(5));
+
+        let highlight_fields = &search_results.hits[0].highlight_fields;
+        assert_eq!(highlight_fields[0].field, "title");
+        let fragment = &highlight_fields[0].fragments[0];
+        assert_eq!(fragment.text, "Test Document");
+        assert_eq!(fragment.matches[0].start, 5);
+        assert_eq!(fragment.matches[0].end, 13);
+    }
+
+    #[test]
+    fn test_facet_aggregations_and_results() {
+        let search_query = SearchQuery {
+            q: None,
+            filters: vec![],
+            filter: None,
+            vector: None,
+            sort: vec![],
+            facets: vec!["genre".to_string()],
+            facet_requests: vec![
+                FacetRequest::Terms(TermsFacet {
+                    field: "author".to_string(),
+                    size: Some(3),
+                    order: FacetOrder::Value,
+                }),
+                FacetRequest::Range(RangeFacet {
+                    field: "price".to_string(),
+                    ranges: vec![
+                        FacetRange {
+                            from: None,
+                            to: Some(10.0),
+                        },
+                        FacetRange {
+                            from: Some(10.0),
+                            to: None,
+                        },
+                    ],
+                }),
+                FacetRequest::DateHistogram(DateHistogramFacet {
+                    field: "published".to_string(),
+                    interval: CalendarInterval::Year,
+                }),
+                FacetRequest::Stats("price".to_string()),
+            ],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        };
+
+        let facets = facet_requests(&search_query).unwrap();
+        let aggs = search_query_to_elasticsearch_query(search_query)
+            .unwrap()
+            .aggs
+            .unwrap();
+        assert_eq!(aggs["facet_0"]["terms"]["field"], "genre.keyword");
+        assert_eq!(aggs["facet_0"]["terms"]["size"], 10);
+        assert_eq!(
+            aggs["facet_1"],
+            serde_json::json!({
+                "terms": { "field": "author", "size": 3, "order": { "_key": "asc" } }
+            })
+        );
+        assert_eq!(
+            aggs["facet_2"]["range"]["ranges"],
+            serde_json::json!([{ "to": 10.0 }, { "from": 10.0 }])
+        );
+        assert_eq!(
+            aggs["facet_3"]["date_histogram"]["calendar_interval"],
+            "year"
+        );
+        assert_eq!(
+            aggs["facet_4"],
+            serde_json::json!({ "stats": { "field": "price" } })
+        );
+
+        let es_response = ElasticsearchSearchResponse {
+            took: 1,
+            timed_out: false,
+            hits: ElasticsearchHits {
+                total: ElasticsearchTotal {
+                    value: 0,
+                    relation: "eq".to_string(),
+                },
+                max_score: None,
+                hits: vec![],
+            },
+            aggregations: Some(serde_json::json!({
+                "facet_0": { "buckets": [{ "key": "fiction", "doc_count": 4 }] },
+                "facet_1": { "buckets": [{ "key": "Asimov", "doc_count": 2 }] },
+                "facet_2": { "buckets": [{ "doc_count": 1 }, { "doc_count": 3 }] },
+                "facet_3": {
+                    "buckets": [{ "key": 1577836800000u64, "key_as_string": "2020", "doc_count": 2 }]
+                },
+                "facet_4": { "count": 4, "min": 5.0, "max": 30.0, "avg": 15.0, "sum": 60.0 }
+            })),
+            pit_id: None,
+        };
+
+        let results = elasticsearch_response_to_search_results(es_response, &facets, None);
+        let facet_results = results.facet_results;
+        assert_eq!(facet_results.len(), 5);
+        assert_eq!(facet_results[0].field, "genre");
+        assert_eq!(facet_results[0].buckets[0].value, "fiction");
+        assert_eq!(facet_results[0].buckets[0].count, 4);
+        assert_eq!(facet_results[2].buckets[0].value, "*..10");
+        assert_eq!(facet_results[2].buckets[1].count, 3);
+        assert_eq!(
+            facet_results[2].buckets[1].range,
+            Some(FacetRange {
+                from: Some(10.0),
+                to: None,
+            })
+        );
+        assert_eq!(facet_results[3].buckets[0].value, "2020");
+        let stats = facet_results[4].stats.clone().unwrap();
+        assert_eq!(stats.count, Some(4));
+        assert_eq!(stats.sum, Some(60.0));
+        assert!(facet_results[4].buckets.is_empty());
     }
 
     #[test]
@@ -621,6 +1513,11 @@ mod tests {
                     facet: false,
                     sort: false,
                     index: true,
+                    array: false,
+                    stored: true,
+                    analyzer: None,
+                    locale: None,
+                    vector: None,
                 },
                 SchemaField {
                     name: "category".to_string(),
@@ -629,6 +1526,11 @@ mod tests {
                     facet: true,
                     sort: true,
                     index: true,
+                    array: false,
+                    stored: true,
+                    analyzer: None,
+                    locale: None,
+                    vector: None,
                 },
                 SchemaField {
                     name: "price".to_string(),
@@ -637,12 +1539,17 @@ mod tests {
                     facet: false,
                     sort: true,
                     index: false,
+                    array: false,
+                    stored: true,
+                    analyzer: None,
+                    locale: None,
+                    vector: None,
                 },
             ],
             primary_key: Some("id".to_string()),
         };
 
-        let settings = schema_to_elasticsearch_settings(schema);
+        let settings = schema_to_elasticsearch_settings(schema).unwrap();
         assert!(settings.mappings.is_some());
         let mappings = settings.mappings.unwrap();
         assert!(mappings.properties.is_some());
@@ -652,13 +1559,222 @@ mod tests {
         assert!(properties.contains_key("price"));
     }
 
+    #[test]
+    fn test_classify_elasticsearch_change() {
+        let field = |field_type, index| SchemaField {
+            name: "year".to_string(),
+            field_type,
+            required: false,
+            facet: false,
+            sort: false,
+            index,
+            array: false,
+            stored: true,
+            analyzer: None,
+            locale: None,
+            vector: None,
+        };
+        let diff = |kind, current, target| SchemaDiff {
+            kind,
+            field: "year".to_string(),
+            current: Some(current),
+            target: Some(target),
+        };
+
+        let type_changed = diff(
+            SchemaChangeKind::FieldTypeChanged,
+            field(FieldType::Text, true),
+            field(FieldType::Integer, true),
+        );
+        assert_eq!(
+            classify_elasticsearch_change(&type_changed).application,
+            SchemaChangeApplication::NeedsReindex
+        );
+
+        let facet_changed = diff(
+            SchemaChangeKind::FieldOptionsChanged,
+            field(FieldType::Integer, true),
+            SchemaField {
+                facet: true,
+                ..field(FieldType::Integer, true)
+            },
+        );
+        assert_eq!(classify_elasticsearch_change(&facet_changed), in_place());
+
+        let index_changed = diff(
+            SchemaChangeKind::FieldOptionsChanged,
+            field(FieldType::Integer, true),
+            field(FieldType::Integer, false),
+        );
+        assert_eq!(
+            classify_elasticsearch_change(&index_changed).application,
+            SchemaChangeApplication::NeedsReindex
+        );
+
+        let array_changed = diff(
+            SchemaChangeKind::FieldOptionsChanged,
+            field(FieldType::Integer, true),
+            SchemaField {
+                array: true,
+                ..field(FieldType::Integer, true)
+            },
+        );
+        assert_eq!(classify_elasticsearch_change(&array_changed), in_place());
+
+        let locale_changed = diff(
+            SchemaChangeKind::FieldOptionsChanged,
+            field(FieldType::Text, true),
+            SchemaField {
+                locale: Some("fr".to_string()),
+                ..field(FieldType::Text, true)
+            },
+        );
+        assert_eq!(
+            classify_elasticsearch_change(&locale_changed).application,
+            SchemaChangeApplication::NeedsReindex
+        );
+    }
+
+    #[test]
+    fn test_nested_and_localized_fields_round_trip() {
+        let field = |name: &str, field_type| SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet: false,
+            sort: false,
+            index: true,
+            array: false,
+            stored: true,
+            analyzer: None,
+            locale: None,
+            vector: None,
+        };
+        let schema = Schema {
+            fields: vec![
+                SchemaField {
+                    locale: Some("fr".to_string()),
+                    ..field("title_fr", FieldType::Text)
+                },
+                SchemaField {
+                    analyzer: Some("whitespace".to_string()),
+                    stored: false,
+                    ..field("body", FieldType::Text)
+                },
+                SchemaField {
+                    array: true,
+                    facet: true,
+                    sort: true,
+                    ..field("tags", FieldType::Keyword)
+                },
+                field("area", FieldType::GeoShape),
+                field("variants", FieldType::Nested),
+                SchemaField {
+                    facet: true,
+                    sort: true,
+                    ..field("variants.color", FieldType::Keyword)
+                },
+                SchemaField {
+                    sort: true,
+                    ..field("variants.price", FieldType::Float)
+                },
+                field("publisher", FieldType::Object),
+                field("publisher.name", FieldType::Text),
+            ],
+            primary_key: Some("id".to_string()),
+        };
+
+        let mappings = schema_to_elasticsearch_settings(schema.clone())
+            .unwrap()
+            .mappings
+            .unwrap();
+        let properties = mappings.properties.as_ref().unwrap();
+        assert_eq!(properties["title_fr"]["analyzer"], json!("french"));
+        assert_eq!(properties["area"]["type"], json!("geo_shape"));
+        assert_eq!(properties["variants"]["type"], json!("nested"));
+        assert_eq!(
+            properties["variants"]["properties"]["color"],
+            json!({ "type": "keyword" })
+        );
+        assert_eq!(mappings.source, Some(json!({ "excludes": ["body"] })));
+
+        // Elasticsearch leaves out the type of objects when returning the mappings
+        let mut mappings = serde_json::to_value(mappings).unwrap();
+        mappings["properties"]["publisher"]
+            .as_object_mut()
+            .unwrap()
+            .remove("type");
+        let mut fields =
+            elasticsearch_mappings_to_schema(json!({ "books": { "mappings": mappings } }), "books")
+                .fields;
+        fields.retain(|f| f.name != "id" && f.name != "year");
+        fields.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut expected = schema.fields;
+        expected.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(fields, expected);
+
+        let analyzed_integer = Schema {
+            fields: vec![SchemaField {
+                locale: Some("fr".to_string()),
+                ..field("year", FieldType::Integer)
+            }],
+            primary_key: None,
+        };
+        assert!(schema_to_elasticsearch_settings(analyzed_integer).is_err());
+    }
+
+    #[test]
+    fn test_vector_field_mapping() {
+        let schema = Schema {
+            fields: vec![SchemaField {
+                name: "embedding".to_string(),
+                field_type: FieldType::Vector,
+                required: false,
+                facet: false,
+                sort: false,
+                index: true,
+                array: false,
+                stored: true,
+                analyzer: None,
+                locale: None,
+                vector: Some(VectorOptions {
+                    dimensions: 3,
+                    similarity: VectorSimilarity::DotProduct,
+                }),
+            }],
+            primary_key: None,
+        };
+
+        let settings = schema_to_elasticsearch_settings(schema.clone()).unwrap();
+        let properties = settings.mappings.unwrap().properties.unwrap();
+        assert_eq!(
+            properties["embedding"],
+            serde_json::json!({ "type": "dense_vector", "dims": 3, "similarity": "dot_product" })
+        );
+
+        let mappings = serde_json::json!({
+            "books": { "mappings": { "properties": { "embedding": properties["embedding"] } } }
+        });
+        assert_eq!(
+            elasticsearch_mappings_to_schema(mappings, "books").fields,
+            schema.fields
+        );
+
+        let mut missing_options = schema;
+        missing_options.fields[0].vector = None;
+        assert!(schema_to_elasticsearch_settings(missing_options).is_err());
+    }
+
     #[test]
     fn test_create_retry_query() {
         let original_query = SearchQuery {
             q: Some("test".to_string()),
             filters: vec![],
+            filter: None,
+            vector: None,
             sort: vec![],
             facets: vec![],
+            facet_requests: vec![],
             page: None,
             per_page: Some(10),
             offset: Some(0),
@@ -672,12 +1788,14 @@ mod tests {
                 score: Some(1.0),
                 content: Some("{}".to_string()),
                 highlights: None,
+                highlight_fields: vec![],
             },
             SearchHit {
                 id: "doc2".to_string(),
                 score: Some(0.8),
                 content: Some("{}".to_string()),
                 highlights: None,
+                highlight_fields: vec![],
             },
         ];
 
@@ -685,6 +1803,65 @@ mod tests {
         assert_eq!(retry_query.offset, Some(2));
     }
 
+    #[test]
+    fn test_search_query_to_elasticsearch_stream_query() {
+        let query = SearchQuery {
+            q: Some("test".to_string()),
+            filters: vec![],
+            filter: None,
+            vector: None,
+            sort: vec!["price:desc".to_string()],
+            facets: vec![],
+            facet_requests: vec![],
+            page: None,
+            per_page: None,
+            offset: Some(10),
+            highlight: None,
+            config: None,
+        };
+
+        let first_page =
+            search_query_to_elasticsearch_stream_query(query.clone(), "pit-1", None).unwrap();
+        assert_eq!(first_page.from, Some(10));
+        assert_eq!(first_page.size, Some(STREAM_PAGE_SIZE));
+        assert_eq!(first_page.pit.unwrap()["id"], "pit-1");
+        assert!(first_page.search_after.is_none());
+        assert_eq!(
+            first_page.sort.unwrap(),
+            vec![
+                serde_json::json!({ "price": { "order": "desc" } }),
+                serde_json::json!({ "_shard_doc": "asc" })
+            ]
+        );
+
+        let cursor = ElasticsearchStreamCursor {
+            pit_id: "pit-2".to_string(),
+            search_after: vec![serde_json::json!(25.0), serde_json::json!(42)],
+        };
+        let encoded = serde_json::to_string(&cursor).unwrap();
+        let decoded: ElasticsearchStreamCursor = serde_json::from_str(&encoded).unwrap();
+        assert_eq!(decoded, cursor);
+
+        let next_page = search_query_to_elasticsearch_stream_query(
+            SearchQuery {
+                sort: vec![],
+                ..query
+            },
+            &decoded.pit_id,
+            Some(decoded.search_after.clone()),
+        )
+        .unwrap();
+        assert_eq!(next_page.from, None);
+        assert_eq!(next_page.search_after, Some(decoded.search_after));
+        assert_eq!(
+            next_page.sort.unwrap(),
+            vec![
+                serde_json::json!({ "_score": "desc" }),
+                serde_json::json!({ "_shard_doc": "asc" })
+            ]
+        );
+    }
+
     #[test]
     fn test_build_bulk_operations() {
         let docs = vec![
@@ -705,6 +1882,79 @@ mod tests {
         assert!(bulk_ops.contains("Document 2"));
     }
 
+    #[test]
+    fn test_elasticsearch_aliases_to_index_aliases() {
+        let aliases: ElasticsearchAliases = serde_json::from_value(json!({
+            "books-v1": { "aliases": {} },
+            "books-v2": { "aliases": { "books": {}, "library": {} } }
+        }))
+        .unwrap();
+
+        let aliases = elasticsearch_aliases_to_index_aliases(aliases);
+        assert_eq!(
+            aliases,
+            vec![
+                IndexAlias {
+                    alias: "books".to_string(),
+                    index: "books-v2".to_string(),
+                },
+                IndexAlias {
+                    alias: "library".to_string(),
+                    index: "books-v2".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alias_swap_actions() {
+        let actions = alias_swap_actions("books", &["books-v1".to_string()], "books-v2");
+        assert_eq!(
+            actions,
+            vec![
+                json!({ "remove": { "index": "books-v1", "alias": "books" } }),
+                json!({ "add": { "index": "books-v2", "alias": "books" } }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_elasticsearch_bulk_response_to_outcomes() {
+        let response: ElasticsearchBulkResponse = serde_json::from_value(serde_json::json!({
+            "took": 3,
+            "errors": true,
+            "items": [
+                { "index": { "_id": "doc1", "status": 201 } },
+                {
+                    "index": {
+                        "_id": "doc2",
+                        "status": 400,
+                        "error": { "type": "mapper_parsing_exception", "reason": "failed to parse field [year]" }
+                    }
+                },
+                {
+                    "index": {
+                        "_id": "doc3",
+                        "status": 429,
+                        "error": { "type": "es_rejected_execution_exception", "reason": "rejected execution" }
+                    }
+                }
+            ]
+        }))
+        .unwrap();
+
+        let outcomes = elasticsearch_bulk_response_to_outcomes(response);
+        assert_eq!(outcomes[0], ItemOutcome::success());
+        assert_eq!(
+            outcomes[1],
+            ItemOutcome::failure(
+                "mapper_parsing_exception: failed to parse field [year]",
+                false
+            )
+        );
+        assert!(outcomes[2].retryable);
+    }
+
     #[test]
     fn test_build_bulk_delete_operations() {
         let ids = vec!["doc1".to_string(), "doc2".to_string()];
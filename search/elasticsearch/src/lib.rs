@@ -1,33 +1,49 @@
-use crate::client::ElasticsearchApi;
+use crate::client::{ElasticsearchApi, ElasticsearchSearchResponse};
 use crate::conversions::{
-    build_bulk_delete_operations, build_bulk_operations, create_retry_query,
-    doc_to_elasticsearch_document, elasticsearch_document_to_doc, elasticsearch_mappings_to_schema,
+    alias_swap_actions, build_bulk_delete_operations, build_bulk_operations,
+    classify_elasticsearch_change, create_retry_query, doc_to_elasticsearch_document,
+    elasticsearch_aliases_to_index_aliases, elasticsearch_bulk_response_to_outcomes,
+    elasticsearch_document_to_doc, elasticsearch_mappings_to_schema,
     elasticsearch_response_to_search_results, schema_to_elasticsearch_settings,
-    search_query_to_elasticsearch_query,
+    search_query_to_elasticsearch_query, search_query_to_elasticsearch_stream_query,
+    ElasticsearchStreamCursor, POINT_IN_TIME_KEEP_ALIVE, STREAM_PAGE_SIZE,
 };
 use golem_rust::wasm_rpc::Pollable;
+use golem_search::bulk::{doc_chunks, id_chunks, send_converted, send_with_retries, write_chunks};
 use golem_search::config::with_config_keys;
 use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::error::internal_error;
+use golem_search::facets::facet_requests;
 use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
 use golem_search::golem::search::types::{
-    Doc, DocumentId, IndexName, Schema, SearchError, SearchHit, SearchQuery, SearchResults,
+    BulkResult, Doc, DocumentId, IndexAlias, IndexName, ReindexResult, ReindexTransform, Schema,
+    SchemaChange, SchemaUpdateMode, SearchError, SearchHit, SearchQuery, SearchResults, TaskId,
+    TaskStatus, WriteConsistency,
 };
+use golem_search::schema::{check_in_place, plan_schema_change, reindex_to_schema};
 use log::trace;
+use serde_json::Value;
 use std::cell::{Cell, RefCell};
 
 mod client;
 mod conversions;
 
-/// Uses scroll API for streaming large result sets
+/// Index and mapping operations are applied synchronously. Document writes are refreshed
+/// asynchronously, unless they wait for the refresh making them visible.
+fn wait_for_refresh(consistency: WriteConsistency) -> bool {
+    consistency == WriteConsistency::WaitForVisible
+}
+
+/// Streams large result sets with a point in time and `search_after`, so that the pages are
+/// neither limited by the max result window nor affected by concurrent index updates
 struct ElasticsearchSearchStream {
     client: ElasticsearchApi,
     index_name: String,
     query: SearchQuery,
-    scroll_id: RefCell<Option<String>>,
+    pit_id: RefCell<Option<String>>,
+    search_after: RefCell<Option<Vec<Value>>>,
     finished: Cell<bool>,
-    current_offset: Cell<u32>,
-    use_scroll: Cell<bool>,
-    scroll_failed: Cell<bool>,
+    failure: RefCell<Option<SearchError>>,
 }
 
 impl ElasticsearchSearchStream {
@@ -35,149 +51,152 @@ impl ElasticsearchSearchStream {
         Self {
             client,
             index_name,
-            query: query.clone(),
-            scroll_id: RefCell::new(None),
+            query,
+            pit_id: RefCell::new(None),
+            search_after: RefCell::new(None),
             finished: Cell::new(false),
-            current_offset: Cell::new(query.offset.unwrap_or(0)),
-            use_scroll: Cell::new(true), // Start with scroll, fallback to pagination if needed
-            scroll_failed: Cell::new(false),
+            failure: RefCell::new(None),
         }
     }
 
-    pub fn subscribe(&self) -> Pollable {
-        golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
+    pub fn resumed(
+        client: ElasticsearchApi,
+        index_name: String,
+        query: SearchQuery,
+        cursor: ElasticsearchStreamCursor,
+    ) -> Self {
+        Self {
+            client,
+            index_name,
+            query,
+            pit_id: RefCell::new(Some(cursor.pit_id)),
+            search_after: RefCell::new(Some(cursor.search_after)),
+            finished: Cell::new(false),
+            failure: RefCell::new(None),
+        }
     }
-}
 
-impl GuestSearchStream for ElasticsearchSearchStream {
-    fn get_next(&self) -> Option<Vec<SearchHit>> {
-        if self.finished.get() {
-            return Some(vec![]);
+    /// Creates a stream failing with the given error on its first page, for example when it
+    /// can't be resumed
+    pub fn failed(
+        client: ElasticsearchApi,
+        index_name: String,
+        query: SearchQuery,
+        error: SearchError,
+    ) -> Self {
+        Self {
+            failure: RefCell::new(Some(error)),
+            ..Self::new(client, index_name, query)
         }
+    }
 
-        if self.use_scroll.get() && !self.scroll_failed.get() {
-            self.try_scroll_next().unwrap_or_else(|| {
-                trace!("Scroll failed, falling back to pagination");
-                self.scroll_failed.set(true);
-                self.use_scroll.set(false);
-                self.try_pagination_next()
-            })
-        } else {
-            self.try_pagination_next()
-        }
+    pub fn subscribe(&self) -> Pollable {
+        golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
     }
 
-    fn blocking_get_next(&self) -> Vec<SearchHit> {
-        self.get_next().unwrap_or_default()
+    pub fn cursor(&self) -> Option<ElasticsearchStreamCursor> {
+        Some(ElasticsearchStreamCursor {
+            pit_id: self.pit_id.borrow().clone()?,
+            search_after: self.search_after.borrow().clone()?,
+        })
     }
-}
 
-impl ElasticsearchSearchStream {
-    fn try_scroll_next(&self) -> Option<Option<Vec<SearchHit>>> {
-        if self.scroll_id.borrow().is_none() {
-            let mut es_query = search_query_to_elasticsearch_query(self.query.clone());
-            es_query.from = Some(0);
-            es_query.size = Some(self.query.per_page.unwrap_or(100)); // Larger page size for scroll
-
-            match self
-                .client
-                .search_with_scroll(&self.index_name, &es_query, "1m")
-            {
-                Ok(response) => {
-                    *self.scroll_id.borrow_mut() = Some(response.scroll_id);
-
-                    let search_results = elasticsearch_response_to_search_results(
-                        crate::client::ElasticsearchSearchResponse {
-                            took: response.took,
-                            timed_out: response.timed_out,
-                            hits: response.hits,
-                            aggregations: response.aggregations,
-                        },
-                    );
-
-                    if search_results.hits.is_empty() {
-                        self.finished.set(true);
-                        return Some(Some(vec![]));
-                    }
-
-                    Some(Some(search_results.hits))
-                }
-                Err(e) => {
-                    trace!("Initial scroll search failed: {e:?}");
-                    None // Signal to fallback to pagination
-                }
-            }
-        } else {
-            let scroll_id = self.scroll_id.borrow().clone().unwrap();
-
-            match self.client.scroll(&scroll_id, "1m") {
-                Ok(response) => {
-                    *self.scroll_id.borrow_mut() = Some(response.scroll_id);
-
-                    let search_results = elasticsearch_response_to_search_results(
-                        crate::client::ElasticsearchSearchResponse {
-                            took: response.took,
-                            timed_out: response.timed_out,
-                            hits: response.hits,
-                            aggregations: response.aggregations,
-                        },
-                    );
-
-                    if search_results.hits.is_empty() {
-                        self.finished.set(true);
-
-                        if let Some(scroll_id) = self.scroll_id.borrow().as_ref() {
-                            let _ = self.client.clear_scroll(scroll_id);
-                        }
-                    }
-
-                    Some(Some(search_results.hits))
-                }
-                Err(e) => {
-                    trace!("Scroll continuation failed: {e:?}");
+    fn finish(&self) {
+        self.finished.set(true);
+        if let Some(pit_id) = self.pit_id.borrow().as_ref() {
+            let _ = self.client.close_point_in_time(pit_id);
+        }
+    }
 
-                    if let Some(scroll_id) = self.scroll_id.borrow().as_ref() {
-                        let _ = self.client.clear_scroll(scroll_id);
-                    }
-                    None
-                }
-            }
+    /// Returns the next page of hits, an empty page once all the hits have been returned, or the
+    /// error which ended the stream
+    fn next_page(&self) -> Result<Vec<SearchHit>, SearchError> {
+        if let Some(error) = self.failure.take() {
+            self.finished.set(true);
+            return Err(error);
+        }
+        if self.finished.get() {
+            return Ok(vec![]);
         }
+
+        let result = self.read_page();
+        if result.is_err() {
+            self.finish();
+        }
+        result
     }
 
-    fn try_pagination_next(&self) -> Option<Vec<SearchHit>> {
-        let mut es_query = search_query_to_elasticsearch_query(self.query.clone());
-        es_query.from = Some(self.current_offset.get());
-        es_query.size = Some(self.query.per_page.unwrap_or(10));
+    fn read_page(&self) -> Result<Vec<SearchHit>, SearchError> {
+        let pit_id = match self.pit_id.borrow().clone() {
+            Some(pit_id) => pit_id,
+            None => self.open_point_in_time()?,
+        };
+
+        // The point in time expires when the stream is idle for too long, for example before a
+        // recovered worker resumes it. The sort values of the last returned hit include a
+        // tiebreaker which is only valid in that point in time, so the stream can't continue in
+        // a new one.
+        let response = self.search_point_in_time(&pit_id)?.ok_or_else(|| {
+            internal_error(format!(
+                "Point in time {pit_id} expired, the stream can't be continued"
+            ))
+        })?;
+        // The point in time id may change between searches
+        if let Some(pit_id) = &response.pit_id {
+            *self.pit_id.borrow_mut() = Some(pit_id.clone());
+        }
+        if let Some(sort) = response.hits.hits.last().and_then(|hit| hit.sort.clone()) {
+            *self.search_after.borrow_mut() = Some(sort);
+        }
+        let received_count = response.hits.hits.len();
+        let page_size = self.query.per_page.unwrap_or(STREAM_PAGE_SIZE) as usize;
 
-        match self.client.search(&self.index_name, &es_query) {
-            Ok(response) => {
-                let search_results = elasticsearch_response_to_search_results(response);
+        let search_results =
+            elasticsearch_response_to_search_results(response, &[], self.query.highlight.as_ref());
 
-                if search_results.hits.is_empty() {
-                    self.finished.set(true);
-                    return Some(vec![]);
-                }
+        if received_count < page_size {
+            self.finish();
+        }
 
-                let current_offset = self.current_offset.get();
-                let received_count = search_results.hits.len() as u32;
-                self.current_offset.set(current_offset + received_count);
+        Ok(search_results.hits)
+    }
 
-                if let Some(total) = search_results.total {
-                    if self.current_offset.get() >= total {
-                        self.finished.set(true);
-                    }
-                }
+    fn open_point_in_time(&self) -> Result<String, SearchError> {
+        let pit_id = self
+            .client
+            .open_point_in_time(&self.index_name, POINT_IN_TIME_KEEP_ALIVE)?;
+        *self.pit_id.borrow_mut() = Some(pit_id.clone());
+        Ok(pit_id)
+    }
 
-                Some(search_results.hits)
-            }
+    /// Searches the next page in the point in time, or returns `None` if it has expired
+    fn search_point_in_time(
+        &self,
+        pit_id: &str,
+    ) -> Result<Option<ElasticsearchSearchResponse>, SearchError> {
+        let query = search_query_to_elasticsearch_stream_query(
+            self.query.clone(),
+            pit_id,
+            self.search_after.borrow().clone(),
+        )?;
+        self.client.search_point_in_time(&query)
+    }
+}
+
+impl GuestSearchStream for ElasticsearchSearchStream {
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        match self.next_page() {
+            Ok(hits) => Some(hits),
             Err(e) => {
-                trace!("Pagination search failed: {e:?}");
-                self.finished.set(true);
+                trace!("Search stream failed: {e:?}");
                 Some(vec![])
             }
         }
     }
+
+    fn blocking_get_next(&self) -> Vec<SearchHit> {
+        self.get_next().unwrap_or_default()
+    }
 }
 
 struct ElasticsearchComponent;
@@ -229,16 +248,23 @@ impl ElasticsearchComponent {
 impl Guest for ElasticsearchComponent {
     type SearchStream = ElasticsearchSearchStream;
 
-    fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+    fn create_index(
+        name: IndexName,
+        schema: Option<Schema>,
+        _consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
         let client = Self::create_client()?;
-        let settings = schema.map(schema_to_elasticsearch_settings);
+        let settings = schema.map(schema_to_elasticsearch_settings).transpose()?;
 
-        client.create_index(&name, settings)
+        client.create_index(&name, settings).map(|()| None)
     }
 
-    fn delete_index(name: IndexName) -> Result<(), SearchError> {
+    fn delete_index(
+        name: IndexName,
+        _consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
         let client = Self::create_client()?;
-        client.delete_index(&name)
+        client.delete_index(&name).map(|()| None)
     }
 
     fn list_indexes() -> Result<Vec<IndexName>, SearchError> {
@@ -249,58 +275,137 @@ impl Guest for ElasticsearchComponent {
         }
     }
 
-    fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+    fn create_alias(alias: IndexName, index: IndexName) -> Result<(), SearchError> {
+        let client = Self::create_client()?;
+        client.update_aliases(alias_swap_actions(&alias, &[], &index))
+    }
+
+    fn swap_alias(alias: IndexName, index: IndexName) -> Result<(), SearchError> {
+        let client = Self::create_client()?;
+        let current_indices = match client.get_aliases(Some(&alias)) {
+            Ok(aliases) => aliases.into_keys().collect::<Vec<_>>(),
+            Err(SearchError::IndexNotFound) => vec![],
+            Err(e) => return Err(e),
+        };
+
+        client.update_aliases(alias_swap_actions(&alias, &current_indices, &index))
+    }
+
+    fn list_aliases() -> Result<Vec<IndexAlias>, SearchError> {
+        let client = Self::create_client()?;
+        let aliases = client.get_aliases(None)?;
+        Ok(elasticsearch_aliases_to_index_aliases(aliases))
+    }
+
+    fn delete_alias(alias: IndexName) -> Result<(), SearchError> {
+        let client = Self::create_client()?;
+        let actions = client
+            .get_aliases(Some(&alias))?
+            .into_keys()
+            .map(|index| serde_json::json!({ "remove": { "index": index, "alias": alias } }))
+            .collect();
+
+        client.update_aliases(actions)
+    }
+
+    fn reindex(
+        source: IndexName,
+        target: IndexName,
+        alias: Option<IndexName>,
+        transform: Option<ReindexTransform>,
+    ) -> Result<ReindexResult, SearchError> {
+        golem_search::reindex::reindex::<Self>(source, target, alias, transform, Self::next_page)
+    }
+
+    fn upsert(
+        index: IndexName,
+        doc: Doc,
+        consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
         let client = Self::create_client()?;
         let document = doc_to_elasticsearch_document(doc).map_err(SearchError::InvalidQuery)?;
 
-        client.index_document(
-            &index,
-            document["id"].as_str().unwrap_or_default(),
-            &document,
-        )
+        client
+            .index_document(
+                &index,
+                document["id"].as_str().unwrap_or_default(),
+                &document,
+                wait_for_refresh(consistency),
+            )
+            .map(|()| None)
     }
 
-    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+    fn upsert_many(
+        index: IndexName,
+        docs: Vec<Doc>,
+        consistency: WriteConsistency,
+    ) -> Result<BulkResult, SearchError> {
         let client = Self::create_client()?;
-        let bulk_operations =
-            build_bulk_operations(&index, &docs, "index").map_err(SearchError::InvalidQuery)?;
-
-        match client.bulk_index(&bulk_operations) {
-            Ok(response) => {
-                if response.errors {
-                    Err(SearchError::Internal(
-                        "Some bulk operations failed".to_string(),
-                    ))
-                } else {
-                    Ok(())
-                }
-            }
-            Err(e) => Err(e),
-        }
+        let limits = Self::bulk_limits();
+
+        write_chunks(
+            doc_chunks(docs, &limits),
+            |doc| doc.id.as_str(),
+            |docs| {
+                send_with_retries(
+                    docs,
+                    |doc| doc.id.as_str(),
+                    &limits,
+                    |docs| {
+                        send_converted(
+                            docs,
+                            |doc| doc_to_elasticsearch_document(doc.clone()).map(|_| doc.clone()),
+                            |docs| {
+                                let bulk_operations = build_bulk_operations(&index, docs, "index")
+                                    .map_err(SearchError::InvalidQuery)?;
+                                client
+                                    .bulk_index(&bulk_operations, wait_for_refresh(consistency))
+                                    .map(elasticsearch_bulk_response_to_outcomes)
+                            },
+                        )
+                    },
+                )
+            },
+        )
     }
 
-    fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+    fn delete(
+        index: IndexName,
+        id: DocumentId,
+        consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
         let client = Self::create_client()?;
-        client.delete_document(&index, &id)
+        client
+            .delete_document(&index, &id, wait_for_refresh(consistency))
+            .map(|()| None)
     }
 
-    fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+    fn delete_many(
+        index: IndexName,
+        ids: Vec<DocumentId>,
+        consistency: WriteConsistency,
+    ) -> Result<BulkResult, SearchError> {
         let client = Self::create_client()?;
-        let bulk_operations =
-            build_bulk_delete_operations(&index, &ids).map_err(SearchError::InvalidQuery)?;
-
-        match client.bulk_index(&bulk_operations) {
-            Ok(response) => {
-                if response.errors {
-                    Err(SearchError::Internal(
-                        "Some bulk delete operations failed".to_string(),
-                    ))
-                } else {
-                    Ok(())
-                }
-            }
-            Err(e) => Err(e),
-        }
+        let limits = Self::bulk_limits();
+
+        write_chunks(
+            id_chunks(ids, &limits),
+            |id| id.as_str(),
+            |ids| {
+                send_with_retries(
+                    ids,
+                    |id| id.as_str(),
+                    &limits,
+                    |ids| {
+                        let bulk_operations = build_bulk_delete_operations(&index, ids)
+                            .map_err(SearchError::InvalidQuery)?;
+                        client
+                            .bulk_index(&bulk_operations, wait_for_refresh(consistency))
+                            .map(elasticsearch_bulk_response_to_outcomes)
+                    },
+                )
+            },
+        )
     }
 
     fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
@@ -314,16 +419,24 @@ impl Guest for ElasticsearchComponent {
 
     fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
         let client = Self::create_client()?;
-        let es_query = search_query_to_elasticsearch_query(query);
+        let facets = facet_requests(&query)?;
+        let highlight = query.highlight.clone();
+        let es_query = search_query_to_elasticsearch_query(query)?;
 
         match client.search(&index, &es_query) {
-            Ok(response) => Ok(elasticsearch_response_to_search_results(response)),
+            Ok(response) => Ok(elasticsearch_response_to_search_results(
+                response,
+                &facets,
+                highlight.as_ref(),
+            )),
             Err(e) => Err(e),
         }
     }
 
     fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
         let client = Self::create_client()?;
+        // Rejects invalid filters before the stream is created
+        search_query_to_elasticsearch_query(query.clone())?;
         let stream = ElasticsearchSearchStream::new(client, index, query);
         Ok(SearchStream::new(stream))
     }
@@ -336,15 +449,41 @@ impl Guest for ElasticsearchComponent {
         }
     }
 
-    fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
+    fn plan_schema_change(
+        index: IndexName,
+        schema: Schema,
+    ) -> Result<Vec<SchemaChange>, SearchError> {
+        let current = Self::get_schema(index)?;
+        Ok(plan_schema_change(
+            &current,
+            &schema,
+            classify_elasticsearch_change,
+        ))
+    }
+
+    fn update_schema(
+        index: IndexName,
+        schema: Schema,
+        _consistency: WriteConsistency,
+        mode: SchemaUpdateMode,
+    ) -> Result<Option<TaskId>, SearchError> {
+        if let SchemaUpdateMode::Reindex(reindex) = mode {
+            return reindex_to_schema::<Self>(index, schema, reindex);
+        }
+        check_in_place(Self::plan_schema_change(index.clone(), schema.clone()))?;
+
         let client = Self::create_client()?;
-        let settings = schema_to_elasticsearch_settings(schema);
+        let settings = schema_to_elasticsearch_settings(schema)?;
 
         if let Some(mappings) = settings.mappings {
-            client.put_mappings(&index, &mappings)
-        } else {
-            Ok(())
+            client.put_mappings(&index, &mappings)?;
         }
+        Ok(None)
+    }
+
+    fn task_status(_index: IndexName, _task_id: TaskId) -> Result<TaskStatus, SearchError> {
+        // Writes are applied synchronously, so no task is returned to track
+        Err(SearchError::Unsupported)
     }
 }
 
@@ -357,6 +496,27 @@ impl ExtendedGuest for ElasticsearchComponent {
         ElasticsearchSearchStream::new(client, index, query)
     }
 
+    fn resumed_stream(index: IndexName, query: SearchQuery, cursor: String) -> Self::SearchStream {
+        let client = Self::create_client().unwrap_or_else(|_| {
+            ElasticsearchApi::new("http://localhost:9200".to_string(), None, None, None)
+        });
+
+        match serde_json::from_str(&cursor) {
+            Ok(cursor) => ElasticsearchSearchStream::resumed(client, index, query, cursor),
+            // Restarting the stream would return the already persisted hits again
+            Err(e) => ElasticsearchSearchStream::failed(
+                client,
+                index,
+                query,
+                internal_error(format!("Invalid stream cursor {cursor}: {e}")),
+            ),
+        }
+    }
+
+    fn stream_cursor(stream: &Self::SearchStream) -> Option<String> {
+        serde_json::to_string(&stream.cursor()?).ok()
+    }
+
     fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
         create_retry_query(original_query, partial_hits)
     }
@@ -364,13 +524,19 @@ impl ExtendedGuest for ElasticsearchComponent {
     fn subscribe(stream: &Self::SearchStream) -> Pollable {
         stream.subscribe()
     }
+
+    fn next_page(stream: &Self::SearchStream) -> Result<Vec<SearchHit>, SearchError> {
+        stream.next_page()
+    }
 }
 
 impl Drop for ElasticsearchSearchStream {
     fn drop(&mut self) {
-        // Clear any active scroll when the stream is dropped
-        if let Some(scroll_id) = self.scroll_id.borrow().as_ref() {
-            let _ = self.client.clear_scroll(scroll_id);
+        // Close the point in time when the stream is dropped before reaching its end
+        if !self.finished.get() {
+            if let Some(pit_id) = self.pit_id.borrow().as_ref() {
+                let _ = self.client.close_point_in_time(pit_id);
+            }
         }
     }
 }
@@ -35,6 +35,11 @@ pub struct MeilisearchIndexListResponse {
     pub total: u32,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MeilisearchSwapIndexesRequest {
+    pub indexes: [String; 2],
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MeilisearchCreateIndexRequest {
     pub uid: String,
@@ -78,6 +83,15 @@ pub struct MeilisearchTask {
     pub duration: Option<String>,
 }
 
+impl MeilisearchTask {
+    pub fn failure_reason(&self) -> String {
+        match &self.error {
+            Some(error) => format!("{}: {}", error.code, error.message),
+            None => "Unknown error".to_string(),
+        }
+    }
+}
+
 // Meilisearch Document
 pub type MeilisearchDocument = JsonMap<String, JsonValue>;
 
@@ -127,6 +141,10 @@ pub struct MeilisearchSearchRequest {
         skip_serializing_if = "Option::is_none"
     )]
     pub attributes_to_highlight: Option<Vec<String>>,
+    #[serde(rename = "highlightPreTag", skip_serializing_if = "Option::is_none")]
+    pub highlight_pre_tag: Option<String>,
+    #[serde(rename = "highlightPostTag", skip_serializing_if = "Option::is_none")]
+    pub highlight_post_tag: Option<String>,
     #[serde(rename = "attributesToCrop", skip_serializing_if = "Option::is_none")]
     pub attributes_to_crop: Option<Vec<String>>,
     #[serde(rename = "cropLength", skip_serializing_if = "Option::is_none")]
@@ -140,6 +158,17 @@ pub struct MeilisearchSearchRequest {
     pub matching_strategy: Option<String>,
     #[serde(rename = "showRankingScore", skip_serializing_if = "Option::is_none")]
     pub show_ranking_score: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vector: Option<Vec<f32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hybrid: Option<MeilisearchHybrid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MeilisearchHybrid {
+    pub embedder: String,
+    #[serde(rename = "semanticRatio")]
+    pub semantic_ratio: f32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -154,6 +183,8 @@ pub struct MeilisearchSearchResponse {
     pub query: String,
     #[serde(rename = "facetDistribution", skip_serializing_if = "Option::is_none")]
     pub facet_distribution: Option<JsonMap<String, JsonValue>>,
+    #[serde(rename = "facetStats", skip_serializing_if = "Option::is_none")]
+    pub facet_stats: Option<JsonMap<String, JsonValue>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -189,6 +220,20 @@ pub struct MeilisearchSettings {
     pub faceting: Option<JsonValue>,
     #[serde(rename = "pagination", skip_serializing_if = "Option::is_none")]
     pub pagination: Option<JsonValue>,
+    #[serde(rename = "embedders", skip_serializing_if = "Option::is_none")]
+    pub embedders: Option<JsonMap<String, JsonValue>>,
+    #[serde(
+        rename = "localizedAttributes",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub localized_attributes: Option<Vec<MeilisearchLocalizedAttributes>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeilisearchLocalizedAttributes {
+    #[serde(rename = "attributePatterns")]
+    pub attribute_patterns: Vec<String>,
+    pub locales: Vec<String>,
 }
 
 impl MeilisearchApi {
@@ -308,6 +353,23 @@ impl MeilisearchApi {
         parse_response(response)
     }
 
+    /// Exchanges the documents, settings and tasks of the two indexes atomically
+    pub fn swap_indexes(&self, first: &str, second: &str) -> Result<MeilisearchTask, SearchError> {
+        trace!("Swapping indexes: {first} and {second}");
+
+        let url = format!("{}/swap-indexes", self.base_url);
+
+        let response = self
+            .create_request("POST", &url)
+            .json(&[MeilisearchSwapIndexesRequest {
+                indexes: [first.to_string(), second.to_string()],
+            }])
+            .send()
+            .map_err(|e| internal_error(format!("Failed to swap indexes: {e}")))?;
+
+        parse_response(response)
+    }
+
     pub fn _get_documents(
         &self,
         index_uid: &str,
@@ -538,6 +600,25 @@ impl MeilisearchApi {
         )
     }
 
+    /// Waits for a task whose failure is reported per document, and returns the reason it
+    /// failed for, if it did
+    pub fn wait_for_document_task(&self, task_uid: u64) -> Result<Option<String>, SearchError> {
+        let task = self.wait_for_finished_task(
+            task_uid,
+            30,
+            Duration::from_millis(100),
+            Duration::from_secs(5),
+        )?;
+
+        match task.status.as_str() {
+            "succeeded" => Ok(None),
+            "failed" => Ok(Some(task.failure_reason())),
+            _ => Err(SearchError::Internal(format!(
+                "Task {task_uid} was canceled"
+            ))),
+        }
+    }
+
     pub fn wait_for_task_with_config(
         &self,
         task_uid: u64,
@@ -545,6 +626,27 @@ impl MeilisearchApi {
         initial_delay: Duration,
         max_delay: Duration,
     ) -> Result<(), SearchError> {
+        let task = self.wait_for_finished_task(task_uid, max_attempts, initial_delay, max_delay)?;
+
+        match task.status.as_str() {
+            "succeeded" => Ok(()),
+            "failed" => Err(SearchError::Internal(format!(
+                "Task {task_uid} failed: {}",
+                task.failure_reason()
+            ))),
+            _ => Err(SearchError::Internal(format!(
+                "Task {task_uid} was canceled"
+            ))),
+        }
+    }
+
+    fn wait_for_finished_task(
+        &self,
+        task_uid: u64,
+        max_attempts: u32,
+        initial_delay: Duration,
+        max_delay: Duration,
+    ) -> Result<MeilisearchTask, SearchError> {
         trace!("Waiting for task {task_uid} with exponential backoff (max_attempts: {max_attempts}, initial_delay: {initial_delay:?}, max_delay: {max_delay:?})");
 
         let mut delay = initial_delay;
@@ -560,20 +662,12 @@ impl MeilisearchApi {
             );
 
             match task.status.as_str() {
-                "succeeded" => {
-                    trace!("Task {task_uid} completed successfully after {attempt} attempts");
-                    return Ok(());
-                }
-                "failed" => {
-                    let error_msg = format!("Task {task_uid} failed after {attempt} attempts");
-                    trace!("{error_msg}");
-                    return Err(SearchError::Internal(error_msg));
-                }
-                "canceled" => {
-                    let error_msg =
-                        format!("Task {task_uid} was canceled after {attempt} attempts");
-                    trace!("{error_msg}");
-                    return Err(SearchError::Internal(error_msg));
+                "succeeded" | "failed" | "canceled" => {
+                    trace!(
+                        "Task {task_uid} finished as {} after {attempt} attempts",
+                        task.status
+                    );
+                    return Ok(task);
                 }
                 status => {
                     trace!(
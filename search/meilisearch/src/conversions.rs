@@ -1,11 +1,22 @@
 use crate::client::{
-    MeilisearchDocument, MeilisearchSearchRequest, MeilisearchSearchResponse, MeilisearchSettings,
+    MeilisearchDocument, MeilisearchHybrid, MeilisearchLocalizedAttributes,
+    MeilisearchSearchRequest, MeilisearchSearchResponse, MeilisearchSettings, MeilisearchTask,
 };
+use golem_search::error::{internal_error, invalid_query};
+use golem_search::facets::{facet_field, facet_requests, terms_facet_result, unsupported_facet};
+use golem_search::filter::{resolve_filter, unsupported_filter, Filter};
 use golem_search::golem::search::types::{
-    Doc, FieldType, Schema, SchemaField, SearchHit, SearchQuery, SearchResults,
+    Doc, FacetBucket, FacetRequest, FacetResult, FacetStats, FieldHighlight, FieldType,
+    FilterValue, HighlightConfig, Schema, SchemaChangeKind, SchemaField, SearchError, SearchHit,
+    SearchQuery, SearchResults, TaskStatus, VectorOptions, VectorSimilarity,
 };
+use golem_search::highlights::{
+    field_highlight, highlight_tags, validate_highlight_config, DEFAULT_SNIPPET_LENGTH,
+};
+use golem_search::schema::{in_place, needs_reindex, validate_field, Classification, SchemaDiff};
+use golem_search::vector::{validate_vector_query, vector_options};
 use serde_json::{Map as JsonMap, Value as JsonValue};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 pub fn doc_to_meilisearch_document(doc: Doc) -> Result<MeilisearchDocument, String> {
     let mut meilisearch_doc = JsonMap::new();
@@ -37,16 +48,34 @@ pub fn meilisearch_document_to_doc(mut doc: MeilisearchDocument) -> Doc {
     Doc { id, content }
 }
 
-pub fn search_query_to_meilisearch_request(query: SearchQuery) -> MeilisearchSearchRequest {
+pub fn search_query_to_meilisearch_request(
+    query: SearchQuery,
+) -> Result<MeilisearchSearchRequest, SearchError> {
+    // The facet distribution and stats cover every requested field, sorting and limiting the
+    // buckets is done on the results
+    let mut facet_fields: Vec<String> = vec![];
+    for facet in facet_requests(&query)? {
+        if matches!(
+            facet,
+            FacetRequest::Range(_) | FacetRequest::DateHistogram(_)
+        ) {
+            return Err(unsupported_facet(&facet, "Meilisearch"));
+        }
+        let field = facet_field(&facet);
+        if !facet_fields.iter().any(|f| f == field) {
+            facet_fields.push(field.to_string());
+        }
+    }
+
     let mut request = MeilisearchSearchRequest {
         q: query.q,
         offset: query.offset,
         limit: query.per_page,
         filter: None,
-        facets: if query.facets.is_empty() {
+        facets: if facet_fields.is_empty() {
             None
         } else {
-            Some(query.facets)
+            Some(facet_fields)
         },
         sort: if query.sort.is_empty() {
             None
@@ -67,34 +96,126 @@ pub fn search_query_to_meilisearch_request(query: SearchQuery) -> MeilisearchSea
                 })
         }),
         attributes_to_highlight: None,
+        highlight_pre_tag: None,
+        highlight_post_tag: None,
         attributes_to_crop: None,
         crop_length: None,
         show_matches_position: None,
         matching_strategy: None,
         show_ranking_score: None,
+        vector: None,
+        hybrid: None,
     };
 
-    if !query.filters.is_empty() {
-        request.filter = Some(convert_filters_to_meilisearch(query.filters));
+    // Highlighted and cropped values are returned in the `_formatted` object of the hits
+    if let Some(highlight) = &query.highlight {
+        validate_highlight_config(highlight)?;
+        let (pre_tag, post_tag) = highlight_tags(highlight);
+        let mut attributes = highlight.fields.clone();
+        attributes.extend(highlight.snippet_fields.iter().cloned());
+        if attributes.is_empty() {
+            attributes.push("*".to_string());
+        }
+        request.attributes_to_highlight = Some(attributes);
+        request.highlight_pre_tag = Some(pre_tag.to_string());
+        request.highlight_post_tag = Some(post_tag.to_string());
+        if !highlight.snippet_fields.is_empty() {
+            request.attributes_to_crop = Some(highlight.snippet_fields.clone());
+            request.crop_length = Some(highlight.snippet_length.unwrap_or(DEFAULT_SNIPPET_LENGTH));
+        }
     }
 
-    request
+    let mut filters = query.filters;
+    if let Some(expression) = &query.filter {
+        let filter = resolve_filter(expression)?;
+        filters.push(filter_to_meilisearch(&filter)?);
+    }
+
+    // Vector fields are user-provided embedders, which read the vectors from `_vectors.<field>`
+    if let Some(vector_query) = query.vector {
+        let semantic_ratio = validate_vector_query(&vector_query)?;
+        let has_text = request.q.as_ref().is_some_and(|q| !q.trim().is_empty());
+        if let Some(expression) = &vector_query.filter {
+            let filter = resolve_filter(expression)?;
+            filters.push(filter_to_meilisearch(&filter)?);
+        }
+        request.vector = Some(vector_query.vector);
+        request.hybrid = Some(MeilisearchHybrid {
+            embedder: vector_query.field,
+            semantic_ratio: if has_text { semantic_ratio } else { 1.0 },
+        });
+        request.limit = request.limit.or(Some(vector_query.k));
+    }
+    if !filters.is_empty() {
+        request.filter = Some(convert_filters_to_meilisearch(filters));
+    }
+
+    Ok(request)
 }
 
 pub fn meilisearch_response_to_search_results(
     response: MeilisearchSearchResponse,
+    facets: &[FacetRequest],
+    highlight: Option<&HighlightConfig>,
 ) -> SearchResults {
     let hits: Vec<SearchHit> = response
         .hits
         .into_iter()
-        .map(|doc| {
-            let converted_doc = meilisearch_document_to_doc(doc.clone());
+        .map(|mut doc| {
+            let formatted = doc.remove("_formatted");
+            let highlight_fields = match (highlight, &formatted) {
+                (Some(config), Some(JsonValue::Object(formatted))) => {
+                    formatted_to_field_highlights(formatted, config)
+                }
+                _ => vec![],
+            };
+            let converted_doc = meilisearch_document_to_doc(doc);
             SearchHit {
                 id: converted_doc.id,
                 score: None,
                 content: Some(converted_doc.content),
-                highlights: None,
+                highlights: formatted
+                    .map(|formatted| serde_json::to_string(&formatted).unwrap_or_default()),
+                highlight_fields,
+            }
+        })
+        .collect();
+
+    let distribution = response
+        .facet_distribution
+        .clone()
+        .map(convert_meilisearch_facets_to_golem)
+        .unwrap_or_default();
+    let stats = response.facet_stats.unwrap_or_default();
+    let facet_results = facets
+        .iter()
+        .filter_map(|facet| match facet {
+            FacetRequest::Terms(terms) => {
+                let buckets = distribution
+                    .get(&terms.field)
+                    .into_iter()
+                    .flatten()
+                    .map(|(value, count)| FacetBucket {
+                        value: value.clone(),
+                        count: *count,
+                        range: None,
+                    })
+                    .collect();
+                Some(terms_facet_result(terms, buckets, None))
             }
+            FacetRequest::Stats(field) => Some(FacetResult {
+                field: field.clone(),
+                buckets: vec![],
+                stats: stats.get(field).map(|field_stats| FacetStats {
+                    count: None,
+                    min: field_stats.get("min").and_then(|m| m.as_f64()),
+                    max: field_stats.get("max").and_then(|m| m.as_f64()),
+                    avg: None,
+                    sum: None,
+                }),
+            }),
+            // Rejected when building the request
+            FacetRequest::Range(_) | FacetRequest::DateHistogram(_) => None,
         })
         .collect();
 
@@ -106,18 +227,72 @@ pub fn meilisearch_response_to_search_results(
         facets: response
             .facet_distribution
             .map(|facets| serde_json::to_string(&facets).unwrap_or_default()),
+        facet_results,
         took_ms: Some(response.processing_time_ms),
     }
 }
 
-pub fn schema_to_meilisearch_settings(schema: Schema) -> MeilisearchSettings {
+/// The formatted document holds every attribute when all of them are highlighted, in which case
+/// only the attributes with matches are kept
+fn formatted_to_field_highlights(
+    formatted: &JsonMap<String, JsonValue>,
+    config: &HighlightConfig,
+) -> Vec<FieldHighlight> {
+    let all_fields = config.fields.is_empty() && config.snippet_fields.is_empty();
+    formatted
+        .iter()
+        .filter(|(field, _)| {
+            all_fields || config.fields.contains(*field) || config.snippet_fields.contains(*field)
+        })
+        .filter_map(|(field, value)| {
+            let highlight = field_highlight(field.as_str(), &[value.as_str()?], config);
+            let matched = highlight
+                .fragments
+                .iter()
+                .any(|fragment| !fragment.matches.is_empty());
+            (matched || !all_fields).then_some(highlight)
+        })
+        .collect()
+}
+
+pub fn schema_to_meilisearch_settings(schema: Schema) -> Result<MeilisearchSettings, SearchError> {
     let mut settings = MeilisearchSettings::default();
 
     let mut searchable_attributes = Vec::new();
     let mut filterable_attributes = Vec::new();
     let mut sortable_attributes = Vec::new();
+    let mut embedders = JsonMap::new();
+    let mut displayed_attributes = vec![schema.primary_key.unwrap_or_else(|| "id".to_string())];
+    let mut all_displayed = true;
+    let mut localized_attributes = BTreeMap::<String, Vec<String>>::new();
 
     for field in schema.fields {
+        validate_field(&field)?;
+        if field.analyzer.is_some() {
+            return Err(invalid_query(format!(
+                "Meilisearch doesn't support custom analyzers, set on the field {}",
+                field.name
+            )));
+        }
+
+        if field.field_type == FieldType::Vector {
+            let vector = vector_options(&field)?;
+            if vector.similarity != VectorSimilarity::Cosine {
+                return Err(invalid_query(format!(
+                    "Meilisearch only supports the cosine similarity, used by the vector field {}",
+                    field.name
+                )));
+            }
+            embedders.insert(
+                field.name,
+                serde_json::json!({
+                    "source": "userProvided",
+                    "dimensions": vector.dimensions
+                }),
+            );
+            continue;
+        }
+
         if field.index {
             searchable_attributes.push(field.name.clone());
         }
@@ -129,8 +304,39 @@ pub fn schema_to_meilisearch_settings(schema: Schema) -> MeilisearchSettings {
         if field.sort {
             sortable_attributes.push(field.name.clone());
         }
+
+        if let Some(locale) = &field.locale {
+            localized_attributes
+                .entry(locale.clone())
+                .or_default()
+                .push(field.name.clone());
+        }
+
+        if field.stored {
+            displayed_attributes.push(field.name);
+        } else {
+            all_displayed = false;
+        }
     }
 
+    // Settings are only replaced when they're sent, so the defaults are sent to reset them
+    settings.displayed_attributes = Some(if all_displayed {
+        vec!["*".to_string()]
+    } else {
+        displayed_attributes
+    });
+    settings.localized_attributes = Some(
+        localized_attributes
+            .into_iter()
+            .map(
+                |(locale, attribute_patterns)| MeilisearchLocalizedAttributes {
+                    attribute_patterns,
+                    locales: vec![locale],
+                },
+            )
+            .collect(),
+    );
+
     if !searchable_attributes.is_empty() {
         settings.searchable_attributes = Some(searchable_attributes);
     }
@@ -143,7 +349,11 @@ pub fn schema_to_meilisearch_settings(schema: Schema) -> MeilisearchSettings {
         settings.sortable_attributes = Some(sortable_attributes);
     }
 
-    settings
+    if !embedders.is_empty() {
+        settings.embedders = Some(embedders);
+    }
+
+    Ok(settings)
 }
 
 pub fn meilisearch_settings_to_schema(settings: MeilisearchSettings) -> Schema {
@@ -172,6 +382,11 @@ pub fn meilisearch_settings_to_schema(settings: MeilisearchSettings) -> Schema {
             facet: false,
             sort: false,
             index: true,
+            array: false,
+            stored: true,
+            analyzer: None,
+            locale: None,
+            vector: None,
         });
     } else {
         for field_name in field_names {
@@ -193,6 +408,19 @@ pub fn meilisearch_settings_to_schema(settings: MeilisearchSettings) -> Schema {
                 .map(|attrs| attrs.contains(&field_name))
                 .unwrap_or(false);
 
+            let stored = settings
+                .displayed_attributes
+                .as_ref()
+                .map(|attrs| attrs.contains(&field_name) || attrs.iter().any(|a| a == "*"))
+                .unwrap_or(true);
+
+            let locale = settings
+                .localized_attributes
+                .iter()
+                .flatten()
+                .find(|rule| rule.attribute_patterns.contains(&field_name))
+                .and_then(|rule| rule.locales.first().cloned());
+
             fields.push(SchemaField {
                 name: field_name,
                 field_type: FieldType::Text,
@@ -200,16 +428,54 @@ pub fn meilisearch_settings_to_schema(settings: MeilisearchSettings) -> Schema {
                 facet,
                 sort,
                 index,
+                array: false,
+                stored,
+                analyzer: None,
+                locale,
+                vector: None,
             });
         }
     }
 
+    for (name, embedder) in settings.embedders.iter().flatten() {
+        fields.push(SchemaField {
+            name: name.clone(),
+            field_type: FieldType::Vector,
+            required: false,
+            facet: false,
+            sort: false,
+            index: true,
+            array: false,
+            stored: true,
+            analyzer: None,
+            locale: None,
+            vector: Some(VectorOptions {
+                dimensions: embedder
+                    .get("dimensions")
+                    .and_then(|d| d.as_u64())
+                    .unwrap_or_default() as u32,
+                similarity: VectorSimilarity::Cosine,
+            }),
+        });
+    }
+
     Schema {
         fields,
         primary_key: None,
     }
 }
 
+/// Meilisearch documents are schemaless and its settings are replaced as a whole, so only the
+/// primary key can't change once documents were added
+pub fn classify_meilisearch_change(diff: &SchemaDiff) -> Classification {
+    match diff.kind {
+        SchemaChangeKind::PrimaryKeyChanged => {
+            needs_reindex("Meilisearch only changes the primary key of an empty index")
+        }
+        _ => in_place(),
+    }
+}
+
 pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
     let mut retry_query = original_query.clone();
 
@@ -231,11 +497,108 @@ fn convert_filters_to_meilisearch(filters: Vec<String>) -> String {
     // In Meilisearch, filter syntax supports expressions like:
     // "genre = horror AND year > 2000"
     // "color = red OR color = blue"
-    filters.join(" AND ")
+    // Each filter is parenthesized, as AND binds tighter than the ORs of a native filter
+    if filters.len() == 1 {
+        return filters.into_iter().next().unwrap_or_default();
+    }
+    filters
+        .iter()
+        .map(|filter| format!("({filter})"))
+        .collect::<Vec<_>>()
+        .join(" AND ")
 }
 
-// for later development :-
-fn _convert_meilisearch_facets_to_golem(
+/// Compiles a provider-neutral filter to the Meilisearch filter syntax. Geo filters only apply
+/// to the `_geo` attribute, and prefix filters are not supported.
+pub fn filter_to_meilisearch(filter: &Filter) -> Result<String, SearchError> {
+    match filter {
+        Filter::Eq(field, value) => Ok(format!("{field} = {}", filter_value_to_meilisearch(value))),
+        Filter::Ne(field, value) => {
+            Ok(format!("{field} != {}", filter_value_to_meilisearch(value)))
+        }
+        Filter::Range(field, bounds) => {
+            let conditions = [
+                (">", &bounds.gt),
+                (">=", &bounds.gte),
+                ("<", &bounds.lt),
+                ("<=", &bounds.lte),
+            ]
+            .into_iter()
+            .filter_map(|(operator, bound)| {
+                bound.as_ref().map(|bound| {
+                    format!("{field} {operator} {}", filter_value_to_meilisearch(bound))
+                })
+            })
+            .collect::<Vec<_>>();
+            Ok(format!("({})", conditions.join(" AND ")))
+        }
+        Filter::In(field, values) => Ok(format!(
+            "{field} IN [{}]",
+            values
+                .iter()
+                .map(filter_value_to_meilisearch)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        Filter::Exists(field) => Ok(format!("{field} EXISTS")),
+        Filter::Prefix(..) => Err(unsupported_filter(filter, "Meilisearch")),
+        Filter::GeoRadius(radius) => {
+            check_geo_field(&radius.field)?;
+            Ok(format!(
+                "_geoRadius({}, {}, {})",
+                radius.center.lat, radius.center.lon, radius.radius_meters
+            ))
+        }
+        Filter::GeoBoundingBox(bbox) => {
+            check_geo_field(&bbox.field)?;
+            // Meilisearch expects the top right and bottom left corners
+            Ok(format!(
+                "_geoBoundingBox([{}, {}], [{}, {}])",
+                bbox.top_left.lat, bbox.bottom_right.lon, bbox.bottom_right.lat, bbox.top_left.lon
+            ))
+        }
+        Filter::And(filters) => Ok(format!(
+            "({})",
+            filters
+                .iter()
+                .map(filter_to_meilisearch)
+                .collect::<Result<Vec<_>, _>>()?
+                .join(" AND ")
+        )),
+        Filter::Or(filters) => Ok(format!(
+            "({})",
+            filters
+                .iter()
+                .map(filter_to_meilisearch)
+                .collect::<Result<Vec<_>, _>>()?
+                .join(" OR ")
+        )),
+        Filter::Not(filter) => Ok(format!("NOT ({})", filter_to_meilisearch(filter)?)),
+    }
+}
+
+fn filter_value_to_meilisearch(value: &FilterValue) -> String {
+    match value {
+        FilterValue::Text(text) => {
+            format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+        }
+        FilterValue::Integer(integer) => integer.to_string(),
+        FilterValue::Float(float) => float.to_string(),
+        FilterValue::Boolean(boolean) => boolean.to_string(),
+    }
+}
+
+fn check_geo_field(field: &str) -> Result<(), SearchError> {
+    if field == "_geo" {
+        Ok(())
+    } else {
+        Err(invalid_query(format!(
+            "Meilisearch only supports geo filters on the _geo attribute, not on {field}"
+        )))
+    }
+}
+
+fn convert_meilisearch_facets_to_golem(
     facets: JsonMap<String, JsonValue>,
 ) -> HashMap<String, HashMap<String, u64>> {
     let mut result = HashMap::new();
@@ -257,10 +620,28 @@ fn _convert_meilisearch_facets_to_golem(
     result
 }
 
+pub fn meilisearch_task_to_task_status(task: MeilisearchTask) -> Result<TaskStatus, SearchError> {
+    match task.status.as_str() {
+        "enqueued" => Ok(TaskStatus::Enqueued),
+        "processing" => Ok(TaskStatus::Processing),
+        "succeeded" => Ok(TaskStatus::Succeeded),
+        "failed" => Ok(TaskStatus::Failed(task.failure_reason())),
+        "canceled" => Ok(TaskStatus::Canceled),
+        status => Err(internal_error(format!(
+            "Unknown status {status} of task {}",
+            task.task_uid
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use golem_search::golem::search::types::{HighlightConfig, SearchConfig};
+    use golem_search::golem::search::types::{
+        CalendarInterval, DateHistogramFacet, FacetOrder, FilterExpression, FilterNode,
+        GeoBoundingBox, GeoPoint, HighlightConfig, RangeBounds, SchemaChangeApplication,
+        SearchConfig, TermsFacet, VectorQuery,
+    };
     use serde_json::json;
 
     #[test]
@@ -341,25 +722,32 @@ mod tests {
                 "category = electronics".to_string(),
                 "price > 100".to_string(),
             ],
+            filter: None,
+            vector: None,
             sort: vec!["price:desc".to_string()],
             facets: vec!["category".to_string(), "brand".to_string()],
+            facet_requests: vec![],
             page: None,
             per_page: Some(20),
             offset: Some(10),
             highlight: Some(HighlightConfig {
-                fields: vec!["title".to_string(), "description".to_string()],
+                fields: vec!["title".to_string()],
                 pre_tag: Some("<mark>".to_string()),
                 post_tag: Some("</mark>".to_string()),
                 max_length: Some(200),
+                snippet_fields: vec!["description".to_string()],
+                snippet_length: Some(12),
+                max_snippets: None,
             }),
             config: None,
         };
 
-        let meilisearch_request = search_query_to_meilisearch_request(search_query);
+        let meilisearch_request =
+            search_query_to_meilisearch_request(search_query.clone()).unwrap();
         assert_eq!(meilisearch_request.q, Some("test query".to_string()));
         assert_eq!(
             meilisearch_request.filter,
-            Some("category = electronics AND price > 100".to_string())
+            Some("(category = electronics) AND (price > 100)".to_string())
         );
         assert_eq!(
             meilisearch_request.sort,
@@ -371,6 +759,31 @@ mod tests {
         );
         assert_eq!(meilisearch_request.limit, Some(20));
         assert_eq!(meilisearch_request.offset, Some(10));
+        assert_eq!(
+            meilisearch_request.attributes_to_highlight,
+            Some(vec!["title".to_string(), "description".to_string()])
+        );
+        assert_eq!(
+            meilisearch_request.attributes_to_crop,
+            Some(vec!["description".to_string()])
+        );
+        assert_eq!(meilisearch_request.crop_length, Some(12));
+        assert_eq!(
+            meilisearch_request.highlight_pre_tag,
+            Some("<mark>".to_string())
+        );
+
+        let histogram = SearchQuery {
+            facet_requests: vec![FacetRequest::DateHistogram(DateHistogramFacet {
+                field: "released".to_string(),
+                interval: CalendarInterval::Month,
+            })],
+            ..search_query
+        };
+        assert!(matches!(
+            search_query_to_meilisearch_request(histogram),
+            Err(SearchError::InvalidQuery(_))
+        ));
     }
 
     #[test]
@@ -378,8 +791,11 @@ mod tests {
         let search_query = SearchQuery {
             q: Some("test".to_string()),
             filters: vec![],
+            filter: None,
+            vector: None,
             sort: vec![],
             facets: vec![],
+            facet_requests: vec![],
             page: None,
             per_page: None,
             offset: None,
@@ -397,7 +813,7 @@ mod tests {
             }),
         };
 
-        let meilisearch_request = search_query_to_meilisearch_request(search_query);
+        let meilisearch_request = search_query_to_meilisearch_request(search_query).unwrap();
         assert_eq!(
             meilisearch_request.attributes_to_retrieve,
             Some(vec!["title".to_string(), "price".to_string()])
@@ -415,6 +831,11 @@ mod tests {
                     facet: false,
                     sort: false,
                     index: true,
+                    array: false,
+                    stored: true,
+                    analyzer: None,
+                    locale: None,
+                    vector: None,
                 },
                 SchemaField {
                     name: "category".to_string(),
@@ -423,6 +844,11 @@ mod tests {
                     facet: true,
                     sort: false,
                     index: true,
+                    array: false,
+                    stored: true,
+                    analyzer: None,
+                    locale: None,
+                    vector: None,
                 },
                 SchemaField {
                     name: "price".to_string(),
@@ -431,12 +857,17 @@ mod tests {
                     facet: true,
                     sort: true,
                     index: false,
+                    array: false,
+                    stored: true,
+                    analyzer: None,
+                    locale: None,
+                    vector: None,
                 },
             ],
             primary_key: Some("id".to_string()),
         };
 
-        let settings = schema_to_meilisearch_settings(schema);
+        let settings = schema_to_meilisearch_settings(schema).unwrap();
         assert_eq!(
             settings.searchable_attributes,
             Some(vec!["title".to_string(), "category".to_string()])
@@ -451,6 +882,155 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_stored_and_localized_fields_round_trip() {
+        let field = |name: &str| SchemaField {
+            name: name.to_string(),
+            field_type: FieldType::Text,
+            required: false,
+            facet: false,
+            sort: false,
+            index: true,
+            array: false,
+            stored: true,
+            analyzer: None,
+            locale: None,
+            vector: None,
+        };
+        let schema = Schema {
+            fields: vec![
+                SchemaField {
+                    locale: Some("fra".to_string()),
+                    ..field("title_fr")
+                },
+                SchemaField {
+                    locale: Some("fra".to_string()),
+                    stored: false,
+                    ..field("body_fr")
+                },
+                field("variants.color"),
+            ],
+            primary_key: Some("id".to_string()),
+        };
+
+        let settings = schema_to_meilisearch_settings(schema.clone()).unwrap();
+        assert_eq!(
+            settings.displayed_attributes,
+            Some(vec![
+                "id".to_string(),
+                "title_fr".to_string(),
+                "variants.color".to_string()
+            ])
+        );
+        assert_eq!(
+            settings.localized_attributes,
+            Some(vec![MeilisearchLocalizedAttributes {
+                attribute_patterns: vec!["title_fr".to_string(), "body_fr".to_string()],
+                locales: vec!["fra".to_string()],
+            }])
+        );
+
+        let fields = meilisearch_settings_to_schema(settings).fields;
+        for field in &schema.fields {
+            assert!(fields.contains(field), "{} isn't read back", field.name);
+        }
+
+        let analyzed = Schema {
+            fields: vec![SchemaField {
+                analyzer: Some("whitespace".to_string()),
+                ..field("title")
+            }],
+            primary_key: None,
+        };
+        assert!(schema_to_meilisearch_settings(analyzed).is_err());
+    }
+
+    #[test]
+    fn test_vector_fields_are_embedders() {
+        let embedding = SchemaField {
+            name: "embedding".to_string(),
+            field_type: FieldType::Vector,
+            required: false,
+            facet: false,
+            sort: false,
+            index: true,
+            array: false,
+            stored: true,
+            analyzer: None,
+            locale: None,
+            vector: Some(VectorOptions {
+                dimensions: 384,
+                similarity: VectorSimilarity::Cosine,
+            }),
+        };
+        let schema = Schema {
+            fields: vec![embedding.clone()],
+            primary_key: None,
+        };
+
+        let settings = schema_to_meilisearch_settings(schema.clone()).unwrap();
+        assert_eq!(settings.searchable_attributes, None);
+        assert_eq!(
+            settings.embedders.as_ref().unwrap()["embedding"],
+            json!({ "source": "userProvided", "dimensions": 384 })
+        );
+        assert!(meilisearch_settings_to_schema(settings)
+            .fields
+            .contains(&embedding));
+
+        let mut euclidean = schema;
+        euclidean.fields[0].vector = Some(VectorOptions {
+            dimensions: 384,
+            similarity: VectorSimilarity::Euclidean,
+        });
+        assert!(schema_to_meilisearch_settings(euclidean).is_err());
+    }
+
+    #[test]
+    fn test_hybrid_search_request() {
+        let search_query = SearchQuery {
+            q: Some("wireless headphones".to_string()),
+            filters: vec![],
+            filter: None,
+            vector: Some(VectorQuery {
+                field: "embedding".to_string(),
+                vector: vec![0.25, 0.5],
+                k: 8,
+                semantic_ratio: Some(0.8),
+                filter: Some(FilterExpression {
+                    nodes: vec![FilterNode::Eq((
+                        "brand".to_string(),
+                        FilterValue::Text("acme".to_string()),
+                    ))],
+                }),
+            }),
+            sort: vec![],
+            facets: vec![],
+            facet_requests: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        };
+
+        let request = search_query_to_meilisearch_request(search_query.clone()).unwrap();
+        assert_eq!(request.vector, Some(vec![0.25, 0.5]));
+        assert_eq!(request.limit, Some(8));
+        assert_eq!(request.filter, Some("brand = \"acme\"".to_string()));
+        let hybrid = request.hybrid.unwrap();
+        assert_eq!(hybrid.embedder, "embedding");
+        assert_eq!(hybrid.semantic_ratio, 0.8);
+
+        // Without q, the search is purely semantic
+        let vector_only = SearchQuery {
+            q: None,
+            ..search_query
+        };
+        let request = search_query_to_meilisearch_request(vector_only).unwrap();
+        assert_eq!(request.hybrid.unwrap().semantic_ratio, 1.0);
+    }
+
     #[test]
     fn test_meilisearch_settings_to_schema() {
         let settings = MeilisearchSettings {
@@ -479,7 +1059,11 @@ mod tests {
     fn test_meilisearch_response_to_search_results() {
         let hit1 = json!({
             "id": "doc1",
-            "title": "Test Document 1"
+            "title": "Test Document 1",
+            "_formatted": {
+                "id": "doc1",
+                "title": "Test <em>Document</em> 1"
+            }
         })
         .as_object()
         .unwrap()
@@ -510,17 +1094,56 @@ mod tests {
             offset: 0,
             processing_time_ms: 5,
             facet_distribution: Some(facet_distribution),
+            facet_stats: json!({ "price": { "min": 9.5, "max": 120.0 } })
+                .as_object()
+                .cloned(),
             query: "test".to_string(),
         };
 
-        let search_results = meilisearch_response_to_search_results(meilisearch_response);
+        let facets = vec![
+            FacetRequest::Terms(TermsFacet {
+                field: "category".to_string(),
+                size: Some(1),
+                order: FacetOrder::Value,
+            }),
+            FacetRequest::Stats("price".to_string()),
+        ];
+        let highlight = HighlightConfig {
+            fields: vec![],
+            pre_tag: None,
+            post_tag: None,
+            max_length: None,
+            snippet_fields: vec![],
+            snippet_length: None,
+            max_snippets: None,
+        };
+        let search_results =
+            meilisearch_response_to_search_results(meilisearch_response, &facets, Some(&highlight));
         assert_eq!(search_results.total, Some(2));
         assert_eq!(search_results.per_page, Some(20));
         assert_eq!(search_results.hits.len(), 2);
         assert_eq!(search_results.hits[0].id, "doc1");
         assert_eq!(search_results.hits[1].id, "doc2");
+        assert!(!search_results.hits[0]
+            .content
+            .as_ref()
+            .unwrap()
+            .contains("_formatted"));
+        let highlight_fields = &search_results.hits[0].highlight_fields;
+        assert_eq!(highlight_fields.len(), 1);
+        assert_eq!(highlight_fields[0].field, "title");
+        assert_eq!(highlight_fields[0].fragments[0].text, "Test Document 1");
+        assert!(search_results.hits[1].highlight_fields.is_empty());
         assert!(search_results.facets.is_some());
         assert_eq!(search_results.took_ms, Some(5));
+
+        let facet_results = search_results.facet_results;
+        assert_eq!(facet_results[0].buckets.len(), 1);
+        assert_eq!(facet_results[0].buckets[0].value, "books");
+        let stats = facet_results[1].stats.clone().unwrap();
+        assert_eq!(stats.min, Some(9.5));
+        assert_eq!(stats.max, Some(120.0));
+        assert_eq!(stats.sum, None);
     }
 
     #[test]
@@ -528,8 +1151,11 @@ mod tests {
         let original_query = SearchQuery {
             q: Some("test".to_string()),
             filters: vec![],
+            filter: None,
+            vector: None,
             sort: vec![],
             facets: vec![],
+            facet_requests: vec![],
             page: None,
             per_page: Some(10),
             offset: Some(20),
@@ -542,6 +1168,7 @@ mod tests {
             score: Some(1.0),
             content: Some("{}".to_string()),
             highlights: None,
+            highlight_fields: vec![],
         }];
 
         let retry_query = create_retry_query(&original_query, &partial_hits);
@@ -553,8 +1180,11 @@ mod tests {
         let original_query = SearchQuery {
             q: Some("test".to_string()),
             filters: vec![],
+            filter: None,
+            vector: None,
             sort: vec![],
             facets: vec![],
+            facet_requests: vec![],
             page: None,
             per_page: Some(2),
             offset: Some(10),
@@ -568,12 +1198,14 @@ mod tests {
                 score: Some(1.0),
                 content: Some("{}".to_string()),
                 highlights: None,
+                highlight_fields: vec![],
             },
             SearchHit {
                 id: "doc2".to_string(),
                 score: Some(1.0),
                 content: Some("{}".to_string()),
                 highlights: None,
+                highlight_fields: vec![],
             },
         ];
 
@@ -588,7 +1220,99 @@ mod tests {
             "price > 100".to_string(),
         ];
         let meilisearch_filter = convert_filters_to_meilisearch(filters);
-        assert_eq!(meilisearch_filter, "category = electronics AND price > 100");
+        assert_eq!(
+            meilisearch_filter,
+            "(category = electronics) AND (price > 100)"
+        );
+
+        let single = convert_filters_to_meilisearch(vec!["price > 100".to_string()]);
+        assert_eq!(single, "price > 100");
+    }
+
+    #[test]
+    fn native_filters_keep_their_precedence() {
+        let search_query = SearchQuery {
+            q: None,
+            filters: vec!["color = red OR color = blue".to_string()],
+            filter: Some(FilterExpression {
+                nodes: vec![FilterNode::Eq((
+                    "in_stock".to_string(),
+                    FilterValue::Boolean(true),
+                ))],
+            }),
+            vector: None,
+            sort: vec![],
+            facets: vec![],
+            facet_requests: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        };
+
+        let request = search_query_to_meilisearch_request(search_query).unwrap();
+        assert_eq!(
+            request.filter,
+            Some("(color = red OR color = blue) AND (in_stock = true)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_filter_to_meilisearch() {
+        let filter = resolve_filter(&FilterExpression {
+            nodes: vec![
+                FilterNode::And(vec![1, 2, 3]),
+                FilterNode::Eq((
+                    "title".to_string(),
+                    FilterValue::Text("The \"Great\" Gatsby".to_string()),
+                )),
+                FilterNode::Or(vec![4, 5]),
+                FilterNode::Not(6),
+                FilterNode::Range((
+                    "year".to_string(),
+                    RangeBounds {
+                        gt: Some(FilterValue::Integer(1900)),
+                        gte: None,
+                        lt: None,
+                        lte: Some(FilterValue::Integer(1950)),
+                    },
+                )),
+                FilterNode::In((
+                    "genre".to_string(),
+                    vec![
+                        FilterValue::Text("fiction".to_string()),
+                        FilterValue::Text("drama".to_string()),
+                    ],
+                )),
+                FilterNode::GeoBbox(GeoBoundingBox {
+                    field: "_geo".to_string(),
+                    top_left: GeoPoint {
+                        lat: 50.0,
+                        lon: 1.0,
+                    },
+                    bottom_right: GeoPoint {
+                        lat: 40.0,
+                        lon: 9.5,
+                    },
+                }),
+            ],
+        })
+        .unwrap();
+
+        assert_eq!(
+            filter_to_meilisearch(&filter).unwrap(),
+            r#"(title = "The \"Great\" Gatsby" AND ((year > 1900 AND year <= 1950) OR genre IN ["fiction", "drama"]) AND NOT (_geoBoundingBox([50, 9.5], [40, 1])))"#
+        );
+
+        let prefix = resolve_filter(&FilterExpression {
+            nodes: vec![FilterNode::Prefix(("title".to_string(), "Gat".to_string()))],
+        })
+        .unwrap();
+        assert!(matches!(
+            filter_to_meilisearch(&prefix),
+            Err(SearchError::InvalidQuery(_))
+        ));
     }
 
     #[test]
@@ -603,7 +1327,7 @@ mod tests {
         .unwrap()
         .clone();
 
-        let golem_facets = _convert_meilisearch_facets_to_golem(facets);
+        let golem_facets = convert_meilisearch_facets_to_golem(facets);
         assert_eq!(golem_facets.len(), 1);
         assert_eq!(
             golem_facets.get("category").unwrap().get("electronics"),
@@ -611,4 +1335,64 @@ mod tests {
         );
         assert_eq!(golem_facets.get("category").unwrap().get("books"), Some(&3));
     }
+
+    #[test]
+    fn test_meilisearch_task_to_task_status() {
+        let task: MeilisearchTask = serde_json::from_value(json!({
+            "uid": 12,
+            "indexUid": "books",
+            "status": "failed",
+            "type": "documentAdditionOrUpdate",
+            "enqueuedAt": "2024-01-01T00:00:00Z",
+            "error": {
+                "message": "Document doesn't have a `id` attribute",
+                "code": "missing_document_id",
+                "type": "invalid_request",
+                "link": "https://docs.meilisearch.com/errors#missing_document_id"
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            meilisearch_task_to_task_status(task).unwrap(),
+            TaskStatus::Failed(
+                "missing_document_id: Document doesn't have a `id` attribute".to_string()
+            )
+        );
+
+        let task: MeilisearchTask = serde_json::from_value(json!({
+            "taskUid": 13,
+            "status": "enqueued",
+            "type": "indexDeletion",
+            "enqueuedAt": "2024-01-01T00:00:00Z"
+        }))
+        .unwrap();
+        assert_eq!(
+            meilisearch_task_to_task_status(task).unwrap(),
+            TaskStatus::Enqueued
+        );
+    }
+
+    #[test]
+    fn test_classify_meilisearch_change() {
+        let diff = |kind| SchemaDiff {
+            kind,
+            field: "year".to_string(),
+            current: None,
+            target: None,
+        };
+
+        assert_eq!(
+            classify_meilisearch_change(&diff(SchemaChangeKind::FieldTypeChanged)),
+            in_place()
+        );
+        assert_eq!(
+            classify_meilisearch_change(&diff(SchemaChangeKind::FieldRemoved)),
+            in_place()
+        );
+        assert_eq!(
+            classify_meilisearch_change(&diff(SchemaChangeKind::PrimaryKeyChanged)).application,
+            SchemaChangeApplication::NeedsReindex
+        );
+    }
 }
@@ -1,30 +1,71 @@
 use crate::client::MeilisearchApi;
 use crate::conversions::{
-    create_retry_query, doc_to_meilisearch_document, meilisearch_document_to_doc,
-    meilisearch_response_to_search_results, meilisearch_settings_to_schema,
+    classify_meilisearch_change, create_retry_query, doc_to_meilisearch_document,
+    meilisearch_document_to_doc, meilisearch_response_to_search_results,
+    meilisearch_settings_to_schema, meilisearch_task_to_task_status,
     schema_to_meilisearch_settings, search_query_to_meilisearch_request,
 };
 use golem_rust::wasm_rpc::Pollable;
+use golem_search::bulk::{
+    doc_chunks, id_chunks, send_converted, send_with_retries, write_chunks, ItemOutcome,
+};
 use golem_search::config::with_config_keys;
 use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::error::invalid_query;
+use golem_search::facets::facet_requests;
 use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
 use golem_search::golem::search::types::{
-    Doc, DocumentId, IndexName, Schema, SearchError, SearchHit, SearchQuery, SearchResults,
+    BulkResult, Doc, DocumentId, IndexAlias, IndexName, ReindexResult, ReindexTransform, Schema,
+    SchemaChange, SchemaUpdateMode, SearchError, SearchHit, SearchQuery, SearchResults, TaskId,
+    TaskStatus, WriteConsistency,
 };
-use std::cell::{Cell, RefCell};
+use golem_search::schema::{check_in_place, plan_schema_change, reindex_to_schema};
+use log::trace;
+use std::cell::Cell;
 
 mod client;
 mod conversions;
 
-/// Simple search stream implementation for Meilisearch
-/// Since Meilisearch doesn't have native streaming, we implement pagination-based streaming
+/// Waits for the task when the write must be visible, and returns its id
+fn finish_task(
+    client: &MeilisearchApi,
+    task_uid: u64,
+    consistency: WriteConsistency,
+) -> Result<Option<TaskId>, SearchError> {
+    if consistency == WriteConsistency::WaitForVisible {
+        client.wait_for_task(task_uid)?;
+    }
+    Ok(Some(task_uid.to_string()))
+}
+
+/// A document task is applied as a whole, so its failure is the failure of each of its documents.
+/// Without waiting for the task, its documents are reported as written once it is enqueued.
+fn document_task_outcomes(
+    client: &MeilisearchApi,
+    task_uid: u64,
+    consistency: WriteConsistency,
+    len: usize,
+) -> Result<Vec<ItemOutcome>, SearchError> {
+    let failure = match consistency {
+        WriteConsistency::NoWait => None,
+        WriteConsistency::WaitForVisible => client.wait_for_document_task(task_uid)?,
+    };
+
+    Ok(match failure {
+        Some(reason) => vec![ItemOutcome::failure(reason, false); len],
+        None => vec![ItemOutcome::success(); len],
+    })
+}
+
+/// Pagination-based search stream, as Meilisearch has neither cursors nor points in time.
+/// Its cursor is the offset of the next page, so that a resumed stream continues exactly after
+/// the hits returned so far.
 struct MeilisearchSearchStream {
     client: MeilisearchApi,
     index_name: String,
     query: SearchQuery,
-    current_page: Cell<u32>,
+    next_offset: Cell<Option<u32>>,
     finished: Cell<bool>,
-    last_response: RefCell<Option<SearchResults>>,
 }
 
 impl MeilisearchSearchStream {
@@ -32,64 +73,87 @@ impl MeilisearchSearchStream {
         Self {
             client,
             index_name,
-            query: query.clone(),
-            current_page: Cell::new(query.offset.unwrap_or(0) / query.page.unwrap_or(20)),
+            query,
+            next_offset: Cell::new(None),
+            finished: Cell::new(false),
+        }
+    }
+
+    pub fn resumed(
+        client: MeilisearchApi,
+        index_name: String,
+        query: SearchQuery,
+        next_offset: u32,
+    ) -> Self {
+        Self {
+            client,
+            index_name,
+            query,
+            next_offset: Cell::new(Some(next_offset)),
             finished: Cell::new(false),
-            last_response: RefCell::new(None),
         }
     }
 
     pub fn subscribe(&self) -> Pollable {
         golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
     }
-}
 
-impl GuestSearchStream for MeilisearchSearchStream {
-    fn get_next(&self) -> Option<Vec<SearchHit>> {
+    pub fn next_offset(&self) -> Option<u32> {
+        self.next_offset.get()
+    }
+
+    /// Returns the next page of hits, an empty page once all the hits have been returned, or the
+    /// error which ended the stream
+    fn next_page(&self) -> Result<Vec<SearchHit>, SearchError> {
         if self.finished.get() {
-            return Some(vec![]);
+            return Ok(vec![]);
+        }
+
+        let result = self.read_page();
+        if result.is_err() {
+            self.finished.set(true);
         }
+        result
+    }
 
+    fn read_page(&self) -> Result<Vec<SearchHit>, SearchError> {
         let mut search_query = self.query.clone();
-        let current_page = self.current_page.get();
+        let current_offset = self
+            .next_offset
+            .get()
+            .unwrap_or(self.query.offset.unwrap_or(0));
         let limit = search_query.per_page.unwrap_or(20);
 
-        search_query.offset = Some(current_page * limit);
-
-        let meilisearch_request = search_query_to_meilisearch_request(search_query);
-
-        match self.client.search(&self.index_name, &meilisearch_request) {
-            Ok(response) => {
-                let search_results = meilisearch_response_to_search_results(response);
-
-                if search_results.hits.is_empty() {
-                    self.finished.set(true);
-                    return Some(vec![]);
-                }
+        search_query.offset = Some(current_offset);
+        search_query.per_page = Some(limit);
 
-                if let (Some(total), Some(per_page)) =
-                    (search_results.total, search_results.per_page)
-                {
-                    let current_offset = current_page * per_page;
-                    let next_offset = current_offset + per_page;
-                    if next_offset >= total {
-                        self.finished.set(true);
-                    }
-                }
+        let meilisearch_request = search_query_to_meilisearch_request(search_query)?;
+        let response = self.client.search(&self.index_name, &meilisearch_request)?;
+        let search_results =
+            meilisearch_response_to_search_results(response, &[], self.query.highlight.as_ref());
 
-                if (search_results.hits.len() as u32) < limit {
-                    self.finished.set(true);
-                }
+        let received_count = search_results.hits.len() as u32;
+        self.next_offset.set(Some(current_offset + received_count));
 
-                self.current_page.set(current_page + 1);
+        if received_count < limit {
+            self.finished.set(true);
+        }
+        if let Some(total) = search_results.total {
+            if current_offset + received_count >= total {
+                self.finished.set(true);
+            }
+        }
 
-                let hits = search_results.hits.clone();
-                *self.last_response.borrow_mut() = Some(search_results);
+        Ok(search_results.hits)
+    }
+}
 
-                Some(hits)
-            }
-            Err(_) => {
-                self.finished.set(true);
+impl GuestSearchStream for MeilisearchSearchStream {
+    fn get_next(&self) -> Option<Vec<SearchHit>> {
+        match self.next_page() {
+            Ok(hits) => Some(hits),
+            Err(e) => {
+                trace!("Search stream failed: {e:?}");
                 Some(vec![])
             }
         }
@@ -126,8 +190,13 @@ impl MeilisearchComponent {
 impl Guest for MeilisearchComponent {
     type SearchStream = MeilisearchSearchStream;
 
-    fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+    fn create_index(
+        name: IndexName,
+        schema: Option<Schema>,
+        consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
         let client = Self::create_client()?;
+        let settings = schema.map(schema_to_meilisearch_settings).transpose()?;
 
         let create_request = client::MeilisearchCreateIndexRequest {
             uid: name.clone(),
@@ -135,25 +204,27 @@ impl Guest for MeilisearchComponent {
         };
 
         let task = client.create_index(&create_request)?;
-
-        client.wait_for_task(task.task_uid)?;
-
-        if let Some(schema) = schema {
-            let settings = schema_to_meilisearch_settings(schema);
-            let settings_task = client.update_settings(&name, &settings)?;
-            client.wait_for_task(settings_task.task_uid)?;
+        let task_id = finish_task(&client, task.task_uid, consistency)?;
+
+        // The tasks of an index are processed in order, so the settings are applied once the
+        // index is created
+        match settings {
+            Some(settings) => {
+                let settings_task = client.update_settings(&name, &settings)?;
+                finish_task(&client, settings_task.task_uid, consistency)
+            }
+            None => Ok(task_id),
         }
-
-        Ok(())
     }
 
-    fn delete_index(name: IndexName) -> Result<(), SearchError> {
+    fn delete_index(
+        name: IndexName,
+        consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
         let client = Self::create_client()?;
 
         let task = client.delete_index(&name)?;
-        client.wait_for_task(task.task_uid)?;
-
-        Ok(())
+        finish_task(&client, task.task_uid, consistency)
     }
 
     fn list_indexes() -> Result<Vec<IndexName>, SearchError> {
@@ -167,49 +238,128 @@ impl Guest for MeilisearchComponent {
             .collect())
     }
 
-    fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
-        let client = Self::create_client()?;
-        let meilisearch_doc =
-            doc_to_meilisearch_document(doc).map_err(SearchError::InvalidQuery)?;
-
-        let task = client.add_documents(&index, &[meilisearch_doc])?;
-        client.wait_for_task(task.task_uid)?;
-
-        Ok(())
+    fn create_alias(_alias: IndexName, _index: IndexName) -> Result<(), SearchError> {
+        // Meilisearch has no aliases, an index can only be swapped with another one
+        Err(SearchError::Unsupported)
     }
 
-    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+    /// The alias is an existing index, whose content is exchanged with the content of the given
+    /// index, so that the given index holds the previous content of the alias afterwards
+    fn swap_alias(alias: IndexName, index: IndexName) -> Result<(), SearchError> {
         let client = Self::create_client()?;
-        let mut meilisearch_docs = Vec::new();
 
-        for doc in docs {
-            let meilisearch_doc =
-                doc_to_meilisearch_document(doc).map_err(SearchError::InvalidQuery)?;
-            meilisearch_docs.push(meilisearch_doc);
-        }
+        let task = client.swap_indexes(&alias, &index)?;
+        client.wait_for_task(task.task_uid)
+    }
 
-        let task = client.add_documents(&index, &meilisearch_docs)?;
-        client.wait_for_task(task.task_uid)?;
+    fn list_aliases() -> Result<Vec<IndexAlias>, SearchError> {
+        Err(SearchError::Unsupported)
+    }
 
-        Ok(())
+    fn delete_alias(_alias: IndexName) -> Result<(), SearchError> {
+        Err(SearchError::Unsupported)
     }
 
-    fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+    fn reindex(
+        source: IndexName,
+        target: IndexName,
+        alias: Option<IndexName>,
+        transform: Option<ReindexTransform>,
+    ) -> Result<ReindexResult, SearchError> {
+        golem_search::reindex::reindex::<Self>(source, target, alias, transform, Self::next_page)
+    }
+
+    fn upsert(
+        index: IndexName,
+        doc: Doc,
+        consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
         let client = Self::create_client()?;
+        let meilisearch_doc =
+            doc_to_meilisearch_document(doc).map_err(SearchError::InvalidQuery)?;
 
-        let task = client.delete_document(&index, &id)?;
-        client.wait_for_task(task.task_uid)?;
+        let task = client.add_documents(&index, &[meilisearch_doc])?;
+        finish_task(&client, task.task_uid, consistency)
+    }
 
-        Ok(())
+    fn upsert_many(
+        index: IndexName,
+        docs: Vec<Doc>,
+        consistency: WriteConsistency,
+    ) -> Result<BulkResult, SearchError> {
+        let client = Self::create_client()?;
+        let limits = Self::bulk_limits();
+
+        write_chunks(
+            doc_chunks(docs, &limits),
+            |doc| doc.id.as_str(),
+            |docs| {
+                let mut task_ids = Vec::new();
+                let mut result = send_with_retries(
+                    docs,
+                    |doc| doc.id.as_str(),
+                    &limits,
+                    |docs| {
+                        send_converted(
+                            docs,
+                            |doc| doc_to_meilisearch_document(doc.clone()),
+                            |meilisearch_docs| {
+                                let task = client.add_documents(&index, meilisearch_docs)?;
+                                task_ids.push(task.task_uid.to_string());
+                                document_task_outcomes(
+                                    &client,
+                                    task.task_uid,
+                                    consistency,
+                                    meilisearch_docs.len(),
+                                )
+                            },
+                        )
+                    },
+                )?;
+                result.task_ids = task_ids;
+                Ok(result)
+            },
+        )
     }
 
-    fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+    fn delete(
+        index: IndexName,
+        id: DocumentId,
+        consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
         let client = Self::create_client()?;
 
-        let task = client.delete_documents(&index, &ids)?;
-        client.wait_for_task(task.task_uid)?;
+        let task = client.delete_document(&index, &id)?;
+        finish_task(&client, task.task_uid, consistency)
+    }
 
-        Ok(())
+    fn delete_many(
+        index: IndexName,
+        ids: Vec<DocumentId>,
+        consistency: WriteConsistency,
+    ) -> Result<BulkResult, SearchError> {
+        let client = Self::create_client()?;
+        let limits = Self::bulk_limits();
+
+        write_chunks(
+            id_chunks(ids, &limits),
+            |id| id.as_str(),
+            |ids| {
+                let mut task_ids = Vec::new();
+                let mut result = send_with_retries(
+                    ids,
+                    |id| id.as_str(),
+                    &limits,
+                    |ids| {
+                        let task = client.delete_documents(&index, ids)?;
+                        task_ids.push(task.task_uid.to_string());
+                        document_task_outcomes(&client, task.task_uid, consistency, ids.len())
+                    },
+                )?;
+                result.task_ids = task_ids;
+                Ok(result)
+            },
+        )
     }
 
     fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
@@ -223,14 +373,22 @@ impl Guest for MeilisearchComponent {
 
     fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
         let client = Self::create_client()?;
-        let meilisearch_request = search_query_to_meilisearch_request(query);
+        let facets = facet_requests(&query)?;
+        let highlight = query.highlight.clone();
+        let meilisearch_request = search_query_to_meilisearch_request(query)?;
 
         let response = client.search(&index, &meilisearch_request)?;
-        Ok(meilisearch_response_to_search_results(response))
+        Ok(meilisearch_response_to_search_results(
+            response,
+            &facets,
+            highlight.as_ref(),
+        ))
     }
 
     fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
         let client = Self::create_client()?;
+        // Rejects invalid filters before the stream is created
+        search_query_to_meilisearch_request(query.clone())?;
         let stream = MeilisearchSearchStream::new(client, index, query);
         Ok(SearchStream::new(stream))
     }
@@ -242,13 +400,43 @@ impl Guest for MeilisearchComponent {
         Ok(meilisearch_settings_to_schema(settings))
     }
 
-    fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
+    fn plan_schema_change(
+        index: IndexName,
+        schema: Schema,
+    ) -> Result<Vec<SchemaChange>, SearchError> {
+        let current = Self::get_schema(index)?;
+        Ok(plan_schema_change(
+            &current,
+            &schema,
+            classify_meilisearch_change,
+        ))
+    }
+
+    fn update_schema(
+        index: IndexName,
+        schema: Schema,
+        consistency: WriteConsistency,
+        mode: SchemaUpdateMode,
+    ) -> Result<Option<TaskId>, SearchError> {
+        if let SchemaUpdateMode::Reindex(reindex) = mode {
+            return reindex_to_schema::<Self>(index, schema, reindex);
+        }
+        check_in_place(Self::plan_schema_change(index.clone(), schema.clone()))?;
+
         let client = Self::create_client()?;
-        let settings = schema_to_meilisearch_settings(schema);
+        let settings = schema_to_meilisearch_settings(schema)?;
 
-        let _task = client.update_settings(&index, &settings)?;
+        let task = client.update_settings(&index, &settings)?;
+        finish_task(&client, task.task_uid, consistency)
+    }
 
-        Ok(())
+    fn task_status(_index: IndexName, task_id: TaskId) -> Result<TaskStatus, SearchError> {
+        let client = Self::create_client()?;
+        let task_uid = task_id
+            .parse()
+            .map_err(|_| invalid_query(format!("Invalid task id: {task_id}")))?;
+
+        meilisearch_task_to_task_status(client.get_task(task_uid)?)
     }
 }
 
@@ -260,6 +448,20 @@ impl ExtendedGuest for MeilisearchComponent {
         MeilisearchSearchStream::new(client, index, query)
     }
 
+    fn resumed_stream(index: IndexName, query: SearchQuery, cursor: String) -> Self::SearchStream {
+        let client = Self::create_client()
+            .unwrap_or_else(|_| MeilisearchApi::new("http://localhost:7700".to_string(), None));
+
+        match cursor.parse() {
+            Ok(next_offset) => MeilisearchSearchStream::resumed(client, index, query, next_offset),
+            Err(_) => MeilisearchSearchStream::new(client, index, query),
+        }
+    }
+
+    fn stream_cursor(stream: &Self::SearchStream) -> Option<String> {
+        stream.next_offset().map(|offset| offset.to_string())
+    }
+
     fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
         create_retry_query(original_query, partial_hits)
     }
@@ -267,6 +469,10 @@ impl ExtendedGuest for MeilisearchComponent {
     fn subscribe(stream: &Self::SearchStream) -> Pollable {
         stream.subscribe()
     }
+
+    fn next_page(stream: &Self::SearchStream) -> Result<Vec<SearchHit>, SearchError> {
+        stream.next_page()
+    }
 }
 
 type DurableMeilisearchComponent = DurableSearch<MeilisearchComponent>;
@@ -2,10 +2,11 @@ use golem_search::config::{get_max_retries_config, get_timeout_config};
 use golem_search::error::{from_reqwest_error, internal_error, search_error_from_status};
 use golem_search::golem::search::types::SearchError;
 use log::trace;
-use reqwest::{Client, Method, RequestBuilder, Response};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::time::Duration;
 
@@ -35,6 +36,10 @@ pub struct OpenSearchMappings {
     pub properties: Option<Map<String, Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dynamic: Option<bool>,
+    #[serde(rename = "_source", skip_serializing_if = "Option::is_none")]
+    pub source: Option<Value>,
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -53,6 +58,10 @@ pub struct OpenSearchQuery {
     pub aggs: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub _source: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pit: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_after: Option<Vec<Value>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -63,6 +72,8 @@ pub struct OpenSearchSearchResponse {
     pub hits: OpenSearchHits,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub aggregations: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pit_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -93,6 +104,8 @@ pub struct OpenSearchHit {
     pub source: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub highlight: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<Vec<Value>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -123,6 +136,15 @@ pub struct OpenSearchBulkResponse {
     pub items: Vec<Value>,
 }
 
+/// Aliases of each index, keyed by index name
+pub type OpenSearchAliases = BTreeMap<String, OpenSearchIndexAliases>;
+
+#[derive(Debug, Deserialize)]
+pub struct OpenSearchIndexAliases {
+    #[serde(default)]
+    pub aliases: Map<String, Value>,
+}
+
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
 pub struct OpenSearchIndexInfo {
@@ -159,21 +181,8 @@ pub struct OpenSearchError {
 }
 
 #[derive(Debug, Deserialize)]
-#[allow(dead_code)]
-pub struct OpenSearchScrollResponse {
-    #[serde(rename = "_scroll_id")]
-    pub scroll_id: String,
-    pub took: u32,
-    pub timed_out: bool,
-    pub hits: OpenSearchHits,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub aggregations: Option<Value>,
-}
-
-#[derive(Debug, Serialize)]
-pub struct ScrollRequest {
-    pub scroll: String,
-    pub scroll_id: String,
+pub struct OpenSearchPointInTime {
+    pub pit_id: String,
 }
 
 impl OpenSearchApi {
@@ -371,6 +380,39 @@ impl OpenSearchApi {
         }
     }
 
+    /// Returns the aliases of each index, only the given alias if any
+    pub fn get_aliases(&self, alias: Option<&str>) -> Result<OpenSearchAliases, SearchError> {
+        trace!("Getting aliases: {alias:?}");
+
+        let url = match alias {
+            Some(alias) => format!("{}/_alias/{}", self.base_url, alias),
+            None => format!("{}/_alias", self.base_url),
+        };
+
+        let response =
+            self.execute_with_retry_sync(|| self.create_request(Method::GET, &url).send())?;
+
+        parse_response(response)
+    }
+
+    /// Applies the alias actions atomically
+    pub fn update_aliases(&self, actions: Vec<Value>) -> Result<(), SearchError> {
+        trace!("Updating aliases with {} actions", actions.len());
+
+        let url = format!("{}/_aliases", self.base_url);
+        let body = json!({ "actions": actions });
+
+        let response = self.execute_with_retry_sync(|| {
+            self.create_request(Method::POST, &url).json(&body).send()
+        })?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(search_error_from_status(response.status()))
+        }
+    }
+
     pub fn list_indices(&self) -> Result<Vec<OpenSearchIndexInfo>, SearchError> {
         trace!("Listing indices");
 
@@ -387,10 +429,17 @@ impl OpenSearchApi {
         index_name: &str,
         id: &str,
         document: &Value,
+        wait_for_refresh: bool,
     ) -> Result<(), SearchError> {
         trace!("Indexing document {id} in index: {index_name}");
 
-        let url = format!("{}/{}/_doc/{}", self.base_url, index_name, id);
+        let url = format!(
+            "{}/{}/_doc/{}{}",
+            self.base_url,
+            index_name,
+            id,
+            refresh_param(wait_for_refresh)
+        );
 
         let response = self.execute_with_retry_sync(|| {
             self.create_request(Method::PUT, &url).json(document).send()
@@ -403,10 +452,14 @@ impl OpenSearchApi {
         }
     }
 
-    pub fn bulk_index(&self, operations: &str) -> Result<OpenSearchBulkResponse, SearchError> {
+    pub fn bulk_index(
+        &self,
+        operations: &str,
+        wait_for_refresh: bool,
+    ) -> Result<OpenSearchBulkResponse, SearchError> {
         trace!("Performing bulk index operation");
 
-        let url = format!("{}/_bulk", self.base_url);
+        let url = format!("{}/_bulk{}", self.base_url, refresh_param(wait_for_refresh));
 
         let response = self.execute_with_retry_sync(|| {
             self.create_request_with_content_type(Method::POST, &url, "application/x-ndjson")
@@ -417,10 +470,21 @@ impl OpenSearchApi {
         parse_response(response)
     }
 
-    pub fn delete_document(&self, index_name: &str, id: &str) -> Result<(), SearchError> {
+    pub fn delete_document(
+        &self,
+        index_name: &str,
+        id: &str,
+        wait_for_refresh: bool,
+    ) -> Result<(), SearchError> {
         trace!("Deleting document {id} from index: {index_name}");
 
-        let url = format!("{}/{}/_doc/{}", self.base_url, index_name, id);
+        let url = format!(
+            "{}/{}/_doc/{}{}",
+            self.base_url,
+            index_name,
+            id,
+            refresh_param(wait_for_refresh)
+        );
 
         let response =
             self.execute_with_retry_sync(|| self.create_request(Method::DELETE, &url).send())?;
@@ -470,55 +534,48 @@ impl OpenSearchApi {
         parse_response(response)
     }
 
-    pub fn search_with_scroll(
+    pub fn create_point_in_time(
         &self,
         index_name: &str,
-        query: &OpenSearchQuery,
-        scroll_timeout: &str,
-    ) -> Result<OpenSearchScrollResponse, SearchError> {
-        trace!("Searching index {index_name} with scroll, timeout: {scroll_timeout}");
+        keep_alive: &str,
+    ) -> Result<String, SearchError> {
+        trace!("Creating point in time on index {index_name}, keep alive: {keep_alive}");
 
         let url = format!(
-            "{}/{}/_search?scroll={}",
-            self.base_url, index_name, scroll_timeout
+            "{}/{}/_search/point_in_time?keep_alive={}",
+            self.base_url, index_name, keep_alive
         );
 
-        let response = self.execute_with_retry_sync(|| {
-            self.create_request(Method::POST, &url).json(query).send()
-        })?;
+        let response =
+            self.execute_with_retry_sync(|| self.create_request(Method::POST, &url).send())?;
 
-        parse_response(response)
+        parse_response::<OpenSearchPointInTime>(response).map(|pit| pit.pit_id)
     }
 
-    pub fn scroll(
+    /// Searches a point in time, the query must not target an index
+    /// Searches a point in time, the query must not target an index. Returns `None` if the point
+    /// in time has expired.
+    pub fn search_point_in_time(
         &self,
-        scroll_id: &str,
-        scroll_timeout: &str,
-    ) -> Result<OpenSearchScrollResponse, SearchError> {
-        trace!("Scrolling with ID: {scroll_id} and timeout: {scroll_timeout}");
+        query: &OpenSearchQuery,
+    ) -> Result<Option<OpenSearchSearchResponse>, SearchError> {
+        trace!("Searching point in time with query: {query:?}");
 
-        let url = format!("{}/_search/scroll", self.base_url);
+        let url = format!("{}/_search", self.base_url);
 
         let response = self.execute_with_retry_sync(|| {
-            let scroll_request = ScrollRequest {
-                scroll: scroll_timeout.to_string(),
-                scroll_id: scroll_id.to_string(),
-            };
-
-            self.create_request(Method::POST, &url)
-                .json(&scroll_request)
-                .send()
+            self.create_request(Method::POST, &url).json(query).send()
         })?;
 
-        parse_response(response)
+        parse_point_in_time_response(response)
     }
 
-    pub fn clear_scroll(&self, scroll_id: &str) -> Result<(), SearchError> {
-        trace!("Clearing scroll: {scroll_id}");
+    pub fn delete_point_in_time(&self, pit_id: &str) -> Result<(), SearchError> {
+        trace!("Deleting point in time: {pit_id}");
 
-        let url = format!("{}/_search/scroll", self.base_url);
+        let url = format!("{}/_search/point_in_time", self.base_url);
         let request_body = json!({
-            "scroll_id": scroll_id
+            "pit_id": [pit_id]
         });
 
         let response = self.execute_with_retry_sync(|| {
@@ -566,6 +623,36 @@ impl OpenSearchApi {
     }
 }
 
+/// `refresh=wait_for` returns once the write is visible to searches, without forcing a refresh
+fn refresh_param(wait_for_refresh: bool) -> &'static str {
+    if wait_for_refresh {
+        "?refresh=wait_for"
+    } else {
+        ""
+    }
+}
+
+/// Expired points in time are reported with a `404` and a `search_context_missing_exception`
+fn parse_point_in_time_response<T: DeserializeOwned + Debug>(
+    response: Response,
+) -> Result<Option<T>, SearchError> {
+    if response.status() != StatusCode::NOT_FOUND {
+        return parse_response(response).map(Some);
+    }
+
+    let error_body = response
+        .text()
+        .map_err(|err| from_reqwest_error("Failed to receive error response body", err))?;
+
+    trace!("Received 404 response from OpenSearch API: {error_body:?}");
+
+    if error_body.contains("search_context_missing_exception") {
+        Ok(None)
+    } else {
+        Err(search_error_from_status(StatusCode::NOT_FOUND))
+    }
+}
+
 fn parse_response<T: DeserializeOwned + Debug>(response: Response) -> Result<T, SearchError> {
     let status = response.status();
 
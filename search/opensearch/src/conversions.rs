@@ -1,10 +1,25 @@
 use crate::client::{
-    OpenSearchMappings, OpenSearchQuery, OpenSearchScrollResponse, OpenSearchSearchResponse,
-    OpenSearchSettings,
+    OpenSearchAliases, OpenSearchBulkResponse, OpenSearchMappings, OpenSearchQuery,
+    OpenSearchSearchResponse, OpenSearchSettings,
 };
+use golem_search::bulk::{is_retryable_status, ItemOutcome};
+use golem_search::error::invalid_query;
+use golem_search::facets::{facet_field, facet_requests, range_label, DEFAULT_FACET_SIZE};
+use golem_search::filter::{resolve_filter, Filter};
 use golem_search::golem::search::types::{
-    Doc, FieldType, Schema, SchemaField, SearchHit, SearchQuery, SearchResults,
+    CalendarInterval, Doc, FacetBucket, FacetOrder, FacetRequest, FacetResult, FacetStats,
+    FieldType, FilterValue, HighlightConfig, IndexAlias, Schema, SchemaChangeKind, SchemaField,
+    SearchError, SearchHit, SearchQuery, SearchResults, VectorOptions, VectorSimilarity,
 };
+use golem_search::highlights::{
+    field_highlight, highlight_tags, validate_highlight_config, DEFAULT_SNIPPET_LENGTH,
+};
+use golem_search::schema::{
+    impossible, in_place, language_analyzer, needs_reindex, validate_field, Classification,
+    SchemaDiff,
+};
+use golem_search::vector::{validate_vector_query, vector_options};
+use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use std::collections::HashMap;
 
@@ -51,7 +66,13 @@ pub fn opensearch_document_to_doc(document: Value) -> Doc {
     Doc { id, content }
 }
 
-pub fn search_query_to_opensearch_request(query: SearchQuery) -> OpenSearchQuery {
+/// Average length of a word with its separator, to size the snippet fragments
+const CHARACTERS_PER_WORD: u32 = 6;
+
+pub fn search_query_to_opensearch_request(
+    query: SearchQuery,
+) -> Result<OpenSearchQuery, SearchError> {
+    let facets = facet_requests(&query)?;
     let mut opensearch_query = OpenSearchQuery {
         query: None,
         from: query.offset,
@@ -60,6 +81,8 @@ pub fn search_query_to_opensearch_request(query: SearchQuery) -> OpenSearchQuery
         highlight: None,
         aggs: None,
         _source: None,
+        pit: None,
+        search_after: None,
     };
 
     if let Some(q) = query.q {
@@ -82,7 +105,7 @@ pub fn search_query_to_opensearch_request(query: SearchQuery) -> OpenSearchQuery
         }));
     }
 
-    if !query.filters.is_empty() {
+    if !query.filters.is_empty() || query.filter.is_some() {
         let mut bool_query = serde_json::json!({
             "bool": {
                 "must": opensearch_query.query.take(),
@@ -112,9 +135,49 @@ pub fn search_query_to_opensearch_request(query: SearchQuery) -> OpenSearchQuery
             }
         }
 
+        if let Some(expression) = &query.filter {
+            let filter = resolve_filter(expression)?;
+            bool_query["bool"]["filter"]
+                .as_array_mut()
+                .unwrap()
+                .push(filter_to_opensearch_query(&filter));
+        }
+
         opensearch_query.query = Some(bool_query);
     }
 
+    if let Some(vector_query) = &query.vector {
+        let semantic_ratio = validate_vector_query(vector_query)?;
+        let mut knn = serde_json::json!({
+            "vector": vector_query.vector,
+            "k": vector_query.k
+        });
+        if let Some(expression) = &vector_query.filter {
+            knn["filter"] = filter_to_opensearch_query(&resolve_filter(expression)?);
+        }
+
+        let mut main_query = opensearch_query.query.take().unwrap();
+        let scoring_query = if main_query.get("bool").is_some() {
+            &mut main_query["bool"]["must"]
+        } else {
+            &mut main_query
+        };
+        // A text query is combined with the nearest neighbours, weighted by the semantic ratio
+        if scoring_query.get("multi_match").is_some() {
+            scoring_query["multi_match"]["boost"] = serde_json::json!(1.0 - semantic_ratio);
+            knn["boost"] = serde_json::json!(semantic_ratio);
+            *scoring_query = serde_json::json!({
+                "bool": {
+                    "should": [scoring_query.take(), { "knn": { &vector_query.field: knn } }]
+                }
+            });
+        } else {
+            *scoring_query = serde_json::json!({ "knn": { &vector_query.field: knn } });
+        }
+        opensearch_query.query = Some(main_query);
+        opensearch_query.size = opensearch_query.size.or(Some(vector_query.k));
+    }
+
     if !query.sort.is_empty() {
         let mut sort_array = Vec::new();
         for sort_field in query.sort {
@@ -141,24 +204,33 @@ pub fn search_query_to_opensearch_request(query: SearchQuery) -> OpenSearchQuery
     }
 
     if let Some(highlight_config) = query.highlight {
+        validate_highlight_config(&highlight_config)?;
+        let (pre_tag, post_tag) = highlight_tags(&highlight_config);
         let mut highlight = serde_json::json!({
-            "fields": {}
+            "fields": {},
+            "pre_tags": [pre_tag],
+            "post_tags": [post_tag]
         });
 
-        if !highlight_config.fields.is_empty() {
-            for field in highlight_config.fields {
+        if !highlight_config.fields.is_empty() || !highlight_config.snippet_fields.is_empty() {
+            for field in &highlight_config.fields {
                 highlight["fields"][field] = serde_json::json!({});
             }
         } else {
             highlight["fields"]["*"] = serde_json::json!({});
         }
 
-        if let Some(pre_tag) = highlight_config.pre_tag {
-            highlight["pre_tags"] = serde_json::json!([pre_tag]);
-        }
-
-        if let Some(post_tag) = highlight_config.post_tag {
-            highlight["post_tags"] = serde_json::json!([post_tag]);
+        // Fragments are sized in characters, rather than in words
+        let snippet_length = highlight_config
+            .snippet_length
+            .unwrap_or(DEFAULT_SNIPPET_LENGTH)
+            .saturating_mul(CHARACTERS_PER_WORD);
+        for field in &highlight_config.snippet_fields {
+            let mut snippet = serde_json::json!({ "fragment_size": snippet_length });
+            if let Some(max_snippets) = highlight_config.max_snippets {
+                snippet["number_of_fragments"] = serde_json::json!(max_snippets);
+            }
+            highlight["fields"][field] = snippet;
         }
 
         if let Some(max_length) = highlight_config.max_length {
@@ -168,38 +240,217 @@ pub fn search_query_to_opensearch_request(query: SearchQuery) -> OpenSearchQuery
         opensearch_query.highlight = Some(highlight);
     }
 
-    if !query.facets.is_empty() {
+    if !facets.is_empty() {
         let mut aggs = Map::new();
-        for facet in query.facets {
-            let field_name = if facet == "year" {
-                facet.clone()
-            } else {
-                format!("{facet}.keyword")
-            };
-
+        for (index, facet) in facets.iter().enumerate() {
+            let keyword_subfield = index < query.facets.len() && facet_field(facet) != "year";
             aggs.insert(
-                format!("{facet}_terms"),
-                serde_json::json!({
-                    "terms": {
-                        "field": field_name,
-                        "size": 100
-                    }
-                }),
+                format!("facet_{index}"),
+                facet_to_opensearch_aggregation(facet, keyword_subfield),
             );
         }
         opensearch_query.aggs = Some(Value::Object(aggs));
     }
 
-    opensearch_query
+    Ok(opensearch_query)
 }
 
-pub fn opensearch_response_to_search_results(response: OpenSearchSearchResponse) -> SearchResults {
+/// Compiles a provider-neutral filter to the query DSL, every operator is supported
+pub fn filter_to_opensearch_query(filter: &Filter) -> Value {
+    match filter {
+        Filter::Eq(field, value) => serde_json::json!({
+            "term": { field: filter_value_to_json(value) }
+        }),
+        Filter::Ne(field, value) => serde_json::json!({
+            "bool": { "must_not": [{ "term": { field: filter_value_to_json(value) } }] }
+        }),
+        Filter::Range(field, bounds) => {
+            let mut range = Map::new();
+            for (name, bound) in [
+                ("gt", &bounds.gt),
+                ("gte", &bounds.gte),
+                ("lt", &bounds.lt),
+                ("lte", &bounds.lte),
+            ] {
+                if let Some(bound) = bound {
+                    range.insert(name.to_string(), filter_value_to_json(bound));
+                }
+            }
+            serde_json::json!({ "range": { field: range } })
+        }
+        Filter::In(field, values) => serde_json::json!({
+            "terms": { field: values.iter().map(filter_value_to_json).collect::<Vec<_>>() }
+        }),
+        Filter::Exists(field) => serde_json::json!({ "exists": { "field": field } }),
+        Filter::Prefix(field, prefix) => serde_json::json!({ "prefix": { field: prefix } }),
+        Filter::GeoRadius(radius) => serde_json::json!({
+            "geo_distance": {
+                "distance": format!("{}m", radius.radius_meters),
+                &radius.field: { "lat": radius.center.lat, "lon": radius.center.lon }
+            }
+        }),
+        Filter::GeoBoundingBox(bbox) => serde_json::json!({
+            "geo_bounding_box": {
+                &bbox.field: {
+                    "top_left": { "lat": bbox.top_left.lat, "lon": bbox.top_left.lon },
+                    "bottom_right": { "lat": bbox.bottom_right.lat, "lon": bbox.bottom_right.lon }
+                }
+            }
+        }),
+        Filter::And(filters) => serde_json::json!({
+            "bool": { "filter": filters.iter().map(filter_to_opensearch_query).collect::<Vec<_>>() }
+        }),
+        Filter::Or(filters) => serde_json::json!({
+            "bool": {
+                "should": filters.iter().map(filter_to_opensearch_query).collect::<Vec<_>>(),
+                "minimum_should_match": 1
+            }
+        }),
+        Filter::Not(filter) => serde_json::json!({
+            "bool": { "must_not": [filter_to_opensearch_query(filter)] }
+        }),
+    }
+}
+
+fn filter_value_to_json(value: &FilterValue) -> Value {
+    match value {
+        FilterValue::Text(text) => serde_json::json!(text),
+        FilterValue::Integer(integer) => serde_json::json!(integer),
+        FilterValue::Float(float) => serde_json::json!(float),
+        FilterValue::Boolean(boolean) => serde_json::json!(boolean),
+    }
+}
+
+/// Compiles a facet to an aggregation named `facet_<index>`. The plain `facets` fields aggregate
+/// the `keyword` sub-field that text fields are mapped with.
+fn facet_to_opensearch_aggregation(facet: &FacetRequest, keyword_subfield: bool) -> Value {
+    match facet {
+        FacetRequest::Terms(terms) => {
+            let field = if keyword_subfield {
+                format!("{}.keyword", terms.field)
+            } else {
+                terms.field.clone()
+            };
+            let order = match terms.order {
+                FacetOrder::Count => serde_json::json!({ "_count": "desc" }),
+                FacetOrder::Value => serde_json::json!({ "_key": "asc" }),
+            };
+            serde_json::json!({
+                "terms": {
+                    "field": field,
+                    "size": terms.size.unwrap_or(DEFAULT_FACET_SIZE),
+                    "order": order
+                }
+            })
+        }
+        FacetRequest::Range(range) => {
+            let ranges = range
+                .ranges
+                .iter()
+                .map(|bounds| {
+                    let mut range = Map::new();
+                    if let Some(from) = bounds.from {
+                        range.insert("from".to_string(), serde_json::json!(from));
+                    }
+                    if let Some(to) = bounds.to {
+                        range.insert("to".to_string(), serde_json::json!(to));
+                    }
+                    Value::Object(range)
+                })
+                .collect::<Vec<_>>();
+            serde_json::json!({ "range": { "field": range.field, "ranges": ranges } })
+        }
+        FacetRequest::DateHistogram(histogram) => {
+            let interval = match histogram.interval {
+                CalendarInterval::Minute => "minute",
+                CalendarInterval::Hour => "hour",
+                CalendarInterval::Day => "day",
+                CalendarInterval::Week => "week",
+                CalendarInterval::Month => "month",
+                CalendarInterval::Quarter => "quarter",
+                CalendarInterval::Year => "year",
+            };
+            serde_json::json!({
+                "date_histogram": { "field": histogram.field, "calendar_interval": interval }
+            })
+        }
+        FacetRequest::Stats(field) => serde_json::json!({ "stats": { "field": field } }),
+    }
+}
+
+fn opensearch_aggregation_to_facet_result(
+    facet: &FacetRequest,
+    aggregation: &Value,
+) -> FacetResult {
+    let buckets = aggregation
+        .get("buckets")
+        .and_then(|buckets| buckets.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let doc_count = |bucket: &Value| {
+        bucket
+            .get("doc_count")
+            .and_then(|c| c.as_u64())
+            .unwrap_or(0)
+    };
+
+    match facet {
+        FacetRequest::Range(range) => FacetResult {
+            field: range.field.clone(),
+            buckets: range
+                .ranges
+                .iter()
+                .zip(buckets)
+                .map(|(bounds, bucket)| FacetBucket {
+                    value: range_label(bounds),
+                    count: doc_count(bucket),
+                    range: Some(bounds.clone()),
+                })
+                .collect(),
+            stats: None,
+        },
+        FacetRequest::Stats(field) => FacetResult {
+            field: field.clone(),
+            buckets: vec![],
+            stats: Some(FacetStats {
+                count: aggregation.get("count").and_then(|c| c.as_u64()),
+                min: aggregation.get("min").and_then(|m| m.as_f64()),
+                max: aggregation.get("max").and_then(|m| m.as_f64()),
+                avg: aggregation.get("avg").and_then(|a| a.as_f64()),
+                sum: aggregation.get("sum").and_then(|s| s.as_f64()),
+            }),
+        },
+        FacetRequest::Terms(_) | FacetRequest::DateHistogram(_) => FacetResult {
+            field: facet_field(facet).to_string(),
+            buckets: buckets
+                .iter()
+                .map(|bucket| FacetBucket {
+                    value: match bucket.get("key_as_string").or_else(|| bucket.get("key")) {
+                        Some(Value::String(key)) => key.clone(),
+                        Some(key) => key.to_string(),
+                        None => String::new(),
+                    },
+                    count: doc_count(bucket),
+                    range: None,
+                })
+                .collect(),
+            stats: None,
+        },
+    }
+}
+
+pub fn opensearch_response_to_search_results(
+    response: OpenSearchSearchResponse,
+    facets: &[FacetRequest],
+    highlight: Option<&HighlightConfig>,
+) -> SearchResults {
     let hits: Vec<SearchHit> = response
         .hits
         .hits
         .into_iter()
         .map(|hit| {
             let mut highlights = HashMap::new();
+            let mut highlight_fields = vec![];
             if let Some(Value::Object(highlight_map)) = hit.highlight {
                 for (field, values) in highlight_map {
                     if let Value::Array(values_array) = values {
@@ -208,6 +459,13 @@ pub fn opensearch_response_to_search_results(response: OpenSearchSearchResponse)
                             .filter_map(|v| v.as_str().map(|s| s.to_string()))
                             .collect();
                         if !highlight_strings.is_empty() {
+                            if let Some(config) = highlight {
+                                highlight_fields.push(field_highlight(
+                                    field.as_str(),
+                                    &highlight_strings,
+                                    config,
+                                ));
+                            }
                             highlights.insert(field, highlight_strings);
                         }
                     }
@@ -224,68 +482,56 @@ pub fn opensearch_response_to_search_results(response: OpenSearchSearchResponse)
                 highlights: Some(
                     serde_json::to_string(&highlights).unwrap_or_else(|_| "{}".to_string()),
                 ),
+                highlight_fields,
             }
         })
         .collect();
 
     let total = response.hits.total.value;
 
-    let facets = response
+    let facet_results: Vec<FacetResult> = response
         .aggregations
+        .as_ref()
         .map(|aggs| {
-            let mut facet_map = HashMap::new();
-            if let Value::Object(aggs_map) = aggs {
-                for (key, value) in aggs_map {
-                    if key.ends_with("_terms") {
-                        let facet_name = key.strip_suffix("_terms").unwrap_or(&key);
-                        if let Some(Value::Array(buckets_array)) = value.get("buckets") {
-                            let facet_values: Vec<String> = buckets_array
-                                .iter()
-                                .filter_map(|bucket| {
-                                    bucket
-                                        .get("key")
-                                        .and_then(|k| k.as_str().map(|s| s.to_string()))
-                                })
-                                .collect();
-                            if !facet_values.is_empty() {
-                                facet_map.insert(facet_name.to_string(), facet_values);
-                            }
-                        }
-                    }
-                }
-            }
-            facet_map
+            facets
+                .iter()
+                .enumerate()
+                .filter_map(|(index, facet)| {
+                    let aggregation = aggs.get(format!("facet_{index}"))?;
+                    Some(opensearch_aggregation_to_facet_result(facet, aggregation))
+                })
+                .collect()
         })
         .unwrap_or_default();
 
+    let mut facet_map = HashMap::new();
+    for (facet, result) in facets.iter().zip(&facet_results) {
+        if matches!(facet, FacetRequest::Terms(_)) && !result.buckets.is_empty() {
+            let values: Vec<&str> = result.buckets.iter().map(|b| b.value.as_str()).collect();
+            facet_map.insert(result.field.as_str(), values);
+        }
+    }
+
     SearchResults {
         total: Some(total),
         page: None,     // OpenSearch uses offset/size, not page numbers
         per_page: None, // We'll calculate this from the request
         hits,
-        facets: Some(serde_json::to_string(&facets).unwrap_or_else(|_| "{}".to_string())),
+        facets: Some(serde_json::to_string(&facet_map).unwrap_or_else(|_| "{}".to_string())),
+        facet_results,
         took_ms: Some(response.took),
     }
 }
 
-pub fn opensearch_scroll_response_to_search_results(
-    response: OpenSearchScrollResponse,
-) -> SearchResults {
-    // Convert scroll response to regular search response format
-    let regular_response = OpenSearchSearchResponse {
-        took: response.took,
-        timed_out: response.timed_out,
-        hits: response.hits,
-        aggregations: response.aggregations,
-    };
-
-    opensearch_response_to_search_results(regular_response)
-}
-
-pub fn schema_to_opensearch_settings(schema: Schema) -> OpenSearchSettings {
+pub fn schema_to_opensearch_settings(schema: Schema) -> Result<OpenSearchSettings, SearchError> {
     let mut properties = Map::new();
+    let mut knn = false;
+    let mut excludes = Vec::new();
+    let mut arrays = Vec::new();
+    let mut locales = Map::new();
 
     for field in schema.fields {
+        validate_field(&field)?;
         let mut field_mapping = Map::new();
 
         let opensearch_type = match field.field_type {
@@ -296,6 +542,10 @@ pub fn schema_to_opensearch_settings(schema: Schema) -> OpenSearchSettings {
             FieldType::Boolean => "boolean",
             FieldType::Date => "date",
             FieldType::GeoPoint => "geo_point",
+            FieldType::GeoShape => "geo_shape",
+            FieldType::Vector => "knn_vector",
+            FieldType::Object => "object",
+            FieldType::Nested => "nested",
         };
         field_mapping.insert(
             "type".to_string(),
@@ -305,16 +555,53 @@ pub fn schema_to_opensearch_settings(schema: Schema) -> OpenSearchSettings {
         if field.field_type == FieldType::Text {
             field_mapping.insert(
                 "analyzer".to_string(),
-                Value::String("standard".to_string()),
+                Value::String(text_analyzer(&field)?),
+            );
+        }
+
+        if field.field_type == FieldType::Vector {
+            let vector = vector_options(&field)?;
+            let space_type = match vector.similarity {
+                VectorSimilarity::Cosine => "cosinesimil",
+                VectorSimilarity::DotProduct => "innerproduct",
+                VectorSimilarity::Euclidean => "l2",
+            };
+            field_mapping.insert(
+                "dimension".to_string(),
+                serde_json::json!(vector.dimensions),
+            );
+            // The lucene engine supports filtering the candidates of a knn query
+            field_mapping.insert(
+                "method".to_string(),
+                serde_json::json!({
+                    "name": "hnsw",
+                    "engine": "lucene",
+                    "space_type": space_type
+                }),
             );
+            knn = true;
         }
 
-        properties.insert(field.name, Value::Object(field_mapping));
+        if !field.stored {
+            excludes.push(Value::String(field.name.clone()));
+        }
+        if field.array {
+            arrays.push(Value::String(field.name.clone()));
+        }
+        if let Some(locale) = &field.locale {
+            locales.insert(field.name.clone(), Value::String(locale.clone()));
+        }
+
+        insert_property(&mut properties, &field.name, field_mapping);
     }
 
+    // Any field can hold a list in OpenSearch, and locales only pick an analyzer, so both are kept
+    // in the mapping metadata for the schema to be read back
     let mappings = OpenSearchMappings {
         properties: Some(properties),
         dynamic: Some(true),
+        source: (!excludes.is_empty()).then(|| serde_json::json!({ "excludes": excludes })),
+        meta: Some(serde_json::json!({ "arrays": arrays, "locales": locales })),
     };
 
     let mut index_settings = Map::new();
@@ -326,10 +613,59 @@ pub fn schema_to_opensearch_settings(schema: Schema) -> OpenSearchSettings {
         "number_of_replicas".to_string(),
         Value::Number(serde_json::Number::from(0)),
     );
+    if knn {
+        index_settings.insert("knn".to_string(), Value::Bool(true));
+    }
 
-    OpenSearchSettings {
+    Ok(OpenSearchSettings {
         mappings: Some(mappings),
         settings: Some(index_settings),
+    })
+}
+
+const DEFAULT_ANALYZER: &str = "standard";
+
+fn text_analyzer(field: &SchemaField) -> Result<String, SearchError> {
+    match (&field.analyzer, &field.locale) {
+        (Some(analyzer), _) => Ok(analyzer.clone()),
+        (None, Some(locale)) => language_analyzer(locale)
+            .map(str::to_string)
+            .ok_or_else(|| {
+                invalid_query(format!(
+                    "OpenSearch has no language analyzer for the locale {locale} of {}, set its analyzer",
+                    field.name
+                ))
+            }),
+        (None, None) => Ok(DEFAULT_ANALYZER.to_string()),
+    }
+}
+
+/// Inserts the mapping of a field under the properties of its parent objects, whether the parents
+/// are declared before or after their sub-fields
+fn insert_property(properties: &mut Map<String, Value>, name: &str, mapping: Map<String, Value>) {
+    match name.split_once('.') {
+        Some((parent, sub_field)) => {
+            let parent = properties
+                .entry(parent.to_string())
+                .or_insert_with(|| serde_json::json!({ "type": "object" }));
+            if let Some(sub_properties) = parent
+                .as_object_mut()
+                .map(|parent| {
+                    parent
+                        .entry("properties")
+                        .or_insert_with(|| serde_json::json!({}))
+                })
+                .and_then(|sub_properties| sub_properties.as_object_mut())
+            {
+                insert_property(sub_properties, sub_field, mapping);
+            }
+        }
+        None => match properties.get_mut(name).and_then(|p| p.as_object_mut()) {
+            Some(existing) => existing.extend(mapping),
+            None => {
+                properties.insert(name.to_string(), Value::Object(mapping));
+            }
+        },
     }
 }
 
@@ -343,34 +679,39 @@ pub fn opensearch_mappings_to_schema(
         for (_, index_info) in indices {
             if let Some(mappings) = index_info.get("mappings") {
                 if let Some(Value::Object(props)) = mappings.get("properties") {
-                    for (field_name, field_def) in props {
-                        if let Value::Object(field_map) = field_def {
-                            let field_type = field_map
-                                .get("type")
-                                .and_then(|t| t.as_str())
-                                .map(|type_str| match type_str {
-                                    "text" => FieldType::Text,
-                                    "keyword" => FieldType::Keyword,
-                                    "integer" | "long" | "short" | "byte" => FieldType::Integer,
-                                    "float" | "double" | "half_float" | "scaled_float" => {
-                                        FieldType::Float
-                                    }
-                                    "boolean" => FieldType::Boolean,
-                                    "date" => FieldType::Date,
-                                    "geo_point" => FieldType::GeoPoint,
-                                    _ => FieldType::Text,
-                                })
-                                .unwrap_or(FieldType::Text);
-
-                            fields.push(SchemaField {
-                                name: field_name.clone(),
-                                field_type,
-                                required: false,
-                                facet: field_type == FieldType::Keyword,
-                                sort: true,
-                                index: true,
-                            });
-                        }
+                    mapped_fields(props, "", &mut fields);
+                }
+
+                let names = |value: Option<&Value>| -> Vec<String> {
+                    value
+                        .and_then(|v| v.as_array())
+                        .map(|names| {
+                            names
+                                .iter()
+                                .filter_map(|name| name.as_str().map(str::to_string))
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                };
+                let excludes = names(mappings.pointer("/_source/excludes"));
+                let arrays = names(mappings.pointer("/_meta/arrays"));
+                let locales = mappings.pointer("/_meta/locales");
+
+                for field in &mut fields {
+                    field.stored = !excludes.contains(&field.name);
+                    field.array = arrays.contains(&field.name);
+                    field.locale = locales
+                        .and_then(|l| l.get(&field.name))
+                        .and_then(|l| l.as_str())
+                        .map(str::to_string);
+                    // The analyzer picked from the locale, or by default, isn't part of the schema
+                    let default = field
+                        .locale
+                        .as_deref()
+                        .and_then(language_analyzer)
+                        .unwrap_or(DEFAULT_ANALYZER);
+                    if field.analyzer.as_deref() == Some(default) {
+                        field.analyzer = None;
                     }
                 }
             }
@@ -383,6 +724,152 @@ pub fn opensearch_mappings_to_schema(
     }
 }
 
+/// Lists the mapped fields, each object followed by its sub-fields named `<object>.<sub-field>`
+fn mapped_fields(properties: &Map<String, Value>, prefix: &str, fields: &mut Vec<SchemaField>) {
+    for (field_name, field_def) in properties {
+        if let Value::Object(field_map) = field_def {
+            let name = format!("{prefix}{field_name}");
+            let sub_properties = field_map.get("properties").and_then(|p| p.as_object());
+            let field_type = field_map
+                .get("type")
+                .and_then(|t| t.as_str())
+                .map(|type_str| match type_str {
+                    "text" => FieldType::Text,
+                    "keyword" => FieldType::Keyword,
+                    "integer" | "long" | "short" | "byte" => FieldType::Integer,
+                    "float" | "double" | "half_float" | "scaled_float" => FieldType::Float,
+                    "boolean" => FieldType::Boolean,
+                    "date" => FieldType::Date,
+                    "geo_point" => FieldType::GeoPoint,
+                    "geo_shape" => FieldType::GeoShape,
+                    "knn_vector" => FieldType::Vector,
+                    "object" => FieldType::Object,
+                    "nested" => FieldType::Nested,
+                    _ => FieldType::Text,
+                })
+                // OpenSearch leaves out the type of objects
+                .unwrap_or(if sub_properties.is_some() {
+                    FieldType::Object
+                } else {
+                    FieldType::Text
+                });
+            let vector = (field_type == FieldType::Vector).then(|| VectorOptions {
+                dimensions: field_map
+                    .get("dimension")
+                    .and_then(|d| d.as_u64())
+                    .unwrap_or_default() as u32,
+                similarity: match field_map
+                    .get("method")
+                    .and_then(|m| m.get("space_type"))
+                    .and_then(|s| s.as_str())
+                {
+                    Some("innerproduct") => VectorSimilarity::DotProduct,
+                    Some("l2") => VectorSimilarity::Euclidean,
+                    _ => VectorSimilarity::Cosine,
+                },
+            });
+
+            fields.push(SchemaField {
+                name: name.clone(),
+                field_type,
+                required: false,
+                facet: field_type == FieldType::Keyword,
+                sort: !matches!(
+                    field_type,
+                    FieldType::Vector | FieldType::GeoShape | FieldType::Object | FieldType::Nested
+                ),
+                index: true,
+                array: false,
+                stored: true,
+                analyzer: field_map
+                    .get("analyzer")
+                    .and_then(|a| a.as_str())
+                    .map(str::to_string),
+                locale: None,
+                vector,
+            });
+
+            if let Some(sub_properties) = sub_properties {
+                mapped_fields(sub_properties, &format!("{name}."), fields);
+            }
+        }
+    }
+}
+
+/// How long the point in time of a search stream is kept alive between two pages
+pub const POINT_IN_TIME_KEEP_ALIVE: &str = "5m";
+
+/// Default number of hits of a search stream page
+pub const STREAM_PAGE_SIZE: u32 = 100;
+
+/// Position of a search stream: the point in time it reads, and the sort values of the last hit
+/// it returned
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenSearchStreamCursor {
+    pub pit_id: String,
+    pub search_after: Vec<Value>,
+}
+
+/// Builds the query of a search stream page, reading the given point in time after the sort
+/// values of the previous page's last hit
+pub fn search_query_to_opensearch_stream_request(
+    query: SearchQuery,
+    pit_id: &str,
+    search_after: Option<Vec<Value>>,
+) -> Result<OpenSearchQuery, SearchError> {
+    let page_size = query.per_page.unwrap_or(STREAM_PAGE_SIZE);
+    let mut opensearch_query = search_query_to_opensearch_request(query)?;
+    opensearch_query.size = Some(page_size);
+    opensearch_query.pit = Some(serde_json::json!({
+        "id": pit_id,
+        "keep_alive": POINT_IN_TIME_KEEP_ALIVE
+    }));
+    // The offset only applies to the first page, the next ones start after the previous hit
+    if search_after.is_some() {
+        opensearch_query.from = None;
+    }
+    opensearch_query.search_after = search_after;
+
+    // Hits with equal sort values are ordered by id, so that no hit is skipped or returned twice
+    // across pages
+    opensearch_query
+        .sort
+        .get_or_insert_with(|| vec![serde_json::json!({ "_score": { "order": "desc" } })])
+        .push(serde_json::json!({ "_id": { "order": "asc" } }));
+
+    Ok(opensearch_query)
+}
+
+/// Mappings only accept new fields: a mapped field keeps its type and options until the documents
+/// are reindexed. A removed field stays mapped, which doesn't prevent documents from omitting it.
+pub fn classify_opensearch_change(diff: &SchemaDiff) -> Classification {
+    match diff.kind {
+        SchemaChangeKind::FieldAdded | SchemaChangeKind::FieldRemoved => in_place(),
+        SchemaChangeKind::FieldTypeChanged => {
+            needs_reindex("OpenSearch can't change the type of a mapped field")
+        }
+        SchemaChangeKind::VectorOptionsChanged => {
+            needs_reindex("OpenSearch can't change the dimension or space type of a knn_vector")
+        }
+        // Facets and sorts use the keyword sub-field, and arrays are only recorded in the
+        // mapping metadata
+        SchemaChangeKind::FieldOptionsChanged => match (&diff.current, &diff.target) {
+            (Some(current), Some(target))
+                if (current.index, current.stored, &current.analyzer, &current.locale)
+                    != (target.index, target.stored, &target.analyzer, &target.locale) =>
+            {
+                needs_reindex(
+                    "OpenSearch can't change whether a mapped field is indexed or stored, or how it's analyzed",
+                )
+            }
+            _ => in_place(),
+        },
+        SchemaChangeKind::PrimaryKeyChanged => {
+            impossible("OpenSearch identifies documents by their _id")
+        }
+    }
+}
+
 pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
     let mut retry_query = original_query.clone();
 
@@ -393,10 +880,69 @@ pub fn create_retry_query(original_query: &SearchQuery, partial_hits: &[SearchHi
     retry_query
 }
 
+/// Reads the outcome of each operation of a bulk request, in the order of the operations
+pub fn opensearch_bulk_response_to_outcomes(response: OpenSearchBulkResponse) -> Vec<ItemOutcome> {
+    response
+        .items
+        .iter()
+        .map(|item| {
+            // Each item is keyed by its operation, e.g. `{"index": {"_id": .., "status": 201}}`
+            let result = item
+                .as_object()
+                .and_then(|item| item.values().next())
+                .unwrap_or(&Value::Null);
+            match result.get("error") {
+                Some(error) => {
+                    let status = result.get("status").and_then(|s| s.as_u64()).unwrap_or(0);
+                    let reason = match (error.get("type"), error.get("reason")) {
+                        (Some(Value::String(error_type)), Some(Value::String(reason))) => {
+                            format!("{error_type}: {reason}")
+                        }
+                        _ => error.to_string(),
+                    };
+                    ItemOutcome::failure(reason, is_retryable_status(status as u16))
+                }
+                None => ItemOutcome::success(),
+            }
+        })
+        .collect()
+}
+
+pub fn opensearch_aliases_to_index_aliases(aliases: OpenSearchAliases) -> Vec<IndexAlias> {
+    aliases
+        .into_iter()
+        .flat_map(|(index, index_aliases)| {
+            index_aliases
+                .aliases
+                .into_iter()
+                .map(move |(alias, _)| IndexAlias {
+                    alias,
+                    index: index.clone(),
+                })
+        })
+        .collect()
+}
+
+/// Actions moving the alias from the indices it points to, to the given index, in one request
+pub fn alias_swap_actions(alias: &str, current_indices: &[String], index: &str) -> Vec<Value> {
+    current_indices
+        .iter()
+        .filter(|current| current.as_str() != index)
+        .map(|current| serde_json::json!({ "remove": { "index": current, "alias": alias } }))
+        .chain(std::iter::once(
+            serde_json::json!({ "add": { "index": index, "alias": alias } }),
+        ))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use golem_search::golem::search::types::HighlightConfig;
+    use crate::client::{OpenSearchHit, OpenSearchHits, OpenSearchTotal};
+    use golem_search::golem::search::types::{
+        FacetRange, FilterExpression, FilterNode, GeoPoint, GeoRadius, HighlightConfig,
+        RangeBounds, RangeFacet, SchemaChangeApplication, VectorQuery,
+    };
 
     #[test]
     fn test_doc_to_opensearch_document() {
@@ -453,8 +999,11 @@ mod tests {
         let search_query = SearchQuery {
             q: Some("test query".to_string()),
             filters: vec!["category:electronics".to_string()],
+            filter: None,
+            vector: None,
             sort: vec!["price:desc".to_string()],
             facets: vec!["category".to_string()],
+            facet_requests: vec![],
             page: Some(1),
             per_page: Some(20),
             offset: Some(10),
@@ -463,15 +1012,413 @@ mod tests {
                 pre_tag: Some("<mark>".to_string()),
                 post_tag: Some("</mark>".to_string()),
                 max_length: Some(200),
+                snippet_fields: vec!["description".to_string()],
+                snippet_length: None,
+                max_snippets: Some(3),
             }),
             config: None,
         };
 
-        let opensearch_query = search_query_to_opensearch_request(search_query);
+        let opensearch_query = search_query_to_opensearch_request(search_query).unwrap();
         assert!(opensearch_query.query.is_some());
         assert!(opensearch_query.sort.is_some());
         assert!(opensearch_query.aggs.is_some());
-        assert!(opensearch_query.highlight.is_some());
+        let highlight = opensearch_query.highlight.unwrap();
+        assert_eq!(highlight["post_tags"], serde_json::json!(["</mark>"]));
+        assert_eq!(
+            highlight["fields"]["description"],
+            serde_json::json!({ "fragment_size": 60, "number_of_fragments": 3 })
+        );
+    }
+
+    #[test]
+    fn test_facets_to_opensearch_aggregations() {
+        let search_query = SearchQuery {
+            q: None,
+            filters: vec![],
+            filter: None,
+            vector: None,
+            sort: vec![],
+            facets: vec!["category".to_string(), "year".to_string()],
+            facet_requests: vec![
+                FacetRequest::Range(RangeFacet {
+                    field: "price".to_string(),
+                    ranges: vec![FacetRange {
+                        from: Some(0.0),
+                        to: Some(50.0),
+                    }],
+                }),
+                FacetRequest::Stats("price".to_string()),
+            ],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        };
+
+        let facets = facet_requests(&search_query).unwrap();
+        let aggs = search_query_to_opensearch_request(search_query)
+            .unwrap()
+            .aggs
+            .unwrap();
+        assert_eq!(aggs["facet_0"]["terms"]["field"], "category.keyword");
+        assert_eq!(aggs["facet_1"]["terms"]["field"], "year");
+        assert_eq!(
+            aggs["facet_2"]["range"]["ranges"],
+            serde_json::json!([{ "from": 0.0, "to": 50.0 }])
+        );
+
+        let response = OpenSearchSearchResponse {
+            took: 2,
+            timed_out: false,
+            hits: OpenSearchHits {
+                total: OpenSearchTotal {
+                    value: 0,
+                    relation: "eq".to_string(),
+                },
+                max_score: None,
+                hits: vec![],
+            },
+            aggregations: Some(serde_json::json!({
+                "facet_0": { "buckets": [{ "key": "books", "doc_count": 5 }] },
+                "facet_1": { "buckets": [{ "key": 2021, "doc_count": 3 }] },
+                "facet_2": { "buckets": [{ "key": "0.0-50.0", "doc_count": 4 }] },
+                "facet_3": { "count": 4, "min": 5.0, "max": 45.0, "avg": 20.0, "sum": 80.0 }
+            })),
+            pit_id: None,
+        };
+
+        let results = opensearch_response_to_search_results(response, &facets, None);
+        assert_eq!(results.facet_results.len(), 4);
+        assert_eq!(results.facet_results[1].buckets[0].value, "2021");
+        assert_eq!(results.facet_results[2].buckets[0].value, "0..50");
+        assert_eq!(results.facet_results[2].buckets[0].count, 4);
+        assert_eq!(
+            results.facet_results[3].stats.as_ref().unwrap().max,
+            Some(45.0)
+        );
+        let raw: Value = serde_json::from_str(&results.facets.unwrap()).unwrap();
+        assert_eq!(raw["category"], serde_json::json!(["books"]));
+    }
+
+    #[test]
+    fn test_opensearch_highlights_to_field_highlights() {
+        let response = OpenSearchSearchResponse {
+            took: 1,
+            timed_out: false,
+            hits: OpenSearchHits {
+                total: OpenSearchTotal {
+                    value: 1,
+                    relation: "eq".to_string(),
+                },
+                max_score: Some(1.0),
+                hits: vec![OpenSearchHit {
+                    index: "books".to_string(),
+                    id: "1".to_string(),
+                    score: Some(1.0),
+                    source: Some(serde_json::json!({ "title": "Dune" })),
+                    highlight: Some(serde_json::json!({
+                        "description": ["a <b>desert</b> planet", "the <b>desert</b> power"]
+                    })),
+                    sort: None,
+                }],
+            },
+            aggregations: None,
+            pit_id: None,
+        };
+        let highlight = HighlightConfig {
+            fields: vec![],
+            pre_tag: Some("<b>".to_string()),
+            post_tag: Some("</b>".to_string()),
+            max_length: None,
+            snippet_fields: vec!["description".to_string()],
+            snippet_length: None,
+            max_snippets: None,
+        };
+
+        let results = opensearch_response_to_search_results(response, &[], Some(&highlight));
+        let highlight_fields = &results.hits[0].highlight_fields;
+        assert_eq!(highlight_fields.len(), 1);
+        assert_eq!(highlight_fields[0].field, "description");
+        assert_eq!(highlight_fields[0].fragments.len(), 2);
+        assert_eq!(highlight_fields[0].fragments[1].text, "the desert power");
+        assert_eq!(highlight_fields[0].fragments[1].matches[0].start, 4);
+        assert!(results.hits[0].highlights.is_some());
+    }
+
+    #[test]
+    fn test_filter_expression_to_opensearch_query() {
+        let search_query = SearchQuery {
+            q: Some("coffee".to_string()),
+            filters: vec![],
+            filter: Some(FilterExpression {
+                nodes: vec![
+                    FilterNode::And(vec![1, 2]),
+                    FilterNode::Range((
+                        "rating".to_string(),
+                        RangeBounds {
+                            gt: None,
+                            gte: Some(FilterValue::Float(4.5)),
+                            lt: None,
+                            lte: None,
+                        },
+                    )),
+                    FilterNode::GeoRadius(GeoRadius {
+                        field: "location".to_string(),
+                        center: GeoPoint {
+                            lat: 48.85,
+                            lon: 2.35,
+                        },
+                        radius_meters: 1500.0,
+                    }),
+                ],
+            }),
+            vector: None,
+            sort: vec![],
+            facets: vec![],
+            facet_requests: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        };
+
+        let opensearch_query = search_query_to_opensearch_request(search_query).unwrap();
+        assert_eq!(
+            opensearch_query.query.unwrap()["bool"]["filter"],
+            serde_json::json!([{
+                "bool": {
+                    "filter": [
+                        { "range": { "rating": { "gte": 4.5 } } },
+                        {
+                            "geo_distance": {
+                                "distance": "1500m",
+                                "location": { "lat": 48.85, "lon": 2.35 }
+                            }
+                        }
+                    ]
+                }
+            }])
+        );
+    }
+
+    #[test]
+    fn test_hybrid_search_to_opensearch_query() {
+        let search_query = SearchQuery {
+            q: Some("coffee".to_string()),
+            filters: vec!["city:paris".to_string()],
+            filter: None,
+            vector: Some(VectorQuery {
+                field: "embedding".to_string(),
+                vector: vec![0.5, -0.5],
+                k: 3,
+                semantic_ratio: Some(0.75),
+                filter: Some(FilterExpression {
+                    nodes: vec![FilterNode::Eq((
+                        "open".to_string(),
+                        FilterValue::Boolean(true),
+                    ))],
+                }),
+            }),
+            sort: vec![],
+            facets: vec![],
+            facet_requests: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        };
+
+        let opensearch_query = search_query_to_opensearch_request(search_query).unwrap();
+        let query = opensearch_query.query.unwrap();
+        assert_eq!(
+            query["bool"]["must"],
+            serde_json::json!({
+                "bool": {
+                    "should": [
+                        {
+                            "multi_match": {
+                                "query": "coffee",
+                                "type": "best_fields",
+                                "fields": ["*"],
+                                "boost": 0.25
+                            }
+                        },
+                        {
+                            "knn": {
+                                "embedding": {
+                                    "vector": [0.5, -0.5],
+                                    "k": 3,
+                                    "filter": { "term": { "open": true } },
+                                    "boost": 0.75
+                                }
+                            }
+                        }
+                    ]
+                }
+            })
+        );
+        assert_eq!(
+            query["bool"]["filter"],
+            serde_json::json!([{ "term": { "city": "paris" } }])
+        );
+        assert_eq!(opensearch_query.size, Some(3));
+    }
+
+    #[test]
+    fn test_classify_opensearch_change() {
+        let field = |field_type, index| SchemaField {
+            name: "year".to_string(),
+            field_type,
+            required: false,
+            facet: false,
+            sort: false,
+            index,
+            array: false,
+            stored: true,
+            analyzer: None,
+            locale: None,
+            vector: None,
+        };
+        let diff = |kind, current, target| SchemaDiff {
+            kind,
+            field: "year".to_string(),
+            current: Some(current),
+            target: Some(target),
+        };
+
+        let type_changed = diff(
+            SchemaChangeKind::FieldTypeChanged,
+            field(FieldType::Text, true),
+            field(FieldType::Integer, true),
+        );
+        assert_eq!(
+            classify_opensearch_change(&type_changed).application,
+            SchemaChangeApplication::NeedsReindex
+        );
+
+        let sort_changed = diff(
+            SchemaChangeKind::FieldOptionsChanged,
+            field(FieldType::Integer, true),
+            SchemaField {
+                sort: true,
+                ..field(FieldType::Integer, true)
+            },
+        );
+        assert_eq!(classify_opensearch_change(&sort_changed), in_place());
+
+        let index_changed = diff(
+            SchemaChangeKind::FieldOptionsChanged,
+            field(FieldType::Integer, true),
+            field(FieldType::Integer, false),
+        );
+        assert_eq!(
+            classify_opensearch_change(&index_changed).application,
+            SchemaChangeApplication::NeedsReindex
+        );
+    }
+
+    #[test]
+    fn test_nested_and_localized_fields_round_trip() {
+        let field = |name: &str, field_type, sort| SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet: false,
+            sort,
+            index: true,
+            array: false,
+            stored: true,
+            analyzer: None,
+            locale: None,
+            vector: None,
+        };
+        let schema = Schema {
+            fields: vec![
+                SchemaField {
+                    locale: Some("de".to_string()),
+                    ..field("title_de", FieldType::Text, true)
+                },
+                SchemaField {
+                    analyzer: Some("whitespace".to_string()),
+                    stored: false,
+                    ..field("body", FieldType::Text, true)
+                },
+                SchemaField {
+                    array: true,
+                    facet: true,
+                    ..field("tags", FieldType::Keyword, true)
+                },
+                field("area", FieldType::GeoShape, false),
+                field("variants", FieldType::Nested, false),
+                SchemaField {
+                    facet: true,
+                    ..field("variants.color", FieldType::Keyword, true)
+                },
+            ],
+            primary_key: Some("id".to_string()),
+        };
+
+        let mappings = schema_to_opensearch_settings(schema.clone())
+            .unwrap()
+            .mappings
+            .unwrap();
+        let properties = mappings.properties.as_ref().unwrap();
+        assert_eq!(properties["title_de"]["analyzer"], "german");
+        assert_eq!(properties["variants"]["type"], "nested");
+        assert_eq!(
+            properties["variants"]["properties"]["color"]["type"],
+            "keyword"
+        );
+        assert_eq!(
+            mappings.source,
+            Some(serde_json::json!({ "excludes": ["body"] }))
+        );
+
+        let mappings = serde_json::json!({ "products": { "mappings": mappings } });
+        let mut fields = opensearch_mappings_to_schema(mappings, None).fields;
+        fields.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut expected = schema.fields;
+        expected.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(fields, expected);
+    }
+
+    #[test]
+    fn test_vector_field_mapping() {
+        let schema = Schema {
+            fields: vec![SchemaField {
+                name: "embedding".to_string(),
+                field_type: FieldType::Vector,
+                required: false,
+                facet: false,
+                sort: false,
+                index: true,
+                array: false,
+                stored: true,
+                analyzer: None,
+                locale: None,
+                vector: Some(VectorOptions {
+                    dimensions: 768,
+                    similarity: VectorSimilarity::Euclidean,
+                }),
+            }],
+            primary_key: Some("id".to_string()),
+        };
+
+        let settings = schema_to_opensearch_settings(schema.clone()).unwrap();
+        assert_eq!(settings.settings.unwrap()["knn"], true);
+        let properties = settings.mappings.unwrap().properties.unwrap();
+        assert_eq!(properties["embedding"]["type"], "knn_vector");
+        assert_eq!(properties["embedding"]["dimension"], 768);
+        assert_eq!(properties["embedding"]["method"]["space_type"], "l2");
+
+        let mappings = serde_json::json!({
+            "places": { "mappings": { "properties": properties } }
+        });
+        assert_eq!(opensearch_mappings_to_schema(mappings, None), schema);
     }
 
     #[test]
@@ -479,8 +1426,11 @@ mod tests {
         let original_query = SearchQuery {
             q: Some("test".to_string()),
             filters: vec![],
+            filter: None,
+            vector: None,
             sort: vec![],
             facets: vec![],
+            facet_requests: vec![],
             page: None,
             per_page: Some(10),
             offset: Some(20),
@@ -493,9 +1443,121 @@ mod tests {
             score: Some(1.0),
             content: Some("{}".to_string()),
             highlights: None,
+            highlight_fields: vec![],
         }];
 
         let retry_query = create_retry_query(&original_query, &partial_hits);
         assert_eq!(retry_query.offset, Some(21)); // 20 + 1 hit received
     }
+
+    #[test]
+    fn test_search_query_to_opensearch_stream_request() {
+        let query = SearchQuery {
+            q: Some("test".to_string()),
+            filters: vec![],
+            filter: None,
+            vector: None,
+            sort: vec![],
+            facets: vec![],
+            facet_requests: vec![],
+            page: None,
+            per_page: Some(50),
+            offset: Some(20),
+            highlight: None,
+            config: None,
+        };
+
+        let first_page =
+            search_query_to_opensearch_stream_request(query.clone(), "pit-1", None).unwrap();
+        assert_eq!(first_page.from, Some(20));
+        assert_eq!(first_page.size, Some(50));
+        assert_eq!(
+            first_page.pit.unwrap()["keep_alive"],
+            POINT_IN_TIME_KEEP_ALIVE
+        );
+        assert_eq!(
+            first_page.sort.unwrap(),
+            vec![
+                serde_json::json!({ "_score": { "order": "desc" } }),
+                serde_json::json!({ "_id": { "order": "asc" } })
+            ]
+        );
+
+        let cursor: OpenSearchStreamCursor =
+            serde_json::from_str(r#"{"pit_id":"pit-2","search_after":[1.2,"doc9"]}"#).unwrap();
+        let next_page = search_query_to_opensearch_stream_request(
+            query,
+            &cursor.pit_id,
+            Some(cursor.search_after.clone()),
+        )
+        .unwrap();
+        assert_eq!(next_page.from, None);
+        assert_eq!(next_page.pit.unwrap()["id"], "pit-2");
+        assert_eq!(next_page.search_after, Some(cursor.search_after));
+    }
+
+    #[test]
+    fn test_opensearch_aliases_to_index_aliases() {
+        let aliases: OpenSearchAliases = serde_json::from_value(serde_json::json!({
+            "books-v1": { "aliases": {} },
+            "books-v2": { "aliases": { "books": {}, "library": {} } }
+        }))
+        .unwrap();
+
+        let aliases = opensearch_aliases_to_index_aliases(aliases);
+        assert_eq!(
+            aliases,
+            vec![
+                IndexAlias {
+                    alias: "books".to_string(),
+                    index: "books-v2".to_string(),
+                },
+                IndexAlias {
+                    alias: "library".to_string(),
+                    index: "books-v2".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alias_swap_actions() {
+        let actions = alias_swap_actions("books", &["books-v1".to_string()], "books-v2");
+        assert_eq!(
+            actions,
+            vec![
+                serde_json::json!({ "remove": { "index": "books-v1", "alias": "books" } }),
+                serde_json::json!({ "add": { "index": "books-v2", "alias": "books" } }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_opensearch_bulk_response_to_outcomes() {
+        let response: OpenSearchBulkResponse = serde_json::from_value(serde_json::json!({
+            "took": 3,
+            "errors": true,
+            "items": [
+                { "delete": { "_id": "doc1", "status": 200 } },
+                {
+                    "delete": {
+                        "_id": "doc2",
+                        "status": 503,
+                        "error": { "type": "unavailable_shards_exception", "reason": "primary shard is not active" }
+                    }
+                }
+            ]
+        }))
+        .unwrap();
+
+        let outcomes = opensearch_bulk_response_to_outcomes(response);
+        assert_eq!(outcomes[0], ItemOutcome::success());
+        assert_eq!(
+            outcomes[1],
+            ItemOutcome::failure(
+                "unavailable_shards_exception: primary shard is not active",
+                true
+            )
+        );
+    }
 }
@@ -1,33 +1,48 @@
-use crate::client::OpenSearchApi;
+use crate::client::{OpenSearchApi, OpenSearchSearchResponse};
 use crate::conversions::{
-    create_retry_query, doc_to_opensearch_document, opensearch_document_to_doc,
-    opensearch_mappings_to_schema, opensearch_response_to_search_results,
-    opensearch_scroll_response_to_search_results, schema_to_opensearch_settings,
-    search_query_to_opensearch_request,
+    alias_swap_actions, classify_opensearch_change, create_retry_query, doc_to_opensearch_document,
+    opensearch_aliases_to_index_aliases, opensearch_bulk_response_to_outcomes,
+    opensearch_document_to_doc, opensearch_mappings_to_schema,
+    opensearch_response_to_search_results, schema_to_opensearch_settings,
+    search_query_to_opensearch_request, search_query_to_opensearch_stream_request,
+    OpenSearchStreamCursor, POINT_IN_TIME_KEEP_ALIVE, STREAM_PAGE_SIZE,
 };
 use golem_rust::wasm_rpc::Pollable;
+use golem_search::bulk::{doc_chunks, id_chunks, send_converted, send_with_retries, write_chunks};
 use golem_search::config::with_config_keys;
 use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::error::internal_error;
+use golem_search::facets::facet_requests;
 use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
 use golem_search::golem::search::types::{
-    Doc, DocumentId, IndexName, Schema, SearchError, SearchHit, SearchQuery, SearchResults,
+    BulkResult, Doc, DocumentId, IndexAlias, IndexName, ReindexResult, ReindexTransform, Schema,
+    SchemaChange, SchemaUpdateMode, SearchError, SearchHit, SearchQuery, SearchResults, TaskId,
+    TaskStatus, WriteConsistency,
 };
+use golem_search::schema::{check_in_place, plan_schema_change, reindex_to_schema};
 use log::trace;
+use serde_json::Value;
 use std::cell::{Cell, RefCell};
 
 mod client;
 mod conversions;
 
-/// Uses scroll API for streaming large result sets with fallback to pagination
+/// Index and mapping operations are applied synchronously. Document writes are refreshed
+/// asynchronously, unless they wait for the refresh making them visible.
+fn wait_for_refresh(consistency: WriteConsistency) -> bool {
+    consistency == WriteConsistency::WaitForVisible
+}
+
+/// Streams large result sets with a point in time and `search_after`, so that the pages are
+/// neither limited by the max result window nor affected by concurrent index updates
 struct OpenSearchSearchStream {
     client: OpenSearchApi,
     index_name: String,
     query: SearchQuery,
-    scroll_id: RefCell<Option<String>>,
+    pit_id: RefCell<Option<String>>,
+    search_after: RefCell<Option<Vec<Value>>>,
     finished: Cell<bool>,
-    current_offset: Cell<u32>,
-    use_scroll: Cell<bool>,
-    scroll_failed: Cell<bool>,
+    failure: RefCell<Option<SearchError>>,
 }
 
 impl OpenSearchSearchStream {
@@ -35,121 +50,145 @@ impl OpenSearchSearchStream {
         Self {
             client,
             index_name,
-            query: query.clone(),
-            scroll_id: RefCell::new(None),
+            query,
+            pit_id: RefCell::new(None),
+            search_after: RefCell::new(None),
+            finished: Cell::new(false),
+            failure: RefCell::new(None),
+        }
+    }
+
+    pub fn resumed(
+        client: OpenSearchApi,
+        index_name: String,
+        query: SearchQuery,
+        cursor: OpenSearchStreamCursor,
+    ) -> Self {
+        Self {
+            client,
+            index_name,
+            query,
+            pit_id: RefCell::new(Some(cursor.pit_id)),
+            search_after: RefCell::new(Some(cursor.search_after)),
             finished: Cell::new(false),
-            current_offset: Cell::new(query.offset.unwrap_or(0)),
-            use_scroll: Cell::new(true), // Start with scroll, fallback to pagination if needed
-            scroll_failed: Cell::new(false),
+            failure: RefCell::new(None),
+        }
+    }
+
+    /// Creates a stream failing with the given error on its first page, for example when it
+    /// can't be resumed
+    pub fn failed(
+        client: OpenSearchApi,
+        index_name: String,
+        query: SearchQuery,
+        error: SearchError,
+    ) -> Self {
+        Self {
+            failure: RefCell::new(Some(error)),
+            ..Self::new(client, index_name, query)
         }
     }
 
     pub fn subscribe(&self) -> Pollable {
         golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
     }
-}
 
-impl OpenSearchSearchStream {
-    fn try_scroll_next(&self) -> Option<Option<Vec<SearchHit>>> {
-        if self.scroll_id.borrow().is_none() {
-            let mut os_query = search_query_to_opensearch_request(self.query.clone());
-            os_query.from = Some(0);
-            os_query.size = Some(self.query.per_page.unwrap_or(100)); // Larger page size for scroll
-
-            match self
-                .client
-                .search_with_scroll(&self.index_name, &os_query, "1m")
-            {
-                Ok(response) => {
-                    let scroll_id = response.scroll_id.clone();
-                    *self.scroll_id.borrow_mut() = Some(scroll_id);
-
-                    let search_results = opensearch_scroll_response_to_search_results(response);
-
-                    if search_results.hits.is_empty() {
-                        self.finished.set(true);
-                        return Some(Some(vec![]));
-                    }
-
-                    Some(Some(search_results.hits))
-                }
-                Err(e) => {
-                    trace!("Initial scroll search failed: {e:?}");
-                    None
-                }
-            }
-        } else {
-            let scroll_id = self.scroll_id.borrow().clone().unwrap();
-
-            match self.client.scroll(&scroll_id, "1m") {
-                Ok(response) => {
-                    let search_results = opensearch_scroll_response_to_search_results(response);
-
-                    if search_results.hits.is_empty() {
-                        self.finished.set(true);
-                        return Some(Some(vec![]));
-                    }
-
-                    Some(Some(search_results.hits))
-                }
-                Err(e) => {
-                    trace!("Scroll continuation failed: {e:?}");
-                    None
-                }
-            }
-        }
+    pub fn cursor(&self) -> Option<OpenSearchStreamCursor> {
+        Some(OpenSearchStreamCursor {
+            pit_id: self.pit_id.borrow().clone()?,
+            search_after: self.search_after.borrow().clone()?,
+        })
     }
 
-    fn try_pagination_next(&self) -> Option<Vec<SearchHit>> {
-        let mut os_query = search_query_to_opensearch_request(self.query.clone());
-        os_query.from = Some(self.current_offset.get());
-        os_query.size = Some(self.query.per_page.unwrap_or(10));
+    fn finish(&self) {
+        self.finished.set(true);
+        if let Some(pit_id) = self.pit_id.borrow().as_ref() {
+            let _ = self.client.delete_point_in_time(pit_id);
+        }
+    }
 
-        match self.client.search(&self.index_name, &os_query) {
-            Ok(response) => {
-                let search_results = opensearch_response_to_search_results(response);
+    /// Returns the next page of hits, an empty page once all the hits have been returned, or the
+    /// error which ended the stream
+    fn next_page(&self) -> Result<Vec<SearchHit>, SearchError> {
+        if let Some(error) = self.failure.take() {
+            self.finished.set(true);
+            return Err(error);
+        }
+        if self.finished.get() {
+            return Ok(vec![]);
+        }
 
-                if search_results.hits.is_empty() {
-                    self.finished.set(true);
-                    return Some(vec![]);
-                }
+        let result = self.read_page();
+        if result.is_err() {
+            self.finish();
+        }
+        result
+    }
 
-                let current_offset = self.current_offset.get();
-                let received_count = search_results.hits.len() as u32;
-                self.current_offset.set(current_offset + received_count);
+    fn read_page(&self) -> Result<Vec<SearchHit>, SearchError> {
+        let pit_id = match self.pit_id.borrow().clone() {
+            Some(pit_id) => pit_id,
+            None => self.open_point_in_time()?,
+        };
+
+        // The point in time expires when the stream is idle for too long, for example before a
+        // recovered worker resumes it. The sort values of the last returned hit include a
+        // tiebreaker which is only valid in that point in time, so the stream can't continue in
+        // a new one.
+        let response = self.search_point_in_time(&pit_id)?.ok_or_else(|| {
+            internal_error(format!(
+                "Point in time {pit_id} expired, the stream can't be continued"
+            ))
+        })?;
+        if let Some(pit_id) = &response.pit_id {
+            *self.pit_id.borrow_mut() = Some(pit_id.clone());
+        }
+        if let Some(sort) = response.hits.hits.last().and_then(|hit| hit.sort.clone()) {
+            *self.search_after.borrow_mut() = Some(sort);
+        }
+        let received_count = response.hits.hits.len();
+        let page_size = self.query.per_page.unwrap_or(STREAM_PAGE_SIZE) as usize;
 
-                if let Some(total) = search_results.total {
-                    if self.current_offset.get() >= total {
-                        self.finished.set(true);
-                    }
-                }
+        let search_results =
+            opensearch_response_to_search_results(response, &[], self.query.highlight.as_ref());
 
-                Some(search_results.hits)
-            }
-            Err(e) => {
-                trace!("Pagination search failed: {e:?}");
-                self.finished.set(true);
-                Some(vec![])
-            }
+        if received_count < page_size {
+            self.finish();
         }
+
+        Ok(search_results.hits)
+    }
+
+    fn open_point_in_time(&self) -> Result<String, SearchError> {
+        let pit_id = self
+            .client
+            .create_point_in_time(&self.index_name, POINT_IN_TIME_KEEP_ALIVE)?;
+        *self.pit_id.borrow_mut() = Some(pit_id.clone());
+        Ok(pit_id)
+    }
+
+    /// Searches the next page in the point in time, or returns `None` if it has expired
+    fn search_point_in_time(
+        &self,
+        pit_id: &str,
+    ) -> Result<Option<OpenSearchSearchResponse>, SearchError> {
+        let query = search_query_to_opensearch_stream_request(
+            self.query.clone(),
+            pit_id,
+            self.search_after.borrow().clone(),
+        )?;
+        self.client.search_point_in_time(&query)
     }
 }
 
 impl GuestSearchStream for OpenSearchSearchStream {
     fn get_next(&self) -> Option<Vec<SearchHit>> {
-        if self.finished.get() {
-            return Some(vec![]);
-        }
-
-        if self.use_scroll.get() && !self.scroll_failed.get() {
-            self.try_scroll_next().unwrap_or_else(|| {
-                trace!("Scroll failed, falling back to pagination");
-                self.scroll_failed.set(true);
-                self.use_scroll.set(false);
-                self.try_pagination_next()
-            })
-        } else {
-            self.try_pagination_next()
+        match self.next_page() {
+            Ok(hits) => Some(hits),
+            Err(e) => {
+                trace!("Search stream failed: {e:?}");
+                Some(vec![])
+            }
         }
     }
 
@@ -189,20 +228,27 @@ impl OpenSearchComponent {
 impl Guest for OpenSearchComponent {
     type SearchStream = OpenSearchSearchStream;
 
-    fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+    fn create_index(
+        name: IndexName,
+        schema: Option<Schema>,
+        _consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
         let client = Self::create_client()?;
 
-        let settings = schema.map(schema_to_opensearch_settings);
+        let settings = schema.map(schema_to_opensearch_settings).transpose()?;
         client.create_index(&name, settings)?;
 
-        Ok(())
+        Ok(None)
     }
 
-    fn delete_index(name: IndexName) -> Result<(), SearchError> {
+    fn delete_index(
+        name: IndexName,
+        _consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
         let client = Self::create_client()?;
         client.delete_index(&name)?;
 
-        Ok(())
+        Ok(None)
     }
 
     fn list_indexes() -> Result<Vec<IndexName>, SearchError> {
@@ -211,85 +257,171 @@ impl Guest for OpenSearchComponent {
         Ok(indices.into_iter().map(|idx| idx.index).collect())
     }
 
-    fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+    fn create_alias(alias: IndexName, index: IndexName) -> Result<(), SearchError> {
         let client = Self::create_client()?;
-        let opensearch_doc = doc_to_opensearch_document(doc).map_err(SearchError::InvalidQuery)?;
+        client.update_aliases(alias_swap_actions(&alias, &[], &index))
+    }
 
-        let doc_id = opensearch_doc
-            .get("id")
-            .and_then(|v| v.as_str())
-            .unwrap_or("unknown")
-            .to_string();
+    fn swap_alias(alias: IndexName, index: IndexName) -> Result<(), SearchError> {
+        let client = Self::create_client()?;
+        let current_indices = match client.get_aliases(Some(&alias)) {
+            Ok(aliases) => aliases.into_keys().collect::<Vec<_>>(),
+            Err(SearchError::IndexNotFound) => vec![],
+            Err(e) => return Err(e),
+        };
 
-        client.index_document(&index, &doc_id, &opensearch_doc)?;
+        client.update_aliases(alias_swap_actions(&alias, &current_indices, &index))
+    }
 
-        Ok(())
+    fn list_aliases() -> Result<Vec<IndexAlias>, SearchError> {
+        let client = Self::create_client()?;
+        let aliases = client.get_aliases(None)?;
+        Ok(opensearch_aliases_to_index_aliases(aliases))
     }
 
-    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+    fn delete_alias(alias: IndexName) -> Result<(), SearchError> {
         let client = Self::create_client()?;
+        let actions = client
+            .get_aliases(Some(&alias))?
+            .into_keys()
+            .map(|index| serde_json::json!({ "remove": { "index": index, "alias": alias } }))
+            .collect();
 
-        if docs.is_empty() {
-            return Ok(());
-        }
+        client.update_aliases(actions)
+    }
 
-        let mut bulk_operations = Vec::new();
-        for doc in docs {
-            let opensearch_doc =
-                doc_to_opensearch_document(doc).map_err(SearchError::InvalidQuery)?;
-
-            let doc_id = opensearch_doc
-                .get("id")
-                .and_then(|v| v.as_str())
-                .unwrap_or("unknown")
-                .to_string();
-
-            let action = serde_json::json!({
-                "index": {
-                    "_index": index,
-                    "_id": doc_id
-                }
-            });
-            bulk_operations.push(serde_json::to_string(&action).unwrap());
-            bulk_operations.push(serde_json::to_string(&opensearch_doc).unwrap());
-        }
+    fn reindex(
+        source: IndexName,
+        target: IndexName,
+        alias: Option<IndexName>,
+        transform: Option<ReindexTransform>,
+    ) -> Result<ReindexResult, SearchError> {
+        golem_search::reindex::reindex::<Self>(source, target, alias, transform, Self::next_page)
+    }
 
-        let bulk_body = bulk_operations.join("\n") + "\n";
+    fn upsert(
+        index: IndexName,
+        doc: Doc,
+        consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
+        let client = Self::create_client()?;
+        let opensearch_doc = doc_to_opensearch_document(doc).map_err(SearchError::InvalidQuery)?;
 
-        let _result = client.bulk_index(&bulk_body)?;
+        let doc_id = opensearch_doc
+            .get("id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string();
 
-        Ok(())
+        client.index_document(
+            &index,
+            &doc_id,
+            &opensearch_doc,
+            wait_for_refresh(consistency),
+        )?;
+
+        Ok(None)
     }
 
-    fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+    fn upsert_many(
+        index: IndexName,
+        docs: Vec<Doc>,
+        consistency: WriteConsistency,
+    ) -> Result<BulkResult, SearchError> {
         let client = Self::create_client()?;
-        client.delete_document(&index, &id)?;
-
-        Ok(())
+        let limits = Self::bulk_limits();
+
+        write_chunks(
+            doc_chunks(docs, &limits),
+            |doc| doc.id.as_str(),
+            |docs| {
+                send_with_retries(
+                    docs,
+                    |doc| doc.id.as_str(),
+                    &limits,
+                    |docs| {
+                        send_converted(
+                            docs,
+                            |doc| doc_to_opensearch_document(doc.clone()),
+                            |opensearch_docs| {
+                                let mut bulk_operations = Vec::new();
+                                for opensearch_doc in opensearch_docs {
+                                    let doc_id = opensearch_doc
+                                        .get("id")
+                                        .and_then(|v| v.as_str())
+                                        .unwrap_or("unknown")
+                                        .to_string();
+
+                                    let action = serde_json::json!({
+                                        "index": {
+                                            "_index": index,
+                                            "_id": doc_id
+                                        }
+                                    });
+                                    bulk_operations.push(serde_json::to_string(&action).unwrap());
+                                    bulk_operations
+                                        .push(serde_json::to_string(opensearch_doc).unwrap());
+                                }
+
+                                let bulk_body = bulk_operations.join("\n") + "\n";
+                                client
+                                    .bulk_index(&bulk_body, wait_for_refresh(consistency))
+                                    .map(opensearch_bulk_response_to_outcomes)
+                            },
+                        )
+                    },
+                )
+            },
+        )
     }
 
-    fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+    fn delete(
+        index: IndexName,
+        id: DocumentId,
+        consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
         let client = Self::create_client()?;
+        client.delete_document(&index, &id, wait_for_refresh(consistency))?;
 
-        if ids.is_empty() {
-            return Ok(());
-        }
-
-        let mut bulk_operations = Vec::new();
-        for id in ids {
-            let action = serde_json::json!({
-                "delete": {
-                    "_index": index,
-                    "_id": id
-                }
-            });
-            bulk_operations.push(serde_json::to_string(&action).unwrap());
-        }
-
-        let bulk_body = bulk_operations.join("\n") + "\n";
-        client.bulk_index(&bulk_body)?;
+        Ok(None)
+    }
 
-        Ok(())
+    fn delete_many(
+        index: IndexName,
+        ids: Vec<DocumentId>,
+        consistency: WriteConsistency,
+    ) -> Result<BulkResult, SearchError> {
+        let client = Self::create_client()?;
+        let limits = Self::bulk_limits();
+
+        write_chunks(
+            id_chunks(ids, &limits),
+            |id| id.as_str(),
+            |ids| {
+                send_with_retries(
+                    ids,
+                    |id| id.as_str(),
+                    &limits,
+                    |ids| {
+                        let mut bulk_operations = Vec::new();
+                        for id in ids {
+                            let action = serde_json::json!({
+                                "delete": {
+                                    "_index": index,
+                                    "_id": id
+                                }
+                            });
+                            bulk_operations.push(serde_json::to_string(&action).unwrap());
+                        }
+
+                        let bulk_body = bulk_operations.join("\n") + "\n";
+                        client
+                            .bulk_index(&bulk_body, wait_for_refresh(consistency))
+                            .map(opensearch_bulk_response_to_outcomes)
+                    },
+                )
+            },
+        )
     }
 
     fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
@@ -303,14 +435,22 @@ impl Guest for OpenSearchComponent {
 
     fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
         let client = Self::create_client()?;
-        let opensearch_request = search_query_to_opensearch_request(query);
+        let facets = facet_requests(&query)?;
+        let highlight = query.highlight.clone();
+        let opensearch_request = search_query_to_opensearch_request(query)?;
 
         let response = client.search(&index, &opensearch_request)?;
-        Ok(opensearch_response_to_search_results(response))
+        Ok(opensearch_response_to_search_results(
+            response,
+            &facets,
+            highlight.as_ref(),
+        ))
     }
 
     fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
         let client = Self::create_client()?;
+        // Rejects invalid filters before the stream is created
+        search_query_to_opensearch_request(query.clone())?;
         let stream = OpenSearchSearchStream::new(client, index, query);
         Ok(SearchStream::new(stream))
     }
@@ -325,15 +465,42 @@ impl Guest for OpenSearchComponent {
         ))
     }
 
-    fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
+    fn plan_schema_change(
+        index: IndexName,
+        schema: Schema,
+    ) -> Result<Vec<SchemaChange>, SearchError> {
+        let current = Self::get_schema(index)?;
+        Ok(plan_schema_change(
+            &current,
+            &schema,
+            classify_opensearch_change,
+        ))
+    }
+
+    fn update_schema(
+        index: IndexName,
+        schema: Schema,
+        _consistency: WriteConsistency,
+        mode: SchemaUpdateMode,
+    ) -> Result<Option<TaskId>, SearchError> {
+        if let SchemaUpdateMode::Reindex(reindex) = mode {
+            return reindex_to_schema::<Self>(index, schema, reindex);
+        }
+        check_in_place(Self::plan_schema_change(index.clone(), schema.clone()))?;
+
         let client = Self::create_client()?;
-        let settings = schema_to_opensearch_settings(schema);
+        let settings = schema_to_opensearch_settings(schema)?;
 
         if let Some(mappings) = settings.mappings {
             client.put_mappings(&index, &mappings)?;
         }
 
-        Ok(())
+        Ok(None)
+    }
+
+    fn task_status(_index: IndexName, _task_id: TaskId) -> Result<TaskStatus, SearchError> {
+        // Writes are applied synchronously, so no task is returned to track
+        Err(SearchError::Unsupported)
     }
 }
 
@@ -346,6 +513,27 @@ impl ExtendedGuest for OpenSearchComponent {
         OpenSearchSearchStream::new(client, index, query)
     }
 
+    fn resumed_stream(index: IndexName, query: SearchQuery, cursor: String) -> Self::SearchStream {
+        let client = Self::create_client().unwrap_or_else(|_| {
+            OpenSearchApi::new("http://localhost:9200".to_string(), None, None, None)
+        });
+
+        match serde_json::from_str(&cursor) {
+            Ok(cursor) => OpenSearchSearchStream::resumed(client, index, query, cursor),
+            // Restarting the stream would return the already persisted hits again
+            Err(e) => OpenSearchSearchStream::failed(
+                client,
+                index,
+                query,
+                internal_error(format!("Invalid stream cursor {cursor}: {e}")),
+            ),
+        }
+    }
+
+    fn stream_cursor(stream: &Self::SearchStream) -> Option<String> {
+        serde_json::to_string(&stream.cursor()?).ok()
+    }
+
     fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
         create_retry_query(original_query, partial_hits)
     }
@@ -353,13 +541,19 @@ impl ExtendedGuest for OpenSearchComponent {
     fn subscribe(stream: &Self::SearchStream) -> Pollable {
         stream.subscribe()
     }
+
+    fn next_page(stream: &Self::SearchStream) -> Result<Vec<SearchHit>, SearchError> {
+        stream.next_page()
+    }
 }
 
 impl Drop for OpenSearchSearchStream {
     fn drop(&mut self) {
-        // Clear any active scroll when the stream is dropped
-        if let Some(scroll_id) = self.scroll_id.borrow().as_ref() {
-            let _ = self.client.clear_scroll(scroll_id);
+        // Delete the point in time when the stream is dropped before reaching its end
+        if !self.finished.get() {
+            if let Some(pit_id) = self.pit_id.borrow().as_ref() {
+                let _ = self.client.delete_point_in_time(pit_id);
+            }
         }
     }
 }
@@ -0,0 +1,378 @@
+use crate::golem::search::types::{BulkItemResult, BulkResult, Doc, DocumentId, SearchError};
+use std::time::Duration;
+
+/// Limits of the chunks a bulk write is split into, and of the retries of each chunk
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BulkLimits {
+    /// Maximum number of documents of a chunk
+    pub max_docs: usize,
+    /// Maximum size of the documents of a chunk, in bytes. A larger document is sent alone.
+    pub max_bytes: usize,
+    /// Number of times the transient failures of a chunk are retried
+    pub max_retries: u32,
+}
+
+impl Default for BulkLimits {
+    fn default() -> Self {
+        Self {
+            max_docs: 1000,
+            max_bytes: 5 * 1024 * 1024,
+            max_retries: 3,
+        }
+    }
+}
+
+/// Outcome of one item of a bulk request, as reported by a provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemOutcome {
+    pub error: Option<String>,
+    /// Whether sending the item again may succeed, e.g. when the provider rejected it because
+    /// it was overloaded
+    pub retryable: bool,
+}
+
+impl ItemOutcome {
+    pub fn success() -> Self {
+        Self {
+            error: None,
+            retryable: false,
+        }
+    }
+
+    pub fn failure(reason: impl Into<String>, retryable: bool) -> Self {
+        Self {
+            error: Some(reason.into()),
+            retryable,
+        }
+    }
+}
+
+/// Retryable item status codes: rate limiting and unavailable or overloaded nodes
+pub fn is_retryable_status(status: u16) -> bool {
+    status == 429 || status >= 500
+}
+
+pub fn doc_chunks(docs: Vec<Doc>, limits: &BulkLimits) -> Vec<Vec<Doc>> {
+    chunks(docs, limits, |doc| doc.id.len() + doc.content.len())
+}
+
+pub fn id_chunks(ids: Vec<DocumentId>, limits: &BulkLimits) -> Vec<Vec<DocumentId>> {
+    chunks(ids, limits, |id| id.len())
+}
+
+fn chunks<T>(items: Vec<T>, limits: &BulkLimits, size: impl Fn(&T) -> usize) -> Vec<Vec<T>> {
+    let mut chunks = Vec::new();
+    let mut chunk = Vec::new();
+    let mut chunk_bytes = 0;
+
+    for item in items {
+        let item_bytes = size(&item);
+        if !chunk.is_empty()
+            && (chunk.len() >= limits.max_docs || chunk_bytes + item_bytes > limits.max_bytes)
+        {
+            chunks.push(std::mem::take(&mut chunk));
+            chunk_bytes = 0;
+        }
+        chunk_bytes += item_bytes;
+        chunk.push(item);
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+
+    chunks
+}
+
+/// Writes the chunks one after the other, and merges their results. Once a chunk has been
+/// written, the errors of the next chunks are reported as failures of their documents, so that
+/// the outcomes of the documents already written are not lost.
+pub fn write_chunks<T>(
+    chunks: Vec<Vec<T>>,
+    id: impl Fn(&T) -> &str,
+    mut write: impl FnMut(Vec<T>) -> Result<BulkResult, SearchError>,
+) -> Result<BulkResult, SearchError> {
+    let mut items = Vec::new();
+    let mut task_ids = Vec::new();
+
+    for (index, chunk) in chunks.into_iter().enumerate() {
+        let ids = chunk
+            .iter()
+            .map(|item| id(item).to_string())
+            .collect::<Vec<_>>();
+        match write(chunk) {
+            Ok(result) => {
+                items.extend(result.items);
+                task_ids.extend(result.task_ids);
+            }
+            Err(error) if index == 0 => return Err(error),
+            Err(error) => {
+                let reason = error_reason(&error);
+                items.extend(ids.into_iter().map(|id| BulkItemResult {
+                    id,
+                    error: Some(reason.clone()),
+                }));
+            }
+        }
+    }
+
+    Ok(BulkResult { items, task_ids })
+}
+
+/// Sends the items of a chunk, then sends again the items that failed with a retryable error,
+/// waiting longer after each attempt. A rate limited or timed out request is retried as a whole.
+///
+/// `send` must return one outcome per item, in the order of the items. The tasks applying the
+/// items are left to the caller to record.
+pub fn send_with_retries<T: Clone>(
+    items: Vec<T>,
+    id: impl Fn(&T) -> &str,
+    limits: &BulkLimits,
+    mut send: impl FnMut(&[T]) -> Result<Vec<ItemOutcome>, SearchError>,
+) -> Result<BulkResult, SearchError> {
+    let mut errors: Vec<Option<String>> = vec![None; items.len()];
+    let mut pending = (0..items.len()).collect::<Vec<_>>();
+    let mut attempt = 0;
+
+    while !pending.is_empty() {
+        let batch = pending
+            .iter()
+            .map(|i| items[*i].clone())
+            .collect::<Vec<_>>();
+        let can_retry = attempt < limits.max_retries;
+
+        match send(&batch) {
+            Ok(outcomes) => {
+                if outcomes.len() != batch.len() {
+                    return Err(SearchError::Internal(format!(
+                        "Expected {} bulk item outcomes, received {}",
+                        batch.len(),
+                        outcomes.len()
+                    )));
+                }
+                let mut retried = Vec::new();
+                for (i, outcome) in pending.into_iter().zip(outcomes) {
+                    if outcome.error.is_some() && outcome.retryable && can_retry {
+                        retried.push(i);
+                    }
+                    errors[i] = outcome.error;
+                }
+                pending = retried;
+            }
+            Err(SearchError::RateLimited | SearchError::Timeout) if can_retry => {}
+            Err(error) => return Err(error),
+        }
+
+        if !pending.is_empty() {
+            std::thread::sleep(retry_delay(attempt));
+            attempt += 1;
+        }
+    }
+
+    Ok(BulkResult {
+        items: items
+            .iter()
+            .zip(errors)
+            .map(|(item, error)| BulkItemResult {
+                id: id(item).to_string(),
+                error,
+            })
+            .collect(),
+        task_ids: vec![],
+    })
+}
+
+/// Converts the items of a chunk to the provider's format, and sends the converted ones.
+/// The items that can't be converted are reported as failures, without being sent.
+pub fn send_converted<T, U>(
+    items: &[T],
+    convert: impl Fn(&T) -> Result<U, String>,
+    send: impl FnOnce(&[U]) -> Result<Vec<ItemOutcome>, SearchError>,
+) -> Result<Vec<ItemOutcome>, SearchError> {
+    let mut outcomes = Vec::with_capacity(items.len());
+    let mut converted = Vec::with_capacity(items.len());
+    for item in items {
+        match convert(item) {
+            Ok(value) => {
+                converted.push(value);
+                outcomes.push(None);
+            }
+            Err(reason) => outcomes.push(Some(ItemOutcome::failure(reason, false))),
+        }
+    }
+
+    let mut sent = if converted.is_empty() {
+        Vec::new()
+    } else {
+        send(&converted)?
+    }
+    .into_iter();
+
+    Ok(outcomes
+        .into_iter()
+        .map(|outcome| {
+            outcome.unwrap_or_else(|| {
+                sent.next().unwrap_or_else(|| {
+                    ItemOutcome::failure("The provider didn't report the outcome", false)
+                })
+            })
+        })
+        .collect())
+}
+
+fn retry_delay(attempt: u32) -> Duration {
+    Duration::from_millis(200 * 2u64.pow(attempt.min(6)))
+}
+
+fn error_reason(error: &SearchError) -> String {
+    match error {
+        SearchError::IndexNotFound => "Index not found".to_string(),
+        SearchError::InvalidQuery(message) | SearchError::Internal(message) => message.clone(),
+        SearchError::Unsupported => "Unsupported".to_string(),
+        SearchError::Timeout => "Timed out".to_string(),
+        SearchError::RateLimited => "Rate limited".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(id: &str, content: &str) -> Doc {
+        Doc {
+            id: id.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    const LIMITS: BulkLimits = BulkLimits {
+        max_docs: 2,
+        max_bytes: 20,
+        max_retries: 1,
+    };
+
+    #[test]
+    fn docs_are_chunked_by_count_and_size() {
+        let docs = vec![
+            doc("1", "{}"),
+            doc("2", "{}"),
+            doc("3", "{}"),
+            doc("4", r#"{"title":"a large document"}"#),
+            doc("5", "{}"),
+        ];
+
+        let chunks = doc_chunks(docs, &LIMITS)
+            .into_iter()
+            .map(|chunk| chunk.into_iter().map(|doc| doc.id).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            chunks,
+            vec![vec!["1", "2"], vec!["3"], vec!["4"], vec!["5"]]
+        );
+        assert!(id_chunks(vec![], &LIMITS).is_empty());
+    }
+
+    #[test]
+    fn only_failed_items_are_retried() {
+        let docs = vec![doc("1", "{}"), doc("2", "{}"), doc("3", "{}")];
+        let mut sent = Vec::new();
+
+        let result = send_with_retries(
+            docs,
+            |doc| doc.id.as_str(),
+            &BulkLimits {
+                max_retries: 2,
+                ..LIMITS
+            },
+            |docs| {
+                sent.push(docs.iter().map(|doc| doc.id.clone()).collect::<Vec<_>>());
+                Ok(docs
+                    .iter()
+                    .map(|doc| match doc.id.as_str() {
+                        "2" if sent.len() == 1 => ItemOutcome::failure("overloaded", true),
+                        "3" => ItemOutcome::failure("mapping error", false),
+                        _ => ItemOutcome::success(),
+                    })
+                    .collect())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(sent, vec![vec!["1", "2", "3"], vec!["2"]]);
+        let errors = result
+            .items
+            .iter()
+            .map(|item| (item.id.as_str(), item.error.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec![("1", None), ("2", None), ("3", Some("mapping error"))]
+        );
+    }
+
+    #[test]
+    fn chunk_errors_after_the_first_chunk_become_item_failures() {
+        let chunks = vec![vec![doc("1", "{}")], vec![doc("2", "{}"), doc("3", "{}")]];
+        let result = write_chunks(
+            chunks,
+            |doc| doc.id.as_str(),
+            |docs| {
+                if docs[0].id == "1" {
+                    Ok(BulkResult {
+                        items: vec![BulkItemResult {
+                            id: "1".to_string(),
+                            error: None,
+                        }],
+                        task_ids: vec!["task-1".to_string()],
+                    })
+                } else {
+                    Err(SearchError::Internal("connection reset".to_string()))
+                }
+            },
+        )
+        .unwrap();
+
+        assert_eq!(result.items.len(), 3);
+        assert_eq!(result.task_ids, vec!["task-1"]);
+        assert_eq!(result.items[2].id, "3");
+        assert_eq!(result.items[2].error.as_deref(), Some("connection reset"));
+
+        let first_chunk_error = write_chunks(
+            vec![vec![doc("1", "{}")]],
+            |doc| doc.id.as_str(),
+            |_| Err(SearchError::IndexNotFound),
+        );
+        assert_eq!(first_chunk_error, Err(SearchError::IndexNotFound));
+    }
+
+    #[test]
+    fn unconvertible_items_are_not_sent() {
+        let docs = vec![doc("1", "{}"), doc("2", "not json"), doc("3", "{}")];
+        let outcomes = send_converted(
+            &docs,
+            |doc| {
+                if doc.content.starts_with('{') {
+                    Ok(doc.id.clone())
+                } else {
+                    Err("Invalid JSON".to_string())
+                }
+            },
+            |ids| {
+                assert_eq!(ids, ["1", "3"]);
+                Ok(vec![
+                    ItemOutcome::success(),
+                    ItemOutcome::failure("rejected", false),
+                ])
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            outcomes,
+            vec![
+                ItemOutcome::success(),
+                ItemOutcome::failure("Invalid JSON", false),
+                ItemOutcome::failure("rejected", false),
+            ]
+        );
+    }
+}
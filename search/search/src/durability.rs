@@ -1,5 +1,6 @@
-use crate::golem::search::core::Guest;
-use crate::golem::search::types::{IndexName, SearchHit, SearchQuery};
+use crate::bulk::BulkLimits;
+use crate::golem::search::core::{Guest, GuestSearchStream};
+use crate::golem::search::types::{IndexName, SearchError, SearchHit, SearchQuery};
 use golem_rust::wasm_rpc::Pollable;
 use std::marker::PhantomData;
 
@@ -10,7 +11,16 @@ pub struct DurableSearch<Impl> {
 pub trait ExtendedGuest: Guest + 'static {
     fn unwrapped_stream(index: IndexName, query: SearchQuery) -> Self::SearchStream;
 
+    /// Creates a stream continuing right after the given cursor, as previously returned by
+    /// `stream_cursor`
+    fn resumed_stream(index: IndexName, query: SearchQuery, cursor: String) -> Self::SearchStream;
+
+    /// Returns the provider-specific cursor pointing after the last page returned by the stream,
+    /// or `None` if no page has been returned yet.
+    fn stream_cursor(stream: &Self::SearchStream) -> Option<String>;
+
     /// Creates the retry query with the original query and any partial results received.
+    /// It is only used when the replayed stream has no persisted cursor.
     /// There is a default implementation here, but it can be overridden with provider-specific
     /// queries if needed.
     fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
@@ -28,6 +38,22 @@ pub trait ExtendedGuest: Guest + 'static {
     }
 
     fn subscribe(stream: &Self::SearchStream) -> Pollable;
+
+    /// Returns the next page of the stream, blocking until it is available. Unlike
+    /// `blocking-get-next`, a failure to read the page is returned as an error instead of ending
+    /// the stream with an empty page, so that a reindex does not mistake it for the end of the
+    /// source index. The default implementation can't tell them apart, it should be overridden
+    /// by the providers whose streams can fail.
+    fn next_page(stream: &Self::SearchStream) -> Result<Vec<SearchHit>, SearchError> {
+        Ok(stream.blocking_get_next())
+    }
+
+    /// Limits of the chunks `upsert-many` and `delete-many` are split into. With durability,
+    /// each chunk is persisted once written, so that a recovered worker continues with the next
+    /// chunk.
+    fn bulk_limits() -> BulkLimits {
+        BulkLimits::default()
+    }
 }
 
 /// When the durability feature flag is off, wrapping with `DurableSearch` is just a passthrough
@@ -36,21 +62,30 @@ mod passthrough_impl {
     use crate::durability::{DurableSearch, ExtendedGuest};
     use crate::golem::search::core::{Guest, SearchStream};
     use crate::golem::search::types::{
-        Doc, DocumentId, IndexName, Schema, SearchError, SearchQuery, SearchResults,
+        BulkResult, Doc, DocumentId, IndexAlias, IndexName, ReindexResult, ReindexTransform,
+        Schema, SchemaChange, SchemaUpdateMode, SearchError, SearchQuery, SearchResults, TaskId,
+        TaskStatus, WriteConsistency,
     };
     use crate::init_logging;
 
     impl<Impl: ExtendedGuest> Guest for DurableSearch<Impl> {
         type SearchStream = Impl::SearchStream;
 
-        fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+        fn create_index(
+            name: IndexName,
+            schema: Option<Schema>,
+            consistency: WriteConsistency,
+        ) -> Result<Option<TaskId>, SearchError> {
             init_logging();
-            Impl::create_index(name, schema)
+            Impl::create_index(name, schema, consistency)
         }
 
-        fn delete_index(name: IndexName) -> Result<(), SearchError> {
+        fn delete_index(
+            name: IndexName,
+            consistency: WriteConsistency,
+        ) -> Result<Option<TaskId>, SearchError> {
             init_logging();
-            Impl::delete_index(name)
+            Impl::delete_index(name, consistency)
         }
 
         fn list_indexes() -> Result<Vec<IndexName>, SearchError> {
@@ -58,24 +93,70 @@ mod passthrough_impl {
             Impl::list_indexes()
         }
 
-        fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+        fn create_alias(alias: IndexName, index: IndexName) -> Result<(), SearchError> {
+            init_logging();
+            Impl::create_alias(alias, index)
+        }
+
+        fn swap_alias(alias: IndexName, index: IndexName) -> Result<(), SearchError> {
+            init_logging();
+            Impl::swap_alias(alias, index)
+        }
+
+        fn list_aliases() -> Result<Vec<IndexAlias>, SearchError> {
             init_logging();
-            Impl::upsert(index, doc)
+            Impl::list_aliases()
         }
 
-        fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+        fn delete_alias(alias: IndexName) -> Result<(), SearchError> {
             init_logging();
-            Impl::upsert_many(index, docs)
+            Impl::delete_alias(alias)
         }
 
-        fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+        fn reindex(
+            source: IndexName,
+            target: IndexName,
+            alias: Option<IndexName>,
+            transform: Option<ReindexTransform>,
+        ) -> Result<ReindexResult, SearchError> {
             init_logging();
-            Impl::delete(index, id)
+            Impl::reindex(source, target, alias, transform)
         }
 
-        fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+        fn upsert(
+            index: IndexName,
+            doc: Doc,
+            consistency: WriteConsistency,
+        ) -> Result<Option<TaskId>, SearchError> {
             init_logging();
-            Impl::delete_many(index, ids)
+            Impl::upsert(index, doc, consistency)
+        }
+
+        fn upsert_many(
+            index: IndexName,
+            docs: Vec<Doc>,
+            consistency: WriteConsistency,
+        ) -> Result<BulkResult, SearchError> {
+            init_logging();
+            Impl::upsert_many(index, docs, consistency)
+        }
+
+        fn delete(
+            index: IndexName,
+            id: DocumentId,
+            consistency: WriteConsistency,
+        ) -> Result<Option<TaskId>, SearchError> {
+            init_logging();
+            Impl::delete(index, id, consistency)
+        }
+
+        fn delete_many(
+            index: IndexName,
+            ids: Vec<DocumentId>,
+            consistency: WriteConsistency,
+        ) -> Result<BulkResult, SearchError> {
+            init_logging();
+            Impl::delete_many(index, ids, consistency)
         }
 
         fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
@@ -101,21 +182,44 @@ mod passthrough_impl {
             Impl::get_schema(index)
         }
 
-        fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
+        fn plan_schema_change(
+            index: IndexName,
+            schema: Schema,
+        ) -> Result<Vec<SchemaChange>, SearchError> {
+            init_logging();
+            Impl::plan_schema_change(index, schema)
+        }
+
+        fn update_schema(
+            index: IndexName,
+            schema: Schema,
+            consistency: WriteConsistency,
+            mode: SchemaUpdateMode,
+        ) -> Result<Option<TaskId>, SearchError> {
+            init_logging();
+            Impl::update_schema(index, schema, consistency, mode)
+        }
+
+        fn task_status(index: IndexName, task_id: TaskId) -> Result<TaskStatus, SearchError> {
             init_logging();
-            Impl::update_schema(index, schema)
+            Impl::task_status(index, task_id)
         }
     }
 }
 
 #[cfg(feature = "durability")]
 mod durable_impl {
+    use crate::bulk::{doc_chunks, id_chunks, write_chunks};
     use crate::durability::{DurableSearch, ExtendedGuest};
     use crate::golem::search::core::{Guest, GuestSearchStream, SearchStream};
     use crate::golem::search::types::{
-        Doc, DocumentId, IndexName, Schema, SearchError, SearchHit, SearchQuery, SearchResults,
+        BulkResult, Doc, DocumentId, IndexAlias, IndexName, ReindexResult, ReindexTransform,
+        Schema, SchemaChange, SchemaUpdateMode, SearchError, SearchHit, SearchQuery, SearchResults,
+        TaskId, TaskStatus, WriteConsistency,
     };
     use crate::init_logging;
+    use crate::reindex::reindex;
+    use crate::schema::reindex_to_schema;
     use golem_rust::bindings::golem::durability::durability::{
         DurableFunctionType, LazyInitializedPollable,
     };
@@ -129,35 +233,52 @@ mod durable_impl {
     struct CreateIndexInput {
         name: IndexName,
         schema: Option<Schema>,
+        consistency: WriteConsistency,
     }
 
     #[derive(Debug, Clone, IntoValue)]
     struct DeleteIndexInput {
         name: IndexName,
+        consistency: WriteConsistency,
+    }
+
+    #[derive(Debug, Clone, IntoValue)]
+    struct AliasInput {
+        alias: IndexName,
+        index: IndexName,
+    }
+
+    #[derive(Debug, Clone, IntoValue)]
+    struct DeleteAliasInput {
+        alias: IndexName,
     }
 
     #[derive(Debug, Clone, IntoValue)]
     struct UpsertInput {
         index: IndexName,
         doc: Doc,
+        consistency: WriteConsistency,
     }
 
     #[derive(Debug, Clone, IntoValue)]
     struct UpsertManyInput {
         index: IndexName,
         docs: Vec<Doc>,
+        consistency: WriteConsistency,
     }
 
     #[derive(Debug, Clone, IntoValue)]
     struct DeleteInput {
         index: IndexName,
         id: DocumentId,
+        consistency: WriteConsistency,
     }
 
     #[derive(Debug, Clone, IntoValue)]
     struct DeleteManyInput {
         index: IndexName,
         ids: Vec<DocumentId>,
+        consistency: WriteConsistency,
     }
 
     #[derive(Debug, Clone, IntoValue)]
@@ -187,6 +308,19 @@ mod durable_impl {
     struct UpdateSchemaInput {
         index: IndexName,
         schema: Schema,
+        consistency: WriteConsistency,
+    }
+
+    #[derive(Debug, Clone, IntoValue)]
+    struct PlanSchemaChangeInput {
+        index: IndexName,
+        schema: Schema,
+    }
+
+    #[derive(Debug, Clone, IntoValue)]
+    struct TaskStatusInput {
+        index: IndexName,
+        task_id: TaskId,
     }
 
     #[derive(Debug, IntoValue)]
@@ -204,11 +338,21 @@ mod durable_impl {
         }
     }
 
+    #[derive(Debug, Clone, PartialEq, FromValueAndType, IntoValue)]
+    struct WriteOutput {
+        task_id: Option<TaskId>,
+    }
+
     #[derive(Debug, Clone, FromValueAndType, IntoValue)]
     struct ListIndexesOutput {
         names: Vec<IndexName>,
     }
 
+    #[derive(Debug, Clone, FromValueAndType, IntoValue)]
+    struct ListAliasesOutput {
+        aliases: Vec<IndexAlias>,
+    }
+
     #[derive(Debug, Clone, FromValueAndType, IntoValue)]
     struct GetDocOutput {
         doc: Option<Doc>,
@@ -224,46 +368,103 @@ mod durable_impl {
         schema: Schema,
     }
 
+    #[derive(Debug, Clone, PartialEq, FromValueAndType, IntoValue)]
+    struct PlanSchemaChangeOutput {
+        changes: Vec<SchemaChange>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, FromValueAndType, IntoValue)]
+    struct TaskStatusOutput {
+        status: TaskStatus,
+    }
+
+    #[derive(Debug, Clone, PartialEq, FromValueAndType, IntoValue)]
+    struct GetNextOutput {
+        hits: Option<Vec<SearchHit>>,
+        error: Option<SearchError>,
+        cursor: Option<String>,
+    }
+
+    impl GetNextOutput {
+        fn new(
+            result: Result<Option<Vec<SearchHit>>, SearchError>,
+            cursor: Option<String>,
+        ) -> Self {
+            match result {
+                Ok(hits) => Self {
+                    hits,
+                    error: None,
+                    cursor,
+                },
+                Err(error) => Self {
+                    hits: None,
+                    error: Some(error),
+                    cursor,
+                },
+            }
+        }
+    }
+
     impl<Impl: ExtendedGuest> Guest for DurableSearch<Impl> {
         type SearchStream = DurableSearchStream<Impl>;
 
-        fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+        fn create_index(
+            name: IndexName,
+            schema: Option<Schema>,
+            consistency: WriteConsistency,
+        ) -> Result<Option<TaskId>, SearchError> {
             init_logging();
 
-            let durability = Durability::<NoOutput, SearchError>::new(
+            let durability = Durability::<WriteOutput, SearchError>::new(
                 "golem_search",
                 "create_index",
                 DurableFunctionType::WriteRemote,
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::create_index(name.clone(), schema.clone()).map(|()| NoOutput)
+                    Impl::create_index(name.clone(), schema.clone(), consistency)
+                        .map(|task_id| WriteOutput { task_id })
                 });
                 durability
-                    .persist(CreateIndexInput { name, schema }, result)
-                    .map(|_: NoOutput| ())
+                    .persist(
+                        CreateIndexInput {
+                            name,
+                            schema,
+                            consistency,
+                        },
+                        result,
+                    )
+                    .map(|result| result.task_id)
             } else {
-                durability.replay().map(|_: NoOutput| ())
+                durability
+                    .replay()
+                    .map(|result: WriteOutput| result.task_id)
             }
         }
 
-        fn delete_index(name: IndexName) -> Result<(), SearchError> {
+        fn delete_index(
+            name: IndexName,
+            consistency: WriteConsistency,
+        ) -> Result<Option<TaskId>, SearchError> {
             init_logging();
 
-            let durability = Durability::<NoOutput, SearchError>::new(
+            let durability = Durability::<WriteOutput, SearchError>::new(
                 "golem_search",
                 "delete_index",
                 DurableFunctionType::WriteRemote,
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::delete_index(name.clone()).map(|()| NoOutput)
+                    Impl::delete_index(name.clone(), consistency)
+                        .map(|task_id| WriteOutput { task_id })
                 });
                 durability
-                    .persist(DeleteIndexInput { name }, result)
-                    .map(|_: NoOutput| ())
+                    .persist(DeleteIndexInput { name, consistency }, result)
+                    .map(|result| result.task_id)
             } else {
-                durability.replay().map(|_: NoOutput| ())
+                durability
+                    .replay()
+                    .map(|result: WriteOutput| result.task_id)
             }
         }
 
@@ -289,86 +490,251 @@ mod durable_impl {
             }
         }
 
-        fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+        fn create_alias(alias: IndexName, index: IndexName) -> Result<(), SearchError> {
             init_logging();
 
             let durability = Durability::<NoOutput, SearchError>::new(
                 "golem_search",
-                "upsert",
+                "create_alias",
                 DurableFunctionType::WriteRemote,
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::upsert(index.clone(), doc.clone()).map(|()| NoOutput)
+                    Impl::create_alias(alias.clone(), index.clone()).map(|()| NoOutput)
                 });
                 durability
-                    .persist(UpsertInput { index, doc }, result)
+                    .persist(AliasInput { alias, index }, result)
                     .map(|_: NoOutput| ())
             } else {
                 durability.replay().map(|_: NoOutput| ())
             }
         }
 
-        fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+        fn swap_alias(alias: IndexName, index: IndexName) -> Result<(), SearchError> {
             init_logging();
 
             let durability = Durability::<NoOutput, SearchError>::new(
                 "golem_search",
-                "upsert_many",
+                "swap_alias",
                 DurableFunctionType::WriteRemote,
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::upsert_many(index.clone(), docs.clone()).map(|_| NoOutput)
+                    Impl::swap_alias(alias.clone(), index.clone()).map(|()| NoOutput)
                 });
                 durability
-                    .persist(UpsertManyInput { index, docs }, result)
+                    .persist(AliasInput { alias, index }, result)
                     .map(|_: NoOutput| ())
             } else {
-                durability.replay().map(|_: NoOutput| {})
+                durability.replay().map(|_: NoOutput| ())
+            }
+        }
+
+        fn list_aliases() -> Result<Vec<IndexAlias>, SearchError> {
+            init_logging();
+
+            let durability = Durability::<ListAliasesOutput, SearchError>::new(
+                "golem_search",
+                "list_aliases",
+                DurableFunctionType::ReadRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    Impl::list_aliases().map(|aliases| ListAliasesOutput { aliases })
+                });
+                durability
+                    .persist(NoInput, result)
+                    .map(|result| result.aliases)
+            } else {
+                durability
+                    .replay()
+                    .map(|result: ListAliasesOutput| result.aliases)
             }
         }
 
-        fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+        fn delete_alias(alias: IndexName) -> Result<(), SearchError> {
             init_logging();
 
             let durability = Durability::<NoOutput, SearchError>::new(
                 "golem_search",
-                "delete",
+                "delete_alias",
                 DurableFunctionType::WriteRemote,
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::delete(index.clone(), id.clone()).map(|()| NoOutput)
+                    Impl::delete_alias(alias.clone()).map(|()| NoOutput)
                 });
                 durability
-                    .persist(DeleteInput { index, id }, result)
+                    .persist(DeleteAliasInput { alias }, result)
                     .map(|_: NoOutput| ())
             } else {
                 durability.replay().map(|_: NoOutput| ())
             }
         }
 
-        fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+        /// The reindex is not persisted as a whole: it is composed of the durable stream, bulk
+        /// writes and alias swap of `DurableSearch`, so that a recovered worker replays the
+        /// steps already done and continues with the next ones
+        fn reindex(
+            source: IndexName,
+            target: IndexName,
+            alias: Option<IndexName>,
+            transform: Option<ReindexTransform>,
+        ) -> Result<ReindexResult, SearchError> {
             init_logging();
+            reindex::<Self>(
+                source,
+                target,
+                alias,
+                transform,
+                DurableSearchStream::<Impl>::next_page,
+            )
+        }
 
-            let durability = Durability::<NoOutput, SearchError>::new(
+        fn upsert(
+            index: IndexName,
+            doc: Doc,
+            consistency: WriteConsistency,
+        ) -> Result<Option<TaskId>, SearchError> {
+            init_logging();
+
+            let durability = Durability::<WriteOutput, SearchError>::new(
                 "golem_search",
-                "delete_many",
+                "upsert",
                 DurableFunctionType::WriteRemote,
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::delete_many(index.clone(), ids.clone()).map(|_| NoOutput)
+                    Impl::upsert(index.clone(), doc.clone(), consistency)
+                        .map(|task_id| WriteOutput { task_id })
                 });
                 durability
-                    .persist(DeleteManyInput { index, ids }, result)
-                    .map(|_: NoOutput| ())
+                    .persist(
+                        UpsertInput {
+                            index,
+                            doc,
+                            consistency,
+                        },
+                        result,
+                    )
+                    .map(|result| result.task_id)
             } else {
-                durability.replay().map(|_: NoOutput| ())
+                durability
+                    .replay()
+                    .map(|result: WriteOutput| result.task_id)
             }
         }
 
+        fn upsert_many(
+            index: IndexName,
+            docs: Vec<Doc>,
+            consistency: WriteConsistency,
+        ) -> Result<BulkResult, SearchError> {
+            init_logging();
+
+            // Each chunk is a separate durable call, so that a recovered worker only writes the
+            // chunks that were not persisted yet
+            let chunks = doc_chunks(docs, &Impl::bulk_limits());
+            write_chunks(
+                chunks,
+                |doc| doc.id.as_str(),
+                |docs| {
+                    let durability = Durability::<BulkResult, SearchError>::new(
+                        "golem_search",
+                        "upsert_many",
+                        DurableFunctionType::WriteRemote,
+                    );
+                    if durability.is_live() {
+                        let result =
+                            with_persistence_level(PersistenceLevel::PersistNothing, || {
+                                Impl::upsert_many(index.clone(), docs.clone(), consistency)
+                            });
+                        durability.persist(
+                            UpsertManyInput {
+                                index: index.clone(),
+                                docs,
+                                consistency,
+                            },
+                            result,
+                        )
+                    } else {
+                        durability.replay()
+                    }
+                },
+            )
+        }
+
+        fn delete(
+            index: IndexName,
+            id: DocumentId,
+            consistency: WriteConsistency,
+        ) -> Result<Option<TaskId>, SearchError> {
+            init_logging();
+
+            let durability = Durability::<WriteOutput, SearchError>::new(
+                "golem_search",
+                "delete",
+                DurableFunctionType::WriteRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    Impl::delete(index.clone(), id.clone(), consistency)
+                        .map(|task_id| WriteOutput { task_id })
+                });
+                durability
+                    .persist(
+                        DeleteInput {
+                            index,
+                            id,
+                            consistency,
+                        },
+                        result,
+                    )
+                    .map(|result| result.task_id)
+            } else {
+                durability
+                    .replay()
+                    .map(|result: WriteOutput| result.task_id)
+            }
+        }
+
+        fn delete_many(
+            index: IndexName,
+            ids: Vec<DocumentId>,
+            consistency: WriteConsistency,
+        ) -> Result<BulkResult, SearchError> {
+            init_logging();
+
+            let chunks = id_chunks(ids, &Impl::bulk_limits());
+            write_chunks(
+                chunks,
+                |id| id.as_str(),
+                |ids| {
+                    let durability = Durability::<BulkResult, SearchError>::new(
+                        "golem_search",
+                        "delete_many",
+                        DurableFunctionType::WriteRemote,
+                    );
+                    if durability.is_live() {
+                        let result =
+                            with_persistence_level(PersistenceLevel::PersistNothing, || {
+                                Impl::delete_many(index.clone(), ids.clone(), consistency)
+                            });
+                        durability.persist(
+                            DeleteManyInput {
+                                index: index.clone(),
+                                ids,
+                                consistency,
+                            },
+                            result,
+                        )
+                    } else {
+                        durability.replay()
+                    }
+                },
+            )
+        }
+
         fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
             init_logging();
 
@@ -462,23 +828,98 @@ mod durable_impl {
             }
         }
 
-        fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
+        fn plan_schema_change(
+            index: IndexName,
+            schema: Schema,
+        ) -> Result<Vec<SchemaChange>, SearchError> {
             init_logging();
 
-            let durability = Durability::<NoOutput, SearchError>::new(
+            let durability = Durability::<PlanSchemaChangeOutput, SearchError>::new(
+                "golem_search",
+                "plan_schema_change",
+                DurableFunctionType::ReadRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    Impl::plan_schema_change(index.clone(), schema.clone())
+                        .map(|changes| PlanSchemaChangeOutput { changes })
+                });
+                durability
+                    .persist(PlanSchemaChangeInput { index, schema }, result)
+                    .map(|plan| plan.changes)
+            } else {
+                durability
+                    .replay()
+                    .map(|plan: PlanSchemaChangeOutput| plan.changes)
+            }
+        }
+
+        /// An update applied by reindexing is not persisted as a whole: it is composed of the
+        /// durable schema plan, index creation and reindex of `DurableSearch`
+        fn update_schema(
+            index: IndexName,
+            schema: Schema,
+            consistency: WriteConsistency,
+            mode: SchemaUpdateMode,
+        ) -> Result<Option<TaskId>, SearchError> {
+            init_logging();
+
+            if let SchemaUpdateMode::Reindex(reindex) = mode {
+                return reindex_to_schema::<Self>(index, schema, reindex);
+            }
+
+            let durability = Durability::<WriteOutput, SearchError>::new(
                 "golem_search",
                 "update_schema",
                 DurableFunctionType::WriteRemote,
             );
             if durability.is_live() {
                 let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
-                    Impl::update_schema(index.clone(), schema.clone()).map(|()| NoOutput)
+                    Impl::update_schema(
+                        index.clone(),
+                        schema.clone(),
+                        consistency,
+                        SchemaUpdateMode::InPlace,
+                    )
+                    .map(|task_id| WriteOutput { task_id })
                 });
                 durability
-                    .persist(UpdateSchemaInput { index, schema }, result)
-                    .map(|_: NoOutput| ())
+                    .persist(
+                        UpdateSchemaInput {
+                            index,
+                            schema,
+                            consistency,
+                        },
+                        result,
+                    )
+                    .map(|result| result.task_id)
             } else {
-                durability.replay().map(|_: NoOutput| ())
+                durability
+                    .replay()
+                    .map(|result: WriteOutput| result.task_id)
+            }
+        }
+
+        fn task_status(index: IndexName, task_id: TaskId) -> Result<TaskStatus, SearchError> {
+            init_logging();
+
+            let durability = Durability::<TaskStatusOutput, SearchError>::new(
+                "golem_search",
+                "task_status",
+                DurableFunctionType::ReadRemote,
+            );
+            if durability.is_live() {
+                let result = with_persistence_level(PersistenceLevel::PersistNothing, || {
+                    Impl::task_status(index.clone(), task_id.clone())
+                        .map(|status| TaskStatusOutput { status })
+                });
+                durability
+                    .persist(TaskStatusInput { index, task_id }, result)
+                    .map(|result| result.status)
+            } else {
+                durability
+                    .replay()
+                    .map(|result: TaskStatusOutput| result.status)
             }
         }
     }
@@ -492,9 +933,11 @@ mod durable_impl {
     /// to be able to reattach them to the new live stream when the switch to live mode
     /// happens.
     ///
-    /// When reaching the end of the replay mode, if the replayed stream was not finished yet,
-    /// the retry query implemented in `ExtendedGuest` is used to create a new Search response
-    /// stream and continue the search seamlessly.
+    /// Each returned page is persisted together with the provider's stream cursor. When reaching
+    /// the end of the replay mode, if the replayed stream was not finished yet, a new Search
+    /// response stream is resumed from the last persisted cursor, continuing the search
+    /// seamlessly. The retry query implemented in `ExtendedGuest` is only used if no cursor
+    /// was persisted.
     enum DurableSearchStreamState<Impl: ExtendedGuest> {
         Live {
             stream: Impl::SearchStream,
@@ -505,6 +948,7 @@ mod durable_impl {
             query: Box<SearchQuery>,
             pollables: Vec<LazyInitializedPollable>,
             partial_result: Vec<SearchHit>,
+            cursor: Option<String>,
             finished: bool,
         },
     }
@@ -532,6 +976,7 @@ mod durable_impl {
                     query: Box::new(query),
                     pollables: Vec::new(),
                     partial_result: Vec::new(),
+                    cursor: None,
                     finished: false,
                 })),
                 subscription: RefCell::new(None),
@@ -576,52 +1021,79 @@ mod durable_impl {
         }
     }
 
-    impl<Impl: ExtendedGuest> GuestSearchStream for DurableSearchStream<Impl> {
-        fn get_next(&self) -> Option<Vec<SearchHit>> {
-            let durability = Durability::<Option<Vec<SearchHit>>, UnusedError>::new(
+    impl<Impl: ExtendedGuest> DurableSearchStream<Impl> {
+        /// Returns the next page of hits, an empty page once all the hits have been returned, or
+        /// the error which ended the stream
+        pub(crate) fn next_page(&self) -> Result<Vec<SearchHit>, SearchError> {
+            self.read(|stream| Impl::next_page(stream).map(Some))
+                .map(Option::unwrap_or_default)
+        }
+
+        /// Reads the next page with `read_live` in live mode, and persists it together with the
+        /// provider's stream cursor, or the error which ended the stream
+        fn read(
+            &self,
+            read_live: impl Fn(&Impl::SearchStream) -> Result<Option<Vec<SearchHit>>, SearchError>,
+        ) -> Result<Option<Vec<SearchHit>>, SearchError> {
+            let durability = Durability::<GetNextOutput, UnusedError>::new(
                 "golem_search",
                 "get_next",
                 DurableFunctionType::ReadRemote,
             );
-            if durability.is_live() {
+            let output = if durability.is_live() {
                 let mut state = self.state.borrow_mut();
-                let (result, new_live_stream) = match &*state {
+                let (output, new_live_stream) = match &*state {
                     Some(DurableSearchStreamState::Live { stream, .. }) => {
-                        let result =
+                        let output =
                             with_persistence_level(PersistenceLevel::PersistNothing, || {
-                                stream.get_next()
+                                GetNextOutput::new(read_live(stream), Impl::stream_cursor(stream))
                             });
-                        (durability.persist_infallible(NoInput, result.clone()), None)
+                        (durability.persist_infallible(NoInput, output), None)
                     }
                     Some(DurableSearchStreamState::Replay {
                         index,
                         query,
                         pollables,
                         partial_result,
+                        cursor,
                         finished,
                     }) => {
                         if *finished {
-                            (None, None)
+                            let output = GetNextOutput {
+                                hits: Some(vec![]),
+                                error: None,
+                                cursor: cursor.clone(),
+                            };
+                            (durability.persist_infallible(NoInput, output), None)
                         } else {
-                            let extended_query = Impl::retry_query(query, partial_result);
-
-                            let (stream, first_live_result) =
+                            let (stream, first_live_output) =
                                 with_persistence_level(PersistenceLevel::PersistNothing, || {
-                                    let stream = <Impl as ExtendedGuest>::unwrapped_stream(
-                                        index.clone(),
-                                        extended_query,
-                                    );
+                                    let stream = match cursor {
+                                        Some(cursor) => Impl::resumed_stream(
+                                            index.clone(),
+                                            (**query).clone(),
+                                            cursor.clone(),
+                                        ),
+                                        None => <Impl as ExtendedGuest>::unwrapped_stream(
+                                            index.clone(),
+                                            Impl::retry_query(query, partial_result),
+                                        ),
+                                    };
 
                                     for lazy_initialized_pollable in pollables {
                                         lazy_initialized_pollable.set(Impl::subscribe(&stream));
                                     }
 
-                                    let next = stream.get_next();
-                                    (stream, next)
+                                    let output = GetNextOutput::new(
+                                        read_live(&stream),
+                                        Impl::stream_cursor(&stream),
+                                    );
+                                    (stream, output)
                                 });
-                            durability.persist_infallible(NoInput, first_live_result.clone());
+                            let first_live_output =
+                                durability.persist_infallible(NoInput, first_live_output);
 
-                            (first_live_result, Some(stream))
+                            (first_live_output, Some(stream))
                         }
                     }
                     None => {
@@ -640,9 +1112,9 @@ mod durable_impl {
                     *state = Some(DurableSearchStreamState::Live { stream, pollables });
                 }
 
-                result
+                output
             } else {
-                let result: Option<Vec<SearchHit>> = durability.replay_infallible();
+                let output: GetNextOutput = durability.replay_infallible();
                 let mut state = self.state.borrow_mut();
                 match &mut *state {
                     Some(DurableSearchStreamState::Live { .. }) => {
@@ -650,22 +1122,49 @@ mod durable_impl {
                     }
                     Some(DurableSearchStreamState::Replay {
                         partial_result,
+                        cursor,
                         finished,
                         ..
                     }) => {
-                        if let Some(ref result) = result {
-                            partial_result.extend_from_slice(result);
-                        } else {
-                            *finished = true;
+                        // The streams return an empty page once they reached their end, and
+                        // they end with the first error
+                        match &output.hits {
+                            Some(hits) if !hits.is_empty() => {
+                                partial_result.extend_from_slice(hits);
+                            }
+                            Some(_) => {
+                                *finished = true;
+                            }
+                            None => {
+                                if output.error.is_some() {
+                                    *finished = true;
+                                }
+                            }
+                        }
+                        if output.cursor.is_some() {
+                            *cursor = output.cursor.clone();
                         }
                     }
                     None => {
                         unreachable!()
                     }
                 }
-                result
+                output
+            };
+
+            match output.error {
+                Some(error) => Err(error),
+                None => Ok(output.hits),
             }
         }
+    }
+
+    impl<Impl: ExtendedGuest> GuestSearchStream for DurableSearchStream<Impl> {
+        fn get_next(&self) -> Option<Vec<SearchHit>> {
+            // The WIT stream has no way to report the error, it ends with an empty page instead
+            self.read(|stream| Ok(stream.get_next()))
+                .unwrap_or_else(|_| Some(vec![]))
+        }
 
         fn blocking_get_next(&self) -> Vec<SearchHit> {
             let mut subscription = self.subscription.borrow_mut();
@@ -733,6 +1232,9 @@ mod durable_impl {
                 pre_tag: Some("<mark>".to_string()),
                 post_tag: Some("</mark>".to_string()),
                 max_length: Some(150),
+                snippet_fields: vec![],
+                snippet_length: None,
+                max_snippets: None,
             };
             roundtrip_test(config);
 
@@ -742,6 +1244,9 @@ mod durable_impl {
                 pre_tag: None,
                 post_tag: None,
                 max_length: None,
+                snippet_fields: vec![],
+                snippet_length: None,
+                max_snippets: None,
             };
             roundtrip_test(minimal_config);
         }
@@ -777,8 +1282,32 @@ mod durable_impl {
             let query = SearchQuery {
                 q: Some("rust programming language".to_string()),
                 filters: vec!["category:programming".to_string(), "lang:en".to_string()],
+                filter: Some(FilterExpression {
+                    nodes: vec![
+                        FilterNode::And(vec![1, 2]),
+                        FilterNode::Range((
+                            "year".to_string(),
+                            RangeBounds {
+                                gt: None,
+                                gte: Some(FilterValue::Integer(2015)),
+                                lt: Some(FilterValue::Integer(2025)),
+                                lte: None,
+                            },
+                        )),
+                        FilterNode::GeoRadius(GeoRadius {
+                            field: "location".to_string(),
+                            center: GeoPoint {
+                                lat: 52.52,
+                                lon: 13.405,
+                            },
+                            radius_meters: 5000.0,
+                        }),
+                    ],
+                }),
+                vector: None,
                 sort: vec!["score:desc".to_string(), "date:asc".to_string()],
                 facets: vec!["category".to_string(), "author".to_string()],
+                facet_requests: vec![],
                 page: Some(2),
                 per_page: Some(20),
                 offset: Some(40),
@@ -787,6 +1316,9 @@ mod durable_impl {
                     pre_tag: Some("<em>".to_string()),
                     post_tag: Some("</em>".to_string()),
                     max_length: Some(200),
+                    snippet_fields: vec![],
+                    snippet_length: None,
+                    max_snippets: None,
                 }),
                 config: Some(SearchConfig {
                     timeout_ms: Some(3000),
@@ -804,8 +1336,11 @@ mod durable_impl {
             let minimal_query = SearchQuery {
                 q: None,
                 filters: vec![],
+                filter: None,
+                vector: None,
                 sort: vec![],
                 facets: vec![],
+                facet_requests: vec![],
                 page: None,
                 per_page: None,
                 offset: None,
@@ -824,6 +1359,7 @@ mod durable_impl {
                     r#"{"title": "Rust Programming", "content": "A guide to Rust"}"#.to_string(),
                 ),
                 highlights: Some(r#"{"title": ["<em>Rust</em> Programming"]}"#.to_string()),
+                highlight_fields: vec![],
             };
             roundtrip_test(hit);
 
@@ -833,6 +1369,7 @@ mod durable_impl {
                 score: None,
                 content: None,
                 highlights: None,
+                highlight_fields: vec![],
             };
             roundtrip_test(minimal_hit);
         }
@@ -849,15 +1386,53 @@ mod durable_impl {
                         score: Some(0.98),
                         content: Some(r#"{"title": "First Document"}"#.to_string()),
                         highlights: None,
+                        highlight_fields: vec![],
                     },
                     SearchHit {
                         id: "doc-2".to_string(),
                         score: Some(0.85),
                         content: Some(r#"{"title": "Second Document"}"#.to_string()),
                         highlights: Some(r#"{"title": ["<em>Second</em> Document"]}"#.to_string()),
+                        highlight_fields: vec![],
                     },
                 ],
                 facets: Some(r#"{"category": {"programming": 50, "tutorial": 25}}"#.to_string()),
+                facet_results: vec![
+                    FacetResult {
+                        field: "category".to_string(),
+                        buckets: vec![
+                            FacetBucket {
+                                value: "programming".to_string(),
+                                count: 50,
+                                range: None,
+                            },
+                            FacetBucket {
+                                value: "tutorial".to_string(),
+                                count: 25,
+                                range: None,
+                            },
+                        ],
+                        stats: None,
+                    },
+                    FacetResult {
+                        field: "price".to_string(),
+                        buckets: vec![FacetBucket {
+                            value: "*..100".to_string(),
+                            count: 75,
+                            range: Some(FacetRange {
+                                from: None,
+                                to: Some(100.0),
+                            }),
+                        }],
+                        stats: Some(FacetStats {
+                            count: Some(75),
+                            min: Some(4.99),
+                            max: Some(99.0),
+                            avg: Some(31.5),
+                            sum: Some(2362.5),
+                        }),
+                    },
+                ],
                 took_ms: Some(15),
             };
             roundtrip_test(results);
@@ -869,6 +1444,7 @@ mod durable_impl {
                 per_page: None,
                 hits: vec![],
                 facets: None,
+                facet_results: vec![],
                 took_ms: Some(5),
             };
             roundtrip_test(empty_results);
@@ -883,6 +1459,10 @@ mod durable_impl {
             roundtrip_test(FieldType::Boolean);
             roundtrip_test(FieldType::Date);
             roundtrip_test(FieldType::GeoPoint);
+            roundtrip_test(FieldType::GeoShape);
+            roundtrip_test(FieldType::Vector);
+            roundtrip_test(FieldType::Object);
+            roundtrip_test(FieldType::Nested);
         }
 
         #[test]
@@ -894,6 +1474,11 @@ mod durable_impl {
                 facet: false,
                 sort: true,
                 index: true,
+                array: false,
+                stored: true,
+                analyzer: None,
+                locale: None,
+                vector: None,
             };
             roundtrip_test(field);
 
@@ -905,6 +1490,11 @@ mod durable_impl {
                 facet: true,
                 sort: false,
                 index: true,
+                array: false,
+                stored: true,
+                analyzer: None,
+                locale: None,
+                vector: None,
             };
             roundtrip_test(keyword_field);
 
@@ -916,8 +1506,48 @@ mod durable_impl {
                 facet: true,
                 sort: true,
                 index: false,
+                array: false,
+                stored: true,
+                analyzer: None,
+                locale: None,
+                vector: None,
             };
             roundtrip_test(numeric_field);
+
+            // Test vector field
+            let vector_field = SchemaField {
+                name: "embedding".to_string(),
+                field_type: FieldType::Vector,
+                required: false,
+                facet: false,
+                sort: false,
+                index: true,
+                array: false,
+                stored: true,
+                analyzer: None,
+                locale: None,
+                vector: Some(VectorOptions {
+                    dimensions: 384,
+                    similarity: VectorSimilarity::DotProduct,
+                }),
+            };
+            roundtrip_test(vector_field);
+
+            // Test localized array field
+            let localized_field = SchemaField {
+                name: "variants.labels".to_string(),
+                field_type: FieldType::Text,
+                required: false,
+                facet: false,
+                sort: false,
+                index: true,
+                array: true,
+                stored: false,
+                analyzer: Some("french".to_string()),
+                locale: Some("fr".to_string()),
+                vector: None,
+            };
+            roundtrip_test(localized_field);
         }
 
         #[test]
@@ -931,6 +1561,11 @@ mod durable_impl {
                         facet: false,
                         sort: false,
                         index: true,
+                        array: false,
+                        stored: true,
+                        analyzer: None,
+                        locale: None,
+                        vector: None,
                     },
                     SchemaField {
                         name: "title".to_string(),
@@ -939,6 +1574,11 @@ mod durable_impl {
                         facet: false,
                         sort: true,
                         index: true,
+                        array: false,
+                        stored: true,
+                        analyzer: None,
+                        locale: None,
+                        vector: None,
                     },
                     SchemaField {
                         name: "category".to_string(),
@@ -947,6 +1587,11 @@ mod durable_impl {
                         facet: true,
                         sort: false,
                         index: true,
+                        array: false,
+                        stored: true,
+                        analyzer: None,
+                        locale: None,
+                        vector: None,
                     },
                     SchemaField {
                         name: "price".to_string(),
@@ -955,6 +1600,11 @@ mod durable_impl {
                         facet: true,
                         sort: true,
                         index: false,
+                        array: false,
+                        stored: true,
+                        analyzer: None,
+                        locale: None,
+                        vector: None,
                     },
                     SchemaField {
                         name: "published_date".to_string(),
@@ -963,6 +1613,11 @@ mod durable_impl {
                         facet: false,
                         sort: true,
                         index: true,
+                        array: false,
+                        stored: true,
+                        analyzer: None,
+                        locale: None,
+                        vector: None,
                     },
                 ],
                 primary_key: Some("id".to_string()),
@@ -1000,8 +1655,11 @@ mod durable_impl {
             let original_query = SearchQuery {
                 q: Some("test".to_string()),
                 filters: vec![],
+                filter: None,
+                vector: None,
                 sort: vec![],
                 facets: vec![],
+                facet_requests: vec![],
                 page: Some(1),
                 per_page: Some(10),
                 offset: Some(0),
@@ -1020,12 +1678,14 @@ mod durable_impl {
                     score: Some(0.9),
                     content: None,
                     highlights: None,
+                    highlight_fields: vec![],
                 },
                 SearchHit {
                     id: "doc2".to_string(),
                     score: Some(0.8),
                     content: None,
                     highlights: None,
+                    highlight_fields: vec![],
                 },
             ];
 
@@ -1040,6 +1700,102 @@ mod durable_impl {
             assert_eq!(retry_query_offset.offset, Some(22)); // 20 + 2 hits
         }
 
+        #[test]
+        fn bulk_result_roundtrip() {
+            roundtrip_test(BulkResult {
+                items: vec![
+                    BulkItemResult {
+                        id: "doc1".to_string(),
+                        error: None,
+                    },
+                    BulkItemResult {
+                        id: "doc2".to_string(),
+                        error: Some("mapper_parsing_exception: failed to parse".to_string()),
+                    },
+                ],
+                task_ids: vec!["42".to_string()],
+            });
+        }
+
+        #[test]
+        fn alias_and_reindex_roundtrip() {
+            roundtrip_test(IndexAlias {
+                alias: "books".to_string(),
+                index: "books-v2".to_string(),
+            });
+            roundtrip_test(ReindexResult {
+                copied: 41,
+                failures: vec![BulkItemResult {
+                    id: "doc7".to_string(),
+                    error: Some("The source document has no content".to_string()),
+                }],
+                alias_switched: false,
+            });
+        }
+
+        #[test]
+        fn plan_schema_change_roundtrip() {
+            roundtrip_test(PlanSchemaChangeOutput {
+                changes: vec![
+                    SchemaChange {
+                        field: "isbn".to_string(),
+                        kind: SchemaChangeKind::FieldAdded,
+                        application: SchemaChangeApplication::InPlace,
+                        reason: None,
+                    },
+                    SchemaChange {
+                        field: "year".to_string(),
+                        kind: SchemaChangeKind::FieldTypeChanged,
+                        application: SchemaChangeApplication::NeedsReindex,
+                        reason: Some("The type of a mapped field can't change".to_string()),
+                    },
+                ],
+            });
+            roundtrip_test(SchemaUpdateMode::Reindex(SchemaReindex {
+                target: "books-v2".to_string(),
+                alias: Some("books".to_string()),
+            }));
+        }
+
+        #[test]
+        fn write_and_task_status_roundtrip() {
+            roundtrip_test(WriteOutput {
+                task_id: Some("42".to_string()),
+            });
+            roundtrip_test(WriteOutput { task_id: None });
+            roundtrip_test(TaskStatusOutput {
+                status: TaskStatus::Failed("invalid_document_fields: year".to_string()),
+            });
+            roundtrip_test(TaskStatusOutput {
+                status: TaskStatus::Processing,
+            });
+        }
+
+        #[test]
+        fn get_next_output_roundtrip() {
+            roundtrip_test(GetNextOutput {
+                hits: Some(vec![SearchHit {
+                    id: "doc1".to_string(),
+                    score: Some(0.9),
+                    content: None,
+                    highlights: None,
+                    highlight_fields: vec![],
+                }]),
+                error: None,
+                cursor: Some(r#"{"pit_id":"abc","search_after":[1.5,42]}"#.to_string()),
+            });
+            roundtrip_test(GetNextOutput {
+                hits: None,
+                error: None,
+                cursor: None,
+            });
+            roundtrip_test(GetNextOutput {
+                hits: None,
+                error: Some(SearchError::Internal("Point in time expired".to_string())),
+                cursor: None,
+            });
+        }
+
         #[test]
         fn index_name_and_document_id_types() {
             // These are type aliases for strings, but test them anyway
@@ -1060,6 +1816,19 @@ mod durable_impl {
                     "price:[100 TO 500]".to_string(),
                     "availability:true".to_string(),
                 ],
+                filter: None,
+                vector: Some(VectorQuery {
+                    field: "embedding".to_string(),
+                    vector: vec![0.12, -0.5, 0.33, 1.0],
+                    k: 25,
+                    semantic_ratio: Some(0.7),
+                    filter: Some(FilterExpression {
+                        nodes: vec![FilterNode::Eq((
+                            "in_stock".to_string(),
+                            FilterValue::Boolean(true),
+                        ))],
+                    }),
+                }),
                 sort: vec![
                     "price:asc".to_string(),
                     "_score:desc".to_string(),
@@ -1070,6 +1839,31 @@ mod durable_impl {
                     "brand".to_string(),
                     "color".to_string(),
                 ],
+                facet_requests: vec![
+                    FacetRequest::Terms(TermsFacet {
+                        field: "tags".to_string(),
+                        size: Some(25),
+                        order: FacetOrder::Value,
+                    }),
+                    FacetRequest::Range(RangeFacet {
+                        field: "price".to_string(),
+                        ranges: vec![
+                            FacetRange {
+                                from: None,
+                                to: Some(100.0),
+                            },
+                            FacetRange {
+                                from: Some(100.0),
+                                to: None,
+                            },
+                        ],
+                    }),
+                    FacetRequest::DateHistogram(DateHistogramFacet {
+                        field: "date".to_string(),
+                        interval: CalendarInterval::Month,
+                    }),
+                    FacetRequest::Stats("price".to_string()),
+                ],
                 page: Some(5),
                 per_page: Some(50),
                 offset: Some(200),
@@ -1082,6 +1876,9 @@ mod durable_impl {
                     pre_tag: Some("<strong class='highlight'>".to_string()),
                     post_tag: Some("</strong>".to_string()),
                     max_length: Some(300),
+                    snippet_fields: vec![],
+                    snippet_length: None,
+                    max_snippets: None,
                 }),
                 config: Some(SearchConfig {
                     timeout_ms: Some(10000),
@@ -0,0 +1,218 @@
+use crate::error::invalid_query;
+use crate::golem::search::types::{
+    FacetBucket, FacetOrder, FacetRange, FacetRequest, FacetResult, FacetStats, SearchError,
+    SearchQuery, TermsFacet,
+};
+use std::cmp::Ordering;
+
+/// Number of buckets of a terms facet that doesn't set a size
+pub const DEFAULT_FACET_SIZE: u32 = 10;
+
+/// Returns the facets requested by a query: a terms facet with the default options for each of
+/// the `facets` fields, followed by the validated `facet-requests`
+pub fn facet_requests(query: &SearchQuery) -> Result<Vec<FacetRequest>, SearchError> {
+    let mut requests = query
+        .facets
+        .iter()
+        .map(|field| {
+            FacetRequest::Terms(TermsFacet {
+                field: field.clone(),
+                size: None,
+                order: FacetOrder::Count,
+            })
+        })
+        .collect::<Vec<_>>();
+    for request in &query.facet_requests {
+        validate_facet_request(request)?;
+        requests.push(request.clone());
+    }
+    Ok(requests)
+}
+
+pub fn facet_field(request: &FacetRequest) -> &str {
+    match request {
+        FacetRequest::Terms(terms) => &terms.field,
+        FacetRequest::Range(range) => &range.field,
+        FacetRequest::DateHistogram(histogram) => &histogram.field,
+        FacetRequest::Stats(field) => field,
+    }
+}
+
+pub fn facet_kind(request: &FacetRequest) -> &'static str {
+    match request {
+        FacetRequest::Terms(_) => "terms",
+        FacetRequest::Range(_) => "range",
+        FacetRequest::DateHistogram(_) => "date-histogram",
+        FacetRequest::Stats(_) => "stats",
+    }
+}
+
+/// Facet rejected by a provider
+pub fn unsupported_facet(request: &FacetRequest, provider: &str) -> SearchError {
+    invalid_query(format!(
+        "The {} facet on {} is not supported by {provider}",
+        facet_kind(request),
+        facet_field(request)
+    ))
+}
+
+/// Label of a range bucket, such as `10..20` or `*..10`
+pub fn range_label(range: &FacetRange) -> String {
+    let bound = |bound: Option<f64>| bound.map_or_else(|| "*".to_string(), |b| b.to_string());
+    format!("{}..{}", bound(range.from), bound(range.to))
+}
+
+/// Builds the result of a terms facet from the buckets returned by a provider that can't sort
+/// or limit them per facet
+pub fn terms_facet_result(
+    facet: &TermsFacet,
+    mut buckets: Vec<FacetBucket>,
+    stats: Option<FacetStats>,
+) -> FacetResult {
+    match facet.order {
+        FacetOrder::Count => buckets.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| compare_values(&a.value, &b.value))
+        }),
+        FacetOrder::Value => buckets.sort_by(|a, b| compare_values(&a.value, &b.value)),
+    }
+    buckets.truncate(facet.size.unwrap_or(DEFAULT_FACET_SIZE) as usize);
+    FacetResult {
+        field: facet.field.clone(),
+        buckets,
+        stats,
+    }
+}
+
+/// Compares numeric values as numbers, and other values as strings
+fn compare_values(a: &str, b: &str) -> Ordering {
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.total_cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+fn validate_facet_request(request: &FacetRequest) -> Result<(), SearchError> {
+    if facet_field(request).trim().is_empty() {
+        return Err(invalid_query(format!(
+            "The {} facet has an empty field",
+            facet_kind(request)
+        )));
+    }
+    match request {
+        FacetRequest::Terms(terms) if terms.size == Some(0) => Err(invalid_query(format!(
+            "The terms facet on {} must have a positive size",
+            terms.field
+        ))),
+        FacetRequest::Range(range) if range.ranges.is_empty() => Err(invalid_query(format!(
+            "The range facet on {} has no ranges",
+            range.field
+        ))),
+        FacetRequest::Range(range) => {
+            for bounds in &range.ranges {
+                let valid = match (bounds.from, bounds.to) {
+                    (None, None) => false,
+                    (Some(from), Some(to)) => from < to,
+                    _ => true,
+                };
+                if !valid {
+                    return Err(invalid_query(format!(
+                        "The range facet on {} has an invalid range {}",
+                        range.field,
+                        range_label(bounds)
+                    )));
+                }
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golem::search::types::RangeFacet;
+
+    fn bucket(value: &str, count: u64) -> FacetBucket {
+        FacetBucket {
+            value: value.to_string(),
+            count,
+            range: None,
+        }
+    }
+
+    #[test]
+    fn terms_facets_are_sorted_and_truncated() {
+        let buckets = vec![bucket("10", 3), bucket("9", 7), bucket("100", 3)];
+
+        let by_count = terms_facet_result(
+            &TermsFacet {
+                field: "pages".to_string(),
+                size: Some(2),
+                order: FacetOrder::Count,
+            },
+            buckets.clone(),
+            None,
+        );
+        assert_eq!(by_count.buckets, vec![bucket("9", 7), bucket("10", 3)]);
+
+        let by_value = terms_facet_result(
+            &TermsFacet {
+                field: "pages".to_string(),
+                size: None,
+                order: FacetOrder::Value,
+            },
+            buckets,
+            None,
+        );
+        assert_eq!(
+            by_value.buckets,
+            vec![bucket("9", 7), bucket("10", 3), bucket("100", 3)]
+        );
+    }
+
+    #[test]
+    fn invalid_facet_requests_are_rejected() {
+        let query = |request: FacetRequest| SearchQuery {
+            q: None,
+            filters: vec![],
+            filter: None,
+            vector: None,
+            sort: vec![],
+            facets: vec!["genre".to_string()],
+            facet_requests: vec![request],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        };
+
+        let stats = facet_requests(&query(FacetRequest::Stats("price".to_string()))).unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(facet_field(&stats[0]), "genre");
+
+        for request in [
+            FacetRequest::Stats(" ".to_string()),
+            FacetRequest::Terms(TermsFacet {
+                field: "genre".to_string(),
+                size: Some(0),
+                order: FacetOrder::Count,
+            }),
+            FacetRequest::Range(RangeFacet {
+                field: "price".to_string(),
+                ranges: vec![FacetRange {
+                    from: Some(20.0),
+                    to: Some(10.0),
+                }],
+            }),
+        ] {
+            assert!(matches!(
+                facet_requests(&query(request)),
+                Err(SearchError::InvalidQuery(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use crate::error::invalid_query;
+use crate::golem::search::types::{
+    FilterExpression, FilterNode, FilterValue, GeoBoundingBox, GeoRadius, RangeBounds, SearchError,
+};
+
+/// A `filter-expression` resolved into a tree, ready to be compiled to the native syntax of a
+/// provider
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    Eq(String, FilterValue),
+    Ne(String, FilterValue),
+    Range(String, RangeBounds),
+    In(String, Vec<FilterValue>),
+    Exists(String),
+    Prefix(String, String),
+    GeoRadius(GeoRadius),
+    GeoBoundingBox(GeoBoundingBox),
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    pub fn operator(&self) -> &'static str {
+        match self {
+            Filter::Eq(..) => "eq",
+            Filter::Ne(..) => "ne",
+            Filter::Range(..) => "range",
+            Filter::In(..) => "in",
+            Filter::Exists(..) => "exists",
+            Filter::Prefix(..) => "prefix",
+            Filter::GeoRadius(..) => "geo-radius",
+            Filter::GeoBoundingBox(..) => "geo-bbox",
+            Filter::And(..) => "and",
+            Filter::Or(..) => "or",
+            Filter::Not(..) => "not",
+        }
+    }
+}
+
+/// Resolves the flattened nodes of a filter expression into a tree, validating the node indices
+/// and the operands
+pub fn resolve_filter(expression: &FilterExpression) -> Result<Filter, SearchError> {
+    if expression.nodes.is_empty() {
+        return Err(invalid_query("The filter expression has no nodes"));
+    }
+    check_single_parents(&expression.nodes)?;
+    resolve_node(&expression.nodes, 0)
+}
+
+/// Operator rejected by a provider
+pub fn unsupported_filter(filter: &Filter, provider: &str) -> SearchError {
+    invalid_query(format!(
+        "The {} filter operator is not supported by {provider}",
+        filter.operator()
+    ))
+}
+
+/// Rejects the nodes referred to by more than one parent. The nodes must form a tree, as a
+/// shared node is resolved once per reference, which grows exponentially with the depth.
+fn check_single_parents(nodes: &[FilterNode]) -> Result<(), SearchError> {
+    let mut referenced = vec![false; nodes.len()];
+    for (index, node) in nodes.iter().enumerate() {
+        let children = match node {
+            FilterNode::And(children) | FilterNode::Or(children) => children.as_slice(),
+            FilterNode::Not(operand) => std::slice::from_ref(operand),
+            _ => &[],
+        };
+        for child in children {
+            // Out of range children are rejected when resolving the nodes
+            if let Some(referenced) = referenced.get_mut(*child as usize) {
+                if *referenced {
+                    return Err(invalid_query(format!(
+                        "Filter node {index} refers to node {child}, which already has a parent"
+                    )));
+                }
+                *referenced = true;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn resolve_node(nodes: &[FilterNode], index: usize) -> Result<Filter, SearchError> {
+    let child = |child: u32| {
+        let child = child as usize;
+        if child <= index || child >= nodes.len() {
+            Err(invalid_query(format!(
+                "Filter node {index} refers to node {child}, children must come after their parent"
+            )))
+        } else {
+            resolve_node(nodes, child)
+        }
+    };
+    let check_field = |field: &str| {
+        if field.trim().is_empty() {
+            Err(invalid_query(format!(
+                "Filter node {index} has an empty field"
+            )))
+        } else {
+            Ok(())
+        }
+    };
+
+    match &nodes[index] {
+        FilterNode::Eq((field, value)) => {
+            check_field(field)?;
+            Ok(Filter::Eq(field.clone(), value.clone()))
+        }
+        FilterNode::Ne((field, value)) => {
+            check_field(field)?;
+            Ok(Filter::Ne(field.clone(), value.clone()))
+        }
+        FilterNode::Range((field, bounds)) => {
+            check_field(field)?;
+            if bounds.gt.is_none()
+                && bounds.gte.is_none()
+                && bounds.lt.is_none()
+                && bounds.lte.is_none()
+            {
+                return Err(invalid_query(format!(
+                    "The range filter on {field} has no bounds"
+                )));
+            }
+            if bounds.gt.is_some() && bounds.gte.is_some()
+                || bounds.lt.is_some() && bounds.lte.is_some()
+            {
+                return Err(invalid_query(format!(
+                    "The range filter on {field} has both an exclusive and an inclusive bound on the same side"
+                )));
+            }
+            Ok(Filter::Range(field.clone(), bounds.clone()))
+        }
+        FilterNode::In((field, values)) => {
+            check_field(field)?;
+            if values.is_empty() {
+                return Err(invalid_query(format!(
+                    "The in filter on {field} has no values"
+                )));
+            }
+            Ok(Filter::In(field.clone(), values.clone()))
+        }
+        FilterNode::Exists(field) => {
+            check_field(field)?;
+            Ok(Filter::Exists(field.clone()))
+        }
+        FilterNode::Prefix((field, prefix)) => {
+            check_field(field)?;
+            Ok(Filter::Prefix(field.clone(), prefix.clone()))
+        }
+        FilterNode::GeoRadius(radius) => {
+            check_field(&radius.field)?;
+            if radius.radius_meters <= 0.0 {
+                return Err(invalid_query(format!(
+                    "The geo-radius filter on {} must have a positive radius",
+                    radius.field
+                )));
+            }
+            Ok(Filter::GeoRadius(radius.clone()))
+        }
+        FilterNode::GeoBbox(bbox) => {
+            check_field(&bbox.field)?;
+            if bbox.top_left.lat < bbox.bottom_right.lat {
+                return Err(invalid_query(format!(
+                    "The geo-bbox filter on {} has its top-left corner below its bottom-right corner",
+                    bbox.field
+                )));
+            }
+            Ok(Filter::GeoBoundingBox(bbox.clone()))
+        }
+        FilterNode::And(children) => {
+            if children.is_empty() {
+                return Err(invalid_query(format!(
+                    "Filter node {index} has no operands"
+                )));
+            }
+            Ok(Filter::And(
+                children
+                    .iter()
+                    .map(|c| child(*c))
+                    .collect::<Result<_, _>>()?,
+            ))
+        }
+        FilterNode::Or(children) => {
+            if children.is_empty() {
+                return Err(invalid_query(format!(
+                    "Filter node {index} has no operands"
+                )));
+            }
+            Ok(Filter::Or(
+                children
+                    .iter()
+                    .map(|c| child(*c))
+                    .collect::<Result<_, _>>()?,
+            ))
+        }
+        FilterNode::Not(operand) => Ok(Filter::Not(Box::new(child(*operand)?))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flattened_nodes_are_resolved_into_a_tree() {
+        let expression = FilterExpression {
+            nodes: vec![
+                FilterNode::And(vec![1, 2]),
+                FilterNode::Eq((
+                    "genre".to_string(),
+                    FilterValue::Text("fiction".to_string()),
+                )),
+                FilterNode::Not(3),
+                FilterNode::Exists("deleted_at".to_string()),
+            ],
+        };
+
+        assert_eq!(
+            resolve_filter(&expression).unwrap(),
+            Filter::And(vec![
+                Filter::Eq(
+                    "genre".to_string(),
+                    FilterValue::Text("fiction".to_string())
+                ),
+                Filter::Not(Box::new(Filter::Exists("deleted_at".to_string()))),
+            ])
+        );
+    }
+
+    #[test]
+    fn invalid_expressions_are_rejected() {
+        let cyclic = FilterExpression {
+            nodes: vec![FilterNode::Not(0)],
+        };
+        let out_of_bounds = FilterExpression {
+            nodes: vec![
+                FilterNode::Or(vec![1, 5]),
+                FilterNode::Exists("a".to_string()),
+            ],
+        };
+        let unbounded = FilterExpression {
+            nodes: vec![FilterNode::Range((
+                "year".to_string(),
+                RangeBounds {
+                    gt: None,
+                    gte: None,
+                    lt: None,
+                    lte: None,
+                },
+            ))],
+        };
+
+        let shared_child = FilterExpression {
+            nodes: vec![
+                FilterNode::And(vec![1, 2]),
+                FilterNode::And(vec![2, 3]),
+                FilterNode::And(vec![3, 4]),
+                FilterNode::Exists("a".to_string()),
+                FilterNode::Exists("b".to_string()),
+            ],
+        };
+
+        for expression in [
+            FilterExpression { nodes: vec![] },
+            shared_child,
+            cyclic,
+            out_of_bounds,
+            unbounded,
+        ] {
+            assert!(matches!(
+                resolve_filter(&expression),
+                Err(SearchError::InvalidQuery(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use crate::error::invalid_query;
+use crate::golem::search::types::{
+    FieldHighlight, HighlightConfig, HighlightFragment, HighlightMatch, SearchError,
+};
+
+/// Tags wrapping the matched terms when the highlight config doesn't set them
+pub const DEFAULT_PRE_TAG: &str = "<em>";
+pub const DEFAULT_POST_TAG: &str = "</em>";
+
+/// Number of words of a snippet when the highlight config doesn't set it
+pub const DEFAULT_SNIPPET_LENGTH: u32 = 10;
+
+pub fn validate_highlight_config(config: &HighlightConfig) -> Result<(), SearchError> {
+    if config.pre_tag.as_deref() == Some("") || config.post_tag.as_deref() == Some("") {
+        return Err(invalid_query("The highlight tags can't be empty"));
+    }
+    if config.snippet_length == Some(0) {
+        return Err(invalid_query("The snippet length must be positive"));
+    }
+    if config.max_snippets == Some(0) {
+        return Err(invalid_query(
+            "The maximum number of snippets must be positive",
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the tags wrapping the matched terms. They are always sent to the providers, so that
+/// the highlighted values can be parsed back into fragments.
+pub fn highlight_tags(config: &HighlightConfig) -> (&str, &str) {
+    (
+        config.pre_tag.as_deref().unwrap_or(DEFAULT_PRE_TAG),
+        config.post_tag.as_deref().unwrap_or(DEFAULT_POST_TAG),
+    )
+}
+
+/// Builds the highlight of a field from the highlighted values returned by a provider
+pub fn field_highlight(
+    field: impl Into<String>,
+    values: &[impl AsRef<str>],
+    config: &HighlightConfig,
+) -> FieldHighlight {
+    let (pre_tag, post_tag) = highlight_tags(config);
+    FieldHighlight {
+        field: field.into(),
+        fragments: values
+            .iter()
+            .map(|value| highlight_fragment(value.as_ref(), pre_tag, post_tag))
+            .collect(),
+    }
+}
+
+/// Strips the tags of a highlighted value, and records the positions of the terms they wrapped
+pub fn highlight_fragment(highlighted: &str, pre_tag: &str, post_tag: &str) -> HighlightFragment {
+    let mut text = String::with_capacity(highlighted.len());
+    let mut matches = Vec::new();
+    let mut rest = highlighted;
+
+    while let Some(start) = rest.find(pre_tag) {
+        text.push_str(&rest[..start]);
+        let after_tag = &rest[start + pre_tag.len()..];
+        let Some(end) = after_tag.find(post_tag) else {
+            // An unclosed tag isn't a match
+            rest = after_tag;
+            break;
+        };
+        let term = &after_tag[..end];
+        if !term.is_empty() {
+            matches.push(HighlightMatch {
+                start: text.len() as u32,
+                end: (text.len() + term.len()) as u32,
+            });
+        }
+        text.push_str(term);
+        rest = &after_tag[end + post_tag.len()..];
+    }
+    text.push_str(rest);
+
+    HighlightFragment { text, matches }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> HighlightConfig {
+        HighlightConfig {
+            fields: vec!["title".to_string()],
+            pre_tag: Some("<mark>".to_string()),
+            post_tag: Some("</mark>".to_string()),
+            max_length: None,
+            snippet_fields: vec![],
+            snippet_length: None,
+            max_snippets: None,
+        }
+    }
+
+    #[test]
+    fn highlighted_values_are_parsed_into_fragments() {
+        let highlight = field_highlight(
+            "title",
+            &["The <mark>Great</mark> <mark>Gatsby</mark>", "Gatsby"],
+            &config(),
+        );
+
+        assert_eq!(highlight.field, "title");
+        let fragment = &highlight.fragments[0];
+        assert_eq!(fragment.text, "The Great Gatsby");
+        assert_eq!(
+            fragment.matches,
+            vec![
+                HighlightMatch { start: 4, end: 9 },
+                HighlightMatch { start: 10, end: 16 }
+            ]
+        );
+        assert_eq!(&fragment.text[4..9], "Great");
+        assert!(highlight.fragments[1].matches.is_empty());
+
+        let unclosed = highlight_fragment("café <em>noir", "<em>", "</em>");
+        assert_eq!(unclosed.text, "café noir");
+        assert!(unclosed.matches.is_empty());
+    }
+
+    #[test]
+    fn invalid_highlight_configs_are_rejected() {
+        assert!(validate_highlight_config(&config()).is_ok());
+        assert_eq!(
+            highlight_tags(&HighlightConfig {
+                pre_tag: None,
+                post_tag: None,
+                ..config()
+            }),
+            (DEFAULT_PRE_TAG, DEFAULT_POST_TAG)
+        );
+
+        for invalid in [
+            HighlightConfig {
+                pre_tag: Some(String::new()),
+                ..config()
+            },
+            HighlightConfig {
+                snippet_length: Some(0),
+                ..config()
+            },
+            HighlightConfig {
+                max_snippets: Some(0),
+                ..config()
+            },
+        ] {
+            assert!(matches!(
+                validate_highlight_config(&invalid),
+                Err(SearchError::InvalidQuery(_))
+            ));
+        }
+    }
+}
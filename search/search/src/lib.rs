@@ -1,6 +1,13 @@
+pub mod bulk;
 pub mod config;
 pub mod durability;
 pub mod error;
+pub mod facets;
+pub mod filter;
+pub mod highlights;
+pub mod reindex;
+pub mod schema;
+pub mod vector;
 
 wit_bindgen::generate!({
     path: "../wit",
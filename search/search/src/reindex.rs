@@ -0,0 +1,269 @@
+use crate::golem::search::core::Guest;
+use crate::golem::search::types::{
+    BulkItemResult, BulkResult, Doc, IndexName, ReindexResult, ReindexTransform, SearchError,
+    SearchHit, SearchQuery, WriteConsistency,
+};
+use serde_json::Value;
+
+/// Copies the documents of the source index to the target index with the operations of `G`,
+/// then switches the alias to the target index if every document was copied.
+///
+/// The pages of the source index are read with `next_page`, which reports a failure to read a
+/// page separately from the end of the results. The reindex fails on the first such failure,
+/// without switching the alias.
+///
+/// Implemented with `DurableSearch`, the pages read from the source index, the chunks written to
+/// the target index and the switch of the alias are each persisted, so that a recovered worker
+/// continues the reindex where it stopped.
+pub fn reindex<G: Guest>(
+    source: IndexName,
+    target: IndexName,
+    alias: Option<IndexName>,
+    transform: Option<ReindexTransform>,
+    next_page: impl Fn(&G::SearchStream) -> Result<Vec<SearchHit>, SearchError>,
+) -> Result<ReindexResult, SearchError> {
+    let stream = G::stream_search(source, match_all_query())?;
+    // The alias must only be switched once the copied documents are visible
+    let (copied, failures) = copy_documents(
+        || next_page(stream.get::<G::SearchStream>()),
+        |docs| G::upsert_many(target.clone(), docs, WriteConsistency::WaitForVisible),
+        transform.as_ref(),
+    )?;
+
+    let alias_switched = match alias {
+        Some(alias) if failures.is_empty() => {
+            G::swap_alias(alias, target)?;
+            true
+        }
+        _ => false,
+    };
+
+    Ok(ReindexResult {
+        copied,
+        failures,
+        alias_switched,
+    })
+}
+
+/// Writes the pages returned by `next_page` with `write` until reaching an empty page, and
+/// returns the number of copied documents and the documents which couldn't be copied
+fn copy_documents(
+    mut next_page: impl FnMut() -> Result<Vec<SearchHit>, SearchError>,
+    mut write: impl FnMut(Vec<Doc>) -> Result<BulkResult, SearchError>,
+    transform: Option<&ReindexTransform>,
+) -> Result<(u64, Vec<BulkItemResult>), SearchError> {
+    let mut copied = 0;
+    let mut failures = Vec::new();
+
+    loop {
+        let hits = next_page()?;
+        if hits.is_empty() {
+            break;
+        }
+
+        let mut docs = Vec::with_capacity(hits.len());
+        for hit in hits {
+            let id = hit.id.clone();
+            match hit_to_doc(hit, transform) {
+                Ok(doc) => docs.push(doc),
+                Err(reason) => failures.push(BulkItemResult {
+                    id,
+                    error: Some(reason),
+                }),
+            }
+        }
+        if docs.is_empty() {
+            continue;
+        }
+
+        let result = write(docs)?;
+        for item in result.items {
+            if item.error.is_some() {
+                failures.push(item);
+            } else {
+                copied += 1;
+            }
+        }
+    }
+
+    Ok((copied, failures))
+}
+
+fn match_all_query() -> SearchQuery {
+    SearchQuery {
+        q: None,
+        filters: vec![],
+        filter: None,
+        vector: None,
+        sort: vec![],
+        facets: vec![],
+        facet_requests: vec![],
+        page: None,
+        per_page: None,
+        offset: None,
+        highlight: None,
+        config: None,
+    }
+}
+
+fn hit_to_doc(hit: SearchHit, transform: Option<&ReindexTransform>) -> Result<Doc, String> {
+    let content = hit
+        .content
+        .ok_or_else(|| "The source document has no content".to_string())?;
+
+    let content = match transform {
+        Some(transform) => transform_content(&content, transform)?,
+        None => content,
+    };
+
+    Ok(Doc {
+        id: hit.id,
+        content,
+    })
+}
+
+fn transform_content(content: &str, transform: &ReindexTransform) -> Result<String, String> {
+    let mut fields = match serde_json::from_str::<Value>(content) {
+        Ok(Value::Object(fields)) => fields,
+        Ok(_) => return Err("The source document is not a JSON object".to_string()),
+        Err(e) => return Err(format!("Failed to parse the source document: {e}")),
+    };
+
+    for (from, to) in &transform.rename_fields {
+        if let Some(value) = fields.remove(from) {
+            fields.insert(to.clone(), value);
+        }
+    }
+    for field in &transform.remove_fields {
+        fields.remove(field);
+    }
+
+    Ok(Value::Object(fields).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(id: &str, content: Option<&str>) -> SearchHit {
+        SearchHit {
+            id: id.to_string(),
+            score: None,
+            content: content.map(|content| content.to_string()),
+            highlights: None,
+            highlight_fields: vec![],
+        }
+    }
+
+    #[test]
+    fn documents_are_transformed() {
+        let transform = ReindexTransform {
+            rename_fields: vec![("name".to_string(), "title".to_string())],
+            remove_fields: vec!["legacy".to_string()],
+        };
+
+        let doc = hit_to_doc(
+            hit(
+                "1",
+                Some(r#"{"name": "Dune", "legacy": true, "year": 1965}"#),
+            ),
+            Some(&transform),
+        )
+        .unwrap();
+
+        assert_eq!(doc.id, "1");
+        assert_eq!(
+            serde_json::from_str::<Value>(&doc.content).unwrap(),
+            serde_json::json!({"title": "Dune", "year": 1965})
+        );
+    }
+
+    #[test]
+    fn read_failures_end_the_copy_with_an_error() {
+        let mut pages = vec![
+            Ok(vec![hit("1", Some(r#"{"name": "Dune"}"#))]),
+            Err(SearchError::Internal(
+                "Point in time search failed".to_string(),
+            )),
+            Ok(vec![hit("2", Some(r#"{"name": "Emma"}"#))]),
+            Ok(vec![]),
+        ]
+        .into_iter();
+        let mut written = Vec::new();
+
+        let result = copy_documents(
+            || pages.next().unwrap(),
+            |docs| {
+                let items = docs
+                    .iter()
+                    .map(|doc| BulkItemResult {
+                        id: doc.id.clone(),
+                        error: None,
+                    })
+                    .collect();
+                written.extend(docs);
+                Ok(BulkResult {
+                    items,
+                    task_ids: vec![],
+                })
+            },
+            None,
+        );
+
+        assert_eq!(
+            result,
+            Err(SearchError::Internal(
+                "Point in time search failed".to_string()
+            ))
+        );
+        assert_eq!(written.len(), 1);
+        assert_eq!(written[0].id, "1");
+    }
+
+    #[test]
+    fn documents_are_copied_until_the_end_of_the_stream() {
+        let mut pages = vec![
+            Ok(vec![hit("1", Some(r#"{"name": "Dune"}"#)), hit("2", None)]),
+            Ok(vec![hit("3", Some(r#"{"name": "Emma"}"#))]),
+            Ok(vec![]),
+        ]
+        .into_iter();
+
+        let (copied, failures) = copy_documents(
+            || pages.next().unwrap(),
+            |docs| {
+                Ok(BulkResult {
+                    items: docs
+                        .into_iter()
+                        .map(|doc| BulkItemResult {
+                            id: doc.id,
+                            error: None,
+                        })
+                        .collect(),
+                    task_ids: vec![],
+                })
+            },
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(copied, 2);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].id, "2");
+    }
+
+    #[test]
+    fn documents_without_content_are_not_copied() {
+        assert!(hit_to_doc(hit("1", None), None).is_err());
+        assert_eq!(
+            hit_to_doc(
+                hit("2", Some("[1, 2]")),
+                Some(&ReindexTransform {
+                    rename_fields: vec![],
+                    remove_fields: vec![],
+                })
+            ),
+            Err("The source document is not a JSON object".to_string())
+        );
+    }
+}
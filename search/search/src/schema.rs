@@ -0,0 +1,442 @@
+use crate::error::{internal_error, invalid_query};
+use crate::golem::search::core::Guest;
+use crate::golem::search::types::{
+    FieldType, IndexName, Schema, SchemaChange, SchemaChangeApplication, SchemaChangeKind,
+    SchemaField, SchemaReindex, SearchError, TaskId, WriteConsistency,
+};
+
+/// A difference between the current schema of an index and a new schema, before the provider
+/// classified it
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaDiff {
+    pub kind: SchemaChangeKind,
+    /// Name of the changed field, or the new primary key
+    pub field: String,
+    pub current: Option<SchemaField>,
+    pub target: Option<SchemaField>,
+}
+
+/// How a provider applies a schema difference
+#[derive(Debug, Clone, PartialEq)]
+pub struct Classification {
+    pub application: SchemaChangeApplication,
+    pub reason: Option<String>,
+}
+
+pub fn in_place() -> Classification {
+    Classification {
+        application: SchemaChangeApplication::InPlace,
+        reason: None,
+    }
+}
+
+pub fn needs_reindex(reason: impl Into<String>) -> Classification {
+    Classification {
+        application: SchemaChangeApplication::NeedsReindex,
+        reason: Some(reason.into()),
+    }
+}
+
+pub fn impossible(reason: impl Into<String>) -> Classification {
+    Classification {
+        application: SchemaChangeApplication::Impossible,
+        reason: Some(reason.into()),
+    }
+}
+
+/// Lists the differences between the schemas, in the order of the new fields, followed by the
+/// removed fields and the primary key.
+///
+/// A field changing type is reported once, as its other options change with its type. The
+/// primary key field of the current schema isn't reported as removed, as providers list it
+/// whether or not the new schema does, and the primary key is only reported as changed when both
+/// schemas name one.
+pub fn diff_schemas(current: &Schema, target: &Schema) -> Vec<SchemaDiff> {
+    let mut diffs = Vec::new();
+
+    for field in &target.fields {
+        let existing = current.fields.iter().find(|f| f.name == field.name);
+        let diff = |kind| SchemaDiff {
+            kind,
+            field: field.name.clone(),
+            current: existing.cloned(),
+            target: Some(field.clone()),
+        };
+        let Some(existing) = existing else {
+            diffs.push(diff(SchemaChangeKind::FieldAdded));
+            continue;
+        };
+
+        if existing.field_type != field.field_type {
+            diffs.push(diff(SchemaChangeKind::FieldTypeChanged));
+            continue;
+        }
+        if existing.vector != field.vector {
+            diffs.push(diff(SchemaChangeKind::VectorOptionsChanged));
+        }
+        if options(existing) != options(field) {
+            diffs.push(diff(SchemaChangeKind::FieldOptionsChanged));
+        }
+    }
+
+    for field in &current.fields {
+        if target.fields.iter().all(|f| f.name != field.name)
+            && current.primary_key.as_deref() != Some(field.name.as_str())
+        {
+            diffs.push(SchemaDiff {
+                kind: SchemaChangeKind::FieldRemoved,
+                field: field.name.clone(),
+                current: Some(field.clone()),
+                target: None,
+            });
+        }
+    }
+
+    if let (Some(current_key), Some(target_key)) = (&current.primary_key, &target.primary_key) {
+        if current_key != target_key {
+            diffs.push(SchemaDiff {
+                kind: SchemaChangeKind::PrimaryKeyChanged,
+                field: target_key.clone(),
+                current: None,
+                target: None,
+            });
+        }
+    }
+
+    diffs
+}
+
+type FieldOptions<'a> = (
+    bool,
+    bool,
+    bool,
+    bool,
+    bool,
+    bool,
+    Option<&'a str>,
+    Option<&'a str>,
+);
+
+fn options(field: &SchemaField) -> FieldOptions<'_> {
+    (
+        field.required,
+        field.facet,
+        field.sort,
+        field.index,
+        field.array,
+        field.stored,
+        field.analyzer.as_deref(),
+        field.locale.as_deref(),
+    )
+}
+
+/// Checks the options of a field that only apply to some field types
+pub fn validate_field(field: &SchemaField) -> Result<(), SearchError> {
+    if (field.analyzer.is_some() || field.locale.is_some()) && field.field_type != FieldType::Text {
+        return Err(invalid_query(format!(
+            "Only text fields have an analyzer or locale, {} is a {:?} field",
+            field.name, field.field_type
+        )));
+    }
+    if field.array && matches!(field.field_type, FieldType::Vector | FieldType::Nested) {
+        return Err(invalid_query(format!(
+            "{} is a {:?} field, which can't be an array",
+            field.name, field.field_type
+        )));
+    }
+    Ok(())
+}
+
+/// Name of the Elasticsearch and OpenSearch language analyzer of a locale
+pub fn language_analyzer(locale: &str) -> Option<&'static str> {
+    let analyzer = match locale.to_lowercase().as_str() {
+        "pt-br" => "brazilian",
+        locale => match locale.split(['-', '_']).next().unwrap_or_default() {
+            "ar" => "arabic",
+            "hy" => "armenian",
+            "eu" => "basque",
+            "bn" => "bengali",
+            "bg" => "bulgarian",
+            "ca" => "catalan",
+            "cs" => "czech",
+            "da" => "danish",
+            "nl" => "dutch",
+            "en" => "english",
+            "et" => "estonian",
+            "fi" => "finnish",
+            "fr" => "french",
+            "gl" => "galician",
+            "de" => "german",
+            "el" => "greek",
+            "hi" => "hindi",
+            "hu" => "hungarian",
+            "id" => "indonesian",
+            "ga" => "irish",
+            "it" => "italian",
+            "lv" => "latvian",
+            "lt" => "lithuanian",
+            "no" | "nb" => "norwegian",
+            "fa" => "persian",
+            "pt" => "portuguese",
+            "ro" => "romanian",
+            "ru" => "russian",
+            "ckb" => "sorani",
+            "es" => "spanish",
+            "sv" => "swedish",
+            "tr" => "turkish",
+            "th" => "thai",
+            _ => return None,
+        },
+    };
+    Some(analyzer)
+}
+
+/// Diffs the schemas and classifies each difference with the rules of the provider
+pub fn plan_schema_change(
+    current: &Schema,
+    target: &Schema,
+    classify: impl Fn(&SchemaDiff) -> Classification,
+) -> Vec<SchemaChange> {
+    diff_schemas(current, target)
+        .into_iter()
+        .map(|diff| {
+            let classification = classify(&diff);
+            SchemaChange {
+                field: diff.field,
+                kind: diff.kind,
+                application: classification.application,
+                reason: classification.reason,
+            }
+        })
+        .collect()
+}
+
+/// Refuses an in-place update if any change can't be applied in place. An index that doesn't
+/// exist yet has no schema to migrate from, so that providers creating indexes on their first
+/// update keep doing so.
+pub fn check_in_place(changes: Result<Vec<SchemaChange>, SearchError>) -> Result<(), SearchError> {
+    let changes = match changes {
+        Ok(changes) => changes,
+        Err(SearchError::IndexNotFound) => return Ok(()),
+        Err(e) => return Err(e),
+    };
+
+    refuse(
+        &changes,
+        |application| application != SchemaChangeApplication::InPlace,
+        "can't be applied in place, update the schema with a reindex",
+    )
+}
+
+/// Applies the new schema by creating the target index with it, and reindexing the documents of
+/// the index into it with the operations of `G`. Impossible changes are refused before the target
+/// index is created.
+pub fn reindex_to_schema<G: Guest>(
+    index: IndexName,
+    schema: Schema,
+    reindex: SchemaReindex,
+) -> Result<Option<TaskId>, SearchError> {
+    let changes = G::plan_schema_change(index.clone(), schema.clone())?;
+    refuse(
+        &changes,
+        |application| application == SchemaChangeApplication::Impossible,
+        "is impossible",
+    )?;
+
+    G::create_index(
+        reindex.target.clone(),
+        Some(schema),
+        WriteConsistency::WaitForVisible,
+    )?;
+    let result = G::reindex(index, reindex.target.clone(), reindex.alias, None)?;
+
+    if result.failures.is_empty() {
+        Ok(None)
+    } else {
+        Err(internal_error(format!(
+            "{} documents couldn't be copied to {}, first failure: {}",
+            result.failures.len(),
+            reindex.target,
+            result.failures[0].error.as_deref().unwrap_or_default()
+        )))
+    }
+}
+
+fn refuse(
+    changes: &[SchemaChange],
+    refused: impl Fn(SchemaChangeApplication) -> bool,
+    outcome: &str,
+) -> Result<(), SearchError> {
+    let refused = changes
+        .iter()
+        .filter(|change| refused(change.application))
+        .map(describe_change)
+        .collect::<Vec<_>>();
+
+    if refused.is_empty() {
+        Ok(())
+    } else {
+        Err(invalid_query(format!(
+            "The schema change {outcome}: {}",
+            refused.join("; ")
+        )))
+    }
+}
+
+fn describe_change(change: &SchemaChange) -> String {
+    let kind = match change.kind {
+        SchemaChangeKind::FieldAdded => "is added",
+        SchemaChangeKind::FieldRemoved => "is removed",
+        SchemaChangeKind::FieldTypeChanged => "changes type",
+        SchemaChangeKind::FieldOptionsChanged => "changes options",
+        SchemaChangeKind::VectorOptionsChanged => "changes vector options",
+        SchemaChangeKind::PrimaryKeyChanged => "becomes the primary key",
+    };
+
+    match &change.reason {
+        Some(reason) => format!("{} {kind} ({reason})", change.field),
+        None => format!("{} {kind}", change.field),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golem::search::types::{VectorOptions, VectorSimilarity};
+
+    fn field(name: &str, field_type: FieldType) -> SchemaField {
+        SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet: false,
+            sort: false,
+            index: true,
+            vector: None,
+            array: false,
+            stored: true,
+            analyzer: None,
+            locale: None,
+        }
+    }
+
+    fn kinds(current: &Schema, target: &Schema) -> Vec<(String, SchemaChangeKind)> {
+        diff_schemas(current, target)
+            .into_iter()
+            .map(|diff| (diff.field, diff.kind))
+            .collect()
+    }
+
+    #[test]
+    fn schemas_are_diffed() {
+        let current = Schema {
+            fields: vec![
+                field("id", FieldType::Keyword),
+                field("title", FieldType::Text),
+                field("year", FieldType::Text),
+                field("legacy", FieldType::Boolean),
+                SchemaField {
+                    vector: Some(VectorOptions {
+                        dimensions: 3,
+                        similarity: VectorSimilarity::Cosine,
+                    }),
+                    ..field("embedding", FieldType::Vector)
+                },
+            ],
+            primary_key: Some("id".to_string()),
+        };
+        let target = Schema {
+            fields: vec![
+                SchemaField {
+                    facet: true,
+                    ..field("title", FieldType::Text)
+                },
+                field("year", FieldType::Integer),
+                SchemaField {
+                    vector: Some(VectorOptions {
+                        dimensions: 4,
+                        similarity: VectorSimilarity::Cosine,
+                    }),
+                    ..field("embedding", FieldType::Vector)
+                },
+                field("isbn", FieldType::Keyword),
+            ],
+            primary_key: Some("isbn".to_string()),
+        };
+
+        assert_eq!(
+            kinds(&current, &target),
+            vec![
+                ("title".to_string(), SchemaChangeKind::FieldOptionsChanged),
+                ("year".to_string(), SchemaChangeKind::FieldTypeChanged),
+                (
+                    "embedding".to_string(),
+                    SchemaChangeKind::VectorOptionsChanged
+                ),
+                ("isbn".to_string(), SchemaChangeKind::FieldAdded),
+                ("legacy".to_string(), SchemaChangeKind::FieldRemoved),
+                ("isbn".to_string(), SchemaChangeKind::PrimaryKeyChanged),
+            ]
+        );
+        assert!(kinds(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn only_changes_that_cant_be_applied_in_place_are_refused() {
+        let current = Schema {
+            fields: vec![field("year", FieldType::Text)],
+            primary_key: None,
+        };
+        let target = Schema {
+            fields: vec![
+                field("year", FieldType::Integer),
+                field("isbn", FieldType::Keyword),
+            ],
+            primary_key: None,
+        };
+        let plan = |classify: fn(&SchemaDiff) -> Classification| {
+            Ok(plan_schema_change(&current, &target, classify))
+        };
+
+        assert_eq!(check_in_place(plan(|_| in_place())), Ok(()));
+        assert_eq!(
+            check_in_place(plan(|diff| match diff.kind {
+                SchemaChangeKind::FieldTypeChanged => needs_reindex("types are fixed"),
+                _ => in_place(),
+            })),
+            Err(SearchError::InvalidQuery(
+                "The schema change can't be applied in place, update the schema with a reindex: \
+                 year changes type (types are fixed)"
+                    .to_string()
+            ))
+        );
+        assert_eq!(check_in_place(Err(SearchError::IndexNotFound)), Ok(()));
+    }
+
+    #[test]
+    fn field_options_are_validated() {
+        let localized = SchemaField {
+            locale: Some("fr".to_string()),
+            ..field("title", FieldType::Text)
+        };
+        assert_eq!(validate_field(&localized), Ok(()));
+        assert_eq!(
+            language_analyzer(localized.locale.as_deref().unwrap()),
+            Some("french")
+        );
+        assert_eq!(language_analyzer("pt-BR"), Some("brazilian"));
+        assert_eq!(language_analyzer("pt_PT"), Some("portuguese"));
+        assert_eq!(language_analyzer("tlh"), None);
+
+        assert!(validate_field(&SchemaField {
+            analyzer: Some("standard".to_string()),
+            ..field("year", FieldType::Integer)
+        })
+        .is_err());
+        assert!(validate_field(&SchemaField {
+            array: true,
+            ..field("variants", FieldType::Nested)
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use crate::error::invalid_query;
+use crate::golem::search::types::{SchemaField, SearchError, VectorOptions, VectorQuery};
+
+/// Semantic ratio of hybrid searches when the query doesn't set one
+pub const DEFAULT_SEMANTIC_RATIO: f32 = 0.5;
+
+/// Returns the options of a `vector` field, which are required for this field type
+pub fn vector_options(field: &SchemaField) -> Result<&VectorOptions, SearchError> {
+    match &field.vector {
+        Some(options) if options.dimensions > 0 => Ok(options),
+        Some(_) => Err(invalid_query(format!(
+            "The vector field {} must have at least one dimension",
+            field.name
+        ))),
+        None => Err(invalid_query(format!(
+            "The vector field {} has no vector options",
+            field.name
+        ))),
+    }
+}
+
+/// Validates a vector query, and returns its semantic ratio
+pub fn validate_vector_query(query: &VectorQuery) -> Result<f32, SearchError> {
+    if query.field.trim().is_empty() {
+        return Err(invalid_query("The vector query has an empty field"));
+    }
+    if query.vector.is_empty() {
+        return Err(invalid_query(format!(
+            "The vector query on {} has an empty vector",
+            query.field
+        )));
+    }
+    if query.k == 0 {
+        return Err(invalid_query(format!(
+            "The vector query on {} must retrieve at least one neighbour",
+            query.field
+        )));
+    }
+    let ratio = query.semantic_ratio.unwrap_or(DEFAULT_SEMANTIC_RATIO);
+    if !(0.0..=1.0).contains(&ratio) {
+        return Err(invalid_query(format!(
+            "The semantic ratio must be between 0.0 and 1.0, got {ratio}"
+        )));
+    }
+    Ok(ratio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::golem::search::types::{FieldType, VectorSimilarity};
+
+    fn vector_query() -> VectorQuery {
+        VectorQuery {
+            field: "embedding".to_string(),
+            vector: vec![0.1, 0.2, 0.3],
+            k: 10,
+            semantic_ratio: None,
+            filter: None,
+        }
+    }
+
+    #[test]
+    fn vector_fields_require_options() {
+        let mut field = SchemaField {
+            name: "embedding".to_string(),
+            field_type: FieldType::Vector,
+            required: false,
+            facet: false,
+            sort: false,
+            index: true,
+            array: false,
+            stored: true,
+            analyzer: None,
+            locale: None,
+            vector: None,
+        };
+        assert!(vector_options(&field).is_err());
+
+        field.vector = Some(VectorOptions {
+            dimensions: 3,
+            similarity: VectorSimilarity::Cosine,
+        });
+        assert_eq!(Some(vector_options(&field).unwrap()), field.vector.as_ref());
+    }
+
+    #[test]
+    fn invalid_vector_queries_are_rejected() {
+        assert_eq!(
+            validate_vector_query(&vector_query()).unwrap(),
+            DEFAULT_SEMANTIC_RATIO
+        );
+
+        let empty = VectorQuery {
+            vector: vec![],
+            ..vector_query()
+        };
+        let no_neighbours = VectorQuery {
+            k: 0,
+            ..vector_query()
+        };
+        let ratio_out_of_range = VectorQuery {
+            semantic_ratio: Some(1.5),
+            ..vector_query()
+        };
+        for query in [empty, no_neighbours, ratio_out_of_range] {
+            assert!(matches!(
+                validate_vector_query(&query),
+                Err(SearchError::InvalidQuery(_))
+            ));
+        }
+    }
+}
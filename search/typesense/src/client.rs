@@ -4,6 +4,7 @@ use log::trace;
 use reqwest::{Client, Method, RequestBuilder, Response};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::fmt::Debug;
 
 /// The Typesense Search API client for managing collections and performing search operations.
@@ -54,6 +55,30 @@ impl TypesenseSearchApi {
         parse_response(response)
     }
 
+    /// Adds and drops fields of the collection, a field dropped and added again in the same
+    /// request changes its definition
+    pub fn update_collection(
+        &self,
+        collection_name: &str,
+        fields: &[Value],
+    ) -> Result<(), SearchError> {
+        trace!("Updating collection: {collection_name}");
+
+        let url = format!("{}/collections/{}", self.base_url, collection_name);
+
+        let response = self
+            .create_request(Method::PATCH, &url)
+            .json(&serde_json::json!({ "fields": fields }))
+            .send()
+            .map_err(|e| internal_error(format!("Failed to update collection: {e}")))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(search_error_from_status(response.status()))
+        }
+    }
+
     pub fn delete_collection(
         &self,
         collection_name: &str,
@@ -83,6 +108,53 @@ impl TypesenseSearchApi {
         parse_response(response)
     }
 
+    /// Creates the alias, or points it to another collection if it already exists
+    pub fn upsert_alias(
+        &self,
+        alias: &str,
+        collection_name: &str,
+    ) -> Result<CollectionAlias, SearchError> {
+        trace!("Pointing alias {alias} to collection: {collection_name}");
+
+        let url = format!("{}/aliases/{}", self.base_url, alias);
+
+        let response = self
+            .create_request(Method::PUT, &url)
+            .json(&UpsertAliasRequest {
+                collection_name: collection_name.to_string(),
+            })
+            .send()
+            .map_err(|e| internal_error(format!("Failed to upsert alias: {e}")))?;
+
+        parse_response(response)
+    }
+
+    pub fn list_aliases(&self) -> Result<ListAliasesResponse, SearchError> {
+        trace!("Listing aliases");
+
+        let url = format!("{}/aliases", self.base_url);
+
+        let response = self
+            .create_request(Method::GET, &url)
+            .send()
+            .map_err(|e| internal_error(format!("Failed to list aliases: {e}")))?;
+
+        parse_response(response)
+    }
+
+    pub fn delete_alias(&self, alias: &str) -> Result<CollectionAlias, SearchError> {
+        trace!("Deleting alias: {alias}");
+
+        let url = format!("{}/aliases/{}", self.base_url, alias);
+
+        let response = self
+            .create_request(Method::DELETE, &url)
+            .send()
+            .map_err(|e| internal_error(format!("Failed to delete alias: {e}")))?;
+
+        parse_response(response)
+    }
+
     pub fn _index_document(
         &self,
         collection_name: &str,
@@ -115,7 +187,7 @@ impl TypesenseSearchApi {
         );
 
         let url = format!(
-            "{}/collections/{}/documents/import",
+            "{}/collections/{}/documents/import?action=upsert",
             self.base_url, collection_name
         );
 
@@ -208,6 +280,21 @@ impl TypesenseSearchApi {
             self.base_url, collection_name
         );
 
+        // Query vectors don't fit in the URL, so vector searches are sent in a multi-search body
+        if query.vector_query.is_some() {
+            let searches = MultiSearchQuery {
+                searches: vec![MultiSearchRequest {
+                    collection: collection_name.to_string(),
+                    query: query.clone(),
+                }],
+            };
+            return self
+                .multi_search(&searches)?
+                .results
+                .pop()
+                .ok_or_else(|| internal_error("Empty multi-search response"));
+        }
+
         let query_string = self.build_query_string(query)?;
         let full_url = if query_string.is_empty() {
             url
@@ -240,8 +327,46 @@ impl TypesenseSearchApi {
         if let Some(ref facet_by) = query.facet_by {
             params.push(format!("facet_by={}", urlencoding::encode(facet_by)));
         }
+        if let Some(max_facet_values) = query.max_facet_values {
+            params.push(format!("max_facet_values={max_facet_values}"));
+        }
+        if let Some(ref highlight_fields) = query.highlight_fields {
+            params.push(format!(
+                "highlight_fields={}",
+                urlencoding::encode(highlight_fields)
+            ));
+        }
+        if let Some(ref highlight_full_fields) = query.highlight_full_fields {
+            params.push(format!(
+                "highlight_full_fields={}",
+                urlencoding::encode(highlight_full_fields)
+            ));
+        }
+        if let Some(highlight_affix_num_tokens) = query.highlight_affix_num_tokens {
+            params.push(format!(
+                "highlight_affix_num_tokens={highlight_affix_num_tokens}"
+            ));
+        }
+        if let Some(ref highlight_start_tag) = query.highlight_start_tag {
+            params.push(format!(
+                "highlight_start_tag={}",
+                urlencoding::encode(highlight_start_tag)
+            ));
+        }
+        if let Some(ref highlight_end_tag) = query.highlight_end_tag {
+            params.push(format!(
+                "highlight_end_tag={}",
+                urlencoding::encode(highlight_end_tag)
+            ));
+        }
+        if let Some(snippet_threshold) = query.snippet_threshold {
+            params.push(format!("snippet_threshold={snippet_threshold}"));
+        }
+        // The offset is an alternative to the page, which takes precedence
         if let Some(page) = query.page {
             params.push(format!("page={page}"));
+        } else if let Some(offset) = query.offset {
+            params.push(format!("offset={offset}"));
         }
         if let Some(per_page) = query.per_page {
             params.push(format!("per_page={per_page}"));
@@ -250,7 +375,7 @@ impl TypesenseSearchApi {
         Ok(params.join("&"))
     }
 
-    pub fn _multi_search(
+    pub fn multi_search(
         &self,
         searches: &MultiSearchQuery,
     ) -> Result<MultiSearchResponse, SearchError> {
@@ -300,33 +425,23 @@ fn parse_bulk_import_response(response: Response) -> Result<IndexDocumentsRespon
             .text()
             .map_err(|err| from_reqwest_error("Failed to read response", err))?;
 
-        let lines: Vec<&str> = body_str.trim().split('\n').collect();
-        let mut success_count = 0;
-        let mut total_processed = 0;
-
-        for line in lines {
-            if !line.trim().is_empty() {
-                total_processed += 1;
-                match serde_json::from_str::<serde_json::Value>(line) {
-                    Ok(json) => {
-                        if json
-                            .get("success")
-                            .and_then(|v| v.as_bool())
-                            .unwrap_or(false)
-                        {
-                            success_count += 1;
-                        }
-                    }
-                    Err(e) => {
-                        println!("[Typesense] Failed to parse NDJSON line: {e} | line: {line}");
-                    }
-                }
-            }
-        }
+        // One result per line, in the order of the imported documents
+        let items = body_str
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str::<ImportResult>(line).unwrap_or_else(|e| ImportResult {
+                    success: false,
+                    error: Some(format!("Failed to parse import result: {e}")),
+                    code: None,
+                })
+            })
+            .collect::<Vec<_>>();
 
         let response = IndexDocumentsResponse {
-            success: success_count == total_processed && total_processed > 0,
-            num_imported: Some(success_count),
+            success: !items.is_empty() && items.iter().all(|item| item.success),
+            num_imported: Some(items.iter().filter(|item| item.success).count() as u32),
+            items,
         };
 
         Ok(response)
@@ -365,7 +480,7 @@ pub struct CollectionSchema {
     pub symbols_to_index: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CollectionField {
     pub name: String,
     #[serde(rename = "type")]
@@ -378,6 +493,14 @@ pub struct CollectionField {
     pub sort: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub optional: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_dim: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vec_dist: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -410,6 +533,8 @@ pub struct SearchQuery {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub exclude_fields: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlight_fields: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub highlight_full_fields: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub highlight_affix_num_tokens: Option<u32>,
@@ -457,6 +582,8 @@ pub struct SearchQuery {
     pub prioritize_token_position: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_candidates: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vector_query: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -553,6 +680,22 @@ pub struct DeleteCollectionResponse {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ListCollectionsResponse(pub Vec<CreateCollectionResponse>);
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpsertAliasRequest {
+    pub collection_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionAlias {
+    pub name: String,
+    pub collection_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListAliasesResponse {
+    pub aliases: Vec<CollectionAlias>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexDocumentResponse {
     pub id: String,
@@ -562,6 +705,16 @@ pub struct IndexDocumentResponse {
 pub struct IndexDocumentsResponse {
     pub success: bool,
     pub num_imported: Option<u32>,
+    pub items: Vec<ImportResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportResult {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<u16>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
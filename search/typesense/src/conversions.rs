@@ -1,10 +1,24 @@
 use crate::client::{
-    CollectionField, CollectionSchema, SearchHit as TypesenseSearchHit,
-    SearchQuery as TypesenseSearchQuery, SearchResponse, TypesenseDocument,
+    CollectionField, CollectionSchema, ImportResult, ListAliasesResponse,
+    SearchHit as TypesenseSearchHit, SearchQuery as TypesenseSearchQuery, SearchResponse,
+    TypesenseDocument,
 };
+use golem_search::bulk::{is_retryable_status, ItemOutcome};
+use golem_search::error::{internal_error, invalid_query};
+use golem_search::facets::{
+    facet_field, facet_requests, terms_facet_result, unsupported_facet, DEFAULT_FACET_SIZE,
+};
+use golem_search::filter::{resolve_filter, unsupported_filter, Filter};
 use golem_search::golem::search::types::{
-    Doc, FieldType, Schema, SchemaField, SearchHit, SearchQuery, SearchResults,
+    Doc, FacetBucket, FacetRequest, FacetResult, FacetStats, FieldHighlight, FieldType,
+    FilterValue, HighlightConfig, IndexAlias, Schema, SchemaChangeKind, SchemaField, SearchError,
+    SearchHit, SearchQuery, SearchResults, VectorOptions, VectorSimilarity,
+};
+use golem_search::highlights::{
+    field_highlight, highlight_tags, validate_highlight_config, DEFAULT_SNIPPET_LENGTH,
 };
+use golem_search::schema::{in_place, needs_reindex, validate_field, Classification, SchemaDiff};
+use golem_search::vector::{validate_vector_query, vector_options};
 use serde_json::{Map, Value};
 
 pub fn doc_to_typesense_document(doc: Doc) -> Result<TypesenseDocument, String> {
@@ -29,7 +43,10 @@ pub fn _typesense_document_to_doc(doc: TypesenseDocument) -> Doc {
     Doc { id, content }
 }
 
-pub fn search_query_to_typesense_query(query: SearchQuery) -> TypesenseSearchQuery {
+pub fn search_query_to_typesense_query(
+    query: SearchQuery,
+) -> Result<TypesenseSearchQuery, SearchError> {
+    let facets = facet_requests(&query)?;
     let mut typesense_query = TypesenseSearchQuery {
         q: query.q.unwrap_or_else(|| "*".to_string()),
         query_by: Some("title,author,description,genre".to_string()),
@@ -42,6 +59,7 @@ pub fn search_query_to_typesense_query(query: SearchQuery) -> TypesenseSearchQue
         offset: query.offset,
         include_fields: None,
         exclude_fields: None,
+        highlight_fields: None,
         highlight_full_fields: None,
         highlight_affix_num_tokens: None,
         highlight_start_tag: None,
@@ -66,27 +84,76 @@ pub fn search_query_to_typesense_query(query: SearchQuery) -> TypesenseSearchQue
         prioritize_exact_match: None,
         prioritize_token_position: None,
         max_candidates: None,
+        vector_query: None,
     };
 
-    if !query.filters.is_empty() {
-        typesense_query.filter_by = Some(query.filters.join(" && "));
+    let mut filters = query.filters;
+    if let Some(expression) = &query.filter {
+        let filter = resolve_filter(expression)?;
+        filters.push(filter_to_typesense(&filter)?);
+    }
+
+    if let Some(vector_query) = &query.vector {
+        let semantic_ratio = validate_vector_query(vector_query)?;
+        if let Some(expression) = &vector_query.filter {
+            let filter = resolve_filter(expression)?;
+            filters.push(filter_to_typesense(&filter)?);
+        }
+        let vector = vector_query
+            .vector
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        // `alpha` is the weight of the vector results in a hybrid search
+        let alpha = if typesense_query.q == "*" {
+            String::new()
+        } else {
+            format!(", alpha:{semantic_ratio}")
+        };
+        typesense_query.vector_query = Some(format!(
+            "{}:([{vector}], k:{}{alpha})",
+            vector_query.field, vector_query.k
+        ));
+        typesense_query.per_page = typesense_query.per_page.or(Some(vector_query.k));
+    }
+    if !filters.is_empty() {
+        typesense_query.filter_by = Some(join_filters(filters));
     }
 
     if !query.sort.is_empty() {
         typesense_query.sort_by = Some(query.sort.join(","));
     }
 
-    if !query.facets.is_empty() {
-        typesense_query.facet_by = Some(query.facets.join(","));
+    // Typesense returns the values with the highest counts, and the stats of numeric fields
+    if !facets.is_empty() {
+        let mut facet_fields: Vec<&str> = vec![];
+        let mut max_facet_values = DEFAULT_FACET_SIZE;
+        for facet in &facets {
+            match facet {
+                FacetRequest::Terms(terms) => {
+                    max_facet_values =
+                        max_facet_values.max(terms.size.unwrap_or(DEFAULT_FACET_SIZE));
+                }
+                FacetRequest::Stats(_) => {}
+                FacetRequest::Range(_) | FacetRequest::DateHistogram(_) => {
+                    return Err(unsupported_facet(facet, "Typesense"));
+                }
+            }
+            if !facet_fields.contains(&facet_field(facet)) {
+                facet_fields.push(facet_field(facet));
+            }
+        }
+        typesense_query.facet_by = Some(facet_fields.join(","));
+        typesense_query.max_facet_values = Some(max_facet_values);
     }
 
+    // Every highlighted field has a snippet, `fields` also get their whole highlighted value
     if let Some(highlight) = query.highlight {
-        if let Some(pre_tag) = highlight.pre_tag {
-            typesense_query.highlight_start_tag = Some(pre_tag);
-        }
-        if let Some(post_tag) = highlight.post_tag {
-            typesense_query.highlight_end_tag = Some(post_tag);
-        }
+        validate_highlight_config(&highlight)?;
+        let (pre_tag, post_tag) = highlight_tags(&highlight);
+        typesense_query.highlight_start_tag = Some(pre_tag.to_string());
+        typesense_query.highlight_end_tag = Some(post_tag.to_string());
         if let Some(max_length) = highlight.max_length {
             typesense_query.snippet_threshold = Some(max_length);
         }
@@ -94,6 +161,14 @@ pub fn search_query_to_typesense_query(query: SearchQuery) -> TypesenseSearchQue
         if !highlight.fields.is_empty() {
             typesense_query.highlight_full_fields = Some(highlight.fields.join(","));
         }
+        if !highlight.snippet_fields.is_empty() {
+            let mut fields = highlight.fields.clone();
+            fields.extend(highlight.snippet_fields.iter().cloned());
+            typesense_query.highlight_fields = Some(fields.join(","));
+            // The snippet has this number of tokens on each side of the matched terms
+            let snippet_length = highlight.snippet_length.unwrap_or(DEFAULT_SNIPPET_LENGTH);
+            typesense_query.highlight_affix_num_tokens = Some(snippet_length.div_ceil(2));
+        }
     }
 
     if let Some(config) = query.config {
@@ -220,14 +295,191 @@ pub fn search_query_to_typesense_query(query: SearchQuery) -> TypesenseSearchQue
         }
     }
 
-    typesense_query
+    Ok(typesense_query)
+}
+
+/// Joins the filters with `&&`, parenthesizing each one as `&&` binds tighter than the `||` of a
+/// native filter
+fn join_filters(filters: Vec<String>) -> String {
+    if filters.len() == 1 {
+        return filters.into_iter().next().unwrap_or_default();
+    }
+    filters
+        .iter()
+        .map(|filter| format!("({filter})"))
+        .collect::<Vec<_>>()
+        .join(" && ")
+}
+
+/// Compiles a provider-neutral filter to the Typesense `filter_by` syntax. Typesense can't filter
+/// on the existence of a field, and only negates equality and `in` filters.
+pub fn filter_to_typesense(filter: &Filter) -> Result<String, SearchError> {
+    match filter {
+        Filter::Eq(field, value) => Ok(format!("{field}:={}", filter_value_to_typesense(value)?)),
+        Filter::Ne(field, value) => Ok(format!("{field}:!={}", filter_value_to_typesense(value)?)),
+        Filter::Range(field, bounds) => {
+            let mut conditions = Vec::new();
+            for (operator, bound) in [
+                (">", &bounds.gt),
+                (">=", &bounds.gte),
+                ("<", &bounds.lt),
+                ("<=", &bounds.lte),
+            ] {
+                if let Some(bound) = bound {
+                    conditions.push(format!(
+                        "{field}:{operator}{}",
+                        filter_value_to_typesense(bound)?
+                    ));
+                }
+            }
+            Ok(format!("({})", conditions.join(" && ")))
+        }
+        Filter::In(field, values) => Ok(format!(
+            "{field}:=[{}]",
+            filter_values_to_typesense(values)?
+        )),
+        Filter::Exists(..) => Err(unsupported_filter(filter, "Typesense")),
+        Filter::Prefix(field, prefix) => {
+            if prefix.is_empty()
+                || !prefix
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ' '))
+            {
+                return Err(invalid_query(format!(
+                    "Typesense prefix filters only support letters, digits, spaces, '-', '_' and '.', got {prefix:?}"
+                )));
+            }
+            Ok(format!("{field}:{prefix}*"))
+        }
+        Filter::GeoRadius(radius) => Ok(format!(
+            "{}:({}, {}, {} km)",
+            radius.field,
+            radius.center.lat,
+            radius.center.lon,
+            radius.radius_meters / 1000.0
+        )),
+        Filter::GeoBoundingBox(bbox) => {
+            // A polygon with the corners of the box, clockwise from the top left
+            Ok(format!(
+                "{}:({}, {}, {}, {}, {}, {}, {}, {})",
+                bbox.field,
+                bbox.top_left.lat,
+                bbox.top_left.lon,
+                bbox.top_left.lat,
+                bbox.bottom_right.lon,
+                bbox.bottom_right.lat,
+                bbox.bottom_right.lon,
+                bbox.bottom_right.lat,
+                bbox.top_left.lon
+            ))
+        }
+        Filter::And(filters) => Ok(format!(
+            "({})",
+            filters
+                .iter()
+                .map(filter_to_typesense)
+                .collect::<Result<Vec<_>, _>>()?
+                .join(" && ")
+        )),
+        Filter::Or(filters) => Ok(format!(
+            "({})",
+            filters
+                .iter()
+                .map(filter_to_typesense)
+                .collect::<Result<Vec<_>, _>>()?
+                .join(" || ")
+        )),
+        Filter::Not(negated) => match negated.as_ref() {
+            Filter::Eq(field, value) => {
+                Ok(format!("{field}:!={}", filter_value_to_typesense(value)?))
+            }
+            Filter::Ne(field, value) => {
+                Ok(format!("{field}:={}", filter_value_to_typesense(value)?))
+            }
+            Filter::In(field, values) => Ok(format!(
+                "{field}:!=[{}]",
+                filter_values_to_typesense(values)?
+            )),
+            _ => Err(invalid_query(format!(
+                "Typesense can only negate eq, ne and in filters, not {}",
+                negated.operator()
+            ))),
+        },
+    }
+}
+
+fn filter_value_to_typesense(value: &FilterValue) -> Result<String, SearchError> {
+    match value {
+        // Backticks escape the special characters of the filter syntax
+        FilterValue::Text(text) if text.contains('`') => Err(invalid_query(format!(
+            "Typesense filter values can't contain backticks, got {text:?}"
+        ))),
+        FilterValue::Text(text) => Ok(format!("`{text}`")),
+        FilterValue::Integer(integer) => Ok(integer.to_string()),
+        FilterValue::Float(float) => Ok(float.to_string()),
+        FilterValue::Boolean(boolean) => Ok(boolean.to_string()),
+    }
+}
+
+fn filter_values_to_typesense(values: &[FilterValue]) -> Result<String, SearchError> {
+    Ok(values
+        .iter()
+        .map(filter_value_to_typesense)
+        .collect::<Result<Vec<_>, _>>()?
+        .join(", "))
 }
 
-pub fn typesense_response_to_search_results(response: SearchResponse) -> SearchResults {
+pub fn typesense_response_to_search_results(
+    response: SearchResponse,
+    facets: &[FacetRequest],
+    highlight: Option<&HighlightConfig>,
+) -> SearchResults {
     let hits = response
         .hits
         .into_iter()
-        .map(typesense_hit_to_search_hit)
+        .map(|hit| typesense_hit_to_search_hit(hit, highlight))
+        .collect();
+
+    let facet_counts = response.facet_counts.as_deref().unwrap_or_default();
+    let facet_count = |field: &str| facet_counts.iter().find(|count| count.field_name == field);
+    let facet_results = facets
+        .iter()
+        .filter_map(|facet| match facet {
+            FacetRequest::Terms(terms) => {
+                let buckets = facet_count(&terms.field)
+                    .map(|count| {
+                        count
+                            .counts
+                            .iter()
+                            .map(|facet_value| FacetBucket {
+                                value: match &facet_value.value {
+                                    Value::String(value) => value.clone(),
+                                    value => value.to_string(),
+                                },
+                                count: facet_value.count as u64,
+                                range: None,
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                Some(terms_facet_result(terms, buckets, None))
+            }
+            FacetRequest::Stats(field) => Some(FacetResult {
+                field: field.clone(),
+                buckets: vec![],
+                stats: facet_count(field)
+                    .and_then(|count| count.stats.as_ref())
+                    .map(|stats| FacetStats {
+                        count: None,
+                        min: stats.min,
+                        max: stats.max,
+                        avg: stats.avg,
+                        sum: stats.sum,
+                    }),
+            }),
+            // Rejected when building the query
+            FacetRequest::Range(_) | FacetRequest::DateHistogram(_) => None,
+        })
         .collect();
 
     let facets = response.facet_counts.map(|facet_counts| {
@@ -256,11 +508,19 @@ pub fn typesense_response_to_search_results(response: SearchResponse) -> SearchR
         per_page: Some(response.request_params.per_page),
         hits,
         facets,
+        facet_results,
         took_ms: Some(response.search_time_ms),
     }
 }
 
-pub fn typesense_hit_to_search_hit(hit: TypesenseSearchHit) -> SearchHit {
+pub fn typesense_hit_to_search_hit(
+    hit: TypesenseSearchHit,
+    highlight: Option<&HighlightConfig>,
+) -> SearchHit {
+    let highlight_fields = highlight
+        .map(|config| typesense_highlights_to_field_highlights(&hit, config))
+        .unwrap_or_default();
+
     let mut document = hit.document;
 
     let (id, content) =
@@ -318,15 +578,79 @@ pub fn typesense_hit_to_search_hit(hit: TypesenseSearchHit) -> SearchHit {
         score,
         content: Some(content),
         highlights,
+        highlight_fields,
+    }
+}
+
+/// Reads the `highlight` object of the hit, or the `highlights` list of older Typesense versions
+fn typesense_highlights_to_field_highlights(
+    hit: &TypesenseSearchHit,
+    config: &HighlightConfig,
+) -> Vec<FieldHighlight> {
+    let fields: Vec<(&str, &Value)> = match (&hit.highlight, &hit.highlights) {
+        (Some(highlight), _) => highlight
+            .iter()
+            .map(|(field, value)| (field.as_str(), value))
+            .collect(),
+        (None, Some(highlights)) => highlights
+            .iter()
+            .filter_map(|value| Some((value.get("field")?.as_str()?, value)))
+            .collect(),
+        (None, None) => vec![],
+    };
+
+    fields
+        .into_iter()
+        .map(|(field, value)| {
+            let snippet = config.snippet_fields.iter().any(|f| f == field);
+            let mut fragments = vec![];
+            typesense_highlight_fragments(value, snippet, &mut fragments);
+            field_highlight(field, &fragments, config)
+        })
+        .collect()
+}
+
+/// Collects the whole highlighted value of a field, or its snippets. Array fields hold one
+/// highlight per element.
+fn typesense_highlight_fragments<'a>(
+    value: &'a Value,
+    snippet: bool,
+    fragments: &mut Vec<&'a str>,
+) {
+    match value {
+        Value::Array(values) => {
+            for value in values {
+                typesense_highlight_fragments(value, snippet, fragments);
+            }
+        }
+        Value::Object(highlight) => {
+            let whole_value = highlight.get("value").and_then(|v| v.as_str());
+            match (
+                whole_value,
+                highlight.get("snippets"),
+                highlight.get("snippet"),
+            ) {
+                (Some(value), _, _) if !snippet => fragments.push(value),
+                (_, Some(Value::Array(snippets)), _) => {
+                    fragments.extend(snippets.iter().filter_map(|s| s.as_str()))
+                }
+                (_, _, Some(Value::String(snippet))) => fragments.push(snippet),
+                _ => {}
+            }
+        }
+        _ => {}
     }
 }
 
-pub fn schema_to_typesense_schema(schema: Schema, collection_name: &str) -> CollectionSchema {
-    let fields: Vec<CollectionField> = schema
+pub fn schema_to_typesense_schema(
+    schema: Schema,
+    collection_name: &str,
+) -> Result<CollectionSchema, SearchError> {
+    let fields = schema
         .fields
         .iter()
         .map(|f| schema_field_to_collection_field(f.clone()))
-        .collect();
+        .collect::<Result<Vec<_>, _>>()?;
 
     let default_sorting_field = schema
         .fields
@@ -334,18 +658,34 @@ pub fn schema_to_typesense_schema(schema: Schema, collection_name: &str) -> Coll
         .find(|f| f.sort && f.name != "id" && f.required)
         .map(|f| f.name.clone());
 
-    CollectionSchema {
+    let enable_nested_fields = schema
+        .fields
+        .iter()
+        .any(|f| matches!(f.field_type, FieldType::Object | FieldType::Nested))
+        .then_some(true);
+
+    Ok(CollectionSchema {
         name: collection_name.to_string(),
         fields,
         default_sorting_field,
-        enable_nested_fields: None,
+        enable_nested_fields,
         token_separators: None,
         symbols_to_index: None,
-    }
+    })
 }
 
-pub fn schema_field_to_collection_field(field: SchemaField) -> CollectionField {
-    let field_type = match field.field_type {
+pub fn schema_field_to_collection_field(
+    field: SchemaField,
+) -> Result<CollectionField, SearchError> {
+    validate_field(&field)?;
+    if field.analyzer.is_some() {
+        return Err(invalid_query(format!(
+            "Typesense doesn't support custom analyzers, set on the field {}",
+            field.name
+        )));
+    }
+
+    let element_type = match field.field_type {
         FieldType::Text => "string",
         FieldType::Keyword => "string",
         FieldType::Integer => "int32",
@@ -353,17 +693,51 @@ pub fn schema_field_to_collection_field(field: SchemaField) -> CollectionField {
         FieldType::Boolean => "bool",
         FieldType::Date => "int64",
         FieldType::GeoPoint => "geopoint",
-    }
-    .to_string();
+        FieldType::GeoShape => "geopolygon",
+        FieldType::Vector => "float[]",
+        FieldType::Object => "object",
+        FieldType::Nested => "object[]",
+    };
+    let field_type = match (field.array, field.field_type) {
+        (true, FieldType::GeoShape) => {
+            return Err(invalid_query(format!(
+                "Typesense doesn't support arrays of polygons, used by the field {}",
+                field.name
+            )))
+        }
+        (true, _) => format!("{element_type}[]"),
+        (false, _) => element_type.to_string(),
+    };
 
-    CollectionField {
+    let (num_dim, vec_dist) = if field.field_type == FieldType::Vector {
+        let vector = vector_options(&field)?;
+        let vec_dist = match vector.similarity {
+            VectorSimilarity::Cosine => "cosine",
+            VectorSimilarity::DotProduct => "ip",
+            VectorSimilarity::Euclidean => {
+                return Err(invalid_query(format!(
+                "Typesense doesn't support the euclidean similarity, used by the vector field {}",
+                field.name
+            )))
+            }
+        };
+        (Some(vector.dimensions), Some(vec_dist.to_string()))
+    } else {
+        (None, None)
+    };
+
+    Ok(CollectionField {
         name: field.name,
         field_type,
         facet: Some(field.facet),
         index: Some(field.index),
         sort: Some(field.sort),
         optional: Some(!field.required),
-    }
+        num_dim,
+        vec_dist,
+        locale: field.locale,
+        store: Some(field.stored),
+    })
 }
 
 pub fn _typesense_schema_to_schema(schema: CollectionSchema) -> Schema {
@@ -380,14 +754,32 @@ pub fn _typesense_schema_to_schema(schema: CollectionSchema) -> Schema {
 }
 
 pub fn collection_field_to_schema_field(field: CollectionField) -> SchemaField {
-    let field_type = match field.field_type.as_str() {
+    let element_type = |field_type: &str| match field_type {
         "string" => FieldType::Text,
         "int32" | "int64" => FieldType::Integer,
         "float" => FieldType::Float,
         "bool" => FieldType::Boolean,
         "geopoint" => FieldType::GeoPoint,
+        "geopolygon" => FieldType::GeoShape,
+        "object" => FieldType::Object,
         _ => FieldType::Text,
     };
+    let (field_type, array) = match field.field_type.as_str() {
+        "float[]" if field.num_dim.is_some() => (FieldType::Vector, false),
+        // Typesense doesn't tell arrays of objects from nested objects
+        "object[]" => (FieldType::Nested, false),
+        field_type => match field_type.strip_suffix("[]") {
+            Some(field_type) => (element_type(field_type), true),
+            None => (element_type(field_type), false),
+        },
+    };
+    let vector = field.num_dim.map(|dimensions| VectorOptions {
+        dimensions,
+        similarity: match field.vec_dist.as_deref() {
+            Some("ip") => VectorSimilarity::DotProduct,
+            _ => VectorSimilarity::Cosine,
+        },
+    });
 
     SchemaField {
         name: field.name,
@@ -396,14 +788,122 @@ pub fn collection_field_to_schema_field(field: CollectionField) -> SchemaField {
         facet: field.facet.unwrap_or(false),
         sort: field.sort.unwrap_or(false),
         index: field.index.unwrap_or(true),
+        vector,
+        array,
+        stored: field.store.unwrap_or(true),
+        analyzer: None,
+        // Typesense returns an empty locale for fields without one
+        locale: field.locale.filter(|locale| !locale.is_empty()),
+    }
+}
+
+/// Typesense alters a collection by dropping fields and adding them again, and re-indexes the
+/// stored documents itself. Only the number of dimensions of a vector can't change, as the stored
+/// vectors don't have it.
+pub fn classify_typesense_change(diff: &SchemaDiff) -> Classification {
+    match diff.kind {
+        SchemaChangeKind::VectorOptionsChanged => {
+            let dimensions = |field: &Option<SchemaField>| {
+                field
+                    .as_ref()
+                    .and_then(|field| field.vector.as_ref())
+                    .map(|vector| vector.dimensions)
+            };
+            if dimensions(&diff.current) == dimensions(&diff.target) {
+                in_place()
+            } else {
+                needs_reindex("The stored vectors don't have the new number of dimensions")
+            }
+        }
+        SchemaChangeKind::PrimaryKeyChanged => {
+            needs_reindex("Typesense can't change the default sorting field of a collection")
+        }
+        _ => in_place(),
     }
 }
 
+/// Fields of the request altering the collection: removed fields are dropped, and changed fields
+/// are dropped and added again. Changes that don't change the Typesense field are skipped, as
+/// several field types are stored the same way.
+pub fn schema_diffs_to_typesense_fields(diffs: &[SchemaDiff]) -> Result<Vec<Value>, SearchError> {
+    let drop_field = |name: &str| serde_json::json!({ "name": name, "drop": true });
+    let add_field = |field: &SchemaField| -> Result<Value, SearchError> {
+        let field = schema_field_to_collection_field(field.clone())?;
+        serde_json::to_value(field).map_err(|e| internal_error(e.to_string()))
+    };
+
+    let mut fields = Vec::new();
+    for diff in diffs {
+        match (&diff.current, &diff.target) {
+            (None, Some(target)) => fields.push(add_field(target)?),
+            (Some(_), None) => fields.push(drop_field(&diff.field)),
+            (Some(current), Some(target)) => {
+                let unchanged = schema_field_to_collection_field(current.clone()).ok()
+                    == Some(schema_field_to_collection_field(target.clone())?);
+                if !unchanged {
+                    fields.push(drop_field(&diff.field));
+                    fields.push(add_field(target)?);
+                }
+            }
+            (None, None) => {}
+        }
+    }
+
+    Ok(fields)
+}
+
+pub fn import_results_to_outcomes(items: &[ImportResult]) -> Vec<ItemOutcome> {
+    items
+        .iter()
+        .map(|item| {
+            if item.success {
+                ItemOutcome::success()
+            } else {
+                ItemOutcome::failure(
+                    item.error
+                        .clone()
+                        .unwrap_or_else(|| "Unknown error".to_string()),
+                    item.code.is_some_and(is_retryable_status),
+                )
+            }
+        })
+        .collect()
+}
+
+pub fn typesense_aliases_to_index_aliases(response: ListAliasesResponse) -> Vec<IndexAlias> {
+    let mut aliases = response
+        .aliases
+        .into_iter()
+        .map(|alias| IndexAlias {
+            alias: alias.name,
+            index: alias.collection_name,
+        })
+        .collect::<Vec<_>>();
+    aliases.sort_by(|a, b| a.alias.cmp(&b.alias));
+    aliases
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::client::{FacetCount, FacetValue, RequestParams, SearchHit as TypesenseSearchHit};
-    use golem_search::golem::search::types::{HighlightConfig, SearchConfig};
+    use golem_search::golem::search::types::{
+        FacetOrder, FacetRange, FilterExpression, FilterNode, GeoPoint, GeoRadius, HighlightConfig,
+        HighlightMatch, RangeBounds, RangeFacet, SchemaChangeApplication, SearchConfig, TermsFacet,
+        VectorQuery,
+    };
+
+    fn highlight_config() -> HighlightConfig {
+        HighlightConfig {
+            fields: vec!["title".to_string()],
+            pre_tag: Some("<mark>".to_string()),
+            post_tag: Some("</mark>".to_string()),
+            max_length: None,
+            snippet_fields: vec!["description".to_string()],
+            snippet_length: None,
+            max_snippets: None,
+        }
+    }
 
     #[test]
     fn test_doc_to_typesense_document() {
@@ -471,8 +971,11 @@ mod tests {
         let search_query = SearchQuery {
             q: Some("test query".to_string()),
             filters: vec!["category:electronics".to_string(), "price:>100".to_string()],
+            filter: None,
+            vector: None,
             sort: vec!["price:desc".to_string()],
             facets: vec!["category".to_string(), "brand".to_string()],
+            facet_requests: vec![],
             page: Some(1),
             per_page: Some(20),
             offset: Some(10),
@@ -481,18 +984,22 @@ mod tests {
                 pre_tag: Some("<mark>".to_string()),
                 post_tag: Some("</mark>".to_string()),
                 max_length: Some(200),
+                snippet_fields: vec![],
+                snippet_length: None,
+                max_snippets: None,
             }),
             config: None,
         };
 
-        let typesense_query = search_query_to_typesense_query(search_query);
+        let typesense_query = search_query_to_typesense_query(search_query).unwrap();
         assert_eq!(typesense_query.q, "test query");
         assert_eq!(
             typesense_query.filter_by,
-            Some("category:electronics && price:>100".to_string())
+            Some("(category:electronics) && (price:>100)".to_string())
         );
         assert_eq!(typesense_query.sort_by, Some("price:desc".to_string()));
         assert_eq!(typesense_query.facet_by, Some("category,brand".to_string()));
+        assert_eq!(typesense_query.max_facet_values, Some(10));
         assert_eq!(typesense_query.page, Some(1));
         assert_eq!(typesense_query.per_page, Some(20));
         assert_eq!(typesense_query.offset, Some(10));
@@ -511,13 +1018,146 @@ mod tests {
         );
     }
 
+    #[test]
+    fn native_filters_keep_their_precedence() {
+        let search_query = SearchQuery {
+            q: None,
+            filters: vec!["color:=red || color:=blue".to_string()],
+            filter: Some(FilterExpression {
+                nodes: vec![FilterNode::Eq((
+                    "in_stock".to_string(),
+                    FilterValue::Boolean(true),
+                ))],
+            }),
+            vector: None,
+            sort: vec![],
+            facets: vec![],
+            facet_requests: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        };
+
+        let typesense_query = search_query_to_typesense_query(search_query).unwrap();
+        assert_eq!(
+            typesense_query.filter_by,
+            Some("(color:=red || color:=blue) && (in_stock:=true)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_hybrid_search_to_typesense_query() {
+        let search_query = SearchQuery {
+            q: Some("noise cancelling".to_string()),
+            filters: vec![],
+            filter: Some(FilterExpression {
+                nodes: vec![FilterNode::Eq((
+                    "in_stock".to_string(),
+                    FilterValue::Boolean(true),
+                ))],
+            }),
+            vector: Some(VectorQuery {
+                field: "embedding".to_string(),
+                vector: vec![0.5, -0.25],
+                k: 20,
+                semantic_ratio: Some(0.3),
+                filter: None,
+            }),
+            sort: vec![],
+            facets: vec![],
+            facet_requests: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        };
+
+        let typesense_query = search_query_to_typesense_query(search_query.clone()).unwrap();
+        assert_eq!(
+            typesense_query.vector_query,
+            Some("embedding:([0.5,-0.25], k:20, alpha:0.3)".to_string())
+        );
+        assert_eq!(
+            typesense_query.filter_by,
+            Some("in_stock:=true".to_string())
+        );
+        assert_eq!(typesense_query.per_page, Some(20));
+
+        let vector_only = SearchQuery {
+            q: None,
+            ..search_query
+        };
+        let typesense_query = search_query_to_typesense_query(vector_only).unwrap();
+        assert_eq!(
+            typesense_query.vector_query,
+            Some("embedding:([0.5,-0.25], k:20)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_filter_to_typesense() {
+        let filter = resolve_filter(&FilterExpression {
+            nodes: vec![
+                FilterNode::And(vec![1, 2, 3]),
+                FilterNode::Not(4),
+                FilterNode::Or(vec![5, 6]),
+                FilterNode::GeoRadius(GeoRadius {
+                    field: "location".to_string(),
+                    center: GeoPoint {
+                        lat: 48.85,
+                        lon: 2.35,
+                    },
+                    radius_meters: 2500.0,
+                }),
+                FilterNode::In((
+                    "genre".to_string(),
+                    vec![
+                        FilterValue::Text("horror".to_string()),
+                        FilterValue::Text("true crime".to_string()),
+                    ],
+                )),
+                FilterNode::Range((
+                    "price".to_string(),
+                    RangeBounds {
+                        gt: None,
+                        gte: Some(FilterValue::Float(9.99)),
+                        lt: Some(FilterValue::Integer(20)),
+                        lte: None,
+                    },
+                )),
+                FilterNode::Eq(("in_stock".to_string(), FilterValue::Boolean(true))),
+            ],
+        })
+        .unwrap();
+
+        assert_eq!(
+            filter_to_typesense(&filter).unwrap(),
+            "(genre:!=[`horror`, `true crime`] && ((price:>=9.99 && price:<20) || in_stock:=true) && location:(48.85, 2.35, 2.5 km))"
+        );
+
+        let negated_range = resolve_filter(&FilterExpression {
+            nodes: vec![FilterNode::Not(1), FilterNode::Exists("isbn".to_string())],
+        })
+        .unwrap();
+        assert!(matches!(
+            filter_to_typesense(&negated_range),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
     #[test]
     fn test_search_query_with_config() {
         let search_query = SearchQuery {
             q: Some("test".to_string()),
             filters: vec![],
+            filter: None,
+            vector: None,
             sort: vec![],
             facets: vec![],
+            facet_requests: vec![],
             page: None,
             per_page: None,
             offset: None,
@@ -536,7 +1176,7 @@ mod tests {
             }),
         };
 
-        let typesense_query = search_query_to_typesense_query(search_query);
+        let typesense_query = search_query_to_typesense_query(search_query).unwrap();
         assert_eq!(
             typesense_query.include_fields,
             Some("title,price".to_string())
@@ -564,6 +1204,11 @@ mod tests {
                     facet: false,
                     sort: false,
                     index: true,
+                    array: false,
+                    stored: true,
+                    analyzer: None,
+                    locale: None,
+                    vector: None,
                 },
                 SchemaField {
                     name: "title".to_string(),
@@ -572,6 +1217,11 @@ mod tests {
                     facet: false,
                     sort: false,
                     index: true,
+                    array: false,
+                    stored: true,
+                    analyzer: None,
+                    locale: None,
+                    vector: None,
                 },
                 SchemaField {
                     name: "category".to_string(),
@@ -580,6 +1230,11 @@ mod tests {
                     facet: true,
                     sort: false,
                     index: true,
+                    array: false,
+                    stored: true,
+                    analyzer: None,
+                    locale: None,
+                    vector: None,
                 },
                 SchemaField {
                     name: "price".to_string(),
@@ -588,12 +1243,17 @@ mod tests {
                     facet: true,
                     sort: true,
                     index: false,
+                    array: false,
+                    stored: true,
+                    analyzer: None,
+                    locale: None,
+                    vector: None,
                 },
             ],
             primary_key: Some("id".to_string()),
         };
 
-        let typesense_schema = schema_to_typesense_schema(schema, "test_collection");
+        let typesense_schema = schema_to_typesense_schema(schema, "test_collection").unwrap();
         assert_eq!(typesense_schema.name, "test_collection");
         assert_eq!(typesense_schema.fields.len(), 4);
 
@@ -631,9 +1291,14 @@ mod tests {
             facet: true,
             sort: true,
             index: false,
+            array: false,
+            stored: true,
+            analyzer: None,
+            locale: None,
+            vector: None,
         };
 
-        let collection_field = schema_field_to_collection_field(field);
+        let collection_field = schema_field_to_collection_field(field).unwrap();
         assert_eq!(collection_field.name, "test_field");
         assert_eq!(collection_field.field_type, "int32");
         assert_eq!(collection_field.optional, Some(false));
@@ -642,6 +1307,110 @@ mod tests {
         assert_eq!(collection_field.index, Some(false));
     }
 
+    #[test]
+    fn test_vector_schema_field_to_collection_field() {
+        let field = SchemaField {
+            name: "embedding".to_string(),
+            field_type: FieldType::Vector,
+            required: false,
+            facet: false,
+            sort: false,
+            index: true,
+            array: false,
+            stored: true,
+            analyzer: None,
+            locale: None,
+            vector: Some(VectorOptions {
+                dimensions: 256,
+                similarity: VectorSimilarity::DotProduct,
+            }),
+        };
+
+        let collection_field = schema_field_to_collection_field(field.clone()).unwrap();
+        assert_eq!(collection_field.field_type, "float[]");
+        assert_eq!(collection_field.num_dim, Some(256));
+        assert_eq!(collection_field.vec_dist, Some("ip".to_string()));
+        assert_eq!(collection_field_to_schema_field(collection_field), field);
+
+        let euclidean = SchemaField {
+            vector: Some(VectorOptions {
+                dimensions: 256,
+                similarity: VectorSimilarity::Euclidean,
+            }),
+            ..field
+        };
+        assert!(schema_field_to_collection_field(euclidean).is_err());
+    }
+
+    #[test]
+    fn test_nested_and_array_fields_round_trip() {
+        let field = |name: &str, field_type| SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet: false,
+            sort: false,
+            index: true,
+            array: false,
+            stored: true,
+            analyzer: None,
+            locale: None,
+            vector: None,
+        };
+        let schema = Schema {
+            fields: vec![
+                SchemaField {
+                    locale: Some("ja".to_string()),
+                    ..field("title_ja", FieldType::Text)
+                },
+                SchemaField {
+                    array: true,
+                    stored: false,
+                    ..field("tags", FieldType::Text)
+                },
+                field("area", FieldType::GeoShape),
+                field("variants", FieldType::Nested),
+                SchemaField {
+                    facet: true,
+                    ..field("variants.color", FieldType::Text)
+                },
+                field("publisher", FieldType::Object),
+            ],
+            primary_key: None,
+        };
+
+        let collection = schema_to_typesense_schema(schema.clone(), "products").unwrap();
+        assert_eq!(collection.enable_nested_fields, Some(true));
+        let types = collection
+            .fields
+            .iter()
+            .map(|f| f.field_type.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![
+                "string",
+                "string[]",
+                "geopolygon",
+                "object[]",
+                "string",
+                "object"
+            ]
+        );
+        assert_eq!(collection.fields[0].locale, Some("ja".to_string()));
+        assert_eq!(collection.fields[1].store, Some(false));
+        assert_eq!(
+            _typesense_schema_to_schema(collection).fields,
+            schema.fields
+        );
+
+        let analyzed = SchemaField {
+            analyzer: Some("whitespace".to_string()),
+            ..field("title", FieldType::Text)
+        };
+        assert!(schema_field_to_collection_field(analyzed).is_err());
+    }
+
     #[test]
     fn test_collection_field_to_schema_field() {
         let collection_field = CollectionField {
@@ -651,6 +1420,10 @@ mod tests {
             index: Some(false),
             sort: Some(true),
             optional: Some(false),
+            num_dim: None,
+            vec_dist: None,
+            locale: None,
+            store: None,
         };
 
         let schema_field = collection_field_to_schema_field(collection_field);
@@ -674,6 +1447,10 @@ mod tests {
                     index: Some(true),
                     sort: Some(false),
                     optional: Some(true),
+                    num_dim: None,
+                    vec_dist: None,
+                    locale: None,
+                    store: None,
                 },
                 CollectionField {
                     name: "price".to_string(),
@@ -682,6 +1459,10 @@ mod tests {
                     index: Some(false),
                     sort: Some(true),
                     optional: Some(false),
+                    num_dim: None,
+                    vec_dist: None,
+                    locale: None,
+                    store: None,
                 },
             ],
             default_sorting_field: Some("price".to_string()),
@@ -706,6 +1487,54 @@ mod tests {
         assert!(price_field.sort);
     }
 
+    #[test]
+    fn test_facet_requests_to_typesense_query() {
+        let search_query = SearchQuery {
+            q: None,
+            filters: vec![],
+            filter: None,
+            vector: None,
+            sort: vec![],
+            facets: vec!["category".to_string()],
+            facet_requests: vec![
+                FacetRequest::Terms(TermsFacet {
+                    field: "brand".to_string(),
+                    size: Some(25),
+                    order: FacetOrder::Count,
+                }),
+                FacetRequest::Stats("price".to_string()),
+                FacetRequest::Stats("category".to_string()),
+            ],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        };
+
+        let typesense_query = search_query_to_typesense_query(search_query.clone()).unwrap();
+        assert_eq!(
+            typesense_query.facet_by,
+            Some("category,brand,price".to_string())
+        );
+        assert_eq!(typesense_query.max_facet_values, Some(25));
+
+        let range = SearchQuery {
+            facet_requests: vec![FacetRequest::Range(RangeFacet {
+                field: "price".to_string(),
+                ranges: vec![FacetRange {
+                    from: Some(0.0),
+                    to: Some(10.0),
+                }],
+            })],
+            ..search_query
+        };
+        assert!(matches!(
+            search_query_to_typesense_query(range),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+
     #[test]
     fn test_typesense_response_to_search_results() {
         let mut hit1 = Map::new();
@@ -768,7 +1597,13 @@ mod tests {
             }]),
         };
 
-        let search_results = typesense_response_to_search_results(typesense_response);
+        let facets = vec![FacetRequest::Terms(TermsFacet {
+            field: "category".to_string(),
+            size: Some(1),
+            order: FacetOrder::Value,
+        })];
+        let search_results =
+            typesense_response_to_search_results(typesense_response, &facets, None);
         assert_eq!(search_results.total, Some(2));
         assert_eq!(search_results.page, Some(1));
         assert_eq!(search_results.per_page, Some(20));
@@ -779,6 +1614,9 @@ mod tests {
         assert_eq!(search_results.hits[1].score, Some(0.8));
         assert!(search_results.facets.is_some());
         assert_eq!(search_results.took_ms, Some(5));
+        assert_eq!(search_results.facet_results.len(), 1);
+        assert_eq!(search_results.facet_results[0].buckets.len(), 1);
+        assert_eq!(search_results.facet_results[0].buckets[0].value, "books");
     }
 
     #[test]
@@ -807,7 +1645,7 @@ mod tests {
             highlights: None,
         };
 
-        let search_hit = typesense_hit_to_search_hit(typesense_hit);
+        let search_hit = typesense_hit_to_search_hit(typesense_hit, None);
         assert_eq!(search_hit.id, "doc1");
         assert_eq!(search_hit.score, Some(1.5));
         assert!(search_hit.content.is_some());
@@ -836,7 +1674,7 @@ mod tests {
             highlights: None,
         };
 
-        let search_hit = typesense_hit_to_search_hit(typesense_hit);
+        let search_hit = typesense_hit_to_search_hit(typesense_hit, None);
         assert_eq!(search_hit.id, "doc1");
         assert_eq!(search_hit.score, Some(1.0));
         assert!(search_hit.content.is_some());
@@ -872,10 +1710,204 @@ mod tests {
             highlights: Some(highlights),
         };
 
-        let search_hit = typesense_hit_to_search_hit(typesense_hit);
+        let search_hit = typesense_hit_to_search_hit(typesense_hit, Some(&highlight_config()));
         assert_eq!(search_hit.id, "doc1");
+        assert_eq!(search_hit.highlight_fields.len(), 1);
+        assert_eq!(search_hit.highlight_fields[0].field, "title");
+        assert_eq!(
+            search_hit.highlight_fields[0].fragments[0].text,
+            "Test Document"
+        );
         assert!(search_hit.highlights.is_some());
         let highlights_str = search_hit.highlights.unwrap();
         assert!(highlights_str.contains("Test <mark>Document</mark>"));
     }
+
+    #[test]
+    fn test_typesense_highlight_to_field_highlights() {
+        let mut document = Map::new();
+        document.insert("id".to_string(), Value::String("doc1".to_string()));
+
+        let highlight = serde_json::json!({
+            "title": {
+                "snippet": "<mark>Dune</mark> Messiah",
+                "value": "<mark>Dune</mark> Messiah",
+                "matched_tokens": ["Dune"]
+            },
+            "description": {
+                "snippet": "the sequel to <mark>Dune</mark>, set twelve years",
+                "value": "Frank Herbert wrote the sequel to <mark>Dune</mark>, set twelve years later",
+                "matched_tokens": ["Dune"]
+            },
+            "tags": [
+                { "snippet": "<mark>dune</mark>", "matched_tokens": ["dune"] },
+                { "snippet": "classic", "matched_tokens": [] }
+            ]
+        });
+
+        let typesense_hit = TypesenseSearchHit {
+            document,
+            text_match: None,
+            text_match_info: None,
+            highlight: highlight.as_object().cloned(),
+            highlights: None,
+        };
+
+        let search_hit = typesense_hit_to_search_hit(typesense_hit, Some(&highlight_config()));
+        let fields = &search_hit.highlight_fields;
+        let field = |name: &str| fields.iter().find(|f| f.field == name).unwrap();
+        assert_eq!(
+            field("title").fragments[0].matches[0],
+            HighlightMatch { start: 0, end: 4 }
+        );
+        assert_eq!(
+            field("description").fragments[0].text,
+            "the sequel to Dune, set twelve years"
+        );
+        assert_eq!(field("tags").fragments.len(), 2);
+        assert!(field("tags").fragments[1].matches.is_empty());
+    }
+
+    #[test]
+    fn test_typesense_aliases_to_index_aliases() {
+        let response: ListAliasesResponse = serde_json::from_value(serde_json::json!({
+            "aliases": [
+                { "name": "library", "collection_name": "books-v1" },
+                { "name": "books", "collection_name": "books-v2" }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            typesense_aliases_to_index_aliases(response),
+            vec![
+                IndexAlias {
+                    alias: "books".to_string(),
+                    index: "books-v2".to_string(),
+                },
+                IndexAlias {
+                    alias: "library".to_string(),
+                    index: "books-v1".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_results_to_outcomes() {
+        let items: Vec<ImportResult> = [
+            r#"{"success": true}"#,
+            r#"{"success": false, "error": "Field `year` must be an int32.", "code": 400, "document": "{}"}"#,
+            r#"{"success": false, "error": "Not Ready or Lagging", "code": 503}"#,
+        ]
+        .iter()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+        let outcomes = import_results_to_outcomes(&items);
+        assert_eq!(
+            outcomes,
+            vec![
+                ItemOutcome::success(),
+                ItemOutcome::failure("Field `year` must be an int32.", false),
+                ItemOutcome::failure("Not Ready or Lagging", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schema_diffs_to_typesense_fields() {
+        let field = |name: &str, field_type| SchemaField {
+            name: name.to_string(),
+            field_type,
+            required: false,
+            facet: false,
+            sort: false,
+            index: true,
+            array: false,
+            stored: true,
+            analyzer: None,
+            locale: None,
+            vector: None,
+        };
+        let diff = |kind, current: Option<SchemaField>, target: Option<SchemaField>| SchemaDiff {
+            kind,
+            field: current
+                .as_ref()
+                .or(target.as_ref())
+                .map(|field| field.name.clone())
+                .unwrap(),
+            current,
+            target,
+        };
+
+        let diffs = vec![
+            diff(
+                SchemaChangeKind::FieldAdded,
+                None,
+                Some(field("isbn", FieldType::Keyword)),
+            ),
+            diff(
+                SchemaChangeKind::FieldTypeChanged,
+                Some(field("year", FieldType::Text)),
+                Some(field("year", FieldType::Integer)),
+            ),
+            // Keywords are stored as strings, like the text they are read back as
+            diff(
+                SchemaChangeKind::FieldTypeChanged,
+                Some(field("genre", FieldType::Text)),
+                Some(field("genre", FieldType::Keyword)),
+            ),
+            diff(
+                SchemaChangeKind::FieldRemoved,
+                Some(field("legacy", FieldType::Boolean)),
+                None,
+            ),
+        ];
+
+        let fields = schema_diffs_to_typesense_fields(&diffs).unwrap();
+        let summary = fields
+            .iter()
+            .map(|field| {
+                (
+                    field["name"].as_str().unwrap(),
+                    field.get("type").and_then(|t| t.as_str()),
+                    field.get("drop").is_some(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("isbn", Some("string"), false),
+                ("year", None, true),
+                ("year", Some("int32"), false),
+                ("legacy", None, true),
+            ]
+        );
+
+        let vector_changed = |dimensions| SchemaDiff {
+            kind: SchemaChangeKind::VectorOptionsChanged,
+            field: "embedding".to_string(),
+            current: Some(SchemaField {
+                vector: Some(VectorOptions {
+                    dimensions: 3,
+                    similarity: VectorSimilarity::Cosine,
+                }),
+                ..field("embedding", FieldType::Vector)
+            }),
+            target: Some(SchemaField {
+                vector: Some(VectorOptions {
+                    dimensions,
+                    similarity: VectorSimilarity::DotProduct,
+                }),
+                ..field("embedding", FieldType::Vector)
+            }),
+        };
+        assert_eq!(classify_typesense_change(&vector_changed(3)), in_place());
+        assert_eq!(
+            classify_typesense_change(&vector_changed(4)).application,
+            SchemaChangeApplication::NeedsReindex
+        );
+    }
 }
@@ -1,27 +1,34 @@
 use crate::client::{CollectionField, CollectionSchema, TypesenseSearchApi};
 use crate::conversions::*;
 use golem_rust::wasm_rpc::Pollable;
+use golem_search::bulk::{
+    doc_chunks, id_chunks, send_converted, send_with_retries, write_chunks, ItemOutcome,
+};
 use golem_search::config::with_config_keys;
 use golem_search::durability::{DurableSearch, ExtendedGuest};
+use golem_search::facets::facet_requests;
 use golem_search::golem::search::core::{Guest, GuestSearchStream, SearchStream};
 use golem_search::golem::search::types::{
-    Doc, DocumentId, IndexName, Schema, SearchError, SearchHit, SearchQuery, SearchResults,
+    BulkResult, Doc, DocumentId, FilterExpression, FilterNode, FilterValue, IndexAlias, IndexName,
+    ReindexResult, ReindexTransform, Schema, SchemaChange, SchemaUpdateMode, SearchError,
+    SearchHit, SearchQuery, SearchResults, TaskId, TaskStatus, WriteConsistency,
 };
+use golem_search::schema::{check_in_place, diff_schemas, plan_schema_change, reindex_to_schema};
 use log::trace;
-use std::cell::{Cell, RefCell};
+use std::cell::Cell;
 
 mod client;
 mod conversions;
 
-/// Simple search stream implementation for Typesense
-/// Since Typesense doesn't have native streaming, we implement pagination-based streaming
+/// Pagination-based search stream, as Typesense has neither cursors nor points in time.
+/// Its cursor is the offset of the next page, so that a resumed stream continues exactly after
+/// the hits returned so far.
 struct TypesenseSearchStream {
     client: TypesenseSearchApi,
     index_name: String,
     query: SearchQuery,
-    current_page: Cell<u32>,
+    next_offset: Cell<Option<u32>>,
     finished: Cell<bool>,
-    last_response: RefCell<Option<SearchResults>>,
 }
 
 impl TypesenseSearchStream {
@@ -29,10 +36,9 @@ impl TypesenseSearchStream {
         Self {
             client,
             index_name,
-            query: query.clone(),
-            current_page: Cell::new(query.page.unwrap_or(1)),
+            query,
+            next_offset: Cell::new(None),
             finished: Cell::new(false),
-            last_response: RefCell::new(None),
         }
     }
 
@@ -40,6 +46,56 @@ impl TypesenseSearchStream {
         // For non-streaming APIs, return an immediately ready pollable
         golem_rust::bindings::wasi::clocks::monotonic_clock::subscribe_duration(0)
     }
+
+    fn next_offset(&self) -> Option<u32> {
+        self.next_offset.get()
+    }
+
+    /// Returns the next page of hits, an empty page once all the hits have been returned, or the
+    /// error which ended the stream
+    fn next_page(&self) -> Result<Vec<SearchHit>, SearchError> {
+        if self.finished.get() {
+            return Ok(vec![]);
+        }
+
+        let result = self.read_page();
+        if result.is_err() {
+            self.finished.set(true);
+        }
+        result
+    }
+
+    fn read_page(&self) -> Result<Vec<SearchHit>, SearchError> {
+        // Prepare query for the current offset, the pages of the query are not used
+        let mut search_query = self.query.clone();
+        let current_offset = self
+            .next_offset
+            .get()
+            .unwrap_or(self.query.offset.unwrap_or(0));
+        let per_page = self.query.per_page.unwrap_or(20);
+        search_query.page = None;
+        search_query.offset = Some(current_offset);
+        search_query.per_page = Some(per_page);
+
+        let typesense_query = search_query_to_typesense_query(search_query)?;
+        let response = self.client.search(&self.index_name, &typesense_query)?;
+        let search_results =
+            typesense_response_to_search_results(response, &[], self.query.highlight.as_ref());
+
+        let received_count = search_results.hits.len() as u32;
+        self.next_offset.set(Some(current_offset + received_count));
+
+        if received_count < per_page {
+            self.finished.set(true);
+        }
+        if let Some(total) = search_results.total {
+            if current_offset + received_count >= total {
+                self.finished.set(true);
+            }
+        }
+
+        Ok(search_results.hits)
+    }
 }
 
 struct TypesenseComponent;
@@ -66,41 +122,10 @@ impl TypesenseComponent {
 
 impl GuestSearchStream for TypesenseSearchStream {
     fn get_next(&self) -> Option<Vec<SearchHit>> {
-        if self.finished.get() {
-            return Some(vec![]);
-        }
-
-        // Prepare query for current page
-        let mut search_query = self.query.clone();
-        search_query.page = Some(self.current_page.get());
-
-        let typesense_query = search_query_to_typesense_query(search_query);
-
-        match self.client.search(&self.index_name, &typesense_query) {
-            Ok(response) => {
-                let search_results = typesense_response_to_search_results(response);
-
-                let current_page = self.current_page.get();
-                let per_page = self.query.per_page.unwrap_or(20);
-                let total_pages = if let Some(total) = search_results.total {
-                    total.div_ceil(per_page) // Ceiling division
-                } else {
-                    current_page + 1
-                };
-
-                if current_page >= total_pages || search_results.hits.is_empty() {
-                    self.finished.set(true);
-                }
-
-                self.current_page.set(current_page + 1);
-
-                let hits = search_results.hits.clone();
-                *self.last_response.borrow_mut() = Some(search_results);
-
-                Some(hits)
-            }
-            Err(_e) => {
-                self.finished.set(true);
+        match self.next_page() {
+            Ok(hits) => Some(hits),
+            Err(e) => {
+                trace!("Search stream failed: {e:?}");
                 Some(vec![])
             }
         }
@@ -114,11 +139,18 @@ impl GuestSearchStream for TypesenseSearchStream {
 impl Guest for TypesenseComponent {
     type SearchStream = TypesenseSearchStream;
 
-    fn create_index(name: IndexName, schema: Option<Schema>) -> Result<(), SearchError> {
+    // Typesense applies writes synchronously, and they are visible once acknowledged, so every
+    // write consistency is met without tracking a task
+    fn create_index(
+        name: IndexName,
+        schema: Option<Schema>,
+        _consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
         let client = Self::create_client()?;
 
         let typesense_schema = schema
             .map(|s| schema_to_typesense_schema(s, &name))
+            .transpose()?
             .unwrap_or_else(|| CollectionSchema {
                 name: name.clone(),
                 fields: vec![CollectionField {
@@ -128,6 +160,10 @@ impl Guest for TypesenseComponent {
                     index: Some(true),
                     sort: Some(false),
                     optional: Some(false),
+                    num_dim: None,
+                    vec_dist: None,
+                    locale: None,
+                    store: None,
                 }],
                 default_sorting_field: None,
                 enable_nested_fields: None,
@@ -136,13 +172,16 @@ impl Guest for TypesenseComponent {
             });
 
         client.create_collection(&name, &typesense_schema)?;
-        Ok(())
+        Ok(None)
     }
 
-    fn delete_index(name: IndexName) -> Result<(), SearchError> {
+    fn delete_index(
+        name: IndexName,
+        _consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
         let client = Self::create_client()?;
         client.delete_collection(&name)?;
-        Ok(())
+        Ok(None)
     }
 
     fn list_indexes() -> Result<Vec<IndexName>, SearchError> {
@@ -155,48 +194,136 @@ impl Guest for TypesenseComponent {
             .collect())
     }
 
-    fn upsert(index: IndexName, doc: Doc) -> Result<(), SearchError> {
+    fn create_alias(alias: IndexName, index: IndexName) -> Result<(), SearchError> {
         let client = Self::create_client()?;
-        let typesense_doc = doc_to_typesense_document(doc).map_err(SearchError::Internal)?;
-        client.upsert_document(&index, &typesense_doc)?;
+        client.upsert_alias(&alias, &index)?;
         Ok(())
     }
 
-    fn upsert_many(index: IndexName, docs: Vec<Doc>) -> Result<(), SearchError> {
+    fn swap_alias(alias: IndexName, index: IndexName) -> Result<(), SearchError> {
+        // Typesense switches an existing alias to the new collection atomically
         let client = Self::create_client()?;
-        let typesense_docs: Result<Vec<_>, _> = docs
-            .iter()
-            .map(|doc| doc_to_typesense_document(doc.clone()))
-            .collect();
-        let typesense_docs = typesense_docs.map_err(SearchError::Internal)?;
-        client.index_documents(&index, &typesense_docs)?;
+        client.upsert_alias(&alias, &index)?;
         Ok(())
     }
 
-    fn delete(index: IndexName, id: DocumentId) -> Result<(), SearchError> {
+    fn list_aliases() -> Result<Vec<IndexAlias>, SearchError> {
         let client = Self::create_client()?;
-        client.delete_document(&index, &id)?;
-        Ok(())
+        let response = client.list_aliases()?;
+        Ok(typesense_aliases_to_index_aliases(response))
     }
 
-    fn delete_many(index: IndexName, ids: Vec<DocumentId>) -> Result<(), SearchError> {
+    fn delete_alias(alias: IndexName) -> Result<(), SearchError> {
         let client = Self::create_client()?;
-        // Typesense doesn't have bulk delete by IDs, so we use filter_by
-        let filter = format!("id:[{}]", ids.join(","));
-        client.delete_documents_by_query(&index, &filter)?;
+        client.delete_alias(&alias)?;
         Ok(())
     }
 
+    fn reindex(
+        source: IndexName,
+        target: IndexName,
+        alias: Option<IndexName>,
+        transform: Option<ReindexTransform>,
+    ) -> Result<ReindexResult, SearchError> {
+        golem_search::reindex::reindex::<Self>(source, target, alias, transform, Self::next_page)
+    }
+
+    fn upsert(
+        index: IndexName,
+        doc: Doc,
+        _consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
+        let client = Self::create_client()?;
+        let typesense_doc = doc_to_typesense_document(doc).map_err(SearchError::Internal)?;
+        client.upsert_document(&index, &typesense_doc)?;
+        Ok(None)
+    }
+
+    fn upsert_many(
+        index: IndexName,
+        docs: Vec<Doc>,
+        _consistency: WriteConsistency,
+    ) -> Result<BulkResult, SearchError> {
+        let client = Self::create_client()?;
+        let limits = Self::bulk_limits();
+
+        write_chunks(
+            doc_chunks(docs, &limits),
+            |doc| doc.id.as_str(),
+            |docs| {
+                send_with_retries(
+                    docs,
+                    |doc| doc.id.as_str(),
+                    &limits,
+                    |docs| {
+                        send_converted(
+                            docs,
+                            |doc| doc_to_typesense_document(doc.clone()),
+                            |typesense_docs| {
+                                let response = client.index_documents(&index, typesense_docs)?;
+                                Ok(import_results_to_outcomes(&response.items))
+                            },
+                        )
+                    },
+                )
+            },
+        )
+    }
+
+    fn delete(
+        index: IndexName,
+        id: DocumentId,
+        _consistency: WriteConsistency,
+    ) -> Result<Option<TaskId>, SearchError> {
+        let client = Self::create_client()?;
+        client.delete_document(&index, &id)?;
+        Ok(None)
+    }
+
+    fn delete_many(
+        index: IndexName,
+        ids: Vec<DocumentId>,
+        _consistency: WriteConsistency,
+    ) -> Result<BulkResult, SearchError> {
+        let client = Self::create_client()?;
+        let limits = Self::bulk_limits();
+
+        write_chunks(
+            id_chunks(ids, &limits),
+            |id| id.as_str(),
+            |ids| {
+                send_with_retries(
+                    ids,
+                    |id| id.as_str(),
+                    &limits,
+                    |ids| {
+                        // Typesense doesn't have bulk delete by IDs, so we use filter_by. Deleting by
+                        // filter doesn't report the outcome of each document.
+                        let filter = format!("id:[{}]", ids.join(","));
+                        client.delete_documents_by_query(&index, &filter)?;
+                        Ok(vec![ItemOutcome::success(); ids.len()])
+                    },
+                )
+            },
+        )
+    }
+
     fn get(index: IndexName, id: DocumentId) -> Result<Option<Doc>, SearchError> {
         let client = Self::create_client()?;
 
         // Typesense doesn't have a direct get document endpoint
         // We need to search for the specific document by ID using a filter-only search
         let query = SearchQuery {
-            q: Some("*".to_string()),             // Match all documents
-            filters: vec![format!("id:={}", id)], // Then filter by exact ID match
+            q: Some("*".to_string()), // Match all documents
+            filters: vec![],
+            // Then filter by exact ID match
+            filter: Some(FilterExpression {
+                nodes: vec![FilterNode::Eq(("id".to_string(), FilterValue::Text(id)))],
+            }),
+            vector: None,
             sort: vec![],
             facets: vec![],
+            facet_requests: vec![],
             page: Some(1),
             per_page: Some(1),
             offset: None,
@@ -204,9 +331,9 @@ impl Guest for TypesenseComponent {
             config: None,
         };
 
-        let typesense_query = search_query_to_typesense_query(query);
+        let typesense_query = search_query_to_typesense_query(query)?;
         let response = client.search(&index, &typesense_query)?;
-        let results = typesense_response_to_search_results(response);
+        let results = typesense_response_to_search_results(response, &[], None);
 
         Ok(results.hits.into_iter().next().map(|hit| Doc {
             id: hit.id,
@@ -216,13 +343,21 @@ impl Guest for TypesenseComponent {
 
     fn search(index: IndexName, query: SearchQuery) -> Result<SearchResults, SearchError> {
         let client = Self::create_client()?;
-        let typesense_query = search_query_to_typesense_query(query);
+        let facets = facet_requests(&query)?;
+        let highlight = query.highlight.clone();
+        let typesense_query = search_query_to_typesense_query(query)?;
         let response = client.search(&index, &typesense_query)?;
-        Ok(typesense_response_to_search_results(response))
+        Ok(typesense_response_to_search_results(
+            response,
+            &facets,
+            highlight.as_ref(),
+        ))
     }
 
     fn stream_search(index: IndexName, query: SearchQuery) -> Result<SearchStream, SearchError> {
         let client = Self::create_client()?;
+        // Rejects invalid filters before the stream is created
+        search_query_to_typesense_query(query.clone())?;
 
         let stream = TypesenseSearchStream::new(client, index, query);
 
@@ -271,22 +406,54 @@ impl Guest for TypesenseComponent {
         Ok(schema)
     }
 
-    fn update_schema(index: IndexName, schema: Schema) -> Result<(), SearchError> {
-        // Typesense doesn't support updating schema after collection creation
-        // We need to delete and recreate the collection
+    fn plan_schema_change(
+        index: IndexName,
+        schema: Schema,
+    ) -> Result<Vec<SchemaChange>, SearchError> {
+        let current = Self::get_schema(index)?;
+        Ok(plan_schema_change(
+            &current,
+            &schema,
+            classify_typesense_change,
+        ))
+    }
+
+    fn update_schema(
+        index: IndexName,
+        schema: Schema,
+        _consistency: WriteConsistency,
+        mode: SchemaUpdateMode,
+    ) -> Result<Option<TaskId>, SearchError> {
+        if let SchemaUpdateMode::Reindex(reindex) = mode {
+            return reindex_to_schema::<Self>(index, schema, reindex);
+        }
+
         let client = Self::create_client()?;
+        let current = match Self::get_schema(index.clone()) {
+            Ok(current) => current,
+            Err(SearchError::IndexNotFound) => {
+                let typesense_schema = schema_to_typesense_schema(schema, &index)?;
+                client.create_collection(&index, &typesense_schema)?;
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        };
 
-        let collections = client.list_collections()?;
-        let exists = collections.0.iter().any(|c| c.name == index);
+        check_in_place(Ok(plan_schema_change(
+            &current,
+            &schema,
+            classify_typesense_change,
+        )))?;
 
-        if exists {
-            client.delete_collection(&index)?;
+        let fields = schema_diffs_to_typesense_fields(&diff_schemas(&current, &schema))?;
+        if !fields.is_empty() {
+            client.update_collection(&index, &fields)?;
         }
+        Ok(None)
+    }
 
-        let typesense_schema = schema_to_typesense_schema(schema, &index);
-        client.create_collection(&index, &typesense_schema)?;
-
-        Ok(())
+    fn task_status(_index: IndexName, _task_id: TaskId) -> Result<TaskStatus, SearchError> {
+        Err(SearchError::Unsupported)
     }
 }
 
@@ -299,11 +466,14 @@ impl ExtendedGuest for TypesenseComponent {
         let simplified_query = SearchQuery {
             q: query.q,
             filters: query.filters,
+            filter: query.filter,
+            vector: query.vector,
             sort: query.sort,
             facets: query.facets,
+            facet_requests: query.facet_requests,
             page: Some(1),
             per_page: query.per_page.or(Some(20)),
-            offset: None,
+            offset: query.offset,
             highlight: None,
             config: query.config,
         };
@@ -311,6 +481,18 @@ impl ExtendedGuest for TypesenseComponent {
         TypesenseSearchStream::new(client, index, simplified_query)
     }
 
+    fn resumed_stream(index: IndexName, query: SearchQuery, cursor: String) -> Self::SearchStream {
+        let stream = Self::unwrapped_stream(index, query);
+        if let Ok(next_offset) = cursor.parse() {
+            stream.next_offset.set(Some(next_offset));
+        }
+        stream
+    }
+
+    fn stream_cursor(stream: &Self::SearchStream) -> Option<String> {
+        stream.next_offset().map(|offset| offset.to_string())
+    }
+
     fn retry_query(original_query: &SearchQuery, partial_hits: &[SearchHit]) -> SearchQuery {
         trace!(
             "[DEBUG] retry_query called with {} partial hits",
@@ -331,6 +513,10 @@ impl ExtendedGuest for TypesenseComponent {
     fn subscribe(stream: &Self::SearchStream) -> Pollable {
         stream.subscribe()
     }
+
+    fn next_page(stream: &Self::SearchStream) -> Result<Vec<SearchHit>, SearchError> {
+        stream.next_page()
+    }
 }
 
 type DurableTypesenseComponent = DurableSearch<TypesenseComponent>;
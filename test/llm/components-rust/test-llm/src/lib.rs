@@ -45,9 +45,16 @@ impl Guest for Component {
             temperature: Some(0.2),
             max_tokens: None,
             stop_sequences: None,
+            top_p: None,
+            top_k: None,
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            repetition_penalty: None,
             tools: vec![],
             tool_choice: None,
             provider_options: vec![],
+            credentials: None,
         };
 
         println!("Sending request to LLM...");
@@ -108,6 +115,12 @@ impl Guest for Component {
             temperature: Some(0.2),
             max_tokens: None,
             stop_sequences: None,
+            top_p: None,
+            top_k: None,
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            repetition_penalty: None,
             tools: vec![llm::ToolDefinition {
                 name: "test-tool".to_string(),
                 description: Some("Test tool for generating test values".to_string()),
@@ -128,6 +141,7 @@ impl Guest for Component {
             }],
             tool_choice: Some("auto".to_string()),
             provider_options: vec![],
+            credentials: None,
         };
 
         let input = vec![
@@ -218,9 +232,16 @@ impl Guest for Component {
             temperature: Some(0.2),
             max_tokens: None,
             stop_sequences: None,
+            top_p: None,
+            top_k: None,
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            repetition_penalty: None,
             tools: vec![],
             tool_choice: None,
             provider_options: vec![],
+            credentials: None,
         };
 
         println!("Starting streaming request to LLM...");
@@ -276,6 +297,12 @@ impl Guest for Component {
             temperature: Some(0.2),
             max_tokens: None,
             stop_sequences: None,
+            top_p: None,
+            top_k: None,
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            repetition_penalty: None,
             tools: vec![llm::ToolDefinition {
                 name: "test-tool".to_string(),
                 description: Some("Test tool for generating test values".to_string()),
@@ -296,6 +323,7 @@ impl Guest for Component {
             }],
             tool_choice: Some("auto".to_string()),
             provider_options: vec![],
+            credentials: None,
         };
 
         let input = vec![
@@ -355,9 +383,16 @@ impl Guest for Component {
             temperature: None,
             max_tokens: None,
             stop_sequences: None,
+            top_p: None,
+            top_k: None,
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            repetition_penalty: None,
             tools: vec![],
             tool_choice: None,
             provider_options: vec![],
+            credentials: None,
         };
 
         println!("Sending request to LLM...");
@@ -431,9 +466,16 @@ impl Guest for Component {
             temperature: Some(0.2),
             max_tokens: None,
             stop_sequences: None,
+            top_p: None,
+            top_k: None,
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            repetition_penalty: None,
             tools: vec![],
             tool_choice: None,
             provider_options: vec![],
+            credentials: None,
         };
 
         println!("Starting streaming request to LLM...");
@@ -529,9 +571,16 @@ impl Guest for Component {
             temperature: None,
             max_tokens: None,
             stop_sequences: None,
+            top_p: None,
+            top_k: None,
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            repetition_penalty: None,
             tools: vec![],
             tool_choice: None,
             provider_options: vec![],
+            credentials: None,
         };
 
         println!("Reading image from Initial File System...");
@@ -608,9 +657,16 @@ impl Guest for Component {
             temperature: Some(0.2),
             max_tokens: None,
             stop_sequences: None,
+            top_p: None,
+            top_k: None,
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            repetition_penalty: None,
             tools: vec![],
             tool_choice: None,
             provider_options: vec![],
+            credentials: None,
         };
 
         let mut messages = vec![llm::Message {
@@ -56,6 +56,11 @@ fn create_test_schema() -> Schema {
                 facet: false,
                 sort: false,
                 index: true,
+                array: false,
+                stored: true,
+                analyzer: None,
+                locale: None,
+                vector: None,
             },
             SchemaField {
                 name: "author".to_string(),
@@ -64,6 +69,11 @@ fn create_test_schema() -> Schema {
                 facet: true,
                 sort: false,
                 index: true,
+                array: false,
+                stored: true,
+                analyzer: None,
+                locale: None,
+                vector: None,
             },
             SchemaField {
                 name: "year".to_string(),
@@ -72,6 +82,11 @@ fn create_test_schema() -> Schema {
                 facet: false,
                 sort: true,
                 index: true,
+                array: false,
+                stored: true,
+                analyzer: None,
+                locale: None,
+                vector: None,
             },
             SchemaField {
                 name: "genre".to_string(),
@@ -80,6 +95,11 @@ fn create_test_schema() -> Schema {
                 facet: true,
                 sort: false,
                 index: true,
+                array: false,
+                stored: true,
+                analyzer: None,
+                locale: None,
+                vector: None,
             },
             SchemaField {
                 name: "description".to_string(),
@@ -88,6 +108,11 @@ fn create_test_schema() -> Schema {
                 facet: false,
                 sort: false,
                 index: true, // Enable search in description
+                array: false,
+                stored: true,
+                analyzer: None,
+                locale: None,
+                vector: None,
             },
         ],
         primary_key: Some("id".to_string()), // Set id as primary key
@@ -106,7 +131,11 @@ impl Guest for Component {
         {
             // Elasticsearch/typesense/opensearch requires a different setup for the index
             println!("Setting   index: {}", index_name);
-            match core::create_index(&index_name, Some(&create_test_schema())) {
+            match core::create_index(
+                &index_name,
+                Some(&create_test_schema()),
+                WriteConsistency::WaitForVisible,
+            ) {
                 Ok(_) => results.push("✓ Index created successfully".to_string()),
                 Err(e) => return format!("✗ Index creation failed: {:?}", e),
             }
@@ -117,7 +146,12 @@ impl Guest for Component {
 
         // Set up index schema (for providers that support schema configuration)
         println!("Setting up index : {}", index_name);
-        match core::update_schema(&index_name, &create_test_schema()) {
+        match core::update_schema(
+            &index_name,
+            &create_test_schema(),
+            WriteConsistency::WaitForVisible,
+            &SchemaUpdateMode::InPlace,
+        ) {
             Ok(_) => results.push("✓ Index schema configured successfully".to_string()),
             Err(SearchError::Unsupported) => {
                 results.push("✓ Schema configuration not required (auto-detected)".to_string())
@@ -131,14 +165,41 @@ impl Guest for Component {
         // Insert test documents (this will auto-create the index for providers like Algolia)
         let docs = create_test_documents();
         println!("Inserting {} documents", docs.len());
-        match core::upsert_many(&index_name, &docs) {
-            Ok(_) => results.push("✓ Documents inserted successfull".to_string()),
+        match core::upsert_many(&index_name, &docs, WriteConsistency::WaitForVisible) {
+            Ok(bulk_result) => {
+                results.push("✓ Documents inserted successfull".to_string());
+                for item in bulk_result.items {
+                    if let Some(error) = item.error {
+                        results.push(format!(
+                            "✗ Document {} was not inserted: {}",
+                            item.id, error
+                        ));
+                    }
+                }
+            }
             Err(e) => {
                 results.push(format!("✗ Document insertion failed: {:?}", e));
                 return results.join("\n");
             }
         }
 
+        // Test an asynchronous write, tracked with its task when the provider returns one
+        let async_doc = Doc {
+            id: "doc-async".to_string(),
+            content: r#"{"title": "Written asynchronously"}"#.to_string(),
+        };
+        match core::upsert(&index_name, &async_doc, WriteConsistency::NoWait) {
+            Ok(Some(task_id)) => match core::task_status(&index_name, &task_id) {
+                Ok(status) => results.push(format!(
+                    "✓ Asynchronous write tracked by task {}: {:?}",
+                    task_id, status
+                )),
+                Err(e) => results.push(format!("✗ Task status failed: {:?}", e)),
+            },
+            Ok(None) => results.push("✓ Asynchronous write accepted without a task".to_string()),
+            Err(e) => results.push(format!("✗ Asynchronous write failed: {:?}", e)),
+        }
+
         // Test document retrieval (with retry logic for eventual consistency)
         println!("Retrieving document with ID: doc1");
         let mut retrieval_success = false;
@@ -170,7 +231,11 @@ impl Guest for Component {
         // Test document deletion (only if we successfully retrieved it)
         if retrieval_success {
             println!("Deleting document with ID: doc1");
-            match core::delete(&index_name, &DocumentId::from("doc1")) {
+            match core::delete(
+                &index_name,
+                &DocumentId::from("doc1"),
+                WriteConsistency::WaitForVisible,
+            ) {
                 Ok(_) => {
                     results.push("✓ Document deleted successfully".to_string());
 
@@ -201,13 +266,13 @@ impl Guest for Component {
 
         // Test index deletion
         println!("Deleting index: {}", index_name);
-        match core::delete_index(&index_name) {
+        match core::delete_index(&index_name, WriteConsistency::WaitForVisible) {
             Ok(_) => results.push("✓ Index deleted successfully".to_string()),
             Err(e) => results.push(format!("✗ Index deletion failed: {:?}", e)),
         }
 
         // Cleanup
-        core::delete_index(&index_name).ok();
+        core::delete_index(&index_name, WriteConsistency::WaitForVisible).ok();
         results.join("\n")
     }
 
@@ -218,7 +283,12 @@ impl Guest for Component {
 
         // Set up index schema first (for providers that support it)
         println!("Setting  index for search tests");
-        match core::update_schema(&index_name, &create_test_schema()) {
+        match core::update_schema(
+            &index_name,
+            &create_test_schema(),
+            WriteConsistency::WaitForVisible,
+            &SchemaUpdateMode::InPlace,
+        ) {
             Ok(_) => {}
             Err(SearchError::Unsupported) => {
                 println!("Schema setup not required (auto-detected on first document)");
@@ -230,8 +300,8 @@ impl Guest for Component {
 
         // Insert test documents (this will auto-create the index)
         let docs = create_test_documents();
-        if let Err(e) = core::upsert_many(&index_name, &docs) {
-            core::delete_index(&index_name).ok(); // Cleanup
+        if let Err(e) = core::upsert_many(&index_name, &docs, WriteConsistency::WaitForVisible) {
+            core::delete_index(&index_name, WriteConsistency::WaitForVisible).ok(); // Cleanup
             return format!("Document insertion failed: {:?}", e);
         }
 
@@ -240,8 +310,11 @@ impl Guest for Component {
         let query = SearchQuery {
             q: Some("Gatsby".to_string()),
             filters: vec![],
+            filter: None,
+            vector: None,
             sort: vec![],
             facets: vec![],
+            facet_requests: vec![],
             page: None,
             per_page: None,
             offset: None,
@@ -285,93 +358,43 @@ impl Guest for Component {
             }
         }
 
-        // Test search with filters (with provider-specific syntax handling)
+        // Test search with a provider-neutral filter
         println!("Testing filtered search for fiction genre");
+        let filtered_query = SearchQuery {
+            q: Some("Gatsby".to_string()), // Use a term that will match in title
+            filters: vec![],
+            filter: Some(FilterExpression {
+                nodes: vec![FilterNode::Eq((
+                    "genre".to_string(),
+                    FilterValue::Text("fiction".to_string()),
+                ))],
+            }),
+            vector: None,
+            sort: vec![],
+            facets: vec![],
+            facet_requests: vec![],
+            page: None,
+            per_page: None,
+            offset: None,
+            highlight: None,
+            config: None,
+        };
 
-        // Try different filter syntaxes based on the provider
-        let filter_attempts = vec![
-            (
-                "Algolia/Elasticsearch/opensearch/typesense",
-                "genre:fiction",
-            ),
-            ("Meilisearch", "genre = \"fiction\""),
-            ("Alternative", "genre=\"fiction\""),
-        ];
-
-        let mut filter_success = false;
-        for (provider_hint, filter_syntax) in &filter_attempts {
-            let filtered_query = SearchQuery {
-                q: Some("Gatsby".to_string()), // Use a term that will match in title
-                filters: vec![filter_syntax.to_string()],
-                sort: vec![],
-                facets: vec![],
-                page: None,
-                per_page: None,
-                offset: None,
-                highlight: None,
-                config: None,
-            };
-
-            match core::search(&index_name, &filtered_query) {
-                Ok(search_results) => {
-                    results.push(format!(
-                        "✓ Filtered search returned {} hits (syntax: {})",
-                        search_results.hits.len(),
-                        provider_hint
-                    ));
-                    filter_success = true;
-                    break;
-                }
-                Err(SearchError::InvalidQuery(_)) => {
-                    continue;
-                }
-                Err(SearchError::Unsupported) => {
-                    results.push("⚠ Filtered search not supported by this provider".to_string());
-                    filter_success = true;
-                    break;
-                }
-                Err(e) => {
-                    // For other errors, log but continue trying
-                    if filter_attempts.iter().position(|(p, _)| p == provider_hint)
-                        == Some(filter_attempts.len() - 1)
-                    {
-                        results.push(format!(
-                            "✗ Filtered search failed with all syntaxes: {:?}",
-                            e
-                        ));
-                    }
-                }
+        match core::search(&index_name, &filtered_query) {
+            Ok(search_results) => {
+                results.push(format!(
+                    "✓ Filtered search returned {} hits",
+                    search_results.hits.len()
+                ));
             }
-        }
-
-        // If no filter syntax worked, try a fallback search without filters
-        if !filter_success {
-            println!("Falling back to text-based search for 'fiction'");
-            let fallback_query = SearchQuery {
-                q: Some("fiction".to_string()), // Search for "fiction" in text instead of using filters
-                filters: vec![],
-                sort: vec![],
-                facets: vec![],
-                page: None,
-                per_page: None,
-                offset: None,
-                highlight: None,
-                config: None,
-            };
-
-            match core::search(&index_name, &fallback_query) {
-                Ok(search_results) => {
-                    results.push(format!(
-                        "✓ Fallback text search for 'fiction' returned {} hits",
-                        search_results.hits.len()
-                    ));
-                }
-                Err(e) => results.push(format!("✗ Even fallback search failed: {:?}", e)),
+            Err(SearchError::Unsupported) => {
+                results.push("⚠ Filtered search not supported by this provider".to_string());
             }
+            Err(e) => results.push(format!("✗ Filtered search failed: {:?}", e)),
         }
 
         // Cleanup
-        core::delete_index(&index_name).ok();
+        core::delete_index(&index_name, WriteConsistency::WaitForVisible).ok();
         results.join("\n")
     }
 
@@ -385,7 +408,11 @@ impl Guest for Component {
             || TEST_INDEX == "test-opensearch-index"
         {
             println!("Setting   index: {}", index_name);
-            match core::create_index(&index_name, Some(&create_test_schema())) {
+            match core::create_index(
+                &index_name,
+                Some(&create_test_schema()),
+                WriteConsistency::WaitForVisible,
+            ) {
                 Ok(_) => results.push("✓ Index created successfully".to_string()),
                 Err(e) => return format!("✗ Index creation failed: {:?}", e),
             }
@@ -395,7 +422,12 @@ impl Guest for Component {
         }
 
         // Setup schema first
-        match core::update_schema(&index_name, &create_test_schema()) {
+        match core::update_schema(
+            &index_name,
+            &create_test_schema(),
+            WriteConsistency::WaitForVisible,
+            &SchemaUpdateMode::InPlace,
+        ) {
             Ok(_) => {}
             Err(SearchError::Unsupported) => {}
             Err(_) => {} // Continue anyway
@@ -403,8 +435,8 @@ impl Guest for Component {
 
         // Insert documents to auto-create index
         let docs = create_test_documents();
-        if let Err(e) = core::upsert_many(&index_name, &docs) {
-            core::delete_index(&index_name).ok();
+        if let Err(e) = core::upsert_many(&index_name, &docs, WriteConsistency::WaitForVisible) {
+            core::delete_index(&index_name, WriteConsistency::WaitForVisible).ok();
             return format!("Document insertion failed: {:?}", e);
         }
 
@@ -413,8 +445,11 @@ impl Guest for Component {
         let sorted_query = SearchQuery {
             q: None,
             filters: vec![],
+            filter: None,
+            vector: None,
             sort: vec!["year:desc".to_string()],
             facets: vec![],
+            facet_requests: vec![],
             page: None,
             per_page: None,
             offset: None,
@@ -441,8 +476,11 @@ impl Guest for Component {
         let paginated_query = SearchQuery {
             q: None,
             filters: vec![],
+            filter: None,
+            vector: None,
             sort: vec!["year:desc".to_string()],
             facets: vec![],
+            facet_requests: vec![],
             page: Some(1),
             per_page: Some(2),
             offset: None,
@@ -467,7 +505,7 @@ impl Guest for Component {
         }
 
         // Cleanup
-        core::delete_index(&index_name).ok();
+        core::delete_index(&index_name, WriteConsistency::WaitForVisible).ok();
         results.join("\n")
     }
 
@@ -481,7 +519,11 @@ impl Guest for Component {
             || TEST_INDEX == "test-opensearch-index"
         {
             println!("Setting   index: {}", index_name);
-            match core::create_index(&index_name, Some(&create_test_schema())) {
+            match core::create_index(
+                &index_name,
+                Some(&create_test_schema()),
+                WriteConsistency::WaitForVisible,
+            ) {
                 Ok(_) => results.push("✓ Index created successfully".to_string()),
                 Err(e) => return format!("✗ Index creation failed: {:?}", e),
             }
@@ -491,7 +533,12 @@ impl Guest for Component {
         }
 
         // Setup schema for faceting support
-        match core::update_schema(&index_name, &create_test_schema()) {
+        match core::update_schema(
+            &index_name,
+            &create_test_schema(),
+            WriteConsistency::WaitForVisible,
+            &SchemaUpdateMode::InPlace,
+        ) {
             Ok(_) => {}
             Err(SearchError::Unsupported) => {}
             Err(_) => {} // Continue anyway
@@ -499,8 +546,8 @@ impl Guest for Component {
 
         // Insert documents to auto-create index
         let docs = create_test_documents();
-        if let Err(e) = core::upsert_many(&index_name, &docs) {
-            core::delete_index(&index_name).ok();
+        if let Err(e) = core::upsert_many(&index_name, &docs, WriteConsistency::WaitForVisible) {
+            core::delete_index(&index_name, WriteConsistency::WaitForVisible).ok();
             return format!("Document insertion failed: {:?}", e);
         }
 
@@ -509,16 +556,22 @@ impl Guest for Component {
         let highlight_query = SearchQuery {
             q: Some("American".to_string()),
             filters: vec![],
+            filter: None,
+            vector: None,
             sort: vec![],
             facets: vec!["genre".to_string(), "author".to_string()],
+            facet_requests: vec![],
             page: None,
             per_page: None,
             offset: None,
             highlight: Some(HighlightConfig {
-                fields: vec!["title".to_string(), "description".to_string()],
+                fields: vec!["title".to_string()],
                 pre_tag: Some("<mark>".to_string()),
                 post_tag: Some("</mark>".to_string()),
                 max_length: Some(200),
+                snippet_fields: vec!["description".to_string()],
+                snippet_length: Some(12),
+                max_snippets: None,
             }),
             config: None,
         };
@@ -537,6 +590,20 @@ impl Guest for Component {
                         break;
                     }
                 }
+                for highlight in search_results
+                    .hits
+                    .iter()
+                    .flat_map(|hit| &hit.highlight_fields)
+                    .take(3)
+                {
+                    let matches: usize = highlight.fragments.iter().map(|f| f.matches.len()).sum();
+                    results.push(format!(
+                        "  Highlighted {}: {} fragments, {} matches",
+                        highlight.field,
+                        highlight.fragments.len(),
+                        matches
+                    ));
+                }
 
                 // Check for facets
                 if search_results.facets.is_some() {
@@ -544,6 +611,13 @@ impl Guest for Component {
                 } else {
                     results.push("  ⚠ No facet data returned (may not be supported)".to_string());
                 }
+                for facet in &search_results.facet_results {
+                    results.push(format!(
+                        "  Facet {}: {} buckets",
+                        facet.field,
+                        facet.buckets.len()
+                    ));
+                }
 
                 // Check timing information
                 if let Some(took_ms) = search_results.took_ms {
@@ -554,11 +628,11 @@ impl Guest for Component {
         }
 
         // Cleanup
-        core::delete_index(&index_name).ok();
+        core::delete_index(&index_name, WriteConsistency::WaitForVisible).ok();
         results.join("\n")
     }
 
-    /// test5 demonstrates schema inspection and validation
+    /// test5 demonstrates schema inspection and validation, and reindexing behind an alias
     fn test5() -> String {
         let index_name = format!("{}-test5", TEST_INDEX);
         let mut results = Vec::new();
@@ -568,7 +642,11 @@ impl Guest for Component {
             || TEST_INDEX == "test-opensearch-index"
         {
             println!("Setting   index: {}", index_name);
-            match core::create_index(&index_name, Some(&create_test_schema())) {
+            match core::create_index(
+                &index_name,
+                Some(&create_test_schema()),
+                WriteConsistency::WaitForVisible,
+            ) {
                 Ok(_) => results.push("✓ Index created successfully".to_string()),
                 Err(e) => return format!("✗ Index creation failed: {:?}", e),
             }
@@ -580,7 +658,12 @@ impl Guest for Component {
         // Set up initial schema
         println!("Setting up index with predefined schema");
         let original_schema = create_test_schema();
-        match core::update_schema(&index_name, &original_schema) {
+        match core::update_schema(
+            &index_name,
+            &original_schema,
+            WriteConsistency::WaitForVisible,
+            &SchemaUpdateMode::InPlace,
+        ) {
             Ok(_) => results.push("✓ Index schema configured successfully".to_string()),
             Err(SearchError::Unsupported) => {
                 results.push(
@@ -589,7 +672,9 @@ impl Guest for Component {
                 );
                 // Insert a test document to auto-create the index
                 let test_docs = vec![create_test_documents().into_iter().next().unwrap()];
-                if let Err(e) = core::upsert_many(&index_name, &test_docs) {
+                if let Err(e) =
+                    core::upsert_many(&index_name, &test_docs, WriteConsistency::WaitForVisible)
+                {
                     return format!("Document insertion failed: {:?}", e);
                 }
             }
@@ -600,7 +685,9 @@ impl Guest for Component {
                 ));
 
                 let test_docs = vec![create_test_documents().into_iter().next().unwrap()];
-                if let Err(e) = core::upsert_many(&index_name, &test_docs) {
+                if let Err(e) =
+                    core::upsert_many(&index_name, &test_docs, WriteConsistency::WaitForVisible)
+                {
                     return format!("Document insertion failed: {:?}", e);
                 }
             }
@@ -640,17 +727,134 @@ impl Guest for Component {
             facet: false,
             sort: false,
             index: true,
+            array: false,
+            stored: true,
+            analyzer: None,
+            locale: None,
+            vector: None,
         });
+        updated_schema.fields.push(SchemaField {
+            name: "tags".to_string(),
+            field_type: FieldType::Keyword,
+            required: false,
+            facet: true,
+            sort: false,
+            index: true,
+            array: true,
+            stored: true,
+            analyzer: None,
+            locale: None,
+            vector: None,
+        });
+
+        match core::plan_schema_change(&index_name, &updated_schema) {
+            Ok(changes) => {
+                results.push(format!(
+                    "✓ Schema change planned: {} changes",
+                    changes.len()
+                ));
+                for change in changes {
+                    results.push(format!(
+                        "  {} {:?}: {:?}",
+                        change.field, change.kind, change.application
+                    ));
+                }
+            }
+            Err(e) => results.push(format!("✗ Schema change planning failed: {:?}", e)),
+        }
 
-        match core::update_schema(&index_name, &updated_schema) {
+        match core::update_schema(
+            &index_name,
+            &updated_schema,
+            WriteConsistency::WaitForVisible,
+            &SchemaUpdateMode::InPlace,
+        ) {
             Ok(_) => results.push("✓ Schema updated successfully".to_string()),
             Err(SearchError::Unsupported) => {
                 results.push("  ⚠ Schema updates not supported by this provider".to_string())
             }
             Err(e) => results.push(format!("✗ Schema update failed: {:?}", e)),
         }
+
+        // Test reindexing to a new index behind an alias
+        println!("Testing reindex");
+        let target_index = format!("{}-v2", index_name);
+        let alias = format!("{}-alias", index_name);
+        if let Err(e) = core::upsert_many(
+            &index_name,
+            &create_test_documents(),
+            WriteConsistency::WaitForVisible,
+        ) {
+            return format!("Document insertion failed: {:?}", e);
+        }
+        if TEST_INDEX == "test-elasticsearch-index"
+            || TEST_INDEX == "test-typesense-index"
+            || TEST_INDEX == "test-opensearch-index"
+        {
+            if let Err(e) = core::create_index(
+                &target_index,
+                Some(&updated_schema),
+                WriteConsistency::WaitForVisible,
+            ) {
+                return format!("✗ Target index creation failed: {:?}", e);
+            }
+            if let Err(e) = core::create_alias(&alias, &index_name) {
+                results.push(format!("✗ Alias creation failed: {:?}", e));
+            }
+        } else if TEST_INDEX == "test-meilisearch-index" {
+            // Meilisearch swaps the content of the index named after the alias
+            if let Err(e) = core::create_index(&alias, None, WriteConsistency::WaitForVisible) {
+                return format!("✗ Alias index creation failed: {:?}", e);
+            }
+        }
+
+        let transform = ReindexTransform {
+            rename_fields: vec![("description".to_string(), "summary".to_string())],
+            remove_fields: vec![],
+        };
+        match core::reindex(
+            &index_name,
+            &target_index,
+            Some(alias.as_str()),
+            Some(&transform),
+        ) {
+            Ok(result) => {
+                results.push(format!("✓ Reindexed {} documents", result.copied));
+                if !result.failures.is_empty() {
+                    results.push(format!("  ✗ {} documents failed", result.failures.len()));
+                }
+                if result.alias_switched {
+                    results.push("  ✓ Alias switched to the new index".to_string());
+                }
+            }
+            Err(SearchError::Unsupported) => {
+                results.push("  ⚠ Aliases not supported by this provider".to_string())
+            }
+            Err(e) => results.push(format!("✗ Reindex failed: {:?}", e)),
+        }
+
+        match core::list_aliases() {
+            Ok(aliases) => {
+                if aliases
+                    .iter()
+                    .any(|a| a.alias == alias && a.index == target_index)
+                {
+                    results.push("  ✓ Alias points to the new index".to_string());
+                } else {
+                    results.push("  ✗ Alias not found".to_string());
+                }
+            }
+            Err(SearchError::Unsupported) => {
+                results.push("  ⚠ Listing aliases not supported by this provider".to_string())
+            }
+            Err(e) => results.push(format!("✗ Listing aliases failed: {:?}", e)),
+        }
+
         // Cleanup
-        core::delete_index(&index_name).ok();
+        core::delete_alias(&alias).ok();
+        core::delete_index(&alias, WriteConsistency::WaitForVisible).ok();
+        core::delete_index(&target_index, WriteConsistency::WaitForVisible).ok();
+        core::delete_index(&index_name, WriteConsistency::WaitForVisible).ok();
         results.join("\n")
     }
 
@@ -664,7 +868,11 @@ impl Guest for Component {
             || TEST_INDEX == "test-opensearch-index"
         {
             println!("Setting   index: {}", index_name);
-            match core::create_index(&index_name, Some(&create_test_schema())) {
+            match core::create_index(
+                &index_name,
+                Some(&create_test_schema()),
+                WriteConsistency::WaitForVisible,
+            ) {
                 Ok(_) => results.push("✓ Index created successfully".to_string()),
                 Err(e) => return format!("✗ Index creation failed: {:?}", e),
             }
@@ -673,7 +881,12 @@ impl Guest for Component {
         }
 
         // Setup schema for streaming test
-        match core::update_schema(&index_name, &create_test_schema()) {
+        match core::update_schema(
+            &index_name,
+            &create_test_schema(),
+            WriteConsistency::WaitForVisible,
+            &SchemaUpdateMode::InPlace,
+        ) {
             Ok(_) => {}
             Err(SearchError::Unsupported) => {}
             Err(_) => {} // Continue anyway
@@ -688,8 +901,8 @@ impl Guest for Component {
             });
         }
 
-        if let Err(e) = core::upsert_many(&index_name, &docs) {
-            core::delete_index(&index_name).ok();
+        if let Err(e) = core::upsert_many(&index_name, &docs, WriteConsistency::WaitForVisible) {
+            core::delete_index(&index_name, WriteConsistency::WaitForVisible).ok();
             return format!("Document insertion failed: {:?}", e);
         }
 
@@ -697,8 +910,11 @@ impl Guest for Component {
         let stream_query = SearchQuery {
             q: Some("book".to_string()),
             filters: vec![],
+            filter: None,
+            vector: None,
             sort: vec!["year:asc".to_string()],
             facets: vec![],
+            facet_requests: vec![],
             page: None,
             per_page: Some(5), // Small page size to encourage streaming
             offset: None,
@@ -712,6 +928,8 @@ impl Guest for Component {
 
                 let mut total_hits = 0;
                 let mut batch_count = 0;
+                let mut seen_ids = std::collections::HashSet::new();
+                let mut duplicate_hits = 0;
 
                 // Use a more conservative approach to streaming
                 for _ in 0..5 {
@@ -723,6 +941,11 @@ impl Guest for Component {
 
                     batch_count += 1;
                     total_hits += hits.len();
+                    for hit in &hits {
+                        if !seen_ids.insert(hit.id.clone()) {
+                            duplicate_hits += 1;
+                        }
+                    }
                     results.push(format!("  Batch {}: {} hits", batch_count, hits.len()));
                 }
 
@@ -730,13 +953,25 @@ impl Guest for Component {
                     "✓ Streamig complete: {} total hits in {} batches",
                     total_hits, batch_count
                 ));
+                if duplicate_hits == 0 {
+                    results.push("✓ No hit was returned twice across batches".to_string());
+                } else {
+                    results.push(format!(
+                        "✗ {} hits were returned twice across batches",
+                        duplicate_hits
+                    ));
+                }
             }
             Err(SearchError::Unsupported) => {
                 results.push("⚠ Streaming search not support by this provider".to_string());
                 if TEST_INDEX == "test-elasticsearch-index" {
                     // Elasticsearch requires a different setup for the index
                     println!("Setting  Elasticsearch index: {}", index_name);
-                    match core::create_index(&index_name, Some(&create_test_schema())) {
+                    match core::create_index(
+                        &index_name,
+                        Some(&create_test_schema()),
+                        WriteConsistency::WaitForVisible,
+                    ) {
                         Ok(_) => results.push("✓ Index created successfully".to_string()),
                         Err(e) => return format!("✗ Index creation failed: {:?}", e),
                     }
@@ -760,7 +995,7 @@ impl Guest for Component {
         }
 
         // Cleanup
-        core::delete_index(&index_name).ok();
+        core::delete_index(&index_name, WriteConsistency::WaitForVisible).ok();
         results.join("\n")
     }
 
@@ -779,7 +1014,7 @@ impl Guest for Component {
             || TEST_INDEX == "test-opensearch-index"
         {
             println!("Setting   index: {}", test_index);
-            match core::create_index(&test_index, Some(&schema)) {
+            match core::create_index(&test_index, Some(&schema), WriteConsistency::WaitForVisible) {
                 Ok(_) => results.push("✓ Index created successfully".to_string()),
                 Err(e) => return format!("✗ Index creation failed: {:?}", e),
             }
@@ -789,8 +1024,13 @@ impl Guest for Component {
         }
 
         // Test schema operations that might not be supported
-        match core::update_schema(&test_index, &schema) {
-            Ok(()) => results.push("✓ Schema update supported and successful".to_string()),
+        match core::update_schema(
+            &test_index,
+            &schema,
+            WriteConsistency::WaitForVisible,
+            &SchemaUpdateMode::InPlace,
+        ) {
+            Ok(_) => results.push("✓ Schema update supported and successful".to_string()),
             Err(SearchError::Unsupported) => {
                 results.push("✓ Schema update gracefully reports as unsupported".to_string())
             }
@@ -801,8 +1041,11 @@ impl Guest for Component {
         let advanced_query = SearchQuery {
             q: Some("test".to_string()),
             filters: vec!["complex_filter:value AND nested.field:value".to_string()],
+            filter: None,
+            vector: None,
             sort: vec!["complex_sort:desc".to_string()],
             facets: vec!["facet1".to_string(), "facet2".to_string()],
+            facet_requests: vec![],
             page: Some(1),
             per_page: Some(10),
             offset: Some(0),
@@ -811,6 +1054,9 @@ impl Guest for Component {
                 pre_tag: Some("<em>".to_string()),
                 post_tag: Some("</em>".to_string()),
                 max_length: Some(150),
+                snippet_fields: vec![],
+                snippet_length: None,
+                max_snippets: None,
             }),
             config: None,
         };
@@ -847,20 +1093,47 @@ impl Guest for Component {
             content: r#"{"invalid": json, "malformed": true"#.to_string(), // Missing closing brace, invalid syntax
         };
 
-        match core::upsert(&test_index, &invalid_doc) {
-            Ok(()) => results.push("⚠ Invalid JSON was accepted (lenient validation)".to_string()),
+        match core::upsert(&test_index, &invalid_doc, WriteConsistency::WaitForVisible) {
+            Ok(_) => results.push("⚠ Invalid JSON was accepted (lenient validation)".to_string()),
             Err(SearchError::InvalidQuery(msg)) => {
                 results.push(format!("✓ Invalid JSON rejected: {}", msg))
             }
             Err(e) => results.push(format!("✓ Invalid input handled with error: {:?}", e)),
         }
 
+        // Test a bulk write mixing valid and malformed documents
+        let mixed_docs = vec![
+            Doc {
+                id: "bulk-valid".to_string(),
+                content: r#"{"title": "Valid"}"#.to_string(),
+            },
+            invalid_doc.clone(),
+        ];
+
+        match core::upsert_many(&test_index, &mixed_docs, WriteConsistency::WaitForVisible) {
+            Ok(bulk_result) => {
+                for item in bulk_result.items {
+                    match item.error {
+                        None => results.push(format!("✓ Bulk document {} written", item.id)),
+                        Some(error) => results.push(format!(
+                            "✓ Bulk document {} rejected on its own: {}",
+                            item.id, error
+                        )),
+                    }
+                }
+            }
+            Err(e) => results.push(format!("✓ Mixed bulk write handled: {:?}", e)),
+        }
+
         // Test with invalid query syntax
         let invalid_query = SearchQuery {
             q: Some("((unclosed parenthesis AND malformed:".to_string()),
             filters: vec!["invalid_filter_syntax:::".to_string()],
+            filter: None,
+            vector: None,
             sort: vec!["invalid_sort_field:invalid_direction".to_string()],
             facets: vec![],
+            facet_requests: vec![],
             page: Some(0),     // Invalid page number
             per_page: Some(0), // Invalid page size
             offset: None,
@@ -897,8 +1170,12 @@ impl Guest for Component {
         }
 
         // Test deleting non-existent document
-        match core::delete(&nonexistent_index, &DocumentId::from("non-existent-doc")) {
-            Ok(()) => {
+        match core::delete(
+            &nonexistent_index,
+            &DocumentId::from("non-existent-doc"),
+            WriteConsistency::WaitForVisible,
+        ) {
+            Ok(_) => {
                 results.push("✓ Deleting non-existent document succeeds (idempotent)".to_string())
             }
             Err(SearchError::IndexNotFound) => {
@@ -928,8 +1205,8 @@ impl Guest for Component {
             content: "{}".to_string(),
         };
 
-        match core::upsert(&test_index, &empty_doc) {
-            Ok(()) => results.push("✓ Empty document accepted".to_string()),
+        match core::upsert(&test_index, &empty_doc, WriteConsistency::WaitForVisible) {
+            Ok(_) => results.push("✓ Empty document accepted".to_string()),
             Err(e) => results.push(format!("✓ Empty document  handled: {:?}", e)),
         }
 
@@ -939,8 +1216,8 @@ impl Guest for Component {
             content: r#"{"test": "value"}"#.to_string(),
         };
 
-        match core::upsert(&test_index, &long_id_doc) {
-            Ok(()) => results.push("✓ Long document ID accepted".to_string()),
+        match core::upsert(&test_index, &long_id_doc, WriteConsistency::WaitForVisible) {
+            Ok(_) => results.push("✓ Long document ID accepted".to_string()),
             Err(SearchError::InvalidQuery(msg)) => {
                 results.push(format!("✓ Long ID rejected: {}", msg))
             }
@@ -951,8 +1228,11 @@ impl Guest for Component {
         let empty_query = SearchQuery {
             q: Some("".to_string()),
             filters: vec![],
+            filter: None,
+            vector: None,
             sort: vec![],
             facets: vec![],
+            facet_requests: vec![],
             page: None,
             per_page: None,
             offset: None,
@@ -979,11 +1259,20 @@ impl Guest for Component {
             ("list_indexes", core::list_indexes().is_ok()),
             (
                 "create_index",
-                core::create_index(&IndexName::from("test-create"), Some(&schema.clone())).is_ok(),
+                core::create_index(
+                    &IndexName::from("test-create"),
+                    Some(&schema.clone()),
+                    WriteConsistency::WaitForVisible,
+                )
+                .is_ok(),
             ),
             (
                 "delete_index",
-                core::delete_index(&IndexName::from("non-existent")).is_ok(),
+                core::delete_index(
+                    &IndexName::from("non-existent"),
+                    WriteConsistency::WaitForVisible,
+                )
+                .is_ok(),
             ),
         ];
 
@@ -1008,8 +1297,8 @@ impl Guest for Component {
                 content: format!(r#"{{"value": {}, "test": "stress"}}"#, i),
             };
 
-            match core::upsert(&stress_index, &doc) {
-                Ok(()) => stress_results.push(true),
+            match core::upsert(&stress_index, &doc, WriteConsistency::WaitForVisible) {
+                Ok(_) => stress_results.push(true),
                 Err(_) => stress_results.push(false),
             }
         }
@@ -1022,9 +1311,12 @@ impl Guest for Component {
         ));
 
         // Final cleanup attempt
-        let _ = core::delete_index(&test_index);
-        let _ = core::delete_index(stress_index);
-        let _ = core::delete_index(&IndexName::from("test-create"));
+        let _ = core::delete_index(&test_index, WriteConsistency::WaitForVisible);
+        let _ = core::delete_index(stress_index, WriteConsistency::WaitForVisible);
+        let _ = core::delete_index(
+            &IndexName::from("test-create"),
+            WriteConsistency::WaitForVisible,
+        );
 
         results.push("\n=== Error Handling Test Complete ===".to_string());
         results.join("\n")
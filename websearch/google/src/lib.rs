@@ -3,6 +3,7 @@ mod conversions;
 
 use crate::client::GoogleSearchApi;
 use crate::conversions::{params_to_request, response_to_results, validate_search_params};
+use golem_web_search::config::get_config_key;
 use golem_web_search::durability::Durablewebsearch;
 use golem_web_search::durability::ExtendedwebsearchGuest;
 use golem_web_search::golem::web_search::web_search::{
@@ -96,15 +97,8 @@ impl GoogleCustomSearchComponent {
     const SEARCH_ENGINE_ID_VAR: &'static str = "GOOGLE_SEARCH_ENGINE_ID";
 
     fn create_client() -> Result<GoogleSearchApi, SearchError> {
-        let api_key = std::env::var(Self::API_KEY_VAR).map_err(|_| {
-            SearchError::BackendError("GOOGLE_API_KEY environment variable not set".to_string())
-        })?;
-
-        let search_engine_id = std::env::var(Self::SEARCH_ENGINE_ID_VAR).map_err(|_| {
-            SearchError::BackendError(
-                "GOOGLE_SEARCH_ENGINE_ID environment variable not set".to_string(),
-            )
-        })?;
+        let api_key = get_config_key(Self::API_KEY_VAR)?;
+        let search_engine_id = get_config_key(Self::SEARCH_ENGINE_ID_VAR)?;
 
         Ok(GoogleSearchApi::new(api_key, search_engine_id))
     }
@@ -5,6 +5,7 @@ use std::cell::RefCell;
 
 use crate::client::SerperSearchApi;
 use crate::conversions::{params_to_request, response_to_results, validate_search_params};
+use golem_web_search::config::get_config_key;
 use golem_web_search::durability::Durablewebsearch;
 use golem_web_search::durability::ExtendedwebsearchGuest;
 use golem_web_search::golem::web_search::web_search::{
@@ -89,9 +90,7 @@ impl SerperSearchComponent {
     const API_KEY_VAR: &'static str = "SERPER_API_KEY";
 
     fn get_api_key() -> Result<String, SearchError> {
-        std::env::var(Self::API_KEY_VAR).map_err(|_| {
-            SearchError::BackendError("SERPER_API_KEY environment variable not set".to_string())
-        })
+        get_config_key(Self::API_KEY_VAR)
     }
 
     fn create_client() -> Result<SerperSearchApi, SearchError> {
@@ -1,3 +1,4 @@
+use crate::golem::web_search::web_search::SearchError as WebSearchError;
 use std::ffi::OsStr;
 
 #[derive(Debug, Clone)]
@@ -9,19 +10,59 @@ pub enum SearchError {
     UnsupportedFeature(String),
 }
 
-/// Gets an expected configuration value from the environment, and fails if its is not found
-/// using the `fail` function. Otherwise, it runs `succeed` with the configuration value.
+/// Gets an expected configuration value from the secret store or the environment, and fails if
+/// its is not found using the `fail` function. Otherwise, it runs `succeed` with the configuration
+/// value.
 pub fn with_search_config<R>(
     key: impl AsRef<OsStr>,
     fail: impl FnOnce(SearchError) -> R,
     succeed: impl FnOnce(String) -> R,
 ) -> R {
     let key_str = key.as_ref().to_string_lossy().to_string();
-    match std::env::var(&key) {
-        Ok(value) => succeed(value),
-        Err(_) => {
+    match resolve_config_key(&key_str) {
+        Ok(Some(value)) => succeed(value),
+        Ok(None) => {
             let error = SearchError::ConfigurationError(format!("Missing config key: {key_str}"));
             fail(error)
         }
+        Err(WebSearchError::BackendError(message)) => {
+            fail(SearchError::ConfigurationError(message))
+        }
+        Err(error) => fail(SearchError::ConfigurationError(format!("{error:?}"))),
+    }
+}
+
+/// Gets an expected configuration value, and fails with a backend error if it is not found
+pub fn get_config_key(key: &str) -> Result<String, WebSearchError> {
+    resolve_config_key(key)?
+        .ok_or_else(|| WebSearchError::BackendError(format!("Missing config key: {key}")))
+}
+
+/// Resolves a configuration key from the secret store when the `secret-store` feature is
+/// enabled, and otherwise from the environment variables.
+pub fn resolve_config_key(key: &str) -> Result<Option<String>, WebSearchError> {
+    #[cfg(feature = "secret-store")]
+    if let Some(value) = secret_store::get_secret(key)? {
+        return Ok(Some(value));
+    }
+
+    Ok(std::env::var(key).ok())
+}
+
+#[cfg(feature = "secret-store")]
+mod secret_store {
+    use crate::golem::web_search::web_search::SearchError;
+
+    wit_bindgen::generate!({
+        path: "../wit",
+        world: "secret-store",
+    });
+
+    pub fn get_secret(name: &str) -> Result<Option<String>, SearchError> {
+        golem::web_search::secrets::get_secret(name).map_err(|error| {
+            SearchError::BackendError(format!(
+                "Failed to get {name} from the secret store: {error}"
+            ))
+        })
     }
 }